@@ -0,0 +1,215 @@
+//! # Vessel Heap - Memory for Mortal Programs
+//!
+//! A `GlobalAlloc` that lets AethelOS programs use `Vec`, `String` and the
+//! rest of `alloc`. All memory comes from the Heartwood through `sys_mmap`.
+//!
+//! ## Strategy
+//! - Small allocations (up to 2 KB) come from power-of-two size classes.
+//!   Each class carves blocks out of a 64 KB mapping and keeps freed blocks
+//!   on its own free list for reuse.
+//! - Larger allocations get a mapping of their own, which is handed straight
+//!   back with `sys_munmap` when freed.
+//!
+//! Mappings are demand-paged by the kernel, so an untouched part of a chunk
+//! costs no physical memory.
+//!
+//! ## Usage
+//! ```ignore
+//! use corelib::allocator::VesselHeap;
+//!
+//! #[global_allocator]
+//! static ALLOCATOR: VesselHeap = VesselHeap::new();
+//! ```
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::cell::UnsafeCell;
+use core::ptr;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::syscalls::{sys_mmap, sys_munmap, PROT_READ, PROT_WRITE};
+
+/// Page size used by the kernel for mappings
+const PAGE_SIZE: usize = 4096;
+
+/// Smallest block handed out (must hold a free-list link)
+const MIN_BLOCK: usize = 16;
+
+/// Largest block served from a size class
+const MAX_SMALL_BLOCK: usize = 2048;
+
+/// Number of size classes: 16, 32, 64, ..., 2048
+const CLASS_COUNT: usize = 8;
+
+/// Size of each mapping a size class carves blocks from
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// A freed block, linked into its size class's free list
+struct FreeBlock {
+    next: *mut FreeBlock,
+}
+
+/// Per-size-class bookkeeping
+#[derive(Clone, Copy)]
+struct SizeClass {
+    /// Blocks that were freed and can be reused
+    free_list: *mut FreeBlock,
+    /// Next never-used byte in the current chunk
+    cursor: usize,
+    /// End of the current chunk
+    end: usize,
+}
+
+impl SizeClass {
+    const EMPTY: Self = Self {
+        free_list: ptr::null_mut(),
+        cursor: 0,
+        end: 0,
+    };
+}
+
+/// The user-space heap allocator
+pub struct VesselHeap {
+    locked: AtomicBool,
+    classes: UnsafeCell<[SizeClass; CLASS_COUNT]>,
+}
+
+// SAFETY: All access to `classes` happens while `locked` is held.
+unsafe impl Sync for VesselHeap {}
+
+impl VesselHeap {
+    /// Create an empty heap (no memory is mapped until the first allocation)
+    pub const fn new() -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            classes: UnsafeCell::new([SizeClass::EMPTY; CLASS_COUNT]),
+        }
+    }
+
+    /// Run `f` with exclusive access to the size classes
+    fn with_classes<R>(&self, f: impl FnOnce(&mut [SizeClass; CLASS_COUNT]) -> R) -> R {
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            core::hint::spin_loop();
+        }
+
+        // SAFETY: the spin lock above gives us exclusive access
+        let result = f(unsafe { &mut *self.classes.get() });
+
+        self.locked.store(false, Ordering::Release);
+        result
+    }
+}
+
+impl Default for VesselHeap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Size class for a layout, or `None` if it needs a mapping of its own
+///
+/// Blocks in class `n` are `MIN_BLOCK << n` bytes and, because chunks are
+/// page-aligned, are naturally aligned to their own size.
+fn size_class(layout: &Layout) -> Option<usize> {
+    let size = layout.size().max(layout.align()).max(MIN_BLOCK);
+    if size > MAX_SMALL_BLOCK {
+        return None;
+    }
+
+    let block = size.next_power_of_two();
+    Some((block.trailing_zeros() - MIN_BLOCK.trailing_zeros()) as usize)
+}
+
+/// Block size of a size class
+const fn class_block_size(class: usize) -> usize {
+    MIN_BLOCK << class
+}
+
+unsafe impl GlobalAlloc for VesselHeap {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let class = match size_class(&layout) {
+            Some(class) => class,
+            None => {
+                if layout.align() > PAGE_SIZE {
+                    return ptr::null_mut();
+                }
+                return sys_mmap(layout.size(), PROT_READ | PROT_WRITE).unwrap_or(ptr::null_mut());
+            }
+        };
+
+        self.with_classes(|classes| {
+            let state = &mut classes[class];
+            let block_size = class_block_size(class);
+
+            // Reuse a freed block first
+            if !state.free_list.is_null() {
+                let block = state.free_list;
+                state.free_list = (*block).next;
+                return block as *mut u8;
+            }
+
+            // Otherwise carve a fresh block, mapping a new chunk if needed
+            if state.cursor + block_size > state.end {
+                match sys_mmap(CHUNK_SIZE, PROT_READ | PROT_WRITE) {
+                    Ok(chunk) => {
+                        state.cursor = chunk as usize;
+                        state.end = chunk as usize + CHUNK_SIZE;
+                    }
+                    Err(_) => return ptr::null_mut(),
+                }
+            }
+
+            let block = state.cursor as *mut u8;
+            state.cursor += block_size;
+            block
+        })
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        match size_class(&layout) {
+            Some(class) => self.with_classes(|classes| {
+                let block = ptr as *mut FreeBlock;
+                (*block).next = classes[class].free_list;
+                classes[class].free_list = block;
+            }),
+            None => {
+                let _ = sys_munmap(ptr, layout.size());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(size: usize, align: usize) -> Layout {
+        Layout::from_size_align(size, align).unwrap()
+    }
+
+    #[test]
+    fn test_small_sizes_round_up_to_a_class() {
+        assert_eq!(size_class(&layout(0, 1)), Some(0));
+        assert_eq!(size_class(&layout(1, 1)), Some(0));
+        assert_eq!(size_class(&layout(16, 8)), Some(0));
+        assert_eq!(size_class(&layout(17, 8)), Some(1));
+        assert_eq!(size_class(&layout(2048, 8)), Some(CLASS_COUNT - 1));
+        assert_eq!(class_block_size(CLASS_COUNT - 1), MAX_SMALL_BLOCK);
+    }
+
+    #[test]
+    fn test_alignment_selects_class() {
+        // A 4-byte value that must be 256-aligned lives in the 256-byte class
+        assert_eq!(size_class(&layout(4, 256)), Some(4));
+        assert_eq!(class_block_size(4), 256);
+    }
+
+    #[test]
+    fn test_large_allocations_get_their_own_mapping() {
+        assert_eq!(size_class(&layout(2049, 8)), None);
+        assert_eq!(size_class(&layout(8, 4096)), None);
+    }
+}
@@ -79,6 +79,9 @@ pub mod result {
 /// Syscall interface for communicating with the kernel
 pub mod syscalls;
 
+/// Heap allocator built on sys_mmap/sys_munmap
+pub mod allocator;
+
 /// Re-exports for convenience
 pub use collections::*;
 pub use strings::*;
@@ -38,117 +38,19 @@
 #![allow(dead_code)]
 
 // ============================================================================
-// Syscall Numbers
+// Syscall Numbers and Error Codes
 // ============================================================================
-
-/// Exit the current thread/process
-pub const SYS_EXIT: u64 = 0;
-
-/// Write bytes to a file descriptor
-pub const SYS_WRITE: u64 = 1;
-
-/// Read bytes from a file descriptor
-pub const SYS_READ: u64 = 2;
-
-/// Open a file/scroll
-pub const SYS_OPEN: u64 = 3;
-
-/// Close a file descriptor
-pub const SYS_CLOSE: u64 = 4;
-
-/// Get current thread ID
-pub const SYS_GETPID: u64 = 5;
-
-/// Query a scroll in the World-Tree
-pub const SYS_QUERY: u64 = 6;
-
-/// Commit changes to the World-Tree
-pub const SYS_COMMIT: u64 = 7;
-
-/// Allocate memory pages
-pub const SYS_MMAP: u64 = 8;
-
-/// Free memory pages
-pub const SYS_MUNMAP: u64 = 9;
-
-/// Sleep for a duration (in heartbeats/ticks)
-pub const SYS_SLEEP: u64 = 10;
-
-/// Yield CPU to another thread
-pub const SYS_YIELD: u64 = 11;
-
-/// Create a new thread
-pub const SYS_THREAD_CREATE: u64 = 12;
-
-/// Wait for a thread to terminate
-pub const SYS_THREAD_JOIN: u64 = 13;
-
-/// Send an IPC message
-pub const SYS_IPC_SEND: u64 = 14;
-
-/// Receive an IPC message
-pub const SYS_IPC_RECV: u64 = 15;
-
-/// Get current time (in heartbeats since boot)
-pub const SYS_TIME: u64 = 16;
-
-/// Execute a Glimmer-Weave script (Ring 1 only)
-pub const SYS_EXEC_SCRIPT: u64 = 17;
-
-// ============================================================================
-// Error Codes (POSIX-like for compatibility)
-// ============================================================================
-
-/// Operation not permitted
-pub const EPERM: i32 = -1;
-
-/// No such file or directory
-pub const ENOENT: i32 = -2;
-
-/// No such process
-pub const ESRCH: i32 = -3;
-
-/// Interrupted system call
-pub const EINTR: i32 = -4;
-
-/// I/O error
-pub const EIO: i32 = -5;
-
-/// Bad file descriptor
-pub const EBADF: i32 = -9;
-
-/// Try again
-pub const EAGAIN: i32 = -11;
-
-/// Out of memory
-pub const ENOMEM: i32 = -12;
-
-/// Permission denied
-pub const EACCES: i32 = -13;
-
-/// Bad address
-pub const EFAULT: i32 = -14;
-
-/// Device or resource busy
-pub const EBUSY: i32 = -16;
-
-/// File exists
-pub const EEXIST: i32 = -17;
-
-/// Invalid argument
-pub const EINVAL: i32 = -22;
-
-/// Too many open files
-pub const EMFILE: i32 = -24;
-
-/// Function not implemented
-pub const ENOSYS: i32 = -38;
-
-/// Directory not empty
-pub const ENOTEMPTY: i32 = -39;
-
-/// Operation would block
-pub const EWOULDBLOCK: i32 = EAGAIN;
+//
+// Both are defined once in the `covenant` crate, which the kernel's
+// dispatcher also depends on. Never redefine them here.
+
+pub use covenant::numbers::*;
+pub use covenant::errno::*;
+pub use covenant::open_flags::*;
+pub use covenant::prot::*;
+pub use covenant::ipc::*;
+pub use covenant::spawn::*;
+pub use covenant::wait::{ExitStatus, ANY_CHILD, EXITED, FAULTED};
 
 // ============================================================================
 // Low-Level Syscall Wrappers
@@ -417,17 +319,64 @@ pub fn sys_sleep(ticks: u64) -> Result<(), i32> {
     }
 }
 
-/// Get the current thread ID
+/// Start another thread in this Vessel
+///
+/// The thread shares the Vessel's memory and runs `entry(argument)` on a
+/// stack of its own. It must end with `sys_exit`, whose code becomes the
+/// value `sys_thread_join` returns.
 ///
 /// # Returns
 ///
-/// The thread ID of the calling thread
+/// * `Ok(thread_id)` - The new thread's ID
+/// * `Err(errno)` - Error code (`EAGAIN` if the kernel has no room for
+///   another thread, `ENOMEM` if its stacks cannot be allocated)
+pub fn sys_thread_create(entry: extern "C" fn(u64) -> !, argument: u64) -> Result<u64, i32> {
+    let ret = unsafe { syscall2(SYS_THREAD_CREATE, entry as usize as u64, argument) };
+    if ret < 0 {
+        Err(ret as i32)
+    } else {
+        Ok(ret as u64)
+    }
+}
+
+/// Wait for another thread of this Vessel to exit
+///
+/// # Returns
+///
+/// * `Ok(exit_value)` - The value the thread passed to `sys_exit`
+/// * `Err(errno)` - Error code (`ESRCH` if there is no such thread in this
+///   Vessel, `EINVAL` for the calling thread itself)
+pub fn sys_thread_join(thread_id: u64) -> Result<u64, i32> {
+    let ret = unsafe { syscall1(SYS_THREAD_JOIN, thread_id) };
+    if ret < 0 {
+        Err(ret as i32)
+    } else {
+        Ok(ret as u64)
+    }
+}
+
+/// Get the current Vessel (process) ID
+///
+/// # Returns
+///
+/// The Vessel ID of the calling thread (0 for kernel threads)
 pub fn sys_getpid() -> u64 {
     unsafe {
         syscall0(SYS_GETPID) as u64
     }
 }
 
+/// Get the current thread ID
+///
+/// # Returns
+///
+/// The thread ID of the calling thread
+pub fn sys_gettid() -> u64 {
+    unsafe {
+        syscall0(SYS_GETTID) as u64
+    }
+}
+
 /// Get the current time in heartbeats (timer ticks since boot)
 ///
 /// # Returns
@@ -444,7 +393,7 @@ pub fn sys_time() -> u64 {
 /// # Arguments
 ///
 /// * `path` - Path to the file
-/// * `flags` - Open flags (e.g., read, write, create)
+/// * `flags` - Open flags (`O_READ`, `O_WRITE`, `O_CREATE`, ...)
 ///
 /// # Returns
 ///
@@ -452,7 +401,7 @@ pub fn sys_time() -> u64 {
 /// * `Err(errno)` - Error code
 pub fn sys_open(path: &str, flags: i32) -> Result<i32, i32> {
     let ret = unsafe {
-        syscall2(SYS_OPEN, path.as_ptr() as u64, flags as u64)
+        syscall3(SYS_OPEN, path.as_ptr() as u64, path.len() as u64, flags as u64)
     };
     if ret < 0 {
         Err(ret as i32)
@@ -482,6 +431,356 @@ pub fn sys_close(fd: i32) -> Result<(), i32> {
     }
 }
 
+/// Map anonymous, zero-filled memory
+///
+/// # Arguments
+///
+/// * `len` - Size in bytes (rounded up to a whole page by the kernel)
+/// * `prot` - Protection flags (`PROT_READ`, `PROT_WRITE`, `PROT_EXEC`)
+///
+/// # Returns
+///
+/// * `Ok(ptr)` - Page-aligned base of the mapping
+/// * `Err(errno)` - Error code
+pub fn sys_mmap(len: usize, prot: u64) -> Result<*mut u8, i32> {
+    let ret = unsafe {
+        syscall2(SYS_MMAP, len as u64, prot)
+    };
+    if ret < 0 {
+        Err(ret as i32)
+    } else {
+        Ok(ret as *mut u8)
+    }
+}
+
+/// Remove a mapping created by `sys_mmap`
+///
+/// # Arguments
+///
+/// * `addr` - Base address returned by `sys_mmap`
+/// * `len` - The same length passed to `sys_mmap`
+///
+/// # Returns
+///
+/// * `Ok(())` - Mapping removed
+/// * `Err(errno)` - Error code
+///
+/// # Safety
+///
+/// No references into the mapping may be used after this call.
+pub unsafe fn sys_munmap(addr: *mut u8, len: usize) -> Result<(), i32> {
+    let ret = syscall2(SYS_MUNMAP, addr as u64, len as u64);
+    if ret < 0 {
+        Err(ret as i32)
+    } else {
+        Ok(())
+    }
+}
+
+/// Send a message through a Nexus channel
+///
+/// # Arguments
+///
+/// * `channel` - Capability ID of the channel (needs the send right)
+/// * `payload` - Bytes to send
+/// * `capabilities` - Capability IDs to pass on (each needs the transfer
+///   right, at most `MAX_MESSAGE_CAPABILITIES`); the receiver gets its own
+///   copies and the caller keeps these
+///
+/// # Returns
+///
+/// * `Ok(())` - Message queued
+/// * `Err(errno)` - Error code (`EAGAIN` if the channel is full, `EACCES` if
+///   a capability lacks the right it needs)
+pub fn sys_ipc_send(channel: u64, payload: &[u8], capabilities: &[u64]) -> Result<(), i32> {
+    let ret = unsafe {
+        syscall5(
+            SYS_IPC_SEND,
+            channel,
+            payload.as_ptr() as u64,
+            payload.len() as u64,
+            capabilities.as_ptr() as u64,
+            capabilities.len() as u64,
+        )
+    };
+    if ret < 0 {
+        Err(ret as i32)
+    } else {
+        Ok(())
+    }
+}
+
+/// Receive a message from a Nexus channel
+///
+/// Blocks (using no CPU) until a message arrives or the timeout passes.
+///
+/// # Arguments
+///
+/// * `channel` - Capability ID of the channel (needs the receive right)
+/// * `buf` - Buffer for the payload (longer payloads are truncated)
+/// * `timeout` - Heartbeats to wait (0 polls, `WAIT_FOREVER` never gives up)
+///
+/// # Returns
+///
+/// * `Ok((n, info))` - Number of payload bytes received, and what was
+///   delivered (`info.kind == MSG_SHARED_MEMORY` when a shared memory object
+///   was mapped at `info.address`; remove it with `sys_munmap`), including
+///   the IDs of any capabilities passed with it
+/// * `Err(errno)` - Error code (`ETIMEDOUT`, or `EPIPE` if the channel closed)
+pub fn sys_ipc_recv(channel: u64, buf: &mut [u8], timeout: u64) -> Result<(usize, RecvInfo), i32> {
+    let mut info = RecvInfo::default();
+    let ret = unsafe {
+        syscall5(
+            SYS_IPC_RECV,
+            channel,
+            buf.as_mut_ptr() as u64,
+            buf.len() as u64,
+            timeout,
+            &mut info as *mut RecvInfo as u64,
+        )
+    };
+    if ret < 0 {
+        Err(ret as i32)
+    } else {
+        Ok((ret as usize, info))
+    }
+}
+
+/// Create a shared memory object and map it into this Vessel
+///
+/// # Arguments
+///
+/// * `len` - Size in bytes (rounded up to whole pages)
+///
+/// # Returns
+///
+/// * `Ok(ptr)` - Base of the zeroed, read-write mapping; pass it on with
+///   `sys_ipc_send_shared` and remove it with `sys_munmap`
+/// * `Err(errno)` - Error code (`ENOMEM` if it could not be created or mapped)
+pub fn sys_shm_create(len: usize) -> Result<*mut u8, i32> {
+    let ret = unsafe { syscall1(SYS_SHM_CREATE, len as u64) };
+    if ret < 0 {
+        Err(ret as i32)
+    } else {
+        Ok(ret as *mut u8)
+    }
+}
+
+/// Send a shared memory mapping through a Nexus channel without copying it
+///
+/// # Arguments
+///
+/// * `channel` - Capability ID of the channel (needs the send right)
+/// * `addr` - Base of a mapping from `sys_shm_create` or `sys_ipc_recv`
+/// * `len` - The mapping's length
+/// * `mode` - `TRANSFER_GRANT`, `TRANSFER_MOVE` or `TRANSFER_SHARE`
+///
+/// # Returns
+///
+/// * `Ok(())` - Message queued
+/// * `Err(errno)` - Error code (`EINVAL` if the range is not exactly one
+///   shared mapping, `EACCES` if the mapping may not be passed on,
+///   `EAGAIN` if the channel is full)
+///
+/// # Safety
+///
+/// With `TRANSFER_MOVE` the mapping is removed, even if the send fails, so
+/// no references into it may be used after this call.
+pub unsafe fn sys_ipc_send_shared(channel: u64, addr: *mut u8, len: usize, mode: u64) -> Result<(), i32> {
+    let ret = syscall4(SYS_IPC_SEND_SHARED, channel, addr as u64, len as u64, mode);
+    if ret < 0 {
+        Err(ret as i32)
+    } else {
+        Ok(())
+    }
+}
+
+/// Register a channel under a service name
+///
+/// # Arguments
+///
+/// * `name` - Hierarchical service name, e.g. `"world-tree/query"`
+/// * `channel` - Capability ID of the channel (needs send and transfer)
+///
+/// # Returns
+///
+/// * `Ok(())` - Name registered
+/// * `Err(errno)` - Error code (`EEXIST` if taken, `EACCES` if the caller's
+///   Fate forbids it)
+pub fn sys_name_register(name: &str, channel: u64) -> Result<(), i32> {
+    let ret = unsafe { syscall3(SYS_NAME_REGISTER, name.as_ptr() as u64, name.len() as u64, channel) };
+    if ret < 0 {
+        Err(ret as i32)
+    } else {
+        Ok(())
+    }
+}
+
+/// Resolve a service name to a channel capability
+///
+/// # Returns
+///
+/// * `Ok(capability)` - A new capability ID with the send right
+/// * `Err(errno)` - Error code (`ENOENT` if nothing is registered under the
+///   name, `EACCES` if the caller's Fate forbids it)
+pub fn sys_name_resolve(name: &str) -> Result<u64, i32> {
+    let ret = unsafe { syscall2(SYS_NAME_RESOLVE, name.as_ptr() as u64, name.len() as u64) };
+    if ret < 0 {
+        Err(ret as i32)
+    } else {
+        Ok(ret as u64)
+    }
+}
+
+/// Create a notification
+///
+/// # Returns
+///
+/// * `Ok(capability)` - Capability ID with the send (raise), receive (wait)
+///   and transfer rights
+/// * `Err(errno)` - Error code (`ENOMEM` if the Nexus is out of notifications)
+pub fn sys_notify_create() -> Result<u64, i32> {
+    let ret = unsafe { syscall0(SYS_NOTIFY_CREATE) };
+    if ret < 0 {
+        Err(ret as i32)
+    } else {
+        Ok(ret as u64)
+    }
+}
+
+/// Raise signal bits on a notification, waking anyone waiting on it
+pub fn sys_notify_raise(notification: u64, signals: u64) -> Result<(), i32> {
+    let ret = unsafe { syscall2(SYS_NOTIFY_RAISE, notification, signals) };
+    if ret < 0 {
+        Err(ret as i32)
+    } else {
+        Ok(())
+    }
+}
+
+/// Wait until any of several channels or notifications is ready
+///
+/// # Arguments
+///
+/// * `sources` - Capability IDs (each needs the receive right, at most
+///   `MAX_WAIT_SOURCES`)
+/// * `timeout` - Heartbeats to wait (0 polls, `WAIT_FOREVER` never gives up)
+///
+/// # Returns
+///
+/// * `Ok((index, signals))` - The first ready entry of `sources`, and the
+///   signals taken if it is a notification (a ready channel's message is
+///   still waiting for `sys_ipc_recv`)
+/// * `Err(errno)` - Error code (`ETIMEDOUT` if nothing became ready)
+pub fn sys_wait_any(sources: &[u64], timeout: u64) -> Result<(usize, u64), i32> {
+    let mut signals = 0u64;
+    let ret = unsafe {
+        syscall4(
+            SYS_WAIT_ANY,
+            sources.as_ptr() as u64,
+            sources.len() as u64,
+            timeout,
+            &mut signals as *mut u64 as u64,
+        )
+    };
+    if ret < 0 {
+        Err(ret as i32)
+    } else {
+        Ok((ret as usize, signals))
+    }
+}
+
+/// Route a hardware IRQ line to a notification (Ring 1 Groves only)
+///
+/// # Returns
+///
+/// * `Ok(())` - `signals` are raised on the notification each time the line fires
+/// * `Err(errno)` - Error code (`EBUSY` if the line is reserved or bound,
+///   `EPERM` from Ring 3)
+pub fn sys_irq_bind(irq: u8, notification: u64, signals: u64) -> Result<(), i32> {
+    let ret = unsafe { syscall3(SYS_IRQ_BIND, irq as u64, notification, signals) };
+    if ret < 0 {
+        Err(ret as i32)
+    } else {
+        Ok(())
+    }
+}
+
+/// Start a new Vessel from an ELF file
+///
+/// # Arguments
+///
+/// * `path` - Absolute path of the ELF file in the VFS
+/// * `args` - Arguments, `args[0]` being the program name by convention
+/// * `env` - Environment strings, e.g. `"HOME=/home"`
+///
+/// # Returns
+///
+/// * `Ok(vessel_id)` - The new Vessel's ID
+/// * `Err(errno)` - Error code (`ENOENT` if there is no such file,
+///   `ENOEXEC` if it is not a valid ELF, `E2BIG` if `args` and `env`
+///   exceed `MAX_ARGS_LEN`, `EINVAL` if a string contains a NUL)
+pub fn sys_spawn(path: &str, args: &[&str], env: &[&str]) -> Result<u64, i32> {
+    let args = string_block(args)?;
+    let env = string_block(env)?;
+    let ret = unsafe {
+        syscall6(
+            SYS_SPAWN,
+            path.as_ptr() as u64,
+            path.len() as u64,
+            args.as_ptr() as u64,
+            args.len() as u64,
+            env.as_ptr() as u64,
+            env.len() as u64,
+        )
+    };
+    if ret < 0 {
+        Err(ret as i32)
+    } else {
+        Ok(ret as u64)
+    }
+}
+
+/// Wait for a child Vessel to vanish, and reap it
+///
+/// # Returns
+///
+/// * `Ok((vessel_id, status))` - The child's ID and how it ended
+/// * `Err(errno)` - Error code (`ECHILD` if `vessel` is not a child of the
+///   caller)
+pub fn sys_wait(vessel: u64) -> Result<(u64, ExitStatus), i32> {
+    let mut status = ExitStatus::default();
+    let ret = unsafe { syscall2(SYS_WAIT, vessel, &mut status as *mut ExitStatus as u64) };
+    if ret < 0 {
+        Err(ret as i32)
+    } else {
+        Ok((ret as u64, status))
+    }
+}
+
+/// Wait for whichever child Vessel vanishes first, and reap it
+///
+/// # Returns
+///
+/// * `Ok((vessel_id, status))` - The child's ID and how it ended
+/// * `Err(errno)` - Error code (`ECHILD` if the caller has no children)
+pub fn sys_wait_any_child() -> Result<(u64, ExitStatus), i32> {
+    sys_wait(ANY_CHILD)
+}
+
+/// Pack strings back to back, each followed by a NUL
+fn string_block(strings: &[&str]) -> Result<alloc::vec::Vec<u8>, i32> {
+    let mut block = alloc::vec::Vec::new();
+    for s in strings {
+        if s.as_bytes().contains(&0) {
+            return Err(EINVAL);
+        }
+        block.extend_from_slice(s.as_bytes());
+        block.push(0);
+    }
+    Ok(block)
+}
+
 // ============================================================================
 // Error Code Utilities
 // ============================================================================
@@ -494,17 +793,28 @@ pub fn errno_str(errno: i32) -> &'static str {
         ESRCH => "No such process",
         EINTR => "Interrupted system call",
         EIO => "I/O error",
+        E2BIG => "Argument list too long",
+        ENOEXEC => "Exec format error",
         EBADF => "Bad file descriptor",
+        ECHILD => "No child processes",
         EAGAIN => "Resource temporarily unavailable",
         ENOMEM => "Out of memory",
         EACCES => "Permission denied",
         EFAULT => "Bad address",
         EBUSY => "Device or resource busy",
         EEXIST => "File exists",
+        ENOTDIR => "Not a directory",
+        EISDIR => "Is a directory",
         EINVAL => "Invalid argument",
         EMFILE => "Too many open files",
+        ENOSPC => "No space left on device",
+        EROFS => "Read-only file system",
+        EPIPE => "Broken pipe",
+        ENAMETOOLONG => "File name too long",
         ENOSYS => "Function not implemented",
         ENOTEMPTY => "Directory not empty",
+        ELOOP => "Too many levels of symbolic links",
+        ETIMEDOUT => "Timed out",
         _ => "Unknown error",
     }
 }
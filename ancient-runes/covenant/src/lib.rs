@@ -0,0 +1,593 @@
+//! # Covenant - The Shared Syscall ABI
+//!
+//! The binding agreement between the Heartwood and everything that petitions it.
+//! Both the kernel's syscall dispatcher and `corelib` depend on this crate, so
+//! a syscall number, an errno value, or an argument layout can only ever be
+//! defined in one place.
+//!
+//! ## Philosophy
+//! A covenant is only worth something if both parties read the same words.
+//! When the kernel and user space disagreed about numbering, `sys_exit(42)`
+//! quietly became a yield. Here there is one scroll, and both sides sign it.
+//!
+//! ## Register Layout
+//!
+//! | Register | Purpose                                  |
+//! |----------|------------------------------------------|
+//! | RAX      | Syscall number (in) / return value (out) |
+//! | RDI      | Argument 1                               |
+//! | RSI      | Argument 2                               |
+//! | RDX      | Argument 3                               |
+//! | R10      | Argument 4 (NOT RCX - clobbered)         |
+//! | R8       | Argument 5                               |
+//! | R9       | Argument 6                               |
+//!
+//! Ring 3 enters through the `syscall` instruction; Ring 1 Groves enter
+//! through `int 0x81`. Both use the same numbers and the same registers.
+//!
+//! ## Return Values
+//!
+//! - **>= 0**: Success (call-specific value)
+//! - **< 0**:  Negated errno from [`errno`]
+
+#![no_std]
+
+/// Syscall numbers
+///
+/// Each constant documents its argument layout. Pointer arguments are always
+/// paired with an explicit length; the kernel never scans user memory for a
+/// terminator.
+pub mod numbers {
+    /// Exit the current thread/process
+    ///
+    /// `rdi` = exit code. Never returns.
+    pub const SYS_EXIT: u64 = 0;
+
+    /// Write bytes to a file descriptor
+    ///
+    /// `rdi` = fd, `rsi` = buffer pointer, `rdx` = length.
+    /// Returns the number of bytes written.
+    pub const SYS_WRITE: u64 = 1;
+
+    /// Read bytes from a file descriptor
+    ///
+    /// `rdi` = fd, `rsi` = buffer pointer, `rdx` = length.
+    /// Returns the number of bytes read (0 = end of file).
+    pub const SYS_READ: u64 = 2;
+
+    /// Open a file/scroll
+    ///
+    /// `rdi` = path pointer, `rsi` = path length, `rdx` = [`open_flags`](crate::open_flags).
+    /// Returns a new file descriptor.
+    pub const SYS_OPEN: u64 = 3;
+
+    /// Close a file descriptor
+    ///
+    /// `rdi` = fd.
+    pub const SYS_CLOSE: u64 = 4;
+
+    /// Get the current Vessel (process) ID
+    ///
+    /// No arguments.
+    pub const SYS_GETPID: u64 = 5;
+
+    /// Query a scroll in the World-Tree
+    ///
+    /// Reserved; the argument layout is defined when the World-Tree Grove lands.
+    pub const SYS_QUERY: u64 = 6;
+
+    /// Commit changes to the World-Tree
+    ///
+    /// Reserved; the argument layout is defined when the World-Tree Grove lands.
+    pub const SYS_COMMIT: u64 = 7;
+
+    /// Allocate memory pages
+    ///
+    /// `rdi` = length in bytes, `rsi` = [`prot`](crate::prot) flags.
+    /// Returns the page-aligned base address of the mapping, which is zeroed
+    /// and placed at a randomized address.
+    pub const SYS_MMAP: u64 = 8;
+
+    /// Free memory pages
+    ///
+    /// `rdi` = base address, `rsi` = length in bytes. Also removes shared
+    /// memory mapped by `SYS_SHM_CREATE` or `SYS_IPC_RECV`.
+    pub const SYS_MUNMAP: u64 = 9;
+
+    /// Sleep for a duration
+    ///
+    /// `rdi` = number of heartbeats (timer ticks); 0 just yields.
+    pub const SYS_SLEEP: u64 = 10;
+
+    /// Yield CPU to another thread
+    ///
+    /// No arguments.
+    pub const SYS_YIELD: u64 = 11;
+
+    /// Create a new thread
+    ///
+    /// `rdi` = entry point, `rsi` = argument passed to the entry point.
+    /// The thread shares the caller's Vessel and gets stacks of its own;
+    /// it ends with `SYS_EXIT`. Returns the new thread's ID.
+    pub const SYS_THREAD_CREATE: u64 = 12;
+
+    /// Wait for a thread to terminate
+    ///
+    /// `rdi` = thread ID (of the caller's own Vessel). Blocks until the
+    /// thread exits and returns its exit value.
+    pub const SYS_THREAD_JOIN: u64 = 13;
+
+    /// Send an IPC message
+    ///
+    /// `rdi` = channel capability ID (needs the send right), `rsi` = payload
+    /// pointer, `rdx` = payload length, `r10` = pointer to an array of
+    /// capability IDs to pass on (each needs the transfer right), `r8` =
+    /// number of them (at most [`ipc::MAX_MESSAGE_CAPABILITIES`](crate::ipc::MAX_MESSAGE_CAPABILITIES)).
+    pub const SYS_IPC_SEND: u64 = 14;
+
+    /// Receive an IPC message, blocking until one arrives
+    ///
+    /// `rdi` = channel capability ID (needs the receive right), `rsi` = buffer
+    /// pointer, `rdx` = buffer length,
+    /// `r10` = timeout in heartbeats (0 polls, [`ipc::WAIT_FOREVER`](crate::ipc::WAIT_FOREVER)
+    /// never gives up), `r8` = pointer to an [`ipc::RecvInfo`](crate::ipc::RecvInfo)
+    /// to fill in (may be null, but then a message carrying shared memory or
+    /// capabilities is consumed and refused with `EINVAL`).
+    /// Returns the number of payload bytes received; a payload longer than
+    /// the buffer is truncated.
+    pub const SYS_IPC_RECV: u64 = 15;
+
+    /// Get current time (in heartbeats since boot)
+    ///
+    /// No arguments.
+    pub const SYS_TIME: u64 = 16;
+
+    /// Execute a Glimmer-Weave script (Ring 1 only)
+    ///
+    /// `rdi` = script pointer, `rsi` = script length.
+    pub const SYS_EXEC_SCRIPT: u64 = 17;
+
+    /// Get the current thread ID
+    ///
+    /// No arguments.
+    pub const SYS_GETTID: u64 = 18;
+
+    /// Register a channel under a service name (e.g. `world-tree/query`)
+    ///
+    /// `rdi` = name pointer, `rsi` = name length, `rdx` = channel capability
+    /// ID (needs the send and transfer rights). Allowed only if the caller's
+    /// Fate permits it.
+    pub const SYS_NAME_REGISTER: u64 = 19;
+
+    /// Resolve a service name to a channel capability
+    ///
+    /// `rdi` = name pointer, `rsi` = name length. Returns a new capability
+    /// ID with the send right, if the caller's Fate permits it.
+    pub const SYS_NAME_RESOLVE: u64 = 20;
+
+    /// Create a notification (a word of signal bits)
+    ///
+    /// No arguments. Returns a capability ID with the send (raise), receive
+    /// (wait) and transfer rights.
+    pub const SYS_NOTIFY_CREATE: u64 = 21;
+
+    /// Raise signals on a notification, waking anyone waiting on it
+    ///
+    /// `rdi` = notification capability ID (needs the send right), `rsi` =
+    /// signal bits to OR in.
+    pub const SYS_NOTIFY_RAISE: u64 = 22;
+
+    /// Wait until any of several channels or notifications is ready
+    ///
+    /// `rdi` = pointer to an array of capability IDs (each needs the receive
+    /// right), `rsi` = number of them (at most [`ipc::MAX_WAIT_SOURCES`](crate::ipc::MAX_WAIT_SOURCES)),
+    /// `rdx` = timeout in heartbeats (0 polls, [`ipc::WAIT_FOREVER`](crate::ipc::WAIT_FOREVER)
+    /// never gives up), `r10` = pointer to a `u64` that receives the signals
+    /// taken from a ready notification (may be null).
+    /// Returns the index of the first ready entry. A channel is ready when
+    /// it holds a message or was closed; the message is left for
+    /// `SYS_IPC_RECV`. A ready notification's signals are consumed.
+    pub const SYS_WAIT_ANY: u64 = 23;
+
+    /// Route a hardware IRQ line to a notification (Ring 1 only)
+    ///
+    /// `rdi` = IRQ line, `rsi` = notification capability ID (needs the send
+    /// right), `rdx` = signal bits raised each time the line fires.
+    pub const SYS_IRQ_BIND: u64 = 24;
+
+    /// Start a new Vessel from an ELF file in the VFS
+    ///
+    /// `rdi` = path pointer, `rsi` = path length, `rdx` = argument block
+    /// pointer, `r10` = argument block length, `r8` = environment block
+    /// pointer, `r9` = environment block length. Each block holds
+    /// NUL-terminated strings back to back (e.g. `b"ls\0-l\0"`); the two
+    /// together may be at most [`spawn::MAX_ARGS_LEN`](crate::spawn::MAX_ARGS_LEN)
+    /// bytes. The new Vessel inherits the caller's Fate and receives them
+    /// on its initial stack (see [`auxv`](crate::auxv)).
+    /// Returns the new Vessel's ID.
+    pub const SYS_SPAWN: u64 = 25;
+
+    /// Wait for a child Vessel to vanish, and reap it
+    ///
+    /// `rdi` = child Vessel ID, or [`wait::ANY_CHILD`](crate::wait::ANY_CHILD)
+    /// for whichever child vanishes first; `rsi` = pointer to a
+    /// [`wait::ExitStatus`](crate::wait::ExitStatus) (may be 0). Blocks until
+    /// the child has vanished, then frees it. Returns the child's ID, or
+    /// `ECHILD` if the caller has no such child.
+    pub const SYS_WAIT: u64 = 26;
+
+    /// Create a shared memory object and map it into the caller
+    ///
+    /// `rdi` = length in bytes. The object is zeroed, rounded up to whole
+    /// pages and mapped read-write at a randomized address, which is
+    /// returned. Pass it on with `SYS_IPC_SEND_SHARED`; remove it with
+    /// `SYS_MUNMAP`.
+    pub const SYS_SHM_CREATE: u64 = 27;
+
+    /// Send a shared memory mapping through a Nexus channel without copying it
+    ///
+    /// `rdi` = channel capability ID (needs the send right), `rsi` = base
+    /// address of a shared mapping (from `SYS_SHM_CREATE` or `SYS_IPC_RECV`),
+    /// `rdx` = its length, `r10` = transfer mode
+    /// ([`ipc::TRANSFER_GRANT`](crate::ipc::TRANSFER_GRANT),
+    /// [`ipc::TRANSFER_MOVE`](crate::ipc::TRANSFER_MOVE) or
+    /// [`ipc::TRANSFER_SHARE`](crate::ipc::TRANSFER_SHARE)). The receiver
+    /// maps the same pages through `SYS_IPC_RECV`. A moved mapping is
+    /// removed from the caller, even if the send then fails.
+    pub const SYS_IPC_SEND_SHARED: u64 = 28;
+
+    /// Number of syscall slots in the ABI
+    ///
+    /// Every number below this value has a kernel table entry, even if that
+    /// entry only answers `ENOSYS`.
+    pub const SYSCALL_COUNT: usize = 29;
+}
+
+/// Error codes (POSIX-like for compatibility)
+///
+/// Stored already negated, exactly as they appear in RAX.
+pub mod errno {
+    /// Operation not permitted
+    pub const EPERM: i32 = -1;
+
+    /// No such file or directory
+    pub const ENOENT: i32 = -2;
+
+    /// No such process
+    pub const ESRCH: i32 = -3;
+
+    /// Interrupted system call
+    pub const EINTR: i32 = -4;
+
+    /// I/O error
+    pub const EIO: i32 = -5;
+
+    /// Argument list too long
+    pub const E2BIG: i32 = -7;
+
+    /// Exec format error
+    pub const ENOEXEC: i32 = -8;
+
+    /// Bad file descriptor
+    pub const EBADF: i32 = -9;
+
+    /// No child Vessel to wait for
+    pub const ECHILD: i32 = -10;
+
+    /// Try again
+    pub const EAGAIN: i32 = -11;
+
+    /// Out of memory
+    pub const ENOMEM: i32 = -12;
+
+    /// Permission denied
+    pub const EACCES: i32 = -13;
+
+    /// Bad address
+    pub const EFAULT: i32 = -14;
+
+    /// Device or resource busy
+    pub const EBUSY: i32 = -16;
+
+    /// File exists
+    pub const EEXIST: i32 = -17;
+
+    /// Not a directory
+    pub const ENOTDIR: i32 = -20;
+
+    /// Is a directory
+    pub const EISDIR: i32 = -21;
+
+    /// Invalid argument
+    pub const EINVAL: i32 = -22;
+
+    /// Too many open files
+    pub const EMFILE: i32 = -24;
+
+    /// No space left on device
+    pub const ENOSPC: i32 = -28;
+
+    /// Read-only file system
+    pub const EROFS: i32 = -30;
+
+    /// Broken pipe (the channel was closed)
+    pub const EPIPE: i32 = -32;
+
+    /// File name too long
+    pub const ENAMETOOLONG: i32 = -36;
+
+    /// Function not implemented
+    pub const ENOSYS: i32 = -38;
+
+    /// Directory not empty
+    pub const ENOTEMPTY: i32 = -39;
+
+    /// Too many levels of symbolic links
+    pub const ELOOP: i32 = -40;
+
+    /// Timed out
+    pub const ETIMEDOUT: i32 = -110;
+
+    /// Operation would block
+    pub const EWOULDBLOCK: i32 = EAGAIN;
+}
+
+/// Flags for `SYS_OPEN`
+pub mod open_flags {
+    /// Open for reading
+    pub const O_READ: u64 = 1 << 0;
+
+    /// Open for writing
+    pub const O_WRITE: u64 = 1 << 1;
+
+    /// Create the file if it does not exist
+    pub const O_CREATE: u64 = 1 << 2;
+
+    /// Truncate the file to zero length on open
+    pub const O_TRUNCATE: u64 = 1 << 3;
+
+    /// Every write goes to the end of the file
+    pub const O_APPEND: u64 = 1 << 4;
+
+    /// Mask of all flags the kernel understands
+    pub const O_ALL: u64 = O_READ | O_WRITE | O_CREATE | O_TRUNCATE | O_APPEND;
+}
+
+/// Protection flags for `SYS_MMAP`
+///
+/// Every mapping must be readable. Writable mappings may not also be
+/// executable (W^X).
+pub mod prot {
+    /// Pages may be read
+    pub const PROT_READ: u64 = 1 << 0;
+
+    /// Pages may be written
+    pub const PROT_WRITE: u64 = 1 << 1;
+
+    /// Pages may be executed
+    pub const PROT_EXEC: u64 = 1 << 2;
+
+    /// Mask of all flags the kernel understands
+    pub const PROT_ALL: u64 = PROT_READ | PROT_WRITE | PROT_EXEC;
+}
+
+/// Constants for the IPC syscalls
+pub mod ipc {
+    /// `SYS_IPC_RECV` timeout meaning "block until a message arrives"
+    pub const WAIT_FOREVER: u64 = u64::MAX;
+
+    /// The message carried bytes, copied into the caller's buffer
+    pub const MSG_BYTES: u64 = 0;
+
+    /// The message carried a shared memory object, now mapped
+    pub const MSG_SHARED_MEMORY: u64 = 1;
+
+    /// `SYS_IPC_SEND_SHARED`: the receiver gets the caller's rights, and both
+    /// keep the mapping
+    pub const TRANSFER_GRANT: u64 = 0;
+
+    /// `SYS_IPC_SEND_SHARED`: the caller's mapping is removed and its rights
+    /// pass to the receiver
+    pub const TRANSFER_MOVE: u64 = 1;
+
+    /// `SYS_IPC_SEND_SHARED`: the receiver gets a read-only mapping, and the
+    /// caller keeps its own
+    pub const TRANSFER_SHARE: u64 = 2;
+
+    /// Most capabilities a single message can carry
+    pub const MAX_MESSAGE_CAPABILITIES: usize = 4;
+
+    /// Most channels and notifications a single `SYS_WAIT_ANY` can watch
+    pub const MAX_WAIT_SOURCES: usize = 16;
+
+    /// What `SYS_IPC_RECV` delivered, written through `r8`
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub struct RecvInfo {
+        /// [`MSG_BYTES`] or [`MSG_SHARED_MEMORY`]
+        pub kind: u64,
+        /// Shared memory: base address of the mapping
+        pub address: u64,
+        /// Shared memory: size of the mapping in bytes
+        pub size: u64,
+        /// Shared memory: [`prot`](crate::prot) flags of the mapping
+        pub prot: u64,
+        /// How many entries of `capabilities` are filled in
+        pub capability_count: u64,
+        /// Capabilities passed with the message, now in the caller's table
+        pub capabilities: [u64; MAX_MESSAGE_CAPABILITIES],
+    }
+}
+
+/// Constants for `SYS_SPAWN`
+pub mod spawn {
+    /// Most bytes the argument and environment blocks may hold together
+    ///
+    /// Both are copied onto the new Vessel's first stack page along with
+    /// the pointer arrays and auxiliary vector, so they must leave room.
+    pub const MAX_ARGS_LEN: usize = 2048;
+}
+
+/// Constants and types for `SYS_WAIT`
+pub mod wait {
+    /// Wait for whichever child vanishes first
+    pub const ANY_CHILD: u64 = 0;
+
+    /// The child's threads all exited; `code` is its main thread's exit code
+    pub const EXITED: u64 = 0;
+
+    /// The kernel terminated the child after an unrecoverable fault at `address`
+    pub const FAULTED: u64 = 1;
+
+    /// How a child ended, written through `rsi`
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub struct ExitStatus {
+        /// [`EXITED`] or [`FAULTED`]
+        pub kind: u64,
+        /// Exited: the exit code passed to `SYS_EXIT`
+        pub code: u64,
+        /// Faulted: the faulting address
+        pub address: u64,
+    }
+}
+
+/// Auxiliary vector entry types
+///
+/// A spawned Vessel starts with the System V layout on its stack: `argc`,
+/// the `argv` pointers and a null, the `envp` pointers and a null, then
+/// `(type, value)` pairs of `u64`s ending with [`AT_NULL`](auxv::AT_NULL).
+/// The stack pointer at entry points at `argc` and is 16-byte aligned.
+pub mod auxv {
+    /// End of the vector
+    pub const AT_NULL: u64 = 0;
+
+    /// Address of the program headers in memory
+    pub const AT_PHDR: u64 = 3;
+
+    /// Size of one program header
+    pub const AT_PHENT: u64 = 4;
+
+    /// Number of program headers
+    pub const AT_PHNUM: u64 = 5;
+
+    /// Page size
+    pub const AT_PAGESZ: u64 = 6;
+
+    /// Entry point of the program
+    pub const AT_ENTRY: u64 = 9;
+
+    /// Address of 16 random bytes
+    pub const AT_RANDOM: u64 = 25;
+}
+
+/// Well-known file descriptors present in every Vessel
+pub mod fds {
+    /// Standard input
+    pub const STDIN: u64 = 0;
+
+    /// Standard output
+    pub const STDOUT: u64 = 1;
+
+    /// Standard error
+    pub const STDERR: u64 = 2;
+}
+
+/// The six syscall arguments, in ABI order
+///
+/// The kernel builds this from the saved registers on entry, so handlers
+/// never need to know which register carried which argument.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyscallArgs {
+    /// RDI
+    pub arg1: u64,
+    /// RSI
+    pub arg2: u64,
+    /// RDX
+    pub arg3: u64,
+    /// R10
+    pub arg4: u64,
+    /// R8
+    pub arg5: u64,
+    /// R9
+    pub arg6: u64,
+}
+
+impl SyscallArgs {
+    /// Bundle six raw register values into a `SyscallArgs`
+    pub const fn new(arg1: u64, arg2: u64, arg3: u64, arg4: u64, arg5: u64, arg6: u64) -> Self {
+        Self { arg1, arg2, arg3, arg4, arg5, arg6 }
+    }
+}
+
+/// Get the human-readable name of a syscall number (for tracing)
+pub fn syscall_name(num: u64) -> &'static str {
+    use numbers::*;
+
+    match num {
+        SYS_EXIT => "exit",
+        SYS_WRITE => "write",
+        SYS_READ => "read",
+        SYS_OPEN => "open",
+        SYS_CLOSE => "close",
+        SYS_GETPID => "getpid",
+        SYS_QUERY => "query",
+        SYS_COMMIT => "commit",
+        SYS_MMAP => "mmap",
+        SYS_MUNMAP => "munmap",
+        SYS_SLEEP => "sleep",
+        SYS_YIELD => "yield",
+        SYS_THREAD_CREATE => "thread_create",
+        SYS_THREAD_JOIN => "thread_join",
+        SYS_IPC_SEND => "ipc_send",
+        SYS_IPC_RECV => "ipc_recv",
+        SYS_TIME => "time",
+        SYS_EXEC_SCRIPT => "exec_script",
+        SYS_GETTID => "gettid",
+        SYS_NAME_REGISTER => "name_register",
+        SYS_NAME_RESOLVE => "name_resolve",
+        SYS_NOTIFY_CREATE => "notify_create",
+        SYS_NOTIFY_RAISE => "notify_raise",
+        SYS_WAIT_ANY => "wait_any",
+        SYS_IRQ_BIND => "irq_bind",
+        SYS_SPAWN => "spawn",
+        SYS_WAIT => "wait",
+        SYS_SHM_CREATE => "shm_create",
+        SYS_IPC_SEND_SHARED => "ipc_send_shared",
+        _ => "unknown",
+    }
+}
+
+/// Check whether a raw syscall return value is an error
+#[inline]
+pub const fn is_error(ret: i64) -> bool {
+    ret < 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_slot_is_named() {
+        for num in 0..numbers::SYSCALL_COUNT as u64 {
+            assert_ne!(syscall_name(num), "unknown", "syscall {} has no name", num);
+        }
+        assert_eq!(syscall_name(numbers::SYSCALL_COUNT as u64), "unknown");
+    }
+
+    #[test]
+    fn test_exit_is_not_yield() {
+        // The original bug: the kernel and corelib disagreed on these two
+        assert_eq!(numbers::SYS_EXIT, 0);
+        assert_eq!(numbers::SYS_YIELD, 11);
+    }
+
+    #[test]
+    fn test_errno_values_are_negative() {
+        for e in [errno::EPERM, errno::ENOENT, errno::EBADF, errno::ENOSYS, errno::EROFS] {
+            assert!(is_error(e as i64));
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! # ACPI - The Firmware's Testament
+//!
+//! The firmware describes the machine in ACPI tables: the RSDP (found by
+//! scanning BIOS memory) points to the RSDT or XSDT, which in turn lists
+//! every other table (MCFG, MADT, HPET, ...) by physical address.
+//!
+//! Only table discovery lives here; callers parse the tables they need.
+//! The tables are found once, on first use, and each stays mapped for good.
+
+use crate::mana_pool::page_tables::map_physical;
+use alloc::vec::Vec;
+use spin::Mutex;
+
+/// The top 2GB of virtual memory maps the first 1GB of physical memory
+const KERNEL_BASE: u64 = 0xFFFFFFFF80000000;
+const DIRECT_MAP_LIMIT: u64 = 0x4000_0000;
+
+/// Where the BIOS keeps the EBDA segment, and the BIOS read-only area
+const EBDA_SEGMENT_PTR: u64 = 0x40E;
+const BIOS_AREA_START: u64 = 0xE0000;
+const BIOS_AREA_END: u64 = 0x100000;
+
+const RSDP_SIGNATURE: &[u8; 8] = b"RSD PTR ";
+
+/// Size of the common header every system description table starts with
+pub const SDT_HEADER_SIZE: usize = 36;
+
+/// Firmware memory mapped beyond the direct map, as whole pages
+/// (physical start, virtual start, length)
+static MAPPINGS: Mutex<Vec<(u64, u64, u64)>> = Mutex::new(Vec::new());
+
+/// Every valid table the root table lists, once they have been looked for
+static TABLES: Mutex<Option<Vec<&'static [u8]>>> = Mutex::new(None);
+
+/// Make `len` bytes of physical memory readable
+///
+/// Tables in the first 1GB are read through the kernel's direct map.
+/// Anything higher is mapped (cached, read-only) the first time it is
+/// asked for, and later requests within those pages reuse the mapping.
+fn phys_slice(phys: u64, len: usize) -> Option<&'static [u8]> {
+    let end = phys.checked_add(len as u64)?;
+    let virt = if end <= DIRECT_MAP_LIMIT {
+        KERNEL_BASE + phys
+    } else {
+        let mut mappings = MAPPINGS.lock();
+        let existing = mappings.iter().find(|&&(start, _, size)| start <= phys && end <= start + size);
+        let (start, virt) = match existing {
+            Some(&(start, virt, _)) => (start, virt),
+            None => {
+                let start = phys & !0xFFF;
+                let size = (end - start).next_multiple_of(0x1000);
+                let virt = unsafe { map_physical(start, size).ok()? };
+                mappings.push((start, virt, size));
+                (start, virt)
+            }
+        };
+        virt + (phys - start)
+    };
+    Some(unsafe { core::slice::from_raw_parts(virt as *const u8, len) })
+}
+
+fn checksum_ok(bytes: &[u8]) -> bool {
+    bytes.iter().fold(0u8, |sum, &b| sum.wrapping_add(b)) == 0
+}
+
+fn read_u32(data: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]])
+}
+
+fn read_u64(data: &[u8], at: usize) -> u64 {
+    read_u32(data, at) as u64 | (read_u32(data, at + 4) as u64) << 32
+}
+
+/// Scan a physical range for the RSDP (it sits on a 16-byte boundary)
+fn scan_for_rsdp(start: u64, end: u64) -> Option<&'static [u8]> {
+    let area = phys_slice(start, (end - start) as usize)?;
+    (0..area.len().saturating_sub(20))
+        .step_by(16)
+        .map(|offset| &area[offset..])
+        .find(|candidate| &candidate[..8] == RSDP_SIGNATURE && checksum_ok(&candidate[..20]))
+}
+
+/// Physical address and entry width of the root table (XSDT or RSDT)
+fn root_table() -> Option<(u64, usize)> {
+    let ebda = (u16::from_le_bytes(phys_slice(EBDA_SEGMENT_PTR, 2)?.try_into().ok()?) as u64) << 4;
+    let rsdp = (ebda != 0)
+        .then(|| scan_for_rsdp(ebda, ebda + 1024))
+        .flatten()
+        .or_else(|| scan_for_rsdp(BIOS_AREA_START, BIOS_AREA_END))?;
+
+    // ACPI 2.0+ adds a 64-bit XSDT address, covered by an extended checksum
+    let revision = rsdp[15];
+    if revision >= 2 && rsdp.len() >= 36 && checksum_ok(&rsdp[..36]) {
+        let xsdt = read_u64(rsdp, 24);
+        if xsdt != 0 {
+            return Some((xsdt, 8));
+        }
+    }
+    Some((read_u32(rsdp, 16) as u64, 4))
+}
+
+/// Read a whole table, verifying its length and checksum
+fn load_table(phys: u64) -> Option<&'static [u8]> {
+    let header = phys_slice(phys, SDT_HEADER_SIZE)?;
+    let length = read_u32(header, 4) as usize;
+    if length < SDT_HEADER_SIZE {
+        return None;
+    }
+
+    let table = phys_slice(phys, length)?;
+    checksum_ok(table).then_some(table)
+}
+
+/// Find an ACPI table by its signature (e.g. `b"MCFG"`)
+///
+/// # Returns
+///
+/// * `Some(table)` - The whole table, header included
+/// * `None` - No RSDP, or no valid table with that signature
+pub fn find_table(signature: &[u8; 4]) -> Option<&'static [u8]> {
+    TABLES.lock()
+        .get_or_insert_with(|| all_tables().unwrap_or_default())
+        .iter()
+        .copied()
+        .find(|table| &table[..4] == signature)
+}
+
+/// Load every valid table the root table lists
+fn all_tables() -> Option<Vec<&'static [u8]>> {
+    let (root_phys, entry_size) = root_table()?;
+    let root = load_table(root_phys)?;
+
+    Some(root[SDT_HEADER_SIZE..]
+        .chunks_exact(entry_size)
+        .map(|entry| if entry_size == 8 { read_u64(entry, 0) } else { read_u32(entry, 0) as u64 })
+        .filter_map(load_table)
+        .collect())
+}
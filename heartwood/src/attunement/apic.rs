@@ -0,0 +1,219 @@
+//! # Local APIC - Each Processor's Own Ear
+//!
+//! Every processor has a Local APIC that accepts its interrupts, takes
+//! their End of Interrupt, and sends and receives inter-processor
+//! interrupts (IPIs). All of them answer at the same physical address;
+//! each processor reaches its own.
+//!
+//! Each Local APIC also has a timer counting down at the bus clock. It is
+//! used one-shot: armed for the next deadline, it fires once.
+//!
+//! Registers are 32 bits wide on 16-byte boundaries (xAPIC mode).
+
+use core::sync::atomic::{AtomicU64, Ordering};
+use x86_64::registers::model_specific::Msr;
+
+/// IA32_APIC_BASE MSR: physical base and the global enable bit
+const IA32_APIC_BASE: u32 = 0x1B;
+const APIC_BASE_ENABLE: u64 = 1 << 11;
+
+/// Register offsets
+const REG_ID: u32 = 0x20;
+const REG_TPR: u32 = 0x80;
+const REG_EOI: u32 = 0xB0;
+const REG_SVR: u32 = 0xF0;
+const REG_ESR: u32 = 0x280;
+const REG_ICR_LOW: u32 = 0x300;
+const REG_ICR_HIGH: u32 = 0x310;
+const REG_LVT_TIMER: u32 = 0x320;
+const REG_LVT_LINT0: u32 = 0x350;
+const REG_LVT_LINT1: u32 = 0x360;
+const REG_LVT_ERROR: u32 = 0x370;
+const REG_TIMER_INITIAL: u32 = 0x380;
+const REG_TIMER_CURRENT: u32 = 0x390;
+const REG_TIMER_DIVIDE: u32 = 0x3E0;
+
+/// Spurious-interrupt vector register: APIC software enable
+const SVR_ENABLE: u32 = 1 << 8;
+
+/// LVT entry mask bit
+const LVT_MASKED: u32 = 1 << 16;
+
+/// LINT0 delivery mode that passes the 8259's interrupts through
+const LVT_DELIVERY_EXTINT: u32 = 0b111 << 8;
+
+/// Timer divide configuration: the bus clock divided by 16
+const TIMER_DIVIDE_16: u32 = 0b0011;
+
+/// Interrupt command register fields
+const ICR_DELIVERY_INIT: u32 = 0b101 << 8;
+const ICR_DELIVERY_STARTUP: u32 = 0b110 << 8;
+const ICR_DELIVERY_PENDING: u32 = 1 << 12;
+const ICR_LEVEL_ASSERT: u32 = 1 << 14;
+const ICR_ALL_EXCLUDING_SELF: u32 = 0b11 << 18;
+
+/// Vector the APIC raises for spurious interrupts (needs no EOI)
+pub const SPURIOUS_VECTOR: u8 = 0xFF;
+
+/// Vector the Local APIC timer raises when a deadline arrives
+pub const TIMER_VECTOR: u8 = 0xEF;
+
+/// Kernel virtual address of the Local APIC registers (0 until mapped)
+static LAPIC_BASE: AtomicU64 = AtomicU64::new(0);
+
+/// Timer counts per second, after the divider (0 until calibrated)
+///
+/// Every Local APIC runs off the same bus clock, so one measurement serves
+/// all processors.
+static TIMER_HZ: AtomicU64 = AtomicU64::new(0);
+
+/// Map the Local APIC registers
+///
+/// Every processor's APIC answers at the same address, so this is done
+/// once, by the bootstrap processor.
+///
+/// # Safety
+///
+/// `phys_addr` must be the Local APIC base reported by the firmware.
+pub unsafe fn map(phys_addr: u64) -> Result<(), &'static str> {
+    let virt = crate::mana_pool::page_tables::map_mmio(phys_addr, 0x1000)?;
+    LAPIC_BASE.store(virt, Ordering::Release);
+    Ok(())
+}
+
+/// Whether the Local APIC registers are mapped
+pub fn is_mapped() -> bool {
+    LAPIC_BASE.load(Ordering::Acquire) != 0
+}
+
+/// Enable this processor's Local APIC
+///
+/// LINT1 (NMI wiring) is masked. LINT0 passes the 8259's interrupts
+/// through on the bootstrap processor while there is no I/O APIC to
+/// replace it, and is masked otherwise. The timer is stopped, and every
+/// interrupt priority is accepted.
+pub fn enable() {
+    unsafe {
+        let mut base = Msr::new(IA32_APIC_BASE);
+        let value = base.read();
+        base.write(value | APIC_BASE_ENABLE);
+    }
+
+    write(REG_TPR, 0);
+    if super::ioapic::is_active() || super::smp::current_cpu() != 0 {
+        write(REG_LVT_LINT0, LVT_MASKED);
+    } else {
+        write(REG_LVT_LINT0, LVT_DELIVERY_EXTINT);
+    }
+    write(REG_LVT_LINT1, LVT_MASKED);
+    write(REG_LVT_ERROR, LVT_MASKED);
+    // The error status register must be written before it is read
+    write(REG_ESR, 0);
+    write(REG_SVR, SVR_ENABLE | SPURIOUS_VECTOR as u32);
+    write(REG_TIMER_DIVIDE, TIMER_DIVIDE_16);
+    stop_timer();
+    end_of_interrupt();
+}
+
+/// Measure the timer's rate
+///
+/// `wait` busy-waits for a while and returns how many nanoseconds it
+/// waited. Runs once, on the bootstrap processor, with interrupts disabled.
+///
+/// # Returns
+///
+/// Timer counts per second (0 if the timer did not move)
+pub fn calibrate_timer(wait: impl FnOnce() -> u64) -> u64 {
+    write(REG_LVT_TIMER, LVT_MASKED | TIMER_VECTOR as u32);
+    write(REG_TIMER_INITIAL, u32::MAX);
+    let elapsed_ns = wait();
+    let counted = u32::MAX - read(REG_TIMER_CURRENT);
+    stop_timer();
+
+    let hz = super::clocksource::frequency_hz(counted as u64, elapsed_ns);
+    TIMER_HZ.store(hz, Ordering::Relaxed);
+    hz
+}
+
+/// Whether the timer's rate is known, so deadlines can be armed
+pub fn timer_is_calibrated() -> bool {
+    TIMER_HZ.load(Ordering::Relaxed) != 0
+}
+
+/// Fire `TIMER_VECTOR` once, `delay_ns` from now
+///
+/// Replaces any deadline already armed on this processor. Delays beyond
+/// the counter's range fire early, at the longest delay it can count.
+pub fn arm_timer(delay_ns: u64) {
+    let counts = super::clocksource::ns_to_counts(delay_ns, TIMER_HZ.load(Ordering::Relaxed));
+    write(REG_LVT_TIMER, TIMER_VECTOR as u32);
+    write(REG_TIMER_INITIAL, counts.clamp(1, u32::MAX as u64) as u32);
+}
+
+/// Cancel this processor's deadline
+pub fn stop_timer() {
+    write(REG_LVT_TIMER, LVT_MASKED | TIMER_VECTOR as u32);
+    write(REG_TIMER_INITIAL, 0);
+}
+
+/// This processor's APIC ID
+///
+/// Returns 0 until the registers are mapped, which only the bootstrap
+/// processor can observe.
+pub fn id() -> u8 {
+    if !is_mapped() {
+        return 0;
+    }
+    (read(REG_ID) >> 24) as u8
+}
+
+/// Signal End of Interrupt for the interrupt being serviced
+pub fn end_of_interrupt() {
+    write(REG_EOI, 0);
+}
+
+/// Send a fixed interrupt to one processor
+pub fn send_ipi(apic_id: u8, vector: u8) {
+    send(apic_id, vector as u32);
+}
+
+/// Send a fixed interrupt to every processor but this one
+pub fn broadcast_ipi(vector: u8) {
+    send(0, ICR_ALL_EXCLUDING_SELF | vector as u32);
+}
+
+/// Send an INIT IPI, resetting the processor into wait-for-SIPI
+pub fn send_init(apic_id: u8) {
+    send(apic_id, ICR_DELIVERY_INIT | ICR_LEVEL_ASSERT);
+}
+
+/// Send a Startup IPI: the processor starts in real mode at `page * 4KB`
+pub fn send_startup(apic_id: u8, page: u8) {
+    send(apic_id, ICR_DELIVERY_STARTUP | ICR_LEVEL_ASSERT | page as u32);
+}
+
+/// Write the interrupt command register and wait for delivery
+fn send(apic_id: u8, command: u32) {
+    crate::loom_of_fate::without_interrupts(|| {
+        wait_for_delivery();
+        write(REG_ICR_HIGH, (apic_id as u32) << 24);
+        write(REG_ICR_LOW, command);
+        wait_for_delivery();
+    });
+}
+
+fn wait_for_delivery() {
+    while read(REG_ICR_LOW) & ICR_DELIVERY_PENDING != 0 {
+        core::hint::spin_loop();
+    }
+}
+
+fn read(reg: u32) -> u32 {
+    let base = LAPIC_BASE.load(Ordering::Acquire);
+    unsafe { core::ptr::read_volatile((base + reg as u64) as *const u32) }
+}
+
+fn write(reg: u32, value: u32) {
+    let base = LAPIC_BASE.load(Ordering::Acquire);
+    unsafe { core::ptr::write_volatile((base + reg as u64) as *mut u32, value) }
+}
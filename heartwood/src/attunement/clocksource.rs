@@ -0,0 +1,240 @@
+//! # Clock Source - How Long Has It Been
+//!
+//! A clock source is a free-running counter of known frequency. The best
+//! one the machine offers is chosen at boot and read as a monotonic clock
+//! in nanoseconds since the Attunement began:
+//!
+//! 1. The TSC, if it is invariant (it then ticks at a constant rate
+//!    whatever the processor's speed or sleep state), calibrated against
+//!    the HPET or, failing that, the PIT
+//! 2. The HPET's main counter
+//! 3. The heartbeat count, kept by the periodic PIT interrupt, which only
+//!    resolves whole heartbeats
+//!
+//! The TSCs of all processors are assumed to run in step, as invariant
+//! TSCs reset together at power-on do.
+
+use super::{hpet, pit, timer};
+use core::arch::x86_64::{__cpuid, _rdtsc};
+use core::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+/// Nanoseconds in a second
+const NS_PER_SECOND: u64 = 1_000_000_000;
+
+/// How long the TSC is measured against its reference
+const CALIBRATION_NS: u64 = 10_000_000;
+
+/// CPUID leaf with the invariant TSC flag (EDX bit 8)
+const CPUID_ADVANCED_POWER: u32 = 0x8000_0007;
+const INVARIANT_TSC: u32 = 1 << 8;
+
+/// A free-running counter that can tell the time
+pub trait ClockSource: Sync {
+    /// Name shown to the user
+    fn name(&self) -> &'static str;
+
+    /// The counter's current value
+    fn read(&self) -> u64;
+
+    /// Counts per second
+    fn frequency(&self) -> u64;
+}
+
+/// The processor's Time Stamp Counter
+struct Tsc;
+
+/// Calibrated TSC rate in Hz (0 until measured)
+static TSC_HZ: AtomicU64 = AtomicU64::new(0);
+
+impl ClockSource for Tsc {
+    fn name(&self) -> &'static str {
+        "TSC"
+    }
+
+    fn read(&self) -> u64 {
+        unsafe { _rdtsc() }
+    }
+
+    fn frequency(&self) -> u64 {
+        TSC_HZ.load(Ordering::Relaxed)
+    }
+}
+
+/// The HPET's main counter
+struct Hpet;
+
+impl ClockSource for Hpet {
+    fn name(&self) -> &'static str {
+        "HPET"
+    }
+
+    fn read(&self) -> u64 {
+        hpet::counter()
+    }
+
+    fn frequency(&self) -> u64 {
+        hpet::frequency()
+    }
+}
+
+/// Heartbeats counted by the periodic timer interrupt
+struct Heartbeat;
+
+impl ClockSource for Heartbeat {
+    fn name(&self) -> &'static str {
+        "heartbeat"
+    }
+
+    fn read(&self) -> u64 {
+        timer::beats()
+    }
+
+    fn frequency(&self) -> u64 {
+        NS_PER_SECOND / timer::HEARTBEAT_NS
+    }
+}
+
+/// Every clock source, indexed by `SOURCE`
+static SOURCES: [&dyn ClockSource; 3] = [&Heartbeat, &Hpet, &Tsc];
+const SOURCE_HEARTBEAT: u8 = 0;
+const SOURCE_HPET: u8 = 1;
+const SOURCE_TSC: u8 = 2;
+
+/// The chosen clock source; the heartbeat until `init` picks another
+static SOURCE: AtomicU8 = AtomicU8::new(SOURCE_HEARTBEAT);
+
+/// Counter value and time when the chosen source took over, so the clock
+/// carries on from where the heartbeat left it
+static BASE_COUNT: AtomicU64 = AtomicU64::new(0);
+static BASE_NS: AtomicU64 = AtomicU64::new(0);
+
+/// Choose the clock source, calibrating the TSC if it will be used
+///
+/// Runs once, on the bootstrap processor, with interrupts disabled.
+pub fn init() {
+    let source = if tsc_is_invariant() {
+        TSC_HZ.store(calibrate_tsc(), Ordering::Relaxed);
+        SOURCE_TSC
+    } else if hpet::is_available() {
+        SOURCE_HPET
+    } else {
+        SOURCE_HEARTBEAT
+    };
+
+    if source != SOURCE_HEARTBEAT {
+        BASE_NS.store(now_ns(), Ordering::Relaxed);
+        BASE_COUNT.store(SOURCES[source as usize].read(), Ordering::Relaxed);
+        SOURCE.store(source, Ordering::Release);
+    }
+}
+
+/// The chosen clock source
+pub fn current() -> &'static dyn ClockSource {
+    SOURCES[SOURCE.load(Ordering::Acquire) as usize]
+}
+
+/// Nanoseconds since the Attunement began
+pub fn now_ns() -> u64 {
+    let source = current();
+    let elapsed = source.read().wrapping_sub(BASE_COUNT.load(Ordering::Relaxed));
+    BASE_NS.load(Ordering::Relaxed) + counts_to_ns(elapsed, source.frequency())
+}
+
+/// Whether the clock resolves finer than a heartbeat
+pub fn is_precise() -> bool {
+    SOURCE.load(Ordering::Acquire) != SOURCE_HEARTBEAT
+}
+
+/// Busy-wait for `ns` nanoseconds
+///
+/// Uses the clock when it is precise, and the PIT otherwise.
+pub fn delay_ns(ns: u64) {
+    if is_precise() {
+        let start = now_ns();
+        while now_ns() - start < ns {
+            core::hint::spin_loop();
+        }
+    } else {
+        pit::delay_us(ns.div_ceil(1000));
+    }
+}
+
+/// Convert a count at `hz` to nanoseconds
+pub fn counts_to_ns(counts: u64, hz: u64) -> u64 {
+    if hz == 0 {
+        return 0;
+    }
+    (counts as u128 * NS_PER_SECOND as u128 / hz as u128) as u64
+}
+
+/// Convert nanoseconds to a count at `hz`, rounding up
+pub fn ns_to_counts(ns: u64, hz: u64) -> u64 {
+    (ns as u128 * hz as u128).div_ceil(NS_PER_SECOND as u128) as u64
+}
+
+/// The rate of a counter that advanced `counts` in `elapsed_ns`
+pub fn frequency_hz(counts: u64, elapsed_ns: u64) -> u64 {
+    if elapsed_ns == 0 {
+        return 0;
+    }
+    (counts as u128 * NS_PER_SECOND as u128 / elapsed_ns as u128) as u64
+}
+
+/// Whether the TSC ticks at a constant rate in every power state
+fn tsc_is_invariant() -> bool {
+    let max_extended = __cpuid(0x8000_0000).eax;
+    max_extended >= CPUID_ADVANCED_POWER && __cpuid(CPUID_ADVANCED_POWER).edx & INVARIANT_TSC != 0
+}
+
+/// Measure the TSC against the HPET, or the PIT if there is none
+fn calibrate_tsc() -> u64 {
+    if hpet::is_available() {
+        let hpet_hz = hpet::frequency();
+        let target = ns_to_counts(CALIBRATION_NS, hpet_hz);
+
+        let hpet_start = hpet::counter();
+        let tsc_start = unsafe { _rdtsc() };
+        let mut hpet_elapsed = 0;
+        while hpet_elapsed < target {
+            hpet_elapsed = hpet::counter() - hpet_start;
+        }
+        let tsc_elapsed = unsafe { _rdtsc() } - tsc_start;
+
+        frequency_hz(tsc_elapsed, counts_to_ns(hpet_elapsed, hpet_hz))
+    } else {
+        let tsc_start = unsafe { _rdtsc() };
+        pit::delay_us(CALIBRATION_NS / 1000);
+        let tsc_elapsed = unsafe { _rdtsc() } - tsc_start;
+
+        frequency_hz(tsc_elapsed, CALIBRATION_NS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_to_ns() {
+        assert_eq!(counts_to_ns(3_000_000_000, 3_000_000_000), NS_PER_SECOND);
+        assert_eq!(counts_to_ns(14_318_180, 14_318_180), NS_PER_SECOND);
+        assert_eq!(counts_to_ns(1, 100), 10_000_000);
+        assert_eq!(counts_to_ns(5, 0), 0);
+        // A TSC at 4GHz after a year does not overflow
+        assert_eq!(counts_to_ns(4_000_000_000 * 31_536_000, 4_000_000_000), 31_536_000 * NS_PER_SECOND);
+    }
+
+    #[test]
+    fn test_ns_to_counts_rounds_up() {
+        assert_eq!(ns_to_counts(1_000, 1_000_000), 1);
+        assert_eq!(ns_to_counts(1_001, 1_000_000), 2);
+        assert_eq!(ns_to_counts(10_000_000, 14_318_180), 143_182);
+    }
+
+    #[test]
+    fn test_frequency_hz() {
+        assert_eq!(frequency_hz(30_000_000, CALIBRATION_NS), 3_000_000_000);
+        assert_eq!(frequency_hz(625_000, CALIBRATION_NS), 62_500_000);
+        assert_eq!(frequency_hz(1, 0), 0);
+    }
+}
@@ -16,6 +16,7 @@
 
 use core::arch::asm;
 use core::mem::{size_of, MaybeUninit};
+use super::per_cpu::MAX_CPUS;
 
 /// GDT Entry - A segment descriptor
 #[derive(Debug, Clone, Copy)]
@@ -361,19 +362,20 @@ pub mod selectors {
 // Runtime GDT and TSS - Placed in .rune for permanence
 // ═══════════════════════════════════════════════════════════════════════════
 
-/// The Task State Segment - placed in .rune for permanence
+/// The Task State Segments, one per CPU - placed in .rune for permanence
 ///
-/// After boot, this becomes read-only, preventing modification of privilege
+/// After boot, these become read-only, preventing modification of privilege
 /// stack pointers or IST entries.
 #[link_section = ".rune"]
-static mut TSS: MaybeUninit<TaskStateSegment> = MaybeUninit::uninit();
+static mut TSS: [MaybeUninit<TaskStateSegment>; MAX_CPUS] = [const { MaybeUninit::uninit() }; MAX_CPUS];
 
-/// The Global Descriptor Table - placed in .rune for permanence
+/// The Global Descriptor Tables, one per CPU - placed in .rune for permanence
 ///
-/// After boot, this becomes read-only, preventing modification of segment
-/// descriptors, privilege levels, or segment boundaries.
+/// After boot, these become read-only, preventing modification of segment
+/// descriptors, privilege levels, or segment boundaries. Each CPU needs its
+/// own table because loading a TSS marks its descriptor busy.
 #[link_section = ".rune"]
-static mut GDT: MaybeUninit<GlobalDescriptorTable> = MaybeUninit::uninit();
+static mut GDT: [MaybeUninit<GlobalDescriptorTable>; MAX_CPUS] = [const { MaybeUninit::uninit() }; MAX_CPUS];
 
 /// Track whether GDT has been initialized
 static mut GDT_INITIALIZED: bool = false;
@@ -388,30 +390,43 @@ pub fn init() {
         crate::serial_println!("[GDT INIT] GDT static variable is at address: {:#x}",
             &GDT as *const _ as u64);
 
-        // Initialize TSS
-        let tss = TaskStateSegment::new();
-        TSS.write(tss);
-        let tss_ref: &'static TaskStateSegment = TSS.assume_init_ref();
-
-        // Initialize GDT with all segments
-        let mut gdt = GlobalDescriptorTable::new();
-        gdt.initialize(tss_ref);
-        GDT.write(gdt);
+        install(0);
         GDT_INITIALIZED = true;
 
         crate::serial_println!("[GDT INIT] After GDT.write(), entries array is at: {:#x}",
-            GDT.assume_init_ref().entries.as_ptr() as u64);
+            GDT[0].assume_init_ref().entries.as_ptr() as u64);
+    }
+}
+
+/// Initialize and load the GDT and TSS of an application processor
+///
+/// # Safety
+/// Must be called once, on the processor itself, before seal_rune_section()
+pub unsafe fn init_ap(cpu_id: usize) {
+    install(cpu_id);
+}
 
-        // Load the new GDT
-        let gdt_ref: &'static GlobalDescriptorTable = GDT.assume_init_ref();
-        gdt_ref.load();
+/// Build a CPU's TSS and GDT, then load both on the calling processor
+unsafe fn install(cpu_id: usize) {
+    let tss_slot = &mut *core::ptr::addr_of_mut!(TSS[cpu_id]);
+    tss_slot.write(TaskStateSegment::new());
+    let tss_ref: &'static TaskStateSegment = tss_slot.assume_init_ref();
 
-        // Load the TSS
-        gdt_ref.load_tss();
-    }
+    // Initialize GDT with all segments
+    let mut gdt = GlobalDescriptorTable::new();
+    gdt.initialize(tss_ref);
+    let gdt_slot = &mut *core::ptr::addr_of_mut!(GDT[cpu_id]);
+    gdt_slot.write(gdt);
+
+    // Load the new GDT
+    let gdt_ref: &'static GlobalDescriptorTable = gdt_slot.assume_init_ref();
+    gdt_ref.load();
+
+    // Load the TSS
+    gdt_ref.load_tss();
 }
 
-/// Get a reference to the GDT (for introspection)
+/// Get a reference to this CPU's GDT (for introspection)
 ///
 /// # Safety
 /// Must only be called after init()
@@ -419,10 +434,10 @@ pub unsafe fn get_gdt() -> &'static GlobalDescriptorTable {
     if !GDT_INITIALIZED {
         panic!("GDT not initialized!");
     }
-    GDT.assume_init_ref()
+    GDT[crate::attunement::smp::current_cpu()].assume_init_ref()
 }
 
-/// Get a reference to the TSS (for introspection)
+/// Get a reference to this CPU's TSS (for introspection)
 ///
 /// # Safety
 /// Must only be called after init()
@@ -430,7 +445,7 @@ pub unsafe fn get_tss() -> &'static TaskStateSegment {
     if !GDT_INITIALIZED {
         panic!("GDT/TSS not initialized!");
     }
-    TSS.assume_init_ref()
+    TSS[crate::attunement::smp::current_cpu()].assume_init_ref()
 }
 
 /// Force-reload Ring 1 segments into the ACTIVE GDT
@@ -471,7 +486,7 @@ pub unsafe fn patch_ring1_segments_into_active_gdt() {
     crate::serial_println!("[GDT PATCH]   SERVICE_DATA at {:#x}", service_data_ptr as u64);
 }
 
-/// Get a mutable reference to the TSS
+/// Get a mutable reference to this CPU's TSS
 ///
 /// # Safety
 /// Must only be called after init()
@@ -480,7 +495,7 @@ pub unsafe fn get_tss_mut() -> &'static mut TaskStateSegment {
     if !GDT_INITIALIZED {
         panic!("GDT/TSS not initialized!");
     }
-    TSS.assume_init_mut()
+    TSS[crate::attunement::smp::current_cpu()].assume_init_mut()
 }
 
 /// Update the kernel stack pointer in the TSS and per-CPU data
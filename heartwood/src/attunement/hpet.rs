@@ -0,0 +1,134 @@
+//! # HPET - The High Precision Event Timer
+//!
+//! A free-running counter shared by every processor, usually at 10-25 MHz,
+//! found through the ACPI `HPET` table. It does not drift with the CPU's
+//! clock speed, which makes it the reference the TSC and the Local APIC
+//! timer are measured against, and a clock source in its own right when
+//! the TSC cannot be trusted.
+//!
+//! Only the main counter is used; its comparators are left disabled.
+
+use super::acpi;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+/// Offset of the base address within the table: the header, the event
+/// timer block ID, then a Generic Address Structure whose address is at
+/// byte 4
+const BASE_ADDRESS_OFFSET: usize = acpi::SDT_HEADER_SIZE + 4 + 4;
+
+/// Register offsets
+const REG_CAPABILITIES: u64 = 0x000;
+const REG_CONFIG: u64 = 0x010;
+const REG_MAIN_COUNTER: u64 = 0x0F0;
+
+/// General capabilities: the main counter is 64 bits wide
+const CAP_COUNTER_64BIT: u64 = 1 << 13;
+
+/// General configuration: the main counter runs
+const CONFIG_ENABLE: u64 = 1 << 0;
+
+/// Femtoseconds in a second
+const FS_PER_SECOND: u64 = 1_000_000_000_000_000;
+
+/// Kernel virtual address of the registers (0 when there is no HPET)
+static HPET_BASE: AtomicU64 = AtomicU64::new(0);
+
+/// Counter period in femtoseconds
+static PERIOD_FS: AtomicU64 = AtomicU64::new(0);
+
+/// The physical address of the HPET's registers, from a whole `HPET` table
+pub fn parse_base_address(table: &[u8]) -> Option<u64> {
+    if table.len() < BASE_ADDRESS_OFFSET + 8 || &table[..4] != b"HPET" {
+        return None;
+    }
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&table[BASE_ADDRESS_OFFSET..BASE_ADDRESS_OFFSET + 8]);
+    let address = u64::from_le_bytes(bytes);
+    (address != 0).then_some(address)
+}
+
+/// Find, map and start the HPET
+///
+/// A counter only 32 bits wide wraps every few minutes, so it is not used.
+///
+/// # Returns
+///
+/// `false` if there is no usable HPET
+pub fn init() -> bool {
+    let Some(phys_addr) = acpi::find_table(b"HPET").and_then(parse_base_address) else {
+        return false;
+    };
+    let Ok(base) = (unsafe { crate::mana_pool::page_tables::map_mmio(phys_addr, 0x400) }) else {
+        return false;
+    };
+
+    let capabilities = read(base, REG_CAPABILITIES);
+    let period_fs = capabilities >> 32;
+    if capabilities & CAP_COUNTER_64BIT == 0 || period_fs == 0 {
+        return false;
+    }
+
+    let config = read(base, REG_CONFIG);
+    write(base, REG_CONFIG, config | CONFIG_ENABLE);
+
+    PERIOD_FS.store(period_fs, Ordering::Relaxed);
+    HPET_BASE.store(base, Ordering::Release);
+    true
+}
+
+/// Whether the HPET is running
+pub fn is_available() -> bool {
+    HPET_BASE.load(Ordering::Acquire) != 0
+}
+
+/// The main counter
+pub fn counter() -> u64 {
+    read(HPET_BASE.load(Ordering::Acquire), REG_MAIN_COUNTER)
+}
+
+/// The main counter's rate in Hz
+pub fn frequency() -> u64 {
+    match PERIOD_FS.load(Ordering::Relaxed) {
+        0 => 0,
+        period_fs => FS_PER_SECOND / period_fs,
+    }
+}
+
+fn read(base: u64, reg: u64) -> u64 {
+    unsafe { core::ptr::read_volatile((base + reg) as *const u64) }
+}
+
+fn write(base: u64, reg: u64, value: u64) {
+    unsafe { core::ptr::write_volatile((base + reg) as *mut u64, value) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn table_with_address(address: u64) -> Vec<u8> {
+        let mut table = Vec::new();
+        table.extend_from_slice(b"HPET");
+        table.resize(acpi::SDT_HEADER_SIZE, 0);
+        table.extend_from_slice(&0x8086_A201u32.to_le_bytes());
+        table.extend_from_slice(&[0, 64, 0, 0]);
+        table.extend_from_slice(&address.to_le_bytes());
+        table.extend_from_slice(&[0, 0x80, 0, 0]);
+        table
+    }
+
+    #[test]
+    fn test_parse_base_address() {
+        assert_eq!(parse_base_address(&table_with_address(0xFED0_0000)), Some(0xFED0_0000));
+    }
+
+    #[test]
+    fn test_rejects_other_tables() {
+        let mut table = table_with_address(0xFED0_0000);
+        table[..4].copy_from_slice(b"APIC");
+        assert_eq!(parse_base_address(&table), None);
+        assert_eq!(parse_base_address(&table_with_address(0)), None);
+        assert_eq!(parse_base_address(b"HPET"), None);
+    }
+}
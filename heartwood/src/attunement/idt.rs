@@ -33,9 +33,29 @@ pub fn init() {
         // The Timer Spell - IRQ 0 = Interrupt 32
         idt[32].set_handler_fn(timer_interrupt_handler);
 
+        // The remaining lines (but the cascade) go to whichever driver Grove
+        // has bound a Nexus notification to them
+        let bindable: [(u8, extern "x86-interrupt" fn(InterruptStackFrame)); 13] = [
+            (3, irq3_handler), (4, irq4_handler), (5, irq5_handler), (6, irq6_handler),
+            (7, irq7_handler), (8, irq8_handler), (9, irq9_handler), (10, irq10_handler),
+            (11, irq11_handler), (12, irq12_handler), (13, irq13_handler), (14, irq14_handler),
+            (15, irq15_handler),
+        ];
+        for (irq, handler) in bindable {
+            idt[(super::PIC_1_OFFSET + irq) as usize].set_handler_fn(handler);
+        }
+
         // The Page Fault Handler - Exception 14
         idt.page_fault.set_handler_fn(page_fault_handler);
 
+        // Inter-processor interrupts, and the Local APIC's spurious vector
+        idt[super::smp::RESCHEDULE_VECTOR as usize].set_handler_fn(reschedule_handler);
+        idt[super::smp::TLB_SHOOTDOWN_VECTOR as usize].set_handler_fn(tlb_shootdown_handler);
+        idt[super::apic::SPURIOUS_VECTOR as usize].set_handler_fn(spurious_interrupt_handler);
+
+        // Each processor's one-shot Local APIC timer
+        idt[super::apic::TIMER_VECTOR as usize].set_handler_fn(deadline_handler);
+
         // The Ring 1 System Call Gate - INT 0x81
         // Ring 1 services (Groves) use this to make kernel calls.
         // We use INT 0x81 instead of the syscall instruction because:
@@ -64,6 +84,16 @@ pub fn init() {
     }
 }
 
+/// Load the IDT on an application processor
+///
+/// Every processor shares the one table built by `init()`.
+///
+/// # Safety
+/// Must only be called after init()
+pub unsafe fn load() {
+    (*core::ptr::addr_of!(IDT)).assume_init_ref().load();
+}
+
 /// Get a reference to the IDT (for debugging/introspection)
 ///
 /// # Safety
@@ -80,13 +110,39 @@ extern "x86-interrupt" fn keyboard_interrupt_handler(_stack_frame: InterruptStac
     // Call the keyboard driver's interrupt handler
     crate::attunement::keyboard::on_interrupt();
 
-    // CRITICAL: Send End of Interrupt to the PIC
+    // CRITICAL: Send End of Interrupt
     // Without this, no more keyboard interrupts will fire!
-    unsafe {
-        super::PICS.lock().notify_end_of_interrupt(33);
-    }
+    super::end_of_interrupt(1);
 }
 
+/// The Bindable IRQ Handlers - Spells Lent to the Groves
+///
+/// Raise the Nexus notification bound to the line (if any), then send the
+/// End of Interrupt. The Grove acknowledges the device itself once woken.
+macro_rules! bindable_irq_handler {
+    ($name:ident, $irq:expr) => {
+        extern "x86-interrupt" fn $name(_stack_frame: InterruptStackFrame) {
+            crate::nexus::irq_raised($irq);
+
+            super::end_of_interrupt($irq);
+        }
+    };
+}
+
+bindable_irq_handler!(irq3_handler, 3);
+bindable_irq_handler!(irq4_handler, 4);
+bindable_irq_handler!(irq5_handler, 5);
+bindable_irq_handler!(irq6_handler, 6);
+bindable_irq_handler!(irq7_handler, 7);
+bindable_irq_handler!(irq8_handler, 8);
+bindable_irq_handler!(irq9_handler, 9);
+bindable_irq_handler!(irq10_handler, 10);
+bindable_irq_handler!(irq11_handler, 11);
+bindable_irq_handler!(irq12_handler, 12);
+bindable_irq_handler!(irq13_handler, 13);
+bindable_irq_handler!(irq14_handler, 14);
+bindable_irq_handler!(irq15_handler, 15);
+
 /// The Ring 1 System Call Handler - Naked Wrapper
 ///
 /// This handles system calls from Ring 1 services (Groves) via INT 0x81.
@@ -208,7 +264,7 @@ unsafe extern "C" fn ring1_syscall_handler_rust(regs: *mut SavedRegisters) -> i6
 /// Saved register state from syscall
 ///
 /// This struct mirrors the order registers are pushed onto the stack
-/// in the naked syscall handler.
+/// in the naked syscall handlers and the page fault wrapper.
 #[repr(C)]
 struct SavedRegisters {
     r15: u64,
@@ -338,13 +394,30 @@ unsafe extern "C" fn syscall_handler_rust(regs: *mut SavedRegisters) -> i64 {
 
 /// The Timer Interrupt Handler - The Rhythm of Time
 ///
-/// This handler is called on every timer tick (typically 1ms).
-/// It increments the tick counter and, if preemptive multitasking is enabled,
-/// tracks quantum usage and triggers context switches.
+/// This handler is called on every PIT beat (10ms) when the system is not
+/// tickless. It advances the beat counter and, if preemptive multitasking
+/// is enabled, tracks quantum usage and triggers context switches.
 extern "x86-interrupt" fn timer_interrupt_handler(_stack_frame: InterruptStackFrame) {
-    // Increment the timer tick counter
-    crate::attunement::timer::tick();
+    // Advance the beat counter and wake any sleepers that are due
+    crate::attunement::timer::on_tick();
+    consider_preemption();
 
+    // Send End of Interrupt (only if we didn't preempt)
+    super::end_of_interrupt(0);
+}
+
+/// The Deadline Handler - this processor's one-shot Local APIC timer fired
+///
+/// On a tickless system this takes the place of the PIT beat: it wakes
+/// the sleepers that are due and arms the next deadline.
+extern "x86-interrupt" fn deadline_handler(_stack_frame: InterruptStackFrame) {
+    crate::attunement::timer::on_deadline();
+    consider_preemption();
+    super::apic::end_of_interrupt();
+}
+
+/// Check the interrupted thread's quantum, and switch away once it is spent
+fn consider_preemption() {
     // === PREEMPTIVE MULTITASKING (Phase 3) - TEMPORARILY DISABLED ===
     // TEMPORARY: Disable LOOM locking in timer to debug allocator deadlock
     // TODO: Re-enable after fixing the deadlock issue
@@ -372,80 +445,183 @@ extern "x86-interrupt" fn timer_interrupt_handler(_stack_frame: InterruptStackFr
             // For now, preemption is disabled (should_preempt = false above)
         }
     }
+}
 
-    // Send End of Interrupt (only if we didn't preempt)
-    unsafe {
-        super::PICS.lock().notify_end_of_interrupt(32);
-    }
+/// The Reschedule IPI - another processor queued work for this one
+///
+/// Returning from the interrupt wakes the idle thread from `hlt`, and it
+/// looks at its run queue again.
+extern "x86-interrupt" fn reschedule_handler(_stack_frame: InterruptStackFrame) {
+    crate::loom_of_fate::finish_switch();
+    super::apic::end_of_interrupt();
+}
+
+/// The TLB Shootdown IPI - another processor changed a mapping
+extern "x86-interrupt" fn tlb_shootdown_handler(_stack_frame: InterruptStackFrame) {
+    super::smp::service_shootdown();
+    super::apic::end_of_interrupt();
 }
 
-/// Page Fault Handler - MINIMAL VERSION
+/// The Local APIC's spurious interrupt, which must not be acknowledged
+extern "x86-interrupt" fn spurious_interrupt_handler(_stack_frame: InterruptStackFrame) {}
+
+/// Page Fault Handler - Naked Wrapper
 ///
-/// This is deliberately minimal to avoid causing cascading faults.
-/// We just output a marker and halt, so we can see if the handler runs at all.
+/// Saves every general-purpose register so that the Rust handler can either
+/// page in the faulting address and return, condemn the faulting Vessel, or
+/// print a complete register dump for a kernel fault.
+///
+/// # Why Naked?
+/// The `extern "x86-interrupt"` convention only exposes the interrupt frame.
+/// A kernel fault report is far more useful with the full register state.
+#[unsafe(naked)]
 extern "x86-interrupt" fn page_fault_handler(
     _stack_frame: InterruptStackFrame,
     _error_code: x86_64::structures::idt::PageFaultErrorCode,
 ) {
-    // Output [PF:addr] via direct port I/O ONLY (no stack, no heap, no formatting!)
-    unsafe {
-        // Read CR2 (faulting address)
-        let cr2: u64;
-        core::arch::asm!("mov {}, cr2", out(reg) cr2, options(nomem, nostack, preserves_flags));
-
-        // Helper to output a hex digit via port I/O
-        #[inline(always)]
-        fn out_hex_digit(digit: u8) {
-            let ch = if digit < 10 {
-                b'0' + digit
-            } else {
-                b'a' + (digit - 10)
-            };
-            unsafe {
-                core::arch::asm!(
-                    "out dx, al",
-                    in("dx") 0x3f8u16,
-                    in("al") ch,
-                    options(nomem, nostack, preserves_flags)
-                );
-            }
-        }
-
-        core::arch::asm!(
-            "mov dx, 0x3f8",
-            "mov al, '['",
-            "out dx, al",
-            "mov al, 'P'",
-            "out dx, al",
-            "mov al, 'F'",
-            "out dx, al",
-            "mov al, ':'",
-            "out dx, al",
-            "mov al, '0'",
-            "out dx, al",
-            "mov al, 'x'",
-            "out dx, al",
-            options(nostack, preserves_flags)
-        );
-
-        // Output CR2 in hex (64-bit value, 16 hex digits)
-        for i in (0..16).rev() {
-            let digit = ((cr2 >> (i * 4)) & 0xF) as u8;
-            out_hex_digit(digit);
-        }
+    core::arch::naked_asm!(
+        // At entry: CPU pushed interrupt frame (SS, RSP, RFLAGS, CS, RIP)
+        // followed by the error code
+
+        // Save ALL registers in order (must match SavedRegisters struct)
+        "push rbp",
+        "push rax",
+        "push rbx",
+        "push rcx",
+        "push rdx",
+        "push rsi",
+        "push rdi",
+        "push r8",
+        "push r9",
+        "push r10",
+        "push r11",
+        "push r12",
+        "push r13",
+        "push r14",
+        "push r15",
+
+        // RSP now points to PageFaultFrame
+        "mov rdi, rsp",
+
+        // Interrupt frame (40) + error code (8) + 15 registers (120) = 168 bytes
+        // from a 16-byte aligned base; realign before the call
+        "sub rsp, 8",
+        "call {handler}",
+        "add rsp, 8",
+
+        // Restore all registers
+        "pop r15",
+        "pop r14",
+        "pop r13",
+        "pop r12",
+        "pop r11",
+        "pop r10",
+        "pop r9",
+        "pop r8",
+        "pop rdi",
+        "pop rsi",
+        "pop rdx",
+        "pop rcx",
+        "pop rbx",
+        "pop rax",
+        "pop rbp",
+
+        // Discard the error code and retry the faulting instruction
+        "add rsp, 8",
+        "iretq",
+
+        handler = sym page_fault_handler_rust,
+    )
+}
 
-        core::arch::asm!(
-            "mov dx, 0x3f8",
-            "mov al, ']'",
-            "out dx, al",
-            options(nostack, preserves_flags)
-        );
-    }
+/// Everything on the stack when the page fault wrapper calls into Rust
+#[repr(C)]
+struct PageFaultFrame {
+    regs: SavedRegisters,
+    error_code: u64,
+    rip: u64,
+    cs: u64,
+    rflags: u64,
+    rsp: u64,
+    ss: u64,
+}
 
-    // Halt the CPU to prevent cascading faults
-    loop {
-        unsafe {
-            core::arch::asm!("hlt", options(nostack, nomem));
+/// The actual page fault handler (called from naked wrapper)
+///
+/// - Faults on demand-paged stack/heap pages are resolved and return.
+/// - Other faults in mortal code condemn the Vessel, which never returns.
+/// - Bad mortal addresses hit by the Ward's copy routine resume at its
+///   fixup, so the copy (and the system call behind it) fails with EFAULT.
+/// - Any other fault in the Heartwood dumps the registers and panics.
+///
+/// # Safety
+/// Must only be called from page_fault_handler with a valid pointer
+/// to a PageFaultFrame on the stack.
+unsafe extern "C" fn page_fault_handler_rust(frame: *mut PageFaultFrame) {
+    use crate::attunement::ward_of_sacred_boundaries::{fault_fixup, is_mortal_pointer};
+    use x86_64::structures::idt::PageFaultErrorCode;
+
+    let frame = &mut *frame;
+
+    let cr2: u64;
+    core::arch::asm!("mov {}, cr2", out(reg) cr2, options(nomem, nostack, preserves_flags));
+
+    let error = PageFaultErrorCode::from_bits_truncate(frame.error_code);
+    let from_kernel = frame.cs & 0x3 == 0;
+
+    // The kernel only touches mortal memory through the Ward's copy helpers,
+    // which run without the Loom or Harbor held, so it is safe to take them
+    // here. Kernel faults elsewhere (e.g. a null dereference) go straight to
+    // the dump without touching any lock.
+    if !from_kernel || is_mortal_pointer(cr2) {
+        match crate::loom_of_fate::handle_user_page_fault(x86_64::VirtAddr::new_truncate(cr2), error) {
+            Ok(()) => return,
+            Err(reason) if !from_kernel => {
+                if let Some(vessel_id) = crate::loom_of_fate::current_vessel_id() {
+                    crate::loom_of_fate::condemn_vessel(
+                        vessel_id,
+                        crate::loom_of_fate::VesselFault {
+                            address: cr2,
+                            instruction: frame.rip,
+                            reason,
+                        },
+                    );
+
+                    // This thread is Fading and will never be scheduled again
+                    crate::loom_of_fate::yield_now();
+                    loop {
+                        core::arch::asm!("hlt", options(nostack, nomem));
+                    }
+                }
+            }
+            Err(_) => {
+                if let Some(fixup) = fault_fixup(frame.rip) {
+                    frame.rip = fixup;
+                    return;
+                }
+            }
         }
     }
+
+    dump_fault_frame(frame, cr2, error);
+    panic!("Page fault in the Heartwood at {:#x} (rip {:#x})", cr2, frame.rip);
+}
+
+/// Print the full register state of an unrecoverable page fault
+fn dump_fault_frame(
+    frame: &PageFaultFrame,
+    cr2: u64,
+    error: x86_64::structures::idt::PageFaultErrorCode,
+) {
+    let r = &frame.regs;
+
+    crate::serial_println!("❖❖❖ DISHARMONY: Page Fault ❖❖❖");
+    crate::serial_println!("  CR2={:#018x}  error={:?}", cr2, error);
+    crate::serial_println!("  RIP={:#018x}  CS={:#06x}  RFLAGS={:#018x}", frame.rip, frame.cs, frame.rflags);
+    crate::serial_println!("  RSP={:#018x}  SS={:#06x}", frame.rsp, frame.ss);
+    crate::serial_println!("  RAX={:#018x}  RBX={:#018x}  RCX={:#018x}", r.rax, r.rbx, r.rcx);
+    crate::serial_println!("  RDX={:#018x}  RSI={:#018x}  RDI={:#018x}", r.rdx, r.rsi, r.rdi);
+    crate::serial_println!("  RBP={:#018x}  R8 ={:#018x}  R9 ={:#018x}", r.rbp, r.r8, r.r9);
+    crate::serial_println!("  R10={:#018x}  R11={:#018x}  R12={:#018x}", r.r10, r.r11, r.r12);
+    crate::serial_println!("  R13={:#018x}  R14={:#018x}  R15={:#018x}", r.r13, r.r14, r.r15);
 }
@@ -0,0 +1,159 @@
+//! # I/O APIC - Where Device Lines Are Routed
+//!
+//! The I/O APIC replaces the 8259 PICs: each of its pins (a global system
+//! interrupt, GSI) has a redirection entry naming the vector to raise and
+//! the processor to raise it on. ISA IRQs map to the GSI of the same
+//! number unless the MADT declares an override (IRQ 0 usually arrives on
+//! GSI 2).
+//!
+//! Registers are reached indirectly: write the register index to IOREGSEL,
+//! then read or write IOWIN.
+
+use super::madt::Madt;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, Ordering};
+use spin::Mutex;
+
+/// Indirect access registers
+const IOREGSEL: u64 = 0x00;
+const IOWIN: u64 = 0x10;
+
+/// Register indices
+const REG_VERSION: u32 = 0x01;
+const REG_REDIRECTION_BASE: u32 = 0x10;
+
+/// Redirection entry fields
+const ENTRY_ACTIVE_LOW: u64 = 1 << 13;
+const ENTRY_LEVEL_TRIGGERED: u64 = 1 << 15;
+const ENTRY_MASKED: u64 = 1 << 16;
+
+/// One mapped I/O APIC
+struct IoApic {
+    base: u64,
+    gsi_base: u32,
+    pins: u32,
+}
+
+impl IoApic {
+    fn read(&self, reg: u32) -> u32 {
+        unsafe {
+            core::ptr::write_volatile((self.base + IOREGSEL) as *mut u32, reg);
+            core::ptr::read_volatile((self.base + IOWIN) as *const u32)
+        }
+    }
+
+    fn write(&self, reg: u32, value: u32) {
+        unsafe {
+            core::ptr::write_volatile((self.base + IOREGSEL) as *mut u32, reg);
+            core::ptr::write_volatile((self.base + IOWIN) as *mut u32, value);
+        }
+    }
+
+    fn write_entry(&self, pin: u32, entry: u64) {
+        let reg = REG_REDIRECTION_BASE + pin * 2;
+        // Mask first, so the half-written entry never fires
+        self.write(reg, ENTRY_MASKED as u32);
+        self.write(reg + 1, (entry >> 32) as u32);
+        self.write(reg, entry as u32);
+    }
+
+    fn serves(&self, gsi: u32) -> bool {
+        gsi >= self.gsi_base && gsi < self.gsi_base + self.pins
+    }
+}
+
+/// The mapped I/O APICs and the MADT's ISA overrides
+struct Routing {
+    io_apics: Vec<IoApic>,
+    madt: Madt,
+}
+
+static ROUTING: Mutex<Option<Routing>> = Mutex::new(None);
+
+/// Set once `ROUTING` is filled in; read on every End of Interrupt
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Map every I/O APIC in the MADT and mask all of their pins
+///
+/// # Returns
+///
+/// The number of I/O APICs found
+pub fn init(madt: &Madt) -> usize {
+    let mut io_apics = Vec::new();
+
+    for entry in &madt.io_apics {
+        let Ok(base) = (unsafe { crate::mana_pool::page_tables::map_mmio(entry.address, 0x20) }) else {
+            continue;
+        };
+        let mut io_apic = IoApic { base, gsi_base: entry.gsi_base, pins: 0 };
+        io_apic.pins = ((io_apic.read(REG_VERSION) >> 16) & 0xFF) + 1;
+        for pin in 0..io_apic.pins {
+            io_apic.write_entry(pin, ENTRY_MASKED);
+        }
+        io_apics.push(io_apic);
+    }
+
+    let count = io_apics.len();
+    if count > 0 {
+        *ROUTING.lock() = Some(Routing { io_apics, madt: madt.clone() });
+        ACTIVE.store(true, Ordering::Release);
+    }
+    count
+}
+
+/// Whether device interrupts are routed through an I/O APIC
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::Acquire)
+}
+
+/// Route an ISA IRQ to `vector` on the processor with `apic_id`
+///
+/// The MADT's override, if any, decides the GSI, polarity and trigger
+/// mode; otherwise the line is GSI `irq`, active-high and edge-triggered.
+///
+/// # Returns
+///
+/// `false` if no I/O APIC serves the line
+pub fn route_irq(irq: u8, vector: u8, apic_id: u8) -> bool {
+    let routing = ROUTING.lock();
+    let Some(routing) = routing.as_ref() else {
+        return false;
+    };
+
+    let mut entry = vector as u64 | (apic_id as u64) << 56;
+    if let Some(source) = routing.madt.source_override(irq) {
+        if source.active_low() {
+            entry |= ENTRY_ACTIVE_LOW;
+        }
+        if source.level_triggered() {
+            entry |= ENTRY_LEVEL_TRIGGERED;
+        }
+    }
+    routing.write_irq_entry(irq, entry)
+}
+
+/// Stop an ISA IRQ from reaching any processor
+///
+/// # Returns
+///
+/// `false` if no I/O APIC serves the line
+pub fn mask_irq(irq: u8) -> bool {
+    match ROUTING.lock().as_ref() {
+        Some(routing) => routing.write_irq_entry(irq, ENTRY_MASKED),
+        None => false,
+    }
+}
+
+impl Routing {
+    /// Write the redirection entry of the pin an ISA IRQ arrives on
+    fn write_irq_entry(&self, irq: u8, entry: u64) -> bool {
+        let gsi = self.madt.source_override(irq).map_or(irq as u32, |source| source.gsi);
+        match self.io_apics.iter().find(|io_apic| io_apic.serves(gsi)) {
+            Some(io_apic) => {
+                io_apic.write_entry(gsi - io_apic.gsi_base, entry);
+                true
+            }
+            None => false,
+        }
+    }
+}
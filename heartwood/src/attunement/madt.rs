@@ -0,0 +1,202 @@
+//! # MADT - The Census of Processors
+//!
+//! The Multiple APIC Description Table (signature `APIC`) lists every
+//! processor's Local APIC, every I/O APIC and the places where the ISA
+//! interrupt lines are wired differently from their IRQ numbers.
+//!
+//! Layout: the common SDT header, the Local APIC physical address and a
+//! flags word, then variable-length entries of `(type, length, ...)`.
+
+use super::acpi;
+use alloc::vec::Vec;
+
+/// Offset of the first entry, after the header, LAPIC address and flags
+const ENTRIES_OFFSET: usize = acpi::SDT_HEADER_SIZE + 8;
+
+/// Entry types
+const ENTRY_LOCAL_APIC: u8 = 0;
+const ENTRY_IO_APIC: u8 = 1;
+const ENTRY_SOURCE_OVERRIDE: u8 = 2;
+const ENTRY_LAPIC_ADDRESS_OVERRIDE: u8 = 5;
+
+/// Local APIC flags
+const LAPIC_ENABLED: u32 = 1 << 0;
+const LAPIC_ONLINE_CAPABLE: u32 = 1 << 1;
+
+/// MADT flags: the machine also has dual 8259 PICs
+const PCAT_COMPAT: u32 = 1 << 0;
+
+/// A processor that can be brought online
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Processor {
+    pub acpi_id: u8,
+    pub apic_id: u8,
+}
+
+/// An I/O APIC and the first global system interrupt (GSI) it serves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IoApic {
+    pub id: u8,
+    pub address: u64,
+    pub gsi_base: u32,
+}
+
+/// An ISA IRQ that arrives on a different GSI, or with non-ISA polarity
+/// or trigger mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceOverride {
+    pub irq: u8,
+    pub gsi: u32,
+    /// MPS INTI flags: polarity in bits 0-1, trigger mode in bits 2-3
+    pub flags: u16,
+}
+
+impl SourceOverride {
+    /// Whether the line is active-low (ISA lines default to active-high)
+    pub fn active_low(&self) -> bool {
+        self.flags & 0b11 == 0b11
+    }
+
+    /// Whether the line is level-triggered (ISA lines default to edge)
+    pub fn level_triggered(&self) -> bool {
+        (self.flags >> 2) & 0b11 == 0b11
+    }
+}
+
+/// Everything the MADT says about interrupt delivery
+#[derive(Debug, Clone, Default)]
+pub struct Madt {
+    /// Physical address of every processor's Local APIC
+    pub lapic_address: u64,
+    /// Whether 8259 PICs are present and must be masked to use the APICs
+    pub has_8259: bool,
+    pub processors: Vec<Processor>,
+    pub io_apics: Vec<IoApic>,
+    pub overrides: Vec<SourceOverride>,
+}
+
+impl Madt {
+    /// Find and parse the firmware's MADT
+    pub fn find() -> Option<Self> {
+        acpi::find_table(b"APIC").and_then(Self::parse)
+    }
+
+    /// Parse a whole MADT, header included
+    pub fn parse(table: &[u8]) -> Option<Self> {
+        if table.len() < ENTRIES_OFFSET || &table[..4] != b"APIC" {
+            return None;
+        }
+
+        let mut madt = Madt {
+            lapic_address: read_u32(table, acpi::SDT_HEADER_SIZE) as u64,
+            has_8259: read_u32(table, acpi::SDT_HEADER_SIZE + 4) & PCAT_COMPAT != 0,
+            ..Madt::default()
+        };
+
+        let mut offset = ENTRIES_OFFSET;
+        while offset + 2 <= table.len() {
+            let kind = table[offset];
+            let length = table[offset + 1] as usize;
+            if length < 2 || offset + length > table.len() {
+                break;
+            }
+            let entry = &table[offset..offset + length];
+
+            match kind {
+                ENTRY_LOCAL_APIC if length >= 8 => {
+                    let flags = read_u32(entry, 4);
+                    if flags & (LAPIC_ENABLED | LAPIC_ONLINE_CAPABLE) != 0 {
+                        madt.processors.push(Processor { acpi_id: entry[2], apic_id: entry[3] });
+                    }
+                }
+                ENTRY_IO_APIC if length >= 12 => {
+                    madt.io_apics.push(IoApic {
+                        id: entry[2],
+                        address: read_u32(entry, 4) as u64,
+                        gsi_base: read_u32(entry, 8),
+                    });
+                }
+                ENTRY_SOURCE_OVERRIDE if length >= 10 => {
+                    madt.overrides.push(SourceOverride {
+                        irq: entry[3],
+                        gsi: read_u32(entry, 4),
+                        flags: u16::from_le_bytes([entry[8], entry[9]]),
+                    });
+                }
+                ENTRY_LAPIC_ADDRESS_OVERRIDE if length >= 12 => {
+                    madt.lapic_address = read_u32(entry, 4) as u64 | (read_u32(entry, 8) as u64) << 32;
+                }
+                _ => {}
+            }
+            offset += length;
+        }
+
+        Some(madt)
+    }
+
+    /// The override for an ISA IRQ, if the firmware declared one
+    pub fn source_override(&self, irq: u8) -> Option<&SourceOverride> {
+        self.overrides.iter().find(|o| o.irq == irq)
+    }
+}
+
+fn read_u32(data: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A QEMU-like MADT: two processors (one disabled), one I/O APIC and
+    /// the IRQ 0 -> GSI 2 override
+    fn sample_table() -> Vec<u8> {
+        let mut table = Vec::new();
+        table.extend_from_slice(b"APIC");
+        table.resize(acpi::SDT_HEADER_SIZE, 0);
+        table.extend_from_slice(&0xFEE0_0000u32.to_le_bytes());
+        table.extend_from_slice(&PCAT_COMPAT.to_le_bytes());
+
+        table.extend_from_slice(&[ENTRY_LOCAL_APIC, 8, 0, 0, 1, 0, 0, 0]);
+        table.extend_from_slice(&[ENTRY_LOCAL_APIC, 8, 1, 1, 1, 0, 0, 0]);
+        table.extend_from_slice(&[ENTRY_LOCAL_APIC, 8, 2, 2, 0, 0, 0, 0]);
+        table.extend_from_slice(&[ENTRY_IO_APIC, 12, 0, 0]);
+        table.extend_from_slice(&0xFEC0_0000u32.to_le_bytes());
+        table.extend_from_slice(&0u32.to_le_bytes());
+        table.extend_from_slice(&[ENTRY_SOURCE_OVERRIDE, 10, 0, 0]);
+        table.extend_from_slice(&2u32.to_le_bytes());
+        table.extend_from_slice(&0u16.to_le_bytes());
+        table
+    }
+
+    #[test]
+    fn test_parse_madt() {
+        let madt = Madt::parse(&sample_table()).unwrap();
+        assert_eq!(madt.lapic_address, 0xFEE0_0000);
+        assert!(madt.has_8259);
+        assert_eq!(madt.processors.len(), 2);
+        assert_eq!(madt.processors[1], Processor { acpi_id: 1, apic_id: 1 });
+        assert_eq!(madt.io_apics, [IoApic { id: 0, address: 0xFEC0_0000, gsi_base: 0 }]);
+        assert_eq!(madt.source_override(0).map(|o| o.gsi), Some(2));
+        assert!(madt.source_override(1).is_none());
+    }
+
+    #[test]
+    fn test_override_flags() {
+        let level_low = SourceOverride { irq: 9, gsi: 9, flags: 0b1111 };
+        assert!(level_low.active_low());
+        assert!(level_low.level_triggered());
+
+        let conforming = SourceOverride { irq: 0, gsi: 2, flags: 0 };
+        assert!(!conforming.active_low());
+        assert!(!conforming.level_triggered());
+    }
+
+    #[test]
+    fn test_truncated_entry_stops_parsing() {
+        let mut table = sample_table();
+        table.extend_from_slice(&[ENTRY_LOCAL_APIC, 8, 3]);
+        let madt = Madt::parse(&table).unwrap();
+        assert_eq!(madt.processors.len(), 2);
+    }
+}
@@ -4,15 +4,30 @@
 //! 1. The Guardian (PIC) - Using pic8259 for proper remapping
 //! 2. The Law (IDT) - Using x86_64 for proper interrupt handling
 //! 3. The Spell (Handler) - Simple, clean interrupt processing
+//!
+//! When the firmware's MADT lists an I/O APIC, the 8259s are masked and
+//! device lines are routed through it instead, and every other processor
+//! is woken (see `smp`). Time then comes from the best clock source found,
+//! with one-shot Local APIC deadlines in place of a periodic tick (see
+//! `timer`).
 
 pub mod gdt;
 pub mod idt;
 pub mod keyboard;
 pub mod timer;
+pub mod pit;
+pub mod hpet;
+pub mod clocksource;
 pub mod ward_of_sacred_boundaries;
 pub mod ward_of_unseen_paths;
 pub mod ward_of_anonymity;
 pub mod per_cpu;
+pub mod acpi;
+pub mod pci;
+pub mod madt;
+pub mod apic;
+pub mod ioapic;
+pub mod smp;
 
 // Export TSS kernel stack update function for context switching
 pub use gdt::set_kernel_stack;
@@ -31,6 +46,85 @@ pub const PIC_2_OFFSET: u8 = PIC_1_OFFSET + 8;
 pub static PICS: InterruptSafeLock<ChainedPics> =
     InterruptSafeLock::new(unsafe { ChainedPics::new(PIC_1_OFFSET, PIC_2_OFFSET) }, "PICS");
 
+/// Let a hardware IRQ line (0-15) through
+///
+/// With an I/O APIC the line is routed to the bootstrap processor.
+/// Otherwise it is unmasked at the PIC; lines on the secondary PIC also
+/// need the cascade (IRQ 2) open.
+pub fn unmask_irq(irq: u8) {
+    if ioapic::is_active() {
+        ioapic::route_irq(irq, PIC_1_OFFSET + irq, smp::apic_id(0));
+        return;
+    }
+
+    let mut pics = PICS.lock();
+    unsafe {
+        let [mut primary, mut secondary] = pics.read_masks();
+        if irq < 8 {
+            primary &= !(1 << irq);
+        } else {
+            primary &= !(1 << 2);
+            secondary &= !(1 << (irq - 8));
+        }
+        pics.write_masks(primary, secondary);
+    }
+}
+
+/// Stop a hardware IRQ line (0-15)
+pub fn mask_irq(irq: u8) {
+    if ioapic::is_active() {
+        ioapic::mask_irq(irq);
+        return;
+    }
+
+    let mut pics = PICS.lock();
+    unsafe {
+        let [mut primary, mut secondary] = pics.read_masks();
+        if irq < 8 {
+            primary |= 1 << irq;
+        } else {
+            secondary |= 1 << (irq - 8);
+        }
+        pics.write_masks(primary, secondary);
+    }
+}
+
+/// Signal End of Interrupt for a hardware IRQ line (0-15)
+pub fn end_of_interrupt(irq: u8) {
+    if ioapic::is_active() {
+        apic::end_of_interrupt();
+    } else {
+        unsafe {
+            PICS.lock().notify_end_of_interrupt(PIC_1_OFFSET + irq);
+        }
+    }
+}
+
+/// Move interrupt delivery to the APICs and wake the other processors
+///
+/// Runs after the IDT is loaded, with interrupts still disabled.
+fn init_apic(madt: &madt::Madt) {
+    if let Err(e) = unsafe { apic::map(madt.lapic_address) } {
+        crate::println!("     ⚠ Local APIC unreachable: {}", e);
+        return;
+    }
+    let io_apics = ioapic::init(madt);
+    apic::enable();
+
+    if io_apics > 0 {
+        // The 8259s fall silent; the keyboard now comes through the I/O
+        // APIC to this processor (the PIT too, if the timer needs it)
+        unsafe {
+            PICS.lock().write_masks(0xFF, 0xFF);
+        }
+        ioapic::route_irq(1, PIC_1_OFFSET + 1, apic::id());
+        crate::println!("     ✓ Device lines routed through {} I/O APIC(s)", io_apics);
+    }
+
+    let cpus = smp::start_aps(madt);
+    crate::println!("     ✓ {} processor(s) awake", cpus);
+}
+
 /// Initialize the Attunement Layer
 /// This follows The Grand Unification sequence
 pub fn init() {
@@ -105,6 +199,19 @@ pub fn init() {
             }
         }
 
+        // Quest 3.5: Hear every processor (Local APIC, I/O APIC and SMP)
+        crate::println!("  ⟡ Quest 3.5: Awakening the other processors (APIC & SMP)...");
+        match madt::Madt::find() {
+            Some(madt) => init_apic(&madt),
+            None => crate::println!("     ⚠ No MADT; one processor, interrupts through the PIC"),
+        }
+
+        // Quest 3.6: Attune to the flow of time (clock source and timers)
+        crate::println!("  ⟡ Quest 3.6: Attuning to the flow of time (clock source & timers)...");
+        timer::init();
+        crate::println!("     ✓ Time flows from the {} ({})", clocksource::current().name(),
+            if timer::is_tickless() { "tickless, Local APIC deadlines" } else { "PIT heartbeat" });
+
         // Quest 4: Initialize keyboard state (no PS/2 commands, trust BIOS)
         crate::println!("  ⟡ Quest 4: Preparing keyboard state...");
         unsafe {
@@ -119,6 +226,12 @@ pub fn init() {
             }
         }
 
+        // Quest 5: Trace the roots (PCI enumeration)
+        crate::println!("  ⟡ Quest 5: Tracing the roots (PCI enumeration)...");
+        let found = pci::init();
+        crate::println!("     ✓ {} device functions found ({})", found,
+            if pci::uses_ecam() { "ECAM" } else { "legacy port I/O" });
+
         // Final Step: Open the gates (enable interrupts)
         crate::println!("  ⟡ Opening the gates to the outside world...");
         unsafe {
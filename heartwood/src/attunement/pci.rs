@@ -0,0 +1,507 @@
+//! # PCI - The Roots Beneath the Board
+//!
+//! Every function of every PCI device answers at a (bus, device, function)
+//! address with a configuration space describing what it is, where its
+//! registers (BARs) live and which interrupt line it raises.
+//!
+//! Configuration space is reached through ECAM (memory-mapped, found in
+//! the ACPI MCFG table) when the firmware provides it, and through the
+//! legacy I/O ports (0xCF8 address, 0xCFC data) otherwise.
+//!
+//! Enumeration starts at the host bridge and follows PCI-to-PCI bridges
+//! to their secondary buses, so the device list is a tree: each device
+//! records the bridge it sits behind. Drivers register a match table and
+//! are offered every device they match.
+
+use crate::attunement::acpi;
+use alloc::vec::Vec;
+use spin::Mutex;
+use x86_64::instructions::port::Port;
+
+/// CONFIG_ADDRESS register
+const CONFIG_ADDRESS: u16 = 0xCF8;
+
+/// CONFIG_DATA register
+const CONFIG_DATA: u16 = 0xCFC;
+
+/// Configuration space register offsets
+const REG_VENDOR_ID: u16 = 0x00;
+const REG_COMMAND: u16 = 0x04;
+const REG_CLASS: u16 = 0x08;
+const REG_HEADER_TYPE: u16 = 0x0C;
+const REG_BAR0: u16 = 0x10;
+const REG_BUS_NUMBERS: u16 = 0x18;
+const REG_INTERRUPT: u16 = 0x3C;
+
+/// Command register bits
+const COMMAND_IO_SPACE: u16 = 1 << 0;
+const COMMAND_MEMORY_SPACE: u16 = 1 << 1;
+const COMMAND_BUS_MASTER: u16 = 1 << 2;
+
+/// Header types (low 7 bits of the header type register)
+const HEADER_GENERAL: u8 = 0x00;
+const HEADER_PCI_BRIDGE: u8 = 0x01;
+const HEADER_MULTI_FUNCTION: u8 = 0x80;
+
+/// Vendor ID read back from an empty slot
+const NO_DEVICE: u16 = 0xFFFF;
+
+/// ECAM maps 4KB of configuration space per function, 1MB per bus
+const ECAM_BUS_SIZE: u64 = 1 << 20;
+
+/// Offset of the first allocation entry in the MCFG table
+const MCFG_ENTRIES_OFFSET: usize = acpi::SDT_HEADER_SIZE + 8;
+const MCFG_ENTRY_SIZE: usize = 16;
+
+/// A base address register, decoded and sized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bar {
+    /// Not implemented, or the upper half of a 64-bit BAR
+    Unused,
+    Memory { address: u64, size: u64, prefetchable: bool },
+    Io { port: u32, size: u32 },
+}
+
+/// A PCI function, identified by its configuration space address
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PciDevice {
+    pub bus: u8,
+    pub device: u8,
+    pub function: u8,
+    pub vendor_id: u16,
+    pub device_id: u16,
+    pub class: u8,
+    pub subclass: u8,
+    pub prog_if: u8,
+    pub revision: u8,
+    pub header_type: u8,
+    pub bars: [Bar; 6],
+    pub interrupt_line: u8,
+    /// 0 = none, 1-4 = INTA#-INTD#
+    pub interrupt_pin: u8,
+    /// Bus behind this device, if it is a PCI-to-PCI bridge
+    pub secondary_bus: Option<u8>,
+    /// Index (in [`devices`]) of the bridge this device sits behind
+    pub parent: Option<usize>,
+    /// Name of the driver that claimed this device
+    pub driver: Option<&'static str>,
+}
+
+impl PciDevice {
+    /// Read a 32-bit configuration register (offset must be 4-byte aligned)
+    pub fn read_config(&self, offset: u16) -> u32 {
+        read_config(self.bus, self.device, self.function, offset)
+    }
+
+    /// Write a 32-bit configuration register (offset must be 4-byte aligned)
+    pub fn write_config(&self, offset: u16, value: u32) {
+        write_config(self.bus, self.device, self.function, offset, value)
+    }
+
+    /// Physical address of a memory BAR
+    ///
+    /// Returns `None` for I/O BARs and unassigned BARs.
+    pub fn memory_bar(&self, index: u8) -> Option<u64> {
+        match self.bars.get(index as usize)? {
+            Bar::Memory { address, .. } if *address != 0 => Some(*address),
+            _ => None,
+        }
+    }
+
+    /// Let the device decode its memory BARs and master the bus (DMA)
+    pub fn enable_bus_mastering(&self) {
+        let value = self.read_config(REG_COMMAND);
+        let command = value as u16 | COMMAND_MEMORY_SPACE | COMMAND_BUS_MASTER;
+        // The upper half is the status register, whose bits are write-1-to-clear
+        self.write_config(REG_COMMAND, command as u32);
+    }
+
+    /// Human-readable name of the device's class
+    pub fn class_name(&self) -> &'static str {
+        class_name(self.class, self.subclass, self.prog_if)
+    }
+}
+
+/// How a driver recognises its devices
+#[derive(Debug, Clone, Copy)]
+pub enum PciMatch {
+    /// A specific vendor and device ID
+    Device(u16, u16),
+    /// A class, subclass and programming interface
+    Class(u8, u8, u8),
+}
+
+impl PciMatch {
+    fn matches(&self, dev: &PciDevice) -> bool {
+        match *self {
+            PciMatch::Device(vendor, device) => (dev.vendor_id, dev.device_id) == (vendor, device),
+            PciMatch::Class(class, subclass, prog_if) => {
+                (dev.class, dev.subclass, dev.prog_if) == (class, subclass, prog_if)
+            }
+        }
+    }
+}
+
+/// A driver for PCI devices
+pub struct PciDriver {
+    pub name: &'static str,
+    pub matches: &'static [PciMatch],
+    /// Take over a matching device; returns whether the driver claimed it
+    pub probe: fn(&PciDevice) -> bool,
+}
+
+/// ECAM region covering segment 0
+struct Ecam {
+    base: u64,
+    start_bus: u8,
+    end_bus: u8,
+    /// Virtual address of each bus's configuration space, once mapped
+    mapped: [Option<u64>; 256],
+}
+
+/// The ECAM region, if any (also serialises legacy port access)
+static CONFIG_SPACE: Mutex<Option<Ecam>> = Mutex::new(None);
+
+/// Enumerated devices (`None` until the first scan)
+static DEVICES: Mutex<Option<Vec<PciDevice>>> = Mutex::new(None);
+
+/// Registered drivers
+static DRIVERS: Mutex<Vec<&'static PciDriver>> = Mutex::new(Vec::new());
+
+/// Discover the configuration mechanism and enumerate every device
+///
+/// # Returns
+///
+/// The number of functions found
+pub fn init() -> usize {
+    if let Some(ecam) = find_ecam() {
+        *CONFIG_SPACE.lock() = Some(ecam);
+    }
+
+    let devices = enumerate();
+    let count = devices.len();
+    *DEVICES.lock() = Some(devices);
+    count
+}
+
+/// Whether configuration space is reached through ECAM
+pub fn uses_ecam() -> bool {
+    CONFIG_SPACE.lock().is_some()
+}
+
+/// Snapshot of every enumerated device, in tree (depth-first) order
+pub fn devices() -> Vec<PciDevice> {
+    ensure_enumerated();
+    DEVICES.lock().clone().unwrap_or_default()
+}
+
+/// Find the first function with the given class code
+pub fn find_by_class(class: u8, subclass: u8, prog_if: u8) -> Option<PciDevice> {
+    devices()
+        .into_iter()
+        .find(|dev| (dev.class, dev.subclass, dev.prog_if) == (class, subclass, prog_if))
+}
+
+/// Register a driver and offer it every unclaimed device it matches
+///
+/// # Returns
+///
+/// The number of devices the driver claimed
+pub fn register_driver(driver: &'static PciDriver) -> usize {
+    ensure_enumerated();
+    DRIVERS.lock().push(driver);
+
+    // Probe without holding the device list; drivers may look at it
+    let candidates: Vec<(usize, PciDevice)> = DEVICES
+        .lock()
+        .iter()
+        .flatten()
+        .enumerate()
+        .filter(|(_, dev)| dev.driver.is_none() && driver.matches.iter().any(|m| m.matches(dev)))
+        .map(|(index, dev)| (index, *dev))
+        .collect();
+
+    let mut claimed = 0;
+    for (index, dev) in candidates {
+        if (driver.probe)(&dev) {
+            if let Some(entry) = DEVICES.lock().as_mut().and_then(|list| list.get_mut(index)) {
+                entry.driver = Some(driver.name);
+            }
+            claimed += 1;
+        }
+    }
+    claimed
+}
+
+/// Names of the registered drivers
+pub fn drivers() -> Vec<&'static str> {
+    DRIVERS.lock().iter().map(|driver| driver.name).collect()
+}
+
+fn ensure_enumerated() {
+    if DEVICES.lock().is_none() {
+        init();
+    }
+}
+
+/// Find the ECAM region for segment 0 in the MCFG table
+fn find_ecam() -> Option<Ecam> {
+    let mcfg = acpi::find_table(b"MCFG")?;
+
+    mcfg.get(MCFG_ENTRIES_OFFSET..)?
+        .chunks_exact(MCFG_ENTRY_SIZE)
+        .find(|entry| u16::from_le_bytes([entry[8], entry[9]]) == 0)
+        .map(|entry| Ecam {
+            base: u64::from_le_bytes(entry[0..8].try_into().unwrap()),
+            start_bus: entry[10],
+            end_bus: entry[11],
+            mapped: [None; 256],
+        })
+}
+
+fn config_address(bus: u8, device: u8, function: u8, offset: u16) -> u32 {
+    (1 << 31)
+        | ((bus as u32) << 16)
+        | ((device as u32 & 0x1F) << 11)
+        | ((function as u32 & 0x7) << 8)
+        | (offset as u32 & 0xFC)
+}
+
+/// Virtual address of a register through ECAM, mapping its bus on first use
+fn ecam_register(ecam: &mut Ecam, bus: u8, device: u8, function: u8, offset: u16) -> Option<u64> {
+    if bus < ecam.start_bus || bus > ecam.end_bus {
+        return None;
+    }
+
+    let base = match ecam.mapped[bus as usize] {
+        Some(base) => base,
+        None => {
+            let phys = ecam.base + (bus - ecam.start_bus) as u64 * ECAM_BUS_SIZE;
+            let base = unsafe { crate::mana_pool::page_tables::map_mmio(phys, ECAM_BUS_SIZE).ok()? };
+            ecam.mapped[bus as usize] = Some(base);
+            base
+        }
+    };
+
+    let function_offset = ((device as u64 & 0x1F) << 15) | ((function as u64 & 0x7) << 12);
+    Some(base + function_offset + (offset as u64 & 0xFFC))
+}
+
+fn read_config(bus: u8, device: u8, function: u8, offset: u16) -> u32 {
+    let mut config_space = CONFIG_SPACE.lock();
+
+    if let Some(ecam) = config_space.as_mut() {
+        if let Some(register) = ecam_register(ecam, bus, device, function, offset) {
+            return unsafe { core::ptr::read_volatile(register as *const u32) };
+        }
+    }
+
+    // The legacy mechanism only reaches the first 256 bytes
+    if offset >= 0x100 {
+        return u32::MAX;
+    }
+    let mut address = Port::<u32>::new(CONFIG_ADDRESS);
+    let mut data = Port::<u32>::new(CONFIG_DATA);
+    unsafe {
+        address.write(config_address(bus, device, function, offset));
+        data.read()
+    }
+}
+
+fn write_config(bus: u8, device: u8, function: u8, offset: u16, value: u32) {
+    let mut config_space = CONFIG_SPACE.lock();
+
+    if let Some(ecam) = config_space.as_mut() {
+        if let Some(register) = ecam_register(ecam, bus, device, function, offset) {
+            unsafe { core::ptr::write_volatile(register as *mut u32, value) };
+            return;
+        }
+    }
+
+    if offset >= 0x100 {
+        return;
+    }
+    let mut address = Port::<u32>::new(CONFIG_ADDRESS);
+    let mut data = Port::<u32>::new(CONFIG_DATA);
+    unsafe {
+        address.write(config_address(bus, device, function, offset));
+        data.write(value);
+    }
+}
+
+fn header_type(bus: u8, device: u8, function: u8) -> u8 {
+    (read_config(bus, device, function, REG_HEADER_TYPE) >> 16) as u8
+}
+
+/// Walk the tree from the host bridge(s)
+fn enumerate() -> Vec<PciDevice> {
+    let mut devices = Vec::new();
+    let mut visited = [false; 256];
+
+    // A multi-function host bridge has one host controller (and root
+    // bus) per function
+    if header_type(0, 0, 0) & HEADER_MULTI_FUNCTION == 0 {
+        scan_bus(0, None, &mut visited, &mut devices);
+    } else {
+        for function in 0..8 {
+            if read_config(0, 0, function, REG_VENDOR_ID) as u16 != NO_DEVICE {
+                scan_bus(function, None, &mut visited, &mut devices);
+            }
+        }
+    }
+
+    devices
+}
+
+fn scan_bus(bus: u8, parent: Option<usize>, visited: &mut [bool; 256], devices: &mut Vec<PciDevice>) {
+    if visited[bus as usize] {
+        return;
+    }
+    visited[bus as usize] = true;
+
+    for device in 0..32u8 {
+        if read_config(bus, device, 0, REG_VENDOR_ID) as u16 == NO_DEVICE {
+            continue;
+        }
+
+        let functions = if header_type(bus, device, 0) & HEADER_MULTI_FUNCTION != 0 { 8 } else { 1 };
+        for function in 0..functions {
+            let Some(dev) = probe(bus, device, function, parent) else {
+                continue;
+            };
+
+            let index = devices.len();
+            devices.push(dev);
+            if let Some(secondary) = dev.secondary_bus {
+                scan_bus(secondary, Some(index), visited, devices);
+            }
+        }
+    }
+}
+
+/// Read the identity, BARs and interrupt routing of one function
+fn probe(bus: u8, device: u8, function: u8, parent: Option<usize>) -> Option<PciDevice> {
+    let ids = read_config(bus, device, function, REG_VENDOR_ID);
+    let vendor_id = ids as u16;
+    if vendor_id == NO_DEVICE {
+        return None;
+    }
+
+    let class = read_config(bus, device, function, REG_CLASS);
+    let header_type = header_type(bus, device, function);
+    let interrupt = read_config(bus, device, function, REG_INTERRUPT);
+
+    let (bar_count, secondary_bus) = match header_type & !HEADER_MULTI_FUNCTION {
+        HEADER_GENERAL => (6, None),
+        HEADER_PCI_BRIDGE => {
+            let secondary = (read_config(bus, device, function, REG_BUS_NUMBERS) >> 8) as u8;
+            (2, (secondary != 0).then_some(secondary))
+        }
+        _ => (0, None),
+    };
+
+    Some(PciDevice {
+        bus,
+        device,
+        function,
+        vendor_id,
+        device_id: (ids >> 16) as u16,
+        class: (class >> 24) as u8,
+        subclass: (class >> 16) as u8,
+        prog_if: (class >> 8) as u8,
+        revision: class as u8,
+        header_type,
+        bars: read_bars(bus, device, function, bar_count),
+        interrupt_line: interrupt as u8,
+        interrupt_pin: (interrupt >> 8) as u8,
+        secondary_bus,
+        parent,
+        driver: None,
+    })
+}
+
+/// Decode and size the first `count` BARs
+///
+/// Sizing writes all ones and reads back which address bits stick, so
+/// decoding is switched off meanwhile.
+fn read_bars(bus: u8, device: u8, function: u8, count: usize) -> [Bar; 6] {
+    let mut bars = [Bar::Unused; 6];
+
+    let command = read_config(bus, device, function, REG_COMMAND);
+    let decode = (COMMAND_IO_SPACE | COMMAND_MEMORY_SPACE) as u32;
+    write_config(bus, device, function, REG_COMMAND, command & 0xFFFF & !decode);
+
+    let mut index = 0;
+    while index < count {
+        let offset = REG_BAR0 + index as u16 * 4;
+        let original = read_config(bus, device, function, offset);
+        write_config(bus, device, function, offset, u32::MAX);
+        let mask = read_config(bus, device, function, offset);
+        write_config(bus, device, function, offset, original);
+
+        if original & 1 != 0 {
+            let size_mask = mask & !0x3;
+            if size_mask != 0 {
+                bars[index] = Bar::Io { port: original & !0x3, size: (!size_mask).wrapping_add(1) };
+            }
+            index += 1;
+            continue;
+        }
+
+        let is_64bit = (original >> 1) & 0b11 == 0b10 && index + 1 < count;
+        let mut address = (original & !0xF) as u64;
+        let mut size_mask = (mask & !0xF) as u64;
+
+        if is_64bit {
+            let high_offset = offset + 4;
+            let high = read_config(bus, device, function, high_offset);
+            write_config(bus, device, function, high_offset, u32::MAX);
+            let high_mask = read_config(bus, device, function, high_offset);
+            write_config(bus, device, function, high_offset, high);
+
+            address |= (high as u64) << 32;
+            size_mask |= (high_mask as u64) << 32;
+        } else if size_mask != 0 {
+            size_mask |= 0xFFFF_FFFF_0000_0000;
+        }
+
+        if size_mask != 0 {
+            bars[index] = Bar::Memory {
+                address,
+                size: (!size_mask).wrapping_add(1),
+                prefetchable: original & 0x8 != 0,
+            };
+        }
+        index += if is_64bit { 2 } else { 1 };
+    }
+
+    write_config(bus, device, function, REG_COMMAND, command & 0xFFFF);
+    bars
+}
+
+/// Name the common class codes
+fn class_name(class: u8, subclass: u8, prog_if: u8) -> &'static str {
+    match (class, subclass, prog_if) {
+        (0x01, 0x01, _) => "IDE controller",
+        (0x01, 0x06, 0x01) => "SATA controller (AHCI)",
+        (0x01, 0x06, _) => "SATA controller",
+        (0x01, 0x08, 0x02) => "NVMe controller",
+        (0x01, _, _) => "Mass storage controller",
+        (0x02, 0x00, _) => "Ethernet controller",
+        (0x02, _, _) => "Network controller",
+        (0x03, 0x00, _) => "VGA controller",
+        (0x03, _, _) => "Display controller",
+        (0x04, _, _) => "Multimedia controller",
+        (0x05, _, _) => "Memory controller",
+        (0x06, 0x00, _) => "Host bridge",
+        (0x06, 0x01, _) => "ISA bridge",
+        (0x06, 0x04, _) => "PCI-to-PCI bridge",
+        (0x06, _, _) => "Bridge",
+        (0x07, _, _) => "Communication controller",
+        (0x08, _, _) => "System peripheral",
+        (0x0C, 0x03, _) => "USB controller",
+        (0x0C, 0x05, _) => "SMBus controller",
+        (0x0C, _, _) => "Serial bus controller",
+        _ => "Unknown device",
+    }
+}
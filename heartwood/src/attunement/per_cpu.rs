@@ -101,10 +101,14 @@ impl PerCpuData {
     }
 }
 
-/// Static storage for per-CPU data (single CPU for now)
+/// Most processors the Heartwood will bring online
+pub const MAX_CPUS: usize = 16;
+
+/// Static storage for per-CPU data, indexed by CPU ID
 ///
-/// TODO: When SMP support is added, this will be an array indexed by CPU ID
-static mut BSP_CPU_DATA: PerCpuData = PerCpuData::new(0, 0);
+/// CPU 0 is the bootstrap processor; application processors take the
+/// following IDs in the order they come online.
+static mut CPU_DATA: [PerCpuData; MAX_CPUS] = [const { PerCpuData::new(0, 0) }; MAX_CPUS];
 
 /// Dedicated kernel stack for syscall handling on BSP (64KB, statically allocated)
 ///
@@ -136,15 +140,7 @@ pub unsafe fn init_bsp() {
     let kernel_stack_top = kernel_stack_bottom + kernel_stack_size as u64;
 
     // Initialize BSP per-CPU data with the dedicated kernel stack
-    BSP_CPU_DATA = PerCpuData::new(0, kernel_stack_top);
-    BSP_CPU_DATA.init_self_ptr();
-
-    // Set GSBASE to point to BSP data
-    let addr = VirtAddr::new(&BSP_CPU_DATA as *const _ as u64);
-    GsBase::write(addr);
-
-    // Set KERNEL_GSBASE to 0 initially (will be set to user GS during context switch)
-    KernelGsBase::write(VirtAddr::new(0));
+    let addr = install(0, kernel_stack_top);
 
     crate::serial_println!("[PER_CPU] ✓ Bootstrap processor per-CPU data initialized");
     crate::serial_println!("[PER_CPU]   GS base: {:#x}", addr.as_u64());
@@ -153,6 +149,33 @@ pub unsafe fn init_bsp() {
     crate::serial_println!("[PER_CPU]   ✓ Dedicated syscall stack configured (static allocation)");
 }
 
+/// Initialize per-CPU data for an application processor
+///
+/// Points the calling processor's GS register at slot `cpu_id`. Unlike the
+/// BSP's, the syscall stack is allocated by the caller.
+///
+/// # Safety
+///
+/// Must be called exactly once per application processor, on that
+/// processor, with a `cpu_id` no other processor uses.
+pub unsafe fn init_ap(cpu_id: u32, kernel_stack_top: u64) {
+    install(cpu_id, kernel_stack_top);
+}
+
+/// Fill in a CPU's slot and point this processor's GS base at it
+unsafe fn install(cpu_id: u32, kernel_stack_top: u64) -> VirtAddr {
+    let slot = &mut *core::ptr::addr_of_mut!(CPU_DATA[cpu_id as usize]);
+    *slot = PerCpuData::new(cpu_id, kernel_stack_top);
+    slot.init_self_ptr();
+
+    let addr = VirtAddr::new(slot as *const _ as u64);
+    GsBase::write(addr);
+
+    // Set KERNEL_GSBASE to 0 initially (will be set to user GS during context switch)
+    KernelGsBase::write(VirtAddr::new(0));
+    addr
+}
+
 /// Get a reference to the current CPU's data
 ///
 /// # Safety
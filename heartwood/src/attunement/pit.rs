@@ -21,10 +21,14 @@ use core::arch::asm;
 
 /// PIT I/O ports
 const PIT_CHANNEL0: u16 = 0x40; // Channel 0 data port (system timer)
+#[allow(dead_code)]
 const PIT_CHANNEL1: u16 = 0x41; // Channel 1 data port (unused)
 const PIT_CHANNEL2: u16 = 0x42; // Channel 2 data port (PC speaker)
 const PIT_COMMAND: u16 = 0x43;  // Mode/Command register
 
+/// Channel 2 gate (bit 0), speaker enable (bit 1) and output (bit 5)
+const PORT_SPEAKER_GATE: u16 = 0x61;
+
 /// PIT base frequency (Hz)
 const PIT_BASE_FREQ: u32 = 1193182;
 
@@ -95,19 +99,17 @@ impl Pit {
     /// # Safety
     /// This function writes to I/O ports and should only be called once during boot.
     pub unsafe fn initialize(&self) {
-        // Disable interrupts during PIT programming
-        asm!("cli", options(nomem, nostack));
-
-        // Send command byte: Channel 0, lobyte/hibyte, mode 3, binary
-        let command = CMD_CHANNEL0 | CMD_ACCESS_LOHI | CMD_MODE3 | CMD_BINARY;
-        outb(PIT_COMMAND, command);
-
-        // Send divisor (low byte, then high byte)
-        outb(PIT_CHANNEL0, (self.divisor & 0xFF) as u8);
-        outb(PIT_CHANNEL0, ((self.divisor >> 8) & 0xFF) as u8);
-
-        // Re-enable interrupts
-        asm!("sti", options(nomem, nostack));
+        // Keep interrupts off during PIT programming, without turning them
+        // on early if the caller has them off
+        x86_64::instructions::interrupts::without_interrupts(|| {
+            // Send command byte: Channel 0, lobyte/hibyte, mode 3, binary
+            let command = CMD_CHANNEL0 | CMD_ACCESS_LOHI | CMD_MODE3 | CMD_BINARY;
+            outb(PIT_COMMAND, command);
+
+            // Send divisor (low byte, then high byte)
+            outb(PIT_CHANNEL0, (self.divisor & 0xFF) as u8);
+            outb(PIT_CHANNEL0, ((self.divisor >> 8) & 0xFF) as u8);
+        });
     }
 
     /// Get the configured frequency in Hz
@@ -141,6 +143,33 @@ impl Pit {
     }
 }
 
+/// Busy-wait for `us` microseconds on channel 2
+///
+/// Channel 2 is gated through port 0x61 and its output can be read back
+/// there, so it works as a one-shot stopwatch that leaves the system timer
+/// (channel 0) alone. Used before any better clock is calibrated.
+pub fn delay_us(us: u64) {
+    let mut remaining = (us * PIT_BASE_FREQ as u64).div_ceil(1_000_000);
+    while remaining > 0 {
+        let count = remaining.min(0xFFFF);
+        unsafe {
+            // Gate channel 2 on, with the speaker off
+            let value = inb(PORT_SPEAKER_GATE);
+            outb(PORT_SPEAKER_GATE, (value & !0x02) | 0x01);
+
+            // Channel 2, low then high byte, mode 0 (output rises at zero)
+            outb(PIT_COMMAND, 0b10_11_000_0);
+            outb(PIT_CHANNEL2, count as u8);
+            outb(PIT_CHANNEL2, (count >> 8) as u8);
+
+            while inb(PORT_SPEAKER_GATE) & 0x20 == 0 {
+                core::hint::spin_loop();
+            }
+        }
+        remaining -= count;
+    }
+}
+
 /// Calculate milliseconds per tick for the current PIT frequency
 pub fn ms_per_tick(pit: &Pit) -> u32 {
     1000 / pit.frequency()
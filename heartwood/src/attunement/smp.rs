@@ -0,0 +1,449 @@
+//! # SMP - The Awakening of the Other Processors
+//!
+//! At power-on only the bootstrap processor (BSP) runs. The application
+//! processors (APs) wait for an INIT-SIPI-SIPI sequence from its Local
+//! APIC, and a Startup IPI starts them in real mode at a page below 1MB.
+//! A small trampoline is copied there: it climbs through protected mode
+//! into long mode on a temporary page table, then jumps to `ap_entry` in
+//! the higher half, which gives the processor its own GDT, TSS, GS area
+//! and idle thread.
+//!
+//! CPU numbers are dense (0 is the BSP) and index every per-CPU array.
+//! APIC IDs may have gaps, so they are translated through a table.
+//!
+//! Once more than one processor is online, unmapping a page must also
+//! flush it from the other processors' TLBs. A shootdown IPI asks each of
+//! them to invalidate it, and the sender waits until all have. The same IPI
+//! retires a dead Vessel's page table, moving any processor still holding it
+//! in CR3 onto the kernel's own.
+
+use super::apic;
+use super::madt::Madt;
+use super::per_cpu::MAX_CPUS;
+use core::arch::{asm, global_asm};
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, Ordering};
+
+/// Vector of the IPI that makes an idle processor look at its run queue
+pub const RESCHEDULE_VECTOR: u8 = 0xF0;
+
+/// Vector of the IPI that asks a processor to flush TLB entries
+pub const TLB_SHOOTDOWN_VECTOR: u8 = 0xF1;
+
+/// Physical address the trampoline is copied to (page 8, the SIPI vector)
+const TRAMPOLINE_PHYS: u64 = 0x8000;
+
+/// Where the first 1GB of physical memory is mapped
+const KERNEL_BASE: u64 = 0xFFFF_FFFF_8000_0000;
+
+/// The parameter block the trampoline reads, at the end of its page
+/// (the assembly below uses the absolute addresses 0x8F00-0x8F18)
+const PARAM_CR3: u64 = 0xF00;
+const PARAM_STACK: u64 = 0xF08;
+const PARAM_ENTRY: u64 = 0xF10;
+const PARAM_CPU: u64 = 0xF18;
+
+/// How long an AP gets to report in before bring-up stops
+const AP_STARTUP_TIMEOUT_US: u64 = 100_000;
+
+/// Shootdown address meaning "flush everything"
+const FLUSH_ALL: u64 = u64::MAX;
+
+/// CPU number of each APIC ID
+static CPU_BY_APIC_ID: [AtomicU8; 256] = [const { AtomicU8::new(0) }; 256];
+
+/// APIC ID of each CPU number
+static APIC_ID_BY_CPU: [AtomicU8; MAX_CPUS] = [const { AtomicU8::new(0) }; MAX_CPUS];
+
+/// Bit per CPU that has finished `ap_entry` (the BSP is always online)
+static ONLINE: AtomicU32 = AtomicU32::new(1);
+
+/// Set once the BSP has handed off to its idle thread; APs wait for it
+static RELEASED: AtomicBool = AtomicBool::new(false);
+
+/// The BSP's control registers, which every AP adopts
+static KERNEL_CR0: AtomicU64 = AtomicU64::new(0);
+static KERNEL_CR3: AtomicU64 = AtomicU64::new(0);
+static KERNEL_CR4: AtomicU64 = AtomicU64::new(0);
+
+/// Top of each AP's system call stack
+static SYSCALL_STACK_TOPS: [AtomicU64; MAX_CPUS] = [const { AtomicU64::new(0) }; MAX_CPUS];
+
+/// Held by the one processor with a shootdown in flight
+static SHOOTDOWN_LOCK: AtomicBool = AtomicBool::new(false);
+/// The page being shot down, or `FLUSH_ALL`
+static SHOOTDOWN_ADDRESS: AtomicU64 = AtomicU64::new(0);
+/// The page table being retired, or 0
+static SHOOTDOWN_RETIRED: AtomicU64 = AtomicU64::new(0);
+/// Bit per CPU that has yet to flush
+static SHOOTDOWN_PENDING: AtomicU32 = AtomicU32::new(0);
+
+// The AP trampoline, assembled into the kernel and copied to TRAMPOLINE_PHYS
+//
+// It is position-dependent: every address is computed relative to
+// ap_trampoline_start and rebased onto TRAMPOLINE_PHYS.
+global_asm!(
+    ".pushsection .text.ap_trampoline, \"ax\"",
+    ".global ap_trampoline_start",
+    ".global ap_trampoline_end",
+    ".code16",
+    "ap_trampoline_start:",
+    "    cli",
+    "    cld",
+    "    xor ax, ax",
+    "    mov ds, ax",
+    // lgdt [ap_gdt_pointer] (the assembler won't take a symbol difference
+    // as a memory operand)
+    "    .byte 0x0F, 0x01, 0x16",
+    "    .word ap_gdt_pointer - ap_trampoline_start + 0x8000",
+    "    mov eax, cr0",
+    "    or eax, 1",
+    "    mov cr0, eax",
+    // jmp dword 0x08:ap_protected_mode
+    "    .byte 0x66, 0xEA",
+    "    .long ap_protected_mode - ap_trampoline_start + 0x8000",
+    "    .word 0x08",
+
+    ".code32",
+    "ap_protected_mode:",
+    "    mov ax, 0x10",
+    "    mov ds, ax",
+    "    mov es, ax",
+    "    mov ss, ax",
+    // PAE, OSFXSR and OSXMMEXCPT
+    "    mov eax, cr4",
+    "    or eax, 0x620",
+    "    mov cr4, eax",
+    "    mov eax, dword ptr [0x8F00]",
+    "    mov cr3, eax",
+    // EFER.LME and EFER.NXE
+    "    mov ecx, 0xC0000080",
+    "    rdmsr",
+    "    or eax, 0x900",
+    "    wrmsr",
+    // Paging on, FPU emulation off, monitor coprocessor on
+    "    mov eax, cr0",
+    "    and eax, 0xFFFFFFFB",
+    "    or eax, 0x80000002",
+    "    mov cr0, eax",
+    // jmp 0x18:ap_long_mode
+    "    .byte 0xEA",
+    "    .long ap_long_mode - ap_trampoline_start + 0x8000",
+    "    .word 0x18",
+
+    ".code64",
+    "ap_long_mode:",
+    "    mov ax, 0x10",
+    "    mov ds, ax",
+    "    mov es, ax",
+    "    mov ss, ax",
+    "    mov rsp, qword ptr [0x8F08]",
+    "    mov rdi, qword ptr [0x8F18]",
+    "    mov rax, qword ptr [0x8F10]",
+    "    jmp rax",
+
+    ".balign 8",
+    "ap_gdt:",
+    "    .quad 0",
+    "    .quad 0x00CF9A000000FFFF",
+    "    .quad 0x00CF92000000FFFF",
+    "    .quad 0x00AF9A000000FFFF",
+    "ap_gdt_end:",
+    "ap_gdt_pointer:",
+    "    .word ap_gdt_end - ap_gdt - 1",
+    "    .long ap_gdt - ap_trampoline_start + 0x8000",
+    "ap_trampoline_end:",
+    ".code64",
+    ".popsection",
+);
+
+extern "C" {
+    static ap_trampoline_start: u8;
+    static ap_trampoline_end: u8;
+}
+
+/// This processor's CPU number
+///
+/// Taken from the Local APIC ID rather than GS, which still holds the user
+/// value in an interrupt that arrived from ring 3. Always 0 before the
+/// Local APIC is mapped.
+pub fn current_cpu() -> usize {
+    if !apic::is_mapped() {
+        return 0;
+    }
+    CPU_BY_APIC_ID[apic::id() as usize].load(Ordering::Relaxed) as usize
+}
+
+/// Number of processors online, the BSP included
+pub fn online_cpus() -> usize {
+    ONLINE.load(Ordering::Acquire).count_ones() as usize
+}
+
+/// Whether `cpu` has come online
+pub fn is_online(cpu: usize) -> bool {
+    cpu < MAX_CPUS && ONLINE.load(Ordering::Acquire) & (1 << cpu) != 0
+}
+
+/// The Local APIC ID of `cpu`
+pub fn apic_id(cpu: usize) -> u8 {
+    APIC_ID_BY_CPU[cpu].load(Ordering::Relaxed)
+}
+
+/// Wake every application processor the MADT lists
+///
+/// The APs set themselves up and then wait for `release_aps`. Bring-up
+/// stops at the first AP that does not answer, so CPU numbers stay dense.
+///
+/// Must run on the BSP, after the Loom is initialized and the Local APIC
+/// is enabled, and before the Rune of Permanence is sealed (each AP writes
+/// its own GDT and TSS there).
+///
+/// # Returns
+/// The number of processors online, the BSP included
+pub fn start_aps(madt: &Madt) -> usize {
+    let bsp_apic_id = apic::id();
+    CPU_BY_APIC_ID[bsp_apic_id as usize].store(0, Ordering::Relaxed);
+    APIC_ID_BY_CPU[0].store(bsp_apic_id, Ordering::Relaxed);
+
+    // Recorded even with a single processor, for `retire_page_table`
+    unsafe { save_control_registers() };
+
+    if madt.processors.iter().all(|p| p.apic_id == bsp_apic_id) {
+        return online_cpus();
+    }
+
+    let boot_cr3 = match unsafe { crate::mana_pool::page_tables::ap_boot_page_table() } {
+        Ok(cr3) => cr3,
+        Err(e) => {
+            crate::println!("     ⚠ No page table for the other processors: {}", e);
+            return online_cpus();
+        }
+    };
+
+    unsafe {
+        install_trampoline();
+        write_param(PARAM_CR3, boot_cr3);
+        let entry: extern "C" fn(u64) -> ! = ap_entry;
+        write_param(PARAM_ENTRY, entry as usize as u64);
+    }
+
+    let mut next_cpu = 1;
+    for processor in madt.processors.iter().filter(|p| p.apic_id != bsp_apic_id) {
+        if next_cpu == MAX_CPUS {
+            crate::println!("     ⚠ Only {} processors are supported", MAX_CPUS);
+            break;
+        }
+        if !start_ap(next_cpu, processor.apic_id) {
+            crate::println!("     ⚠ Processor with APIC ID {} did not answer", processor.apic_id);
+            break;
+        }
+        next_cpu += 1;
+    }
+
+    online_cpus()
+}
+
+/// Let the waiting APs switch to their idle threads and start scheduling
+///
+/// Called by the BSP's idle thread once the system has awakened.
+pub fn release_aps() {
+    RELEASED.store(true, Ordering::Release);
+}
+
+/// Send a reschedule IPI, so an idle `cpu` notices new work
+pub fn kick(cpu: usize) {
+    if is_online(cpu) && cpu != current_cpu() {
+        apic::send_ipi(apic_id(cpu), RESCHEDULE_VECTOR);
+    }
+}
+
+/// Flush `address` from every online processor's TLB
+///
+/// Returns once every processor has flushed, so the page's frame can be
+/// reused safely.
+pub fn shootdown_page(address: u64) {
+    shootdown(address & !0xFFF, 0);
+}
+
+/// Flush every processor's TLB
+pub fn shootdown_all() {
+    shootdown(FLUSH_ALL, 0);
+}
+
+/// Make every processor let go of a page table that is about to be freed
+///
+/// Kernel threads run on whatever page table was loaded before them, so a
+/// processor can keep a dead Vessel's PML4 in CR3 long after its last
+/// thread left. Each processor still holding `pml4_phys` loads the kernel's
+/// page table instead, and every TLB is flushed.
+///
+/// # Returns
+/// `false` if the kernel's page table was never recorded; `pml4_phys` may
+/// then still be in use and must not be freed
+pub fn retire_page_table(pml4_phys: u64) -> bool {
+    if KERNEL_CR3.load(Ordering::Relaxed) == 0 {
+        return false;
+    }
+    shootdown(FLUSH_ALL, pml4_phys);
+    true
+}
+
+/// Flush this processor's TLB if a shootdown is waiting on it
+///
+/// Called from the shootdown IPI, and by any processor spinning with
+/// interrupts disabled, so a sender never waits on a processor that is
+/// itself waiting on the sender.
+pub fn service_shootdown() {
+    let bit = 1 << current_cpu();
+    if SHOOTDOWN_PENDING.load(Ordering::Acquire) & bit == 0 {
+        return;
+    }
+    flush_local(SHOOTDOWN_ADDRESS.load(Ordering::Relaxed), SHOOTDOWN_RETIRED.load(Ordering::Relaxed));
+    SHOOTDOWN_PENDING.fetch_and(!bit, Ordering::Release);
+}
+
+fn shootdown(address: u64, retired: u64) {
+    flush_local(address, retired);
+
+    let me = current_cpu();
+    let others = ONLINE.load(Ordering::Acquire) & !(1 << me);
+    if others == 0 {
+        return;
+    }
+
+    while SHOOTDOWN_LOCK.swap(true, Ordering::Acquire) {
+        service_shootdown();
+        core::hint::spin_loop();
+    }
+
+    SHOOTDOWN_ADDRESS.store(address, Ordering::Relaxed);
+    SHOOTDOWN_RETIRED.store(retired, Ordering::Relaxed);
+    SHOOTDOWN_PENDING.store(others, Ordering::Release);
+    apic::broadcast_ipi(TLB_SHOOTDOWN_VECTOR);
+
+    while SHOOTDOWN_PENDING.load(Ordering::Acquire) != 0 {
+        core::hint::spin_loop();
+    }
+
+    SHOOTDOWN_LOCK.store(false, Ordering::Release);
+}
+
+fn flush_local(address: u64, retired: u64) {
+    if retired != 0 && current_cr3() == retired {
+        // Loading CR3 also flushes every non-global TLB entry
+        unsafe { asm!("mov cr3, {}", in(reg) KERNEL_CR3.load(Ordering::Relaxed), options(nostack)) };
+    } else if address == FLUSH_ALL {
+        crate::mana_pool::page_tables::flush_tlb();
+    } else {
+        x86_64::instructions::tlb::flush(x86_64::VirtAddr::new(address));
+    }
+}
+
+/// Bring one AP online as `cpu`
+///
+/// # Returns
+/// `false` if the AP did not report in
+fn start_ap(cpu: usize, apic_id: u8) -> bool {
+    // Both stacks live for the life of the system
+    let (Some(boot_stack), Some(syscall_stack)) =
+        (crate::loom_of_fate::stack::Stack::new(), crate::loom_of_fate::stack::Stack::new())
+    else {
+        return false;
+    };
+    SYSCALL_STACK_TOPS[cpu].store(syscall_stack.top(), Ordering::Relaxed);
+
+    CPU_BY_APIC_ID[apic_id as usize].store(cpu as u8, Ordering::Relaxed);
+    APIC_ID_BY_CPU[cpu].store(apic_id, Ordering::Relaxed);
+
+    unsafe {
+        write_param(PARAM_STACK, boot_stack.top());
+        write_param(PARAM_CPU, cpu as u64);
+    }
+    core::mem::forget(boot_stack);
+    core::mem::forget(syscall_stack);
+
+    // INIT, wait 10ms, then up to two Startup IPIs 200us apart
+    apic::send_init(apic_id);
+    super::pit::delay_us(10_000);
+    for _ in 0..2 {
+        apic::send_startup(apic_id, (TRAMPOLINE_PHYS >> 12) as u8);
+        super::pit::delay_us(200);
+        if is_online(cpu) {
+            return true;
+        }
+    }
+
+    let mut waited = 0;
+    while waited < AP_STARTUP_TIMEOUT_US {
+        if is_online(cpu) {
+            return true;
+        }
+        super::pit::delay_us(100);
+        waited += 100;
+    }
+    false
+}
+
+/// Where each AP arrives in the higher half, on its boot stack with
+/// interrupts disabled
+extern "C" fn ap_entry(cpu: u64) -> ! {
+    let cpu = cpu as usize;
+
+    unsafe {
+        // Leave the trampoline's page table and match the BSP's control registers
+        asm!("mov cr3, {}", in(reg) KERNEL_CR3.load(Ordering::Relaxed), options(nostack));
+        asm!("mov cr0, {}", in(reg) KERNEL_CR0.load(Ordering::Relaxed), options(nostack));
+        asm!("mov cr4, {}", in(reg) KERNEL_CR4.load(Ordering::Relaxed), options(nostack));
+        asm!("fninit", options(nomem, nostack));
+
+        super::gdt::init_ap(cpu);
+        super::idt::load();
+        super::per_cpu::init_ap(cpu as u32, SYSCALL_STACK_TOPS[cpu].load(Ordering::Relaxed));
+        crate::loom_of_fate::syscalls::init_syscall();
+    }
+    apic::enable();
+
+    let idle_context = unsafe { crate::loom_of_fate::prepare_secondary_handoff() };
+    ONLINE.fetch_or(1 << cpu, Ordering::Release);
+
+    // Wait for the BSP to finish booting, keeping this TLB coherent meanwhile
+    while !RELEASED.load(Ordering::Acquire) {
+        service_shootdown();
+        core::hint::spin_loop();
+    }
+
+    match idle_context {
+        Some(context) => unsafe { crate::loom_of_fate::context::context_switch_first(context) },
+        None => loop {
+            x86_64::instructions::hlt();
+        },
+    }
+}
+
+/// Record the BSP's CR0, CR3 and CR4 for the APs to load
+unsafe fn save_control_registers() {
+    let (cr0, cr3, cr4): (u64, u64, u64);
+    asm!("mov {}, cr0", out(reg) cr0, options(nomem, nostack, preserves_flags));
+    asm!("mov {}, cr3", out(reg) cr3, options(nomem, nostack, preserves_flags));
+    asm!("mov {}, cr4", out(reg) cr4, options(nomem, nostack, preserves_flags));
+    KERNEL_CR0.store(cr0, Ordering::Relaxed);
+    KERNEL_CR3.store(cr3, Ordering::Relaxed);
+    KERNEL_CR4.store(cr4, Ordering::Relaxed);
+}
+
+/// The page table this processor has loaded, without CR3's flag bits
+fn current_cr3() -> u64 {
+    let cr3: u64;
+    unsafe { asm!("mov {}, cr3", out(reg) cr3, options(nomem, nostack, preserves_flags)) };
+    cr3 & !0xFFF
+}
+
+/// Copy the trampoline to its page below 1MB
+unsafe fn install_trampoline() {
+    let start = core::ptr::addr_of!(ap_trampoline_start);
+    let length = core::ptr::addr_of!(ap_trampoline_end) as usize - start as usize;
+    core::ptr::copy_nonoverlapping(start, (KERNEL_BASE + TRAMPOLINE_PHYS) as *mut u8, length);
+}
+
+unsafe fn write_param(offset: u64, value: u64) {
+    core::ptr::write_volatile((KERNEL_BASE + TRAMPOLINE_PHYS + offset) as *mut u64, value);
+}
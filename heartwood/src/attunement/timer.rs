@@ -1,26 +1,121 @@
 //! # Timer - The Rhythm of Time
+//!
+//! Time is read from the clock source (see `clocksource`); sleeps and
+//! timeouts are counted in heartbeats of 10ms.
+//!
+//! With a calibrated Local APIC timer the system is tickless: each
+//! processor arms a one-shot deadline for the earliest sleeper, or the
+//! end of its thread's quantum, and hears nothing in between. Without one
+//! the PIT beats at 100 Hz and every beat checks the sleep queue.
 
-use core::sync::atomic::{AtomicU64, Ordering};
+use super::per_cpu::MAX_CPUS;
+use super::{apic, clocksource, pit, smp};
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 
+/// Length of a heartbeat, the unit of sleeps and timeouts
+pub const HEARTBEAT_NS: u64 = 10_000_000;
+
+/// How long the Local APIC timer is measured against the clock
+const CALIBRATION_NS: u64 = 10_000_000;
+
+/// Beats of the periodic PIT interrupt
 static TICKS: AtomicU64 = AtomicU64::new(0);
 
-/// Get the current tick count
+/// Whether deadlines are one-shot Local APIC timers instead of PIT beats
+static TICKLESS: AtomicBool = AtomicBool::new(false);
+
+/// The deadline each CPU's timer is armed for (`u64::MAX` when idle)
+static ARMED: [AtomicU64; MAX_CPUS] = [const { AtomicU64::new(u64::MAX) }; MAX_CPUS];
+
+/// Get the current time in heartbeats
 pub fn ticks() -> u64 {
+    clocksource::now_ns() / HEARTBEAT_NS
+}
+
+/// Get the number of periodic timer interrupts so far
+pub fn beats() -> u64 {
     TICKS.load(Ordering::Relaxed)
 }
 
-/// Increment the tick count (called from timer interrupt)
+/// Increment the beat count (called from timer interrupt)
 pub fn tick() {
     TICKS.fetch_add(1, Ordering::Relaxed);
 }
 
-/// Called on each timer tick from the interrupt handler
-/// This is where we can trigger the scheduler to switch tasks
+/// Whether the system is tickless
+pub fn is_tickless() -> bool {
+    TICKLESS.load(Ordering::Relaxed)
+}
+
+/// Choose the clock source and start the timer
+///
+/// Runs once, on the bootstrap processor, after the APICs are set up and
+/// with interrupts disabled. Going tickless needs both a Local APIC timer
+/// and a clock that runs without the PIT; otherwise the PIT is programmed
+/// and IRQ 0 let through.
+pub fn init() {
+    super::hpet::init();
+    clocksource::init();
+
+    let tickless = clocksource::is_precise() && apic::is_mapped() && apic::calibrate_timer(|| {
+        let start = clocksource::now_ns();
+        clocksource::delay_ns(CALIBRATION_NS);
+        clocksource::now_ns() - start
+    }) != 0;
+
+    if tickless {
+        TICKLESS.store(true, Ordering::Relaxed);
+        super::mask_irq(0);
+    } else {
+        unsafe {
+            pit::Pit::new().initialize();
+        }
+        super::unmask_irq(0);
+    }
+}
+
+/// Called on each PIT beat from the interrupt handler
+///
+/// Advances the beat count and wakes any sleepers whose time has come.
 pub fn on_tick() {
-    // Increment the global tick counter
+    // Increment the global beat counter
     tick();
 
-    // For now, we don't trigger scheduling from timer interrupts
-    // The threads yield cooperatively
-    // In a preemptive system, we would call the scheduler here
+    // Threads still yield cooperatively; the tick only drains the sleep queue
+    crate::loom_of_fate::wake_sleepers(ticks());
+}
+
+/// Called when this CPU's one-shot deadline fires
+///
+/// Wakes the sleepers that are due, then arms the timer for the next one.
+pub fn on_deadline() {
+    ARMED[smp::current_cpu()].store(u64::MAX, Ordering::Relaxed);
+    crate::loom_of_fate::wake_sleepers(ticks());
+    arm_next_wake();
+}
+
+/// Make sure this CPU's timer fires by `deadline_ns` (clock nanoseconds)
+///
+/// A timer already armed for an earlier deadline is left alone; that
+/// interrupt arms the next deadline in turn. Does nothing unless the
+/// system is tickless.
+pub fn request_deadline(deadline_ns: u64) {
+    if !is_tickless() {
+        return;
+    }
+    crate::loom_of_fate::without_interrupts(|| {
+        let armed = &ARMED[smp::current_cpu()];
+        if deadline_ns < armed.load(Ordering::Relaxed) {
+            armed.store(deadline_ns, Ordering::Relaxed);
+            apic::arm_timer(deadline_ns.saturating_sub(clocksource::now_ns()));
+        }
+    });
+}
+
+/// Make sure this CPU's timer fires when the earliest sleeper is due
+pub fn arm_next_wake() {
+    let next_wake = crate::loom_of_fate::scheduler::next_wake_tick();
+    if next_wake != u64::MAX {
+        request_deadline(next_wake.saturating_mul(HEARTBEAT_NS));
+    }
 }
@@ -24,10 +24,12 @@
 //! This module provides:
 //! - CPU feature detection and enablement (SMEP/SMAP in CR4)
 //! - Safe copy functions (`sanctified_copy_from_mortal`, `sanctified_copy_to_mortal`)
+//! - A fault fixup for those copies, so a bad mortal pointer fails the copy
+//!   instead of bringing down the Heartwood (`fault_fixup`)
 //! - User pointer validation (`is_mortal_pointer`, `validate_mortal_region`)
 //! - Compile-time enforcement via type system (`MortalPointer<T>`)
 
-use core::arch::asm;
+use core::arch::{asm, global_asm};
 use core::mem::size_of;
 
 /// CR4 bit for SMEP (Supervisor Mode Execution Prevention)
@@ -285,19 +287,8 @@ pub unsafe fn sanctified_copy_from_mortal<T: Copy>(
     mortal_ptr: &MortalPointer<T>,
     dest: &mut T,
 ) -> Result<(), WardError> {
-    // Temporarily disable SMAP to allow access (STAC instruction)
-    // This is the "sanctification ritual" - controlled access with intent
-    stac();
-
-    // Perform the copy
-    let src = mortal_ptr.addr() as *const T;
-    let result = core::ptr::read_volatile(src);
-    *dest = result;
-
-    // Re-enable SMAP protection (CLAC instruction)
-    clac();
-
-    Ok(())
+    // The "sanctification ritual" - controlled access with intent
+    mortal_copy(dest as *mut T as *mut u8, mortal_ptr.addr() as *const u8, size_of::<T>())
 }
 
 /// Sanctified copy to mortal lands (copy_to_user)
@@ -322,47 +313,67 @@ pub unsafe fn sanctified_copy_to_mortal<T: Copy>(
     src: &T,
     mortal_ptr: &MortalPointer<T>,
 ) -> Result<(), WardError> {
-    // Temporarily disable SMAP (STAC instruction)
-    stac();
-
-    // Perform the copy
-    let dest = mortal_ptr.addr() as *mut T;
-    core::ptr::write_volatile(dest, *src);
-
-    // Re-enable SMAP (CLAC instruction)
-    clac();
+    mortal_copy(mortal_ptr.addr() as *mut u8, src as *const T as *const u8, size_of::<T>())
+}
 
-    Ok(())
+// The one routine allowed to fault on a mortal address
+//
+// `ward_mortal_copy(dest, src, len)` copies `len` bytes with SMAP lifted
+// (STAC/CLAC) and returns how many it left uncopied. If `rep movsb` faults
+// on a page that can't be brought in, the page fault handler resumes at the
+// fixup, where RCX still counts the bytes not yet moved.
+global_asm!(
+    ".pushsection .text.ward_mortal_copy, \"ax\"",
+    ".global ward_mortal_copy",
+    ".global ward_mortal_copy_access",
+    ".global ward_mortal_copy_fixup",
+    "ward_mortal_copy:",
+    "    mov rcx, rdx",
+    "    stac",
+    "ward_mortal_copy_access:",
+    "    rep movsb",
+    "    clac",
+    "    mov rax, rcx",
+    "    ret",
+    "ward_mortal_copy_fixup:",
+    "    clac",
+    "    mov rax, rcx",
+    "    ret",
+    ".popsection",
+);
+
+extern "C" {
+    fn ward_mortal_copy(dest: *mut u8, src: *const u8, len: usize) -> usize;
+    static ward_mortal_copy_access: u8;
+    static ward_mortal_copy_fixup: u8;
 }
 
-/// STAC (Set AC flag) - Temporarily allow access to user space
+/// Where to resume after an unresolvable page fault at `rip`
 ///
-/// This is part of the sanctification ritual. The Heartwood explicitly declares
-/// its intent to touch mortal lands, making the access controlled and logged.
+/// Only the Ward's copy routine may fault on mortal memory. The page fault
+/// handler sends it to its fixup, and the copy reports `CopyFailed`.
 ///
-/// # Safety
+/// # Returns
 ///
-/// Must be paired with CLAC. Use only within sanctified_copy functions.
-#[inline(always)]
-unsafe fn stac() {
-    // STAC instruction - sets AC flag in RFLAGS
-    // This temporarily allows supervisor mode to access user pages (even with SMAP)
-    asm!("stac", options(nomem, nostack));
+/// * `Some(address)` - `rip` is the copy's access; resume at `address`
+/// * `None` - The fault is not the Ward's to recover from
+pub fn fault_fixup(rip: u64) -> Option<u64> {
+    let access = core::ptr::addr_of!(ward_mortal_copy_access) as u64;
+    let fixup = core::ptr::addr_of!(ward_mortal_copy_fixup) as u64;
+    (rip == access).then_some(fixup)
 }
 
-/// CLAC (Clear AC flag) - Re-enable user space protection
-///
-/// Ends the sanctification ritual. The Heartwood withdraws from mortal lands
-/// and re-establishes the sacred boundary.
+/// Copy `len` bytes across the boundary, failing rather than faulting
 ///
 /// # Safety
 ///
-/// Must be called after STAC to restore protection.
-#[inline(always)]
-unsafe fn clac() {
-    // CLAC instruction - clears AC flag in RFLAGS
-    // This re-enables SMAP protection
-    asm!("clac", options(nomem, nostack));
+/// The mortal side must already be validated with `validate_mortal_pointer`
+/// and the kernel side must be valid for `len` bytes.
+unsafe fn mortal_copy(dest: *mut u8, src: *const u8, len: usize) -> Result<(), WardError> {
+    match ward_mortal_copy(dest, src, len) {
+        0 => Ok(()),
+        _ => Err(WardError::CopyFailed),
+    }
 }
 
 /// Copy a slice from mortal lands
@@ -385,16 +396,7 @@ pub unsafe fn sanctified_copy_slice_from_mortal<T: Copy>(
     let size = dest.len() * size_of::<T>();
     validate_mortal_pointer(mortal_addr, size)?;
 
-    stac();
-
-    let src = mortal_addr as *const T;
-    for (i, elem) in dest.iter_mut().enumerate() {
-        *elem = core::ptr::read_volatile(src.add(i));
-    }
-
-    clac();
-
-    Ok(())
+    mortal_copy(dest.as_mut_ptr() as *mut u8, mortal_addr as *const u8, size)
 }
 
 /// Copy a slice to mortal lands
@@ -417,16 +419,7 @@ pub unsafe fn sanctified_copy_slice_to_mortal<T: Copy>(
     let size = src.len() * size_of::<T>();
     validate_mortal_pointer(mortal_addr, size)?;
 
-    stac();
-
-    let dest = mortal_addr as *mut T;
-    for (i, elem) in src.iter().enumerate() {
-        core::ptr::write_volatile(dest.add(i), *elem);
-    }
-
-    clac();
-
-    Ok(())
+    mortal_copy(mortal_addr as *mut u8, src.as_ptr() as *const u8, size)
 }
 
 #[cfg(test)]
@@ -0,0 +1,575 @@
+//! AHCI/SATA disk driver (DMA mode)
+//!
+//! AHCI controllers are found through PCI (class 01h, subclass 06h,
+//! prog-if 01h) and expose their registers through BAR5 (ABAR). Each port
+//! that has a SATA disk attached gets a command list, a received-FIS area
+//! and a command table in kernel memory; commands are described by a FIS
+//! and a PRDT (scatter/gather list) and the HBA moves the data by DMA.
+//!
+//! **Supported:**
+//! - SATA disks on every AHCI controller claimed through the PCI registry
+//! - 48-bit LBA
+//! - READ/WRITE FPDMA QUEUED (NCQ), falling back to READ/WRITE DMA EXT
+//! - FLUSH CACHE EXT on sync
+//!
+//! **Not yet implemented:**
+//! - Interrupts (completion is polled)
+//! - More than one command in flight (slot 0 / tag 0 only)
+//! - ATAPI devices and port multipliers
+//!
+//! Test under QEMU with:
+//!
+//! ```text
+//! -drive id=disk,file=disk.img,if=none,format=raw
+//! -device ahci,id=ahci -device ide-hd,drive=disk,bus=ahci.0
+//! ```
+
+use crate::attunement::pci::{PciDevice, PciDriver, PciMatch};
+use crate::vfs::block_device::{BlockDevice, BlockDeviceError};
+use alloc::alloc::{alloc_zeroed, dealloc, Layout};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::sync::atomic::{fence, Ordering};
+use spin::Mutex;
+
+/// PCI class code of an AHCI controller (mass storage, SATA, AHCI 1.0)
+const AHCI_CLASS: u8 = 0x01;
+const AHCI_SUBCLASS: u8 = 0x06;
+const AHCI_PROG_IF: u8 = 0x01;
+
+/// BAR holding the HBA registers (ABAR)
+const ABAR_INDEX: u8 = 5;
+
+/// Generic host control registers
+const HBA_CAP: usize = 0x00;
+const HBA_GHC: usize = 0x04;
+const HBA_PI: usize = 0x0C;
+const HBA_CAP2: usize = 0x24;
+const HBA_BOHC: usize = 0x28;
+
+/// Port registers, relative to the port's register block
+const PORT_BASE: usize = 0x100;
+const PORT_SIZE: usize = 0x80;
+const MAX_PORTS: usize = 32;
+const PX_CLB: usize = 0x00;
+const PX_CLBU: usize = 0x04;
+const PX_FB: usize = 0x08;
+const PX_FBU: usize = 0x0C;
+const PX_IS: usize = 0x10;
+const PX_IE: usize = 0x14;
+const PX_CMD: usize = 0x18;
+const PX_TFD: usize = 0x20;
+const PX_SIG: usize = 0x24;
+const PX_SSTS: usize = 0x28;
+const PX_SERR: usize = 0x30;
+const PX_SACT: usize = 0x34;
+const PX_CI: usize = 0x38;
+
+/// Register bits
+const CAP_SNCQ: u32 = 1 << 30;            // Supports native command queuing
+const CAP2_BOH: u32 = 1 << 0;             // Supports BIOS/OS handoff
+const BOHC_BOS: u32 = 1 << 0;             // BIOS owns the HBA
+const BOHC_OOS: u32 = 1 << 1;             // OS requests ownership
+const GHC_AE: u32 = 1 << 31;              // AHCI enable
+const PX_CMD_ST: u32 = 1 << 0;            // Start processing the command list
+const PX_CMD_FRE: u32 = 1 << 4;           // FIS receive enable
+const PX_CMD_FR: u32 = 1 << 14;           // FIS receive running
+const PX_CMD_CR: u32 = 1 << 15;           // Command list running
+const PX_IS_TFES: u32 = 1 << 30;          // Task file error
+const TFD_ERR: u32 = 0x01;
+const TFD_DRQ: u32 = 0x08;
+const TFD_BSY: u32 = 0x80;
+
+/// PxSSTS: device present with PHY communication established, interface active
+const SSTS_DET_PRESENT: u32 = 0x3;
+const SSTS_IPM_ACTIVE: u32 = 0x1;
+
+/// PxSIG of a plain SATA disk
+const SATA_SIG_ATA: u32 = 0x0000_0101;
+
+/// ATA commands
+const ATA_CMD_READ_DMA_EXT: u8 = 0x25;
+const ATA_CMD_WRITE_DMA_EXT: u8 = 0x35;
+const ATA_CMD_READ_FPDMA_QUEUED: u8 = 0x60;
+const ATA_CMD_WRITE_FPDMA_QUEUED: u8 = 0x61;
+const ATA_CMD_FLUSH_CACHE_EXT: u8 = 0xEA;
+const ATA_CMD_IDENTIFY: u8 = 0xEC;
+
+/// Register host-to-device FIS
+const FIS_TYPE_REG_H2D: u8 = 0x27;
+const FIS_LENGTH_DWORDS: u32 = 5;
+const DEVICE_LBA: u8 = 1 << 6;
+
+/// Layout of the per-port command memory (one 4KB page)
+///
+/// The command list must be 1KB aligned, the received-FIS area 256-byte
+/// aligned and the command table 128-byte aligned.
+const COMMAND_MEMORY_SIZE: usize = 0x1000;
+const RECEIVED_FIS_OFFSET: usize = 0x400;
+const COMMAND_TABLE_OFFSET: usize = 0x500;
+const PRDT_OFFSET: usize = 0x80;
+
+/// Command header bits (DW0)
+const HEADER_WRITE: u32 = 1 << 6;
+
+/// Sector size and the largest transfer issued as one command
+const SECTOR_SIZE: usize = 512;
+const MAX_SECTORS_PER_COMMAND: usize = 128;
+const DMA_BUFFER_SIZE: usize = SECTOR_SIZE * MAX_SECTORS_PER_COMMAND;
+
+/// Polling budget for the HBA and the disk
+const SPIN_TIMEOUT: u32 = 1_000_000;
+
+/// Kernel heap memory handed to the HBA for DMA
+///
+/// The kernel heap lives in the first 1GB of physical memory, mapped
+/// linearly at KERNEL_BASE, so a heap allocation is physically contiguous
+/// and its physical address is a subtraction away.
+struct DmaMemory {
+    ptr: *mut u8,
+    layout: Layout,
+}
+
+impl DmaMemory {
+    fn new(size: usize, align: usize) -> Option<Self> {
+        let layout = Layout::from_size_align(size, align).ok()?;
+        let ptr = unsafe { alloc_zeroed(layout) };
+        if ptr.is_null() {
+            None
+        } else {
+            Some(Self { ptr, layout })
+        }
+    }
+
+    fn phys(&self) -> u64 {
+        const KERNEL_BASE: u64 = 0xFFFFFFFF80000000;
+        self.ptr as u64 - KERNEL_BASE
+    }
+}
+
+impl Drop for DmaMemory {
+    fn drop(&mut self) {
+        unsafe { dealloc(self.ptr, self.layout) };
+    }
+}
+
+/// PCI driver entry: claims AHCI controllers as they are registered
+pub static AHCI_DRIVER: PciDriver = PciDriver {
+    name: "ahci",
+    matches: &[PciMatch::Class(AHCI_CLASS, AHCI_SUBCLASS, AHCI_PROG_IF)],
+    probe: probe_controller,
+};
+
+/// A controller claimed by [`probe_controller`]
+struct Controller {
+    /// Virtual address of the HBA registers
+    abar: u64,
+    /// The HBA supports native command queuing
+    ncq: bool,
+    /// Ports owned by an AhciDisk
+    ports_in_use: u32,
+}
+
+static CONTROLLERS: Mutex<Vec<Controller>> = Mutex::new(Vec::new());
+
+/// Take over an AHCI controller from the firmware
+fn probe_controller(device: &PciDevice) -> bool {
+    let Some(abar_phys) = device.memory_bar(ABAR_INDEX) else {
+        return false;
+    };
+    device.enable_bus_mastering();
+
+    let size = (PORT_BASE + MAX_PORTS * PORT_SIZE) as u64;
+    let Ok(abar) = (unsafe { crate::mana_pool::page_tables::map_mmio(abar_phys, size) }) else {
+        return false;
+    };
+    let hba_read = |reg: usize| unsafe { core::ptr::read_volatile((abar + reg as u64) as *const u32) };
+    let hba_write = |reg: usize, value: u32| unsafe {
+        core::ptr::write_volatile((abar + reg as u64) as *mut u32, value)
+    };
+
+    // Ask the firmware to let go of the controller
+    if hba_read(HBA_CAP2) & CAP2_BOH != 0 {
+        hba_write(HBA_BOHC, hba_read(HBA_BOHC) | BOHC_OOS);
+        spin_until(|| hba_read(HBA_BOHC) & BOHC_BOS == 0);
+    }
+    hba_write(HBA_GHC, hba_read(HBA_GHC) | GHC_AE);
+
+    CONTROLLERS.lock().push(Controller {
+        abar,
+        ncq: hba_read(HBA_CAP) & CAP_SNCQ != 0,
+        ports_in_use: 0,
+    });
+    true
+}
+
+/// One AHCI port with its command memory
+struct AhciPort {
+    /// Index of the owning controller in CONTROLLERS
+    controller: usize,
+    /// Port number on the controller
+    index: usize,
+    /// Virtual address of the port's register block
+    regs: u64,
+    /// Command list, received FIS and command table
+    memory: DmaMemory,
+    /// Bounce buffer the data phase goes through
+    buffer: DmaMemory,
+}
+
+// SAFETY: The port's registers and DMA memory are only touched through
+// the Mutex in AhciDisk.
+unsafe impl Send for AhciPort {}
+
+impl AhciPort {
+    fn read(&self, reg: usize) -> u32 {
+        unsafe { core::ptr::read_volatile((self.regs + reg as u64) as *const u32) }
+    }
+
+    fn write(&self, reg: usize, value: u32) {
+        unsafe { core::ptr::write_volatile((self.regs + reg as u64) as *mut u32, value) }
+    }
+
+    /// Point the HBA at our command memory and start the port
+    fn start(&self) -> Result<(), BlockDeviceError> {
+        self.stop()?;
+
+        let command_list = self.memory.phys();
+        let received_fis = command_list + RECEIVED_FIS_OFFSET as u64;
+        self.write(PX_CLB, command_list as u32);
+        self.write(PX_CLBU, (command_list >> 32) as u32);
+        self.write(PX_FB, received_fis as u32);
+        self.write(PX_FBU, (received_fis >> 32) as u32);
+
+        // Clear stale errors and interrupt status; completion is polled
+        self.write(PX_SERR, u32::MAX);
+        self.write(PX_IS, u32::MAX);
+        self.write(PX_IE, 0);
+
+        self.write(PX_CMD, self.read(PX_CMD) | PX_CMD_FRE);
+        if !spin_until(|| self.read(PX_TFD) & (TFD_BSY | TFD_DRQ) == 0) {
+            return Err(BlockDeviceError::NotReady);
+        }
+        self.write(PX_CMD, self.read(PX_CMD) | PX_CMD_ST);
+        Ok(())
+    }
+
+    /// Stop command processing and FIS reception
+    fn stop(&self) -> Result<(), BlockDeviceError> {
+        self.write(PX_CMD, self.read(PX_CMD) & !PX_CMD_ST);
+        if !spin_until(|| self.read(PX_CMD) & PX_CMD_CR == 0) {
+            return Err(BlockDeviceError::NotReady);
+        }
+
+        self.write(PX_CMD, self.read(PX_CMD) & !PX_CMD_FRE);
+        if !spin_until(|| self.read(PX_CMD) & PX_CMD_FR == 0) {
+            return Err(BlockDeviceError::NotReady);
+        }
+        Ok(())
+    }
+
+    /// Issue one command in slot 0 and wait for it to complete
+    ///
+    /// `bytes` of data move between the disk and the bounce buffer;
+    /// `write` gives the direction. Queued (NCQ) commands are also
+    /// tracked in PxSACT, which the disk clears when it is done.
+    fn issue(&self, fis: &[u8; 20], write: bool, bytes: usize, queued: bool) -> Result<(), BlockDeviceError> {
+        if !spin_until(|| self.read(PX_TFD) & (TFD_BSY | TFD_DRQ) == 0) {
+            return Err(BlockDeviceError::NotReady);
+        }
+
+        let table_phys = self.memory.phys() + COMMAND_TABLE_OFFSET as u64;
+        let prdt_length = if bytes > 0 { 1 } else { 0 };
+        let mut flags = FIS_LENGTH_DWORDS | (prdt_length << 16);
+        if write {
+            flags |= HEADER_WRITE;
+        }
+
+        unsafe {
+            // Command header 0
+            let header = self.memory.ptr as *mut u32;
+            header.write_volatile(flags);
+            header.add(1).write_volatile(0);
+            header.add(2).write_volatile(table_phys as u32);
+            header.add(3).write_volatile((table_phys >> 32) as u32);
+
+            // Command table: the FIS, then a single PRD covering the buffer
+            let table = self.memory.ptr.add(COMMAND_TABLE_OFFSET);
+            core::ptr::write_bytes(table, 0, PRDT_OFFSET + 16);
+            core::ptr::copy_nonoverlapping(fis.as_ptr(), table, fis.len());
+            if bytes > 0 {
+                let prd = table.add(PRDT_OFFSET) as *mut u32;
+                let buffer_phys = self.buffer.phys();
+                prd.write_volatile(buffer_phys as u32);
+                prd.add(1).write_volatile((buffer_phys >> 32) as u32);
+                prd.add(3).write_volatile((bytes - 1) as u32);
+            }
+        }
+
+        self.write(PX_IS, u32::MAX);
+        fence(Ordering::SeqCst);
+        if queued {
+            self.write(PX_SACT, 1);
+        }
+        self.write(PX_CI, 1);
+
+        let completed = spin_until(|| {
+            let busy = self.read(PX_CI) & 1 != 0 || (queued && self.read(PX_SACT) & 1 != 0);
+            !busy || self.read(PX_IS) & PX_IS_TFES != 0
+        });
+        fence(Ordering::SeqCst);
+
+        if !completed || self.read(PX_IS) & PX_IS_TFES != 0 || self.read(PX_TFD) & TFD_ERR != 0 {
+            // Restarting the port clears PxCI/PxSACT and the error state
+            let _ = self.start();
+            return Err(BlockDeviceError::IoError);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for AhciPort {
+    fn drop(&mut self) {
+        // The HBA must stop using the command memory before it is freed
+        let _ = self.stop();
+        if let Some(controller) = CONTROLLERS.lock().get_mut(self.controller) {
+            controller.ports_in_use &= !(1 << self.index);
+        }
+    }
+}
+
+/// SATA disk attached to an AHCI port
+pub struct AhciDisk {
+    port: Mutex<AhciPort>,
+    sectors: u64,
+    ncq: bool,
+    model: String,
+}
+
+impl AhciDisk {
+    /// Detect and initialize the first free SATA disk
+    ///
+    /// Only controllers claimed by [`AHCI_DRIVER`] are searched, so the
+    /// driver must have been registered (see `drivers::init`).
+    pub fn detect_first() -> Option<Self> {
+        let count = CONTROLLERS.lock().len();
+        (0..count).find_map(Self::probe_ports)
+    }
+
+    /// Get the total number of sectors on this disk
+    pub fn sector_count(&self) -> u64 {
+        self.sectors
+    }
+
+    /// Model string reported by IDENTIFY DEVICE
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Whether transfers use native command queuing
+    pub fn uses_ncq(&self) -> bool {
+        self.ncq
+    }
+
+    /// Return the first free port of a controller with a disk that answers
+    fn probe_ports(controller: usize) -> Option<Self> {
+        for index in 0..MAX_PORTS {
+            let (port, ncq) = {
+                let mut controllers = CONTROLLERS.lock();
+                let hba = controllers.get_mut(controller)?;
+                let implemented = unsafe { core::ptr::read_volatile((hba.abar + HBA_PI as u64) as *const u32) };
+                if implemented & (1 << index) == 0 || hba.ports_in_use & (1 << index) != 0 {
+                    continue;
+                }
+
+                let regs = hba.abar + (PORT_BASE + index * PORT_SIZE) as u64;
+                let status = unsafe { core::ptr::read_volatile((regs + PX_SSTS as u64) as *const u32) };
+                let signature = unsafe { core::ptr::read_volatile((regs + PX_SIG as u64) as *const u32) };
+                if status & 0xF != SSTS_DET_PRESENT
+                    || (status >> 8) & 0xF != SSTS_IPM_ACTIVE
+                    || signature != SATA_SIG_ATA
+                {
+                    continue;
+                }
+
+                let memory = DmaMemory::new(COMMAND_MEMORY_SIZE, COMMAND_MEMORY_SIZE)?;
+                let buffer = DmaMemory::new(DMA_BUFFER_SIZE, 0x1000)?;
+
+                // From here on, dropping the port releases it again
+                hba.ports_in_use |= 1 << index;
+                (AhciPort { controller, index, regs, memory, buffer }, hba.ncq)
+            };
+
+            if let Some(disk) = Self::attach(port, ncq) {
+                return Some(disk);
+            }
+        }
+
+        None
+    }
+
+    /// Start a port and identify the disk on it
+    fn attach(port: AhciPort, hba_ncq: bool) -> Option<Self> {
+        port.start().ok()?;
+
+        let fis = command_fis(ATA_CMD_IDENTIFY, 0, 0, 0, 0);
+        port.issue(&fis, false, SECTOR_SIZE, false).ok()?;
+
+        let mut identify = [0u16; 256];
+        for (i, word) in identify.iter_mut().enumerate() {
+            let bytes = unsafe { core::slice::from_raw_parts(port.buffer.ptr.add(i * 2), 2) };
+            *word = u16::from_le_bytes([bytes[0], bytes[1]]);
+        }
+
+        // Words 100-103 hold the 48-bit sector count when LBA48 is supported
+        // (word 83 bit 10), otherwise words 60-61 hold the 28-bit count
+        let sectors = if identify[83] & (1 << 10) != 0 {
+            (0..4).fold(0u64, |acc, i| acc | (identify[100 + i] as u64) << (16 * i))
+        } else {
+            identify[60] as u64 | (identify[61] as u64) << 16
+        };
+        if sectors == 0 {
+            return None;
+        }
+
+        // Word 76 bit 8: the disk supports NCQ
+        let ncq = hba_ncq && identify[76] & (1 << 8) != 0;
+
+        // Words 27-46: model number, two characters per word, high byte first
+        let model: String = identify[27..47]
+            .iter()
+            .flat_map(|word| [(word >> 8) as u8 as char, (*word & 0xFF) as u8 as char])
+            .collect();
+
+        Some(AhciDisk {
+            port: Mutex::new(port),
+            sectors,
+            ncq,
+            model: String::from(model.trim()),
+        })
+    }
+
+    /// Move up to MAX_SECTORS_PER_COMMAND sectors through the bounce buffer
+    fn transfer(&self, port: &AhciPort, lba: u64, count: usize, write: bool) -> Result<(), BlockDeviceError> {
+        let bytes = count * SECTOR_SIZE;
+        let fis = if self.ncq {
+            let command = if write { ATA_CMD_WRITE_FPDMA_QUEUED } else { ATA_CMD_READ_FPDMA_QUEUED };
+            // NCQ puts the sector count in the features field and the tag
+            // (always 0 here) in bits 7:3 of the count field
+            command_fis(command, lba, 0, count as u16, DEVICE_LBA)
+        } else {
+            let command = if write { ATA_CMD_WRITE_DMA_EXT } else { ATA_CMD_READ_DMA_EXT };
+            command_fis(command, lba, count as u16, 0, DEVICE_LBA)
+        };
+
+        port.issue(&fis, write, bytes, self.ncq)
+    }
+
+    fn check_range(&self, start_sector: u64, count: u64) -> Result<(), BlockDeviceError> {
+        match start_sector.checked_add(count) {
+            Some(end) if end <= self.sectors => Ok(()),
+            _ => Err(BlockDeviceError::InvalidSector),
+        }
+    }
+}
+
+impl BlockDevice for AhciDisk {
+    fn sector_size(&self) -> u32 {
+        SECTOR_SIZE as u32
+    }
+
+    fn sector_count(&self) -> u64 {
+        self.sectors
+    }
+
+    fn read_sector(&self, sector: u64) -> Result<Vec<u8>, BlockDeviceError> {
+        self.read_sectors(sector, 1)
+    }
+
+    fn read_sectors(&self, start_sector: u64, count: u32) -> Result<Vec<u8>, BlockDeviceError> {
+        self.check_range(start_sector, count as u64)?;
+
+        let port = self.port.lock();
+        let mut result = Vec::with_capacity(count as usize * SECTOR_SIZE);
+        let mut lba = start_sector;
+        let mut remaining = count as usize;
+
+        while remaining > 0 {
+            let chunk = core::cmp::min(remaining, MAX_SECTORS_PER_COMMAND);
+            self.transfer(&port, lba, chunk, false)?;
+
+            let data = unsafe { core::slice::from_raw_parts(port.buffer.ptr, chunk * SECTOR_SIZE) };
+            result.extend_from_slice(data);
+
+            lba += chunk as u64;
+            remaining -= chunk;
+        }
+
+        Ok(result)
+    }
+
+    fn write_sector(&self, sector: u64, data: &[u8]) -> Result<(), BlockDeviceError> {
+        if data.len() != SECTOR_SIZE {
+            return Err(BlockDeviceError::IoError);
+        }
+        self.write_sectors(sector, data)
+    }
+
+    fn write_sectors(&self, start_sector: u64, data: &[u8]) -> Result<(), BlockDeviceError> {
+        if data.len() % SECTOR_SIZE != 0 {
+            return Err(BlockDeviceError::IoError);
+        }
+        self.check_range(start_sector, (data.len() / SECTOR_SIZE) as u64)?;
+
+        let port = self.port.lock();
+        let mut lba = start_sector;
+
+        for chunk in data.chunks(DMA_BUFFER_SIZE) {
+            unsafe {
+                core::ptr::copy_nonoverlapping(chunk.as_ptr(), port.buffer.ptr, chunk.len());
+            }
+            let sectors = chunk.len() / SECTOR_SIZE;
+            self.transfer(&port, lba, sectors, true)?;
+            lba += sectors as u64;
+        }
+
+        Ok(())
+    }
+
+    fn sync(&self) -> Result<(), BlockDeviceError> {
+        let port = self.port.lock();
+        let fis = command_fis(ATA_CMD_FLUSH_CACHE_EXT, 0, 0, 0, 0);
+        port.issue(&fis, false, 0, false)
+    }
+}
+
+/// Build a register host-to-device FIS
+fn command_fis(command: u8, lba: u64, count: u16, features: u16, device: u8) -> [u8; 20] {
+    let mut fis = [0u8; 20];
+    fis[0] = FIS_TYPE_REG_H2D;
+    fis[1] = 1 << 7; // This FIS carries a command
+    fis[2] = command;
+    fis[3] = features as u8;
+    fis[4] = lba as u8;
+    fis[5] = (lba >> 8) as u8;
+    fis[6] = (lba >> 16) as u8;
+    fis[7] = device;
+    fis[8] = (lba >> 24) as u8;
+    fis[9] = (lba >> 32) as u8;
+    fis[10] = (lba >> 40) as u8;
+    fis[11] = (features >> 8) as u8;
+    fis[12] = count as u8;
+    fis[13] = (count >> 8) as u8;
+    fis
+}
+
+/// Poll `done` until it holds or the budget runs out
+fn spin_until(mut done: impl FnMut() -> bool) -> bool {
+    for _ in 0..SPIN_TIMEOUT {
+        if done() {
+            return true;
+        }
+        core::hint::spin_loop();
+    }
+    false
+}
@@ -2,7 +2,15 @@
 //!
 //! This module contains drivers for various hardware devices.
 
+pub mod ahci;
 pub mod ata;
 pub mod serial;
 
+pub use ahci::AhciDisk;
 pub use ata::AtaDrive;
+
+/// Register the PCI drivers, letting each claim the devices it matches
+pub fn init() {
+    let controllers = crate::attunement::pci::register_driver(&ahci::AHCI_DRIVER);
+    crate::serial_println!("[DRIVERS] ahci claimed {} controller(s)", controllers);
+}
@@ -493,11 +493,13 @@ fn execute_command(input: &str) {
         "help" => cmd_help(),
         "preempt" => cmd_preempt(args),
         "uptime" => cmd_uptime(),
+        "roots" => cmd_roots(),            // PCI devices
         "wards" => cmd_wards(),            // Security wards (ASLR, W^X)
         "sigils" => cmd_sigils(),          // Weaver's Sigils (stack canaries)
         "permanence" => cmd_permanence(),  // Rune of Permanence (immutable structures)
         "fate" => cmd_fate(args),          // Concordance of Fates (RBAC)
         "test-user" => cmd_test_user(),    // Launch test user space program
+        "summon" => cmd_summon(args),      // Launch a user program from the VFS
         "eval" => cmd_eval(args),          // Execute Glimmer-Weave script
         "compile" => cmd_compile(args),    // Compile Glimmer-Weave to assembly
         "elf" => cmd_elf(args),            // Generate ELF object file
@@ -505,6 +507,7 @@ fn execute_command(input: &str) {
         "grove-list" => cmd_grove_list(),  // List all Ring 1 services
         "grove-info" => cmd_grove_info(args), // Show service details
         "grove-test" => cmd_grove_test(),  // Load test service
+        "grove-names" => cmd_grove_names(), // List Nexus service names
         // Filesystem commands (Eldarin naming)
         "reveal" => cmd_vfs_ls(args),      // vfs-ls → reveal
         "recite" => cmd_vfs_cat(args),     // vfs-cat → recite
@@ -545,6 +548,7 @@ fn cmd_harmony() {
         crate::println!("    • Weaving: {}", stats.weaving_threads);
         crate::println!("    • Resting: {}", stats.resting_threads);
         crate::println!("    • Tangled: {}", stats.tangled_threads);
+        crate::println!("    • Blocked: {} ({} with a deadline)", stats.blocked_threads, stats.sleeping_threads);
     }
     crate::println!();
 
@@ -556,6 +560,7 @@ fn cmd_harmony() {
 
     crate::println!("  Performance:");
     crate::println!("    • Context Switches: {}", stats.context_switches);
+    crate::println!("    • Processors: {}", stats.online_cpus);
     crate::println!();
 
     // Interpret the harmony level
@@ -857,6 +862,48 @@ fn cmd_grove_info(name: &str) {
     }
 }
 
+/// The Grove Names Spell - Show every service name known to the Nexus
+fn cmd_grove_names() {
+    use crate::groves::manager::get_grove_manager;
+
+    crate::println!("◈ Names in the Nexus");
+    crate::println!();
+
+    let registrations = crate::nexus::names::registrations();
+    if registrations.is_empty() {
+        crate::println!("  No names registered.");
+        return;
+    }
+
+    // A name whose owner has revoked its capability is dropped on next resolve
+    let live: alloc::vec::Vec<bool> = crate::loom_of_fate::without_interrupts(|| {
+        let harbor = crate::loom_of_fate::get_harbor().lock();
+        registrations
+            .iter()
+            .map(|registration| {
+                harbor
+                    .find_vessel(registration.owner)
+                    .is_some_and(|owner| owner.capabilities().get(registration.capability).is_ok())
+            })
+            .collect()
+    });
+
+    crate::println!("  {} name(s) registered:", registrations.len());
+    crate::println!();
+
+    let manager = get_grove_manager().lock();
+    for (registration, live) in registrations.iter().zip(live) {
+        let owner = manager
+            .all_services()
+            .find(|service| service.vessel_id == registration.owner)
+            .map(|service| service.name.as_str())
+            .unwrap_or("(not a Grove)");
+
+        crate::println!("  {} {} → {}", if live { "✓" } else { "✗" }, registration.name, owner);
+        crate::println!("     Vessel: {:?}, Capability: #{}", registration.owner, registration.capability.raw());
+    }
+}
+
 /// The Grove Test Spell - Start a real Ring 1 service
 fn cmd_grove_test() {
     use crate::groves::lifecycle::{ServiceConfig, load_service, start_service, ring1_test_service};
@@ -964,6 +1011,7 @@ fn show_help_page(page: usize) {
             crate::println!("  mana-flow          - Visualize memory (Mana Pool) usage");
             crate::println!("  observe-weave      - Real-time view of the Loom's activity");
             crate::println!("  uptime             - Show how long the realm has been awake");
+            crate::println!("  roots              - Trace the roots of the realm (PCI devices)");
             crate::println!("  wards              - Display security protections (ASLR, W^X)");
             crate::println!("  sigils             - Show The Weaver's Sigils (canary protection)");
             crate::println!("  permanence         - View The Rune of Permanence (immutable structures)");
@@ -974,6 +1022,7 @@ fn show_help_page(page: usize) {
             crate::println!("  soothe [id]        - Lower a thread's priority (more harmonious)");
             crate::println!("  release [id]       - Gracefully release a thread's resources");
             crate::println!("  rest [ms]          - Rest for a duration (sleep)");
+            crate::println!("  summon <elf> [arg] - Summon a Vessel from an ELF scroll");
             crate::println!();
             crate::println!("─── Press ENTER for next page (1/3) ───");
         }
@@ -1187,8 +1236,10 @@ fn cmd_mana_flow() {
 
 /// The Uptime Spell - Show how long the system has been running
 fn cmd_uptime() {
-    let ticks = crate::attunement::timer::ticks();
-    let seconds = ticks / 1000;  // Assuming 1ms ticks
+    use crate::attunement::{clocksource, timer};
+
+    let ticks = timer::ticks();
+    let seconds = clocksource::now_ns() / 1_000_000_000;
     let minutes = seconds / 60;
     let hours = minutes / 60;
     let days = hours / 24;
@@ -1208,7 +1259,58 @@ fn cmd_uptime() {
         crate::println!("  {} seconds", seconds);
     }
 
-    crate::println!("  ({} timer ticks)", ticks);
+    crate::println!("  ({} heartbeats, kept by the {}{})", ticks, clocksource::current().name(),
+        if timer::is_tickless() { ", tickless" } else { "" });
+}
+
+/// The Roots Spell - List the PCI devices beneath the realm
+fn cmd_roots() {
+    use crate::attunement::pci::{self, Bar};
+
+    crate::println!("◈ The Roots of the Realm (PCI)");
+    crate::println!();
+
+    let devices = pci::devices();
+    if devices.is_empty() {
+        crate::println!("  No devices found.");
+        return;
+    }
+
+    for dev in &devices {
+        // Indent devices behind bridges by their depth in the tree
+        let mut depth = 0;
+        let mut parent = dev.parent;
+        while let Some(index) = parent {
+            depth += 1;
+            parent = devices[index].parent;
+        }
+
+        crate::print!("  {:width$}{:02x}:{:02x}.{} {:04x}:{:04x} {}",
+            "", dev.bus, dev.device, dev.function, dev.vendor_id, dev.device_id,
+            dev.class_name(), width = depth * 2);
+        if let Some(driver) = dev.driver {
+            crate::print!(" [{}]", driver);
+        }
+        if dev.interrupt_pin != 0 {
+            crate::print!(" IRQ {}", dev.interrupt_line);
+        }
+        crate::println!();
+
+        for (index, bar) in dev.bars.iter().enumerate() {
+            match *bar {
+                Bar::Memory { address, size, .. } => crate::println!("  {:width$}    BAR{} mem {:#x} ({} KB)",
+                    "", index, address, size / 1024, width = depth * 2),
+                Bar::Io { port, size } => crate::println!("  {:width$}    BAR{} io  {:#x} ({} ports)",
+                    "", index, port, size, width = depth * 2),
+                Bar::Unused => {}
+            }
+        }
+    }
+
+    crate::println!();
+    crate::println!("  {} function(s) via {}; drivers: {}", devices.len(),
+        if pci::uses_ecam() { "ECAM" } else { "legacy port I/O" },
+        pci::drivers().join(", "));
 }
 
 /// The Soothe Spell - Lower a thread's priority
@@ -1269,11 +1371,8 @@ fn cmd_rest(args: &str) {
     crate::println!();
     crate::println!("  Resting for {} milliseconds...", ms);
 
-    // Simple busy-wait (in a real implementation, this would yield to other threads)
-    let start = crate::attunement::timer::ticks();
-    while crate::attunement::timer::ticks() - start < ms {
-        // Busy wait
-    }
+    // The shell thread sleeps in the Loom, leaving the CPU to others
+    crate::loom_of_fate::sleep(ms);
 
     crate::println!("  ✓ Awakened");
 }
@@ -1389,7 +1488,7 @@ fn cmd_vfs_info() {
 
 /// VFS LS - List directory contents
 fn cmd_vfs_ls(args: &str) {
-    use crate::vfs::Path;
+    use crate::vfs::{FileType, Path};
     use crate::vfs::global as vfs_global;
 
     let path_str = if args.is_empty() { "/" } else { args.trim() };
@@ -1428,9 +1527,18 @@ fn cmd_vfs_ls(args: &str) {
                 crate::println!("  (empty directory)");
             } else {
                 for entry in entries {
-                    let icon = if entry.is_dir { "📁" } else { "📄" };
-                    let type_str = if entry.is_dir { "DIR " } else { "FILE" };
-                    crate::println!("  {} [{}] {}", icon, type_str, entry.name);
+                    let (icon, type_str) = match entry.file_type {
+                        FileType::Directory => ("📁", "DIR "),
+                        FileType::Symlink => ("🔗", "LINK"),
+                        FileType::File | FileType::Other => ("📄", "FILE"),
+                    };
+                    match entry.file_type {
+                        FileType::Symlink => match fs.read_link(&path.join(&entry.name)) {
+                            Ok(target) => crate::println!("  {} [{}] {} -> {}", icon, type_str, entry.name, target),
+                            Err(_) => crate::println!("  {} [{}] {} -> ?", icon, type_str, entry.name),
+                        },
+                        _ => crate::println!("  {} [{}] {}", icon, type_str, entry.name),
+                    }
                 }
             }
             crate::println!();
@@ -1532,6 +1640,29 @@ fn cmd_fate(args: &str) {
 }
 
 /// Launch test user space program
+/// The Summon Spell - Start a user program stored in the World-Tree
+fn cmd_summon(args: &str) {
+    use crate::loom_of_fate::summoning;
+
+    let argv: alloc::vec::Vec<&[u8]> = args.split_whitespace().map(str::as_bytes).collect();
+    let Some(path) = args.split_whitespace().next() else {
+        crate::println!("Usage: summon <path> [args...]");
+        crate::println!("Example: summon /BIN/HELLO");
+        return;
+    };
+
+    crate::println!("◈ Summoning {}...", path);
+
+    match summoning::summon_from_path(path, &argv, &[], None, summoning::default_fate()) {
+        Ok(vessel_id) => {
+            crate::println!("✓ Vessel {} summoned", vessel_id.0);
+        }
+        Err(e) => {
+            crate::println!("✗ Failed to summon: {}", e);
+        }
+    }
+}
+
 fn cmd_test_user() {
     use crate::test_programs::HELLO_ELF;
     use crate::loom_of_fate::{create_user_thread, get_harbor, ThreadPriority, load_elf, ThreadId};
@@ -1558,6 +1689,7 @@ fn cmd_test_user() {
     match harbor_lock.moor_user_vessel(
         None,  // No parent
         HELLO_ELF,
+        &[],  // Statically linked, no Groves
         alloc::string::String::from("test-user"),
         ThreadId(0),  // Placeholder main thread ID
     ) {
@@ -194,6 +194,18 @@ fn cmd_fate_show(fate_name: &str) {
                 crate::println!();
             }
 
+            if !fate.service_rules.is_empty() {
+                crate::println!("  Service Rules:");
+                for rule in &fate.service_rules {
+                    let perm = match rule.permission {
+                        crate::mana_pool::concordance_of_fates::Permission::Allow => "✓ Allow",
+                        crate::mana_pool::concordance_of_fates::Permission::Deny => "✗ Deny",
+                    };
+                    crate::println!("    {} {:?} on {}", perm, rule.access, rule.name_pattern);
+                }
+                crate::println!();
+            }
+
             if !fate.allowed_transitions.is_empty() {
                 crate::println!("  Allowed Fate Transitions:");
                 for transition in &fate.allowed_transitions {
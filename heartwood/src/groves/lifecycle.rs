@@ -216,12 +216,11 @@ pub fn stop_service(service_id: ServiceId) -> Result<(), GroveError> {
         crate::serial_println!("[Lifecycle] Warning: Failed to terminate thread {:?}: {:?}", thread_id, e);
     }
 
-    // Clean up the Vessel and its memory
-    crate::serial_println!("[Lifecycle] Unmooring Vessel {:?}", vessel_id);
-    {
-        let mut harbor = loom_of_fate::get_harbor().lock();
-        harbor.unmoor_vessel(vessel_id);
-    }
+    // Its endpoints die with it
+    crate::nexus::names::forget_vessel(vessel_id);
+
+    // The Vessel has vanished with its thread; having no parent, it is
+    // reaped by the Harbormaster once the thread is off every CPU
 
     crate::serial_println!("[Lifecycle] Service '{}' stopped and cleaned up", name);
 
@@ -389,13 +388,12 @@ pub fn ring1_test_service() -> ! {
 
             // Yield to other threads via INT 0x81 syscall
             // Register convention:
-            //   RAX = syscall number (0 = SYS_YIELD)
+            //   RAX = syscall number (SYS_YIELD from the covenant)
             //   No arguments needed for yield
             unsafe {
                 core::arch::asm!(
-                    "mov rax, 0",   // SYS_YIELD = 0
                     "int 0x81",     // Ring 1 syscall interrupt
-                    out("rax") _,   // Result ignored for yield
+                    inlateout("rax") covenant::numbers::SYS_YIELD => _,  // Result ignored for yield
                     options(nomem, preserves_flags)
                 );
             }
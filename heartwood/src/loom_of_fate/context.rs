@@ -45,6 +45,7 @@ pub struct ThreadContext {
     pub rsp: u64,      // Stack pointer
     pub ss: u64,       // Stack segment
     pub cr3: u64,      // Page table base (physical address) - for per-Vessel address spaces
+    pub fs_base: u64,  // FS base - the thread pointer of user TLS (0 if none)
 }
 
 impl ThreadContext {
@@ -84,6 +85,7 @@ impl ThreadContext {
             rsp: stack_top - 8,
             ss: 0x10,  // Kernel data segment (from GDT)
             cr3: 0,  // 0 means "use current CR3" (kernel threads share kernel page tables)
+            fs_base: 0,
         }
     }
 
@@ -95,7 +97,7 @@ impl ThreadContext {
             r9: 0, r8: 0, rax: 0, rcx: 0,
             rdx: 0, rsi: 0, rdi: 0,
             rip: 0, cs: 0, rflags: 0,
-            rsp: 0, ss: 0, cr3: 0,
+            rsp: 0, ss: 0, cr3: 0, fs_base: 0,
         }
     }
 
@@ -130,6 +132,7 @@ impl ThreadContext {
             rsp: user_stack_top,      // User stack (must be 16-byte aligned for IRETQ)
             ss: 0x18 | 3,  // User data segment (GDT index 3) | RPL=3
             cr3: page_table_phys,  // Vessel's page table
+            fs_base: 0,  // Set once the thread has a TLS block
         }
     }
 
@@ -161,6 +164,7 @@ impl ThreadContext {
             rsp: service_stack_top,  // Service stack (must be 16-byte aligned)
             ss: 0x30 | 1,  // Service data segment (GDT index 6) | RPL=1
             cr3: page_table_phys,  // Service's page table (isolated address space)
+            fs_base: 0,
         }
     }
 }
@@ -237,6 +241,14 @@ pub unsafe extern "C" fn switch_context(_old_context: *mut ThreadContext, _new_c
         "mov ax, ss",
         "mov [rdi + 0x98], rax",
 
+        // Save FS base (IA32_FS_BASE); the kernel never changes it, so it
+        // still holds the thread's TLS pointer
+        "mov ecx, 0xC0000100",
+        "rdmsr",
+        "shl rdx, 32",
+        "or rax, rdx",
+        "mov [rdi + 0xA8], rax",
+
         // Now restore new context
         // rsi = new_context pointer
 
@@ -262,6 +274,12 @@ pub unsafe extern "C" fn switch_context(_old_context: *mut ThreadContext, _new_c
 
         "2:",  // Continue with normal context restore
 
+        // Restore FS base (IA32_FS_BASE)
+        "mov ecx, 0xC0000100",
+        "mov eax, [rsi + 0xA8]",
+        "mov edx, [rsi + 0xAC]",
+        "wrmsr",
+
         // DEBUG: Mark before register restore
         "push rax",
         "mov dx, 0x3f8",
@@ -445,6 +463,14 @@ pub unsafe extern "C" fn switch_context_cooperative(_old_context: *mut ThreadCon
         "pop rax",
         "mov [rdi + 0x88], rax",
 
+        // Save FS base (IA32_FS_BASE); the kernel never changes it, so it
+        // still holds the thread's TLS pointer
+        "mov ecx, 0xC0000100",
+        "rdmsr",
+        "shl rdx, 32",
+        "or rax, rdx",
+        "mov [rdi + 0xA8], rax",
+
         // Now restore new context from rsi
 
         // Check if we need to switch page tables (CR3)
@@ -458,6 +484,12 @@ pub unsafe extern "C" fn switch_context_cooperative(_old_context: *mut ThreadCon
 
         "2:",  // Continue with normal context restore
 
+        // Restore FS base (IA32_FS_BASE)
+        "mov ecx, 0xC0000100",
+        "mov eax, [rsi + 0xA8]",
+        "mov edx, [rsi + 0xAC]",
+        "wrmsr",
+
         // Restore callee-saved registers
         "mov r15, [rsi + 0x00]",
         "mov r14, [rsi + 0x08]",
@@ -511,6 +543,9 @@ pub unsafe extern "C" fn context_switch_first(_new_context: *const ThreadContext
         // Load the stack pointer
         "mov rsp, [rdi + 0x90]",  // Load RSP
 
+        // A new kernel thread carries its entry point in R12 (see thread_start)
+        "mov r12, [rdi + 0x18]",
+
         // Clear base pointer (indicates top of call stack)
         "xor rbp, rbp",
 
@@ -600,6 +635,14 @@ pub unsafe extern "C" fn save_preempted_context(_context: *mut ThreadContext, _s
         "mov rax, [rsi + 32]",   // SS from interrupt frame
         "mov [rdi + 0x98], rax",
 
+        // Save FS base (IA32_FS_BASE); the kernel never changes it, so it
+        // still holds the thread's TLS pointer
+        "mov ecx, 0xC0000100",
+        "rdmsr",
+        "shl rdx, 32",
+        "or rax, rdx",
+        "mov [rdi + 0xA8], rax",
+
         "ret",
     );
 }
@@ -630,6 +673,12 @@ pub unsafe extern "C" fn restore_context(_new_context: *const ThreadContext) ->
 
         "2:",  // Continue with normal context restore
 
+        // Restore FS base (IA32_FS_BASE)
+        "mov ecx, 0xC0000100",
+        "mov eax, [rdi + 0xA8]",
+        "mov edx, [rdi + 0xAC]",
+        "wrmsr",
+
         // Restore general purpose registers
         "mov r15, [rdi + 0x00]",
         "mov r14, [rdi + 0x08]",
@@ -686,6 +735,24 @@ pub extern "C" fn thread_entry_wrapper(entry_point: extern "C" fn() -> !) -> ! {
     entry_point()
 }
 
+/// Where every new kernel thread begins
+///
+/// The entry point arrives in R12, which every context restore loads.
+/// Before calling it, the switch that started the thread is finished, so
+/// the thread it replaced can be scheduled again (see `finish_switch`).
+#[unsafe(naked)]
+pub unsafe extern "C" fn thread_start() -> ! {
+    core::arch::naked_asm!(
+        "call {finish}",
+        "jmp r12",
+        finish = sym finish_switch_hook,
+    );
+}
+
+extern "C" fn finish_switch_hook() {
+    super::finish_switch();
+}
+
 /// Enter user mode for the first time
 ///
 /// This function uses IRETQ to transition from ring 0 to ring 3.
@@ -711,6 +778,12 @@ pub unsafe extern "C" fn enter_user_mode(_context: *const ThreadContext) -> ! {
         "mov rax, [rdi + 0xA0]",  // cr3 offset (from struct definition)
         "mov cr3, rax",
 
+        // Restore FS base (IA32_FS_BASE)
+        "mov ecx, 0xC0000100",
+        "mov eax, [rdi + 0xA8]",
+        "mov edx, [rdi + 0xAC]",
+        "wrmsr",
+
         // Set up IRETQ frame on stack:
         // Stack layout (pushed in reverse order):
         // [SS]      <- Top
@@ -776,5 +849,13 @@ mod tests {
         assert_eq!(ctx.rip, 0);
         assert_eq!(ctx.rsp, 0);
         assert_eq!(ctx.rax, 0);
+        assert_eq!(ctx.fs_base, 0);
+    }
+
+    #[test]
+    fn test_offsets_used_by_switch_code() {
+        assert_eq!(core::mem::offset_of!(ThreadContext, rip), 0x78);
+        assert_eq!(core::mem::offset_of!(ThreadContext, cr3), 0xA0);
+        assert_eq!(core::mem::offset_of!(ThreadContext, fs_base), 0xA8);
     }
 }
@@ -14,8 +14,11 @@
 //! Supports ELF64 (x86-64) format with:
 //! - Program header loading (PT_LOAD segments)
 //! - Position-independent executables (PIE)
+//! - Thread-local storage templates (PT_TLS)
+//! - Dynamic sections (PT_DYNAMIC), for the Grove Linker
 //! - Basic validation and security checks
 
+use alloc::string::String;
 use alloc::vec::Vec;
 use core::mem::size_of;
 
@@ -43,6 +46,31 @@ const EM_X86_64: u16 = 62;
 /// Program header type: loadable segment
 const PT_LOAD: u32 = 1;
 
+/// Program header type: dynamic linking information
+const PT_DYNAMIC: u32 = 2;
+
+/// Program header type: path of the program interpreter
+const PT_INTERP: u32 = 3;
+
+/// Program header type: the program header table itself
+const PT_PHDR: u32 = 6;
+
+/// Program header type: thread-local storage template
+const PT_TLS: u32 = 7;
+
+/// Where position-independent programs linked at address 0 are loaded
+pub const PIE_BASE: u64 = 0x0000_0000_0040_0000;
+
+/// Largest TLS block a program may ask every thread for
+const MAX_TLS_SIZE: u64 = 0x10000;
+
+/// Bytes reserved at the thread pointer for the thread control block
+///
+/// Only its first word, a pointer to itself, is filled in; the rest stays
+/// zero for runtimes that keep their own fields there (e.g. a stack guard
+/// at `fs:0x28`).
+pub const TCB_SIZE: u64 = 0x40;
+
 /// Program header flags: executable
 const PF_X: u32 = 1;
 
@@ -133,6 +161,8 @@ pub enum ElfError {
     SegmentOverlapsKernel,
     /// No loadable segments found
     NoLoadableSegments,
+    /// The TLS template is malformed, too large, or not loaded
+    InvalidTls,
 }
 
 impl core::fmt::Display for ElfError {
@@ -149,6 +179,7 @@ impl core::fmt::Display for ElfError {
             ElfError::InvalidAlignment => write!(f, "Invalid segment alignment"),
             ElfError::SegmentOverlapsKernel => write!(f, "Segment overlaps kernel space"),
             ElfError::NoLoadableSegments => write!(f, "No loadable segments"),
+            ElfError::InvalidTls => write!(f, "Invalid TLS segment"),
         }
     }
 }
@@ -158,10 +189,103 @@ impl core::fmt::Display for ElfError {
 pub struct LoadedElf {
     /// Entry point address
     pub entry_point: u64,
-    /// Base address where the ELF was loaded (for PIE)
+    /// How far the image was moved from its link-time addresses (for PIE)
     pub base_address: u64,
+    /// Whether the file is position-independent (ET_DYN)
+    pub position_independent: bool,
     /// Loaded segments
     pub segments: Vec<LoadedSegment>,
+    /// Where the program header table is in memory (0 if no segment loads it)
+    pub program_headers: u64,
+    /// Size of one program header
+    pub phentsize: u16,
+    /// Number of program headers
+    pub phnum: u16,
+    /// The thread-local storage template, if the program has one
+    pub tls: Option<TlsTemplate>,
+    /// File offset and size of the dynamic section (PT_DYNAMIC), if any
+    pub dynamic: Option<(u64, u64)>,
+    /// The program interpreter named by PT_INTERP, if any
+    pub interpreter: Option<String>,
+}
+
+impl LoadedElf {
+    /// The page-aligned range `[start, end)` covered by the loaded segments
+    pub fn image_span(&self) -> (u64, u64) {
+        let start = self.segments.iter().map(|s| s.vaddr).min().unwrap_or(0) & !0xFFF;
+        let end = self.segments.iter().map(|s| s.vaddr + s.memsz).max().unwrap_or(0);
+        (start, (end + 0xFFF) & !0xFFF)
+    }
+
+    /// Move the whole image `bias` bytes up from where it is now
+    pub fn rebased(mut self, bias: u64) -> Self {
+        self.entry_point += bias;
+        self.base_address += bias;
+        for segment in &mut self.segments {
+            segment.vaddr += bias;
+        }
+        if self.program_headers != 0 {
+            self.program_headers += bias;
+        }
+        if let Some(tls) = &mut self.tls {
+            tls.vaddr += bias;
+        }
+        self
+    }
+}
+
+/// The initialization image of a program's thread-local storage
+///
+/// Every thread gets a copy: the first `filesz` bytes (.tdata) from the
+/// image at `vaddr`, and zeros up to `memsz` (.tbss).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TlsTemplate {
+    /// Where the image is in memory (inside a PT_LOAD segment)
+    pub vaddr: u64,
+    /// Size of the initialized part
+    pub filesz: u64,
+    /// Size of one thread's TLS data
+    pub memsz: u64,
+    /// Alignment of the TLS data (a power of two, at most a page)
+    pub align: u64,
+}
+
+/// Where the parts of one thread's TLS block go, as offsets from its base
+///
+/// The x86-64 ABI uses TLS variant II: the data ends at the thread pointer
+/// (the FS base), where the thread control block begins. The linker
+/// addresses the data at `-align_up(memsz, align)` from the thread pointer.
+///
+/// ```text
+///   base →  (padding)
+///   data →  .tdata, then .tbss
+///   tp   →  TCB: pointer to itself, then zeros
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TlsLayout {
+    /// Offset of the TLS data
+    pub data: u64,
+    /// Offset of the thread pointer
+    pub thread_pointer: u64,
+    /// Size of the whole block
+    pub size: u64,
+}
+
+impl TlsTemplate {
+    /// Lay out one thread's TLS block
+    ///
+    /// The thread pointer is aligned to the data's alignment, and to at
+    /// least 16 bytes for the TCB, given a block base aligned as well.
+    pub fn layout(&self) -> TlsLayout {
+        let align = self.align.max(1);
+        let tp_offset = self.memsz.next_multiple_of(align);
+        let thread_pointer = tp_offset.next_multiple_of(align.max(16));
+        TlsLayout {
+            data: thread_pointer - tp_offset,
+            thread_pointer,
+            size: thread_pointer + TCB_SIZE,
+        }
+    }
 }
 
 /// Information about a loaded segment
@@ -385,11 +509,11 @@ fn validate_segment(phdr: &Elf64Phdr) -> Result<(), ElfError> {
     Ok(())
 }
 
-/// Load an ELF file (validation only for now)
+/// Parse and validate an ELF program, placing it where it will be loaded
 ///
-/// This function parses and validates an ELF file but doesn't actually
-/// load it into memory yet. Full loading will be implemented when we
-/// have page table support for user address spaces.
+/// Executables stay at their link-time addresses. PIEs are moved up to
+/// `PIE_BASE` if they were linked below it; the bias is deterministic, so
+/// every call for the same file gives the same addresses.
 ///
 /// # Arguments
 ///
@@ -400,7 +524,38 @@ fn validate_segment(phdr: &Elf64Phdr) -> Result<(), ElfError> {
 /// * `Ok(LoadedElf)` - Information about the ELF file
 /// * `Err(ElfError)` - Load error
 pub fn load_elf(data: &[u8]) -> Result<LoadedElf, ElfError> {
-    crate::serial_println!("[ELF] load_elf called");
+    let elf = parse_elf(data)?;
+    let bias = if elf.position_independent {
+        pie_bias(elf.image_span().0)
+    } else {
+        0
+    };
+    Ok(elf.rebased(bias))
+}
+
+/// How far to move a PIE whose lowest page is `lowest_page`
+///
+/// PIEs linked at or above `PIE_BASE`, as the AethelOS user linker script
+/// does, load where they were linked.
+fn pie_bias(lowest_page: u64) -> u64 {
+    PIE_BASE.saturating_sub(lowest_page)
+}
+
+/// Parse and validate an ELF file at its link-time addresses
+///
+/// Nothing is moved: for a PIE or Grove the caller chooses a bias and
+/// applies it with `LoadedElf::rebased`.
+///
+/// # Arguments
+///
+/// * `data` - The ELF file data
+///
+/// # Returns
+///
+/// * `Ok(LoadedElf)` - Information about the ELF file
+/// * `Err(ElfError)` - Load error
+pub fn parse_elf(data: &[u8]) -> Result<LoadedElf, ElfError> {
+    crate::serial_println!("[ELF] parse_elf called");
     // Parse and validate ELF header
     let header = parse_elf_header(data)?;
     crate::serial_println!("[ELF] parse_elf_header returned");
@@ -416,6 +571,9 @@ pub fn load_elf(data: &[u8]) -> Result<LoadedElf, ElfError> {
     // Validate and collect loadable segments
     let mut segments = Vec::new();
     let mut has_loadable = false;
+    let mut tls = None;
+    let mut dynamic = None;
+    let mut interpreter = None;
 
     for phdr in &phdrs {
         if phdr.p_type == PT_LOAD {
@@ -440,6 +598,14 @@ pub fn load_elf(data: &[u8]) -> Result<LoadedElf, ElfError> {
                 file_offset: phdr.p_offset,
                 flags: phdr.p_flags,
             });
+        } else if phdr.p_type == PT_TLS {
+            tls = Some(tls_template(phdr, &phdrs)?);
+            crate::serial_println!("[ELF]   TLS: vaddr={:#x} filesz={:#x} memsz={:#x} align={:#x}",
+                phdr.p_vaddr, phdr.p_filesz, phdr.p_memsz, phdr.p_align);
+        } else if phdr.p_type == PT_DYNAMIC {
+            dynamic = Some((phdr.p_offset, phdr.p_filesz));
+        } else if phdr.p_type == PT_INTERP {
+            interpreter = interpreter_path(data, phdr);
         }
     }
 
@@ -447,23 +613,77 @@ pub fn load_elf(data: &[u8]) -> Result<LoadedElf, ElfError> {
         return Err(ElfError::NoLoadableSegments);
     }
 
-    // For PIE executables, we'll need to choose a base address
-    // For now, we'll use 0x400000 as a placeholder
-    let base_address = if header.e_type == ET_DYN {
-        0x0000_0000_0040_0000
-    } else {
-        0
-    };
-
     crate::serial_println!("[ELF] ✓ ELF file validated successfully");
 
     Ok(LoadedElf {
-        entry_point: base_address + header.e_entry,
-        base_address,
+        entry_point: header.e_entry,
+        base_address: 0,
+        position_independent: header.e_type == ET_DYN,
         segments,
+        program_headers: program_header_address(&phdrs, header.e_phoff),
+        phentsize: header.e_phentsize,
+        phnum: header.e_phnum,
+        tls,
+        dynamic,
+        interpreter,
     })
 }
 
+/// Read the NUL-terminated path a PT_INTERP segment names
+fn interpreter_path(data: &[u8], phdr: &Elf64Phdr) -> Option<String> {
+    let start = usize::try_from(phdr.p_offset).ok()?;
+    let end = start.checked_add(usize::try_from(phdr.p_filesz).ok()?)?;
+    let bytes = data.get(start..end)?;
+    let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    core::str::from_utf8(&bytes[..len]).ok().map(String::from)
+}
+
+/// Check a PT_TLS header and turn it into a template
+///
+/// The initialized part must lie in the file-backed part of a PT_LOAD
+/// segment, so each new thread's copy can be taken from the loaded image.
+fn tls_template(phdr: &Elf64Phdr, phdrs: &[Elf64Phdr]) -> Result<TlsTemplate, ElfError> {
+    let align = phdr.p_align.max(1);
+    if !align.is_power_of_two() || align > 0x1000 {
+        return Err(ElfError::InvalidTls);
+    }
+    if phdr.p_filesz > phdr.p_memsz || phdr.p_memsz > MAX_TLS_SIZE {
+        return Err(ElfError::InvalidTls);
+    }
+
+    let image_end = phdr.p_vaddr.checked_add(phdr.p_filesz).ok_or(ElfError::InvalidTls)?;
+    let loaded = phdr.p_filesz == 0
+        || phdrs.iter().any(|p| {
+            p.p_type == PT_LOAD && p.p_vaddr <= phdr.p_vaddr && image_end <= p.p_vaddr + p.p_filesz
+        });
+    if !loaded {
+        return Err(ElfError::InvalidTls);
+    }
+
+    Ok(TlsTemplate {
+        vaddr: phdr.p_vaddr,
+        filesz: phdr.p_filesz,
+        memsz: phdr.p_memsz,
+        align,
+    })
+}
+
+/// Find where the program header table lands in memory
+///
+/// Uses `PT_PHDR` when present, otherwise the `PT_LOAD` segment whose file
+/// contents cover the table. Returns 0 if the table is not loaded.
+fn program_header_address(phdrs: &[Elf64Phdr], phoff: u64) -> u64 {
+    if let Some(phdr) = phdrs.iter().find(|p| p.p_type == PT_PHDR) {
+        return phdr.p_vaddr;
+    }
+
+    phdrs
+        .iter()
+        .find(|p| p.p_type == PT_LOAD && p.p_offset <= phoff && phoff < p.p_offset + p.p_filesz)
+        .map(|p| p.p_vaddr + (phoff - p.p_offset))
+        .unwrap_or(0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -492,4 +712,128 @@ mod tests {
         let result = parse_elf_header(&data);
         assert_eq!(result, Err(ElfError::InvalidMagic));
     }
+
+    fn phdr(p_type: u32, p_offset: u64, p_vaddr: u64, p_filesz: u64) -> Elf64Phdr {
+        Elf64Phdr {
+            p_type,
+            p_flags: PF_R,
+            p_offset,
+            p_vaddr,
+            p_paddr: p_vaddr,
+            p_filesz,
+            p_memsz: p_filesz,
+            p_align: 0x1000,
+        }
+    }
+
+    #[test]
+    fn test_pie_bias() {
+        // Linked at 0: moved up to PIE_BASE
+        assert_eq!(pie_bias(0), PIE_BASE);
+        assert_eq!(pie_bias(0x1000), PIE_BASE - 0x1000);
+
+        // Linked at or above PIE_BASE: loaded where linked
+        assert_eq!(pie_bias(PIE_BASE), 0);
+        assert_eq!(pie_bias(0x200_0000), 0);
+    }
+
+    #[test]
+    fn test_rebased_moves_every_address() {
+        let elf = LoadedElf {
+            entry_point: 0x1040,
+            base_address: 0,
+            position_independent: true,
+            segments: alloc::vec![
+                LoadedSegment { vaddr: 0, memsz: 0x1200, filesz: 0x1200, file_offset: 0, flags: PF_R | PF_X },
+                LoadedSegment { vaddr: 0x2200, memsz: 0x100, filesz: 0x80, file_offset: 0x1200, flags: PF_R | PF_W },
+            ],
+            program_headers: 0x40,
+            phentsize: 56,
+            phnum: 4,
+            tls: Some(TlsTemplate { vaddr: 0x2200, filesz: 0x10, memsz: 0x20, align: 8 }),
+            dynamic: Some((0x1100, 0x100)),
+            interpreter: None,
+        };
+        assert_eq!(elf.image_span(), (0, 0x3000));
+
+        let elf = elf.rebased(PIE_BASE);
+        assert_eq!(elf.entry_point, PIE_BASE + 0x1040);
+        assert_eq!(elf.base_address, PIE_BASE);
+        assert_eq!(elf.image_span(), (PIE_BASE, PIE_BASE + 0x3000));
+        assert_eq!(elf.program_headers, PIE_BASE + 0x40);
+        assert_eq!(elf.tls.unwrap().vaddr, PIE_BASE + 0x2200);
+        // File offsets stay put
+        assert_eq!(elf.segments[1].file_offset, 0x1200);
+        assert_eq!(elf.dynamic, Some((0x1100, 0x100)));
+    }
+
+    #[test]
+    fn test_interpreter_path() {
+        let data = b"....../lib/ld-grove.so\0";
+        let interp = phdr(PT_INTERP, 6, 0, 17);
+        assert_eq!(interpreter_path(data, &interp).as_deref(), Some("/lib/ld-grove.so"));
+
+        let past_end = phdr(PT_INTERP, 6, 0, 64);
+        assert_eq!(interpreter_path(data, &past_end), None);
+    }
+
+    #[test]
+    fn test_program_header_address() {
+        // Found through the PT_LOAD segment covering the table
+        let phdrs = [phdr(PT_LOAD, 0, 0x200000, 0x1000)];
+        assert_eq!(program_header_address(&phdrs, 64), 0x200040);
+
+        // PT_PHDR wins when present
+        let phdrs = [phdr(PT_LOAD, 0, 0x200000, 0x1000), phdr(PT_PHDR, 64, 0x300040, 0x70)];
+        assert_eq!(program_header_address(&phdrs, 64), 0x300040);
+
+        // Not loaded at all
+        let phdrs = [phdr(PT_LOAD, 0x1000, 0x201000, 0x1000)];
+        assert_eq!(program_header_address(&phdrs, 64), 0);
+    }
+
+    fn tls_phdr(p_vaddr: u64, p_filesz: u64, p_memsz: u64, p_align: u64) -> Elf64Phdr {
+        Elf64Phdr { p_memsz, p_align, ..phdr(PT_TLS, 0x1800, p_vaddr, p_filesz) }
+    }
+
+    #[test]
+    fn test_tls_template() {
+        let load = phdr(PT_LOAD, 0x1000, 0x201000, 0x1000);
+
+        let template = tls_template(&tls_phdr(0x201800, 0x10, 0x30, 8), &[load]).unwrap();
+        assert_eq!((template.vaddr, template.filesz, template.memsz, template.align), (0x201800, 0x10, 0x30, 8));
+
+        // The image must be loaded, unless it is all .tbss
+        assert_eq!(tls_template(&tls_phdr(0x201ff8, 0x10, 0x10, 8), &[load]), Err(ElfError::InvalidTls));
+        assert!(tls_template(&tls_phdr(0x500000, 0, 0x10, 8), &[load]).is_ok());
+
+        assert_eq!(tls_template(&tls_phdr(0x201800, 0x20, 0x10, 8), &[load]), Err(ElfError::InvalidTls));
+        assert_eq!(tls_template(&tls_phdr(0x201800, 0, 0x10, 24), &[load]), Err(ElfError::InvalidTls));
+        assert_eq!(tls_template(&tls_phdr(0x201800, 0, 0x10, 0x2000), &[load]), Err(ElfError::InvalidTls));
+        assert_eq!(tls_template(&tls_phdr(0x201800, 0, MAX_TLS_SIZE + 1, 8), &[load]), Err(ElfError::InvalidTls));
+    }
+
+    #[test]
+    fn test_tls_layout() {
+        let template = |memsz, align| TlsTemplate { vaddr: 0, filesz: 0, memsz, align };
+
+        // The data ends exactly at the thread pointer
+        let layout = template(0x30, 16).layout();
+        assert_eq!(layout, TlsLayout { data: 0, thread_pointer: 0x30, size: 0x30 + TCB_SIZE });
+
+        // The linker rounds memsz to the alignment; the thread pointer is
+        // then padded up to 16 bytes
+        let layout = template(0x0d, 4).layout();
+        assert_eq!(layout.thread_pointer - layout.data, 0x10);
+        assert_eq!(layout.thread_pointer % 16, 0);
+
+        let layout = template(0x0d, 1).layout();
+        assert_eq!((layout.data, layout.thread_pointer), (3, 0x10));
+
+        // Large alignments carry over to the thread pointer
+        let layout = template(0x10, 64).layout();
+        assert_eq!((layout.data, layout.thread_pointer), (0, 0x40));
+        let layout = template(0x50, 64).layout();
+        assert_eq!((layout.data, layout.thread_pointer), (0, 0x80));
+    }
 }
@@ -0,0 +1,980 @@
+//! # Grove Linker - Binding Vessels to Their Groves
+//!
+//! A Grove is a shared library: a position-independent ELF object
+//! (ET_DYN) whose code many programs use instead of each carrying its own
+//! copy. The Heartwood links Groves itself while it builds a Vessel, so no
+//! user-space interpreter is needed; a program's PT_INTERP is noted and
+//! otherwise ignored.
+//!
+//! ## Linking a Vessel
+//!
+//! 1. `gather_groves` follows the program's DT_NEEDED entries breadth
+//!    first, reading each Grove from `/lib`. This happens before the
+//!    Harbor is locked, since reading a file may wait on the disk.
+//! 2. `link` maps every Grove at a random base in the ASLR Grove window.
+//! 3. The relocations of the program and of every Grove are applied.
+//!    Symbols are looked up in the program first, then in the Groves in
+//!    load order, through DT_GNU_HASH or DT_HASH.
+//!
+//! Everything is bound at load time; there is no lazy PLT binding. The
+//! relocations understood are R_X86_64_NONE, RELATIVE, 64, GLOB_DAT and
+//! JUMP_SLOT. COPY relocations (from non-PIE programs) and Groves with
+//! thread-local storage are refused.
+
+use super::elf_loader::{parse_elf, ElfError, LoadedElf};
+use crate::mana_pool::aslr;
+use crate::mana_pool::elf_relocations::{Elf64Rela, RelocationType};
+use crate::mana_pool::user_space::{MemoryRegion, RegionType, UserAddressSpace};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use x86_64::VirtAddr;
+
+/// Directory searched for Groves named without a path
+pub const GROVE_PATH: &str = "/lib";
+
+/// Most Groves a single program may pull in
+pub const MAX_GROVES: usize = 16;
+
+/// How many random bases are tried for each Grove
+const GROVE_PLACEMENT_ATTEMPTS: usize = 16;
+
+/// Dynamic tag: end of the dynamic section
+const DT_NULL: u64 = 0;
+
+/// Dynamic tag: string table offset of a needed Grove's name
+const DT_NEEDED: u64 = 1;
+
+/// Dynamic tag: size of the PLT relocations
+const DT_PLTRELSZ: u64 = 2;
+
+/// Dynamic tag: System V symbol hash table
+const DT_HASH: u64 = 4;
+
+/// Dynamic tag: string table
+const DT_STRTAB: u64 = 5;
+
+/// Dynamic tag: symbol table
+const DT_SYMTAB: u64 = 6;
+
+/// Dynamic tag: RELA relocations
+const DT_RELA: u64 = 7;
+
+/// Dynamic tag: size of the RELA relocations
+const DT_RELASZ: u64 = 8;
+
+/// Dynamic tag: size of one RELA relocation
+const DT_RELAENT: u64 = 9;
+
+/// Dynamic tag: size of the string table
+const DT_STRSZ: u64 = 10;
+
+/// Dynamic tag: size of one symbol
+const DT_SYMENT: u64 = 11;
+
+/// Dynamic tag: REL relocations (without addends)
+const DT_REL: u64 = 17;
+
+/// Dynamic tag: kind of the PLT relocations (DT_REL or DT_RELA)
+const DT_PLTREL: u64 = 20;
+
+/// Dynamic tag: PLT relocations
+const DT_JMPREL: u64 = 23;
+
+/// Dynamic tag: GNU symbol hash table
+const DT_GNU_HASH: u64 = 0x6fff_fef5;
+
+/// Size of one dynamic section entry
+const DYN_SIZE: usize = 16;
+
+/// Size of one symbol table entry
+const SYM_SIZE: u64 = 24;
+
+/// Size of one RELA relocation
+const RELA_SIZE: u64 = 24;
+
+/// Symbol section index: undefined
+const SHN_UNDEF: u16 = 0;
+
+/// Symbol section index: absolute value, not moved with the object
+const SHN_ABS: u16 = 0xfff1;
+
+/// Symbol binding: local to its object
+const STB_LOCAL: u8 = 0;
+
+/// Symbol binding: global
+const STB_GLOBAL: u8 = 1;
+
+/// Symbol binding: weak (may stay undefined)
+const STB_WEAK: u8 = 2;
+
+/// Symbol binding: global, unique across the process
+const STB_GNU_UNIQUE: u8 = 10;
+
+/// Symbol type: thread-local variable
+const STT_TLS: u8 = 6;
+
+/// A Grove read from the VFS, waiting to be linked into a Vessel
+#[derive(Debug, Clone)]
+pub struct Grove {
+    /// The name a DT_NEEDED entry gave it
+    pub name: String,
+    /// The whole ELF file
+    pub data: Vec<u8>,
+}
+
+/// Why a program and its Groves could not be linked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkError {
+    /// A needed Grove is not in the VFS
+    MissingGrove,
+    /// A Grove is not a valid ELF file
+    BadGrove(ElfError),
+    /// A Grove is not position-independent (ET_DYN)
+    NotShared,
+    /// A Grove has thread-local storage
+    GroveTls,
+    /// The program needs more than `MAX_GROVES` Groves
+    TooManyGroves,
+    /// A dynamic section, or a table it points at, is malformed
+    Malformed(&'static str),
+    /// No object defines a symbol that is needed
+    UndefinedSymbol,
+    /// A relocation type the linker does not handle
+    UnsupportedRelocation(u32),
+    /// A Grove could not be mapped, or a relocation written
+    Mapping(&'static str),
+}
+
+impl LinkError {
+    /// A short description, for callers that report `&'static str` errors
+    pub fn description(&self) -> &'static str {
+        match self {
+            LinkError::MissingGrove => "Needed Grove not found",
+            LinkError::BadGrove(_) => "Grove is not a valid ELF file",
+            LinkError::NotShared => "Grove is not a shared object",
+            LinkError::GroveTls => "Groves with thread-local storage are not supported",
+            LinkError::TooManyGroves => "Too many Groves",
+            LinkError::Malformed(e) => e,
+            LinkError::UndefinedSymbol => "Undefined symbol",
+            LinkError::UnsupportedRelocation(_) => "Unsupported relocation",
+            LinkError::Mapping(e) => e,
+        }
+    }
+}
+
+impl core::fmt::Display for LinkError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            LinkError::BadGrove(e) => write!(f, "Bad Grove: {}", e),
+            LinkError::UnsupportedRelocation(kind) => write!(f, "Unsupported relocation type {}", kind),
+            other => write!(f, "{}", other.description()),
+        }
+    }
+}
+
+/// The parts of a dynamic section the linker uses
+///
+/// Table addresses are link-time addresses, before the object's bias.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DynamicInfo {
+    /// String table offsets of the needed Groves' names, in order
+    pub needed: Vec<u64>,
+    /// System V hash table (DT_HASH)
+    pub hash: Option<u64>,
+    /// GNU hash table (DT_GNU_HASH)
+    pub gnu_hash: Option<u64>,
+    /// Symbol table
+    pub symtab: Option<u64>,
+    /// String table
+    pub strtab: Option<u64>,
+    /// Size of the string table
+    pub strsz: u64,
+    /// RELA relocations
+    pub rela: Option<u64>,
+    /// Size of the RELA relocations
+    pub relasz: u64,
+    /// PLT relocations (always RELA)
+    pub jmprel: Option<u64>,
+    /// Size of the PLT relocations
+    pub pltrelsz: u64,
+}
+
+/// Parse a dynamic section, up to its DT_NULL entry
+///
+/// Objects with REL relocations are refused: x86-64 objects use RELA.
+pub fn parse_dynamic(section: &[u8]) -> Result<DynamicInfo, LinkError> {
+    let mut info = DynamicInfo::default();
+
+    for entry in section.chunks_exact(DYN_SIZE) {
+        let value = le_u64(&entry[8..]);
+        match le_u64(entry) {
+            DT_NULL => break,
+            DT_NEEDED => info.needed.push(value),
+            DT_HASH => info.hash = Some(value),
+            DT_GNU_HASH => info.gnu_hash = Some(value),
+            DT_SYMTAB => info.symtab = Some(value),
+            DT_STRTAB => info.strtab = Some(value),
+            DT_STRSZ => info.strsz = value,
+            DT_RELA => info.rela = Some(value),
+            DT_RELASZ => info.relasz = value,
+            DT_JMPREL => info.jmprel = Some(value),
+            DT_PLTRELSZ => info.pltrelsz = value,
+            DT_RELAENT if value != RELA_SIZE => return Err(LinkError::Malformed("Unexpected relocation size")),
+            DT_SYMENT if value != SYM_SIZE => return Err(LinkError::Malformed("Unexpected symbol size")),
+            DT_REL => return Err(LinkError::Malformed("REL relocations are not supported")),
+            DT_PLTREL if value != DT_RELA => {
+                return Err(LinkError::Malformed("REL relocations are not supported"));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(info)
+}
+
+/// The System V hash of a symbol name (DT_HASH)
+pub fn elf_hash(name: &[u8]) -> u32 {
+    let mut hash: u32 = 0;
+    for &c in name {
+        hash = (hash << 4).wrapping_add(c as u32);
+        let high = hash & 0xf000_0000;
+        hash ^= high >> 24;
+        hash &= !high;
+    }
+    hash
+}
+
+/// The GNU hash of a symbol name (DT_GNU_HASH)
+pub fn gnu_hash(name: &[u8]) -> u32 {
+    name.iter().fold(5381u32, |hash, &c| hash.wrapping_mul(33).wrapping_add(c as u32))
+}
+
+/// The value a relocation stores, or `None` for R_X86_64_NONE
+///
+/// # Arguments
+///
+/// * `kind` - The relocation type
+/// * `symbol` - Address of the relocation's symbol (S)
+/// * `addend` - The relocation's addend (A)
+/// * `bias` - How far the object was moved from its link-time addresses (B)
+pub fn relocation_value(
+    kind: RelocationType,
+    symbol: u64,
+    addend: i64,
+    bias: u64,
+) -> Result<Option<u64>, LinkError> {
+    match kind {
+        RelocationType::None => Ok(None),
+        RelocationType::Relative => Ok(Some(bias.wrapping_add_signed(addend))),
+        RelocationType::R64 => Ok(Some(symbol.wrapping_add_signed(addend))),
+        RelocationType::GlobDAT | RelocationType::JumpSlot => Ok(Some(symbol)),
+        other => Err(LinkError::UnsupportedRelocation(other as u32)),
+    }
+}
+
+/// Where the Grove a DT_NEEDED entry names is found
+pub fn grove_path(name: &str) -> String {
+    if name.starts_with('/') {
+        String::from(name)
+    } else {
+        format!("{}/{}", GROVE_PATH, name)
+    }
+}
+
+/// Read every Grove a program needs, following DT_NEEDED breadth first
+///
+/// `read` fetches a whole file by path. Call this before taking the Harbor
+/// lock, since reading may wait on the disk. A program without a dynamic
+/// section needs no Groves.
+///
+/// # Arguments
+///
+/// * `program` - The parsed program
+/// * `elf_data` - The program's ELF file
+/// * `read` - Reads a file from the VFS
+///
+/// # Returns
+///
+/// * `Ok(Vec<Grove>)` - The Groves, in load order
+/// * `Err(LinkError)` - A Grove is missing or malformed
+pub fn gather_groves(
+    program: &LoadedElf,
+    elf_data: &[u8],
+    mut read: impl FnMut(&str) -> Option<Vec<u8>>,
+) -> Result<Vec<Grove>, LinkError> {
+    let mut wanted = needed_names(program, elf_data)?;
+    let mut groves: Vec<Grove> = Vec::new();
+
+    while let Some(name) = wanted.get(groves.len()).cloned() {
+        if groves.len() == MAX_GROVES {
+            return Err(LinkError::TooManyGroves);
+        }
+
+        let path = grove_path(&name);
+        let Some(data) = read(&path) else {
+            crate::serial_println!("[GROVE] ✗ Needed Grove {} not found", path);
+            return Err(LinkError::MissingGrove);
+        };
+        let elf = parse_elf(&data).map_err(LinkError::BadGrove)?;
+        for dependency in needed_names(&elf, &data)? {
+            if !wanted.contains(&dependency) {
+                wanted.push(dependency);
+            }
+        }
+
+        crate::serial_println!("[GROVE] Gathered {} ({} bytes)", path, data.len());
+        groves.push(Grove { name, data });
+    }
+
+    Ok(groves)
+}
+
+/// Map a program's Groves into its address space and apply every relocation
+///
+/// The program itself must already be mapped where `program` says. Each
+/// Grove is placed at a random base in the Grove window, then the program
+/// and the Groves are relocated in turn.
+///
+/// # Arguments
+///
+/// * `space` - The Vessel's address space
+/// * `program` - The loaded program
+/// * `elf_data` - The program's ELF file
+/// * `groves` - The Groves from `gather_groves`
+///
+/// # Returns
+///
+/// * `Ok(())` - Everything is mapped and bound
+/// * `Err(LinkError)` - Linking failed; the address space is half-built
+pub fn link(
+    space: &mut UserAddressSpace,
+    program: &LoadedElf,
+    elf_data: &[u8],
+    groves: &[Grove],
+) -> Result<(), LinkError> {
+    let mut placed = Vec::with_capacity(groves.len());
+    for grove in groves {
+        placed.push(place_grove(space, grove)?);
+    }
+
+    let mut objects = Vec::with_capacity(groves.len() + 1);
+    objects.extend(LinkedObject::new(elf_data, program)?);
+    for (grove, elf) in groves.iter().zip(&placed) {
+        objects.extend(LinkedObject::new(&grove.data, elf)?);
+    }
+
+    for object in &objects {
+        let count = relocate(space, &objects, object)?;
+        if count > 0 {
+            crate::serial_println!("[GROVE] Applied {} relocations at base {:#x}",
+                count, object.elf.base_address);
+        }
+    }
+
+    Ok(())
+}
+
+/// Map one Grove at a random free base in the Grove window
+fn place_grove(space: &mut UserAddressSpace, grove: &Grove) -> Result<LoadedElf, LinkError> {
+    let elf = parse_elf(&grove.data).map_err(LinkError::BadGrove)?;
+    if !elf.position_independent {
+        return Err(LinkError::NotShared);
+    }
+    if elf.tls.is_some() {
+        return Err(LinkError::GroveTls);
+    }
+
+    let (start, end) = elf.image_span();
+    let size = end - start;
+
+    for _ in 0..GROVE_PLACEMENT_ATTEMPTS {
+        let base = aslr::randomize_grove_base(size);
+        let probe = MemoryRegion::new(VirtAddr::new(base), size, RegionType::Code);
+        if space.regions.iter().any(|r| r.overlaps(&probe)) {
+            continue;
+        }
+
+        let elf = elf.rebased(base - start);
+        space.map_elf_image(&elf.segments, &grove.data).map_err(LinkError::Mapping)?;
+        crate::serial_println!("[GROVE] Mapped {} at {:#x} ({:#x} bytes)", grove.name, base, size);
+        return Ok(elf);
+    }
+
+    Err(LinkError::Mapping("No room for Grove in address space"))
+}
+
+/// The names in an object's DT_NEEDED entries
+fn needed_names(elf: &LoadedElf, data: &[u8]) -> Result<Vec<String>, LinkError> {
+    let Some(object) = LinkedObject::new(data, elf)? else {
+        return Ok(Vec::new());
+    };
+
+    object
+        .dynamic
+        .needed
+        .iter()
+        .map(|&offset| {
+            let name = object.string(offset)?;
+            core::str::from_utf8(name)
+                .map(String::from)
+                .map_err(|_| LinkError::Malformed("Grove name is not UTF-8"))
+        })
+        .collect()
+}
+
+/// Apply every relocation of `object`, returning how many there were
+fn relocate(
+    space: &UserAddressSpace,
+    objects: &[LinkedObject],
+    object: &LinkedObject,
+) -> Result<usize, LinkError> {
+    let bias = object.elf.base_address;
+    let relocations = object.relocations()?;
+
+    for rela in &relocations {
+        let kind = rela
+            .relocation_type()
+            .ok_or(LinkError::UnsupportedRelocation(rela.get_type()))?;
+        let symbol = match kind {
+            RelocationType::R64 | RelocationType::GlobDAT | RelocationType::JumpSlot => {
+                resolve(objects, object, rela.get_symbol())?
+            }
+            _ => 0,
+        };
+
+        if let Some(value) = relocation_value(kind, symbol, rela.r_addend, bias)? {
+            let target = bias
+                .checked_add(rela.r_offset)
+                .and_then(|addr| VirtAddr::try_new(addr).ok())
+                .ok_or(LinkError::Malformed("Relocation outside the address space"))?;
+            space.write_mapped(target, &value.to_le_bytes()).map_err(LinkError::Mapping)?;
+        }
+    }
+
+    Ok(relocations.len())
+}
+
+/// Find the address a relocation's symbol refers to
+///
+/// Local symbols bind within their own object. Everything else is looked
+/// up across `objects` in order, so the program can interpose on its
+/// Groves; an undefined weak symbol is 0.
+fn resolve(objects: &[LinkedObject], object: &LinkedObject, index: u32) -> Result<u64, LinkError> {
+    if index == 0 {
+        return Ok(0);
+    }
+
+    let symbol = object.symbol(index)?;
+    if symbol.binding() == STB_LOCAL {
+        return object.address_of(&symbol);
+    }
+
+    let name = object.string(symbol.name as u64)?;
+    for candidate in objects {
+        if let Some(address) = candidate.lookup(name)? {
+            return Ok(address);
+        }
+    }
+
+    if symbol.binding() == STB_WEAK {
+        return Ok(0);
+    }
+    crate::serial_println!("[GROVE] ✗ Undefined symbol: {}", String::from_utf8_lossy(name));
+    Err(LinkError::UndefinedSymbol)
+}
+
+/// An ELF64 symbol table entry (the fields the linker uses)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Symbol {
+    /// String table offset of the name
+    name: u32,
+    /// Binding (high nibble) and type (low nibble)
+    info: u8,
+    /// Section the symbol is defined in
+    shndx: u16,
+    /// Link-time address
+    value: u64,
+}
+
+impl Symbol {
+    fn parse(bytes: &[u8]) -> Self {
+        Self {
+            name: le_u32(bytes),
+            info: bytes[4],
+            shndx: u16::from_le_bytes([bytes[6], bytes[7]]),
+            value: le_u64(&bytes[8..]),
+        }
+    }
+
+    fn binding(&self) -> u8 {
+        self.info >> 4
+    }
+
+    /// Whether other objects may bind to this symbol
+    fn is_exported_definition(&self) -> bool {
+        self.shndx != SHN_UNDEF
+            && self.info & 0xf != STT_TLS
+            && matches!(self.binding(), STB_GLOBAL | STB_WEAK | STB_GNU_UNIQUE)
+    }
+}
+
+/// One ELF object in a Vessel, as the linker sees it
+///
+/// Its tables are read from the file rather than from the loaded image,
+/// through the segments that map them.
+struct LinkedObject<'a> {
+    /// The ELF file
+    data: &'a [u8],
+    /// Where the object was loaded
+    elf: &'a LoadedElf,
+    /// Its dynamic section
+    dynamic: DynamicInfo,
+}
+
+impl<'a> LinkedObject<'a> {
+    /// Read an object's dynamic section; `None` if it has none
+    fn new(data: &'a [u8], elf: &'a LoadedElf) -> Result<Option<Self>, LinkError> {
+        let Some((offset, size)) = elf.dynamic else {
+            return Ok(None);
+        };
+        let section = file_slice(data, offset, size)
+            .ok_or(LinkError::Malformed("Dynamic section out of bounds"))?;
+        Ok(Some(Self { data, elf, dynamic: parse_dynamic(section)? }))
+    }
+
+    /// The file bytes a segment loads at link-time address `addr`
+    fn bytes(&self, addr: u64, len: u64) -> Result<&'a [u8], LinkError> {
+        let bias = self.elf.base_address;
+        self.elf
+            .segments
+            .iter()
+            .find_map(|segment| {
+                let offset = addr.checked_sub(segment.vaddr.checked_sub(bias)?)?;
+                if offset.checked_add(len)? > segment.filesz {
+                    return None;
+                }
+                file_slice(self.data, segment.file_offset.checked_add(offset)?, len)
+            })
+            .ok_or(LinkError::Malformed("Dynamic table is not loaded from the file"))
+    }
+
+    /// The NUL-terminated string at `offset` in the string table
+    fn string(&self, offset: u64) -> Result<&'a [u8], LinkError> {
+        let strtab = self.dynamic.strtab.ok_or(LinkError::Malformed("No string table"))?;
+        if offset >= self.dynamic.strsz {
+            return Err(LinkError::Malformed("String out of bounds"));
+        }
+        let rest = &self.bytes(strtab, self.dynamic.strsz)?[offset as usize..];
+        let len = rest
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or(LinkError::Malformed("Unterminated string"))?;
+        Ok(&rest[..len])
+    }
+
+    /// Entry `index` of the symbol table
+    fn symbol(&self, index: u32) -> Result<Symbol, LinkError> {
+        let symtab = self.dynamic.symtab.ok_or(LinkError::Malformed("No symbol table"))?;
+        Ok(Symbol::parse(self.bytes(entry_address(symtab, index as u64, SYM_SIZE)?, SYM_SIZE)?))
+    }
+
+    /// Where a symbol of this object is in the Vessel
+    fn address_of(&self, symbol: &Symbol) -> Result<u64, LinkError> {
+        if symbol.shndx == SHN_ABS {
+            Ok(symbol.value)
+        } else {
+            self.elf
+                .base_address
+                .checked_add(symbol.value)
+                .ok_or(LinkError::Malformed("Symbol outside the address space"))
+        }
+    }
+
+    /// Where this object defines `name`, if it does
+    fn lookup(&self, name: &[u8]) -> Result<Option<u64>, LinkError> {
+        let symbol = if let Some(table) = self.dynamic.gnu_hash {
+            self.gnu_lookup(table, name)?
+        } else if let Some(table) = self.dynamic.hash {
+            self.sysv_lookup(table, name)?
+        } else {
+            None
+        };
+        symbol.map(|symbol| self.address_of(&symbol)).transpose()
+    }
+
+    /// Whether `symbol` is an exported definition of `name`
+    fn defines(&self, symbol: &Symbol, name: &[u8]) -> Result<bool, LinkError> {
+        Ok(symbol.is_exported_definition() && self.string(symbol.name as u64)? == name)
+    }
+
+    /// Look `name` up in a System V hash table
+    ///
+    /// ```text
+    ///   nbucket, nchain, bucket[nbucket], chain[nchain]   (all u32)
+    /// ```
+    fn sysv_lookup(&self, table: u64, name: &[u8]) -> Result<Option<Symbol>, LinkError> {
+        let header = self.bytes(table, 8)?;
+        let nbucket = le_u32(header) as u64;
+        let nchain = le_u32(&header[4..]) as u64;
+        if nbucket == 0 {
+            return Ok(None);
+        }
+
+        let buckets = entry_address(table, 2, 4)?;
+        let chains = entry_address(buckets, nbucket, 4)?;
+        let mut index = le_u32(self.bytes(entry_address(buckets, elf_hash(name) as u64 % nbucket, 4)?, 4)?) as u64;
+
+        // Index 0 ends a chain; a chain longer than the table is a loop
+        let mut steps = 0;
+        while index != 0 {
+            if index >= nchain || steps >= nchain {
+                return Err(LinkError::Malformed("Corrupt DT_HASH chain"));
+            }
+            let symbol = self.symbol(index as u32)?;
+            if self.defines(&symbol, name)? {
+                return Ok(Some(symbol));
+            }
+            index = le_u32(self.bytes(entry_address(chains, index, 4)?, 4)?) as u64;
+            steps += 1;
+        }
+
+        Ok(None)
+    }
+
+    /// Look `name` up in a GNU hash table
+    ///
+    /// ```text
+    ///   nbuckets, symoffset, bloom_size, bloom_shift      (u32)
+    ///   bloom[bloom_size]                                 (u64)
+    ///   buckets[nbuckets], chain[...]                     (u32)
+    /// ```
+    ///
+    /// Each chain holds the hashes of consecutive symbols from the bucket's
+    /// first one, with bit 0 set on the last.
+    fn gnu_lookup(&self, table: u64, name: &[u8]) -> Result<Option<Symbol>, LinkError> {
+        let header = self.bytes(table, 16)?;
+        let nbuckets = le_u32(header) as u64;
+        let symoffset = le_u32(&header[4..]) as u64;
+        let bloom_size = le_u32(&header[8..]) as u64;
+        let bloom_shift = le_u32(&header[12..]);
+        if nbuckets == 0 || bloom_size == 0 {
+            return Ok(None);
+        }
+        let hash = gnu_hash(name);
+
+        // The Bloom filter rules most names out without touching the chains
+        let bloom = entry_address(table, 4, 4)?;
+        let word = le_u64(self.bytes(entry_address(bloom, (hash as u64 / 64) % bloom_size, 8)?, 8)?);
+        let mask = (1u64 << (hash % 64)) | (1u64 << (hash.checked_shr(bloom_shift).unwrap_or(0) % 64));
+        if word & mask != mask {
+            return Ok(None);
+        }
+
+        let buckets = entry_address(bloom, bloom_size, 8)?;
+        let chains = entry_address(buckets, nbuckets, 4)?;
+        let mut index = le_u32(self.bytes(entry_address(buckets, hash as u64 % nbuckets, 4)?, 4)?) as u64;
+        if index == 0 || index < symoffset {
+            return Ok(None);
+        }
+
+        loop {
+            let chain_hash = le_u32(self.bytes(entry_address(chains, index - symoffset, 4)?, 4)?);
+            if chain_hash | 1 == hash | 1 {
+                let symbol = self.symbol(index as u32)?;
+                if self.defines(&symbol, name)? {
+                    return Ok(Some(symbol));
+                }
+            }
+            if chain_hash & 1 != 0 {
+                return Ok(None);
+            }
+            index += 1;
+        }
+    }
+
+    /// Every RELA relocation: DT_RELA, then DT_JMPREL
+    fn relocations(&self) -> Result<Vec<Elf64Rela>, LinkError> {
+        let tables = [
+            (self.dynamic.rela, self.dynamic.relasz),
+            (self.dynamic.jmprel, self.dynamic.pltrelsz),
+        ];
+
+        let mut relocations = Vec::new();
+        for (table, size) in tables {
+            let Some(table) = table else { continue };
+            let bytes = self.bytes(table, size)?;
+            relocations.extend(bytes.chunks_exact(RELA_SIZE as usize).map(|entry| Elf64Rela {
+                r_offset: le_u64(entry),
+                r_info: le_u64(&entry[8..]),
+                r_addend: le_u64(&entry[16..]) as i64,
+            }));
+        }
+        Ok(relocations)
+    }
+}
+
+/// Link-time address of entry `index` in a table of `size`-byte entries
+fn entry_address(table: u64, index: u64, size: u64) -> Result<u64, LinkError> {
+    index
+        .checked_mul(size)
+        .and_then(|offset| table.checked_add(offset))
+        .ok_or(LinkError::Malformed("Table entry outside the address space"))
+}
+
+/// `len` bytes of the file from `offset`, if they are all there
+fn file_slice(data: &[u8], offset: u64, len: u64) -> Option<&[u8]> {
+    let start = usize::try_from(offset).ok()?;
+    let end = start.checked_add(usize::try_from(len).ok()?)?;
+    data.get(start..end)
+}
+
+fn le_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn le_u64(bytes: &[u8]) -> u64 {
+    let mut word = [0u8; 8];
+    word.copy_from_slice(&bytes[..8]);
+    u64::from_le_bytes(word)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loom_of_fate::elf_loader::LoadedSegment;
+
+    #[test]
+    fn test_hashes() {
+        assert_eq!(elf_hash(b""), 0);
+        assert_eq!(elf_hash(b"printf"), 0x077905a6);
+        assert_eq!(elf_hash(b"exit"), 0x0006cf04);
+        assert_eq!(gnu_hash(b""), 0x00001505);
+        assert_eq!(gnu_hash(b"printf"), 0x156b2bb8);
+        assert_eq!(gnu_hash(b"exit"), 0x7c967e3f);
+    }
+
+    fn dyn_entry(section: &mut Vec<u8>, tag: u64, value: u64) {
+        section.extend_from_slice(&tag.to_le_bytes());
+        section.extend_from_slice(&value.to_le_bytes());
+    }
+
+    #[test]
+    fn test_parse_dynamic() {
+        let mut section = Vec::new();
+        dyn_entry(&mut section, DT_NEEDED, 1);
+        dyn_entry(&mut section, DT_NEEDED, 12);
+        dyn_entry(&mut section, DT_GNU_HASH, 0x300);
+        dyn_entry(&mut section, DT_SYMTAB, 0x400);
+        dyn_entry(&mut section, DT_STRTAB, 0x500);
+        dyn_entry(&mut section, DT_STRSZ, 0x40);
+        dyn_entry(&mut section, DT_RELA, 0x600);
+        dyn_entry(&mut section, DT_RELASZ, 48);
+        dyn_entry(&mut section, DT_RELAENT, 24);
+        dyn_entry(&mut section, DT_PLTREL, DT_RELA);
+        dyn_entry(&mut section, DT_NULL, 0);
+        dyn_entry(&mut section, DT_NEEDED, 99);
+
+        let info = parse_dynamic(&section).unwrap();
+        assert_eq!(info.needed, [1, 12]);
+        assert_eq!((info.gnu_hash, info.hash), (Some(0x300), None));
+        assert_eq!((info.symtab, info.strtab, info.strsz), (Some(0x400), Some(0x500), 0x40));
+        assert_eq!((info.rela, info.relasz, info.jmprel), (Some(0x600), 48, None));
+
+        let mut rel = Vec::new();
+        dyn_entry(&mut rel, DT_REL, 0x600);
+        assert!(parse_dynamic(&rel).is_err());
+
+        let mut plt_rel = Vec::new();
+        dyn_entry(&mut plt_rel, DT_PLTREL, DT_REL);
+        assert!(parse_dynamic(&plt_rel).is_err());
+    }
+
+    #[test]
+    fn test_relocation_value() {
+        let bias = 0x4000_0000_0000;
+        assert_eq!(relocation_value(RelocationType::None, 0, 0, bias), Ok(None));
+        assert_eq!(relocation_value(RelocationType::Relative, 0, 0x1234, bias), Ok(Some(bias + 0x1234)));
+        assert_eq!(relocation_value(RelocationType::R64, 0x5000, -8, bias), Ok(Some(0x4ff8)));
+        assert_eq!(relocation_value(RelocationType::GlobDAT, 0x5000, 8, bias), Ok(Some(0x5000)));
+        assert_eq!(relocation_value(RelocationType::JumpSlot, 0x5000, 0, bias), Ok(Some(0x5000)));
+        assert_eq!(relocation_value(RelocationType::Copy, 0x5000, 0, bias),
+            Err(LinkError::UnsupportedRelocation(5)));
+    }
+
+    #[test]
+    fn test_grove_path() {
+        assert_eq!(grove_path("libcorelib.so"), "/lib/libcorelib.so");
+        assert_eq!(grove_path("/opt/libfoo.so"), "/opt/libfoo.so");
+    }
+
+    /// Symbols of the test objects: (name, binding, defined)
+    const SYMBOLS: [(&str, u8, bool); 5] = [
+        ("", STB_LOCAL, false),
+        ("puts", STB_GLOBAL, false),
+        ("grove_main", STB_GLOBAL, true),
+        ("weak_thing", STB_WEAK, true),
+        ("helper", STB_GLOBAL, true),
+    ];
+
+    /// An object whose file is its image, linked at 0: a string table at
+    /// 0x100, symbols at 0x200 (each defined one at 0x1000 + 0x10 * index)
+    /// and a hash table at 0x400
+    fn test_object(hash_tag: u64, hash_table: Vec<u8>) -> (Vec<u8>, LoadedElf) {
+        let mut data = alloc::vec![0u8; 0x800];
+
+        let mut strtab = alloc::vec![0u8];
+        let mut symtab = Vec::new();
+        for (index, (name, binding, defined)) in SYMBOLS.iter().enumerate() {
+            let name_offset = if name.is_empty() { 0 } else { strtab.len() as u32 };
+            if !name.is_empty() {
+                strtab.extend_from_slice(name.as_bytes());
+                strtab.push(0);
+            }
+            symtab.extend_from_slice(&name_offset.to_le_bytes());
+            symtab.push(binding << 4 | 2);
+            symtab.push(0);
+            symtab.extend_from_slice(&(if *defined { 7u16 } else { SHN_UNDEF }).to_le_bytes());
+            let value = if *defined { 0x1000 + 0x10 * index as u64 } else { 0 };
+            symtab.extend_from_slice(&value.to_le_bytes());
+            symtab.extend_from_slice(&0u64.to_le_bytes());
+        }
+        data[0x100..0x100 + strtab.len()].copy_from_slice(&strtab);
+        data[0x200..0x200 + symtab.len()].copy_from_slice(&symtab);
+        data[0x400..0x400 + hash_table.len()].copy_from_slice(&hash_table);
+
+        let mut dynamic = Vec::new();
+        dyn_entry(&mut dynamic, DT_STRTAB, 0x100);
+        dyn_entry(&mut dynamic, DT_STRSZ, strtab.len() as u64);
+        dyn_entry(&mut dynamic, DT_SYMTAB, 0x200);
+        dyn_entry(&mut dynamic, hash_tag, 0x400);
+        dyn_entry(&mut dynamic, DT_NULL, 0);
+        data[0x700..0x700 + dynamic.len()].copy_from_slice(&dynamic);
+
+        let elf = LoadedElf {
+            entry_point: 0,
+            base_address: 0,
+            position_independent: true,
+            segments: alloc::vec![LoadedSegment { vaddr: 0, memsz: 0x800, filesz: 0x800, file_offset: 0, flags: 4 }],
+            program_headers: 0,
+            phentsize: 56,
+            phnum: 0,
+            tls: None,
+            dynamic: Some((0x700, dynamic.len() as u64)),
+            interpreter: None,
+        };
+        (data, elf)
+    }
+
+    fn words(values: &[u32]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn test_sysv_lookup() {
+        // A single bucket chaining every symbol: 4 → 3 → 2 → 1
+        let table = words(&[1, 5, 4, 0, 0, 1, 2, 3]);
+        let (data, elf) = test_object(DT_HASH, table);
+        let object = LinkedObject::new(&data, &elf).unwrap().unwrap();
+
+        assert_eq!(object.lookup(b"grove_main"), Ok(Some(0x1020)));
+        assert_eq!(object.lookup(b"weak_thing"), Ok(Some(0x1030)));
+        assert_eq!(object.lookup(b"helper"), Ok(Some(0x1040)));
+        // Undefined here, and unknown
+        assert_eq!(object.lookup(b"puts"), Ok(None));
+        assert_eq!(object.lookup(b"missing"), Ok(None));
+
+        // Moved objects report moved addresses
+        let moved = elf.clone().rebased(0x4000_0000_0000);
+        let object = LinkedObject::new(&data, &moved).unwrap().unwrap();
+        assert_eq!(object.lookup(b"helper"), Ok(Some(0x4000_0000_1040)));
+    }
+
+    #[test]
+    fn test_wrapping_addresses_are_malformed() {
+        let table = words(&[1, 5, 4, 0, 0, 1, 2, 3]);
+        let (mut data, elf) = test_object(DT_HASH, table);
+
+        // A symbol that would land past the end of the address space
+        let mut high = elf.clone();
+        high.base_address = u64::MAX - 0xFFF;
+        high.segments[0].vaddr = high.base_address;
+        let object = LinkedObject::new(&data, &high).unwrap().unwrap();
+        assert_eq!(object.lookup(b"helper"), Err(LinkError::Malformed("Symbol outside the address space")));
+
+        // A symbol table whose entries run off the end of the address space
+        data[0x700 + 2 * 16 + 8..0x700 + 3 * 16].copy_from_slice(&(u64::MAX - 8).to_le_bytes());
+        let object = LinkedObject::new(&data, &elf).unwrap().unwrap();
+        assert_eq!(object.lookup(b"helper"), Err(LinkError::Malformed("Table entry outside the address space")));
+    }
+
+    #[test]
+    fn test_sysv_chain_loop() {
+        // Symbol 2 chains back to itself
+        let table = words(&[1, 5, 2, 0, 0, 2, 0, 0]);
+        let (data, elf) = test_object(DT_HASH, table);
+        let object = LinkedObject::new(&data, &elf).unwrap().unwrap();
+        assert!(object.lookup(b"helper").is_err());
+    }
+
+    #[test]
+    fn test_gnu_lookup() {
+        // Symbols 2..=4 are hashed, all in one bucket; the Bloom filter is
+        // built the way the linker does
+        let names = ["grove_main", "weak_thing", "helper"];
+        let bloom_shift = 6;
+        let mut bloom = 0u64;
+        for name in names {
+            let hash = gnu_hash(name.as_bytes());
+            bloom |= 1 << (hash % 64) | 1 << ((hash >> bloom_shift) % 64);
+        }
+
+        let mut table = words(&[1, 2, 1, bloom_shift]);
+        table.extend_from_slice(&bloom.to_le_bytes());
+        table.extend_from_slice(&words(&[2]));
+        let chain: Vec<u32> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| gnu_hash(name.as_bytes()) & !1 | (i == names.len() - 1) as u32)
+            .collect();
+        table.extend_from_slice(&words(&chain));
+
+        let (data, elf) = test_object(DT_GNU_HASH, table);
+        let object = LinkedObject::new(&data, &elf).unwrap().unwrap();
+
+        assert_eq!(object.lookup(b"grove_main"), Ok(Some(0x1020)));
+        assert_eq!(object.lookup(b"weak_thing"), Ok(Some(0x1030)));
+        assert_eq!(object.lookup(b"helper"), Ok(Some(0x1040)));
+        assert_eq!(object.lookup(b"puts"), Ok(None));
+        assert_eq!(object.lookup(b"missing"), Ok(None));
+    }
+
+    #[test]
+    fn test_needed_names() {
+        let mut data = alloc::vec![0u8; 0x200];
+        let strtab = b"\0libcorelib.so\0libm.so\0";
+        data[0x100..0x100 + strtab.len()].copy_from_slice(strtab);
+
+        let mut dynamic = Vec::new();
+        dyn_entry(&mut dynamic, DT_NEEDED, 1);
+        dyn_entry(&mut dynamic, DT_NEEDED, 15);
+        dyn_entry(&mut dynamic, DT_STRTAB, 0x100);
+        dyn_entry(&mut dynamic, DT_STRSZ, strtab.len() as u64);
+        dyn_entry(&mut dynamic, DT_NULL, 0);
+        data[0x40..0x40 + dynamic.len()].copy_from_slice(&dynamic);
+
+        let (_, mut elf) = test_object(DT_HASH, Vec::new());
+        elf.segments[0].filesz = 0x200;
+        elf.dynamic = Some((0x40, dynamic.len() as u64));
+        assert_eq!(needed_names(&elf, &data).unwrap(), ["libcorelib.so", "libm.so"]);
+
+        // No dynamic section, no Groves
+        elf.dynamic = None;
+        assert!(needed_names(&elf, &data).unwrap().is_empty());
+
+        // A name running past the string table
+        elf.dynamic = Some((0x40, dynamic.len() as u64));
+        data[0x100 + strtab.len() - 1] = b'x';
+        assert!(needed_names(&elf, &data).is_err());
+    }
+}
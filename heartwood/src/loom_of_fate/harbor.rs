@@ -13,7 +13,9 @@
 //! - Fast lookup by VesselId
 //! - Thread-to-Vessel mapping
 //! - Vessel lifecycle management
+//! - Parent-child links, for waiting on and reaping vanished Vessels
 
+use super::grove_linker::Grove;
 use super::vessel::{Vessel, VesselId, VesselState};
 use super::thread::ThreadId;
 use alloc::vec::Vec;
@@ -133,6 +135,7 @@ impl Harbor {
     /// # Arguments
     /// * `parent` - Parent VesselId (None for init process)
     /// * `elf_data` - Raw ELF binary data
+    /// * `groves` - The program's Groves (see `grove_linker::gather_groves`)
     /// * `fate` - RBAC role from Concordance
     /// * `main_thread` - ThreadId of the main thread
     ///
@@ -143,6 +146,7 @@ impl Harbor {
         &mut self,
         parent: Option<VesselId>,
         elf_data: &[u8],
+        groves: &[Grove],
         fate: String,
         main_thread: ThreadId,
     ) -> Result<VesselId, &'static str> {
@@ -150,7 +154,7 @@ impl Harbor {
         self.next_beacon_id += 1;
 
         // Create Vessel from ELF
-        let mut vessel = Vessel::from_elf(beacon, parent, elf_data, fate, main_thread)?;
+        let mut vessel = Vessel::from_elf(beacon, parent, elf_data, groves, fate, main_thread)?;
 
         // The Vessel ID is already set by from_elf
         self.vessels.push(vessel);
@@ -192,6 +196,26 @@ impl Harbor {
             .map(|v| v.beacon)
     }
 
+    /// Find the Vessels whose parent is `parent`
+    ///
+    /// `None` finds the Vessels moored without a parent.
+    pub fn children_of(&self, parent: Option<VesselId>) -> impl Iterator<Item = &Vessel> {
+        self.vessels.iter().filter(move |v| v.parent == parent)
+    }
+
+    /// Hand the children of a vanished Vessel to the Harbormaster
+    ///
+    /// # Returns
+    /// true if any of them has already vanished, and so is waiting to be reaped
+    pub fn orphan_children(&mut self, parent: VesselId) -> bool {
+        let mut any_vanished = false;
+        for vessel in self.vessels.iter_mut().filter(|v| v.parent == Some(parent)) {
+            vessel.parent = None;
+            any_vanished |= vessel.state == VesselState::Vanished;
+        }
+        any_vanished
+    }
+
     /// Unmoor a Vessel from the Harbor (remove it)
     ///
     /// # Arguments
@@ -202,14 +226,19 @@ impl Harbor {
     ///
     /// # Note
     /// This should only be called after all threads have been cleaned up
-    /// and the Vessel is in Vanished state.
+    /// and the Vessel is in Vanished state. Its resources are dropped
+    /// without being freed; use `cast_off` and `Vessel::dismantle` for that.
     pub fn unmoor_vessel(&mut self, beacon: VesselId) -> bool {
-        if let Some(pos) = self.vessels.iter().position(|v| v.beacon == beacon) {
-            self.vessels.remove(pos);
-            true
-        } else {
-            false
-        }
+        self.cast_off(beacon).is_some()
+    }
+
+    /// Remove a Vessel from the Harbor and hand it to the caller
+    ///
+    /// # Returns
+    /// The Vessel, or None if there is no such Vessel
+    pub fn cast_off(&mut self, beacon: VesselId) -> Option<Vessel> {
+        let pos = self.vessels.iter().position(|v| v.beacon == beacon)?;
+        Some(self.vessels.remove(pos))
     }
 
     /// Get the number of Vessels currently in the Harbor
@@ -11,7 +11,14 @@
 //!
 //! ## Architecture
 //! - Cooperative scheduling with implicit yielding
-//! - Thread states: Weaving, Resting, Tangled, Fading
+//! - Thread states: Weaving, Resting, Tangled, Blocked, Fading
+//! - Blocked threads record why they wait (sleep, the Nexus, a join, a
+//!   child Vessel, a lock); those with a deadline sit in a timer-ordered
+//!   sleep queue that the timer tick drains
+//! - A Vessel vanishes with its last thread and is reaped by its parent
+//!   or, if it has none, by the Harbormaster
+//! - One ready queue per processor; an idle processor steals from the
+//!   busiest queue
 //! - Resource negotiation based on system-wide harmony
 //! - Parasite detection and throttling (not killing)
 
@@ -25,17 +32,22 @@ pub mod vessel;
 pub mod harbor;
 pub mod syscalls;
 pub mod elf_loader;
+pub mod grove_linker;
+pub mod summoning;
 
 pub use scheduler::{Scheduler, SchedulerStats};
-pub use thread::{Thread, ThreadId, ThreadState, ThreadPriority, ThreadType};
+pub use thread::{BlockReason, CrewStacks, Thread, ThreadId, ThreadState, ThreadPriority, ThreadType};
 pub use harmony::{HarmonyAnalyzer, HarmonyMetrics};
-pub use vessel::{Vessel, VesselId, VesselState};
+pub use vessel::{Vessel, VesselExit, VesselFault, VesselId, VesselState};
 pub use harbor::{Harbor, HarborStats};
 pub use syscalls::{dispatch_syscall, SyscallResult, SyscallError};
-pub use elf_loader::{load_elf, LoadedElf, ElfError};
+pub use elf_loader::{load_elf, LoadedElf, ElfError, TlsTemplate};
+pub use grove_linker::{Grove, LinkError};
+pub use summoning::{summon_from_path, SummonError};
 pub use context::ThreadContext;
 
 use crate::mana_pool::InterruptSafeLock;
+use crate::mana_pool::user_space::USER_STACK_SIZE;
 use core::mem::MaybeUninit;
 use alloc::boxed::Box;
 
@@ -191,6 +203,11 @@ pub fn init() {
         }
     }
 
+    // Spawn the Harbormaster, which reaps Vessels that have no parent
+    if spawn(system_threads::harbormaster_thread, ThreadPriority::Low).is_err() {
+        crate::println!("  ⚠ No Harbormaster: Vessels without a parent will not be reaped");
+    }
+
     // Debug: Verify thread contexts are correct
     // CRITICAL: Collect data FIRST, release lock, THEN print
     // to avoid deadlock if println tries to lock LOOM
@@ -271,7 +288,9 @@ pub fn spawn(entry_point: fn() -> !, priority: ThreadPriority) -> Result<ThreadI
 /// ThreadId of the created thread
 ///
 /// # Note
-/// Creates a user-mode thread within a Vessel (Ring 3 execution).
+/// Creates a user-mode thread within a Vessel (Ring 3 execution). If the
+/// program has thread-local storage, the thread gets a TLS block, which
+/// lives as long as the Vessel's address space.
 pub fn create_user_thread(
     vessel_id: VesselId,
     entry_point: u64,
@@ -281,11 +300,14 @@ pub fn create_user_thread(
     without_interrupts(|| {
         let mut loom = unsafe { get_loom().lock() };
 
-        // Get Vessel info (page table address)
-        let harbor = get_harbor().lock();
-        let vessel = harbor.find_vessel(vessel_id)
+        // Get Vessel info (page table address) and the thread's TLS
+        let mut harbor = get_harbor().lock();
+        let vessel = harbor.find_vessel_mut(vessel_id)
             .ok_or(LoomError::VesselNotFound)?;
         let page_table_phys = vessel.page_table_phys();
+        let tls = vessel.address_space_mut()
+            .allocate_tls()
+            .map_err(|_| LoomError::StackAllocationFailed)?;
         drop(harbor);
 
         // Generate thread ID
@@ -293,11 +315,14 @@ pub fn create_user_thread(
         loom.next_thread_id += 1;
 
         // Create user-mode context (Ring 3 with user segments)
-        let context = ThreadContext::new_user_mode(
+        let mut context = ThreadContext::new_user_mode(
             entry_point,
             user_stack_top,
             page_table_phys,
         );
+        if let Some((_, thread_pointer)) = tls {
+            context.fs_base = thread_pointer;
+        }
 
         // Create thread with pre-initialized context
         let thread = Thread::new_with_context(
@@ -309,8 +334,7 @@ pub fn create_user_thread(
         );
 
         // Add to thread list and ready queue
-        loom.threads.push(thread);
-        loom.ready_queue.push_back(thread_id);
+        loom.admit(thread)?;
 
         crate::serial_println!("[LOOM] Created user thread {} for Vessel {} at entry {:#x}",
                                thread_id.0, vessel_id.0, entry_point);
@@ -319,6 +343,103 @@ pub fn create_user_thread(
     })
 }
 
+/// Create another thread in a user-mode Vessel
+///
+/// The thread shares the Vessel's address space, but runs on a user stack
+/// placed at a random address and on a kernel stack of its own, with a TLS
+/// block of its own if the program has thread-local storage; all are given
+/// back when it is joined or its Vessel is reaped. It starts at
+/// `entry_point` as if called with `argument` as its only argument.
+///
+/// # Arguments
+/// * `vessel_id` - The Vessel this thread belongs to
+/// * `entry_point` - User space entry point address
+/// * `argument` - Passed to the thread in `rdi`
+/// * `priority` - Thread priority
+///
+/// # Returns
+/// * `Ok(ThreadId)` - The new thread, ready to run
+/// * `Err(LoomError::VesselNotFound)` - No such user-mode Vessel, or it is
+///   vanishing
+/// * `Err(LoomError::OutOfThreads)` - The thread table is full
+/// * `Err(LoomError::StackAllocationFailed)` - No memory for its stacks
+pub fn create_crew_thread(
+    vessel_id: VesselId,
+    entry_point: u64,
+    argument: u64,
+    priority: ThreadPriority,
+) -> Result<ThreadId, LoomError> {
+    let kernel_stack = vessel::allocate_kernel_stack()
+        .map_err(|_| LoomError::StackAllocationFailed)?;
+
+    let created = without_interrupts(|| {
+        let mut loom = unsafe { get_loom().lock() };
+        if loom.is_full() {
+            return Err(LoomError::OutOfThreads);
+        }
+
+        // Loom before Harbor; a Vessel being condemned is refused here,
+        // since it is condemned and its crew faded under the Loom's lock
+        let mut harbor = get_harbor().lock();
+        let vessel = harbor.find_vessel_mut(vessel_id)
+            .filter(|v| v.is_user_mode() && v.fault().is_none() && v.state() != VesselState::Vanished)
+            .ok_or(LoomError::VesselNotFound)?;
+        let page_table_phys = vessel.page_table_phys();
+        let space = vessel.address_space_mut();
+        let user_stack_top = space
+            .allocate_thread_stack(USER_STACK_SIZE)
+            .map_err(|_| LoomError::StackAllocationFailed)?;
+        let tls = match space.allocate_tls() {
+            Ok(tls) => tls,
+            Err(_) => {
+                let _ = space.release_stack(user_stack_top - USER_STACK_SIZE);
+                return Err(LoomError::StackAllocationFailed);
+            }
+        };
+        let user_stack_top = user_stack_top.as_u64();
+        drop(harbor);
+
+        let thread_id = ThreadId(loom.next_thread_id);
+        loom.next_thread_id += 1;
+
+        // Enter as a call would: rsp + 8 is 16-byte aligned, with no
+        // return address to go back to
+        let mut context = ThreadContext::new_user_mode(
+            entry_point,
+            user_stack_top - 8,
+            page_table_phys,
+        );
+        context.rdi = argument;
+        if let Some((_, thread_pointer)) = tls {
+            context.fs_base = thread_pointer;
+        }
+
+        let mut thread = Thread::new_with_context(
+            thread_id,
+            context,
+            priority,
+            ThreadType::User,
+            Some(vessel_id),
+        );
+        thread.crew_stacks = Some(CrewStacks {
+            user_stack: user_stack_top - USER_STACK_SIZE,
+            kernel_stack,
+            tls_block: tls.map(|(base, _)| base.as_u64()),
+        });
+        loom.admit(thread)?;
+
+        crate::serial_println!("[LOOM] Created crew thread {} for Vessel {} at entry {:#x}, stack top {:#x}",
+                               thread_id.0, vessel_id.0, entry_point, user_stack_top);
+
+        Ok(thread_id)
+    });
+
+    if created.is_err() {
+        unsafe { vessel::free_kernel_stack(kernel_stack) };
+    }
+    created
+}
+
 /// Create a Ring 1 service thread for a Grove (privileged service)
 ///
 /// # Arguments
@@ -367,8 +488,7 @@ pub fn create_service_thread(
         );
 
         // Add to thread list and ready queue
-        loom.threads.push(thread);
-        loom.ready_queue.push_back(thread_id);
+        loom.admit(thread)?;
 
         crate::serial_println!("[LOOM] Created service thread {} for Vessel {} at entry {:#x}",
                               thread_id.0, vessel_id.0, entry_point);
@@ -379,8 +499,9 @@ pub fn create_service_thread(
 
 /// Terminate a thread by ID
 ///
-/// Marks the thread as Fading so it won't be scheduled again.
-/// The thread's resources will be cleaned up by the scheduler.
+/// Marks the thread as Fading so it won't be scheduled again, and wakes
+/// every thread joining it. If it was the last thread of its Vessel, the
+/// Vessel vanishes and waits to be reaped.
 ///
 /// # Arguments
 /// * `thread_id` - The ID of the thread to terminate
@@ -389,26 +510,186 @@ pub fn create_service_thread(
 /// * `Ok(())` - Thread marked as Fading
 /// * `Err(LoomError)` - If thread not found
 pub fn terminate_thread(thread_id: ThreadId) -> Result<(), LoomError> {
-    without_interrupts(|| {
-        unsafe {
-            let mut loom = get_loom().lock();
+    exit_thread(thread_id, 0)?;
+    crate::serial_println!("[LOOM] Terminated thread {:?}", thread_id);
+    Ok(())
+}
 
-            // Find the thread and mark it as Fading
-            if let Some(thread) = loom.threads.iter_mut().find(|t| t.id() == thread_id) {
-                thread.set_state(ThreadState::Fading);
+/// Fade a thread, leaving `exit_value` for any joiners
+///
+/// When the last living thread of a Vessel fades, the Vessel vanishes with
+/// its main thread's exit value as its exit code. If `thread_id` is the
+/// current thread, the caller must yield afterwards.
+///
+/// # Returns
+/// * `Ok(())` - The thread is Fading (it may already have been)
+/// * `Err(LoomError::ThreadNotFound)` - No such thread
+pub fn exit_thread(thread_id: ThreadId, exit_value: u64) -> Result<(), LoomError> {
+    without_interrupts(|| unsafe {
+        let mut loom = get_loom().lock();
+        let thread = loom.threads.iter_mut()
+            .find(|t| t.id() == thread_id)
+            .ok_or(LoomError::ThreadNotFound)?;
+        if thread.state() == ThreadState::Fading {
+            return Ok(());
+        }
 
-                // Remove from ready queue if present
-                loom.ready_queue.retain(|&tid| tid != thread_id);
+        thread.exit_value = exit_value;
+        let vessel_id = thread.vessel_id();
+        loom.fade(thread_id)?;
 
-                crate::serial_println!("[LOOM] Terminated thread {:?}", thread_id);
-                Ok(())
-            } else {
-                Err(LoomError::ThreadNotFound)
-            }
+        if let Some(vessel_id) = vessel_id {
+            settle_vessel(&mut loom, vessel_id, exit_value);
         }
+        Ok(())
     })
 }
 
+/// Vanish a Vessel if none of its threads is left alive
+///
+/// Its exit code is its main thread's exit value, or `last_exit_value` if
+/// the main thread is not yet recorded. Its children pass to the
+/// Harbormaster, and whoever may be waiting for it is woken. Takes the
+/// Harbor's lock under the Loom's.
+fn settle_vessel(loom: &mut Scheduler, vessel_id: VesselId, last_exit_value: u64) {
+    let alive = loom.threads.iter()
+        .any(|t| t.vessel_id() == Some(vessel_id) && t.state() != ThreadState::Fading);
+    if alive {
+        return;
+    }
+
+    let mut harbor = get_harbor().lock();
+    let Some(vessel) = harbor.find_vessel_mut(vessel_id) else {
+        return;
+    };
+    if vessel.state() == VesselState::Vanished {
+        return;
+    }
+
+    let exit_code = loom.thread(vessel.main_thread()).map_or(last_exit_value, |t| t.exit_value());
+    vessel.vanish(exit_code);
+    let parent = vessel.parent();
+    let orphans_vanished = harbor.orphan_children(vessel_id);
+    drop(harbor);
+
+    loom.wake_child_waiters(parent);
+    if orphans_vanished && parent.is_some() {
+        loom.wake_child_waiters(None);
+    }
+}
+
+/// Wait for a child Vessel to vanish, and reap it
+///
+/// # Arguments
+/// * `parent` - The waiting Vessel; `None` waits on the Vessels moored
+///   without a parent, which the Harbormaster looks after
+/// * `child` - The child to wait for, or `None` for whichever vanishes first
+///
+/// # Returns
+/// * `Ok((child, exit))` - The reaped child and how it ended
+/// * `Err(LoomError::VesselNotFound)` - There is no such child
+pub fn wait_for_child(
+    parent: Option<VesselId>,
+    child: Option<VesselId>,
+) -> Result<(VesselId, VesselExit), LoomError> {
+    loop {
+        let seen = scheduler::vanishings();
+        let vanished = without_interrupts(|| {
+            let harbor = get_harbor().lock();
+            let mut children = harbor.children_of(parent)
+                .filter(|v| child.is_none_or(|id| v.id() == id))
+                .peekable();
+            if children.peek().is_none() {
+                return Err(LoomError::VesselNotFound);
+            }
+            Ok(children.find(|v| v.state() == VesselState::Vanished).map(|v| v.id()))
+        })?;
+
+        match vanished {
+            Some(vessel_id) => match reap(vessel_id) {
+                Some(exit) => return Ok((vessel_id, exit)),
+                // Its last thread is still leaving a CPU, or another
+                // waiter got there first
+                None => yield_now(),
+            },
+            // Parking rechecks the vanishing count under the Loom's lock,
+            // so a child that vanishes between the check above and here
+            // is not waited on
+            None => park_current(BlockReason::Wait { seen }, None),
+        }
+    }
+}
+
+/// Reap a vanished Vessel: take it out of the Harbor and dismantle it
+///
+/// # Returns
+/// How the Vessel ended, or `None` if it has not vanished, is already
+/// reaped, or a CPU is still switching away from its last thread
+pub fn reap(vessel_id: VesselId) -> Option<VesselExit> {
+    let (vessel, exit, crew_stacks) = without_interrupts(|| unsafe {
+        let mut loom = get_loom().lock();
+        let mut harbor = get_harbor().lock();
+        let exit = harbor.find_vessel(vessel_id)?.exit_status()?;
+
+        let crew: alloc::vec::Vec<ThreadId> = loom.threads.iter()
+            .filter(|t| t.vessel_id() == Some(vessel_id))
+            .map(|t| t.id())
+            .collect();
+        let crew_stacks = loom.release_threads(&crew)?;
+        Some((harbor.cast_off(vessel_id)?, exit, crew_stacks))
+    })?;
+
+    // Tearing down the address space waits on the other processors, so it
+    // happens outside the locks. The crew's user stacks and TLS blocks go
+    // with it.
+    unsafe {
+        vessel.dismantle();
+        for stacks in crew_stacks {
+            vessel::free_kernel_stack(stacks.kernel_stack);
+        }
+    }
+    Some(exit)
+}
+
+/// Give a joined crew thread's table slot and stacks back
+///
+/// Only threads from `create_crew_thread` are released: the main thread's
+/// exit value becomes its Vessel's exit code, so it stays until the Vessel
+/// is reaped. So does a thread whose CPU is still switching away from it.
+pub fn release_crew_thread(thread_id: ThreadId) {
+    let released = without_interrupts(|| unsafe {
+        let mut loom = get_loom().lock();
+        let thread = loom.thread(thread_id)?;
+        thread.crew_stacks?;
+        let vessel_id = thread.vessel_id()?;
+        Some((vessel_id, loom.release_threads(&[thread_id])?))
+    });
+    let Some((vessel_id, crew_stacks)) = released else {
+        return;
+    };
+
+    for stacks in crew_stacks {
+        without_interrupts(|| {
+            if let Some(vessel) = get_harbor().lock().find_vessel_mut(vessel_id) {
+                let space = vessel.address_space_mut();
+                let _ = space.release_stack(x86_64::VirtAddr::new(stacks.user_stack));
+                if let Some(tls_block) = stacks.tls_block {
+                    let _ = space.release_tls(x86_64::VirtAddr::new(tls_block));
+                }
+            }
+        });
+        unsafe { vessel::free_kernel_stack(stacks.kernel_stack) };
+    }
+}
+
+/// Cast off and dismantle a Vessel none of whose threads ever ran
+pub(crate) fn scuttle(vessel_id: VesselId) {
+    let vessel = without_interrupts(|| get_harbor().lock().cast_off(vessel_id));
+    if let Some(vessel) = vessel {
+        unsafe { vessel.dismantle() };
+    }
+}
+
 /// Execute a closure with interrupts disabled
 /// This prevents deadlocks when acquiring locks that might be used in interrupt handlers
 pub(crate) fn without_interrupts<F, R>(f: F) -> R
@@ -450,6 +731,110 @@ where
 
 /// Yield the current thread
 pub fn yield_now() {
+    switch_away(Departure::Yield);
+}
+
+/// Park the current thread until it is woken or the timer reaches `deadline`
+///
+/// The thread is marked Blocked for `reason` and kept off the ready queue,
+/// so it uses no CPU while it waits. Returns at once if a wake-up is
+/// already pending. Wake-ups may be spurious: callers recheck their
+/// condition in a loop.
+pub fn park_current(reason: BlockReason, deadline: Option<u64>) {
+    switch_away(Departure::Park { reason, deadline, hand_to: None });
+}
+
+/// Wake `target` and switch straight to it, parking the current thread
+///
+/// A direct thread-to-thread hand-off for a Nexus call: `target` runs next
+/// without waiting its turn in the ready queue. If a wake-up is already
+/// pending for the current thread it stays ready and simply yields to
+/// `target`.
+pub fn park_and_hand_off(target: ThreadId) {
+    switch_away(Departure::Park { reason: BlockReason::Channel, deadline: None, hand_to: Some(target) });
+}
+
+/// Wake a thread parked with `park_current`
+///
+/// # Returns
+/// `false` if the thread does not exist or is Fading
+pub fn wake(thread_id: ThreadId) -> bool {
+    without_interrupts(|| {
+        unsafe { get_loom().lock().wake(thread_id) }
+    })
+}
+
+/// Sleep for `ticks` timer ticks
+///
+/// The thread waits in the sleep queue and uses no CPU. Before the Loom is
+/// weaving there is nothing to park, so this spins instead.
+pub fn sleep(ticks: u64) {
+    sleep_until(crate::attunement::timer::ticks().saturating_add(ticks));
+}
+
+/// Sleep until the timer reaches `tick`
+pub fn sleep_until(tick: u64) {
+    while crate::attunement::timer::ticks() < tick {
+        if current_thread().is_some() {
+            park_current(BlockReason::Sleep { until: tick }, Some(tick));
+        } else {
+            core::hint::spin_loop();
+        }
+    }
+}
+
+/// Wait for a thread to fade
+///
+/// # Returns
+/// * `Ok(exit_value)` - The value the thread passed to SYS_EXIT (0 if it
+///   was terminated some other way)
+/// * `Err(LoomError::ThreadNotFound)` - No such thread, or it is the
+///   calling thread itself
+pub fn join(thread_id: ThreadId) -> Result<u64, LoomError> {
+    if current_thread() == Some(thread_id) {
+        return Err(LoomError::ThreadNotFound);
+    }
+
+    loop {
+        let exit_value = without_interrupts(|| unsafe {
+            let loom = get_loom().lock();
+            let thread = loom.thread(thread_id).ok_or(LoomError::ThreadNotFound)?;
+            Ok((thread.state() == ThreadState::Fading).then(|| thread.exit_value()))
+        })?;
+        if let Some(exit_value) = exit_value {
+            return Ok(exit_value);
+        }
+
+        // Parking rechecks the target under the Loom's lock, so a thread
+        // that fades between the check above and here is not waited on
+        park_current(BlockReason::Join(thread_id), None);
+    }
+}
+
+/// Wake the sleepers whose deadline has come (called from the timer interrupt)
+///
+/// The Loom's lock is only taken once the earliest deadline has passed.
+pub fn wake_sleepers(now: u64) {
+    if now < scheduler::next_wake_tick() {
+        return;
+    }
+    without_interrupts(|| {
+        unsafe { get_loom().lock().wake_expired(now) }
+    });
+}
+
+/// Why the current thread is giving up the CPU
+#[derive(Debug, Clone, Copy)]
+enum Departure {
+    /// Go to the back of the ready queue
+    Yield,
+    /// Leave the ready queue until woken (or until the deadline tick),
+    /// optionally waking a thread to run in our place
+    Park { reason: BlockReason, deadline: Option<u64>, hand_to: Option<ThreadId> },
+}
+
+/// Switch away from the current thread
+fn switch_away(departure: Departure) {
     // DEBUG: Mark function entry
     unsafe {
         for &byte in b"[FUNC:yield_now]".iter() {
@@ -476,6 +861,18 @@ pub fn yield_now() {
                 return;
             }
 
+            // Parking is decided under the same lock as the switch, so a
+            // wake-up can never slip in between the two
+            if let Departure::Park { reason, deadline, hand_to } = departure {
+                if let Some(target) = hand_to {
+                    loom.wake(target);
+                    loom.hand_off_to(target);
+                }
+                if !loom.park_current(reason, deadline) && hand_to.is_none() {
+                    return;
+                }
+            }
+
             // Step 2: Prepare for context switch
             let (should_switch, from_ctx_ptr, to_ctx_ptr, new_kernel_stack) = loom.prepare_yield();
 
@@ -545,8 +942,9 @@ pub fn yield_now() {
                 }
 
                 // --- WE ARE NOW THE NEW THREAD ---
-                // The lock was released before the switch, so we don't hold it
-                // No cleanup needed
+                // The lock was released before the switch, so we don't hold it.
+                // The thread we left is saved now and may run elsewhere.
+                finish_switch();
             }
 
             // Step 4: The `without_interrupts` guard drops here,
@@ -555,6 +953,21 @@ pub fn yield_now() {
     });
 }
 
+/// Let the thread this CPU just switched away from run again
+///
+/// A thread that yields stays out of the ready queues until its CPU has
+/// saved its context, so no other CPU can resume it half-saved. Call this
+/// on the new thread's side of every switch; it does nothing (and takes no
+/// lock) when there is nothing to release.
+pub fn finish_switch() {
+    if !scheduler::has_outgoing(crate::attunement::smp::current_cpu()) {
+        return;
+    }
+    without_interrupts(|| {
+        unsafe { get_loom().lock().finish_switch() }
+    });
+}
+
 /// Preemptive yield - called from timer interrupt when quantum expires
 ///
 /// This is specifically designed for interrupt context and properly handles
@@ -627,6 +1040,82 @@ pub fn current_thread() -> Option<ThreadId> {
     })
 }
 
+/// Get the Vessel the current thread belongs to
+///
+/// Returns None for kernel threads, which live outside any Vessel.
+pub fn current_vessel_id() -> Option<VesselId> {
+    without_interrupts(|| {
+        unsafe {
+            let loom = get_loom().lock();
+            let current_tid = loom.current_thread_id()?;
+            loom.threads.iter()
+                .find(|t| t.id() == current_tid)
+                .and_then(|t| t.vessel_id())
+        }
+    })
+}
+
+/// Resolve a page fault in the current thread's Vessel
+///
+/// Looks up the faulting Vessel's address space and lets it page in the
+/// address if it belongs to a demand-paged region.
+///
+/// # Returns
+///
+/// * `Ok(())` - The page was mapped; the faulting access can be retried
+/// * `Err(UserFault)` - The access is invalid (or no Vessel is running)
+pub fn handle_user_page_fault(
+    addr: x86_64::VirtAddr,
+    error: x86_64::structures::idt::PageFaultErrorCode,
+) -> Result<(), crate::mana_pool::UserFault> {
+    use crate::mana_pool::UserFault;
+
+    let vessel_id = current_vessel_id().ok_or(UserFault::Unmapped)?;
+
+    without_interrupts(|| {
+        let mut harbor = get_harbor().lock();
+        let vessel = harbor.find_vessel_mut(vessel_id).ok_or(UserFault::Unmapped)?;
+        unsafe { vessel.address_space_mut().handle_page_fault(addr, error) }
+    })
+}
+
+/// Terminate a Vessel after an unrecoverable fault
+///
+/// Records the fault on the Vessel and fades every one of its threads, so
+/// the scheduler never runs them again and the Vessel vanishes. If the
+/// current thread belongs to the Vessel, the caller must yield afterwards.
+pub fn condemn_vessel(vessel_id: VesselId, fault: VesselFault) {
+    crate::serial_println!(
+        "[FAULT] Vessel {} condemned: {} at {:#x} (rip {:#x})",
+        vessel_id.0,
+        fault.reason.description(),
+        fault.address,
+        fault.instruction
+    );
+
+    without_interrupts(|| {
+        unsafe {
+            // Loom before Harbor, matching the order used by the scheduler
+            let mut loom = get_loom().lock();
+            {
+                let mut harbor = get_harbor().lock();
+                if let Some(vessel) = harbor.find_vessel_mut(vessel_id) {
+                    vessel.condemn(fault);
+                }
+            }
+
+            let crew: alloc::vec::Vec<ThreadId> = loom.threads.iter()
+                .filter(|t| t.vessel_id() == Some(vessel_id))
+                .map(|t| t.id())
+                .collect();
+            for thread_id in crew {
+                let _ = loom.fade(thread_id);
+            }
+            settle_vessel(&mut loom, vessel_id, 0);
+        }
+    });
+}
+
 /// Get scheduler statistics
 pub fn stats() -> SchedulerStats {
     without_interrupts(|| {
@@ -770,6 +1259,27 @@ pub unsafe fn prepare_great_handoff() {
     loom.prepare_handoff(idle_thread_id);
 }
 
+/// Create this application processor's idle thread for its Great Hand-Off
+///
+/// # Returns
+/// The idle thread's context, to pass to `context_switch_first`, or `None`
+/// if the Loom is out of threads or stack memory
+///
+/// # Safety
+/// Must be called once per application processor, from `ap_entry`
+pub unsafe fn prepare_secondary_handoff() -> Option<*const context::ThreadContext> {
+    let mut loom = get_loom().lock();
+    let idle_id = loom.spawn_idle(system_threads::secondary_idle_thread).ok()?;
+    loom.get_thread_context(idle_id)
+}
+
+/// Whether any thread is waiting in a ready queue
+pub fn has_ready_threads() -> bool {
+    without_interrupts(|| {
+        unsafe { get_loom().lock().has_ready_threads() }
+    })
+}
+
 /// Begin multitasking - The Sacred First Weave (DEPRECATED)
 ///
 /// This performs the one-time transition from bootstrap code to the first
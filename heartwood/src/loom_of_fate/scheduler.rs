@@ -3,19 +3,75 @@
 use super::context::{switch_context_cooperative, context_switch_first, ThreadContext};
 use super::harmony::{HarmonyAnalyzer, HarmonyMetrics};
 use super::stack::Stack;
-use super::thread::{Thread, ThreadId, ThreadPriority, ThreadState};
+use super::thread::{BlockReason, CrewStacks, Thread, ThreadId, ThreadPriority, ThreadState};
+use super::vessel::VesselId;
 use super::LoomError;
-use alloc::collections::VecDeque;
+use crate::attunement::per_cpu::MAX_CPUS;
+use crate::attunement::{clocksource, smp, timer};
+use alloc::collections::{BTreeSet, VecDeque};
 use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+/// Most threads the Loom holds at once
+///
+/// The thread table is allocated at full size up front: context pointers
+/// are used after the lock is dropped, so it must never reallocate while
+/// another CPU is in the middle of a switch.
+const MAX_THREADS: usize = 256;
+
+/// Earliest deadline in the sleep queue (`u64::MAX` when it is empty)
+///
+/// Kept outside the Loom's lock so the timer interrupt can tell, without
+/// locking, whether any sleeper is due.
+static NEXT_WAKE_TICK: AtomicU64 = AtomicU64::new(u64::MAX);
+
+/// The tick at which the next parked thread is due to wake
+pub fn next_wake_tick() -> u64 {
+    NEXT_WAKE_TICK.load(Ordering::Relaxed)
+}
+
+/// How many Vessels have vanished since boot
+///
+/// Only changed under the Loom's lock. A thread about to wait for a child
+/// records it first; if it has moved by the time the thread parks, a child
+/// may have vanished in between and the thread must look again.
+static VANISHINGS: AtomicU64 = AtomicU64::new(0);
+
+/// The number of Vessels that have vanished since boot
+pub fn vanishings() -> u64 {
+    VANISHINGS.load(Ordering::Acquire)
+}
+
+/// Bit per CPU whose outgoing thread has not rejoined the ready queues
+///
+/// Kept outside the lock so that finishing a switch costs nothing when
+/// there is nothing to release.
+static OUTGOING: AtomicU32 = AtomicU32::new(0);
 
-const MAX_THREADS: usize = 1024;
+/// Whether `cpu` switched away from a thread that is still held back
+pub fn has_outgoing(cpu: usize) -> bool {
+    OUTGOING.load(Ordering::Acquire) & (1 << cpu) != 0
+}
 
 /// The harmony-based cooperative/preemptive scheduler
 pub struct Scheduler {
     pub(crate) threads: Vec<Thread>,
+    /// Slots of `threads` whose faded thread was released, free for reuse
+    vacant: Vec<usize>,
     stacks: Vec<Stack>,  // Stack storage (owned by scheduler)
-    pub(crate) ready_queue: VecDeque<ThreadId>,
-    current_thread: Option<ThreadId>,
+    /// Ready threads, one queue per CPU
+    run_queues: [VecDeque<ThreadId>; MAX_CPUS],
+    /// Parked threads with a deadline, soonest first
+    sleep_queue: BTreeSet<(u64, ThreadId)>,
+    /// The thread running on each CPU
+    current: [Option<ThreadId>; MAX_CPUS],
+    /// Each CPU's idle thread, run when there is nothing to run or steal
+    idle: [Option<ThreadId>; MAX_CPUS],
+    /// The thread each CPU has just switched away from
+    ///
+    /// It rejoins the ready queues only once its context is saved, so no
+    /// other CPU can resume it from a stale one.
+    outgoing: [Option<ThreadId>; MAX_CPUS],
     pub(crate) next_thread_id: u64,
     harmony_analyzer: HarmonyAnalyzer,
     /// Latest harmony metrics from the analyzer
@@ -26,10 +82,13 @@ pub struct Scheduler {
     // === Preemptive Multitasking Support ===
     /// Is preemptive scheduling enabled?
     preemption_enabled: bool,
-    /// Time quantum in timer ticks (e.g., 10 ticks = 10ms if timer is 1ms)
+    /// Time quantum in milliseconds
     time_quantum: u64,
-    /// Ticks remaining in current thread's quantum
-    quantum_remaining: u64,
+    /// When each CPU's current thread's quantum ends, in clock nanoseconds
+    quantum_ends: [u64; MAX_CPUS],
+
+    /// Thread to run next regardless of its place in the ready queue
+    handoff_target: Option<ThreadId>,
 }
 
 impl Default for Scheduler {
@@ -43,16 +102,19 @@ impl Scheduler {
 
         // Pre-allocate capacity to prevent reallocation during push
         // This avoids memory overlap between Vec storage and stack allocations
-        let threads = Vec::with_capacity(16);
+        let threads = Vec::with_capacity(MAX_THREADS);
         let stacks = Vec::with_capacity(16);
-        let ready_queue = VecDeque::new();
         let harmony_analyzer = HarmonyAnalyzer::new();
 
         Self {
             threads,
+            vacant: Vec::with_capacity(MAX_THREADS),
             stacks,
-            ready_queue,
-            current_thread: None,
+            run_queues: [const { VecDeque::new() }; MAX_CPUS],
+            sleep_queue: BTreeSet::new(),
+            current: [None; MAX_CPUS],
+            idle: [None; MAX_CPUS],
+            outgoing: [None; MAX_CPUS],
             next_thread_id: 1,
             harmony_analyzer,
             latest_metrics: HarmonyMetrics::default(),
@@ -60,7 +122,8 @@ impl Scheduler {
             // Preemption disabled by default (cooperative mode)
             preemption_enabled: false,
             time_quantum: 100,  // Default: 100ms quantum (conservative for testing)
-            quantum_remaining: 100,
+            quantum_ends: [u64::MAX; MAX_CPUS],
+            handoff_target: None,
         }
     }
 
@@ -76,13 +139,21 @@ impl Scheduler {
         unsafe {
             let ptr: *mut Scheduler = boxed.as_mut_ptr();
 
-            core::ptr::write(core::ptr::addr_of_mut!((*ptr).threads), Vec::with_capacity(16));
+            core::ptr::write(core::ptr::addr_of_mut!((*ptr).threads), Vec::with_capacity(MAX_THREADS));
+
+            core::ptr::write(core::ptr::addr_of_mut!((*ptr).vacant), Vec::with_capacity(MAX_THREADS));
 
             core::ptr::write(core::ptr::addr_of_mut!((*ptr).stacks), Vec::with_capacity(16));
 
-            core::ptr::write(core::ptr::addr_of_mut!((*ptr).ready_queue), VecDeque::new());
+            core::ptr::write(core::ptr::addr_of_mut!((*ptr).run_queues), [const { VecDeque::new() }; MAX_CPUS]);
+
+            core::ptr::write(core::ptr::addr_of_mut!((*ptr).sleep_queue), BTreeSet::new());
+
+            core::ptr::write(core::ptr::addr_of_mut!((*ptr).current), [None; MAX_CPUS]);
+
+            core::ptr::write(core::ptr::addr_of_mut!((*ptr).idle), [None; MAX_CPUS]);
 
-            core::ptr::write(core::ptr::addr_of_mut!((*ptr).current_thread), None);
+            core::ptr::write(core::ptr::addr_of_mut!((*ptr).outgoing), [None; MAX_CPUS]);
 
             core::ptr::write(core::ptr::addr_of_mut!((*ptr).next_thread_id), 1);
 
@@ -97,7 +168,9 @@ impl Scheduler {
 
             core::ptr::write(core::ptr::addr_of_mut!((*ptr).time_quantum), 100);
 
-            core::ptr::write(core::ptr::addr_of_mut!((*ptr).quantum_remaining), 100);
+            core::ptr::write(core::ptr::addr_of_mut!((*ptr).quantum_ends), [u64::MAX; MAX_CPUS]);
+
+            core::ptr::write(core::ptr::addr_of_mut!((*ptr).handoff_target), None);
 
             boxed.assume_init()
         }
@@ -105,7 +178,52 @@ impl Scheduler {
 
     /// Spawn a new thread
     pub fn spawn(&mut self, entry_point: fn() -> !, priority: ThreadPriority) -> Result<ThreadId, LoomError> {
-        if self.threads.len() >= MAX_THREADS {
+        let thread = self.forge_kernel_thread(entry_point, priority)?;
+        self.admit(thread)
+    }
+
+    /// Create this CPU's idle thread and make it the CPU's current thread
+    ///
+    /// Used by each application processor before its Great Hand-Off. Idle
+    /// threads never enter the ready queues.
+    pub fn spawn_idle(&mut self, entry_point: fn() -> !) -> Result<ThreadId, LoomError> {
+        let thread = self.forge_kernel_thread(entry_point, ThreadPriority::Idle)?;
+        let thread_id = self.insert(thread)?;
+        self.prepare_handoff(thread_id);
+        Ok(thread_id)
+    }
+
+    /// Add a new thread to the table and make it ready
+    pub(crate) fn admit(&mut self, thread: Thread) -> Result<ThreadId, LoomError> {
+        let thread_id = self.insert(thread)?;
+        self.enqueue(thread_id);
+        Ok(thread_id)
+    }
+
+    /// Put a thread in the table, in a released slot if there is one
+    ///
+    /// Slots are overwritten in place, so the table never shifts or grows
+    /// past its capacity.
+    fn insert(&mut self, thread: Thread) -> Result<ThreadId, LoomError> {
+        let thread_id = thread.id();
+        if let Some(index) = self.vacant.pop() {
+            self.threads[index] = thread;
+        } else if self.threads.len() < MAX_THREADS {
+            self.threads.push(thread);
+        } else {
+            return Err(LoomError::OutOfThreads);
+        }
+        Ok(thread_id)
+    }
+
+    /// Whether the thread table has no room left
+    pub(crate) fn is_full(&self) -> bool {
+        self.threads.len() >= MAX_THREADS && self.vacant.is_empty()
+    }
+
+    /// Allocate a stack and build a kernel thread around it
+    fn forge_kernel_thread(&mut self, entry_point: fn() -> !, priority: ThreadPriority) -> Result<Thread, LoomError> {
+        if self.is_full() {
             return Err(LoomError::OutOfThreads);
         }
 
@@ -122,12 +240,47 @@ impl Scheduler {
 
         // Create the thread with its stack
         // Pass None for vessel_id since current threads are kernel threads
-        let thread = Thread::new(thread_id, entry_point, priority, stack_bottom, stack_top);
+        Ok(Thread::new(thread_id, entry_point, priority, stack_bottom, stack_top))
+    }
+
+    /// Make a thread ready on the least-loaded online CPU
+    ///
+    /// Ties go to this CPU. A CPU sitting in its idle thread is sent a
+    /// reschedule IPI so it notices.
+    pub(crate) fn enqueue(&mut self, thread_id: ThreadId) {
+        let this_cpu = smp::current_cpu();
+        let target = (0..smp::online_cpus())
+            .min_by_key(|&cpu| (self.load(cpu), cpu != this_cpu))
+            .unwrap_or(this_cpu);
+
+        self.run_queues[target].push_back(thread_id);
+        if target != this_cpu && self.is_idling(target) {
+            smp::kick(target);
+        }
+    }
+
+    /// Threads queued on `cpu`, plus the one it is running (if not idle)
+    fn load(&self, cpu: usize) -> usize {
+        self.run_queues[cpu].len() + usize::from(!self.is_idling(cpu))
+    }
 
-        self.threads.push(thread);
-        self.ready_queue.push_back(thread_id);
+    fn is_idling(&self, cpu: usize) -> bool {
+        self.current[cpu].is_none() || self.current[cpu] == self.idle[cpu]
+    }
 
-        Ok(thread_id)
+    fn is_idle_thread(&self, thread_id: ThreadId) -> bool {
+        self.idle.contains(&Some(thread_id))
+    }
+
+    fn remove_from_queues(&mut self, thread_id: ThreadId) {
+        for queue in self.run_queues.iter_mut() {
+            queue.retain(|&id| id != thread_id);
+        }
+    }
+
+    /// Whether there is anything for an idle CPU to run or steal
+    pub fn has_ready_threads(&self) -> bool {
+        self.run_queues.iter().any(|queue| !queue.is_empty())
     }
 
     /// Yield the current thread and switch to the next one
@@ -146,6 +299,13 @@ impl Scheduler {
     /// can be dropped before the actual context switch.
     /// new_kernel_stack is Some(addr) if we need to update TSS.rsp[0]
     pub fn prepare_yield(&mut self) -> (bool, *mut ThreadContext, *const ThreadContext, Option<u64>) {
+        let cpu = smp::current_cpu();
+
+        // The thread this CPU left last time has been saved by now
+        self.release_outgoing(cpu);
+
+        // Parked threads whose deadline has passed rejoin the ready queue
+        self.wake_expired(crate::attunement::timer::ticks());
 
         // Analyze harmony before scheduling
         let metrics = self.harmony_analyzer.analyze(&mut self.threads);
@@ -154,45 +314,28 @@ impl Scheduler {
         // Adaptive scheduling based on system harmony
         if metrics.system_harmony < 0.5 {
             // System is in disharmony - prioritize cooperative threads
-            self.rebalance_for_harmony();
-        }
-
-        // Find the next thread to run
-        let next_thread_id = self.select_next_thread();
-
-        if next_thread_id.is_none() {
-            // No threads ready - this shouldn't happen in a well-designed system
-            // but if it does, we just return (stay on current thread if any)
-            return (false, core::ptr::null_mut(), core::ptr::null(), None);
+            self.rebalance_for_harmony(cpu);
         }
 
-        // CRITICAL: Do NOT use .unwrap() here! It can panic with formatting,
-        // which might try to lock LOOM again, causing deadlock.
-        let next_id = match next_thread_id {
+        // Find the next thread to run. With nothing else ready, the current
+        // thread keeps running - unless it is parking or fading, in which
+        // case this CPU idles.
+        let current = self.current[cpu];
+        let next_id = match self.select_next_thread(cpu) {
             Some(id) => id,
-            None => {
-                // This should never happen due to check above, but handle gracefully
-                unsafe {
-                    let msg = b"\n[FATAL] next_thread_id unwrap failed in prepare_yield!\n";
-                    for &byte in msg.iter() {
-                        core::arch::asm!(
-                            "out dx, al",
-                            in("dx") 0x3f8u16,
-                            in("al") byte,
-                            options(nomem, nostack, preserves_flags)
-                        );
-                    }
-                    loop {
-                        core::arch::asm!("hlt", options(nomem, nostack, preserves_flags));
-                    }
+            None => match self.idle[cpu] {
+                Some(idle) if !self.can_continue(current) => idle,
+                _ => {
+                    self.cancel_current_park();
+                    return (false, core::ptr::null_mut(), core::ptr::null(), None);
                 }
-            }
+            },
         };
 
         // If we're switching to a different thread, prepare for context switch
-        if self.current_thread.is_some() && self.current_thread != Some(next_id) {
+        if current.is_some() && current != Some(next_id) {
             // CRITICAL: Do NOT use .unwrap() here! It can panic with formatting.
-            let current_id = match self.current_thread {
+            let current_id = match current {
                 Some(id) => id,
                 None => {
                     // This should never happen due to check above, but handle gracefully
@@ -214,30 +357,29 @@ impl Scheduler {
             };
 
             // Update current thread state
-            let should_requeue = if let Some(current_thread) = self.find_thread_mut(current_id) {
+            if let Some(current_thread) = self.find_thread_mut(current_id) {
                 current_thread.record_yield();
 
-                // Only requeue if not Fading
+                // Fading and Blocked threads stay out of the ready queues (a
+                // parked thread rejoins them when it is woken)
                 let is_fading = current_thread.state() == ThreadState::Fading;
-                if !is_fading {
+                let is_parked = current_thread.is_parked();
+                if !is_fading && !is_parked {
                     current_thread.set_state(ThreadState::Resting);
                 }
-                !is_fading
-            } else {
-                false
-            };
-
-            // Add current thread back to ready queue (unless it's Fading)
-            if should_requeue {
-                self.ready_queue.push_back(current_id);
             }
 
+            // It rejoins the ready queues once this CPU has saved its context
+            self.outgoing[cpu] = Some(current_id);
+            OUTGOING.fetch_or(1 << cpu, Ordering::Release);
+
             // Update next thread state
             if let Some(next_thread) = self.find_thread_mut(next_id) {
                 next_thread.set_state(ThreadState::Weaving);
                 next_thread.record_time_slice();
                 next_thread.last_run_time = crate::attunement::timer::ticks();
             }
+            self.start_quantum(cpu);
 
             // Get raw pointers to the contexts
             // CRITICAL: Do NOT use .unwrap() here! It can panic with formatting.
@@ -293,18 +435,25 @@ impl Scheduler {
 
             // Update The Weaver's Sigil (stack canary) for the new thread
             // SECURITY: This MUST happen before the context switch so that
-            // LLVM-generated code in the new thread uses the correct canary
-            let next_sigil = self.threads[to_idx].sigil;
-            unsafe {
-                crate::stack_protection::set_current_canary(next_sigil);
+            // LLVM-generated code in the new thread uses the correct canary.
+            // The guard is a single global, so once other CPUs are running
+            // threads it stays at its boot value.
+            if smp::online_cpus() == 1 {
+                let next_sigil = self.threads[to_idx].sigil;
+                unsafe {
+                    crate::stack_protection::set_current_canary(next_sigil);
+                }
             }
 
             // Update current thread ID
-            self.current_thread = Some(next_id);
+            self.current[cpu] = Some(next_id);
             self.context_switches += 1;
 
             // Check if we need to update TSS.rsp[0]
-            let new_kernel_stack = if let Some(vessel_id) = self.threads[to_idx].vessel_id() {
+            let new_kernel_stack = if let Some(stacks) = self.threads[to_idx].crew_stacks {
+                // A Vessel thread with a kernel stack of its own
+                Some(stacks.kernel_stack)
+            } else if let Some(vessel_id) = self.threads[to_idx].vessel_id() {
                 // This is a user-mode thread - get its vessel's kernel stack
                 use crate::loom_of_fate::get_harbor;
                 let harbor = get_harbor().lock();
@@ -315,7 +464,9 @@ impl Scheduler {
 
             (true, from_ctx_ptr, to_ctx_ptr, new_kernel_stack)
         } else {
-            // Same thread or no current thread - don't switch
+            // Same thread or no current thread - don't switch. A thread that
+            // tried to park but was picked again simply keeps running.
+            self.cancel_current_park();
             (false, core::ptr::null_mut(), core::ptr::null(), None)
         }
     }
@@ -325,6 +476,33 @@ impl Scheduler {
         // Currently nothing to do here, but this provides a hook for future cleanup
     }
 
+    /// Release the thread this CPU switched away from, now that the switch
+    /// is complete and its context saved
+    pub fn finish_switch(&mut self) {
+        self.release_outgoing(smp::current_cpu());
+    }
+
+    fn release_outgoing(&mut self, cpu: usize) {
+        OUTGOING.fetch_and(!(1 << cpu), Ordering::Release);
+        let Some(thread_id) = self.outgoing[cpu].take() else {
+            return;
+        };
+
+        // Parked and Fading threads stay out; idle threads are never queued
+        let ready = !self.is_idle_thread(thread_id)
+            && self.find_thread(thread_id).is_some_and(|thread| thread.state() == ThreadState::Resting);
+        if ready {
+            self.enqueue(thread_id);
+        }
+    }
+
+    /// Whether a thread may keep its CPU (it is neither parking nor Fading)
+    fn can_continue(&self, thread_id: Option<ThreadId>) -> bool {
+        thread_id
+            .and_then(|id| self.find_thread(id))
+            .is_some_and(|thread| thread.state() != ThreadState::Fading && !thread.is_parked())
+    }
+
     /// Perform a context switch between two threads
     ///
     /// # Safety
@@ -443,12 +621,12 @@ impl Scheduler {
         }
     }
 
-    /// Rebalance the ready queue when system harmony is low
+    /// Rebalance a CPU's ready queue when system harmony is low
     /// This promotes cooperative threads and demotes parasitic ones
-    fn rebalance_for_harmony(&mut self) {
+    fn rebalance_for_harmony(&mut self, cpu: usize) {
         // Collect thread info from ready queue
         let mut queue_info: Vec<(ThreadId, f32)> = self
-            .ready_queue
+            .run_queues[cpu]
             .iter()
             .filter_map(|&id| {
                 self.find_thread(id).map(|t| (id, t.harmony_score()))
@@ -459,18 +637,43 @@ impl Scheduler {
         queue_info.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(core::cmp::Ordering::Equal));
 
         // Rebuild ready queue with harmony-prioritized order
-        self.ready_queue.clear();
+        self.run_queues[cpu].clear();
         for (id, _) in queue_info {
-            self.ready_queue.push_back(id);
+            self.run_queues[cpu].push_back(id);
         }
     }
 
-    /// Select the next thread to run - simple round-robin
-    fn select_next_thread(&mut self) -> Option<ThreadId> {
-        // Pop from the front of the queue, skipping any Fading threads
-        // (defensive programming - Fading threads should not be in the queue)
+    /// Select the next thread to run - round-robin on this CPU's queue,
+    /// stealing from the busiest other queue when it is empty
+    fn select_next_thread(&mut self, cpu: usize) -> Option<ThreadId> {
+        // A direct hand-off jumps the queue, as long as the target is ready
+        if let Some(target) = self.handoff_target.take() {
+            for queue in self.run_queues.iter_mut() {
+                if let Some(position) = queue.iter().position(|&id| id == target) {
+                    queue.remove(position);
+                    return Some(target);
+                }
+            }
+        }
+
+        if let Some(next_id) = self.pop_ready(cpu, false) {
+            return Some(next_id);
+        }
+
+        // Work stealing: take from the back of the longest other queue, the
+        // thread its owner would have run last
+        let victim = (0..MAX_CPUS)
+            .filter(|&other| other != cpu && !self.run_queues[other].is_empty())
+            .max_by_key(|&other| self.run_queues[other].len())?;
+        self.pop_ready(victim, true)
+    }
+
+    /// Take a thread from one end of a CPU's queue, skipping any Fading
+    /// threads (defensive programming - Fading threads should not be queued)
+    fn pop_ready(&mut self, cpu: usize, from_back: bool) -> Option<ThreadId> {
         loop {
-            let next_id = self.ready_queue.pop_front()?;
+            let queue = &mut self.run_queues[cpu];
+            let next_id = if from_back { queue.pop_back()? } else { queue.pop_front()? };
 
             // Check if thread is still valid and not Fading
             if let Some(thread) = self.find_thread(next_id) {
@@ -493,16 +696,16 @@ impl Scheduler {
         self.threads.iter_mut().find(|t| t.id() == id)
     }
 
-    /// Get the current thread ID
+    /// Get the ID of the thread running on this CPU
     pub fn current_thread_id(&self) -> Option<ThreadId> {
-        self.current_thread
+        self.current[smp::current_cpu()]
     }
 
     /// Get scheduler statistics
     pub fn stats(&self) -> SchedulerStats {
         // Use the latest metrics from the analyzer
         SchedulerStats {
-            total_threads: self.threads.len(),
+            total_threads: self.threads.len() - self.vacant.len(),
             weaving_threads: self
                 .threads
                 .iter()
@@ -518,6 +721,13 @@ impl Scheduler {
                 .iter()
                 .filter(|t| t.state() == ThreadState::Tangled)
                 .count(),
+            blocked_threads: self
+                .threads
+                .iter()
+                .filter(|t| t.is_parked())
+                .count(),
+            sleeping_threads: self.sleep_queue.len(),
+            online_cpus: smp::online_cpus(),
             average_harmony: self.latest_metrics.average_harmony,
             system_harmony: self.latest_metrics.system_harmony,
             parasite_count: self.latest_metrics.parasite_count,
@@ -561,7 +771,8 @@ impl Scheduler {
     pub fn start_weaving(&mut self) -> ! {
         // Select the first thread to run (highest priority thread from ready queue)
         // CRITICAL: Do NOT use .expect() here! It can panic with formatting.
-        let next_id = match self.select_next_thread() {
+        let cpu = smp::current_cpu();
+        let next_id = match self.select_next_thread(cpu) {
             Some(id) => id,
             None => {
                 unsafe {
@@ -582,7 +793,7 @@ impl Scheduler {
         };
 
         // Mark this thread as currently running
-        self.current_thread = Some(next_id);
+        self.current[cpu] = Some(next_id);
 
         // Update the thread's state to Weaving
         if let Some(thread) = self.find_thread_mut(next_id) {
@@ -590,6 +801,7 @@ impl Scheduler {
             thread.record_time_slice();
             thread.last_run_time = crate::attunement::timer::ticks();
         }
+        self.start_quantum(cpu);
 
         self.context_switches += 1;
 
@@ -625,14 +837,17 @@ impl Scheduler {
 
     /// Prepare for the Great Hand-Off
     ///
-    /// Sets the idle thread as the current thread and removes it from the ready queue.
+    /// Sets the idle thread as this CPU's current thread and idle thread, and
+    /// removes it from the ready queues.
     /// This must be called before context_switch_first to ensure proper scheduler state.
     pub fn prepare_handoff(&mut self, thread_id: ThreadId) {
         // Remove thread from ready queue since it's about to become current
-        self.ready_queue.retain(|&id| id != thread_id);
+        self.remove_from_queues(thread_id);
 
         // Set it as the current thread
-        self.current_thread = Some(thread_id);
+        let cpu = smp::current_cpu();
+        self.current[cpu] = Some(thread_id);
+        self.idle[cpu] = Some(thread_id);
 
         // Mark it as Weaving (running)
         if let Some(thread) = self.find_thread_mut(thread_id) {
@@ -640,6 +855,230 @@ impl Scheduler {
         }
     }
 
+    // === Parking (Blocking Waits) ===
+
+    /// Take the current thread off the ready queue at its next yield
+    ///
+    /// The thread is Blocked for `reason`, outside the ready queue, until
+    /// `wake` is called for it or the timer reaches `deadline`. Joining a
+    /// thread that has already faded does not block at all.
+    ///
+    /// # Returns
+    /// * `true` - The thread is parked; the caller must now yield
+    /// * `false` - A wake-up arrived first; the caller should not block
+    pub fn park_current(&mut self, reason: BlockReason, deadline: Option<u64>) -> bool {
+        let Some(current_id) = self.current_thread_id() else {
+            return false;
+        };
+        match reason {
+            BlockReason::Join(target) if self.has_faded(target) => return false,
+            BlockReason::Wait { seen } if vanishings() != seen => return false,
+            _ => {}
+        }
+        let Some(thread) = self.find_thread_mut(current_id) else {
+            return false;
+        };
+
+        if core::mem::take(&mut thread.wake_pending) {
+            return false;
+        }
+
+        thread.set_state(ThreadState::Blocked(reason));
+        thread.wake_deadline = deadline;
+        if let Some(deadline) = deadline {
+            self.sleep_queue.insert((deadline, current_id));
+            self.publish_next_wake();
+        }
+        true
+    }
+
+    /// Wake a parked thread, returning it to the ready queue
+    ///
+    /// If the thread has not parked yet the wake-up is remembered, so one
+    /// that races with `park_current` is never lost. Waiters must therefore
+    /// tolerate spurious wake-ups and recheck what they were waiting for.
+    ///
+    /// # Returns
+    /// `false` if the thread does not exist or is Fading
+    pub fn wake(&mut self, thread_id: ThreadId) -> bool {
+        let Some(thread) = self.find_thread_mut(thread_id) else {
+            return false;
+        };
+
+        if thread.state() == ThreadState::Fading {
+            return false;
+        }
+
+        if !thread.is_parked() {
+            thread.wake_pending = true;
+            return true;
+        }
+
+        self.unblock(thread_id);
+        true
+    }
+
+    /// Run `thread_id` next, ahead of everything in the ready queue
+    ///
+    /// Used for synchronous IPC, where the caller has just handed work to a
+    /// server and has nothing to do until it replies. The target must be
+    /// ready (e.g. just woken); otherwise the hint is ignored.
+    pub fn hand_off_to(&mut self, thread_id: ThreadId) {
+        self.handoff_target = Some(thread_id);
+    }
+
+    /// Wake every parked thread whose deadline is at or before `now`
+    ///
+    /// Only the front of the sleep queue is examined, so this is cheap
+    /// enough to run from the timer interrupt.
+    pub fn wake_expired(&mut self, now: u64) {
+        while let Some(&(deadline, thread_id)) = self.sleep_queue.first() {
+            if deadline > now {
+                break;
+            }
+            self.sleep_queue.pop_first();
+
+            let still_waiting = self
+                .find_thread(thread_id)
+                .is_some_and(|thread| thread.is_parked() && thread.wake_deadline == Some(deadline));
+            if still_waiting {
+                self.unblock(thread_id);
+            }
+        }
+        self.publish_next_wake();
+    }
+
+    /// Mark a thread Fading and wake every thread joining it
+    ///
+    /// The thread leaves the ready and sleep queues for good. If it is the
+    /// current thread, the caller must yield afterwards.
+    pub fn fade(&mut self, thread_id: ThreadId) -> Result<(), LoomError> {
+        let thread = self.find_thread_mut(thread_id).ok_or(LoomError::ThreadNotFound)?;
+        thread.set_state(ThreadState::Fading);
+        if let Some(deadline) = thread.wake_deadline.take() {
+            self.sleep_queue.remove(&(deadline, thread_id));
+            self.publish_next_wake();
+        }
+        self.remove_from_queues(thread_id);
+
+        let joiners: Vec<ThreadId> = self
+            .threads
+            .iter()
+            .filter(|thread| thread.block_reason() == Some(BlockReason::Join(thread_id)))
+            .map(|thread| thread.id())
+            .collect();
+        for joiner in joiners {
+            self.unblock(joiner);
+        }
+        Ok(())
+    }
+
+    /// Wake the threads of `vessel_id` waiting for one of its children to
+    /// vanish (`None`: the kernel threads waiting for Vessels with no parent)
+    ///
+    /// Called whenever a Vessel vanishes, after the Harbor records it.
+    pub fn wake_child_waiters(&mut self, vessel_id: Option<VesselId>) {
+        VANISHINGS.fetch_add(1, Ordering::Release);
+
+        let waiters: Vec<ThreadId> = self
+            .threads
+            .iter()
+            .filter(|thread| {
+                matches!(thread.block_reason(), Some(BlockReason::Wait { .. })) && thread.vessel_id() == vessel_id
+            })
+            .map(|thread| thread.id())
+            .collect();
+        for waiter in waiters {
+            self.unblock(waiter);
+        }
+    }
+
+    /// Whether a thread is running, or its CPU is still switching away from it
+    pub fn is_on_cpu(&self, thread_id: ThreadId) -> bool {
+        self.current.contains(&Some(thread_id)) || self.outgoing.contains(&Some(thread_id))
+    }
+
+    /// Give the table slots of faded threads back, for new threads to reuse
+    ///
+    /// # Returns
+    /// The stacks of their own the released threads leave behind, for the
+    /// caller to free, or `None`, releasing nothing, if any of them is still
+    /// on a CPU; its context may still be being saved, so the caller must
+    /// try again later
+    pub fn release_threads(&mut self, thread_ids: &[ThreadId]) -> Option<Vec<CrewStacks>> {
+        if thread_ids.iter().any(|&thread_id| self.is_on_cpu(thread_id)) {
+            return None;
+        }
+
+        let mut stacks = Vec::new();
+        for &thread_id in thread_ids {
+            let index = self
+                .threads
+                .iter()
+                .position(|thread| thread.id() == thread_id && thread.state() == ThreadState::Fading);
+            if let Some(index) = index {
+                if !self.vacant.contains(&index) {
+                    self.vacant.push(index);
+                    stacks.extend(self.threads[index].crew_stacks.take());
+                }
+            }
+        }
+        Some(stacks)
+    }
+
+    /// Check whether a thread has faded (or never existed)
+    pub fn has_faded(&self, thread_id: ThreadId) -> bool {
+        self.find_thread(thread_id)
+            .is_none_or(|thread| thread.state() == ThreadState::Fading)
+    }
+
+    /// Find a thread by ID
+    pub fn thread(&self, thread_id: ThreadId) -> Option<&Thread> {
+        self.find_thread(thread_id)
+    }
+
+    /// Undo a park when no switch happens, so the running thread is never
+    /// left Blocked
+    fn cancel_current_park(&mut self) {
+        if let Some(current_id) = self.current_thread_id() {
+            if self.find_thread(current_id).is_some_and(|thread| thread.is_parked()) {
+                self.unblock(current_id);
+            }
+        }
+    }
+
+    /// Take a thread out of Blocked
+    ///
+    /// It rejoins the ready queue, unless it is a current thread caught
+    /// while still parking, which simply keeps running, or one whose CPU
+    /// is still switching away from it, which rejoins once that finishes.
+    fn unblock(&mut self, thread_id: ThreadId) {
+        let is_current = self.current.contains(&Some(thread_id));
+        let is_outgoing = self.outgoing.contains(&Some(thread_id));
+        let Some(thread) = self.find_thread_mut(thread_id) else {
+            return;
+        };
+
+        thread.set_state(if is_current { ThreadState::Weaving } else { ThreadState::Resting });
+        if let Some(deadline) = thread.wake_deadline.take() {
+            self.sleep_queue.remove(&(deadline, thread_id));
+            self.publish_next_wake();
+        }
+        if !is_current && !is_outgoing {
+            self.enqueue(thread_id);
+        }
+    }
+
+    /// Publish the sleep queue's earliest deadline for the timer interrupt
+    ///
+    /// On a tickless system this CPU also arms its timer for it, so every
+    /// deadline is watched by the CPU that queued it.
+    fn publish_next_wake(&self) {
+        let next = self.sleep_queue.first().map_or(u64::MAX, |&(deadline, _)| deadline);
+        NEXT_WAKE_TICK.store(next, Ordering::Relaxed);
+        crate::attunement::timer::arm_next_wake();
+    }
+
     // === Preemptive Multitasking Control ===
 
     /// Enable preemptive multitasking with the given time quantum
@@ -652,7 +1091,7 @@ impl Scheduler {
     pub fn enable_preemption(&mut self, quantum_ms: u64) {
         self.preemption_enabled = true;
         self.time_quantum = quantum_ms;
-        self.quantum_remaining = quantum_ms;
+        self.start_quantum(smp::current_cpu());
     }
 
     /// Disable preemptive multitasking (return to cooperative mode)
@@ -666,28 +1105,44 @@ impl Scheduler {
     ///
     /// Returns true if:
     /// - Preemption is enabled
-    /// - Current thread's quantum has expired (quantum_remaining == 0)
+    /// - The clock has passed the end of this CPU's current quantum
     pub fn should_preempt(&mut self) -> bool {
         if !self.preemption_enabled {
             return false;
         }
 
-        if self.quantum_remaining == 0 {
+        let cpu = smp::current_cpu();
+        if clocksource::now_ns() >= self.quantum_ends[cpu] {
             // Quantum expired! Reset for next thread
-            self.quantum_remaining = self.time_quantum;
+            self.start_quantum(cpu);
             return true;
         }
 
         false
     }
 
-    /// Decrement the current thread's quantum (called on each timer tick)
+    /// Check the current thread's quantum against the clock (called from
+    /// the timer interrupt)
     ///
-    /// This is called from the timer interrupt handler to track how much
-    /// time the current thread has used.
+    /// A tickless CPU's timer may have fired for something else, such as
+    /// a sleeper; while the quantum still runs its end is armed again, so
+    /// the CPU is interrupted when it is spent.
     pub fn tick_quantum(&mut self) {
-        if self.preemption_enabled && self.quantum_remaining > 0 {
-            self.quantum_remaining -= 1;
+        if !self.preemption_enabled {
+            return;
+        }
+        let quantum_end = self.quantum_ends[smp::current_cpu()];
+        if clocksource::now_ns() < quantum_end {
+            timer::request_deadline(quantum_end);
+        }
+    }
+
+    /// Give the thread now running on `cpu` a fresh quantum
+    fn start_quantum(&mut self, cpu: usize) {
+        let quantum_end = clocksource::now_ns().saturating_add(self.time_quantum * 1_000_000);
+        self.quantum_ends[cpu] = quantum_end;
+        if self.preemption_enabled {
+            timer::request_deadline(quantum_end);
         }
     }
 
@@ -709,6 +1164,11 @@ pub struct SchedulerStats {
     pub weaving_threads: usize,
     pub resting_threads: usize,
     pub tangled_threads: usize,
+    pub blocked_threads: usize,
+    /// Blocked threads with a wake-up deadline
+    pub sleeping_threads: usize,
+    /// Processors running threads
+    pub online_cpus: usize,
     pub average_harmony: f32,
     pub system_harmony: f32,
     pub parasite_count: usize,
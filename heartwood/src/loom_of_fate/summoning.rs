@@ -0,0 +1,343 @@
+//! # Summoning - Calling Vessels Forth From Scrolls
+//!
+//! Starts a new Vessel from an ELF file in the VFS, so user programs can
+//! live on a data disk instead of being embedded in the Heartwood.
+//!
+//! The file is read and checked, the Groves it needs are gathered (see
+//! `grove_linker`), and it is moored in the Harbor with
+//! `Harbor::moor_user_vessel` and given a first thread whose stack holds
+//! what a System V program expects at its entry point:
+//!
+//! ```text
+//!   stack top →  16 random bytes (AT_RANDOM)
+//!                argument and environment strings
+//!                (padding to 16 bytes)
+//!                AT_NULL, 0
+//!                auxiliary vector (type, value) pairs
+//!                NULL
+//!                envp[envc - 1] ... envp[0]
+//!                NULL
+//!                argv[argc - 1] ... argv[0]
+//!   rsp      →   argc
+//! ```
+//!
+//! Only the top page of a new stack is mapped, so everything has to fit
+//! in it; `covenant::spawn::MAX_ARGS_LEN` keeps the strings well inside.
+
+use super::grove_linker::{self, LinkError};
+use super::{ElfError, LoomError, ThreadId, ThreadPriority, VesselId};
+use crate::mana_pool::concordance_of_fates;
+use crate::mana_pool::entropy::HardwareRng;
+use crate::mana_pool::user_space::RegionType;
+use crate::vfs::{FsError, Path};
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use covenant::auxv::{AT_ENTRY, AT_NULL, AT_PAGESZ, AT_PHDR, AT_PHENT, AT_PHNUM, AT_RANDOM};
+use x86_64::VirtAddr;
+
+/// Bytes of the initial stack that are mapped before the first thread runs
+const INITIAL_STACK_PAGE: u64 = 0x1000;
+
+/// Why a Vessel could not be summoned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SummonError {
+    /// No filesystem is mounted
+    NoFilesystem,
+
+    /// The file could not be read
+    Read(FsError),
+
+    /// The file is not a loadable ELF executable
+    BadElf(ElfError),
+
+    /// A Grove the program needs is missing or malformed
+    Link(LinkError),
+
+    /// The arguments and environment do not fit on the initial stack
+    ArgsTooLong,
+
+    /// The Vessel's address space could not be built
+    Vessel(&'static str),
+
+    /// The initial stack could not be written into the new address space
+    Stack(&'static str),
+
+    /// The Vessel's first thread could not be created
+    Thread(LoomError),
+}
+
+impl core::fmt::Display for SummonError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            SummonError::NoFilesystem => write!(f, "No filesystem mounted"),
+            SummonError::Read(e) => write!(f, "Cannot read file: {:?}", e),
+            SummonError::BadElf(e) => write!(f, "{}", e),
+            SummonError::Link(e) => write!(f, "{}", e),
+            SummonError::ArgsTooLong => write!(f, "Argument list too long"),
+            SummonError::Vessel(e) => write!(f, "{}", e),
+            SummonError::Stack(e) => write!(f, "Cannot build the initial stack: {}", e),
+            SummonError::Thread(e) => write!(f, "Cannot create thread: {:?}", e),
+        }
+    }
+}
+
+/// A laid-out initial stack, ready to be copied into the Vessel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InitialStack {
+    /// The bytes from `rsp` up to the stack top
+    pub image: Vec<u8>,
+
+    /// Stack pointer at entry (points at `argc`, 16-byte aligned)
+    pub rsp: u64,
+}
+
+/// The Fate given to Vessels summoned by the kernel itself (e.g. from Eldarin)
+pub fn default_fate() -> String {
+    if concordance_of_fates::is_concordance_active() {
+        String::from(unsafe { concordance_of_fates::get_concordance() }.default_fate())
+    } else {
+        String::from("user")
+    }
+}
+
+/// Summon a Vessel from an ELF file in the VFS
+///
+/// # Arguments
+///
+/// * `path` - Absolute path of the ELF file
+/// * `argv` - Arguments, `argv[0]` being the program name by convention
+/// * `envp` - Environment strings (`KEY=value`)
+/// * `parent` - The summoning Vessel, if any
+/// * `fate` - RBAC role of the new Vessel
+///
+/// # Returns
+///
+/// * `Ok(VesselId)` - The new Vessel, whose first thread is ready to run
+/// * `Err(SummonError)` - Why it could not be summoned
+pub fn summon_from_path(
+    path: &str,
+    argv: &[&[u8]],
+    envp: &[&[u8]],
+    parent: Option<VesselId>,
+    fate: String,
+) -> Result<VesselId, SummonError> {
+    let elf_data = read_scroll(path)?;
+    summon_from_elf(&elf_data, argv, envp, parent, fate)
+}
+
+/// Summon a Vessel from ELF data already in memory
+///
+/// See [`summon_from_path`].
+pub fn summon_from_elf(
+    elf_data: &[u8],
+    argv: &[&[u8]],
+    envp: &[&[u8]],
+    parent: Option<VesselId>,
+    fate: String,
+) -> Result<VesselId, SummonError> {
+    let loaded_elf = super::load_elf(elf_data).map_err(SummonError::BadElf)?;
+
+    // The Heartwood is the dynamic linker; any interpreter is ignored
+    if let Some(interpreter) = &loaded_elf.interpreter {
+        crate::serial_println!("[SUMMON] Ignoring PT_INTERP {}; Groves are linked by the kernel", interpreter);
+    }
+    let groves = grove_linker::gather_groves(&loaded_elf, elf_data, |path| read_scroll(path).ok())
+        .map_err(SummonError::Link)?;
+
+    let mut random = [0u8; 16];
+    random[..8].copy_from_slice(&HardwareRng::u64().to_le_bytes());
+    random[8..].copy_from_slice(&HardwareRng::u64().to_le_bytes());
+
+    let mut auxv = vec![
+        (AT_PAGESZ, 0x1000),
+        (AT_ENTRY, loaded_elf.entry_point),
+    ];
+    if loaded_elf.program_headers != 0 {
+        auxv.push((AT_PHDR, loaded_elf.program_headers));
+        auxv.push((AT_PHENT, loaded_elf.phentsize as u64));
+        auxv.push((AT_PHNUM, loaded_elf.phnum as u64));
+    }
+
+    let (vessel_id, stack) = super::without_interrupts(|| {
+        let mut harbor = super::get_harbor().lock();
+        let vessel_id = harbor
+            .moor_user_vessel(parent, elf_data, &groves, fate, ThreadId(0))
+            .map_err(SummonError::Vessel)?;
+
+        let stack = harbor
+            .find_vessel(vessel_id)
+            .ok_or(SummonError::Stack("Vessel vanished while being summoned"))
+            .and_then(|vessel| {
+                let stack_top = vessel
+                    .address_space()
+                    .regions
+                    .iter()
+                    .find(|r| r.region_type == RegionType::Stack)
+                    .ok_or(SummonError::Stack("No stack region"))?
+                    .end()
+                    .as_u64();
+                let stack = build_initial_stack(stack_top, argv, envp, &auxv, random)
+                    .ok_or(SummonError::ArgsTooLong)?;
+                vessel
+                    .address_space()
+                    .write_mapped(VirtAddr::new(stack.rsp), &stack.image)
+                    .map_err(SummonError::Stack)?;
+                Ok((vessel.entry_point(), stack.rsp))
+            });
+
+        Ok::<_, SummonError>((vessel_id, stack))
+    })?;
+
+    let (entry_point, rsp) = match stack {
+        Ok(stack) => stack,
+        Err(e) => {
+            super::scuttle(vessel_id);
+            return Err(e);
+        }
+    };
+
+    let thread_id = match super::create_user_thread(vessel_id, entry_point, rsp, ThreadPriority::Normal) {
+        Ok(thread_id) => thread_id,
+        Err(e) => {
+            super::scuttle(vessel_id);
+            return Err(SummonError::Thread(e));
+        }
+    };
+
+    super::without_interrupts(|| {
+        if let Some(vessel) = super::get_harbor().lock().find_vessel_mut(vessel_id) {
+            vessel.main_thread = thread_id;
+        }
+    });
+
+    crate::serial_println!("[SUMMON] Vessel {} summoned with {} args, thread {}",
+        vessel_id.0, argv.len(), thread_id.0);
+
+    Ok(vessel_id)
+}
+
+/// Read a whole file through the global filesystem
+fn read_scroll(path: &str) -> Result<Vec<u8>, SummonError> {
+    let global_fs = crate::vfs::global::get().ok_or(SummonError::NoFilesystem)?;
+    let fs_lock = global_fs.lock();
+    let fs = fs_lock.as_ref().ok_or(SummonError::NoFilesystem)?;
+    fs.read(&Path::new(path)).map_err(SummonError::Read)
+}
+
+/// Lay out argc, argv, envp and the auxiliary vector below `stack_top`
+///
+/// `AT_RANDOM` (pointing at `random`) and the closing `AT_NULL` are added
+/// to `auxv`. Returns `None` if the result would not fit in the stack's
+/// first page.
+pub fn build_initial_stack(
+    stack_top: u64,
+    argv: &[&[u8]],
+    envp: &[&[u8]],
+    auxv: &[(u64, u64)],
+    random: [u8; 16],
+) -> Option<InitialStack> {
+    let strings_len: usize = argv.iter().chain(envp).map(|s| s.len() + 1).sum();
+    let strings_start = stack_top.checked_sub((strings_len + random.len()) as u64)?;
+
+    // argc, argv + NULL, envp + NULL, auxv + AT_RANDOM + AT_NULL
+    let words = 1 + (argv.len() + 1) + (envp.len() + 1) + 2 * (auxv.len() + 2);
+    let rsp = (strings_start & !0xF).checked_sub(words as u64 * 8)? & !0xF;
+    if stack_top - rsp > INITIAL_STACK_PAGE {
+        return None;
+    }
+
+    let mut image = vec![0u8; (stack_top - rsp) as usize];
+    let offset = |addr: u64| (addr - rsp) as usize;
+
+    // Strings, then the random bytes at the very top
+    let mut pointers = Vec::with_capacity(argv.len() + envp.len());
+    let mut cursor = strings_start;
+    for s in argv.iter().chain(envp) {
+        image[offset(cursor)..offset(cursor) + s.len()].copy_from_slice(s);
+        pointers.push(cursor);
+        cursor += s.len() as u64 + 1;
+    }
+    let random_addr = cursor;
+    image[offset(random_addr)..].copy_from_slice(&random);
+
+    // The word area, upwards from rsp
+    let (argv_ptrs, envp_ptrs) = pointers.split_at(argv.len());
+    let mut word_values = Vec::with_capacity(words);
+    word_values.push(argv.len() as u64);
+    word_values.extend_from_slice(argv_ptrs);
+    word_values.push(0);
+    word_values.extend_from_slice(envp_ptrs);
+    word_values.push(0);
+    for &(key, value) in auxv.iter().chain(&[(AT_RANDOM, random_addr), (AT_NULL, 0)]) {
+        word_values.push(key);
+        word_values.push(value);
+    }
+    for (i, word) in word_values.iter().enumerate() {
+        image[i * 8..i * 8 + 8].copy_from_slice(&word.to_le_bytes());
+    }
+
+    Some(InitialStack { image, rsp })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOP: u64 = 0x7FFF_FFFF_0000;
+
+    fn word(stack: &InitialStack, index: usize) -> u64 {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&stack.image[index * 8..index * 8 + 8]);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn string_at(stack: &InitialStack, addr: u64) -> &[u8] {
+        let start = (addr - stack.rsp) as usize;
+        let len = stack.image[start..].iter().position(|&b| b == 0).unwrap();
+        &stack.image[start..start + len]
+    }
+
+    #[test]
+    fn test_initial_stack_layout() {
+        let argv: [&[u8]; 2] = [b"echo", b"hello"];
+        let envp: [&[u8]; 1] = [b"HOME=/"];
+        let stack = build_initial_stack(TOP, &argv, &envp, &[(AT_PAGESZ, 0x1000)], [7; 16]).unwrap();
+
+        assert_eq!(stack.rsp % 16, 0);
+        assert_eq!(stack.rsp + stack.image.len() as u64, TOP);
+
+        assert_eq!(word(&stack, 0), 2);
+        assert_eq!(string_at(&stack, word(&stack, 1)), b"echo");
+        assert_eq!(string_at(&stack, word(&stack, 2)), b"hello");
+        assert_eq!(word(&stack, 3), 0);
+        assert_eq!(string_at(&stack, word(&stack, 4)), b"HOME=/");
+        assert_eq!(word(&stack, 5), 0);
+
+        assert_eq!((word(&stack, 6), word(&stack, 7)), (AT_PAGESZ, 0x1000));
+        assert_eq!(word(&stack, 8), AT_RANDOM);
+        let random = (word(&stack, 9) - stack.rsp) as usize;
+        assert_eq!(&stack.image[random..random + 16], &[7; 16]);
+        assert_eq!((word(&stack, 10), word(&stack, 11)), (AT_NULL, 0));
+    }
+
+    #[test]
+    fn test_empty_arguments() {
+        let stack = build_initial_stack(TOP, &[], &[], &[], [0; 16]).unwrap();
+        assert_eq!(stack.rsp % 16, 0);
+        assert_eq!(word(&stack, 0), 0);
+        assert_eq!(word(&stack, 1), 0);
+        assert_eq!(word(&stack, 2), 0);
+        assert_eq!(word(&stack, 3), AT_RANDOM);
+    }
+
+    #[test]
+    fn test_must_fit_in_first_page() {
+        let long = [b'x'; 4080];
+        assert!(build_initial_stack(TOP, &[&long[..]], &[], &[], [0; 16]).is_none());
+
+        // Many empty arguments cost pointers, not string bytes
+        let many: Vec<&[u8]> = (0..600).map(|_| &b""[..]).collect();
+        assert!(build_initial_stack(TOP, &many, &[], &[], [0; 16]).is_none());
+    }
+}
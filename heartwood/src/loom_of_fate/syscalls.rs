@@ -30,9 +30,35 @@
 //! ; Result in rax
 //! ```
 
-use super::ThreadId;
 use x86_64::VirtAddr;
 use x86_64::registers::model_specific::{Efer, EferFlags, LStar, Msr};
+use covenant::errno;
+use covenant::fds;
+use covenant::ipc::{
+    RecvInfo, MAX_MESSAGE_CAPABILITIES, MAX_WAIT_SOURCES, MSG_BYTES, MSG_SHARED_MEMORY, TRANSFER_GRANT, TRANSFER_MOVE,
+    TRANSFER_SHARE, WAIT_FOREVER,
+};
+use covenant::open_flags::{O_ALL, O_CREATE, O_READ, O_TRUNCATE, O_WRITE};
+use covenant::prot::{PROT_ALL, PROT_EXEC, PROT_READ, PROT_WRITE};
+use covenant::spawn::MAX_ARGS_LEN;
+use covenant::wait::{ExitStatus, ANY_CHILD, EXITED, FAULTED};
+use covenant::SyscallArgs;
+use super::{LinkError, LoomError, SummonError, ThreadPriority, VesselExit, VesselId};
+use crate::attunement::ward_of_sacred_boundaries::{
+    sanctified_copy_slice_from_mortal, sanctified_copy_slice_to_mortal, validate_mortal_pointer,
+    WardError,
+};
+use crate::vfs::descriptor::{FdError, FileDescriptorTable, OpenFile, OpenFileKind};
+use crate::mana_pool::{Capability, CapabilityId, CapabilityRights};
+use crate::nexus::{Message, MessagePriority, MessageType, NexusError, TransferMode};
+use crate::vfs::{FileSystem, FsError, Path};
+
+/// System call numbers (AethelOS ABI)
+///
+/// These are the magical numbers that identify each system call.
+/// They live in the `covenant` crate so that the kernel and corelib can
+/// never disagree about them again.
+pub use covenant::numbers as syscall_numbers;
 
 /// Initialize syscall/sysret mechanism
 ///
@@ -269,17 +295,19 @@ extern "C" fn syscall_entry() {
     )
 }
 
-/// System call numbers (AethelOS ABI)
+/// Largest transfer a single read or write will perform
 ///
-/// These are the magical numbers that identify each system call.
-/// They are stable across kernel versions (ABI compatibility).
-pub mod syscall_numbers {
-    pub const SYS_YIELD: u64 = 0;   // Yield CPU to another thread
-    pub const SYS_WRITE: u64 = 1;   // Write to file descriptor
-    pub const SYS_EXIT: u64 = 2;    // Exit current thread
-    pub const SYS_GETPID: u64 = 3;  // Get process (Vessel) ID
-    pub const SYS_GETTID: u64 = 4;  // Get thread ID
-}
+/// Larger requests are rejected for writes and shortened for reads.
+const MAX_IO_CHUNK: usize = 4096;
+
+/// Longest path `SYS_OPEN` will copy in from user space
+const MAX_PATH_LEN: usize = 4096;
+
+/// Largest single mapping `SYS_MMAP` or `SYS_SHM_CREATE` will create (1 GB)
+const MAX_MMAP_SIZE: u64 = 0x4000_0000;
+
+/// The kernel heap, which backs shared objects, is mapped at KERNEL_BASE + phys
+const KERNEL_BASE: u64 = 0xFFFF_FFFF_8000_0000;
 
 /// System call result type
 ///
@@ -288,23 +316,89 @@ pub mod syscall_numbers {
 pub type SyscallResult = i64;
 
 /// System call error codes
+///
+/// Discriminants come straight from `covenant::errno`, so the value placed in
+/// RAX is exactly what corelib compares against.
 #[repr(i64)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SyscallError {
-    /// Invalid syscall number
-    ENOSYS = -38,
+    /// Permission denied
+    EPERM = errno::EPERM as i64,
+
+    /// No such file or directory
+    ENOENT = errno::ENOENT as i64,
+
+    /// No such process
+    ESRCH = errno::ESRCH as i64,
+
+    /// I/O error
+    EIO = errno::EIO as i64,
+
+    /// Argument list too long
+    E2BIG = errno::E2BIG as i64,
+
+    /// Not a loadable executable
+    ENOEXEC = errno::ENOEXEC as i64,
+
+    /// Bad file descriptor
+    EBADF = errno::EBADF as i64,
+
+    /// No child Vessel to wait for
+    ECHILD = errno::ECHILD as i64,
+
+    /// Try again
+    EAGAIN = errno::EAGAIN as i64,
+
+    /// Out of memory
+    ENOMEM = errno::ENOMEM as i64,
+
+    /// Access denied by the Concordance
+    EACCES = errno::EACCES as i64,
 
     /// Bad address (pointer validation failed)
-    EFAULT = -14,
+    EFAULT = errno::EFAULT as i64,
+
+    /// Device or resource busy
+    EBUSY = errno::EBUSY as i64,
+
+    /// File exists
+    EEXIST = errno::EEXIST as i64,
+
+    /// Not a directory
+    ENOTDIR = errno::ENOTDIR as i64,
+
+    /// Is a directory
+    EISDIR = errno::EISDIR as i64,
 
     /// Invalid argument
-    EINVAL = -22,
+    EINVAL = errno::EINVAL as i64,
 
-    /// Permission denied
-    EPERM = -1,
+    /// Too many open files
+    EMFILE = errno::EMFILE as i64,
 
-    /// No such process
-    ESRCH = -3,
+    /// No space left on device
+    ENOSPC = errno::ENOSPC as i64,
+
+    /// Read-only file system
+    EROFS = errno::EROFS as i64,
+
+    /// Broken pipe (the channel was closed)
+    EPIPE = errno::EPIPE as i64,
+
+    /// File name too long
+    ENAMETOOLONG = errno::ENAMETOOLONG as i64,
+
+    /// Invalid syscall number
+    ENOSYS = errno::ENOSYS as i64,
+
+    /// Directory not empty
+    ENOTEMPTY = errno::ENOTEMPTY as i64,
+
+    /// Too many levels of symbolic links
+    ELOOP = errno::ELOOP as i64,
+
+    /// Timed out
+    ETIMEDOUT = errno::ETIMEDOUT as i64,
 }
 
 impl From<SyscallError> for SyscallResult {
@@ -313,9 +407,136 @@ impl From<SyscallError> for SyscallResult {
     }
 }
 
+impl From<FsError> for SyscallError {
+    fn from(err: FsError) -> Self {
+        match err {
+            FsError::NotFound => SyscallError::ENOENT,
+            FsError::AlreadyExists => SyscallError::EEXIST,
+            FsError::PermissionDenied => SyscallError::EACCES,
+            FsError::NotADirectory => SyscallError::ENOTDIR,
+            FsError::IsADirectory => SyscallError::EISDIR,
+            FsError::InvalidPath => SyscallError::EINVAL,
+            FsError::IoError => SyscallError::EIO,
+            FsError::OutOfSpace => SyscallError::ENOSPC,
+            FsError::DirectoryNotEmpty => SyscallError::ENOTEMPTY,
+            FsError::ReadOnly => SyscallError::EROFS,
+            FsError::NotSupported => SyscallError::ENOSYS,
+            FsError::SymlinkLoop => SyscallError::ELOOP,
+        }
+    }
+}
+
+impl From<FdError> for SyscallError {
+    fn from(err: FdError) -> Self {
+        match err {
+            FdError::BadDescriptor => SyscallError::EBADF,
+            FdError::TooManyOpen => SyscallError::EMFILE,
+        }
+    }
+}
+
+impl From<NexusError> for SyscallError {
+    fn from(err: NexusError) -> Self {
+        match err {
+            NexusError::ChannelNotFound | NexusError::InvalidCapability => SyscallError::EBADF,
+            NexusError::ChannelFull => SyscallError::EAGAIN,
+            NexusError::ChannelClosed => SyscallError::EPIPE,
+            NexusError::OutOfChannels => SyscallError::ENOMEM,
+            NexusError::Timeout => SyscallError::ETIMEDOUT,
+            NexusError::PermissionDenied => SyscallError::EACCES,
+            NexusError::OutOfCapabilities => SyscallError::EMFILE,
+            NexusError::InvalidName => SyscallError::EINVAL,
+            NexusError::NameTaken => SyscallError::EEXIST,
+            NexusError::NameNotFound => SyscallError::ENOENT,
+            NexusError::NotificationNotFound => SyscallError::EBADF,
+            NexusError::IrqUnavailable => SyscallError::EBUSY,
+        }
+    }
+}
+
+impl From<SummonError> for SyscallError {
+    fn from(err: SummonError) -> Self {
+        match err {
+            SummonError::NoFilesystem => SyscallError::ENOENT,
+            SummonError::Read(e) => e.into(),
+            SummonError::BadElf(_) => SyscallError::ENOEXEC,
+            SummonError::Link(LinkError::MissingGrove) => SyscallError::ENOENT,
+            SummonError::Link(_) => SyscallError::ENOEXEC,
+            SummonError::ArgsTooLong => SyscallError::E2BIG,
+            SummonError::Vessel(_) | SummonError::Stack(_) => SyscallError::ENOMEM,
+            SummonError::Thread(_) => SyscallError::EAGAIN,
+        }
+    }
+}
+
+impl From<WardError> for SyscallError {
+    fn from(_err: WardError) -> Self {
+        SyscallError::EFAULT
+    }
+}
+
+/// Collapse a handler's inner result into the value placed in RAX
+fn into_syscall_result(result: Result<u64, SyscallError>) -> SyscallResult {
+    match result {
+        Ok(value) => value as i64,
+        Err(err) => err.into(),
+    }
+}
+
+/// A kernel-side syscall implementation
+///
+/// Every handler receives the full argument bundle and picks out what it needs.
+type SyscallHandler = unsafe fn(&SyscallArgs) -> SyscallResult;
+
+/// The syscall table, indexed by syscall number
+///
+/// Built by name rather than by position, so a slot can never drift away from
+/// its `covenant` constant. Slots without a kernel implementation yet answer
+/// `ENOSYS` through `sys_not_implemented`.
+static SYSCALL_TABLE: [SyscallHandler; syscall_numbers::SYSCALL_COUNT] = build_syscall_table();
+
+const fn build_syscall_table() -> [SyscallHandler; syscall_numbers::SYSCALL_COUNT] {
+    use syscall_numbers::*;
+
+    let mut table: [SyscallHandler; SYSCALL_COUNT] = [sys_not_implemented; SYSCALL_COUNT];
+
+    table[SYS_EXIT as usize] = sys_exit;
+    table[SYS_WRITE as usize] = sys_write;
+    table[SYS_READ as usize] = sys_read;
+    table[SYS_OPEN as usize] = sys_open;
+    table[SYS_CLOSE as usize] = sys_close;
+    table[SYS_GETPID as usize] = sys_getpid;
+    table[SYS_QUERY as usize] = sys_not_implemented;
+    table[SYS_COMMIT as usize] = sys_not_implemented;
+    table[SYS_MMAP as usize] = sys_mmap;
+    table[SYS_MUNMAP as usize] = sys_munmap;
+    table[SYS_SLEEP as usize] = sys_sleep;
+    table[SYS_YIELD as usize] = sys_yield;
+    table[SYS_THREAD_CREATE as usize] = sys_thread_create;
+    table[SYS_THREAD_JOIN as usize] = sys_thread_join;
+    table[SYS_IPC_SEND as usize] = sys_ipc_send;
+    table[SYS_IPC_RECV as usize] = sys_ipc_recv;
+    table[SYS_TIME as usize] = sys_time;
+    table[SYS_EXEC_SCRIPT as usize] = sys_not_implemented;
+    table[SYS_GETTID as usize] = sys_gettid;
+    table[SYS_NAME_REGISTER as usize] = sys_name_register;
+    table[SYS_NAME_RESOLVE as usize] = sys_name_resolve;
+    table[SYS_NOTIFY_CREATE as usize] = sys_notify_create;
+    table[SYS_NOTIFY_RAISE as usize] = sys_notify_raise;
+    table[SYS_WAIT_ANY as usize] = sys_wait_any;
+    table[SYS_IRQ_BIND as usize] = sys_irq_bind;
+    table[SYS_SPAWN as usize] = sys_spawn;
+    table[SYS_WAIT as usize] = sys_wait;
+    table[SYS_SHM_CREATE as usize] = sys_shm_create;
+    table[SYS_IPC_SEND_SHARED as usize] = sys_ipc_send_shared;
+
+    table
+}
+
 /// Dispatch a system call based on the syscall number and arguments
 ///
-/// This is called from the INT 0x80 handler with the thread's register state.
+/// This is called from both the `syscall` entry (Ring 3) and the INT 0x81
+/// gate (Ring 1) with the thread's register state.
 ///
 /// # Arguments
 /// * `syscall_num` - The syscall number (from RAX)
@@ -337,20 +558,29 @@ pub unsafe fn dispatch_syscall(
     arg1: u64,
     arg2: u64,
     arg3: u64,
-    _arg4: u64,
-    _arg5: u64,
-    _arg6: u64,
+    arg4: u64,
+    arg5: u64,
+    arg6: u64,
 ) -> SyscallResult {
-    match syscall_num {
-        syscall_numbers::SYS_YIELD => sys_yield(),
-        syscall_numbers::SYS_WRITE => sys_write(arg1, arg2, arg3),
-        syscall_numbers::SYS_EXIT => sys_exit(arg1 as i32),
-        syscall_numbers::SYS_GETPID => sys_getpid(),
-        syscall_numbers::SYS_GETTID => sys_gettid(),
-        _ => SyscallError::ENOSYS.into(),
+    // A thread started by iretq has not yet released the one it replaced
+    super::finish_switch();
+
+    let args = SyscallArgs::new(arg1, arg2, arg3, arg4, arg5, arg6);
+
+    match SYSCALL_TABLE.get(syscall_num as usize) {
+        Some(handler) => handler(&args),
+        None => SyscallError::ENOSYS.into(),
     }
 }
 
+/// Placeholder for ABI slots the kernel does not implement yet
+///
+/// # Returns
+/// Always `ENOSYS`
+unsafe fn sys_not_implemented(_args: &SyscallArgs) -> SyscallResult {
+    SyscallError::ENOSYS.into()
+}
+
 /// SYS_YIELD: Yield the CPU to another thread
 ///
 /// This allows cooperative multitasking from user space.
@@ -358,79 +588,111 @@ pub unsafe fn dispatch_syscall(
 ///
 /// # Returns
 /// Always returns 0 (success)
-fn sys_yield() -> SyscallResult {
+unsafe fn sys_yield(_args: &SyscallArgs) -> SyscallResult {
     // Call the existing yield_now() function
     super::yield_now();
     0
 }
 
+/// SYS_SLEEP: Sleep for a number of heartbeats
+///
+/// The thread waits in the Loom's sleep queue, using no CPU, until the
+/// timer tick that wakes it.
+///
+/// # Arguments
+/// * `arg1` - Number of heartbeats (timer ticks); 0 just yields
+///
+/// # Returns
+/// Always returns 0 (success)
+unsafe fn sys_sleep(args: &SyscallArgs) -> SyscallResult {
+    if args.arg1 == 0 {
+        super::yield_now();
+    } else {
+        super::sleep(args.arg1);
+    }
+    0
+}
+
+/// SYS_THREAD_CREATE: Start another thread in the caller's Vessel
+///
+/// The thread shares the Vessel's address space and gets its own user
+/// stack, at a random address, and kernel stack. It ends with SYS_EXIT.
+///
+/// # Arguments
+/// * `arg1` - Entry point, called with `arg2` as its only argument
+/// * `arg2` - Argument for the entry point
+///
+/// # Returns
+/// The new thread's ID, `EFAULT` if the entry point is not a user-space
+/// address, `EPERM` if the caller is not a user-mode Vessel, `EAGAIN` if
+/// the thread table is full, `ENOMEM` if its stacks cannot be allocated
+unsafe fn sys_thread_create(args: &SyscallArgs) -> SyscallResult {
+    into_syscall_result(create_thread(args.arg1, args.arg2))
+}
+
+/// SYS_THREAD_JOIN: Wait for a thread to terminate
+///
+/// Only threads of the caller's own Vessel can be joined. A joined thread's
+/// stacks are given back, and its ID may later be reused.
+///
+/// # Arguments
+/// * `arg1` - Thread ID
+///
+/// # Returns
+/// The thread's exit value, `ESRCH` if there is no such thread in the
+/// caller's Vessel, `EINVAL` if it is the calling thread
+unsafe fn sys_thread_join(args: &SyscallArgs) -> SyscallResult {
+    into_syscall_result(join_thread(args.arg1))
+}
+
 /// SYS_WRITE: Write data to a file descriptor
 ///
 /// # Arguments
-/// * `fd` - File descriptor (1 = stdout, 2 = stderr)
-/// * `buf` - Pointer to buffer in user space
-/// * `count` - Number of bytes to write
+/// * `arg1` - File descriptor (console or a file opened with `O_WRITE`)
+/// * `arg2` - Pointer to buffer in user space
+/// * `arg3` - Number of bytes to write
 ///
 /// # Returns
 /// Number of bytes written on success, negative error code on failure
 ///
 /// # Safety
 /// Validates that buf is a valid user-space pointer before accessing.
-unsafe fn sys_write(fd: u64, buf: u64, count: u64) -> SyscallResult {
-    // Validate file descriptor
-    if fd != 1 && fd != 2 {
-        // Only stdout (1) and stderr (2) are supported for now
-        return SyscallError::EINVAL.into();
-    }
+unsafe fn sys_write(args: &SyscallArgs) -> SyscallResult {
+    let (fd, buf, count) = (args.arg1, args.arg2, args.arg3);
 
     // Validate count
-    if count > 4096 {
+    if count > MAX_IO_CHUNK as u64 {
         // Prevent excessively large writes
         return SyscallError::EINVAL.into();
     }
 
-    // Validate buffer pointer (must be in user space < 0x8000_0000_0000)
-    if buf >= 0x8000_0000_0000 {
-        return SyscallError::EFAULT.into();
-    }
-
-    // TODO(phase-3): Use sanctified_copy_from_mortal to safely read user buffer
-    // For now, we'll use a direct access with STAC/CLAC
+    let file = match lookup_descriptor(fd) {
+        Ok(file) if file.is_writable() => file,
+        Ok(_) => return SyscallError::EBADF.into(),
+        Err(err) => return err.into(),
+    };
 
-    // Temporarily disable SMAP to access user memory
-    core::arch::asm!("stac", options(nomem, nostack, preserves_flags));
+    match file.kind {
+        OpenFileKind::Console => into_syscall_result(write_to_console(buf, count)),
+        OpenFileKind::File(_) => into_syscall_result(write_to_file(fd, &file, buf, count)),
+    }
+}
 
-    // DEBUG: Mark that STAC completed
-    core::arch::asm!(
-        "out dx, al",
-        in("dx") 0x3f8u16,
-        in("al") b'T',
-        options(nomem, nostack, preserves_flags)
-    );
+/// Write a user buffer straight to the serial console
+///
+/// The buffer comes in through the Ward a chunk at a time, so a bad pointer
+/// fails with EFAULT instead of faulting in the Heartwood.
+fn write_to_console(buf: u64, count: u64) -> Result<u64, SyscallError> {
+    validate_user_pointer(buf, count as usize)?;
 
-    // Read bytes from user buffer and write to console
-    let bytes_written = {
-        // DEBUG: Mark before creating slice
-        core::arch::asm!(
-            "out dx, al",
-            in("dx") 0x3f8u16,
-            in("al") b'B',
-            options(nomem, nostack, preserves_flags)
-        );
-
-        let slice = core::slice::from_raw_parts(buf as *const u8, count as usize);
-
-        // DEBUG: Mark after creating slice
-        core::arch::asm!(
-            "out dx, al",
-            in("dx") 0x3f8u16,
-            in("al") b'A',
-            options(nomem, nostack, preserves_flags)
-        );
+    let mut chunk = [0u8; 256];
+    let mut written = 0;
+    while written < count {
+        let len = core::cmp::min(chunk.len() as u64, count - written) as usize;
+        unsafe { sanctified_copy_slice_from_mortal(buf + written, &mut chunk[..len])? };
 
         // Write directly to serial port (avoid VGA/print! which may cause page faults)
-        for &byte in slice {
-            // Output via direct port I/O to avoid print infrastructure
+        for &byte in &chunk[..len] {
             unsafe {
                 core::arch::asm!(
                     "out dx, al",
@@ -441,23 +703,23 @@ unsafe fn sys_write(fd: u64, buf: u64, count: u64) -> SyscallResult {
             }
         }
 
-        count as i64
-    };
-
-    // Re-enable SMAP protection
-    core::arch::asm!("clac", options(nomem, nostack, preserves_flags));
+        written += len as u64;
+    }
 
-    bytes_written
+    Ok(count)
 }
 
 /// SYS_EXIT: Exit the current thread
 ///
+/// The Vessel vanishes with its last thread, keeping the main thread's
+/// exit code for whoever reaps it.
+///
 /// # Arguments
-/// * `exit_code` - Exit status code
+/// * `arg1` - Exit status code
 ///
 /// # Returns
 /// Never returns (thread is terminated)
-fn sys_exit(_exit_code: i32) -> SyscallResult {
+unsafe fn sys_exit(args: &SyscallArgs) -> SyscallResult {
     // Output simple debug marker via direct serial port I/O
     // (avoid print! macros which can cause page faults in syscall context)
     unsafe {
@@ -472,40 +734,23 @@ fn sys_exit(_exit_code: i32) -> SyscallResult {
         }
     }
 
-    // Mark the current thread as Fading so it won't be scheduled again
-    use super::thread::ThreadState;
-
-    super::without_interrupts(|| {
-        unsafe {
-            let loom = super::get_loom();
-            let mut loom_lock = loom.lock();
-
-            // Get current thread ID
-            if let Some(current_tid) = loom_lock.current_thread_id() {
-                // Find the thread and mark it as Fading
-                if let Some(thread) = loom_lock.threads.iter_mut().find(|t| t.id() == current_tid) {
-                    thread.set_state(ThreadState::Fading);
-
-                    // Debug output
+    // Mark the current thread as Fading so it won't be scheduled again,
+    // leaving its exit value for any joiners (and, if it is the last
+    // thread, as its Vessel's exit code)
+    if let Some(current_tid) = super::current_thread() {
+        if super::exit_thread(current_tid, args.arg1).is_ok() {
+            unsafe {
+                for &byte in b"[FADING]\n" {
                     core::arch::asm!(
                         "out dx, al",
                         in("dx") 0x3f8u16,
-                        in("al") b'[',
+                        in("al") byte,
                         options(nomem, nostack, preserves_flags)
                     );
-                    let msg = b"FADING]\n";
-                    for &byte in msg {
-                        core::arch::asm!(
-                            "out dx, al",
-                            in("dx") 0x3f8u16,
-                            in("al") byte,
-                            options(nomem, nostack, preserves_flags)
-                        );
-                    }
                 }
             }
         }
-    });
+    }
 
     // Yield one final time to switch to another thread
     // This thread will never be scheduled again because it's marked as Fading
@@ -521,17 +766,10 @@ fn sys_exit(_exit_code: i32) -> SyscallResult {
 ///
 /// # Returns
 /// The VesselId of the current thread's Vessel, or 0 if not in a Vessel
-fn sys_getpid() -> SyscallResult {
-    // Get current thread
-    let current_tid = super::current_thread();
-
-    if let Some(tid) = current_tid {
-        // TODO(phase-3): Look up the Vessel for this thread
-        // For now, return a dummy value
-        crate::serial_println!("SYS_GETPID called for thread {:?}", tid);
-        1 // Dummy PID
-    } else {
-        0
+unsafe fn sys_getpid(_args: &SyscallArgs) -> SyscallResult {
+    match super::current_vessel_id() {
+        Some(vessel_id) => vessel_id.0 as i64,
+        None => 0,
     }
 }
 
@@ -539,7 +777,7 @@ fn sys_getpid() -> SyscallResult {
 ///
 /// # Returns
 /// The ThreadId of the current thread
-fn sys_gettid() -> SyscallResult {
+unsafe fn sys_gettid(_args: &SyscallArgs) -> SyscallResult {
     let current_tid = super::current_thread();
 
     if let Some(tid) = current_tid {
@@ -549,6 +787,895 @@ fn sys_gettid() -> SyscallResult {
     }
 }
 
+/// SYS_TIME: Get the current time in heartbeats
+///
+/// # Returns
+/// Timer ticks since boot
+unsafe fn sys_time(_args: &SyscallArgs) -> SyscallResult {
+    crate::attunement::timer::ticks() as i64
+}
+
+/// SYS_OPEN: Open a file on the mounted filesystem
+///
+/// # Arguments
+/// * `arg1` - Pointer to the path in user space (not NUL-terminated)
+/// * `arg2` - Length of the path in bytes
+/// * `arg3` - `covenant::open_flags`
+///
+/// # Returns
+/// The new file descriptor (lowest free slot) on success, negative error code
+/// on failure
+unsafe fn sys_open(args: &SyscallArgs) -> SyscallResult {
+    into_syscall_result(open_descriptor(args.arg1, args.arg2, args.arg3))
+}
+
+/// SYS_READ: Read from a file descriptor at its current offset
+///
+/// # Arguments
+/// * `arg1` - File descriptor
+/// * `arg2` - Pointer to destination buffer in user space
+/// * `arg3` - Buffer length (reads are capped at 4096 bytes)
+///
+/// # Returns
+/// Number of bytes read (0 at end of file), negative error code on failure
+unsafe fn sys_read(args: &SyscallArgs) -> SyscallResult {
+    into_syscall_result(read_descriptor(args.arg1, args.arg2, args.arg3))
+}
+
+/// SYS_CLOSE: Close a file descriptor
+///
+/// # Arguments
+/// * `arg1` - File descriptor
+///
+/// # Returns
+/// 0 on success, `EBADF` if the descriptor was not open
+unsafe fn sys_close(args: &SyscallArgs) -> SyscallResult {
+    let fd = args.arg1;
+    let result = current_fd_table(|table| table.remove(fd as usize))
+        .and_then(|removed| removed.map_err(SyscallError::from))
+        .map(|_| 0);
+
+    into_syscall_result(result)
+}
+
+/// SYS_MMAP: Map anonymous, zero-filled memory into the calling Vessel
+///
+/// Pages are placed at an ASLR-randomized address and only backed by
+/// frames when first touched.
+///
+/// # Arguments
+/// * `arg1` - Length in bytes (rounded up to a whole page)
+/// * `arg2` - `covenant::prot` flags (must include `PROT_READ`; W^X enforced)
+///
+/// # Returns
+/// The base address of the mapping, or a negative error code
+unsafe fn sys_mmap(args: &SyscallArgs) -> SyscallResult {
+    into_syscall_result(map_anonymous(args.arg1, args.arg2))
+}
+
+/// SYS_MUNMAP: Remove a mapping created by SYS_MMAP, SYS_SHM_CREATE or SYS_IPC_RECV
+///
+/// Removing a shared memory mapping releases the Vessel's capability to it.
+///
+/// # Arguments
+/// * `arg1` - Base address returned by SYS_MMAP or SYS_SHM_CREATE (or reported by SYS_IPC_RECV)
+/// * `arg2` - Length of the mapping
+///
+/// # Returns
+/// 0 on success, `EINVAL` if the range is not exactly one mapping
+unsafe fn sys_munmap(args: &SyscallArgs) -> SyscallResult {
+    into_syscall_result(unmap_anonymous(args.arg1, args.arg2))
+}
+
+/// SYS_IPC_SEND: Send a message through a Nexus channel
+///
+/// Wakes a receiver parked on the channel, if any.
+///
+/// # Arguments
+/// * `arg1` - Capability ID of the channel (needs the send right)
+/// * `arg2` - Pointer to the payload in user space
+/// * `arg3` - Payload length (at most `MAX_IO_CHUNK`)
+/// * `arg4` - Pointer to an array of capability IDs to pass on
+/// * `arg5` - Number of capability IDs (at most `MAX_MESSAGE_CAPABILITIES`)
+///
+/// The sender keeps its capabilities; the receiver is given derived copies,
+/// which are revoked along with the sender's.
+///
+/// # Returns
+/// 0 on success, `EAGAIN` if the channel is full, `EPIPE` if it was closed,
+/// `EACCES` if a capability lacks the send or transfer right
+unsafe fn sys_ipc_send(args: &SyscallArgs) -> SyscallResult {
+    into_syscall_result(send_message(args.arg1, args.arg2, args.arg3, args.arg4, args.arg5))
+}
+
+/// SYS_IPC_RECV: Receive a message from a Nexus channel
+///
+/// The calling thread is parked until a message arrives, so a waiting
+/// service uses no CPU at all.
+///
+/// # Arguments
+/// * `arg1` - Capability ID of the channel (needs the receive right)
+/// * `arg2` - Pointer to destination buffer in user space
+/// * `arg3` - Buffer length (longer payloads are truncated)
+/// * `arg4` - Timeout in heartbeats (0 polls, `WAIT_FOREVER` never gives up)
+/// * `arg5` - Pointer to a `covenant::ipc::RecvInfo` in user space (may be 0)
+///
+/// Shared memory messages are mapped into the caller's address space (Ring 1
+/// Groves get the object's kernel address) and described in the `RecvInfo`,
+/// along with the IDs of any capabilities the message carried.
+///
+/// Without a `RecvInfo` there is nowhere to report a mapping or passed
+/// capabilities, so a message carrying either is taken off the channel and
+/// refused with `EINVAL`.
+///
+/// # Returns
+/// Number of payload bytes copied, `ETIMEDOUT` if nothing arrived in time,
+/// `EPIPE` if the channel was closed, `EBADF` for an unknown capability and
+/// `EACCES` if it lacks the receive right
+unsafe fn sys_ipc_recv(args: &SyscallArgs) -> SyscallResult {
+    into_syscall_result(receive_message(args.arg1, args.arg2, args.arg3, args.arg4, args.arg5))
+}
+
+/// SYS_NAME_REGISTER: Register a channel under a service name
+///
+/// # Arguments
+/// * `arg1` - Pointer to the name (e.g. `world-tree/query`)
+/// * `arg2` - Name length
+/// * `arg3` - Capability ID of the channel (needs send and transfer rights)
+///
+/// # Returns
+/// 0 on success, `EEXIST` if the name is taken, `EACCES` if the caller's
+/// Fate forbids it or the capability lacks a right
+unsafe fn sys_name_register(args: &SyscallArgs) -> SyscallResult {
+    into_syscall_result(register_name(args.arg1, args.arg2, args.arg3))
+}
+
+/// SYS_NAME_RESOLVE: Resolve a service name to a channel capability
+///
+/// # Arguments
+/// * `arg1` - Pointer to the name
+/// * `arg2` - Name length
+///
+/// # Returns
+/// The new capability ID, `ENOENT` if nothing is registered under the name,
+/// `EACCES` if the caller's Fate forbids resolving it
+unsafe fn sys_name_resolve(args: &SyscallArgs) -> SyscallResult {
+    into_syscall_result(resolve_name(args.arg1, args.arg2))
+}
+
+/// SYS_NOTIFY_CREATE: Create a notification
+///
+/// # Returns
+/// A capability ID with the send, receive and transfer rights, `ENOMEM` if
+/// the Nexus is out of notifications
+unsafe fn sys_notify_create(_args: &SyscallArgs) -> SyscallResult {
+    into_syscall_result(create_notification())
+}
+
+/// SYS_NOTIFY_RAISE: Raise signals on a notification
+///
+/// # Arguments
+/// * `arg1` - Capability ID of the notification (needs the send right)
+/// * `arg2` - Signal bits to raise
+///
+/// # Returns
+/// 0 on success, `EBADF` if the capability is not a notification,
+/// `EACCES` if it lacks the send right
+unsafe fn sys_notify_raise(args: &SyscallArgs) -> SyscallResult {
+    into_syscall_result(raise_notification(args.arg1, args.arg2))
+}
+
+/// SYS_WAIT_ANY: Wait until any of several channels or notifications is ready
+///
+/// # Arguments
+/// * `arg1` - Pointer to an array of capability IDs (each needs the receive right)
+/// * `arg2` - Number of capability IDs (1 to `MAX_WAIT_SOURCES`)
+/// * `arg3` - Timeout in heartbeats (0 polls, `WAIT_FOREVER` never gives up)
+/// * `arg4` - Pointer to a `u64` for the signals of a ready notification (may be 0)
+///
+/// # Returns
+/// Index of the first ready entry, `ETIMEDOUT` if none became ready in time
+unsafe fn sys_wait_any(args: &SyscallArgs) -> SyscallResult {
+    into_syscall_result(wait_on_any(args.arg1, args.arg2, args.arg3, args.arg4))
+}
+
+/// SYS_IRQ_BIND: Route a hardware IRQ line to a notification
+///
+/// Only Ring 1 Groves drive hardware, so Ring 3 Vessels get `EPERM`.
+///
+/// # Arguments
+/// * `arg1` - IRQ line (0-15; the timer, keyboard and cascade are taken)
+/// * `arg2` - Capability ID of the notification (needs the send right)
+/// * `arg3` - Signal bits raised each time the line fires
+///
+/// # Returns
+/// 0 on success, `EBUSY` if the line is reserved or already bound
+unsafe fn sys_irq_bind(args: &SyscallArgs) -> SyscallResult {
+    into_syscall_result(bind_irq(args.arg1, args.arg2, args.arg3))
+}
+
+/// SYS_SPAWN: Start a new Vessel from an ELF file in the VFS
+///
+/// The new Vessel is a child of the caller and inherits its Fate. Its ID
+/// means something only to the caller: SYS_WAIT looks among the waiting
+/// Vessel's own children, so any other Vessel gets `ECHILD` for it.
+///
+/// # Arguments
+/// * `arg1` - Pointer to the path
+/// * `arg2` - Path length
+/// * `arg3` - Pointer to the argument block (NUL-terminated strings)
+/// * `arg4` - Argument block length
+/// * `arg5` - Pointer to the environment block (NUL-terminated strings)
+/// * `arg6` - Environment block length
+///
+/// # Returns
+/// The new Vessel's ID, `ENOENT` if there is no such file, `ENOEXEC` if it
+/// is not a loadable ELF, `E2BIG` if the blocks are too long, `ENOMEM` if
+/// its address space or initial stack could not be built
+unsafe fn sys_spawn(args: &SyscallArgs) -> SyscallResult {
+    into_syscall_result(spawn_vessel(args.arg1, args.arg2, args.arg3, args.arg4, args.arg5, args.arg6))
+}
+
+/// SYS_WAIT: Wait for a child Vessel to vanish, and reap it
+///
+/// Only the caller's own children can be waited on; a Vessel ID belonging
+/// to anyone else is treated as no child at all.
+///
+/// # Arguments
+/// * `arg1` - Child Vessel ID, or `ANY_CHILD` for whichever vanishes first
+/// * `arg2` - Pointer to an `ExitStatus` for how the child ended (may be 0)
+///
+/// # Returns
+/// The child's Vessel ID, `ECHILD` if the caller has no such child
+unsafe fn sys_wait(args: &SyscallArgs) -> SyscallResult {
+    into_syscall_result(reap_child(args.arg1, args.arg2))
+}
+
+/// SYS_SHM_CREATE: Create a shared memory object and map it into the caller
+///
+/// The object is zeroed, rounded up to whole pages and mapped read-write at
+/// an ASLR-randomized address. Its frames are mapped up front, since they
+/// belong to the Mana Pool object rather than to the Vessel.
+///
+/// # Arguments
+/// * `arg1` - Length in bytes
+///
+/// # Returns
+/// The base address of the mapping, `ENOMEM` if the object could not be
+/// created or mapped, `EPERM` from a Ring 1 Grove
+unsafe fn sys_shm_create(args: &SyscallArgs) -> SyscallResult {
+    into_syscall_result(create_shared_memory(args.arg1))
+}
+
+/// SYS_IPC_SEND_SHARED: Send a shared memory mapping through a Nexus channel
+///
+/// The pages are not copied; the receiver maps the same frames through
+/// SYS_IPC_RECV.
+///
+/// # Arguments
+/// * `arg1` - Capability ID of the channel (needs the send right)
+/// * `arg2` - Base address of a shared mapping (from SYS_SHM_CREATE or SYS_IPC_RECV)
+/// * `arg3` - Length of the mapping
+/// * `arg4` - `TRANSFER_GRANT`, `TRANSFER_MOVE` or `TRANSFER_SHARE`
+///
+/// A moved mapping is removed from the caller before the message is sent,
+/// so the receiver never shares frames the sender can still release; if the
+/// send then fails, the object is released.
+///
+/// # Returns
+/// 0 on success, `EINVAL` if the range is not exactly one shared mapping or
+/// the mode is unknown, `EACCES` if the mapping may not be passed on,
+/// `EAGAIN` if the channel is full, `EPIPE` if it was closed
+unsafe fn sys_ipc_send_shared(args: &SyscallArgs) -> SyscallResult {
+    into_syscall_result(send_shared_memory(args.arg1, args.arg2, args.arg3, args.arg4))
+}
+
+fn map_anonymous(len: u64, prot: u64) -> Result<u64, SyscallError> {
+    use x86_64::structures::paging::PageTableFlags;
+
+    if len == 0 || prot & !PROT_ALL != 0 || prot & PROT_READ == 0 {
+        return Err(SyscallError::EINVAL);
+    }
+    if prot & PROT_WRITE != 0 && prot & PROT_EXEC != 0 {
+        return Err(SyscallError::EACCES);
+    }
+    if len > MAX_MMAP_SIZE {
+        return Err(SyscallError::ENOMEM);
+    }
+
+    let mut flags = PageTableFlags::PRESENT | PageTableFlags::USER_ACCESSIBLE;
+    if prot & PROT_WRITE != 0 {
+        flags |= PageTableFlags::WRITABLE;
+    }
+    if prot & PROT_EXEC == 0 {
+        flags |= PageTableFlags::NO_EXECUTE;
+    }
+
+    with_current_address_space(|space| {
+        space
+            .map_anonymous(len, flags)
+            .map(|base| base.as_u64())
+            .map_err(|_| SyscallError::ENOMEM)
+    })
+}
+
+fn unmap_anonymous(addr: u64, len: u64) -> Result<u64, SyscallError> {
+    if addr % 0x1000 != 0 || len == 0 || !crate::attunement::ward_of_sacred_boundaries::is_mortal_pointer(addr) {
+        return Err(SyscallError::EINVAL);
+    }
+
+    let shared = with_current_address_space(|space| {
+        let start = x86_64::VirtAddr::new(addr);
+        if space.unmap_anonymous(start, len).is_ok() {
+            return Ok(None);
+        }
+        space
+            .unmap_shared(start, len)
+            .map(Some)
+            .map_err(|_| SyscallError::EINVAL)
+    })?;
+
+    // The Mana Pool object outlives the mapping until its last capability goes
+    if let Some(capability) = shared {
+        let _ = crate::mana_pool::release(&capability);
+    }
+    Ok(0)
+}
+
+fn create_shared_memory(len: u64) -> Result<u64, SyscallError> {
+    if len == 0 {
+        return Err(SyscallError::EINVAL);
+    }
+    if len > MAX_MMAP_SIZE {
+        return Err(SyscallError::ENOMEM);
+    }
+
+    let capability = crate::mana_pool::animate_shared(len as usize).map_err(|_| SyscallError::ENOMEM)?;
+    let mapped = crate::mana_pool::shared_memory(&capability)
+        .map_err(|_| SyscallError::ENOMEM)
+        .and_then(|(kernel_address, size)| {
+            with_current_address_space(|space| {
+                space
+                    .map_shared(capability, x86_64::PhysAddr::new(kernel_address as u64 - KERNEL_BASE), size as u64, true)
+                    .map(|base| base.as_u64())
+                    .map_err(|_| SyscallError::ENOMEM)
+            })
+        });
+
+    if mapped.is_err() {
+        let _ = crate::mana_pool::release(&capability);
+    }
+    mapped
+}
+
+fn send_shared_memory(capability: u64, addr: u64, len: u64, mode: u64) -> Result<u64, SyscallError> {
+    let mode = match mode {
+        TRANSFER_GRANT => TransferMode::Grant,
+        TRANSFER_MOVE => TransferMode::Move,
+        TRANSFER_SHARE => TransferMode::Share,
+        _ => return Err(SyscallError::EINVAL),
+    };
+    if addr % 0x1000 != 0 || len == 0 || !crate::attunement::ward_of_sacred_boundaries::is_mortal_pointer(addr) {
+        return Err(SyscallError::EINVAL);
+    }
+
+    let vessel_id: VesselId = super::current_vessel_id().ok_or(SyscallError::EBADF)?;
+    let channel = crate::nexus::resolve(vessel_id, CapabilityId::new(capability), CapabilityRights::SEND)?;
+
+    let start = x86_64::VirtAddr::new(addr);
+    let object = with_current_address_space(|space| {
+        let object = space.shared_object(start, len).ok_or(SyscallError::EINVAL)?;
+        if !object.can_transfer() {
+            return Err(SyscallError::EACCES);
+        }
+
+        // A moved capability must leave the sender's page tables first
+        if mode == TransferMode::Move {
+            space.unmap_shared(start, len).map_err(|_| SyscallError::EINVAL)?;
+        }
+        Ok(object)
+    })?;
+
+    let sent = crate::nexus::send_shared(channel, object, mode, MessagePriority::Normal);
+    if sent.is_err() && mode == TransferMode::Move {
+        let _ = crate::mana_pool::release(&object);
+    }
+    sent?;
+    Ok(0)
+}
+
+/// Run `f` on the calling Vessel's address space
+///
+/// Ring 1 Groves share the Heartwood's page tables and have no address space
+/// of their own to map into, so they are refused with `EPERM`.
+fn with_current_address_space<R>(
+    f: impl FnOnce(&mut crate::mana_pool::UserAddressSpace) -> Result<R, SyscallError>,
+) -> Result<R, SyscallError> {
+    let vessel_id: VesselId = super::current_vessel_id().ok_or(SyscallError::EPERM)?;
+
+    super::without_interrupts(|| {
+        let mut harbor = super::get_harbor().lock();
+        let vessel = harbor.find_vessel_mut(vessel_id).ok_or(SyscallError::ESRCH)?;
+        if !vessel.is_user_mode() {
+            return Err(SyscallError::EPERM);
+        }
+        f(vessel.address_space_mut())
+    })
+}
+
+fn open_descriptor(path_ptr: u64, path_len: u64, flags: u64) -> Result<u64, SyscallError> {
+    if flags & !O_ALL != 0 || flags & (O_READ | O_WRITE) == 0 {
+        return Err(SyscallError::EINVAL);
+    }
+    if flags & (O_CREATE | O_TRUNCATE) != 0 && flags & O_WRITE == 0 {
+        return Err(SyscallError::EINVAL);
+    }
+    if path_len == 0 {
+        return Err(SyscallError::ENOENT);
+    }
+    if path_len as usize > MAX_PATH_LEN {
+        return Err(SyscallError::ENAMETOOLONG);
+    }
+
+    // Copy the path into the kernel before touching anything else, so the
+    // caller cannot change it between the checks and the use
+    let mut raw_path = alloc::vec![0u8; path_len as usize];
+    unsafe { sanctified_copy_slice_from_mortal(path_ptr, &mut raw_path)? };
+    let path = core::str::from_utf8(&raw_path)
+        .map(Path::new)
+        .map_err(|_| SyscallError::EINVAL)?;
+
+    with_filesystem(|fs| match fs.stat(&path) {
+        Ok(stat) if stat.is_dir => Err(FsError::IsADirectory),
+        Ok(_) if flags & O_TRUNCATE != 0 => fs.write(&path, &[]),
+        Ok(_) => Ok(()),
+        Err(FsError::NotFound) if flags & O_CREATE != 0 => fs.write(&path, &[]),
+        Err(err) => Err(err),
+    })?;
+
+    let fd = current_fd_table(|table| table.insert(OpenFile::file(path, flags)))??;
+    Ok(fd as u64)
+}
+
+fn read_descriptor(fd: u64, buf: u64, count: u64) -> Result<u64, SyscallError> {
+    let file = lookup_descriptor(fd)?;
+    if !file.is_readable() {
+        return Err(SyscallError::EBADF);
+    }
+
+    let len = core::cmp::min(count as usize, MAX_IO_CHUNK);
+    if len == 0 || file.kind == OpenFileKind::Console {
+        // There is no keyboard plumbing for Vessels yet; stdin is always at EOF
+        return Ok(0);
+    }
+    validate_mortal_pointer(buf, len)?;
+
+    // Read into a kernel buffer with no Harbor lock held, then hand it over
+    let mut kernel_buf = alloc::vec![0u8; len];
+    let read = with_filesystem(|fs| file.read(fs, &mut kernel_buf))?;
+    unsafe { sanctified_copy_slice_to_mortal(&kernel_buf[..read], buf)? };
+
+    current_fd_table(|table| table.advance(fd as usize, file.offset + read as u64))??;
+    Ok(read as u64)
+}
+
+fn send_message(capability: u64, buf: u64, len: u64, caps_ptr: u64, cap_count: u64) -> Result<u64, SyscallError> {
+    if len > MAX_IO_CHUNK as u64 || cap_count > MAX_MESSAGE_CAPABILITIES as u64 {
+        return Err(SyscallError::EINVAL);
+    }
+
+    let vessel_id: VesselId = super::current_vessel_id().ok_or(SyscallError::EBADF)?;
+    let channel = crate::nexus::resolve(vessel_id, CapabilityId::new(capability), CapabilityRights::SEND)?;
+
+    let mut payload = alloc::vec![0u8; len as usize];
+    if len > 0 {
+        unsafe { sanctified_copy_slice_from_mortal(buf, &mut payload)? };
+    }
+
+    let mut raw_ids = [0u64; MAX_MESSAGE_CAPABILITIES];
+    let raw_ids = &mut raw_ids[..cap_count as usize];
+    if cap_count > 0 {
+        unsafe { sanctified_copy_slice_from_mortal(caps_ptr, raw_ids)? };
+    }
+    let capabilities: alloc::vec::Vec<CapabilityId> = raw_ids.iter().map(|&raw| CapabilityId::new(raw)).collect();
+
+    let message = Message::new(MessageType::Data { payload }, MessagePriority::Normal);
+    crate::nexus::send_with_capabilities(vessel_id, channel, message, &capabilities)?;
+    Ok(0)
+}
+
+fn register_name(name_ptr: u64, name_len: u64, capability: u64) -> Result<u64, SyscallError> {
+    let vessel_id: VesselId = super::current_vessel_id().ok_or(SyscallError::EPERM)?;
+    let name = copy_service_name(name_ptr, name_len)?;
+
+    crate::nexus::names::register(vessel_id, &name, CapabilityId::new(capability))?;
+    Ok(0)
+}
+
+fn resolve_name(name_ptr: u64, name_len: u64) -> Result<u64, SyscallError> {
+    let vessel_id: VesselId = super::current_vessel_id().ok_or(SyscallError::EPERM)?;
+    let name = copy_service_name(name_ptr, name_len)?;
+
+    let capability = crate::nexus::names::resolve(vessel_id, &name)?;
+    Ok(capability.raw())
+}
+
+fn join_thread(thread_id: u64) -> Result<u64, SyscallError> {
+    let target = super::ThreadId(thread_id);
+    if super::current_thread() == Some(target) {
+        return Err(SyscallError::EINVAL);
+    }
+
+    let caller = super::current_vessel_id();
+    let target_vessel = super::without_interrupts(|| unsafe {
+        super::get_loom().lock().thread(target).map(|thread| thread.vessel_id())
+    });
+    if target_vessel != Some(caller) {
+        return Err(SyscallError::ESRCH);
+    }
+
+    let exit_value = super::join(target).map_err(|_| SyscallError::ESRCH)?;
+    super::release_crew_thread(target);
+    Ok(exit_value)
+}
+
+fn create_thread(entry_point: u64, argument: u64) -> Result<u64, SyscallError> {
+    if !crate::attunement::ward_of_sacred_boundaries::is_mortal_pointer(entry_point) {
+        return Err(SyscallError::EFAULT);
+    }
+
+    let vessel_id = super::current_vessel_id().ok_or(SyscallError::EPERM)?;
+    let priority = super::without_interrupts(|| unsafe {
+        let loom = super::get_loom().lock();
+        loom.current_thread_id().and_then(|id| loom.thread(id)).map(|thread| thread.priority())
+    });
+
+    match super::create_crew_thread(vessel_id, entry_point, argument, priority.unwrap_or(ThreadPriority::Normal)) {
+        Ok(thread_id) => Ok(thread_id.0),
+        Err(LoomError::VesselNotFound) => Err(SyscallError::EPERM),
+        Err(LoomError::OutOfThreads) => Err(SyscallError::EAGAIN),
+        Err(_) => Err(SyscallError::ENOMEM),
+    }
+}
+
+fn create_notification() -> Result<u64, SyscallError> {
+    let vessel_id: VesselId = super::current_vessel_id().ok_or(SyscallError::EPERM)?;
+    let capability = crate::nexus::create_notification(vessel_id)?;
+    Ok(capability.raw())
+}
+
+fn raise_notification(capability: u64, signals: u64) -> Result<u64, SyscallError> {
+    let vessel_id: VesselId = super::current_vessel_id().ok_or(SyscallError::EBADF)?;
+    let notification =
+        crate::nexus::resolve_notification(vessel_id, CapabilityId::new(capability), CapabilityRights::SEND)?;
+
+    crate::nexus::raise(notification, signals)?;
+    Ok(0)
+}
+
+fn wait_on_any(caps_ptr: u64, count: u64, timeout: u64, signals_ptr: u64) -> Result<u64, SyscallError> {
+    if count == 0 || count > MAX_WAIT_SOURCES as u64 {
+        return Err(SyscallError::EINVAL);
+    }
+    let vessel_id: VesselId = super::current_vessel_id().ok_or(SyscallError::EBADF)?;
+
+    let mut raw_ids = [0u64; MAX_WAIT_SOURCES];
+    let raw_ids = &mut raw_ids[..count as usize];
+    unsafe { sanctified_copy_slice_from_mortal(caps_ptr, raw_ids)? };
+    if signals_ptr != 0 {
+        validate_mortal_pointer(signals_ptr, core::mem::size_of::<u64>())?;
+    }
+
+    let sources = raw_ids
+        .iter()
+        .map(|&raw| crate::nexus::resolve_wait_source(vessel_id, CapabilityId::new(raw), CapabilityRights::RECEIVE))
+        .collect::<Result<alloc::vec::Vec<_>, _>>()?;
+
+    let timeout = (timeout != WAIT_FOREVER).then_some(timeout);
+    let ready = crate::nexus::wait_any(&sources, timeout)?;
+
+    if signals_ptr != 0 {
+        unsafe { sanctified_copy_slice_to_mortal(core::slice::from_ref(&ready.signals), signals_ptr)? };
+    }
+    Ok(ready.index as u64)
+}
+
+fn bind_irq(irq: u64, capability: u64, signals: u64) -> Result<u64, SyscallError> {
+    let vessel_id: VesselId = super::current_vessel_id().ok_or(SyscallError::EPERM)?;
+    let is_grove = super::without_interrupts(|| {
+        let harbor = super::get_harbor().lock();
+        harbor.find_vessel(vessel_id).map(|vessel| !vessel.is_user_mode())
+    });
+    if is_grove != Some(true) {
+        return Err(SyscallError::EPERM);
+    }
+
+    let irq = u8::try_from(irq).map_err(|_| SyscallError::EINVAL)?;
+    let notification =
+        crate::nexus::resolve_notification(vessel_id, CapabilityId::new(capability), CapabilityRights::SEND)?;
+
+    crate::nexus::bind_irq(irq, notification, signals)?;
+    Ok(0)
+}
+
+fn spawn_vessel(
+    path_ptr: u64,
+    path_len: u64,
+    args_ptr: u64,
+    args_len: u64,
+    env_ptr: u64,
+    env_len: u64,
+) -> Result<u64, SyscallError> {
+    let vessel_id: VesselId = super::current_vessel_id().ok_or(SyscallError::EPERM)?;
+    if path_len == 0 {
+        return Err(SyscallError::EINVAL);
+    }
+    if path_len as usize > MAX_PATH_LEN {
+        return Err(SyscallError::ENAMETOOLONG);
+    }
+    if args_len.saturating_add(env_len) > MAX_ARGS_LEN as u64 {
+        return Err(SyscallError::E2BIG);
+    }
+
+    let mut raw_path = alloc::vec![0u8; path_len as usize];
+    unsafe { sanctified_copy_slice_from_mortal(path_ptr, &mut raw_path)? };
+    let path = core::str::from_utf8(&raw_path).map_err(|_| SyscallError::EINVAL)?;
+
+    let args = copy_string_block(args_ptr, args_len)?;
+    let env = copy_string_block(env_ptr, env_len)?;
+    let argv = split_string_block(&args)?;
+    let envp = split_string_block(&env)?;
+
+    let fate = super::without_interrupts(|| {
+        let harbor = super::get_harbor().lock();
+        harbor.find_vessel(vessel_id).map(|vessel| alloc::string::String::from(vessel.fate()))
+    })
+    .ok_or(SyscallError::ESRCH)?;
+
+    let child = super::summon_from_path(path, &argv, &envp, Some(vessel_id), fate)?;
+    Ok(child.0)
+}
+
+fn reap_child(child: u64, status_ptr: u64) -> Result<u64, SyscallError> {
+    let vessel_id: VesselId = super::current_vessel_id().ok_or(SyscallError::ECHILD)?;
+    if status_ptr != 0 {
+        validate_mortal_pointer(status_ptr, core::mem::size_of::<ExitStatus>())?;
+    }
+
+    // Only the caller's own children are searched, so naming another
+    // Vessel's child (or any other Vessel) finds nothing
+    let child = (child != ANY_CHILD).then_some(VesselId(child));
+    let (child, exit) = super::wait_for_child(Some(vessel_id), child).map_err(|_| SyscallError::ECHILD)?;
+
+    if status_ptr != 0 {
+        let status = match exit {
+            VesselExit::Exited(code) => ExitStatus { kind: EXITED, code, ..ExitStatus::default() },
+            VesselExit::Faulted(fault) => ExitStatus { kind: FAULTED, address: fault.address, ..ExitStatus::default() },
+        };
+        unsafe { sanctified_copy_slice_to_mortal(core::slice::from_ref(&status), status_ptr)? };
+    }
+    Ok(child.0)
+}
+
+/// Copy a block of NUL-terminated strings in from user space
+fn copy_string_block(ptr: u64, len: u64) -> Result<alloc::vec::Vec<u8>, SyscallError> {
+    let mut block = alloc::vec![0u8; len as usize];
+    if len > 0 {
+        unsafe { sanctified_copy_slice_from_mortal(ptr, &mut block)? };
+    }
+    Ok(block)
+}
+
+/// Split a block of NUL-terminated strings (the last must be terminated too)
+fn split_string_block(block: &[u8]) -> Result<alloc::vec::Vec<&[u8]>, SyscallError> {
+    match block.split_last() {
+        None => Ok(alloc::vec::Vec::new()),
+        Some((0, strings)) => Ok(strings.split(|&b| b == 0).collect()),
+        Some(_) => Err(SyscallError::EINVAL),
+    }
+}
+
+/// Copy a service name in from user space
+fn copy_service_name(name_ptr: u64, name_len: u64) -> Result<alloc::string::String, SyscallError> {
+    if name_len == 0 {
+        return Err(SyscallError::EINVAL);
+    }
+    if name_len as usize > crate::nexus::names::MAX_NAME_LEN {
+        return Err(SyscallError::ENAMETOOLONG);
+    }
+
+    let mut raw_name = alloc::vec![0u8; name_len as usize];
+    unsafe { sanctified_copy_slice_from_mortal(name_ptr, &mut raw_name)? };
+    alloc::string::String::from_utf8(raw_name).map_err(|_| SyscallError::EINVAL)
+}
+
+fn receive_message(capability: u64, buf: u64, len: u64, timeout: u64, info_ptr: u64) -> Result<u64, SyscallError> {
+    let vessel_id: VesselId = super::current_vessel_id().ok_or(SyscallError::EBADF)?;
+    let channel = crate::nexus::resolve(vessel_id, CapabilityId::new(capability), CapabilityRights::RECEIVE)?;
+
+    // Check the user pointers before a message is taken off the channel
+    let len = core::cmp::min(len as usize, MAX_IO_CHUNK);
+    if len > 0 {
+        validate_mortal_pointer(buf, len)?;
+    }
+    if info_ptr != 0 {
+        validate_mortal_pointer(info_ptr, core::mem::size_of::<RecvInfo>())?;
+    }
+
+    let timeout = (timeout != WAIT_FOREVER).then_some(timeout);
+    let message = crate::nexus::receive(channel, timeout)?;
+
+    let shared = match &message.message_type {
+        MessageType::SharedMemory { capability, .. } => Some(*capability),
+        _ => None,
+    };
+    let carries_capabilities = message.transfer.as_ref().is_some_and(|transfer| !transfer.capabilities.is_empty());
+    if info_ptr == 0 && (shared.is_some() || carries_capabilities) {
+        // Nowhere to report a mapping or passed capabilities; refuse the
+        // message rather than leak or silently lose what it carried
+        if let Some(capability) = shared {
+            let _ = crate::mana_pool::release(&capability);
+        }
+        return Err(SyscallError::EINVAL);
+    }
+
+    let (copied, mut info) = match &message.message_type {
+        MessageType::SharedMemory { capability, .. } => (0, map_shared_memory(*capability)?),
+        MessageType::Data { payload } | MessageType::Response { data: payload } => {
+            let copied = core::cmp::min(payload.len(), len);
+            if copied > 0 {
+                unsafe { sanctified_copy_slice_to_mortal(&payload[..copied], buf)? };
+            }
+            (copied, RecvInfo { kind: MSG_BYTES, ..RecvInfo::default() })
+        }
+        _ => (0, RecvInfo { kind: MSG_BYTES, ..RecvInfo::default() }),
+    };
+
+    if info_ptr != 0 {
+        // Passed capabilities are only installed when they can be reported
+        let accepted = crate::nexus::accept_capabilities(vessel_id, &message);
+        for (slot, id) in info.capabilities.iter_mut().zip(&accepted) {
+            *slot = id.raw();
+        }
+        info.capability_count = accepted.len() as u64;
+
+        if let Err(err) = unsafe { sanctified_copy_slice_to_mortal(core::slice::from_ref(&info), info_ptr) } {
+            // The caller never learns what it was given, so take it back
+            undo_delivery(vessel_id, shared, &info, &accepted);
+            return Err(err.into());
+        }
+    }
+    Ok(copied as u64)
+}
+
+/// Take back a shared memory mapping and capabilities that could not be reported
+fn undo_delivery(vessel_id: VesselId, shared: Option<Capability>, info: &RecvInfo, accepted: &[CapabilityId]) {
+    for &id in accepted {
+        let _ = crate::nexus::revoke(vessel_id, id);
+    }
+
+    let Some(capability) = shared else {
+        return;
+    };
+    let unmapped = with_current_address_space(|space| {
+        space
+            .unmap_shared(x86_64::VirtAddr::new(info.address), info.size)
+            .map_err(|_| SyscallError::EINVAL)
+    });
+    match unmapped {
+        Ok(capability) => {
+            let _ = crate::mana_pool::release(&capability);
+        }
+        // Groves were handed the kernel address and have nothing mapped
+        Err(SyscallError::EPERM) => {
+            let _ = crate::mana_pool::release(&capability);
+        }
+        Err(_) => {}
+    }
+}
+
+/// Deliver a received shared memory object to the calling thread
+///
+/// A Ring 3 Vessel gets the object's frames mapped into its address space.
+/// A Ring 1 Grove already runs with the Heartwood's mappings and is handed
+/// the object's kernel address. On failure the capability is released.
+fn map_shared_memory(capability: Capability) -> Result<RecvInfo, SyscallError> {
+    let (kernel_address, size) = match crate::mana_pool::shared_memory(&capability) {
+        Ok(object) => object,
+        Err(_) => {
+            let _ = crate::mana_pool::release(&capability);
+            return Err(SyscallError::EFAULT);
+        }
+    };
+    let (kernel_address, size) = (kernel_address as u64, size as u64);
+    let writable = capability.can_write();
+
+    let mapped = with_current_address_space(|space| {
+        space
+            .map_shared(capability, x86_64::PhysAddr::new(kernel_address - KERNEL_BASE), size, writable)
+            .map(|base| base.as_u64())
+            .map_err(|_| SyscallError::ENOMEM)
+    });
+
+    let address = match mapped {
+        Ok(base) => base,
+        Err(SyscallError::EPERM) => kernel_address,
+        Err(err) => {
+            let _ = crate::mana_pool::release(&capability);
+            return Err(err);
+        }
+    };
+
+    Ok(RecvInfo {
+        kind: MSG_SHARED_MEMORY,
+        address,
+        size,
+        prot: if writable { PROT_READ | PROT_WRITE } else { PROT_READ },
+        ..RecvInfo::default()
+    })
+}
+
+fn write_to_file(fd: u64, file: &OpenFile, buf: u64, count: u64) -> Result<u64, SyscallError> {
+    if count == 0 {
+        return Ok(0);
+    }
+
+    let mut data = alloc::vec![0u8; count as usize];
+    unsafe { sanctified_copy_slice_from_mortal(buf, &mut data)? };
+
+    let new_offset = with_filesystem(|fs| file.write(fs, &data))?;
+    current_fd_table(|table| table.advance(fd as usize, new_offset))??;
+    Ok(count)
+}
+
+/// Snapshot an open descriptor of the calling thread
+///
+/// Threads outside any Vessel (kernel threads, Ring 1 Groves) have no table;
+/// for them descriptors 0-2 are the console and everything else is `EBADF`.
+fn lookup_descriptor(fd: u64) -> Result<OpenFile, SyscallError> {
+    if super::current_vessel_id().is_none() {
+        return match fd {
+            fds::STDIN => Ok(OpenFile::console(O_READ)),
+            fds::STDOUT | fds::STDERR => Ok(OpenFile::console(O_WRITE)),
+            _ => Err(SyscallError::EBADF),
+        };
+    }
+
+    current_fd_table(|table| table.get(fd as usize).cloned())?
+        .map_err(SyscallError::from)
+}
+
+/// Run `f` on the calling Vessel's descriptor table
+///
+/// The Harbor lock is held only for the duration of `f`, which must not do
+/// any filesystem I/O.
+fn current_fd_table<R>(f: impl FnOnce(&mut FileDescriptorTable) -> R) -> Result<R, SyscallError> {
+    let vessel_id: VesselId = super::current_vessel_id().ok_or(SyscallError::EBADF)?;
+
+    super::without_interrupts(|| {
+        let mut harbor = super::get_harbor().lock();
+        harbor
+            .find_vessel_mut(vessel_id)
+            .map(|vessel| f(vessel.fd_table_mut()))
+            .ok_or(SyscallError::ESRCH)
+    })
+}
+
+/// Run `f` against the globally mounted filesystem
+///
+/// Uses the VFS's own (interrupt-enabled) mutex, never the Harbor or Loom.
+fn with_filesystem<R>(
+    f: impl FnOnce(&dyn FileSystem) -> Result<R, FsError>,
+) -> Result<R, SyscallError> {
+    let global_fs = crate::vfs::global::get().ok_or(SyscallError::ENOENT)?;
+    let fs_lock = global_fs.lock();
+
+    match &*fs_lock {
+        Some(fs) => f(fs.as_ref()).map_err(SyscallError::from),
+        None => Err(SyscallError::ENOENT),
+    }
+}
+
 /// Validate a user-space pointer
 ///
 /// Ensures the pointer is in user space and properly aligned.
@@ -559,7 +1686,6 @@ fn sys_gettid() -> SyscallResult {
 ///
 /// # Returns
 /// Ok(()) if valid, Err(SyscallError) if invalid
-#[allow(dead_code)]
 fn validate_user_pointer(ptr: u64, len: usize) -> Result<(), SyscallError> {
     // Check that pointer is in user space (< 0x8000_0000_0000)
     if ptr >= 0x8000_0000_0000 {
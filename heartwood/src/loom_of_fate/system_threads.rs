@@ -9,8 +9,9 @@
 //! The idle thread waits patiently, consuming nothing.
 //! The keyboard thread listens attentively to user intentions.
 //! The shell thread translates human wishes into system actions.
+//! The Harbormaster lays to rest the Vessels no one waits for.
 
-use super::yield_now;
+use super::{yield_now, BlockReason, VesselExit};
 
 /// The Idle Thread - The First Awakening
 ///
@@ -55,15 +56,41 @@ pub fn idle_thread() -> ! {
         core::arch::asm!("sti", options(nomem, nostack, preserves_flags));
     }
 
+    // The other processors may start taking threads too
+    crate::attunement::smp::release_aps();
+
     // The eternal, silent loop of the idle thread
     // We speak not. We hold no locks. We are the void between actions.
-    loop {
-        // Yield to let other threads run
-        yield_now();
+    rest()
+}
+
+/// The Idle Thread of an Application Processor
+///
+/// Each processor but the first has its own, entered from `ap_entry`
+/// once the system has awakened.
+///
+/// Priority: Idle (lowest)
+pub fn secondary_idle_thread() -> ! {
+    rest()
+}
 
-        // Halt CPU until next interrupt (most power-efficient)
-        unsafe {
-            core::arch::asm!("hlt", options(nomem, nostack, preserves_flags));
+/// Run whatever is ready, and otherwise halt until there is something
+///
+/// On a tickless system nothing interrupts a halted processor but its
+/// own deadline and the reschedule IPI sent when work is queued for it,
+/// so the check for work, arming the timer for the next sleeper and the
+/// halt all happen with interrupts disabled: an interrupt that arrives
+/// after the check still wakes the `hlt`.
+fn rest() -> ! {
+    loop {
+        x86_64::instructions::interrupts::disable();
+        if super::has_ready_threads() {
+            x86_64::instructions::interrupts::enable();
+            yield_now();
+        } else {
+            crate::attunement::timer::arm_next_wake();
+            // sti takes effect after the next instruction, so nothing slips in
+            x86_64::instructions::interrupts::enable_and_hlt();
         }
     }
 }
@@ -133,6 +160,35 @@ pub fn shell_thread() -> ! {
     }
 }
 
+/// The Harbormaster - Keeper of the Harbor
+///
+/// Reaps the Vessels that vanish with no parent to wait for them: those
+/// summoned from the shell, the Groves, and orphans whose parent vanished
+/// first. Each one's address space, kernel stack and capabilities are
+/// freed, and how it ended is written to the serial log.
+///
+/// Priority: Low (reaping can always wait a little)
+pub fn harbormaster_thread() -> ! {
+    loop {
+        let seen = super::scheduler::vanishings();
+        match super::wait_for_child(None, None) {
+            Ok((vessel_id, VesselExit::Exited(code))) => {
+                crate::serial_println!("[HARBORMASTER] Vessel {} exited with code {}", vessel_id.0, code);
+            }
+            Ok((vessel_id, VesselExit::Faulted(fault))) => {
+                crate::serial_println!(
+                    "[HARBORMASTER] Vessel {} faulted: {} at {:#x}",
+                    vessel_id.0,
+                    fault.reason.description(),
+                    fault.address
+                );
+            }
+            // No Vessel without a parent is moored; wait until one vanishes
+            Err(_) => super::park_current(BlockReason::Wait { seen }, None),
+        }
+    }
+}
+
 /// Display the welcome message
 fn display_welcome() {
     crate::println!("====================================================================");
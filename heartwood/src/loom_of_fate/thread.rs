@@ -4,7 +4,7 @@ use super::context::ThreadContext;
 use super::vessel::VesselId;
 
 /// A unique identifier for a thread
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct ThreadId(pub u64);
 
 /// The type of thread, determining its privilege level
@@ -30,13 +30,48 @@ pub enum ThreadState {
     /// The thread is idle, waiting for work
     Resting,
 
-    /// The thread is blocked or has encountered an error
+    /// The thread has encountered an error
     Tangled,
 
+    /// The thread is off the ready queue, waiting for something
+    Blocked(BlockReason),
+
     /// The thread is in the process of exiting
     Fading,
 }
 
+/// What a Blocked thread is waiting for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockReason {
+    /// Sleeping until the timer reaches this tick
+    Sleep { until: u64 },
+
+    /// Waiting on the Nexus (a receive, a call's reply, or `wait_any`)
+    Channel,
+
+    /// Waiting for another thread to fade
+    Join(ThreadId),
+
+    /// Waiting for a child Vessel to vanish; `seen` is the vanishing count
+    /// when the wait began, so one that happened since is not missed
+    Wait { seen: u64 },
+
+    /// Waiting for a contended lock to be released
+    Mutex,
+}
+
+impl BlockReason {
+    pub fn description(&self) -> &'static str {
+        match self {
+            BlockReason::Sleep { .. } => "sleeping",
+            BlockReason::Channel => "awaiting the Nexus",
+            BlockReason::Join(_) => "joining",
+            BlockReason::Wait { .. } => "awaiting a child Vessel",
+            BlockReason::Mutex => "awaiting a lock",
+        }
+    }
+}
+
 /// Priority levels for threads (used in harmony calculation)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ThreadPriority {
@@ -47,6 +82,24 @@ pub enum ThreadPriority {
     Idle = 4,      // Lowest priority
 }
 
+/// The stacks of a Vessel thread beyond its first
+///
+/// A Vessel's main thread runs on the Vessel's own kernel stack and the user
+/// stack built with its address space; every thread created after it gets
+/// its own pair, and its own TLS block, given back when the thread is
+/// released.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CrewStacks {
+    /// Base of the user stack region in the Vessel's address space
+    pub user_stack: u64,
+
+    /// Top of the kernel stack used for syscalls and interrupts
+    pub kernel_stack: u64,
+
+    /// Base of its TLS block, if the program has thread-local storage
+    pub tls_block: Option<u64>,
+}
+
 /// A thread of fate in the Loom
 pub struct Thread {
     pub(crate) id: ThreadId,
@@ -86,6 +139,18 @@ pub struct Thread {
     pub(crate) time_slices_used: u64,
     pub(crate) yields: u64,
     pub(crate) last_run_time: u64,
+
+    // Parking (blocking waits); a parked thread is Blocked
+    /// Tick at which a parked thread is woken even if nobody wakes it
+    pub(crate) wake_deadline: Option<u64>,
+    /// A wake-up that arrived before the thread finished parking
+    pub(crate) wake_pending: bool,
+
+    /// The value passed to SYS_EXIT, handed to joiners
+    pub(crate) exit_value: u64,
+
+    /// Stacks of its own, for Vessel threads other than the main one
+    pub(crate) crew_stacks: Option<CrewStacks>,
 }
 
 impl Thread {
@@ -104,8 +169,12 @@ impl Thread {
         stack_bottom: u64,
         stack_top: u64,
     ) -> Self {
-        // Create initial context for this thread (kernel mode, Ring 0)
-        let context = ThreadContext::new(entry_point as u64, stack_top);
+        // Create initial context for this thread (kernel mode, Ring 0).
+        // It starts in the trampoline, which finishes the switch and then
+        // jumps to the entry point.
+        let start: unsafe extern "C" fn() -> ! = super::context::thread_start;
+        let mut context = ThreadContext::new(start as usize as u64, stack_top);
+        context.r12 = entry_point as u64;
 
         // Generate unique Weaver's Sigil (stack canary) for this thread
         let sigil = Self::generate_sigil();
@@ -131,6 +200,10 @@ impl Thread {
             time_slices_used: 0,
             yields: 0,
             last_run_time: 0,
+            wake_deadline: None,
+            wake_pending: false,
+            exit_value: 0,
+            crew_stacks: None,
         }
     }
 
@@ -199,6 +272,10 @@ impl Thread {
             time_slices_used: 0,
             yields: 0,
             last_run_time: 0,
+            wake_deadline: None,
+            wake_pending: false,
+            exit_value: 0,
+            crew_stacks: None,
         }
     }
 
@@ -274,6 +351,24 @@ impl Thread {
     pub fn set_vessel_id(&mut self, vessel_id: Option<VesselId>) {
         self.vessel_id = vessel_id;
     }
+
+    /// Check if this thread is parked, waiting to be woken
+    pub fn is_parked(&self) -> bool {
+        matches!(self.state, ThreadState::Blocked(_))
+    }
+
+    /// What the thread is waiting for, if it is Blocked
+    pub fn block_reason(&self) -> Option<BlockReason> {
+        match self.state {
+            ThreadState::Blocked(reason) => Some(reason),
+            _ => None,
+        }
+    }
+
+    /// The value the thread passed to SYS_EXIT (0 if it never called it)
+    pub fn exit_value(&self) -> u64 {
+        self.exit_value
+    }
 }
 
 /// Tracks a thread's resource consumption
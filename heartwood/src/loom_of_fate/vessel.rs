@@ -17,11 +17,18 @@
 //!
 //! ## Lifecycle
 //! Nascent → Weaving → Resting/Fading → Vanished
+//!
+//! A Vessel vanishes when its last thread fades, keeping only its exit
+//! status. It stays moored until it is reaped, by its parent through
+//! SYS_WAIT or by the Harbormaster, and is then dismantled: its address
+//! space, kernel stack and capabilities are freed.
 
+use super::grove_linker::Grove;
 use super::thread::ThreadId;
 use alloc::string::String;
-use alloc::alloc::{alloc, Layout};
-use crate::mana_pool::{UserAddressSpace, create_address_space_from_elf};
+use alloc::alloc::{alloc, dealloc, Layout};
+use crate::mana_pool::{CapabilityTable, UserAddressSpace, UserFault, create_address_space_from_elf};
+use crate::vfs::descriptor::FileDescriptorTable;
 
 /// Size of kernel stack for syscall handling (16 KB)
 ///
@@ -40,15 +47,12 @@ pub const KERNEL_STACK_SIZE: usize = 16 * 1024;
 ///
 /// # Safety
 ///
-/// The allocated stack is never freed (leaked). This is acceptable for Phase 2;
-/// proper cleanup will be implemented in Phase 3+ when Vessels are destroyed.
-fn allocate_kernel_stack() -> Result<u64, &'static str> {
-    // Align to 16 bytes (required by x86-64 calling convention)
-    let size = (KERNEL_STACK_SIZE + 15) & !15;
-
-    // Create layout for allocation
-    let layout = Layout::from_size_align(size, 16)
-        .map_err(|_| "Invalid layout for kernel stack")?;
+/// The stack lives until its owner frees it with `free_kernel_stack`: the
+/// Vessel when it is dismantled, or the Loom when it releases a thread that
+/// was given one of its own.
+pub(crate) fn allocate_kernel_stack() -> Result<u64, &'static str> {
+    let layout = kernel_stack_layout().ok_or("Invalid layout for kernel stack")?;
+    let size = layout.size();
 
     // Allocate the stack
     let ptr = unsafe { alloc(layout) };
@@ -66,6 +70,23 @@ fn allocate_kernel_stack() -> Result<u64, &'static str> {
     Ok(stack_top)
 }
 
+/// Free a kernel stack from `allocate_kernel_stack`, given its top
+///
+/// # Safety
+///
+/// No thread may be running on the stack, or ever return to it.
+pub(crate) unsafe fn free_kernel_stack(stack_top: u64) {
+    if let Some(layout) = kernel_stack_layout() {
+        dealloc((stack_top - layout.size() as u64) as *mut u8, layout);
+    }
+}
+
+/// The layout of a kernel stack (16-byte aligned, as the x86-64 calling
+/// convention requires)
+fn kernel_stack_layout() -> Option<Layout> {
+    Layout::from_size_align((KERNEL_STACK_SIZE + 15) & !15, 16).ok()
+}
+
 /// A unique identifier for a Vessel (process)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct VesselId(pub u64);
@@ -89,6 +110,32 @@ pub enum VesselState {
     Vanished,
 }
 
+/// Why a Vessel was terminated by the kernel
+///
+/// Recorded when a fault in mortal code cannot be resolved, so the cause of
+/// death can be inspected after the Vessel's threads have faded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VesselFault {
+    /// The faulting address (CR2)
+    pub address: u64,
+
+    /// The instruction that caused the fault
+    pub instruction: u64,
+
+    /// Why the access could not be allowed
+    pub reason: UserFault,
+}
+
+/// How a Vessel ended, reported to whoever reaps it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VesselExit {
+    /// Its threads all exited; the exit code of its main thread
+    Exited(u64),
+
+    /// The kernel terminated it after an unrecoverable fault
+    Faulted(VesselFault),
+}
+
 /// A Vessel - The process abstraction of AethelOS
 ///
 /// Each Vessel contains one or more threads and has:
@@ -123,6 +170,18 @@ pub struct Vessel {
 
     /// Current state of the Vessel
     pub state: VesselState,
+
+    /// Open file descriptors (0/1/2 start bound to the console)
+    pub fd_table: FileDescriptorTable,
+
+    /// Sealed capabilities held by this Vessel (Nexus channels, ...)
+    pub capabilities: CapabilityTable,
+
+    /// The fault that terminated this Vessel, if it was killed by the kernel
+    pub fault: Option<VesselFault>,
+
+    /// The exit code of its main thread, once the Vessel has vanished
+    pub exit_code: Option<u64>,
 }
 
 impl Vessel {
@@ -160,6 +219,10 @@ impl Vessel {
             main_thread,
             fate,
             state: VesselState::Nascent,
+            fd_table: FileDescriptorTable::with_stdio(),
+            capabilities: CapabilityTable::new(),
+            fault: None,
+            exit_code: None,
         }
     }
 
@@ -226,11 +289,83 @@ impl Vessel {
         self.page_table_phys
     }
 
+    /// Get a reference to the file descriptor table
+    pub fn fd_table(&self) -> &FileDescriptorTable {
+        &self.fd_table
+    }
+
+    /// Get a mutable reference to the file descriptor table
+    pub fn fd_table_mut(&mut self) -> &mut FileDescriptorTable {
+        &mut self.fd_table
+    }
+
+    /// Get a reference to the capability table
+    pub fn capabilities(&self) -> &CapabilityTable {
+        &self.capabilities
+    }
+
+    /// Get a mutable reference to the capability table
+    pub fn capabilities_mut(&mut self) -> &mut CapabilityTable {
+        &mut self.capabilities
+    }
+
+    /// Get the fault that terminated this Vessel, if any
+    pub fn fault(&self) -> Option<VesselFault> {
+        self.fault
+    }
+
+    /// Record an unrecoverable fault and mark the Vessel as Fading
+    pub fn condemn(&mut self, fault: VesselFault) {
+        self.fault = Some(fault);
+        self.state = VesselState::Fading;
+    }
+
+    /// Mark the Vessel as Vanished, its last thread having faded
+    pub fn vanish(&mut self, exit_code: u64) {
+        self.exit_code = Some(exit_code);
+        self.state = VesselState::Vanished;
+    }
+
+    /// How the Vessel ended, once it has vanished
+    pub fn exit_status(&self) -> Option<VesselExit> {
+        if self.state != VesselState::Vanished {
+            return None;
+        }
+        Some(match self.fault {
+            Some(fault) => VesselExit::Faulted(fault),
+            None => VesselExit::Exited(self.exit_code.unwrap_or(0)),
+        })
+    }
+
+    /// Free everything the Vessel holds: its Nexus names, capabilities,
+    /// address space and kernel stack
+    ///
+    /// # Safety
+    ///
+    /// None of the Vessel's threads may run again, nor still be on a CPU.
+    pub unsafe fn dismantle(mut self) {
+        crate::nexus::names::forget_vessel(self.beacon);
+        self.capabilities.clear();
+
+        // Shared objects outlive the mapping until their last capability goes
+        for capability in self.address_space.teardown() {
+            let _ = crate::mana_pool::release(&capability);
+        }
+
+        // Only Vessels built from ELF files own their kernel stack
+        if self.is_user_mode() {
+            free_kernel_stack(self.kernel_stack);
+        }
+
+        crate::serial_println!("[VESSEL] Vessel {} dismantled", self.beacon.0);
+    }
+
     /// Create a Vessel from an ELF binary
     ///
     /// This is a factory method that:
     /// 1. Parses the ELF file
     /// 2. Creates an isolated address space with all segments mapped
+    ///    and the program's Groves linked in
     /// 3. Allocates a user stack
     /// 4. Allocates a kernel stack for syscall handling
     /// 5. Creates the Vessel structure
@@ -240,6 +375,7 @@ impl Vessel {
     /// * `beacon` - Unique VesselId for this Vessel
     /// * `parent` - Parent VesselId (None for init process)
     /// * `elf_data` - Raw ELF binary data
+    /// * `groves` - The program's Groves (see `grove_linker::gather_groves`)
     /// * `fate` - RBAC role from Concordance
     /// * `main_thread` - ThreadId of the main thread (must be created separately)
     ///
@@ -255,6 +391,7 @@ impl Vessel {
     ///     VesselId(1),
     ///     None,
     ///     elf_binary_data,
+    ///     &[],
     ///     "user".to_string(),
     ///     ThreadId(42),
     /// )?;
@@ -263,13 +400,14 @@ impl Vessel {
         beacon: VesselId,
         parent: Option<VesselId>,
         elf_data: &[u8],
+        groves: &[Grove],
         fate: String,
         main_thread: ThreadId,
     ) -> Result<Self, &'static str> {
         crate::serial_println!("[VESSEL] Creating Vessel {} from ELF", beacon.0);
 
         // Parse ELF and create address space
-        let (address_space, entry_point) = create_address_space_from_elf(elf_data)?;
+        let (address_space, entry_point) = create_address_space_from_elf(elf_data, groves)?;
 
         // Get the PML4 physical address (CR3 value)
         let page_table_phys = address_space.pml4_phys.as_u64();
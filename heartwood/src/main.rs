@@ -150,40 +150,65 @@ pub extern "C" fn _start() -> ! {
     }
 }
 
-/// Detect ATA drives and mount filesystem (FAT32 or ext4)
-fn detect_and_mount_storage() {
-    use heartwood::drivers::AtaDrive;
-    use heartwood::vfs::fat32::Fat32;
-    use heartwood::vfs::ext4::Ext4;
-    use heartwood::vfs::global as vfs_global;
+/// Detect a disk (AHCI first, then legacy ATA)
+fn detect_disk(verbose: bool) -> Option<alloc::boxed::Box<dyn heartwood::vfs::block_device::BlockDevice>> {
+    use heartwood::drivers::{AhciDisk, AtaDrive};
     use alloc::boxed::Box;
 
-    // Initialize global VFS
-    vfs_global::init();
+    if verbose {
+        println!("  Checking AHCI controller...");
+    }
+    if let Some(disk) = AhciDisk::detect_first() {
+        if verbose {
+            let sectors = disk.sector_count();
+            let size_mb = (sectors * 512) / (1024 * 1024);
+            println!("  ✓ Detected SATA disk \"{}\": {} sectors (~{} MB){}",
+                disk.model(), sectors, size_mb, if disk.uses_ncq() { ", NCQ" } else { "" });
+        }
+        return Some(Box::new(disk));
+    }
 
-    // Try to detect primary master first
-    println!("  Checking primary master...");
+    // Try primary master first
+    if verbose {
+        println!("  Checking primary master...");
+    }
     let drive = match AtaDrive::detect_primary_master() {
         Some(d) => Some(d),
         None => {
             // Master not found or is ATAPI, try slave
-            println!("  Checking primary slave...");
+            if verbose {
+                println!("  Checking primary slave...");
+            }
             AtaDrive::detect_primary_slave()
         }
-    };
+    }?;
 
-    match drive {
-        Some(drive) => {
-            let sectors = drive.sector_count();
-            let size_mb = (sectors * 512) / (1024 * 1024);
-            println!("  ✓ Detected ATA drive: {} sectors (~{} MB)", sectors, size_mb);
+    if verbose {
+        let sectors = drive.sector_count();
+        let size_mb = (sectors * 512) / (1024 * 1024);
+        println!("  ✓ Detected ATA drive: {} sectors (~{} MB)", sectors, size_mb);
+    }
+    Some(Box::new(drive))
+}
 
+/// Detect a disk and mount its filesystem (FAT32 or ext4)
+fn detect_and_mount_storage() {
+    use heartwood::vfs::fat32::Fat32;
+    use heartwood::vfs::ext4::Ext4;
+    use heartwood::vfs::global as vfs_global;
+    use alloc::boxed::Box;
+
+    // Initialize global VFS
+    vfs_global::init();
+
+    match detect_disk(true) {
+        Some(drive) => {
             // Try to auto-detect filesystem type
             println!("  ◈ Detecting filesystem type...");
 
             // Try ext4 first (check magic number)
             println!("  ◈ Attempting to mount ext4 filesystem...");
-            match Ext4::new(Box::new(drive)) {
+            match Ext4::new(drive) {
                 Ok(fs) => {
                     println!("  ✓ ext4 filesystem mounted successfully!");
 
@@ -198,14 +223,8 @@ fn detect_and_mount_storage() {
                     println!("  ⚠ Not an ext4 filesystem, trying FAT32...");
 
                     // Need to re-detect drive since we consumed it
-                    // Try master first, then slave
-                    let drive2 = match AtaDrive::detect_primary_master() {
-                        Some(d) => Some(d),
-                        None => AtaDrive::detect_primary_slave()
-                    };
-
-                    if let Some(drive2) = drive2 {
-                        match Fat32::new(Box::new(drive2)) {
+                    if let Some(drive2) = detect_disk(false) {
+                        match Fat32::new(drive2) {
                             Ok(fs) => {
                                 println!("  ✓ FAT32 filesystem mounted successfully!");
 
@@ -227,8 +246,8 @@ fn detect_and_mount_storage() {
             }
         }
         None => {
-            println!("  ⚠ No ATA drive detected on primary channel");
-            println!("  (Use QEMU with -hda <disk.img> to attach a disk)");
+            println!("  ⚠ No AHCI or ATA drive detected");
+            println!("  (Use QEMU with -hda <disk.img>, or -device ahci with an ide-hd on it)");
         }
     }
 }
@@ -467,6 +486,7 @@ fn heartwood_init() {
             core::arch::asm!("out dx, al", in("dx") 0x3f8u16, in("al") byte, options(nomem, nostack, preserves_flags));
         }
     }
+    heartwood::drivers::init();
     detect_and_mount_storage();
     println!();
 
@@ -12,7 +12,7 @@
 //! - Stack: 28 bits of entropy (256MB range)
 //! - Heap: 28 bits of entropy (256MB range)
 //! - Code: 24 bits of entropy (16MB range, aligned)
-//! - Groves (libraries): 20 bits of entropy (1MB range, page-aligned)
+//! - Groves (libraries): 20 bits of entropy (4GB window, page-aligned)
 
 use super::entropy::{HardwareRng, ChaCha8Rng};
 
@@ -196,6 +196,40 @@ pub fn randomize_offset(max_bytes: usize, alignment: usize) -> usize {
     (random_value % max_units) * alignment
 }
 
+/// Start of the window used for anonymous mappings (`SYS_MMAP`)
+pub const MMAP_WINDOW_START: u64 = 0x0000_1000_0000_0000;
+
+/// Size of the anonymous mapping window (16TB, 32 bits of page entropy)
+pub const MMAP_WINDOW_SIZE: u64 = 0x0000_1000_0000_0000;
+
+/// Pick a random page-aligned base for an anonymous mapping of `size` bytes
+///
+/// The whole mapping is guaranteed to fit inside the mmap window; the caller
+/// is responsible for retrying if it overlaps an existing region.
+pub fn randomize_mmap_base(size: u64) -> u64 {
+    const PAGE_SIZE: usize = 0x1000;
+
+    let slack = MMAP_WINDOW_SIZE.saturating_sub(size) as usize;
+    MMAP_WINDOW_START + randomize_offset(slack, PAGE_SIZE) as u64
+}
+
+/// Start of the window Groves (shared libraries) are loaded in
+pub const GROVE_WINDOW_START: u64 = 0x0000_4000_0000_0000;
+
+/// Size of the Grove window (4GB, 20 bits of page entropy)
+pub const GROVE_WINDOW_SIZE: u64 = 0x0000_0001_0000_0000;
+
+/// Pick a random page-aligned base for a Grove image of `size` bytes
+///
+/// Like `randomize_mmap_base`, but in the Grove window; the caller retries
+/// if the image would overlap an existing region.
+pub fn randomize_grove_base(size: u64) -> u64 {
+    const PAGE_SIZE: usize = 0x1000;
+
+    let slack = GROVE_WINDOW_SIZE.saturating_sub(size) as usize;
+    GROVE_WINDOW_START + randomize_offset(slack, PAGE_SIZE) as u64
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -242,6 +276,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_mmap_base_in_window() {
+        for size in [0x1000u64, 0x10_0000, 0x4000_0000] {
+            let base = randomize_mmap_base(size);
+
+            assert!(base >= MMAP_WINDOW_START);
+            assert!(base + size <= MMAP_WINDOW_START + MMAP_WINDOW_SIZE);
+            assert_eq!(base % 0x1000, 0);
+        }
+    }
+
+    #[test]
+    fn test_grove_base_in_window() {
+        for size in [0x1000u64, 0x20_0000, 0x1000_0000] {
+            let base = randomize_grove_base(size);
+
+            assert!(base >= GROVE_WINDOW_START);
+            assert!(base + size <= GROVE_WINDOW_START + GROVE_WINDOW_SIZE);
+            assert_eq!(base % 0x1000, 0);
+        }
+    }
+
     #[test]
     fn test_deterministic_layout() {
         let layout = AslrManager::deterministic_layout();
@@ -234,11 +234,17 @@ impl SealedCapability {
 
 bitflags::bitflags! {
     /// Rights that can be granted to a capability
+    ///
+    /// For Nexus channels, TRANSFER is the grant right: the holder may pass
+    /// the capability (or one derived from it) on to another Vessel. For
+    /// Nexus notifications, SEND raises signals and RECEIVE waits on them.
     pub struct CapabilityRights: u32 {
         const READ     = 0b0001;
         const WRITE    = 0b0010;
         const EXECUTE  = 0b0100;
         const TRANSFER = 0b1000;
+        const SEND     = 0b1_0000;
+        const RECEIVE  = 0b10_0000;
     }
 }
 
@@ -284,6 +290,11 @@ impl CapabilityRights {
     pub fn code_with_transfer() -> Self {
         Self::READ | Self::EXECUTE | Self::TRANSFER
     }
+
+    /// Both ends of a fresh Nexus channel: send, receive and grant
+    pub fn channel_endpoint() -> Self {
+        Self::SEND | Self::RECEIVE | Self::TRANSFER
+    }
 }
 
 #[cfg(test)]
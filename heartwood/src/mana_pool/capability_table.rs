@@ -12,7 +12,9 @@
 
 use super::capability::{CapabilityId, SealedCapability, CapabilityRights};
 use super::object_manager::ObjectHandle;
-use alloc::collections::BTreeMap;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec;
+use alloc::vec::Vec;
 
 /// Errors that can occur during capability table operations
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -55,6 +57,12 @@ pub struct CapabilityTable {
     /// Mapping from opaque ID → sealed capability
     /// Using BTreeMap instead of HashMap for deterministic iteration
     table: BTreeMap<CapabilityId, SealedCapability>,
+
+    /// Lineage of derived capabilities (child → parent), for revocation
+    ///
+    /// The parent may live in another Vessel's table; IDs are unique
+    /// system-wide, so the link still holds.
+    parents: BTreeMap<CapabilityId, CapabilityId>,
 }
 
 impl CapabilityTable {
@@ -62,6 +70,7 @@ impl CapabilityTable {
     pub const fn new() -> Self {
         Self {
             table: BTreeMap::new(),
+            parents: BTreeMap::new(),
         }
     }
 
@@ -87,6 +96,16 @@ impl CapabilityTable {
         Ok(id)
     }
 
+    /// Insert a capability derived from `parent`, which may live elsewhere
+    ///
+    /// Revoking `parent` (through `revoke_descendants` on this table) will
+    /// take this capability with it.
+    pub fn insert_derived(&mut self, cap: SealedCapability, parent: CapabilityId) -> Result<CapabilityId, CapabilityError> {
+        let id = self.insert(cap)?;
+        self.parents.insert(id, parent);
+        Ok(id)
+    }
+
     /// Lookup capability by ID and validate seal
     ///
     /// # Arguments
@@ -146,14 +165,63 @@ impl CapabilityTable {
         }
     }
 
-    /// Remove capability from table (revocation)
+    /// Remove capability from table
+    ///
+    /// Capabilities derived from it are left in place; use `revoke` to
+    /// take them too.
     ///
     /// # Returns
     /// The removed capability, if it existed
     pub fn remove(&mut self, id: CapabilityId) -> Option<SealedCapability> {
+        self.parents.remove(&id);
         self.table.remove(&id)
     }
 
+    /// Revoke a capability and everything derived from it in this table
+    ///
+    /// # Returns
+    /// * `Ok(ids)` - Every revoked ID, starting with `id` itself; pass them
+    ///   to `revoke_descendants` on other tables to finish the cascade
+    /// * `Err(InvalidId)` / `Err(SealBroken)` - As for `get`
+    pub fn revoke(&mut self, id: CapabilityId) -> Result<Vec<CapabilityId>, CapabilityError> {
+        self.get(id)?;
+        self.remove(id);
+
+        let mut revoked = vec![id];
+        revoked.extend(self.revoke_descendants(&[id]));
+        Ok(revoked)
+    }
+
+    /// Revoke every capability descended from any of `ancestors`
+    ///
+    /// The ancestors themselves need not be in this table.
+    ///
+    /// # Returns
+    /// The IDs removed from this table
+    pub fn revoke_descendants(&mut self, ancestors: &[CapabilityId]) -> Vec<CapabilityId> {
+        let mut doomed: BTreeSet<CapabilityId> = ancestors.iter().copied().collect();
+        let mut revoked = Vec::new();
+
+        // Each pass takes one more generation of children
+        loop {
+            let generation: Vec<CapabilityId> = self
+                .parents
+                .iter()
+                .filter(|(_, parent)| doomed.contains(parent))
+                .map(|(&child, _)| child)
+                .collect();
+            if generation.is_empty() {
+                return revoked;
+            }
+
+            for child in generation {
+                self.remove(child);
+                doomed.insert(child);
+                revoked.push(child);
+            }
+        }
+    }
+
     /// Derive a new capability with reduced rights
     ///
     /// Creates a new sealed capability with attenuated rights and
@@ -176,8 +244,8 @@ impl CapabilityTable {
         // Derive child capability (validates rights attenuation)
         let child = parent.derive(new_rights);
 
-        // Insert child into table
-        self.insert(child)
+        // Insert child into table, remembering where it came from
+        self.insert_derived(child, parent_id)
     }
 
     /// Get the object handle for a capability (after validation)
@@ -210,6 +278,7 @@ impl CapabilityTable {
     /// Use with caution - this revokes ALL capabilities in the table.
     pub fn clear(&mut self) {
         self.table.clear();
+        self.parents.clear();
     }
 }
 
@@ -222,9 +291,11 @@ impl Default for CapabilityTable {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mana_pool::sealing;
 
     #[test]
     fn test_insert_and_lookup() {
+        sealing::init_for_tests();
         let mut table = CapabilityTable::new();
         let handle = ObjectHandle(0x1000);
         let rights = CapabilityRights::READ;
@@ -240,15 +311,17 @@ mod tests {
 
     #[test]
     fn test_invalid_id() {
+        sealing::init_for_tests();
         let table = CapabilityTable::new();
         let invalid_id = CapabilityId::new(9999);
 
         // Should fail - ID not in table
-        assert_eq!(table.get(invalid_id), Err(CapabilityError::InvalidId));
+        assert_eq!(table.get(invalid_id).err(), Some(CapabilityError::InvalidId));
     }
 
     #[test]
     fn test_rights_check() {
+        sealing::init_for_tests();
         let mut table = CapabilityTable::new();
         let handle = ObjectHandle(0x1000);
         let rights = CapabilityRights::READ;
@@ -268,6 +341,7 @@ mod tests {
 
     #[test]
     fn test_derive_attenuation() {
+        sealing::init_for_tests();
         let mut table = CapabilityTable::new();
         let handle = ObjectHandle(0x1000);
         let parent_rights = CapabilityRights::READ | CapabilityRights::WRITE;
@@ -287,6 +361,7 @@ mod tests {
     #[test]
     #[should_panic(expected = "Cannot amplify rights")]
     fn test_derive_amplification_panics() {
+        sealing::init_for_tests();
         let mut table = CapabilityTable::new();
         let handle = ObjectHandle(0x1000);
         let parent_rights = CapabilityRights::READ;
@@ -300,6 +375,7 @@ mod tests {
 
     #[test]
     fn test_revocation() {
+        sealing::init_for_tests();
         let mut table = CapabilityTable::new();
         let handle = ObjectHandle(0x1000);
         let cap = SealedCapability::new(handle, CapabilityRights::READ);
@@ -313,11 +389,54 @@ mod tests {
         assert!(removed.is_some());
 
         // Should no longer exist
-        assert_eq!(table.get(cap_id), Err(CapabilityError::InvalidId));
+        assert_eq!(table.get(cap_id).err(), Some(CapabilityError::InvalidId));
+    }
+
+    #[test]
+    fn test_revocation_cascades() {
+        sealing::init_for_tests();
+        let mut table = CapabilityTable::new();
+        let root = SealedCapability::new(ObjectHandle(0x1000), CapabilityRights::channel_endpoint());
+        let root_id = table.insert(root).unwrap();
+
+        let child_id = table.derive(root_id, CapabilityRights::SEND | CapabilityRights::TRANSFER).unwrap();
+        let grandchild_id = table.derive(child_id, CapabilityRights::SEND).unwrap();
+        let sibling_id = table.derive(root_id, CapabilityRights::RECEIVE).unwrap();
+
+        // Revoking the child takes the grandchild but spares its parent and sibling
+        let revoked = table.revoke(child_id).unwrap();
+        assert_eq!(revoked, vec![child_id, grandchild_id]);
+        assert!(table.get(root_id).is_ok());
+        assert!(table.get(sibling_id).is_ok());
+        assert_eq!(table.get(grandchild_id).err(), Some(CapabilityError::InvalidId));
+
+        // Revoking the root takes everything left
+        assert_eq!(table.revoke(root_id).unwrap(), vec![root_id, sibling_id]);
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn test_revoke_descendants_across_tables() {
+        sealing::init_for_tests();
+        let mut owner = CapabilityTable::new();
+        let mut holder = CapabilityTable::new();
+
+        let root = SealedCapability::new(ObjectHandle(0x1000), CapabilityRights::channel_endpoint());
+        let root_id = owner.insert(root).unwrap();
+        let child_id = owner.derive(root_id, CapabilityRights::SEND).unwrap();
+
+        // Move the child to another table, as a transfer would
+        let child = owner.remove(child_id).unwrap();
+        holder.insert_derived(child, root_id).unwrap();
+
+        let revoked = owner.revoke(root_id).unwrap();
+        assert_eq!(holder.revoke_descendants(&revoked), vec![child_id]);
+        assert!(holder.is_empty());
     }
 
     #[test]
     fn test_table_full() {
+        sealing::init_for_tests();
         let mut table = CapabilityTable::new();
 
         // Fill table to capacity
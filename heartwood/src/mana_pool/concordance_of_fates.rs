@@ -62,6 +62,9 @@ pub struct Fate {
     /// Memory access rules
     pub memory_rules: Vec<MemoryRule>,
 
+    /// Nexus name service rules
+    pub service_rules: Vec<ServiceRule>,
+
     /// Can this Fate transition to other Fates?
     pub allowed_transitions: Vec<String>,
 
@@ -178,6 +181,26 @@ pub enum MemoryAccess {
     All,
 }
 
+/// Rule for the Nexus name service
+#[derive(Debug, Clone)]
+pub struct ServiceRule {
+    /// Service name pattern (supports a trailing *, e.g. "world-tree/*")
+    pub name_pattern: String,
+
+    /// Registering the name, or resolving it
+    pub access: ServiceAccess,
+
+    /// Allow or deny?
+    pub permission: Permission,
+}
+
+/// Name service operations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceAccess {
+    Register,
+    Resolve,
+}
+
 /// Permission type
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Permission {
@@ -349,8 +372,21 @@ impl Concordance {
         let subject = self.subjects.get(&subject_id)
             .ok_or(ConcordanceError::SubjectNotFound(subject_id))?;
 
-        let fate = self.fates.get(&subject.fate)
-            .ok_or(ConcordanceError::FateNotFound(subject.fate.clone()))?;
+        self.check_fate_permission(&subject.fate, operation)
+    }
+
+    /// Check if a Fate allows an operation
+    ///
+    /// For entities judged by the Fate they carry rather than by a
+    /// registered Subject, such as Vessels.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(true)` / `Ok(false)` - Operation allowed / denied by the Fate
+    /// * `Err(ConcordanceError)` - Fate not found
+    pub fn check_fate_permission(&self, fate_name: &str, operation: &Operation) -> Result<bool, ConcordanceError> {
+        let fate = self.fates.get(fate_name)
+            .ok_or_else(|| ConcordanceError::FateNotFound(String::from(fate_name)))?;
 
         // Check based on operation type
         match operation {
@@ -361,6 +397,8 @@ impl Concordance {
             Operation::NetworkConnect(port) => self.check_network_permission(fate, *port, NetworkOperation::Connect),
             Operation::MemoryRead(addr) => self.check_memory_permission(fate, *addr, MemoryAccess::Read),
             Operation::MemoryWrite(addr) => self.check_memory_permission(fate, *addr, MemoryAccess::Write),
+            Operation::ServiceRegister(name) => self.check_service_permission(fate, name, ServiceAccess::Register),
+            Operation::ServiceResolve(name) => self.check_service_permission(fate, name, ServiceAccess::Resolve),
             Operation::Fork => Ok(fate.capabilities.can_fork),
             Operation::ReadSymbols => Ok(fate.capabilities.can_read_symbols),
         }
@@ -446,6 +484,29 @@ impl Concordance {
         Ok(true)
     }
 
+    /// Check name service permission against Fate's rules
+    fn check_service_permission(&self, fate: &Fate, name: &str, access: ServiceAccess) -> Result<bool, ConcordanceError> {
+        // Serving a name means receiving on it; using one means sending
+        let cap_allowed = match access {
+            ServiceAccess::Register => fate.capabilities.can_receive_ipc,
+            ServiceAccess::Resolve => fate.capabilities.can_send_ipc,
+        };
+
+        if !cap_allowed {
+            return Ok(false);
+        }
+
+        // Check explicit rules
+        for rule in &fate.service_rules {
+            if rule.access == access && self.path_matches(&rule.name_pattern, name) {
+                return Ok(rule.permission == Permission::Allow);
+            }
+        }
+
+        // Default deny
+        Ok(false)
+    }
+
     /// Simple path matching (supports * wildcard)
     fn path_matches(&self, pattern: &str, path: &str) -> bool {
         if pattern == "*" || pattern == "**" {
@@ -483,6 +544,11 @@ impl Concordance {
         Ok(())
     }
 
+    /// Get the default Fate for new Subjects
+    pub fn default_fate(&self) -> &str {
+        &self.default_fate
+    }
+
     /// Get the number of defined Fates
     pub fn fate_count(&self) -> usize {
         self.fates.len()
@@ -504,6 +570,8 @@ pub enum Operation {
     NetworkConnect(u16),
     MemoryRead(u64),
     MemoryWrite(u64),
+    ServiceRegister(String),
+    ServiceResolve(String),
     Fork,
     ReadSymbols,
 }
@@ -639,6 +707,18 @@ fn define_default_fates(concordance: &mut Concordance) {
             },
         ],
         memory_rules: vec![],
+        service_rules: vec![
+            ServiceRule {
+                name_pattern: String::from("*"),
+                access: ServiceAccess::Register,
+                permission: Permission::Allow,
+            },
+            ServiceRule {
+                name_pattern: String::from("*"),
+                access: ServiceAccess::Resolve,
+                permission: Permission::Allow,
+            },
+        ],
         allowed_transitions: vec![String::from("Guardian"), String::from("Weaver")],
         is_privileged: true,
     };
@@ -686,6 +766,13 @@ fn define_default_fates(concordance: &mut Concordance) {
             },
         ],
         memory_rules: vec![],
+        service_rules: vec![
+            ServiceRule {
+                name_pattern: String::from("*"),
+                access: ServiceAccess::Resolve,
+                permission: Permission::Allow,
+            },
+        ],
         allowed_transitions: vec![String::from("Weaver")],
         is_privileged: false,
     };
@@ -750,6 +837,7 @@ mod tests {
             file_rules: vec![],
             network_rules: vec![],
             memory_rules: vec![],
+            service_rules: vec![],
             allowed_transitions: vec![],
             is_privileged: false,
         };
@@ -768,4 +856,19 @@ mod tests {
         assert!(concordance.register_subject(subject_id, SubjectType::KernelThread).is_ok());
         assert_eq!(concordance.subject_count(), 1);
     }
+
+    #[test]
+    fn test_service_rules() {
+        let mut concordance = Concordance::new();
+        define_default_fates(&mut concordance);
+
+        let register = Operation::ServiceRegister(String::from("world-tree/query"));
+        let resolve = Operation::ServiceResolve(String::from("world-tree/query"));
+
+        // Weavers may use services but not offer them
+        assert!(concordance.check_fate_permission("Weaver", &resolve).unwrap());
+        assert!(!concordance.check_fate_permission("Weaver", &register).unwrap());
+        assert!(concordance.check_fate_permission("Guardian", &register).unwrap());
+        assert!(concordance.check_fate_permission("Wanderer", &resolve).is_err());
+    }
 }
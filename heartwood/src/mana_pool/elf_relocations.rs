@@ -3,6 +3,8 @@
 //! This module parses and applies ELF relocations to support KASLR Phase 3.
 //! When the kernel is loaded at a different virtual address than it was
 //! linked for, all absolute addresses must be fixed up.
+//! The same entry types are used by the Grove Linker
+//! (`loom_of_fate::grove_linker`) to relocate user programs and Groves.
 //!
 //! ## ELF Relocation Types (x86_64)
 //!
@@ -173,9 +175,9 @@ pub unsafe fn apply_kaslr_relocations(
     );
 
     let mut relative_count = 0;
-    let mut other_count = 0;
+    let mut _other_count = 0;
 
-    for (i, rela) in rela_table.iter().enumerate() {
+    for rela in rela_table.iter() {
         match rela.relocation_type() {
             Some(RelocationType::Relative) => {
                 // R_X86_64_RELATIVE: *r_offset = base + r_addend
@@ -198,8 +200,8 @@ pub unsafe fn apply_kaslr_relocations(
                 //     );
                 // }
             }
-            Some(rela_type) => {
-                other_count += 1;
+            Some(_rela_type) => {
+                _other_count += 1;
                 // if i < 5 {
                 //     crate::serial_println!(
                 //         "[RELOC]   [{:4}] {} (skipped)",
@@ -236,7 +238,7 @@ pub unsafe fn apply_kaslr_relocations(
 pub unsafe fn apply_simple_relocations(
     kernel_start: u64,
     kernel_end: u64,
-    kaslr_offset: u64,
+    _kaslr_offset: u64,
 ) -> Result<usize, &'static str> {
     crate::serial_println!("[RELOC] No relocation table found, using pattern-based approach");
     crate::serial_println!("[RELOC] Kernel range: 0x{:016x} - 0x{:016x}", kernel_start, kernel_end);
@@ -82,6 +82,10 @@ impl<T> InterruptSafeLock<T> {
             core::sync::atomic::fence(core::sync::atomic::Ordering::SeqCst);
             core::hint::spin_loop();
 
+            // The holder may be waiting for this CPU to answer a TLB
+            // shootdown, which it cannot take as an interrupt right now
+            crate::attunement::smp::service_shootdown();
+
             spin_count += 1;
 
             // Report spinning progress to help diagnose where we're stuck
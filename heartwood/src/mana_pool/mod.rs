@@ -33,6 +33,7 @@ pub mod security_policy;  // Immutable security configuration
 pub mod rune_of_permanence;  // Hardware-enforced kernel data immutability
 pub mod concordance_of_fates;  // Role-Based Access Control (RBAC)
 pub mod kernel_remap;  // Kernel memory write permission remapping
+pub mod elf_relocations;  // ELF relocation entries (KASLR and Grove linking)
 
 pub use object_manager::{ObjectManager, ObjectHandle, ObjectType, ObjectInfo};
 pub use capability::{Capability, CapabilityRights, CapabilityId, SealedCapability};
@@ -40,8 +41,8 @@ pub use capability_table::{CapabilityTable, CapabilityError};
 pub use sanctuary::Sanctuary;
 pub use ephemeral_mist::EphemeralMist;
 pub use interrupt_lock::InterruptSafeLock;
-pub use user_space::{UserAddressSpace, MemoryRegion, RegionType, create_address_space_from_elf};
-pub use page_tables::{map_user_page, clone_kernel_page_table, flush_tlb};
+pub use user_space::{UserAddressSpace, MemoryRegion, RegionType, UserFault, create_address_space_from_elf};
+pub use page_tables::{map_user_page, unmap_user_page, clone_kernel_page_table, flush_tlb};
 pub use kernel_remap::ensure_kernel_memory_writable;
 
 use core::mem::MaybeUninit;
@@ -115,6 +116,17 @@ impl ManaPool {
         self.object_manager.create_object(address, size, purpose)
     }
 
+    /// Animate a shared memory object that can be mapped into Vessels
+    /// Returns a capability with full rights to the new object
+    pub fn animate_shared(&mut self, size: usize) -> Result<Capability, ManaError> {
+        self.object_manager.create_shared_object(size)
+    }
+
+    /// Get a shared memory object's kernel address and size
+    pub fn shared_memory(&self, capability: &Capability) -> Result<(usize, usize), ManaError> {
+        self.object_manager.shared_memory(capability)
+    }
+
     /// Release an object back to the Mana Pool
     /// Requires a valid capability to the object
     pub fn release(&mut self, capability: &Capability) -> Result<(), ManaError> {
@@ -208,6 +220,18 @@ pub fn animate(size: usize, purpose: AllocationPurpose) -> Result<Capability, Ma
     unsafe { get_mana_pool().lock().animate(size, purpose) }
 }
 
+/// Allocate page-backed memory that can be shared between Vessels
+/// Returns a capability with full rights to the newly created object
+pub fn animate_shared(size: usize) -> Result<Capability, ManaError> {
+    unsafe { get_mana_pool().lock().animate_shared(size) }
+}
+
+/// Get a shared memory object's kernel address and size
+/// Requires READ rights
+pub fn shared_memory(capability: &Capability) -> Result<(usize, usize), ManaError> {
+    unsafe { get_mana_pool().lock().shared_memory(capability) }
+}
+
 /// Release memory back to the pool
 /// Requires a valid capability
 pub fn release(capability: &Capability) -> Result<(), ManaError> {
@@ -3,8 +3,12 @@
 use super::capability::{Capability, CapabilityRights, CapabilityId, SealedCapability};
 use super::capability_table::{CapabilityTable, CapabilityError};
 use super::{AllocationPurpose, ManaError};
+use alloc::alloc::Layout;
 use alloc::collections::BTreeMap;
 
+/// Shared memory objects are whole pages, so they can be mapped into Vessels
+const PAGE_SIZE: usize = 0x1000;
+
 /// A handle to an object in the Mana Pool
 /// This is what user-space processes receive - never raw pointers
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -14,6 +18,9 @@ pub struct ObjectHandle(pub u64);
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ObjectType {
     Memory,
+    /// Page-aligned memory backed by real frames, passed between Vessels
+    /// over the Nexus without copying
+    SharedMemory,
     File,
     Channel,
     Thread,
@@ -80,6 +87,61 @@ impl ObjectManager {
         Ok(Capability::new(handle, CapabilityRights::full()))
     }
 
+    /// Create a shared memory object and return a capability with full rights
+    ///
+    /// The object is backed by zeroed, page-aligned kernel heap memory, which
+    /// is physically contiguous; its frames can therefore be mapped straight
+    /// into a Vessel. The size is rounded up to whole pages.
+    pub fn create_shared_object(&mut self, size: usize) -> Result<Capability, ManaError> {
+        let size = size
+            .max(1)
+            .checked_next_multiple_of(PAGE_SIZE)
+            .ok_or(ManaError::AllocationTooLarge)?;
+        let layout = Layout::from_size_align(size, PAGE_SIZE).map_err(|_| ManaError::AllocationTooLarge)?;
+
+        let address = unsafe { alloc::alloc::alloc_zeroed(layout) };
+        if address.is_null() {
+            return Err(ManaError::OutOfMemory);
+        }
+
+        let handle = ObjectHandle(self.next_handle);
+        self.next_handle += 1;
+
+        self.objects.insert(handle, Object {
+            handle,
+            object_type: ObjectType::SharedMemory,
+            address: address as usize,
+            size,
+            purpose: AllocationPurpose::LongLived,
+            ref_count: 1,
+        });
+
+        Ok(Capability::new(handle, CapabilityRights::full()))
+    }
+
+    /// Kernel address and size of a shared memory object
+    ///
+    /// Requires READ rights; fails for any other kind of object.
+    pub fn shared_memory(&self, capability: &Capability) -> Result<(usize, usize), ManaError> {
+        let (address, size) = self.access_object(capability)?;
+        match self.objects.get(&capability.handle) {
+            Some(object) if object.object_type == ObjectType::SharedMemory => Ok((address, size)),
+            _ => Err(ManaError::InvalidHandle),
+        }
+    }
+
+    /// Drop an object whose last reference is gone
+    fn destroy_object(&mut self, handle: ObjectHandle) {
+        if let Some(object) = self.objects.remove(&handle) {
+            if object.object_type == ObjectType::SharedMemory {
+                unsafe {
+                    let layout = Layout::from_size_align_unchecked(object.size, PAGE_SIZE);
+                    alloc::alloc::dealloc(object.address as *mut u8, layout);
+                }
+            }
+        }
+    }
+
     /// Release an object (decrement ref count, free if zero)
     /// Requires a valid capability to the object
     pub fn release_object(&mut self, capability: &Capability) -> Result<(), ManaError> {
@@ -101,7 +163,7 @@ impl ObjectManager {
 
         if object.ref_count == 0 {
             // Actually free the memory and remove the object
-            self.objects.remove(&capability.handle);
+            self.destroy_object(capability.handle);
         }
 
         Ok(())
@@ -307,7 +369,7 @@ impl ObjectManager {
 
         if object.ref_count == 0 {
             // Free the object
-            self.objects.remove(&handle);
+            self.destroy_object(handle);
         }
 
         Ok(())
@@ -347,3 +409,59 @@ pub struct ObjectInfo {
     pub purpose: AllocationPurpose,
     pub ref_count: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_object_is_zeroed_whole_pages() {
+        let mut objects = ObjectManager::new();
+        let capability = objects.create_shared_object(100).unwrap();
+
+        let (address, size) = objects.shared_memory(&capability).unwrap();
+        assert_eq!(size, PAGE_SIZE);
+        assert_eq!(address % PAGE_SIZE, 0);
+        let contents = unsafe { core::slice::from_raw_parts(address as *const u8, size) };
+        assert!(contents.iter().all(|&byte| byte == 0));
+
+        objects.release_object(&capability).unwrap();
+        assert_eq!(objects.object_count(), 0);
+    }
+
+    #[test]
+    fn test_shared_object_lives_until_last_release() {
+        let mut objects = ObjectManager::new();
+        let owner = objects.create_shared_object(2 * PAGE_SIZE).unwrap();
+        let holder = objects.clone_capability(&owner).unwrap();
+        assert_eq!(objects.get_object_info(&owner).unwrap().ref_count, 2);
+
+        // The first release leaves the object to its other holder
+        objects.release_object(&owner).unwrap();
+        assert_eq!(objects.object_count(), 1);
+        assert_eq!(objects.get_object_info(&holder).unwrap().ref_count, 1);
+        assert!(objects.shared_memory(&holder).is_ok());
+
+        // The last one destroys it, and the handle is dead from then on
+        objects.release_object(&holder).unwrap();
+        assert_eq!(objects.object_count(), 0);
+        assert_eq!(objects.shared_memory(&holder), Err(ManaError::InvalidCapability));
+        assert_eq!(objects.release_object(&holder), Err(ManaError::InvalidCapability));
+    }
+
+    #[test]
+    fn test_shared_memory_needs_read_on_a_shared_object() {
+        let mut objects = ObjectManager::new();
+        let shared = objects.create_shared_object(PAGE_SIZE).unwrap();
+        let write_only = objects.derive_capability(&shared, CapabilityRights::WRITE).unwrap();
+        assert_eq!(objects.shared_memory(&write_only), Err(ManaError::InsufficientRights));
+
+        // Plain memory objects are not page-backed and cannot be mapped
+        let plain = objects.create_object(0x1000, 64, AllocationPurpose::ShortLived).unwrap();
+        assert_eq!(objects.shared_memory(&plain), Err(ManaError::InvalidHandle));
+
+        objects.release_object(&shared).unwrap();
+        objects.release_object(&plain).unwrap();
+        assert_eq!(objects.object_count(), 0);
+    }
+}
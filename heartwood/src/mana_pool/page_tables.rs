@@ -461,8 +461,8 @@ unsafe fn phys_to_virt(phys: u64) -> u64 {
 ///
 /// # Safety
 ///
-/// The page table is leaked and will never be freed.
-/// This is acceptable for Phase 2; proper cleanup will be implemented in Phase 3+.
+/// The page table is leaked. Tables under a Vessel's PML4 are freed with
+/// its address space by `free_user_page_tables`.
 unsafe fn allocate_page_table() -> Result<u64, &'static str> {
     use alloc::boxed::Box;
 
@@ -617,6 +617,261 @@ pub unsafe fn map_user_page(
     Ok(())
 }
 
+/// Remove a 4KB user page mapping from a Vessel's page tables
+///
+/// Intermediate tables are left in place until the whole address space is
+/// freed with `free_user_page_tables`; only the leaf entry is cleared.
+///
+/// # Arguments
+///
+/// * `pml4_phys` - Physical address of the PML4 (CR3 value)
+/// * `virt_addr` - Page-aligned user-space virtual address to unmap
+///
+/// # Returns
+///
+/// * `Some(phys_addr)` - The frame that was mapped there
+/// * `None` - Nothing was mapped at `virt_addr`
+///
+/// # Safety
+///
+/// - The caller must ensure pml4_phys points to a valid PML4
+/// - The caller must flush the TLB entry if this PML4 is active
+/// - The caller owns the returned frame and is responsible for freeing it
+pub unsafe fn unmap_user_page(pml4_phys: u64, virt_addr: u64) -> Option<u64> {
+    if virt_addr >= 0x0000_8000_0000_0000 || virt_addr % 0x1000 != 0 {
+        return None;
+    }
+
+    let mut table = &mut *(phys_to_virt(pml4_phys) as *mut PageTable);
+
+    // Walk PML4 -> PDPT -> PD, refusing to descend through huge pages
+    for level in (2..=4).rev() {
+        let entry = table.entry(page_table_index(virt_addr, level));
+        if !entry.is_present() || (level < 4 && entry.is_huge()) {
+            return None;
+        }
+        table = &mut *(phys_to_virt(entry.address()) as *mut PageTable);
+    }
+
+    let pt_entry = table.entry_mut(page_table_index(virt_addr, 1));
+    if !pt_entry.is_present() {
+        return None;
+    }
+
+    let phys_addr = pt_entry.address();
+    pt_entry.set_raw(0);
+    Some(phys_addr)
+}
+
+/// Find the frame behind a 4KB user page in a Vessel's page tables
+///
+/// # Arguments
+///
+/// * `pml4_phys` - Physical address of the PML4 (CR3 value)
+/// * `virt_addr` - Page-aligned user-space virtual address to look up
+///
+/// # Returns
+///
+/// * `Some(phys_addr)` - The frame mapped there
+/// * `None` - Nothing is mapped at `virt_addr`
+///
+/// # Safety
+///
+/// The caller must ensure pml4_phys points to a valid PML4
+pub unsafe fn translate_user_page(pml4_phys: u64, virt_addr: u64) -> Option<u64> {
+    if virt_addr >= 0x0000_8000_0000_0000 || virt_addr % 0x1000 != 0 {
+        return None;
+    }
+
+    let mut table = &*(phys_to_virt(pml4_phys) as *const PageTable);
+
+    for level in (2..=4).rev() {
+        let entry = table.entry(page_table_index(virt_addr, level));
+        if !entry.is_present() || (level < 4 && entry.is_huge()) {
+            return None;
+        }
+        table = &*(phys_to_virt(entry.address()) as *const PageTable);
+    }
+
+    let pt_entry = table.entry(page_table_index(virt_addr, 1));
+    pt_entry.is_present().then(|| pt_entry.address())
+}
+
+/// Free a Vessel's page tables: every table under the user half of its
+/// PML4, then the PML4 itself
+///
+/// The frames the tables map are not touched; the caller must have unmapped
+/// and freed them already. The kernel half is shared with every other
+/// address space and is left alone.
+///
+/// # Safety
+///
+/// - pml4_phys must be a PML4 made by `clone_kernel_page_table`
+/// - No processor may have it loaded in CR3 (see `smp::retire_page_table`)
+pub unsafe fn free_user_page_tables(pml4_phys: u64) {
+    use alloc::boxed::Box;
+
+    let pml4 = phys_to_virt(pml4_phys) as *mut PageTable;
+    for index in 0..256 {
+        free_table_tree((*pml4).entry(index), 3);
+    }
+    drop(Box::from_raw(pml4));
+}
+
+/// Free the table an entry points to, and every table below it
+///
+/// `level` is the level of the table pointed to (3 = PDPT, 2 = PD, 1 = PT).
+unsafe fn free_table_tree(entry: PageTableEntry, level: usize) {
+    use alloc::boxed::Box;
+
+    if !entry.is_present() || entry.is_huge() {
+        return;
+    }
+
+    let table = phys_to_virt(entry.address()) as *mut PageTable;
+    if level > 1 {
+        for index in 0..512 {
+            free_table_tree((*table).entry(index), level - 1);
+        }
+    }
+    drop(Box::from_raw(table));
+}
+
+/// Start of the kernel's device memory window (PDPT[511] of the top 2GB)
+///
+/// The first 1GB of the top 2GB (PDPT[510]) maps physical RAM; the last
+/// 1GB is otherwise unused, so device registers are mapped there. Because
+/// every Vessel shares the kernel's PDPT, mappings made here are visible in
+/// all address spaces.
+const MMIO_WINDOW_START: u64 = 0xFFFF_FFFF_C000_0000;
+
+/// Next free virtual address in the device memory window
+static NEXT_MMIO_ADDR: spin::Mutex<u64> = spin::Mutex::new(MMIO_WINDOW_START);
+
+/// Map a range of device registers (MMIO) into kernel space
+///
+/// The pages are mapped writable and uncached (PCD | PWT), as device
+/// registers must never be served from the cache.
+///
+/// # Arguments
+///
+/// * `phys_addr` - Physical address of the registers (need not be page-aligned)
+/// * `size` - Size of the register range in bytes
+///
+/// # Returns
+///
+/// * `Ok(virt_addr)` - Kernel virtual address corresponding to `phys_addr`
+/// * `Err(&str)` - The window is exhausted or a table couldn't be allocated
+///
+/// # Safety
+///
+/// `phys_addr..phys_addr + size` must be device memory; mapping RAM
+/// uncached alongside its cached alias in the top 2GB is undefined.
+pub unsafe fn map_mmio(phys_addr: u64, size: u64) -> Result<u64, &'static str> {
+    let page_flags = (PageFlag::Present as u64)
+        | (PageFlag::ReadWrite as u64)
+        | (PageFlag::WriteThrough as u64)
+        | (PageFlag::CacheDisable as u64);
+    map_window(phys_addr, size, page_flags)
+}
+
+/// Map a range of ordinary memory beyond the first 1GB read-only
+///
+/// For firmware data (such as ACPI tables) that lies outside the top 2GB's
+/// direct map. Unlike [`map_mmio`] the pages are cached.
+///
+/// # Arguments
+///
+/// * `phys_addr` - Physical address of the data (need not be page-aligned)
+/// * `size` - Size of the data in bytes
+///
+/// # Returns
+///
+/// * `Ok(virt_addr)` - Kernel virtual address corresponding to `phys_addr`
+/// * `Err(&str)` - The window is exhausted or a table couldn't be allocated
+///
+/// # Safety
+///
+/// `phys_addr..phys_addr + size` must be RAM, not device memory. The
+/// mapping is never removed, so callers should map each range only once.
+pub unsafe fn map_physical(phys_addr: u64, size: u64) -> Result<u64, &'static str> {
+    map_window(phys_addr, size, PageFlag::Present as u64)
+}
+
+/// Map a physical range into the device memory window with `page_flags`
+unsafe fn map_window(phys_addr: u64, size: u64, page_flags: u64) -> Result<u64, &'static str> {
+    let first_page = phys_addr & !0xFFF;
+    let page_count = (phys_addr + size - first_page).div_ceil(0x1000);
+
+    let mut next = NEXT_MMIO_ADDR.lock();
+    let virt_base = *next;
+    if virt_base.checked_add(page_count * 0x1000).is_none() {
+        return Err("MMIO window exhausted");
+    }
+
+    let table_flags = (PageFlag::Present as u64) | (PageFlag::ReadWrite as u64);
+
+    for page in 0..page_count {
+        let virt_addr = virt_base + page * 0x1000;
+
+        // Walk PML4 -> PDPT -> PD -> PT, creating the PD and PT as needed
+        let mut table = &mut *(phys_to_virt(read_cr3()) as *mut PageTable);
+        for level in (2..=4).rev() {
+            let entry = table.entry_mut(page_table_index(virt_addr, level));
+            if !entry.is_present() {
+                let new_table = allocate_page_table()?;
+                entry.set_raw(new_table | table_flags);
+            } else if entry.is_huge() {
+                return Err("Huge page in the MMIO window");
+            }
+            table = &mut *(phys_to_virt(entry.address()) as *mut PageTable);
+        }
+
+        table
+            .entry_mut(page_table_index(virt_addr, 1))
+            .set_raw((first_page + page * 0x1000) | page_flags);
+        core::arch::asm!("invlpg [{}]", in(reg) virt_addr, options(nostack, preserves_flags));
+    }
+
+    *next = virt_base + page_count * 0x1000;
+    Ok(virt_base + (phys_addr - first_page))
+}
+
+/// Build the page tables an application processor starts on
+///
+/// A processor leaving its startup trampoline still runs from the low
+/// physical page it was started at, so the kernel's mappings alone are not
+/// enough: the first 2MB are identity-mapped as well. The processor moves
+/// to the kernel's own PML4 as soon as it reaches the higher half.
+///
+/// # Returns
+/// Physical address of the PML4 (below 4GB, so a 32-bit `mov cr3` can load it)
+///
+/// # Safety
+/// Must be called after the heap allocator is initialized. The tables are
+/// never freed.
+pub unsafe fn ap_boot_page_table() -> Result<u64, &'static str> {
+    let table_flags = (PageFlag::Present as u64) | (PageFlag::ReadWrite as u64);
+
+    let pml4_phys = allocate_page_table()?;
+    let pdpt_phys = allocate_page_table()?;
+    let pd_phys = allocate_page_table()?;
+
+    let kernel_pml4 = &*(phys_to_virt(read_cr3()) as *const PageTable);
+    let pml4 = &mut *(phys_to_virt(pml4_phys) as *mut PageTable);
+    for index in 256..512 {
+        *pml4.entry_mut(index) = kernel_pml4.entry(index);
+    }
+
+    pml4.entry_mut(0).set_raw(pdpt_phys | table_flags);
+    (*(phys_to_virt(pdpt_phys) as *mut PageTable)).entry_mut(0).set_raw(pd_phys | table_flags);
+    (*(phys_to_virt(pd_phys) as *mut PageTable))
+        .entry_mut(0)
+        .set_raw(table_flags | (PageFlag::HugePage as u64));
+
+    Ok(pml4_phys)
+}
+
 /// Clone the kernel's page tables for a new Vessel
 ///
 /// Creates a new PML4 with:
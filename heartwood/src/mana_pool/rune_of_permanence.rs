@@ -122,8 +122,8 @@ pub unsafe fn seal_rune_section() {
         }
     }
 
-    // Flush TLB to ensure changes take effect immediately
-    super::page_tables::flush_tlb();
+    // Flush TLB to ensure changes take effect immediately, on every CPU
+    crate::attunement::smp::shootdown_all();
 
     crate::println!("  ✓ The Rune is sealed. Permanence enforced by the MMU.");
 }
@@ -118,6 +118,13 @@ pub unsafe fn get_sealer() -> &'static CapabilitySealer {
     &*core::ptr::addr_of!(SEALER).cast::<CapabilitySealer>()
 }
 
+/// Initialize the global sealer once for tests running on many threads
+#[cfg(test)]
+pub(crate) fn init_for_tests() {
+    static INIT: spin::Once = spin::Once::new();
+    INIT.call_once(|| unsafe { init() });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
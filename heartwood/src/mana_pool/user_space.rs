@@ -2,7 +2,11 @@
 //!
 //! Manages virtual memory for userspace processes (Vessels).
 
+use super::Capability;
+use crate::loom_of_fate::elf_loader::{LoadedSegment, TlsTemplate};
+use crate::loom_of_fate::grove_linker::{self, Grove};
 use alloc::vec::Vec;
+use x86_64::structures::idt::PageFaultErrorCode;
 use x86_64::structures::paging::PageTableFlags;
 use x86_64::{PhysAddr, VirtAddr};
 
@@ -22,6 +26,13 @@ pub enum RegionType {
     ReadOnlyData,
     Heap,
     Stack,
+    /// Anonymous memory handed out by `SYS_MMAP`
+    Anonymous,
+    /// Pages of a Mana Pool shared memory object, created here or received
+    /// over the Nexus
+    Shared,
+    /// A thread's TLS block, mapped eagerly when the thread is created
+    Tls,
 }
 
 /// Memory region in user address space
@@ -31,13 +42,16 @@ pub struct MemoryRegion {
     pub size: u64,
     pub region_type: RegionType,
     pub flags: PageTableFlags,
+    /// For shared regions, the capability that keeps the object alive
+    pub object: Option<Capability>,
 }
 
 impl MemoryRegion {
     pub fn new(start: VirtAddr, size: u64, region_type: RegionType) -> Self {
         let flags = match region_type {
             RegionType::Code => PageTableFlags::PRESENT | PageTableFlags::USER_ACCESSIBLE,
-            RegionType::Data | RegionType::Heap | RegionType::Stack => {
+            RegionType::Data | RegionType::Heap | RegionType::Stack | RegionType::Anonymous
+            | RegionType::Shared | RegionType::Tls => {
                 PageTableFlags::PRESENT | PageTableFlags::WRITABLE | 
                 PageTableFlags::USER_ACCESSIBLE | PageTableFlags::NO_EXECUTE
             }
@@ -46,7 +60,7 @@ impl MemoryRegion {
                 PageTableFlags::NO_EXECUTE
             }
         };
-        Self { start, size, region_type, flags }
+        Self { start, size, region_type, flags, object: None }
     }
 
     pub fn end(&self) -> VirtAddr {
@@ -62,12 +76,64 @@ impl MemoryRegion {
     }
 }
 
+/// Why a page fault in user space could not be resolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserFault {
+    /// The address lies outside every region of the Vessel
+    Unmapped,
+    /// A write to a region that is not writable
+    WriteToReadOnly,
+    /// An instruction fetch from a no-execute region
+    ExecuteFromNoExecute,
+    /// The page was present but the access violated its protection
+    ProtectionViolation,
+    /// The region should have been mapped eagerly (code/data), yet was not
+    NotDemandPaged,
+    /// Reserved bits set in a page table entry
+    MalformedPageTable,
+    /// No frame could be allocated to back the page
+    OutOfMemory,
+    /// The page tables refused the new mapping
+    MapFailed(&'static str),
+}
+
+impl UserFault {
+    /// Short human-readable description (for fault reports)
+    pub fn description(&self) -> &'static str {
+        match self {
+            UserFault::Unmapped => "access outside any mapped region",
+            UserFault::WriteToReadOnly => "write to read-only memory",
+            UserFault::ExecuteFromNoExecute => "execution of non-executable memory",
+            UserFault::ProtectionViolation => "protection violation",
+            UserFault::NotDemandPaged => "missing page in an eagerly mapped region",
+            UserFault::MalformedPageTable => "malformed page table entry",
+            UserFault::OutOfMemory => "out of memory while paging in",
+            UserFault::MapFailed(reason) => reason,
+        }
+    }
+}
+
+impl RegionType {
+    /// Whether pages of this region are allocated on first touch
+    ///
+    /// Code and data come from the ELF image and are mapped when the Vessel
+    /// is created; stacks and heaps start empty and grow on demand.
+    pub fn is_demand_paged(&self) -> bool {
+        matches!(self, RegionType::Heap | RegionType::Stack | RegionType::Anonymous)
+    }
+}
+
+/// How many random placements `map_anonymous` and its kin try before giving up
+const MMAP_PLACEMENT_ATTEMPTS: usize = 16;
+
 /// User address space for a Vessel
 pub struct UserAddressSpace {
     pub pml4_phys: PhysAddr,
     pub regions: Vec<MemoryRegion>,
     pub heap_break: VirtAddr,
     pub next_stack: VirtAddr,
+    /// The program's TLS template, copied into every thread's TLS block
+    pub tls_template: Option<TlsTemplate>,
 }
 
 impl UserAddressSpace {
@@ -85,6 +151,7 @@ impl UserAddressSpace {
             regions: Vec::new(),
             heap_break: VirtAddr::new(0x0000_0000_0040_0000),
             next_stack: VirtAddr::new(USER_STACK_TOP),
+            tls_template: None,
         })
     }
 
@@ -104,6 +171,7 @@ impl UserAddressSpace {
             regions: Vec::new(),
             heap_break: VirtAddr::new(0x0000_0000_0040_0000),
             next_stack: VirtAddr::new(USER_STACK_TOP),
+            tls_template: None,
         }
     }
 
@@ -189,6 +257,9 @@ impl UserAddressSpace {
         // Round size up to page boundary (4KB)
         let size_pages = (size + 0xFFF) / 0x1000;
         let aligned_size = size_pages * 0x1000;
+        if size_pages == 0 {
+            return Err("Stack size must be non-zero");
+        }
 
         // Allocate stack growing downward from next_stack
         let stack_bottom = self.next_stack - aligned_size;
@@ -199,28 +270,20 @@ impl UserAddressSpace {
         );
 
         // Check for overlaps
-        self.add_region(stack_region.clone())?;
+        self.add_region(stack_region)?;
 
-        // Allocate physical frames for the stack
-        let mut allocated_frames = alloc::vec::Vec::with_capacity(size_pages as usize);
-        for _ in 0..size_pages {
-            let frame = allocate_physical_frame()?;
-            allocated_frames.push(frame);
-        }
-
-        // Extract physical addresses for mapping
-        let phys_addrs: alloc::vec::Vec<PhysAddr> = allocated_frames
-            .iter()
-            .map(|f| f.phys_addr)
-            .collect();
-
-        // Map the stack
+        // Only the top page is populated up front; the rest of the stack is
+        // demand-paged by the page fault handler as the stack grows into it.
+        let top_page = MemoryRegion::new(
+            stack_bottom + (aligned_size - 0x1000),
+            0x1000,
+            RegionType::Stack,
+        );
+        let frame = allocate_physical_frame()?;
         unsafe {
-            self.map_region(&stack_region, &phys_addrs)?;
+            self.map_region(&top_page, &[frame.phys_addr])?;
         }
 
-        // Note: Stack frames are already zero-initialized, no need to copy data
-
         // Update next_stack pointer (leave 64KB guard gap)
         self.next_stack = stack_bottom - 0x10000u64;
 
@@ -241,6 +304,623 @@ impl UserAddressSpace {
     pub fn find_region(&self, addr: VirtAddr) -> Option<&MemoryRegion> {
         self.regions.iter().find(|r| r.contains(addr))
     }
+
+    /// Copy bytes into pages of this address space that are already mapped
+    ///
+    /// Works whether or not this address space is active, by writing
+    /// through the kernel's mapping of each frame. Used to lay out a new
+    /// Vessel's initial stack before its first thread runs.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - User virtual address to copy to
+    /// * `data` - Bytes to copy
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - Data copied
+    /// * `Err(&str)` - Part of the range is not mapped
+    pub fn write_mapped(&self, addr: VirtAddr, data: &[u8]) -> Result<(), &'static str> {
+        self.for_each_mapped_chunk(addr, data.len(), |kernel_ptr, range| unsafe {
+            core::ptr::copy_nonoverlapping(data[range.clone()].as_ptr(), kernel_ptr, range.len());
+        })
+    }
+
+    /// Copy bytes out of pages of this address space that are already mapped
+    ///
+    /// The counterpart of `write_mapped`, used to take new threads' TLS
+    /// from the program's loaded image.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - User virtual address to copy from
+    /// * `buffer` - Where to copy the bytes
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - Data copied
+    /// * `Err(&str)` - Part of the range is not mapped
+    pub fn read_mapped(&self, addr: VirtAddr, buffer: &mut [u8]) -> Result<(), &'static str> {
+        self.for_each_mapped_chunk(addr, buffer.len(), |kernel_ptr, range| unsafe {
+            core::ptr::copy_nonoverlapping(kernel_ptr, buffer[range.clone()].as_mut_ptr(), range.len());
+        })
+    }
+
+    /// Walk `len` bytes from `addr` a page at a time, handing `f` the
+    /// kernel's pointer to each chunk and the chunk's range within the bytes
+    fn for_each_mapped_chunk(
+        &self,
+        addr: VirtAddr,
+        len: usize,
+        mut f: impl FnMut(*mut u8, core::ops::Range<usize>),
+    ) -> Result<(), &'static str> {
+        const KERNEL_BASE: u64 = 0xFFFFFFFF80000000;
+
+        let mut addr = addr.as_u64();
+        let mut done = 0;
+        while done < len {
+            let page = addr & !0xFFF;
+            let offset = addr - page;
+            let chunk = (len - done).min((0x1000 - offset) as usize);
+
+            let frame = unsafe {
+                crate::mana_pool::page_tables::translate_user_page(self.pml4_phys.as_u64(), page)
+            }
+            .ok_or("Page is not mapped")?;
+
+            f((KERNEL_BASE + frame + offset) as *mut u8, done..done + chunk);
+
+            addr += chunk as u64;
+            done += chunk;
+        }
+        Ok(())
+    }
+
+    /// Reserve an anonymous, demand-paged mapping at a randomized address
+    ///
+    /// No frames are allocated here: the region is registered with
+    /// `add_region` and each page is zero-filled by the page fault handler
+    /// on first touch.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - Size in bytes (rounded up to page size)
+    /// * `flags` - Page table flags for the mapping
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(VirtAddr)` - Base of the new mapping
+    /// * `Err(&str)` - No free spot was found
+    pub fn map_anonymous(
+        &mut self,
+        size: u64,
+        flags: PageTableFlags,
+    ) -> Result<VirtAddr, &'static str> {
+        let aligned_size = (size + 0xFFF) & !0xFFF;
+        if aligned_size == 0 {
+            return Err("Mapping size must be non-zero");
+        }
+
+        for _ in 0..MMAP_PLACEMENT_ATTEMPTS {
+            let base = crate::mana_pool::aslr::randomize_mmap_base(aligned_size);
+            let mut region = MemoryRegion::new(VirtAddr::new(base), aligned_size, RegionType::Anonymous);
+            region.flags = flags;
+
+            if self.add_region(region).is_ok() {
+                return Ok(VirtAddr::new(base));
+            }
+        }
+
+        Err("No room for mapping in address space")
+    }
+
+    /// Remove an anonymous mapping created by `map_anonymous`
+    ///
+    /// The range must describe exactly one whole mapping. Pages that were
+    /// touched are unmapped and their frames returned to the heap.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - Base address returned by `map_anonymous`
+    /// * `size` - Size in bytes (rounded up to page size)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - Mapping removed
+    /// * `Err(&str)` - No anonymous mapping matches the range
+    pub fn unmap_anonymous(&mut self, start: VirtAddr, size: u64) -> Result<(), &'static str> {
+        let aligned_size = (size + 0xFFF) & !0xFFF;
+
+        let index = self.regions
+            .iter()
+            .position(|r| {
+                r.region_type == RegionType::Anonymous
+                    && r.start == start
+                    && r.size == aligned_size
+            })
+            .ok_or("No anonymous mapping at this range")?;
+
+        let region = self.regions.remove(index);
+        self.release_pages(&region);
+        Ok(())
+    }
+
+    /// Reserve a thread stack at a randomized address
+    ///
+    /// Like `allocate_stack`, but placed in the mmap window the way
+    /// `map_anonymous` places mappings, so every thread's stack lands
+    /// somewhere different. Only the top page is populated up front.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - Size of the stack in bytes (will be rounded up to page size)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(VirtAddr)` - Top of the new stack
+    /// * `Err(&str)` - No free spot, or the top page could not be mapped
+    pub fn allocate_thread_stack(&mut self, size: u64) -> Result<VirtAddr, &'static str> {
+        let aligned_size = (size + 0xFFF) & !0xFFF;
+        if aligned_size == 0 {
+            return Err("Stack size must be non-zero");
+        }
+
+        let base = (0..MMAP_PLACEMENT_ATTEMPTS)
+            .map(|_| crate::mana_pool::aslr::randomize_mmap_base(aligned_size))
+            .find(|&base| {
+                let region = MemoryRegion::new(VirtAddr::new(base), aligned_size, RegionType::Stack);
+                self.add_region(region).is_ok()
+            })
+            .ok_or("No room for thread stack in address space")?;
+        let stack_top = VirtAddr::new(base + aligned_size);
+
+        let top_page = MemoryRegion::new(stack_top - 0x1000u64, 0x1000, RegionType::Stack);
+        let mapped = allocate_physical_frame()
+            .and_then(|frame| unsafe { self.map_region(&top_page, &[frame.phys_addr]) });
+        if let Err(e) = mapped {
+            self.release_stack(VirtAddr::new(base))?;
+            return Err(e);
+        }
+
+        Ok(stack_top)
+    }
+
+    /// Remove a thread stack created by `allocate_thread_stack`
+    ///
+    /// Pages that were touched are unmapped and their frames returned to
+    /// the heap.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - Lowest address of the stack
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - Stack removed
+    /// * `Err(&str)` - No stack starts at `base`
+    pub fn release_stack(&mut self, base: VirtAddr) -> Result<(), &'static str> {
+        self.release_region_at(base, RegionType::Stack).ok_or("No stack at this address")
+    }
+
+    /// Give a new thread a TLS block, copied from the program's template
+    ///
+    /// The block is laid out by `TlsTemplate::layout` at a randomized
+    /// address and mapped eagerly: the image's initialized bytes are copied
+    /// from the loaded program, the rest is zero, and the thread control
+    /// block's first word points to itself.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some((base, thread_pointer)))` - The block, and the value for
+    ///   the thread's FS base
+    /// * `Ok(None)` - The program has no thread-local storage
+    /// * `Err(&str)` - No free spot, or no memory for the block
+    pub fn allocate_tls(&mut self) -> Result<Option<(VirtAddr, u64)>, &'static str> {
+        let Some(template) = self.tls_template else {
+            return Ok(None);
+        };
+        let layout = template.layout();
+        let aligned_size = (layout.size + 0xFFF) & !0xFFF;
+
+        let mut image = alloc::vec![0u8; template.filesz as usize];
+        self.read_mapped(VirtAddr::new(template.vaddr), &mut image)?;
+
+        let base = (0..MMAP_PLACEMENT_ATTEMPTS)
+            .map(|_| crate::mana_pool::aslr::randomize_mmap_base(aligned_size))
+            .find(|&base| {
+                let region = MemoryRegion::new(VirtAddr::new(base), aligned_size, RegionType::Tls);
+                self.add_region(region).is_ok()
+            })
+            .ok_or("No room for TLS block in address space")?;
+        let base = VirtAddr::new(base);
+        let thread_pointer = base.as_u64() + layout.thread_pointer;
+
+        let populated = self.populate_tls(base, aligned_size, &image, layout.data, thread_pointer);
+        if let Err(e) = populated {
+            self.release_tls(base)?;
+            return Err(e);
+        }
+
+        Ok(Some((base, thread_pointer)))
+    }
+
+    /// Map a new TLS block's pages and fill in its image and TCB
+    fn populate_tls(
+        &mut self,
+        base: VirtAddr,
+        size: u64,
+        image: &[u8],
+        data_offset: u64,
+        thread_pointer: u64,
+    ) -> Result<(), &'static str> {
+        for page in (base.as_u64()..base.as_u64() + size).step_by(0x1000) {
+            let frame = allocate_physical_frame()?;
+            let page_region = MemoryRegion::new(VirtAddr::new(page), 0x1000, RegionType::Tls);
+            unsafe { self.map_region(&page_region, &[frame.phys_addr])? };
+        }
+
+        self.write_mapped(base + data_offset, image)?;
+        self.write_mapped(VirtAddr::new(thread_pointer), &thread_pointer.to_le_bytes())
+    }
+
+    /// Remove a TLS block created by `allocate_tls`
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - Lowest address of the block
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - Block removed
+    /// * `Err(&str)` - No TLS block starts at `base`
+    pub fn release_tls(&mut self, base: VirtAddr) -> Result<(), &'static str> {
+        self.release_region_at(base, RegionType::Tls).ok_or("No TLS block at this address")
+    }
+
+    /// Remove the region of `region_type` starting at `base`, freeing its pages
+    fn release_region_at(&mut self, base: VirtAddr, region_type: RegionType) -> Option<()> {
+        let index = self.regions
+            .iter()
+            .position(|r| r.region_type == region_type && r.start == base)?;
+
+        let region = self.regions.remove(index);
+        self.release_pages(&region);
+        Some(())
+    }
+
+    /// Unmap the touched pages of a removed region and free their frames
+    fn release_pages(&self, region: &MemoryRegion) {
+        for page in (region.start.as_u64()..region.end().as_u64()).step_by(0x1000) {
+            let unmapped = unsafe {
+                crate::mana_pool::page_tables::unmap_user_page(self.pml4_phys.as_u64(), page)
+            };
+
+            if let Some(phys_addr) = unmapped {
+                // Every CPU must forget the page before its frame is reused
+                crate::attunement::smp::shootdown_page(page);
+                unsafe { free_physical_frame(PhysAddr::new(phys_addr)) };
+            }
+        }
+    }
+
+    /// Map a shared memory object at a randomized address
+    ///
+    /// The object's frames are physically contiguous and are mapped eagerly;
+    /// they stay owned by the Mana Pool object, which `capability` keeps
+    /// alive until the region is removed with `unmap_shared`.
+    ///
+    /// # Arguments
+    ///
+    /// * `capability` - The Vessel's capability to the object
+    /// * `phys_start` - Physical address of the object's first frame
+    /// * `size` - Size in bytes (a whole number of pages)
+    /// * `writable` - Map the pages writable (otherwise read-only)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(VirtAddr)` - Base of the new mapping
+    /// * `Err(&str)` - No free spot, or the page tables could not be built
+    pub fn map_shared(
+        &mut self,
+        capability: Capability,
+        phys_start: PhysAddr,
+        size: u64,
+        writable: bool,
+    ) -> Result<VirtAddr, &'static str> {
+        let mut flags = PageTableFlags::PRESENT | PageTableFlags::USER_ACCESSIBLE | PageTableFlags::NO_EXECUTE;
+        if writable {
+            flags |= PageTableFlags::WRITABLE;
+        }
+
+        let base = (0..MMAP_PLACEMENT_ATTEMPTS)
+            .map(|_| crate::mana_pool::aslr::randomize_mmap_base(size))
+            .find(|&base| {
+                let mut region = MemoryRegion::new(VirtAddr::new(base), size, RegionType::Shared);
+                region.flags = flags;
+                region.object = Some(capability);
+                self.add_region(region).is_ok()
+            })
+            .ok_or("No room for mapping in address space")?;
+
+        for offset in (0..size).step_by(0x1000) {
+            let mapped = unsafe {
+                crate::mana_pool::page_tables::map_user_page(
+                    self.pml4_phys.as_u64(),
+                    base + offset,
+                    phys_start.as_u64() + offset,
+                    flags.bits(),
+                )
+            };
+
+            if let Err(err) = mapped {
+                // Leave the object unmapped and the capability with the caller
+                self.regions.retain(|r| r.start.as_u64() != base);
+                self.unmap_shared_pages(base, offset);
+                return Err(err);
+            }
+        }
+
+        Ok(VirtAddr::new(base))
+    }
+
+    /// Remove a shared mapping created by `map_shared`
+    ///
+    /// The frames belong to the Mana Pool object and are not freed here.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Capability)` - The capability that held the mapping, for the
+    ///   caller to release
+    /// * `Err(&str)` - No shared mapping starts at `start`
+    pub fn unmap_shared(&mut self, start: VirtAddr, size: u64) -> Result<Capability, &'static str> {
+        let index = self.shared_region(start, size).ok_or("No shared mapping at this range")?;
+
+        let region = self.regions.remove(index);
+        self.unmap_shared_pages(region.start.as_u64(), region.size);
+
+        region.object.ok_or("Shared mapping without an object")
+    }
+
+    /// The capability behind a shared mapping, which stays in place
+    ///
+    /// Like `unmap_shared`, `start` and `size` must cover the whole mapping.
+    pub fn shared_object(&self, start: VirtAddr, size: u64) -> Option<Capability> {
+        self.shared_region(start, size).and_then(|index| self.regions[index].object)
+    }
+
+    /// Index of the shared region covering exactly `start..start + size`
+    fn shared_region(&self, start: VirtAddr, size: u64) -> Option<usize> {
+        let aligned_size = size.checked_next_multiple_of(0x1000)?;
+
+        self.regions.iter().position(|r| {
+            r.region_type == RegionType::Shared
+                && r.start == start
+                && r.size == aligned_size
+        })
+    }
+
+    /// Clear the page table entries of a shared range, keeping the frames
+    fn unmap_shared_pages(&mut self, start: u64, size: u64) {
+        for page in (start..start + size).step_by(0x1000) {
+            let unmapped = unsafe {
+                crate::mana_pool::page_tables::unmap_user_page(self.pml4_phys.as_u64(), page)
+            };
+            if unmapped.is_some() {
+                crate::attunement::smp::shootdown_page(page);
+            }
+        }
+    }
+
+    /// Map the loadable segments of an ELF image and copy in their contents
+    ///
+    /// Segments that share a page are merged into one region. Every page is
+    /// allocated and mapped up front, so the image can be relocated with
+    /// `write_mapped` straight away.
+    ///
+    /// # Arguments
+    ///
+    /// * `segments` - The image's segments, at the addresses to load them at
+    /// * `elf_data` - The ELF file holding the segments' contents
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - The image is mapped
+    /// * `Err(&str)` - A region overlaps another, or memory ran out
+    pub fn map_elf_image(
+        &mut self,
+        segments: &[LoadedSegment],
+        elf_data: &[u8],
+    ) -> Result<(), &'static str> {
+        // Group segments by page-aligned regions to handle overlaps
+        let merged_regions = merge_overlapping_segments(segments)?;
+
+        crate::serial_println!("[USER_SPACE] Merged {} segments into {} regions",
+            segments.len(), merged_regions.len());
+
+        // Map each merged region
+        for merged in &merged_regions {
+            crate::serial_println!(
+                "[USER_SPACE]   Mapping merged region: vaddr={:#x} size={:#x} ({} segments)",
+                merged.page_aligned_start,
+                merged.aligned_size,
+                merged.contributing_segments.len()
+            );
+
+            // Create memory region
+            let region = MemoryRegion::new(
+                VirtAddr::new(merged.page_aligned_start),
+                merged.aligned_size,
+                merged.region_type,
+            );
+
+            // Add to address space
+            self.add_region(region.clone())?;
+
+            // Allocate physical frames for this region
+            let num_pages = merged.aligned_size / 0x1000;
+            let mut allocated_frames = alloc::vec::Vec::with_capacity(num_pages as usize);
+            for _ in 0..num_pages {
+                let frame = allocate_physical_frame()?;
+                allocated_frames.push(frame);
+            }
+
+            // Extract physical addresses for mapping
+            let phys_addrs: alloc::vec::Vec<PhysAddr> = allocated_frames
+                .iter()
+                .map(|f| f.phys_addr)
+                .collect();
+
+            // Map the region with allocated frames
+            unsafe {
+                self.map_region(&region, &phys_addrs)?;
+            }
+
+            // Copy data from all contributing segments
+            for seg_info in &merged.contributing_segments {
+                if seg_info.segment.filesz > 0 {
+                    crate::serial_println!(
+                        "[USER_SPACE]   Copying segment data: vaddr={:#x} filesz={:#x}",
+                        seg_info.segment.vaddr,
+                        seg_info.segment.filesz
+                    );
+
+                    let file_start = seg_info.segment.file_offset as usize;
+                    let file_end = file_start + seg_info.segment.filesz as usize;
+
+                    if file_end <= elf_data.len() {
+                        let segment_data = &elf_data[file_start..file_end];
+
+                        // Calculate offset from the start of the merged region
+                        let offset_from_region_start = seg_info.segment.vaddr - merged.page_aligned_start;
+
+                        crate::serial_println!(
+                            "[USER_SPACE]   Offset from region start: {:#x}",
+                            offset_from_region_start
+                        );
+
+                        unsafe {
+                            copy_to_frames_with_offset(&allocated_frames, segment_data, offset_from_region_start);
+                        }
+                        crate::serial_println!("[USER_SPACE]   ✓ Segment data copied");
+                    } else {
+                        crate::serial_println!("[USER_SPACE]   WARNING: Segment data out of bounds");
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Free the whole address space: every frame it owns, then its page tables
+    ///
+    /// Frames of shared regions belong to their Mana Pool objects and are
+    /// only unmapped. The address space is left empty, with no PML4.
+    ///
+    /// # Returns
+    ///
+    /// The capabilities that kept shared objects mapped, for the caller to
+    /// release
+    ///
+    /// # Safety
+    ///
+    /// No thread may run in this address space again.
+    pub unsafe fn teardown(&mut self) -> Vec<Capability> {
+        let pml4 = self.pml4_phys.as_u64();
+        let regions = core::mem::take(&mut self.regions);
+        let shared: Vec<Capability> = regions.iter().filter_map(|r| r.object).collect();
+        if pml4 == 0 {
+            return shared;
+        }
+
+        // Another processor may still have the page table loaded, running a
+        // kernel thread on it; until none does, nothing under it can be freed
+        if !crate::attunement::smp::retire_page_table(pml4) {
+            crate::serial_println!("[USER_SPACE] PML4 @ {:#x} may still be in use; leaking it", pml4);
+            return shared;
+        }
+
+        for region in &regions {
+            for page in (region.start.as_u64()..region.end().as_u64()).step_by(0x1000) {
+                let unmapped = crate::mana_pool::page_tables::unmap_user_page(pml4, page);
+                if let Some(phys_addr) = unmapped {
+                    if region.region_type != RegionType::Shared {
+                        free_physical_frame(PhysAddr::new(phys_addr));
+                    }
+                }
+            }
+        }
+
+        crate::mana_pool::page_tables::free_user_page_tables(pml4);
+        self.pml4_phys = PhysAddr::new(0);
+        shared
+    }
+
+    /// Resolve a page fault at `addr` inside this address space
+    ///
+    /// Faults on not-yet-present pages of demand-paged regions (stack, heap)
+    /// are satisfied by mapping a freshly zeroed frame. Every other fault is
+    /// a genuine access violation and is reported back to the caller.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The faulting address (CR2)
+    /// * `error` - The error code pushed by the CPU
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - The page is now mapped; the access can be retried
+    /// * `Err(UserFault)` - Why the access is not allowed
+    ///
+    /// # Safety
+    ///
+    /// Must be called with this address space's PML4 active (or at least not
+    /// concurrently modified), from the page fault path.
+    pub unsafe fn handle_page_fault(
+        &mut self,
+        addr: VirtAddr,
+        error: PageFaultErrorCode,
+    ) -> Result<(), UserFault> {
+        if error.contains(PageFaultErrorCode::MALFORMED_TABLE) {
+            return Err(UserFault::MalformedPageTable);
+        }
+
+        let region = self.find_region(addr).ok_or(UserFault::Unmapped)?;
+
+        if error.contains(PageFaultErrorCode::CAUSED_BY_WRITE)
+            && !region.flags.contains(PageTableFlags::WRITABLE)
+        {
+            return Err(UserFault::WriteToReadOnly);
+        }
+        if error.contains(PageFaultErrorCode::INSTRUCTION_FETCH)
+            && region.flags.contains(PageTableFlags::NO_EXECUTE)
+        {
+            return Err(UserFault::ExecuteFromNoExecute);
+        }
+        if error.contains(PageFaultErrorCode::PROTECTION_VIOLATION) {
+            return Err(UserFault::ProtectionViolation);
+        }
+        if !region.region_type.is_demand_paged() {
+            return Err(UserFault::NotDemandPaged);
+        }
+
+        let page = addr.align_down(0x1000u64);
+        let flags = region.flags.bits();
+        let frame = allocate_physical_frame().map_err(|_| UserFault::OutOfMemory)?;
+
+        if let Err(reason) = crate::mana_pool::page_tables::map_user_page(
+            self.pml4_phys.as_u64(),
+            page.as_u64(),
+            frame.phys_addr.as_u64(),
+            flags,
+        ) {
+            free_physical_frame(frame.phys_addr);
+            return Err(UserFault::MapFailed(reason));
+        }
+
+        x86_64::instructions::tlb::flush(page);
+        Ok(())
+    }
 }
 
 /// Information about a segment contributing to a merged region
@@ -348,11 +1028,13 @@ fn merge_overlapping_segments<'a>(
 /// Create a user address space from a loaded ELF file
 ///
 /// This function creates page table mappings for all loadable segments
-/// in the ELF file and sets up the initial user stack.
+/// in the ELF file, links in the Groves it needs (see `grove_linker`) and
+/// sets up the initial user stack.
 ///
 /// # Arguments
 ///
 /// * `elf_data` - The raw ELF file data
+/// * `groves` - The program's Groves, gathered by `grove_linker::gather_groves`
 ///
 /// # Returns
 ///
@@ -360,6 +1042,7 @@ fn merge_overlapping_segments<'a>(
 /// * `Err(&str)` - Load error
 pub fn create_address_space_from_elf(
     elf_data: &[u8],
+    groves: &[Grove],
 ) -> Result<(UserAddressSpace, u64), &'static str> {
     use crate::loom_of_fate::elf_loader::{load_elf, ElfError};
 
@@ -376,6 +1059,7 @@ pub fn create_address_space_from_elf(
         ElfError::InvalidAlignment => "Invalid alignment",
         ElfError::SegmentOverlapsKernel => "Segment overlaps kernel",
         ElfError::NoLoadableSegments => "No loadable segments",
+        ElfError::InvalidTls => "Invalid TLS segment",
     })?;
 
     crate::serial_println!("[USER_SPACE] Creating address space from ELF");
@@ -385,84 +1069,18 @@ pub fn create_address_space_from_elf(
     // Create new address space
     let mut address_space = UserAddressSpace::new()?;
 
-    // Group segments by page-aligned regions to handle overlaps
-    let merged_regions = merge_overlapping_segments(&loaded_elf.segments)?;
-
-    crate::serial_println!("[USER_SPACE] Merged {} segments into {} regions",
-        loaded_elf.segments.len(), merged_regions.len());
-
-    // Map each merged region
-    for merged in &merged_regions {
-        crate::serial_println!(
-            "[USER_SPACE]   Mapping merged region: vaddr={:#x} size={:#x} ({} segments)",
-            merged.page_aligned_start,
-            merged.aligned_size,
-            merged.contributing_segments.len()
-        );
-
-        // Create memory region
-        let region = MemoryRegion::new(
-            VirtAddr::new(merged.page_aligned_start),
-            merged.aligned_size,
-            merged.region_type,
-        );
-
-        // Add to address space
-        address_space.add_region(region.clone())?;
-
-        // Allocate physical frames for this region
-        let num_pages = merged.aligned_size / 0x1000;
-        let mut allocated_frames = alloc::vec::Vec::with_capacity(num_pages as usize);
-        for _ in 0..num_pages {
-            let frame = allocate_physical_frame()?;
-            allocated_frames.push(frame);
-        }
-
-        // Extract physical addresses for mapping
-        let phys_addrs: alloc::vec::Vec<PhysAddr> = allocated_frames
-            .iter()
-            .map(|f| f.phys_addr)
-            .collect();
-
-        // Map the region with allocated frames
-        unsafe {
-            address_space.map_region(&region, &phys_addrs)?;
-        }
-
-        // Copy data from all contributing segments
-        for seg_info in &merged.contributing_segments {
-            if seg_info.segment.filesz > 0 {
-                crate::serial_println!(
-                    "[USER_SPACE]   Copying segment data: vaddr={:#x} filesz={:#x}",
-                    seg_info.segment.vaddr,
-                    seg_info.segment.filesz
-                );
-
-                let file_start = seg_info.segment.file_offset as usize;
-                let file_end = file_start + seg_info.segment.filesz as usize;
-
-                if file_end <= elf_data.len() {
-                    let segment_data = &elf_data[file_start..file_end];
-
-                    // Calculate offset from the start of the merged region
-                    let offset_from_region_start = seg_info.segment.vaddr - merged.page_aligned_start;
-
-                    crate::serial_println!(
-                        "[USER_SPACE]   Offset from region start: {:#x}",
-                        offset_from_region_start
-                    );
+    address_space.map_elf_image(&loaded_elf.segments, elf_data)?;
 
-                    unsafe {
-                        copy_to_frames_with_offset(&allocated_frames, segment_data, offset_from_region_start);
-                    }
-                    crate::serial_println!("[USER_SPACE]   ✓ Segment data copied");
-                } else {
-                    crate::serial_println!("[USER_SPACE]   WARNING: Segment data out of bounds");
-                }
-            }
-        }
+    // Bring in the Groves and apply every relocation
+    if let Err(e) = grove_linker::link(&mut address_space, &loaded_elf, elf_data, groves) {
+        crate::serial_println!("[USER_SPACE] ✗ Linking failed: {}", e);
+        unsafe { address_space.teardown(); }
+        return Err(e.description());
     }
 
+    // Threads copy their TLS from the loaded image
+    address_space.tls_template = loaded_elf.tls;
+
     // Allocate initial user stack
     let stack_top = address_space.allocate_stack(USER_STACK_SIZE)?;
     crate::serial_println!("[USER_SPACE]   User stack: {:#x}", stack_top.as_u64());
@@ -528,6 +1146,21 @@ fn allocate_physical_frame() -> Result<AllocatedFrame, &'static str> {
     })
 }
 
+/// Return a frame obtained from `allocate_physical_frame` to the heap
+///
+/// # Safety
+/// The frame must have come from `allocate_physical_frame` and must no
+/// longer be mapped anywhere.
+unsafe fn free_physical_frame(phys_addr: PhysAddr) {
+    use alloc::alloc::{dealloc, Layout};
+
+    // Frames live in the kernel heap, which is mapped at KERNEL_BASE + phys
+    const KERNEL_BASE: u64 = 0xFFFFFFFF80000000;
+    let ptr = (phys_addr.as_u64() + KERNEL_BASE) as *mut u8;
+
+    dealloc(ptr, Layout::from_size_align_unchecked(0x1000, 0x1000));
+}
+
 /// Copy data into allocated physical frames with an optional offset in the first frame
 ///
 /// # Arguments
@@ -576,3 +1209,53 @@ unsafe fn copy_to_frames_with_offset(frames: &[AllocatedFrame], data: &[u8], pag
     crate::serial_println!("[COPY] ✓ All frames copied");
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mana_pool::{CapabilityRights, ObjectHandle};
+
+    const SHARED_BASE: u64 = 0x1000_0000;
+
+    /// An address space without page tables, for its region bookkeeping
+    fn space_with_shared_region(region_type: RegionType) -> UserAddressSpace {
+        let mut space = UserAddressSpace {
+            pml4_phys: PhysAddr::new(0),
+            regions: Vec::new(),
+            heap_break: VirtAddr::new(0x0000_0000_0040_0000),
+            next_stack: VirtAddr::new(USER_STACK_TOP),
+            tls_template: None,
+        };
+        let mut region = MemoryRegion::new(VirtAddr::new(SHARED_BASE), 0x2000, region_type);
+        region.object = Some(Capability::new(ObjectHandle(7), CapabilityRights::full()));
+        space.add_region(region).unwrap();
+        space
+    }
+
+    #[test]
+    fn test_shared_object_needs_the_whole_mapping() {
+        let mut space = space_with_shared_region(RegionType::Shared);
+        let start = VirtAddr::new(SHARED_BASE);
+
+        // The size is rounded up to whole pages, as for unmapping
+        let object = space.shared_object(start, 0x1800).unwrap();
+        assert_eq!(object.handle, ObjectHandle(7));
+        assert!(space.shared_object(start, 0x1000).is_none());
+        assert!(space.shared_object(start + 0x1000u64, 0x1000).is_none());
+        assert!(space.shared_object(start, u64::MAX).is_none());
+
+        // A partial unmap is refused and leaves the mapping in place
+        assert!(space.unmap_shared(start, 0x1000).is_err());
+        assert!(space.shared_object(start, 0x2000).is_some());
+    }
+
+    #[test]
+    fn test_shared_object_ignores_other_regions() {
+        let mut space = space_with_shared_region(RegionType::Anonymous);
+        let start = VirtAddr::new(SHARED_BASE);
+
+        assert!(space.shared_object(start, 0x2000).is_none());
+        assert!(space.unmap_shared(start, 0x2000).is_err());
+        assert_eq!(space.regions.len(), 1);
+    }
+}
@@ -1,6 +1,7 @@
 //! Channels - The conduits through which messages flow
 
 use super::message::Message;
+use crate::loom_of_fate::ThreadId;
 use alloc::collections::VecDeque;
 
 /// Maximum messages in a channel before backpressure
@@ -10,46 +11,15 @@ const CHANNEL_CAPACITY: usize = 256;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct ChannelId(pub u64);
 
-/// A capability granting access to a channel
-/// This is what processes actually hold - not raw channel IDs
-#[derive(Debug, Clone, Copy)]
-pub struct ChannelCapability {
-    pub channel_id: ChannelId,
-    pub can_send: bool,
-    pub can_receive: bool,
-}
-
-impl ChannelCapability {
-    pub fn new_sender(channel_id: ChannelId) -> Self {
-        Self {
-            channel_id,
-            can_send: true,
-            can_receive: false,
-        }
-    }
-
-    pub fn new_receiver(channel_id: ChannelId) -> Self {
-        Self {
-            channel_id,
-            can_send: false,
-            can_receive: true,
-        }
-    }
-
-    pub fn new_bidirectional(channel_id: ChannelId) -> Self {
-        Self {
-            channel_id,
-            can_send: true,
-            can_receive: true,
-        }
-    }
-}
-
 /// A channel is a queue of messages with priority-based ordering
 pub struct Channel {
     id: ChannelId,
     messages: VecDeque<Message>,
+    /// Threads parked in a blocking receive, oldest first
+    waiters: VecDeque<ThreadId>,
     closed: bool,
+    /// A reply channel: accepts exactly one message, then closes
+    one_shot: bool,
 }
 
 impl Channel {
@@ -57,7 +27,20 @@ impl Channel {
         Self {
             id,
             messages: VecDeque::with_capacity(CHANNEL_CAPACITY),
+            waiters: VecDeque::new(),
+            closed: false,
+            one_shot: false,
+        }
+    }
+
+    /// Create a one-shot reply channel for a synchronous call
+    pub fn new_reply(id: ChannelId) -> Self {
+        Self {
+            id,
+            messages: VecDeque::with_capacity(1),
+            waiters: VecDeque::new(),
             closed: false,
+            one_shot: true,
         }
     }
 
@@ -66,7 +49,10 @@ impl Channel {
     }
 
     /// Send a message to this channel
-    pub fn send(&mut self, message: Message) -> Result<(), ChannelError> {
+    ///
+    /// # Returns
+    /// The longest-waiting receiver, if any, which the caller must wake
+    pub fn send(&mut self, message: Message) -> Result<Option<ThreadId>, ChannelError> {
         if self.closed {
             return Err(ChannelError::Closed);
         }
@@ -85,7 +71,12 @@ impl Channel {
 
         self.messages.insert(insert_pos, message);
 
-        Ok(())
+        // A reply capability is spent by its first use
+        if self.one_shot {
+            self.closed = true;
+        }
+
+        Ok(self.waiters.pop_front())
     }
 
     /// Try to receive a message (non-blocking)
@@ -107,14 +98,36 @@ impl Channel {
         self.messages.len()
     }
 
+    /// Register a thread to be woken by the next send
+    pub fn add_waiter(&mut self, thread_id: ThreadId) {
+        if !self.waiters.contains(&thread_id) {
+            self.waiters.push_back(thread_id);
+        }
+    }
+
+    /// Forget a waiter that gave up (e.g. its receive timed out)
+    pub fn remove_waiter(&mut self, thread_id: ThreadId) {
+        self.waiters.retain(|&waiter| waiter != thread_id);
+    }
+
     /// Close the channel
-    pub fn close(&mut self) {
+    ///
+    /// # Returns
+    /// Every parked receiver, which the caller must wake so they can see
+    /// the channel is closed
+    pub fn close(&mut self) -> VecDeque<ThreadId> {
         self.closed = true;
+        core::mem::take(&mut self.waiters)
     }
 
     pub fn is_closed(&self) -> bool {
         self.closed
     }
+
+    /// Check if this is a one-shot reply channel
+    pub fn is_reply(&self) -> bool {
+        self.one_shot
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -123,3 +136,99 @@ pub enum ChannelError {
     Closed,
     NotFound,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nexus::message::{MessagePriority, MessageType};
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    /// A message whose payload tags it with `tag`
+    fn tagged(tag: u8, priority: MessagePriority) -> Message {
+        Message::new(MessageType::Data { payload: vec![tag] }, priority)
+    }
+
+    fn tag_of(message: &Message) -> u8 {
+        match &message.message_type {
+            MessageType::Data { payload } => payload[0],
+            _ => panic!("not a tagged message"),
+        }
+    }
+
+    #[test]
+    fn test_priority_ordering() {
+        let mut channel = Channel::new(ChannelId(1));
+        channel.send(tagged(1, MessagePriority::Normal)).unwrap();
+        channel.send(tagged(2, MessagePriority::Low)).unwrap();
+        channel.send(tagged(3, MessagePriority::Critical)).unwrap();
+        channel.send(tagged(4, MessagePriority::Normal)).unwrap();
+        channel.send(tagged(5, MessagePriority::High)).unwrap();
+
+        // Highest priority first; equal priorities keep their order
+        let mut order = Vec::new();
+        while let Some(message) = channel.try_receive().unwrap() {
+            order.push(tag_of(&message));
+        }
+        assert_eq!(order, [3, 5, 1, 4, 2]);
+    }
+
+    #[test]
+    fn test_full_queue() {
+        let mut channel = Channel::new(ChannelId(1));
+        for _ in 0..CHANNEL_CAPACITY {
+            channel.send(tagged(0, MessagePriority::Low)).unwrap();
+        }
+
+        // Even a critical message waits for room
+        assert_eq!(channel.send(tagged(1, MessagePriority::Critical)).err(), Some(ChannelError::Full));
+        assert_eq!(channel.message_count(), CHANNEL_CAPACITY);
+
+        channel.try_receive().unwrap();
+        channel.send(tagged(1, MessagePriority::Critical)).unwrap();
+        assert_eq!(tag_of(&channel.try_receive().unwrap().unwrap()), 1);
+    }
+
+    #[test]
+    fn test_waiters_are_woken_oldest_first() {
+        let mut channel = Channel::new(ChannelId(1));
+        channel.add_waiter(ThreadId(1));
+        channel.add_waiter(ThreadId(2));
+        channel.add_waiter(ThreadId(1));
+        channel.add_waiter(ThreadId(3));
+
+        // Each send wakes one waiter; a repeated registration counts once
+        assert_eq!(channel.send(tagged(0, MessagePriority::Normal)), Ok(Some(ThreadId(1))));
+        channel.remove_waiter(ThreadId(2));
+        assert_eq!(channel.send(tagged(0, MessagePriority::Normal)), Ok(Some(ThreadId(3))));
+        assert_eq!(channel.send(tagged(0, MessagePriority::Normal)), Ok(None));
+    }
+
+    #[test]
+    fn test_close_wakes_everyone_and_drains() {
+        let mut channel = Channel::new(ChannelId(1));
+        channel.send(tagged(7, MessagePriority::Normal)).unwrap();
+        channel.add_waiter(ThreadId(1));
+        channel.add_waiter(ThreadId(2));
+
+        assert_eq!(channel.close(), [ThreadId(1), ThreadId(2)]);
+        assert_eq!(channel.send(tagged(8, MessagePriority::Normal)), Err(ChannelError::Closed));
+
+        // Queued messages can still be received, then the channel reports closed
+        assert_eq!(tag_of(&channel.try_receive().unwrap().unwrap()), 7);
+        assert_eq!(channel.try_receive().err(), Some(ChannelError::Closed));
+    }
+
+    #[test]
+    fn test_reply_channel_takes_one_message() {
+        let mut channel = Channel::new_reply(ChannelId(1));
+        channel.add_waiter(ThreadId(1));
+
+        assert_eq!(channel.send(tagged(1, MessagePriority::Normal)), Ok(Some(ThreadId(1))));
+        assert!(channel.is_closed());
+        assert_eq!(channel.send(tagged(2, MessagePriority::Critical)), Err(ChannelError::Closed));
+
+        assert_eq!(tag_of(&channel.try_receive().unwrap().unwrap()), 1);
+        assert_eq!(channel.try_receive().err(), Some(ChannelError::Closed));
+    }
+}
@@ -1,9 +1,14 @@
 //! Message definitions - The Astral Packets of the Nexus
 
+use super::channel::ChannelId;
+use crate::loom_of_fate::VesselId;
+use crate::mana_pool::{Capability, CapabilityId};
 use alloc::vec::Vec;
 
+pub use covenant::ipc::MAX_MESSAGE_CAPABILITIES;
+
 /// A message is the fundamental unit of communication in AethelOS
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct Message {
     /// The type and payload of the message
     pub message_type: MessageType,
@@ -12,7 +17,13 @@ pub struct Message {
     pub priority: MessagePriority,
 
     /// The sender's capability (for replies)
-    pub reply_to: Option<u64>,
+    ///
+    /// Set by `nexus::call` to a single-use reply capability, which the
+    /// receiver spends with `nexus::reply`.
+    pub reply_to: Option<ReplyCapability>,
+
+    /// Capabilities passed along with the message, if any
+    pub transfer: Option<CapabilityTransfer>,
 }
 
 impl Message {
@@ -21,13 +32,51 @@ impl Message {
             message_type,
             priority,
             reply_to: None,
+            transfer: None,
         }
     }
 
-    pub fn with_reply(mut self, reply_capability: u64) -> Self {
+    pub fn with_reply(mut self, reply_capability: ReplyCapability) -> Self {
         self.reply_to = Some(reply_capability);
         self
     }
+
+    pub fn with_capabilities(mut self, transfer: CapabilityTransfer) -> Self {
+        self.transfer = Some(transfer);
+        self
+    }
+}
+
+/// The right to answer one call
+///
+/// Only the Nexus mints these, one per call. It can be neither copied nor
+/// built from a number, and `nexus::reply` consumes it, so the only thread
+/// able to answer a call is the one that received its message.
+#[derive(Debug)]
+pub struct ReplyCapability {
+    channel: ChannelId,
+}
+
+impl ReplyCapability {
+    pub(super) fn new(channel: ChannelId) -> Self {
+        Self { channel }
+    }
+
+    /// The one-shot channel the caller is waiting on
+    pub(super) fn channel(&self) -> ChannelId {
+        self.channel
+    }
+}
+
+/// Capabilities travelling with a message
+///
+/// Only the sender's IDs travel. When the message is delivered, the
+/// receiver gets capabilities of its own, derived from whatever the sender
+/// still holds at that moment; one revoked in flight is simply dropped.
+#[derive(Debug, Clone)]
+pub struct CapabilityTransfer {
+    pub sender: VesselId,
+    pub capabilities: Vec<CapabilityId>,
 }
 
 /// The type and payload of a message
@@ -55,6 +104,16 @@ pub enum MessageType {
         payload: Vec<u8>,
     },
 
+    /// Zero-copy transfer of a Mana Pool shared memory object
+    ///
+    /// `capability` is the receiver's own capability, already minted by
+    /// the Nexus according to `mode`. When a Vessel receives it, the
+    /// object's pages are mapped into its address space.
+    SharedMemory {
+        capability: Capability,
+        mode: TransferMode,
+    },
+
     /// A query for system information
     Query {
         query_type: QueryType,
@@ -72,6 +131,17 @@ pub enum MessageType {
     },
 }
 
+/// How a shared memory capability changes hands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferMode {
+    /// The receiver gets the sender's rights; both keep access
+    Grant,
+    /// The sender's capability passes to the receiver and is spent
+    Move,
+    /// The receiver gets read-only access; the sender keeps its own
+    Share,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ResourceType {
     Memory,
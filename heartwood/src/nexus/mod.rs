@@ -10,21 +10,42 @@
 //! through intent rather than rigid addressing.
 //!
 //! ## Architecture
-//! - Asynchronous, non-blocking message passing
-//! - Capability-based addressing (no raw process IDs)
+//! - Asynchronous message passing; receivers may poll or block
+//! - Blocked receivers are parked in the Loom of Fate, using no CPU
+//! - Capability-based addressing (no raw process IDs): a Vessel names a
+//!   channel only through an opaque `CapabilityId` sealed into its own
+//!   capability table, carrying send, receive and grant rights
+//! - Capabilities travel inside messages, which is how a Grove is handed
+//!   the channels it serves
+//! - Services are found by name (`world-tree/query`) through the Names
+//! - Notifications carry bare signal bits, raised even from IRQ context;
+//!   `wait_any` watches channels and notifications together
 //! - Priority-aware delivery (harmony-based routing)
-//! - Zero-copy where possible (messages live in the Mana Pool)
+//! - Zero-copy where possible (shared memory objects from the Mana Pool are
+//!   mapped into the receiver instead of copied)
 
 pub mod message;
 pub mod nexus_core;
 pub mod channel;
+pub mod names;
+pub mod notification;
 
-pub use message::{Message, MessageType, MessagePriority};
+pub use message::{
+    CapabilityTransfer, Message, MessageType, MessagePriority, ReplyCapability, TransferMode, MAX_MESSAGE_CAPABILITIES,
+};
 pub use nexus_core::NexusCore;
-pub use channel::{Channel, ChannelId, ChannelCapability};
+pub use channel::{Channel, ChannelId};
+pub use notification::{Notification, NotificationId};
+pub use covenant::ipc::MAX_WAIT_SOURCES;
 
-use crate::mana_pool::InterruptSafeLock;
+use crate::loom_of_fate::{BlockReason, VesselId};
+use crate::mana_pool::{
+    Capability, CapabilityError, CapabilityId, CapabilityRights, CapabilityTable, InterruptSafeLock,
+    ObjectHandle, ObjectManager, ObjectType, SealedCapability,
+};
+use alloc::vec::Vec;
 use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicU64, Ordering};
 
 // Using InterruptSafeLock to prevent deadlocks during preemptive multitasking
 static mut NEXUS: MaybeUninit<InterruptSafeLock<NexusCore>> = MaybeUninit::uninit();
@@ -65,8 +86,135 @@ unsafe fn get_nexus() -> &'static InterruptSafeLock<NexusCore> {
 }
 
 /// Send a message through the Nexus
+///
+/// Wakes the longest-waiting receiver parked on the channel, if any.
 pub fn send(channel: ChannelId, message: Message) -> Result<(), NexusError> {
-    unsafe { get_nexus().lock().send(channel, message) }
+    // The Nexus lock is released before touching the Loom
+    let waiter = unsafe { get_nexus().lock().send(channel, message)? };
+    if let Some(thread_id) = waiter {
+        crate::loom_of_fate::wake(thread_id);
+    }
+    Ok(())
+}
+
+/// Send a message carrying capabilities from the sender's table
+///
+/// Every capability must carry TRANSFER (the grant right); the sender keeps
+/// its own. The receiver gets derived copies when it accepts the message
+/// with [`accept_capabilities`], so revoking the sender's capability later
+/// revokes theirs too.
+///
+/// # Returns
+/// * `Err(NexusError::InvalidCapability)` - More than
+///   `MAX_MESSAGE_CAPABILITIES`, or one the sender does not hold
+/// * `Err(NexusError::PermissionDenied)` - One lacks TRANSFER
+pub fn send_with_capabilities(
+    sender: VesselId,
+    channel: ChannelId,
+    message: Message,
+    capabilities: &[CapabilityId],
+) -> Result<(), NexusError> {
+    if capabilities.len() > MAX_MESSAGE_CAPABILITIES {
+        return Err(NexusError::InvalidCapability);
+    }
+
+    with_capabilities(sender, |table| {
+        capabilities
+            .iter()
+            .try_for_each(|&id| table.check_rights(id, CapabilityRights::TRANSFER))
+    })??;
+
+    let transfer = CapabilityTransfer { sender, capabilities: capabilities.to_vec() };
+    send(channel, message.with_capabilities(transfer))
+}
+
+/// Install the capabilities carried by a message into the receiver's table
+///
+/// Each one is derived from the sender's capability with the same rights.
+/// Any the sender no longer holds (revoked in flight, or the sender has
+/// faded) are dropped, as are any that do not fit in the receiver's table.
+///
+/// # Returns
+/// The receiver's new capability IDs, in message order
+pub fn accept_capabilities(receiver: VesselId, message: &Message) -> Vec<CapabilityId> {
+    let Some(transfer) = &message.transfer else {
+        return Vec::new();
+    };
+
+    crate::loom_of_fate::without_interrupts(|| {
+        let mut harbor = crate::loom_of_fate::get_harbor().lock();
+        let mut accepted = Vec::new();
+
+        for &id in &transfer.capabilities {
+            let derived = harbor
+                .find_vessel(transfer.sender)
+                .and_then(|sender| sender.capabilities().get(id).ok())
+                .filter(|parent| parent.rights.contains(CapabilityRights::TRANSFER))
+                .map(|parent| parent.derive(parent.rights));
+
+            let installed = derived.and_then(|capability| {
+                harbor
+                    .find_vessel_mut(receiver)?
+                    .capabilities_mut()
+                    .insert_derived(capability, id)
+                    .ok()
+            });
+            accepted.extend(installed);
+        }
+        accepted
+    })
+}
+
+/// Send a Mana Pool shared memory object without copying it
+///
+/// The sender's capability must carry TRANSFER rights. The receiver gets a
+/// capability of its own, minted according to `mode`; with
+/// `TransferMode::Move` the sender's capability is spent and must not be
+/// used or released afterwards.
+///
+/// # Returns
+/// * `Err(NexusError::InvalidCapability)` - Not a transferable shared
+///   memory capability
+pub fn send_shared(
+    channel: ChannelId,
+    capability: Capability,
+    mode: TransferMode,
+    priority: MessagePriority,
+) -> Result<(), NexusError> {
+    let granted = mint_transfer(&capability, mode)?;
+    let message = Message::new(MessageType::SharedMemory { capability: granted, mode }, priority);
+
+    let sent = send(channel, message);
+    if sent.is_err() && mode != TransferMode::Move {
+        let _ = crate::mana_pool::release(&granted);
+    }
+    sent
+}
+
+/// Mint the receiver's capability for a shared memory transfer
+fn mint_transfer(capability: &Capability, mode: TransferMode) -> Result<Capability, NexusError> {
+    let mut pool = unsafe { crate::mana_pool::get_mana_pool().lock() };
+    mint_from(&mut pool.object_manager, capability, mode)
+}
+
+/// Mint a transfer capability, taking a new reference for Grant and Share
+fn mint_from(objects: &mut ObjectManager, capability: &Capability, mode: TransferMode) -> Result<Capability, NexusError> {
+    let info = objects.get_object_info(capability).map_err(|_| NexusError::InvalidCapability)?;
+    if info.object_type != ObjectType::SharedMemory || !capability.can_transfer() {
+        return Err(NexusError::InvalidCapability);
+    }
+
+    let minted = match mode {
+        TransferMode::Move => Ok(*capability),
+        TransferMode::Grant => objects.clone_capability(capability),
+        TransferMode::Share => {
+            // Check the attenuation before taking the new reference
+            objects
+                .derive_capability(capability, CapabilityRights::read_only())
+                .and_then(|read_only| objects.clone_capability(capability).map(|_| read_only))
+        }
+    };
+    minted.map_err(|_| NexusError::InvalidCapability)
 }
 
 /// Receive a message from a channel (non-blocking)
@@ -74,9 +222,400 @@ pub fn try_receive(channel: ChannelId) -> Result<Option<Message>, NexusError> {
     unsafe { get_nexus().lock().try_receive(channel) }
 }
 
-/// Create a new bidirectional channel
-pub fn create_channel() -> Result<(ChannelCapability, ChannelCapability), NexusError> {
-    unsafe { get_nexus().lock().create_channel() }
+/// Receive a message from a channel, blocking until one arrives
+///
+/// The calling thread is parked in the channel's wait queue and uses no CPU
+/// until a sender wakes it.
+///
+/// # Arguments
+/// * `channel` - The channel to receive from
+/// * `timeout` - Give up after this many timer ticks (`None` waits forever)
+///
+/// # Returns
+/// * `Ok(message)` - The highest-priority queued message
+/// * `Err(NexusError::Timeout)` - Nothing arrived in time
+/// * `Err(NexusError::ChannelClosed)` - The channel was closed and drained
+pub fn receive(channel: ChannelId, timeout: Option<u64>) -> Result<Message, NexusError> {
+    let deadline = timeout.map(|ticks| crate::attunement::timer::ticks().saturating_add(ticks));
+
+    // Before the Loom is weaving there is nothing to park; just poll
+    let Some(me) = crate::loom_of_fate::current_thread() else {
+        loop {
+            if let Some(message) = try_receive(channel)? {
+                return Ok(message);
+            }
+            if deadline.is_some_and(|deadline| crate::attunement::timer::ticks() >= deadline) {
+                return Err(NexusError::Timeout);
+            }
+            core::hint::spin_loop();
+        }
+    };
+
+    loop {
+        let received = unsafe { get_nexus().lock().receive_or_wait(channel, me)? };
+        if let Some(message) = received {
+            return Ok(message);
+        }
+
+        if deadline.is_some_and(|deadline| crate::attunement::timer::ticks() >= deadline) {
+            unsafe { get_nexus().lock().cancel_wait(channel, me) };
+            return Err(NexusError::Timeout);
+        }
+
+        crate::loom_of_fate::park_current(BlockReason::Channel, deadline);
+    }
+}
+
+/// Send a message and block until the receiver replies
+///
+/// The message carries a fresh single-use reply capability in `reply_to`;
+/// the server answers with [`reply`]. If a server thread is already waiting
+/// on the channel, the caller hands the CPU straight to it instead of
+/// waiting for a full trip through the ready queue.
+///
+/// # Returns
+/// * `Ok(message)` - The server's reply
+/// * `Err(NexusError::ChannelClosed)` - The reply channel was closed unanswered
+pub fn call(channel: ChannelId, message: Message) -> Result<Message, NexusError> {
+    let me = crate::loom_of_fate::current_thread();
+    let (reply_channel, server) = unsafe { get_nexus().lock().call_send(channel, message, me)? };
+
+    if let Some(server) = server {
+        if me.is_some() {
+            crate::loom_of_fate::park_and_hand_off(server);
+        } else {
+            crate::loom_of_fate::wake(server);
+        }
+    }
+
+    // The reply channel is revoked whether or not the reply came
+    let reply = receive(reply_channel, None);
+    unsafe { get_nexus().lock().remove_channel(reply_channel) };
+    reply
+}
+
+/// Answer a call, spending its reply capability
+///
+/// # Arguments
+/// * `reply_capability` - The `reply_to` taken from the message being answered
+/// * `message` - The response
+///
+/// # Returns
+/// * `Err(NexusError::InvalidCapability)` - The caller is no longer waiting
+pub fn reply(reply_capability: ReplyCapability, message: Message) -> Result<(), NexusError> {
+    let caller = unsafe { get_nexus().lock().reply(reply_capability, message)? };
+    if let Some(thread_id) = caller {
+        crate::loom_of_fate::wake(thread_id);
+    }
+    Ok(())
+}
+
+/// Create a new bidirectional channel for a Vessel
+///
+/// Two endpoint capabilities (send, receive and grant) are sealed into the
+/// Vessel's capability table: one to keep, one to hand to a peer. Only
+/// their opaque IDs ever leave the kernel.
+pub fn create_channel(vessel: VesselId) -> Result<(CapabilityId, CapabilityId), NexusError> {
+    let channel = unsafe { get_nexus().lock().create_channel()? };
+
+    // The Nexus lock is released before the Harbor's is taken
+    let installed = with_capabilities(vessel, |table| {
+        let endpoint = || SealedCapability::new(ObjectHandle(channel.0), CapabilityRights::channel_endpoint());
+        let first = table.insert(endpoint())?;
+        let second = table.insert(endpoint());
+        if second.is_err() {
+            table.remove(first);
+        }
+        second.map(|second| (first, second))
+    })
+    .and_then(|installed| installed.map_err(NexusError::from));
+
+    if installed.is_err() {
+        unsafe { get_nexus().lock().remove_channel(channel) };
+    }
+    installed
+}
+
+/// Find the channel behind one of a Vessel's capabilities
+///
+/// # Returns
+/// * `Err(NexusError::InvalidCapability)` - The Vessel holds no such
+///   capability, or its seal is broken
+/// * `Err(NexusError::PermissionDenied)` - The capability lacks `required`
+pub fn resolve(vessel: VesselId, capability: CapabilityId, required: CapabilityRights) -> Result<ChannelId, NexusError> {
+    with_capabilities(vessel, |table| table.get_handle(capability, required))?
+        .map(|handle| ChannelId(handle.0))
+        .map_err(NexusError::from)
+}
+
+/// Derive a capability to the same channel with fewer rights
+///
+/// The new capability lands in the same Vessel's table and is revoked
+/// along with its parent.
+///
+/// # Returns
+/// * `Err(NexusError::PermissionDenied)` - `rights` is not a subset of the
+///   parent's rights
+pub fn derive(vessel: VesselId, capability: CapabilityId, rights: CapabilityRights) -> Result<CapabilityId, NexusError> {
+    with_capabilities(vessel, |table| {
+        // Refuse amplification here; the table treats it as a kernel bug
+        if !table.get(capability)?.rights.contains(rights) {
+            return Err(CapabilityError::PermissionDenied);
+        }
+        table.derive(capability, rights)
+    })?
+    .map_err(NexusError::from)
+}
+
+/// Revoke a capability and everything derived from it, in every Vessel
+///
+/// The channel itself stays open for holders of unrelated capabilities.
+pub fn revoke(vessel: VesselId, capability: CapabilityId) -> Result<(), NexusError> {
+    crate::loom_of_fate::without_interrupts(|| {
+        let mut harbor = crate::loom_of_fate::get_harbor().lock();
+        let mut revoked = harbor
+            .find_vessel_mut(vessel)
+            .ok_or(NexusError::InvalidCapability)?
+            .capabilities_mut()
+            .revoke(capability)?;
+
+        // Derived capabilities may have been passed to other Vessels (and
+        // on again from there), so sweep until a pass finds nothing new
+        let beacons = harbor.all_beacons();
+        let mut swept = 0;
+        while swept < revoked.len() {
+            let frontier = revoked[swept..].to_vec();
+            swept = revoked.len();
+
+            for beacon in &beacons {
+                if let Some(holder) = harbor.find_vessel_mut(*beacon) {
+                    revoked.extend(holder.capabilities_mut().revoke_descendants(&frontier));
+                }
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Run `f` on a Vessel's capability table under the Harbor lock
+fn with_capabilities<R>(vessel: VesselId, f: impl FnOnce(&mut CapabilityTable) -> R) -> Result<R, NexusError> {
+    crate::loom_of_fate::without_interrupts(|| {
+        let mut harbor = crate::loom_of_fate::get_harbor().lock();
+        harbor
+            .find_vessel_mut(vessel)
+            .map(|vessel| f(vessel.capabilities_mut()))
+            .ok_or(NexusError::InvalidCapability)
+    })
+}
+
+/// Close a channel, waking every receiver blocked on it
+pub fn close_channel(channel: ChannelId) -> Result<(), NexusError> {
+    let waiters = unsafe { get_nexus().lock().close_channel(channel)? };
+    for thread_id in waiters {
+        crate::loom_of_fate::wake(thread_id);
+    }
+    Ok(())
+}
+
+/// Something `wait_any` can wait on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitSource {
+    Channel(ChannelId),
+    Notification(NotificationId),
+}
+
+/// The source that ended a `wait_any`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ready {
+    /// Position of the source in the slice passed to `wait_any`
+    pub index: usize,
+
+    /// Signals taken from a notification (always 0 for a channel)
+    pub signals: u64,
+}
+
+/// Create a notification for a Vessel
+///
+/// One capability carrying send (raise), receive (wait) and grant rights
+/// is sealed into the Vessel's table.
+pub fn create_notification(vessel: VesselId) -> Result<CapabilityId, NexusError> {
+    let notification = unsafe { get_nexus().lock().create_notification()? };
+
+    let installed = with_capabilities(vessel, |table| {
+        let rights = CapabilityRights::SEND | CapabilityRights::RECEIVE | CapabilityRights::TRANSFER;
+        table.insert(SealedCapability::new(ObjectHandle(notification.0), rights))
+    })
+    .and_then(|installed| installed.map_err(NexusError::from));
+
+    if installed.is_err() {
+        let _ = destroy_notification(notification);
+    }
+    installed
+}
+
+/// Find the notification behind one of a Vessel's capabilities
+///
+/// # Returns
+/// * `Err(NexusError::NotificationNotFound)` - The capability names a
+///   channel, or a notification that is gone
+pub fn resolve_notification(
+    vessel: VesselId,
+    capability: CapabilityId,
+    required: CapabilityRights,
+) -> Result<NotificationId, NexusError> {
+    match resolve_wait_source(vessel, capability, required)? {
+        WaitSource::Notification(notification) => Ok(notification),
+        WaitSource::Channel(_) => Err(NexusError::NotificationNotFound),
+    }
+}
+
+/// Find the channel or notification behind one of a Vessel's capabilities
+pub fn resolve_wait_source(
+    vessel: VesselId,
+    capability: CapabilityId,
+    required: CapabilityRights,
+) -> Result<WaitSource, NexusError> {
+    let handle = with_capabilities(vessel, |table| table.get_handle(capability, required))??;
+    unsafe { get_nexus().lock().classify(handle.0) }.ok_or(NexusError::InvalidCapability)
+}
+
+/// Raise signal bits on a notification, waking everyone waiting on it
+///
+/// Never blocks. It takes the Nexus lock to raise the signals and drops it
+/// before taking the Loom lock to wake the waiters, so the two are never
+/// held together. Both are interrupt-safe locks, so no handler can
+/// interrupt a holder on the same CPU, and this is safe to call from an
+/// interrupt handler.
+pub fn raise(notification: NotificationId, signals: u64) -> Result<(), NexusError> {
+    let waiters = unsafe { get_nexus().lock().raise(notification, signals)? };
+    for thread_id in waiters {
+        crate::loom_of_fate::wake(thread_id);
+    }
+    Ok(())
+}
+
+/// Destroy a notification, waking everyone waiting on it
+///
+/// Any IRQ lines bound to it fall silent again.
+pub fn destroy_notification(notification: NotificationId) -> Result<(), NexusError> {
+    unbind_irqs(notification);
+
+    let waiters = unsafe { get_nexus().lock().destroy_notification(notification)? };
+    for thread_id in waiters {
+        crate::loom_of_fate::wake(thread_id);
+    }
+    Ok(())
+}
+
+/// Block until any of several channels or notifications is ready
+///
+/// Sources are checked in order, so an earlier one wins when several are
+/// ready at once. A ready notification's signals are taken; a ready
+/// channel's messages are left for [`receive`] or [`try_receive`].
+///
+/// A channel send wakes only its longest-waiting receiver, so a channel
+/// watched here should not also have other threads receiving from it.
+///
+/// # Arguments
+/// * `sources` - What to wait on (at most `MAX_WAIT_SOURCES`)
+/// * `timeout` - Give up after this many timer ticks (`None` waits forever)
+///
+/// # Returns
+/// * `Ok(ready)` - Which source became ready, and any signals taken
+/// * `Err(NexusError::Timeout)` - Nothing became ready in time
+/// * `Err(NexusError::ChannelNotFound)` or `NotificationNotFound` - A
+///   source is gone
+pub fn wait_any(sources: &[WaitSource], timeout: Option<u64>) -> Result<Ready, NexusError> {
+    if sources.is_empty() || sources.len() > MAX_WAIT_SOURCES {
+        return Err(NexusError::InvalidCapability);
+    }
+
+    let deadline = timeout.map(|ticks| crate::attunement::timer::ticks().saturating_add(ticks));
+    let me = crate::loom_of_fate::current_thread();
+
+    loop {
+        let ready = unsafe { get_nexus().lock().poll_or_wait(sources, me)? };
+        if let Some(ready) = ready {
+            return Ok(ready);
+        }
+
+        if deadline.is_some_and(|deadline| crate::attunement::timer::ticks() >= deadline) {
+            if let Some(me) = me {
+                unsafe { get_nexus().lock().cancel_wait_any(sources, me) };
+            }
+            return Err(NexusError::Timeout);
+        }
+
+        // Before the Loom is weaving there is nothing to park; just poll
+        match me {
+            Some(_) => crate::loom_of_fate::park_current(BlockReason::Channel, deadline),
+            None => core::hint::spin_loop(),
+        }
+    }
+}
+
+/// Number of legacy PIC lines
+const IRQ_LINES: usize = 16;
+
+/// Lines the Heartwood drives itself: the timer, keyboard and cascade
+const RESERVED_IRQS: [u8; 3] = [0, 1, 2];
+
+/// For each IRQ line, the notification it raises (0 when unbound)
+static IRQ_BINDINGS: [AtomicU64; IRQ_LINES] = [const { AtomicU64::new(0) }; IRQ_LINES];
+
+/// For each IRQ line, the signals it raises
+static IRQ_SIGNALS: [AtomicU64; IRQ_LINES] = [const { AtomicU64::new(0) }; IRQ_LINES];
+
+/// Route a hardware IRQ line to a notification and unmask it
+///
+/// Each time the line fires, `signals` are raised on the notification. A
+/// line can be bound to only one notification at a time.
+///
+/// # Returns
+/// * `Err(NexusError::IrqUnavailable)` - No such line, a line the
+///   Heartwood keeps for itself, or already bound
+pub fn bind_irq(irq: u8, notification: NotificationId, signals: u64) -> Result<(), NexusError> {
+    let line = irq as usize;
+    if line >= IRQ_LINES || RESERVED_IRQS.contains(&irq) || signals == 0 {
+        return Err(NexusError::IrqUnavailable);
+    }
+
+    // With interrupts off the line cannot fire between the two stores
+    crate::loom_of_fate::without_interrupts(|| claim_irq(line, notification, signals))?;
+
+    crate::attunement::unmask_irq(irq);
+    Ok(())
+}
+
+/// Record a line's notification and signals, unless the line is taken
+fn claim_irq(line: usize, notification: NotificationId, signals: u64) -> Result<(), NexusError> {
+    IRQ_BINDINGS[line]
+        .compare_exchange(0, notification.0, Ordering::AcqRel, Ordering::Relaxed)
+        .map(|_| IRQ_SIGNALS[line].store(signals, Ordering::Release))
+        .map_err(|_| NexusError::IrqUnavailable)
+}
+
+/// Release every line bound to a notification
+fn unbind_irqs(notification: NotificationId) {
+    for binding in &IRQ_BINDINGS {
+        let _ = binding.compare_exchange(notification.0, 0, Ordering::AcqRel, Ordering::Relaxed);
+    }
+}
+
+/// The notification an IRQ line raises, and with which signals
+fn irq_binding(irq: u8) -> Option<(NotificationId, u64)> {
+    let notification = IRQ_BINDINGS.get(irq as usize)?.load(Ordering::Acquire);
+    (notification != 0).then(|| (NotificationId(notification), IRQ_SIGNALS[irq as usize].load(Ordering::Acquire)))
+}
+
+/// Raise whatever notification is bound to an IRQ line
+///
+/// Called by the interrupt handlers for the lines without a driver of
+/// their own.
+pub fn irq_raised(irq: u8) {
+    if let Some((notification, signals)) = irq_binding(irq) {
+        let _ = raise(notification, signals);
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -86,4 +625,144 @@ pub enum NexusError {
     ChannelClosed,
     InvalidCapability,
     OutOfChannels,
+    /// A blocking receive gave up before a message arrived
+    Timeout,
+    /// The capability does not carry the rights the operation needs
+    PermissionDenied,
+    /// The Vessel's capability table is full
+    OutOfCapabilities,
+    /// A service name is malformed
+    InvalidName,
+    /// A service name is already registered
+    NameTaken,
+    /// No service is registered under the name
+    NameNotFound,
+    /// The notification does not exist (or the handle names a channel)
+    NotificationNotFound,
+    /// The IRQ line cannot be bound
+    IrqUnavailable,
+}
+
+impl From<CapabilityError> for NexusError {
+    fn from(err: CapabilityError) -> Self {
+        match err {
+            CapabilityError::InvalidId | CapabilityError::SealBroken => NexusError::InvalidCapability,
+            CapabilityError::PermissionDenied => NexusError::PermissionDenied,
+            CapabilityError::TableFull => NexusError::OutOfCapabilities,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bind_irq_refuses_unusable_lines() {
+        let notification = NotificationId(1);
+        for irq in RESERVED_IRQS {
+            assert_eq!(bind_irq(irq, notification, 1), Err(NexusError::IrqUnavailable));
+        }
+        assert_eq!(bind_irq(IRQ_LINES as u8, notification, 1), Err(NexusError::IrqUnavailable));
+        assert_eq!(bind_irq(5, notification, 0), Err(NexusError::IrqUnavailable));
+        assert_eq!(irq_binding(5), None);
+    }
+
+    #[test]
+    fn test_irq_binding_bookkeeping() {
+        // Lines and notifications of its own: the bindings are global
+        let (first, second) = (NotificationId(0x1701), NotificationId(0x1702));
+        assert_eq!(irq_binding(9), None);
+
+        claim_irq(9, first, 0b100).unwrap();
+        claim_irq(10, first, 0b1000).unwrap();
+        assert_eq!(claim_irq(9, second, 1), Err(NexusError::IrqUnavailable));
+        assert_eq!(irq_binding(9), Some((first, 0b100)));
+        assert_eq!(irq_binding(10), Some((first, 0b1000)));
+
+        // Releasing a notification frees all of its lines and no others
+        claim_irq(11, second, 1).unwrap();
+        unbind_irqs(first);
+        assert_eq!(irq_binding(9), None);
+        assert_eq!(irq_binding(10), None);
+        assert_eq!(irq_binding(11), Some((second, 1)));
+
+        claim_irq(9, second, 2).unwrap();
+        assert_eq!(irq_binding(9), Some((second, 2)));
+        unbind_irqs(second);
+        assert_eq!(irq_binding(11), None);
+    }
+
+    fn ref_count(objects: &ObjectManager, capability: &Capability) -> usize {
+        objects.get_object_info(capability).unwrap().ref_count
+    }
+
+    #[test]
+    fn test_grant_mints_a_second_reference() {
+        let mut objects = ObjectManager::new();
+        let sender = objects.create_shared_object(0x1000).unwrap();
+
+        let granted = mint_from(&mut objects, &sender, TransferMode::Grant).unwrap();
+        assert_eq!(granted.handle, sender.handle);
+        assert_eq!(granted.rights, sender.rights);
+        assert_eq!(ref_count(&objects, &sender), 2);
+
+        // Either side may let go first
+        objects.release_object(&sender).unwrap();
+        assert!(objects.shared_memory(&granted).is_ok());
+        objects.release_object(&granted).unwrap();
+        assert_eq!(objects.object_count(), 0);
+    }
+
+    #[test]
+    fn test_move_hands_over_the_only_reference() {
+        let mut objects = ObjectManager::new();
+        let sender = objects.create_shared_object(0x1000).unwrap();
+
+        let moved = mint_from(&mut objects, &sender, TransferMode::Move).unwrap();
+        assert_eq!(moved.handle, sender.handle);
+        assert_eq!(moved.rights, sender.rights);
+        assert_eq!(ref_count(&objects, &moved), 1);
+
+        objects.release_object(&moved).unwrap();
+        assert_eq!(objects.object_count(), 0);
+    }
+
+    #[test]
+    fn test_share_attenuates_to_read_only() {
+        let mut objects = ObjectManager::new();
+        let sender = objects.create_shared_object(0x1000).unwrap();
+
+        let shared = mint_from(&mut objects, &sender, TransferMode::Share).unwrap();
+        assert_eq!(shared.rights, CapabilityRights::read_only());
+        assert!(shared.can_read() && !shared.can_write() && !shared.can_transfer());
+        assert!(sender.can_write());
+        assert_eq!(ref_count(&objects, &sender), 2);
+
+        // A read-only holder cannot pass the object on in any mode
+        for mode in [TransferMode::Grant, TransferMode::Move, TransferMode::Share] {
+            assert_eq!(mint_from(&mut objects, &shared, mode).err(), Some(NexusError::InvalidCapability));
+        }
+        assert_eq!(ref_count(&objects, &sender), 2);
+
+        // The object outlives the sender's capability
+        objects.release_object(&sender).unwrap();
+        assert!(objects.shared_memory(&shared).is_ok());
+        objects.release_object(&shared).unwrap();
+        assert_eq!(objects.object_count(), 0);
+    }
+
+    #[test]
+    fn test_only_shared_memory_is_minted() {
+        let mut objects = ObjectManager::new();
+        let plain = objects
+            .create_object(0x1000, 64, crate::mana_pool::AllocationPurpose::ShortLived)
+            .unwrap();
+        assert_eq!(mint_from(&mut objects, &plain, TransferMode::Grant).err(), Some(NexusError::InvalidCapability));
+        assert_eq!(ref_count(&objects, &plain), 1);
+
+        let stale = objects.create_shared_object(0x1000).unwrap();
+        objects.release_object(&stale).unwrap();
+        assert_eq!(mint_from(&mut objects, &stale, TransferMode::Grant).err(), Some(NexusError::InvalidCapability));
+    }
 }
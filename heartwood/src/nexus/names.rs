@@ -0,0 +1,217 @@
+//! The Names - Where services make themselves known
+//!
+//! Groves register a channel capability under a hierarchical name such as
+//! `world-tree/query` or `weave/compositor`; clients resolve the name to a
+//! send capability of their own. Both are judged by the caller's Fate in
+//! the Concordance of Fates.
+//!
+//! The registry holds no capabilities itself. Each entry points at the
+//! registrant's capability and every resolution derives from it, so a
+//! service that revokes its capability cuts off all its clients too.
+
+use super::{with_capabilities, NexusError};
+use crate::loom_of_fate::VesselId;
+use crate::mana_pool::concordance_of_fates::{self, Operation};
+use crate::mana_pool::{CapabilityId, CapabilityRights};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use spin::Mutex;
+
+/// Longest name the registry accepts, separators included
+pub const MAX_NAME_LEN: usize = 64;
+
+/// Maximum number of names that can be registered at once
+const MAX_NAMES: usize = 256;
+
+/// A name and the endpoint behind it
+#[derive(Debug, Clone)]
+pub struct Registration {
+    pub name: String,
+
+    /// The Vessel that registered the name
+    pub owner: VesselId,
+
+    /// The owner's capability, which resolutions derive from
+    pub capability: CapabilityId,
+}
+
+/// The registry of service names
+pub struct NameRegistry {
+    entries: BTreeMap<String, Registration>,
+}
+
+impl NameRegistry {
+    pub const fn new() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Add a registration; names are first come, first served
+    pub fn insert(&mut self, registration: Registration) -> Result<(), NexusError> {
+        if self.entries.contains_key(&registration.name) {
+            return Err(NexusError::NameTaken);
+        }
+        if self.entries.len() >= MAX_NAMES {
+            return Err(NexusError::OutOfChannels);
+        }
+
+        self.entries.insert(registration.name.clone(), registration);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Registration> {
+        self.entries.get(name)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Registration> {
+        self.entries.remove(name)
+    }
+
+    /// Drop every name a Vessel registered
+    pub fn remove_owned_by(&mut self, owner: VesselId) {
+        self.entries.retain(|_, registration| registration.owner != owner);
+    }
+
+    /// All registrations, in name order
+    pub fn iter(&self) -> impl Iterator<Item = &Registration> {
+        self.entries.values()
+    }
+}
+
+impl Default for NameRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Global name registry
+static NAMES: Mutex<NameRegistry> = Mutex::new(NameRegistry::new());
+
+/// Check that a name is well formed
+///
+/// Names are `/`-separated segments of lowercase letters, digits, `-`,
+/// `_` and `.`, with no empty segments.
+pub fn is_valid_name(name: &str) -> bool {
+    let valid_segment = |segment: &str| {
+        !segment.is_empty()
+            && segment
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || matches!(b, b'-' | b'_' | b'.'))
+    };
+
+    name.len() <= MAX_NAME_LEN && name.split('/').all(valid_segment)
+}
+
+/// Register one of a Vessel's channel capabilities under a name
+///
+/// The capability needs send and grant rights, since clients are handed
+/// capabilities derived from it.
+///
+/// # Returns
+/// * `Err(NexusError::InvalidName)` - Malformed name
+/// * `Err(NexusError::NameTaken)` - Someone else got there first
+/// * `Err(NexusError::PermissionDenied)` - The Vessel's Fate forbids
+///   registering this name, or the capability lacks send or grant
+pub fn register(vessel: VesselId, name: &str, capability: CapabilityId) -> Result<(), NexusError> {
+    if !is_valid_name(name) {
+        return Err(NexusError::InvalidName);
+    }
+    if !fate_allows(vessel, &Operation::ServiceRegister(String::from(name))) {
+        return Err(NexusError::PermissionDenied);
+    }
+
+    with_capabilities(vessel, |table| {
+        table.check_rights(capability, CapabilityRights::SEND | CapabilityRights::TRANSFER)
+    })??;
+
+    let registration = Registration {
+        name: String::from(name),
+        owner: vessel,
+        capability,
+    };
+    crate::loom_of_fate::without_interrupts(|| NAMES.lock().insert(registration))
+}
+
+/// Resolve a name to a capability in the calling Vessel's table
+///
+/// The new capability carries the send and grant rights of the registered
+/// one, never receive.
+///
+/// # Returns
+/// * `Err(NexusError::NameNotFound)` - No such name, or its owner has
+///   revoked the capability behind it
+/// * `Err(NexusError::PermissionDenied)` - The Vessel's Fate forbids
+///   resolving this name
+pub fn resolve(vessel: VesselId, name: &str) -> Result<CapabilityId, NexusError> {
+    if !fate_allows(vessel, &Operation::ServiceResolve(String::from(name))) {
+        return Err(NexusError::PermissionDenied);
+    }
+
+    let registration = crate::loom_of_fate::without_interrupts(|| NAMES.lock().get(name).cloned())
+        .ok_or(NexusError::NameNotFound)?;
+
+    let resolved = crate::loom_of_fate::without_interrupts(|| {
+        let mut harbor = crate::loom_of_fate::get_harbor().lock();
+        let endpoint = harbor
+            .find_vessel(registration.owner)
+            .and_then(|owner| owner.capabilities().get(registration.capability).ok())
+            .map(|endpoint| {
+                endpoint.derive(endpoint.rights & (CapabilityRights::SEND | CapabilityRights::TRANSFER))
+            })
+            .ok_or(NexusError::NameNotFound)?;
+
+        let client = harbor.find_vessel_mut(vessel).ok_or(NexusError::InvalidCapability)?;
+        client
+            .capabilities_mut()
+            .insert_derived(endpoint, registration.capability)
+            .map_err(NexusError::from)
+    });
+
+    // The owner is gone or took its capability back; the name goes with it
+    if matches!(resolved, Err(NexusError::NameNotFound)) {
+        crate::loom_of_fate::without_interrupts(|| {
+            let mut names = NAMES.lock();
+            if names.get(name).is_some_and(|entry| entry.capability == registration.capability) {
+                names.remove(name);
+            }
+        });
+    }
+    resolved
+}
+
+/// Drop every name a Vessel registered (when it is unmoored)
+pub fn forget_vessel(vessel: VesselId) {
+    crate::loom_of_fate::without_interrupts(|| NAMES.lock().remove_owned_by(vessel));
+}
+
+/// Snapshot of all registrations, in name order
+pub fn registrations() -> Vec<Registration> {
+    crate::loom_of_fate::without_interrupts(|| NAMES.lock().iter().cloned().collect())
+}
+
+/// Judge a Vessel's request by its Fate
+///
+/// A Vessel whose Fate is not written in the Concordance (Groves carry
+/// their service name) is judged by the default Fate, as a newly
+/// registered Subject would be. Nothing is allowed before the Concordance
+/// exists.
+fn fate_allows(vessel: VesselId, operation: &Operation) -> bool {
+    let fate = crate::loom_of_fate::without_interrupts(|| {
+        let harbor = crate::loom_of_fate::get_harbor().lock();
+        harbor.find_vessel(vessel).map(|vessel| String::from(vessel.fate()))
+    });
+    let Some(fate) = fate else {
+        return false;
+    };
+    if !concordance_of_fates::is_concordance_active() {
+        return false;
+    }
+
+    let concordance = unsafe { concordance_of_fates::get_concordance() };
+    concordance
+        .check_fate_permission(&fate, operation)
+        .or_else(|_| concordance.check_fate_permission(concordance.default_fate(), operation))
+        .unwrap_or(false)
+}
@@ -1,17 +1,24 @@
 //! The Nexus Core - The beating heart of IPC
 
-use super::channel::{Channel, ChannelId, ChannelCapability};
-use super::message::Message;
-use super::NexusError;
-use alloc::collections::BTreeMap;
+use super::channel::{Channel, ChannelId};
+use super::message::{Message, ReplyCapability};
+use super::notification::{Notification, NotificationId};
+use super::{NexusError, Ready, WaitSource};
+use crate::loom_of_fate::ThreadId;
+use alloc::collections::{BTreeMap, VecDeque};
 
 /// The maximum number of channels the Nexus can manage
 const MAX_CHANNELS: usize = 4096;
 
-/// The central Nexus manages all channels in the system
+/// The maximum number of notifications the Nexus can manage
+const MAX_NOTIFICATIONS: usize = 1024;
+
+/// The central Nexus manages all channels and notifications in the system
 pub struct NexusCore {
     channels: BTreeMap<ChannelId, Channel>,
-    next_channel_id: u64,
+    notifications: BTreeMap<NotificationId, Notification>,
+    /// Next ID for a channel or notification (they share one numbering)
+    next_object_id: u64,
 }
 
 impl Default for NexusCore {
@@ -24,36 +31,110 @@ impl NexusCore {
     pub fn new() -> Self {
         Self {
             channels: BTreeMap::new(),
-            next_channel_id: 1, // 0 is reserved as invalid
+            notifications: BTreeMap::new(),
+            next_object_id: 1, // 0 is reserved as invalid
         }
     }
 
-    /// Create a new bidirectional channel pair
-    /// Returns two capabilities: (sender_side, receiver_side)
-    pub fn create_channel(&mut self) -> Result<(ChannelCapability, ChannelCapability), NexusError> {
+    fn allocate_id(&mut self) -> u64 {
+        let id = self.next_object_id;
+        self.next_object_id += 1;
+        id
+    }
+
+    /// Create a new bidirectional channel
+    ///
+    /// Access to it is granted through sealed capabilities; see
+    /// `nexus::create_channel`.
+    pub fn create_channel(&mut self) -> Result<ChannelId, NexusError> {
         if self.channels.len() >= MAX_CHANNELS {
             return Err(NexusError::OutOfChannels);
         }
 
-        let channel_id = ChannelId(self.next_channel_id);
-        self.next_channel_id += 1;
+        let channel_id = ChannelId(self.allocate_id());
 
         let channel = Channel::new(channel_id);
         self.channels.insert(channel_id, channel);
 
-        // Create bidirectional capabilities
-        let cap_a = ChannelCapability::new_bidirectional(channel_id);
-        let cap_b = ChannelCapability::new_bidirectional(channel_id);
+        Ok(channel_id)
+    }
 
-        Ok((cap_a, cap_b))
+    /// Create a one-shot reply channel
+    fn create_reply_channel(&mut self) -> Result<ChannelId, NexusError> {
+        if self.channels.len() >= MAX_CHANNELS {
+            return Err(NexusError::OutOfChannels);
+        }
+
+        let channel_id = ChannelId(self.allocate_id());
+
+        self.channels.insert(channel_id, Channel::new_reply(channel_id));
+        Ok(channel_id)
+    }
+
+    /// Send half of a synchronous call
+    ///
+    /// Creates a one-shot reply channel, registers `caller` (if any) as its
+    /// waiter, and sends `message` carrying the reply capability.
+    ///
+    /// # Returns
+    /// The reply channel, and the server thread to wake if one was parked
+    pub fn call_send(
+        &mut self,
+        channel_id: ChannelId,
+        message: Message,
+        caller: Option<ThreadId>,
+    ) -> Result<(ChannelId, Option<ThreadId>), NexusError> {
+        let reply_id = self.create_reply_channel()?;
+        if let (Some(caller), Some(reply)) = (caller, self.channels.get_mut(&reply_id)) {
+            reply.add_waiter(caller);
+        }
+
+        match self.send(channel_id, message.with_reply(ReplyCapability::new(reply_id))) {
+            Ok(server) => Ok((reply_id, server)),
+            Err(err) => {
+                self.channels.remove(&reply_id);
+                Err(err)
+            }
+        }
+    }
+
+    /// Answer a call, spending its reply capability
+    ///
+    /// A reply whose caller has already gone (its reply channel removed) is
+    /// refused with `InvalidCapability`.
+    ///
+    /// # Returns
+    /// The caller to wake
+    pub fn reply(&mut self, reply_capability: ReplyCapability, message: Message) -> Result<Option<ThreadId>, NexusError> {
+        let channel = self
+            .channels
+            .get_mut(&reply_capability.channel())
+            .filter(|channel| channel.is_reply() && !channel.is_closed())
+            .ok_or(NexusError::InvalidCapability)?;
+
+        channel.send(message).map_err(|_| NexusError::InvalidCapability)
+    }
+
+    /// Remove a channel entirely, revoking every capability to it
+    pub fn remove_channel(&mut self, channel_id: ChannelId) {
+        self.channels.remove(&channel_id);
     }
 
     /// Send a message through a channel
-    pub fn send(&mut self, channel_id: ChannelId, message: Message) -> Result<(), NexusError> {
+    ///
+    /// A reply channel takes nothing but the answer sent with its reply
+    /// capability, so sending to one is refused with `InvalidCapability`.
+    ///
+    /// # Returns
+    /// The receiver to wake, if one was parked on the channel
+    pub fn send(&mut self, channel_id: ChannelId, message: Message) -> Result<Option<ThreadId>, NexusError> {
         let channel = self
             .channels
             .get_mut(&channel_id)
             .ok_or(NexusError::ChannelNotFound)?;
+        if channel.is_reply() {
+            return Err(NexusError::InvalidCapability);
+        }
 
         channel
             .send(message)
@@ -80,15 +161,152 @@ impl NexusCore {
             })
     }
 
+    /// Park-side half of a blocking receive
+    ///
+    /// Takes a message if one is queued; otherwise registers `waiter` to be
+    /// woken by the next send. Both happen under one lock, so a send can
+    /// never fall between the check and the registration.
+    pub fn receive_or_wait(&mut self, channel_id: ChannelId, waiter: ThreadId) -> Result<Option<Message>, NexusError> {
+        let message = self.try_receive(channel_id)?;
+        if message.is_none() {
+            if let Some(channel) = self.channels.get_mut(&channel_id) {
+                channel.add_waiter(waiter);
+            }
+        }
+        Ok(message)
+    }
+
+    /// Stop waiting on a channel (the channel may already be gone)
+    pub fn cancel_wait(&mut self, channel_id: ChannelId, waiter: ThreadId) {
+        if let Some(channel) = self.channels.get_mut(&channel_id) {
+            channel.remove_waiter(waiter);
+        }
+    }
+
+    /// Create a notification with no signals pending
+    pub fn create_notification(&mut self) -> Result<NotificationId, NexusError> {
+        if self.notifications.len() >= MAX_NOTIFICATIONS {
+            return Err(NexusError::OutOfChannels);
+        }
+
+        let notification_id = NotificationId(self.allocate_id());
+        self.notifications.insert(notification_id, Notification::new(notification_id));
+        Ok(notification_id)
+    }
+
+    /// Raise signal bits on a notification
+    ///
+    /// # Returns
+    /// The threads parked on it, which must be woken
+    pub fn raise(&mut self, notification_id: NotificationId, signals: u64) -> Result<VecDeque<ThreadId>, NexusError> {
+        self.notifications
+            .get_mut(&notification_id)
+            .map(|notification| notification.raise(signals))
+            .ok_or(NexusError::NotificationNotFound)
+    }
+
+    /// Remove a notification entirely
+    ///
+    /// # Returns
+    /// The threads parked on it, which must be woken
+    pub fn destroy_notification(&mut self, notification_id: NotificationId) -> Result<VecDeque<ThreadId>, NexusError> {
+        self.notifications
+            .remove(&notification_id)
+            .map(|mut notification| notification.destroy())
+            .ok_or(NexusError::NotificationNotFound)
+    }
+
+    /// Tell whether a handle names a channel or a notification
+    pub fn classify(&self, handle: u64) -> Option<WaitSource> {
+        if self.channels.contains_key(&ChannelId(handle)) {
+            Some(WaitSource::Channel(ChannelId(handle)))
+        } else if self.notifications.contains_key(&NotificationId(handle)) {
+            Some(WaitSource::Notification(NotificationId(handle)))
+        } else {
+            None
+        }
+    }
+
+    /// Park-side half of `wait_any`
+    ///
+    /// Returns the first ready source in `sources`, taking a notification's
+    /// signals; otherwise registers `waiter` on every source. As with
+    /// `receive_or_wait`, both happen under one lock so nothing raised or
+    /// sent in between is missed.
+    ///
+    /// A channel is ready when it holds a message or has been closed; its
+    /// messages are left for the caller to receive.
+    pub fn poll_or_wait(&mut self, sources: &[WaitSource], waiter: Option<ThreadId>) -> Result<Option<Ready>, NexusError> {
+        let mut ready = None;
+        for (index, source) in sources.iter().enumerate() {
+            let signals = match *source {
+                WaitSource::Channel(channel_id) => {
+                    let channel = self.channels.get(&channel_id).ok_or(NexusError::ChannelNotFound)?;
+                    (channel.has_messages() || channel.is_closed()).then_some(0)
+                }
+                WaitSource::Notification(notification_id) => {
+                    let notification = self
+                        .notifications
+                        .get_mut(&notification_id)
+                        .ok_or(NexusError::NotificationNotFound)?;
+                    Some(notification.take()).filter(|&signals| signals != 0)
+                }
+            };
+
+            if let Some(signals) = signals {
+                ready = Some(Ready { index, signals });
+                break;
+            }
+        }
+
+        if let Some(waiter) = waiter {
+            if ready.is_some() {
+                self.cancel_wait_any(sources, waiter);
+            } else {
+                for source in sources {
+                    match *source {
+                        WaitSource::Channel(channel_id) => {
+                            if let Some(channel) = self.channels.get_mut(&channel_id) {
+                                channel.add_waiter(waiter);
+                            }
+                        }
+                        WaitSource::Notification(notification_id) => {
+                            if let Some(notification) = self.notifications.get_mut(&notification_id) {
+                                notification.add_waiter(waiter);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(ready)
+    }
+
+    /// Stop waiting on every source (some may already be gone)
+    pub fn cancel_wait_any(&mut self, sources: &[WaitSource], waiter: ThreadId) {
+        for source in sources {
+            match *source {
+                WaitSource::Channel(channel_id) => self.cancel_wait(channel_id, waiter),
+                WaitSource::Notification(notification_id) => {
+                    if let Some(notification) = self.notifications.get_mut(&notification_id) {
+                        notification.remove_waiter(waiter);
+                    }
+                }
+            }
+        }
+    }
+
     /// Close a channel
-    pub fn close_channel(&mut self, channel_id: ChannelId) -> Result<(), NexusError> {
+    ///
+    /// # Returns
+    /// The receivers that were parked on it, which must be woken
+    pub fn close_channel(&mut self, channel_id: ChannelId) -> Result<VecDeque<ThreadId>, NexusError> {
         let channel = self
             .channels
             .get_mut(&channel_id)
             .ok_or(NexusError::ChannelNotFound)?;
 
-        channel.close();
-        Ok(())
+        Ok(channel.close())
     }
 
     /// Get statistics about the Nexus
@@ -105,6 +323,7 @@ impl NexusCore {
                 .values()
                 .map(|c| c.message_count())
                 .sum(),
+            total_notifications: self.notifications.len(),
         }
     }
 }
@@ -115,4 +334,195 @@ pub struct NexusStats {
     pub total_channels: usize,
     pub active_channels: usize,
     pub total_queued_messages: usize,
+    pub total_notifications: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loom_of_fate::VesselId;
+    use crate::mana_pool::CapabilityId;
+    use crate::nexus::message::{CapabilityTransfer, MessagePriority, MessageType};
+    use alloc::vec;
+
+    fn data(byte: u8) -> Message {
+        Message::new(MessageType::Data { payload: vec![byte] }, MessagePriority::Normal)
+    }
+
+    #[test]
+    fn test_receive_or_wait_parks_until_send() {
+        let mut nexus = NexusCore::new();
+        let channel = nexus.create_channel().unwrap();
+
+        assert!(nexus.receive_or_wait(channel, ThreadId(1)).unwrap().is_none());
+        assert_eq!(nexus.send(channel, data(1)), Ok(Some(ThreadId(1))));
+
+        // The woken receiver takes the message without parking again
+        assert!(nexus.receive_or_wait(channel, ThreadId(1)).unwrap().is_some());
+        assert_eq!(nexus.send(channel, data(2)), Ok(None));
+    }
+
+    #[test]
+    fn test_cancel_wait() {
+        let mut nexus = NexusCore::new();
+        let channel = nexus.create_channel().unwrap();
+
+        assert!(nexus.receive_or_wait(channel, ThreadId(1)).unwrap().is_none());
+        nexus.cancel_wait(channel, ThreadId(1));
+        assert_eq!(nexus.send(channel, data(1)), Ok(None));
+
+        // Cancelling on a channel that is gone is harmless
+        nexus.remove_channel(channel);
+        nexus.cancel_wait(channel, ThreadId(1));
+        assert_eq!(nexus.receive_or_wait(channel, ThreadId(1)).err(), Some(NexusError::ChannelNotFound));
+    }
+
+    #[test]
+    fn test_call_and_reply() {
+        let mut nexus = NexusCore::new();
+        let channel = nexus.create_channel().unwrap();
+        assert!(nexus.receive_or_wait(channel, ThreadId(2)).unwrap().is_none());
+
+        // The call wakes the parked server and leaves the caller waiting
+        let (reply_channel, server) = nexus.call_send(channel, data(1), Some(ThreadId(1))).unwrap();
+        assert_eq!(server, Some(ThreadId(2)));
+
+        let mut request = nexus.try_receive(channel).unwrap().unwrap();
+        let reply_capability = request.reply_to.take().unwrap();
+        assert_eq!(nexus.reply(reply_capability, data(2)), Ok(Some(ThreadId(1))));
+
+        // One answer, then the reply channel is spent
+        assert!(nexus.try_receive(reply_channel).unwrap().is_some());
+        assert_eq!(nexus.try_receive(reply_channel).err(), Some(NexusError::ChannelClosed));
+    }
+
+    #[test]
+    fn test_reply_channel_refuses_everyone_else() {
+        let mut nexus = NexusCore::new();
+        let channel = nexus.create_channel().unwrap();
+        let (reply_channel, _) = nexus.call_send(channel, data(1), Some(ThreadId(1))).unwrap();
+
+        // Knowing the reply channel's number is not the right to answer
+        assert_eq!(nexus.send(reply_channel, data(2)), Err(NexusError::InvalidCapability));
+        assert!(nexus.try_receive(reply_channel).unwrap().is_none());
+
+        // Nor does the capability outlive a caller that gave up
+        let reply_capability = nexus.try_receive(channel).unwrap().unwrap().reply_to.unwrap();
+        nexus.remove_channel(reply_channel);
+        assert_eq!(nexus.reply(reply_capability, data(3)), Err(NexusError::InvalidCapability));
+    }
+
+    #[test]
+    fn test_failed_call_leaves_no_reply_channel() {
+        let mut nexus = NexusCore::new();
+        let channel = nexus.create_channel().unwrap();
+        nexus.close_channel(channel).unwrap();
+
+        assert_eq!(nexus.call_send(channel, data(1), Some(ThreadId(1))).err(), Some(NexusError::ChannelClosed));
+        assert_eq!(nexus.stats().total_channels, 1);
+    }
+
+    #[test]
+    fn test_capabilities_travel_with_the_message() {
+        let mut nexus = NexusCore::new();
+        let channel = nexus.create_channel().unwrap();
+
+        let ids = vec![CapabilityId::new(7), CapabilityId::new(9)];
+        let transfer = CapabilityTransfer { sender: VesselId(3), capabilities: ids.clone() };
+        nexus.send(channel, data(1)).unwrap();
+        nexus.send(channel, data(2).with_capabilities(transfer)).unwrap();
+
+        // Only the sender's IDs are queued, untouched until delivery
+        assert!(nexus.try_receive(channel).unwrap().unwrap().transfer.is_none());
+        let transfer = nexus.try_receive(channel).unwrap().unwrap().transfer.unwrap();
+        assert_eq!(transfer.sender, VesselId(3));
+        assert_eq!(transfer.capabilities, ids);
+    }
+
+    #[test]
+    fn test_raise_and_take_clears() {
+        let mut nexus = NexusCore::new();
+        let notification = nexus.create_notification().unwrap();
+        let sources = [WaitSource::Notification(notification)];
+
+        // Raising nothing wakes nobody; raising bits wakes every waiter
+        assert_eq!(nexus.poll_or_wait(&sources, Some(ThreadId(1))), Ok(None));
+        assert_eq!(nexus.poll_or_wait(&sources, Some(ThreadId(2))), Ok(None));
+        assert!(nexus.raise(notification, 0).unwrap().is_empty());
+        assert_eq!(nexus.raise(notification, 0b01).unwrap(), [ThreadId(1), ThreadId(2)]);
+        assert!(nexus.raise(notification, 0b10).unwrap().is_empty());
+
+        // Every pending bit is taken at once, leaving none
+        assert_eq!(nexus.poll_or_wait(&sources, None), Ok(Some(Ready { index: 0, signals: 0b11 })));
+        assert_eq!(nexus.poll_or_wait(&sources, None), Ok(None));
+    }
+
+    #[test]
+    fn test_poll_or_wait_prefers_earlier_sources() {
+        let mut nexus = NexusCore::new();
+        let channel = nexus.create_channel().unwrap();
+        let notification = nexus.create_notification().unwrap();
+        let sources = [WaitSource::Channel(channel), WaitSource::Notification(notification)];
+
+        nexus.raise(notification, 1).unwrap();
+        nexus.send(channel, data(1)).unwrap();
+        assert_eq!(nexus.poll_or_wait(&sources, None), Ok(Some(Ready { index: 0, signals: 0 })));
+
+        // The channel's message is left for a receive; once it is gone the
+        // notification's turn comes
+        assert_eq!(nexus.poll_or_wait(&sources, None), Ok(Some(Ready { index: 0, signals: 0 })));
+        nexus.try_receive(channel).unwrap();
+        assert_eq!(nexus.poll_or_wait(&sources, None), Ok(Some(Ready { index: 1, signals: 1 })));
+
+        // A closed channel is ready too, so a waiter sees the close
+        nexus.close_channel(channel).unwrap();
+        assert_eq!(nexus.poll_or_wait(&sources, None), Ok(Some(Ready { index: 0, signals: 0 })));
+    }
+
+    #[test]
+    fn test_poll_or_wait_registers_on_every_source() {
+        let mut nexus = NexusCore::new();
+        let first = nexus.create_channel().unwrap();
+        let second = nexus.create_channel().unwrap();
+        let notification = nexus.create_notification().unwrap();
+        let sources = [WaitSource::Channel(first), WaitSource::Channel(second), WaitSource::Notification(notification)];
+
+        assert_eq!(nexus.poll_or_wait(&sources, Some(ThreadId(1))), Ok(None));
+        assert_eq!(nexus.send(second, data(1)), Ok(Some(ThreadId(1))));
+
+        // Finding a ready source withdraws the waiter from the rest
+        assert!(nexus.poll_or_wait(&sources, Some(ThreadId(1))).unwrap().is_some());
+        assert_eq!(nexus.send(first, data(2)), Ok(None));
+        assert!(nexus.raise(notification, 1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_cancel_wait_any() {
+        let mut nexus = NexusCore::new();
+        let channel = nexus.create_channel().unwrap();
+        let notification = nexus.create_notification().unwrap();
+        let sources = [WaitSource::Channel(channel), WaitSource::Notification(notification)];
+
+        assert_eq!(nexus.poll_or_wait(&sources, Some(ThreadId(1))), Ok(None));
+        nexus.cancel_wait_any(&sources, ThreadId(1));
+        assert_eq!(nexus.send(channel, data(1)), Ok(None));
+        assert!(nexus.raise(notification, 1).unwrap().is_empty());
+
+        // Sources that have gone away are skipped
+        nexus.remove_channel(channel);
+        nexus.cancel_wait_any(&sources, ThreadId(1));
+        assert_eq!(nexus.poll_or_wait(&sources, None), Err(NexusError::ChannelNotFound));
+    }
+
+    #[test]
+    fn test_destroy_notification_wakes_waiters() {
+        let mut nexus = NexusCore::new();
+        let notification = nexus.create_notification().unwrap();
+        let sources = [WaitSource::Notification(notification)];
+
+        assert_eq!(nexus.poll_or_wait(&sources, Some(ThreadId(1))), Ok(None));
+        assert_eq!(nexus.destroy_notification(notification).unwrap(), [ThreadId(1)]);
+        assert_eq!(nexus.poll_or_wait(&sources, None), Err(NexusError::NotificationNotFound));
+        assert_eq!(nexus.classify(notification.0), None);
+    }
 }
@@ -0,0 +1,81 @@
+//! Notifications - Signals without a message
+//!
+//! A notification is a word of signal bits. Raising it ORs bits in and
+//! never blocks or allocates, so it is safe from an interrupt handler;
+//! waiting on it takes every pending bit at once. A driver Grove binds its
+//! device's IRQ to a notification and waits on it alongside its request
+//! channel with `nexus::wait_any`.
+
+use crate::loom_of_fate::ThreadId;
+use alloc::collections::VecDeque;
+
+/// A unique identifier for a notification
+///
+/// Notifications draw from the same numbering as channels, so a capability
+/// handle names exactly one of the two.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NotificationId(pub u64);
+
+/// A word of pending signals and the threads waiting for any of them
+pub struct Notification {
+    id: NotificationId,
+    signals: u64,
+    /// Threads parked until a signal is raised, oldest first
+    waiters: VecDeque<ThreadId>,
+}
+
+impl Notification {
+    pub fn new(id: NotificationId) -> Self {
+        Self {
+            id,
+            signals: 0,
+            waiters: VecDeque::new(),
+        }
+    }
+
+    pub fn id(&self) -> NotificationId {
+        self.id
+    }
+
+    /// Raise signal bits
+    ///
+    /// # Returns
+    /// Every parked waiter, which the caller must wake
+    pub fn raise(&mut self, signals: u64) -> VecDeque<ThreadId> {
+        self.signals |= signals;
+        if self.signals == 0 {
+            return VecDeque::new();
+        }
+        core::mem::take(&mut self.waiters)
+    }
+
+    /// Take every pending signal, leaving none
+    pub fn take(&mut self) -> u64 {
+        core::mem::take(&mut self.signals)
+    }
+
+    /// Signals raised but not yet taken
+    pub fn pending(&self) -> u64 {
+        self.signals
+    }
+
+    /// Register a thread to be woken by the next raise
+    pub fn add_waiter(&mut self, thread_id: ThreadId) {
+        if !self.waiters.contains(&thread_id) {
+            self.waiters.push_back(thread_id);
+        }
+    }
+
+    /// Forget a waiter that gave up or was woken by another source
+    pub fn remove_waiter(&mut self, thread_id: ThreadId) {
+        self.waiters.retain(|&waiter| waiter != thread_id);
+    }
+
+    /// Tear the notification down
+    ///
+    /// # Returns
+    /// Every parked waiter, which the caller must wake
+    pub fn destroy(&mut self) -> VecDeque<ThreadId> {
+        core::mem::take(&mut self.waiters)
+    }
+}
@@ -116,6 +116,7 @@ fn show_stack_protection_page() {
                     ThreadState::Weaving => "Weaving",
                     ThreadState::Resting => "Resting",
                     ThreadState::Tangled => "Tangled",
+                    ThreadState::Blocked(_) => "Blocked",
                     ThreadState::Fading => "Fading",
                 };
 
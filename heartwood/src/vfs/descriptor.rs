@@ -0,0 +1,282 @@
+//! File Descriptor Tables - Per-Vessel open files
+//!
+//! Every Vessel owns a `FileDescriptorTable` mapping small integers (file
+//! descriptors) to `OpenFile` objects. An `OpenFile` remembers which scroll it
+//! refers to, how it was opened, and the current offset, so successive
+//! `SYS_READ` calls walk through a file instead of re-reading its start.
+//!
+//! Descriptors 0, 1 and 2 are pre-populated with the console so that
+//! existing programs writing to stdout/stderr keep working unchanged.
+//!
+//! The table itself never touches a filesystem while it is borrowed from the
+//! Harbor: callers snapshot the `OpenFile`, perform I/O with the Harbor lock
+//! released, then write the new offset back with `advance()`.
+
+use super::{FileSystem, FsError, Path};
+use alloc::vec::Vec;
+use covenant::open_flags::{O_APPEND, O_READ, O_WRITE};
+
+/// Maximum number of simultaneously open descriptors per Vessel
+pub const MAX_OPEN_FILES: usize = 64;
+
+/// What an open descriptor refers to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpenFileKind {
+    /// The kernel console (serial port)
+    Console,
+
+    /// A file on the globally mounted filesystem
+    File(Path),
+}
+
+/// An open file object
+#[derive(Debug, Clone)]
+pub struct OpenFile {
+    /// What this descriptor refers to
+    pub kind: OpenFileKind,
+
+    /// `covenant::open_flags` the descriptor was opened with
+    pub flags: u64,
+
+    /// Current byte offset for the next read or write
+    pub offset: u64,
+}
+
+impl OpenFile {
+    /// Create a console descriptor
+    pub fn console(flags: u64) -> Self {
+        Self {
+            kind: OpenFileKind::Console,
+            flags,
+            offset: 0,
+        }
+    }
+
+    /// Create a descriptor for a file, positioned at its start
+    pub fn file(path: Path, flags: u64) -> Self {
+        Self {
+            kind: OpenFileKind::File(path),
+            flags,
+            offset: 0,
+        }
+    }
+
+    /// Whether the descriptor permits reading
+    pub fn is_readable(&self) -> bool {
+        self.flags & O_READ != 0
+    }
+
+    /// Whether the descriptor permits writing
+    pub fn is_writable(&self) -> bool {
+        self.flags & O_WRITE != 0
+    }
+
+    /// Read from the current offset into `buf`
+    ///
+    /// Does not move the offset; the caller commits it with
+    /// `FileDescriptorTable::advance()` once the bytes reach the reader.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(n)` - Bytes read (0 at end of file)
+    /// * `Err(FsError)` - Underlying filesystem error
+    pub fn read(&self, fs: &dyn FileSystem, buf: &mut [u8]) -> Result<usize, FsError> {
+        match &self.kind {
+            OpenFileKind::Console => Ok(0),
+            OpenFileKind::File(path) => fs.read_at(path, self.offset, buf),
+        }
+    }
+
+    /// Write `data` at the current offset (or at the end for `O_APPEND`)
+    ///
+    /// Only the bytes being written reach the filesystem, so writing a file
+    /// sequentially doesn't rewrite everything before the offset each time.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(new_offset)` - Offset just past the written bytes
+    /// * `Err(FsError)` - Underlying filesystem error
+    pub fn write(&self, fs: &dyn FileSystem, data: &[u8]) -> Result<u64, FsError> {
+        let path = match &self.kind {
+            OpenFileKind::Console => return Ok(self.offset),
+            OpenFileKind::File(path) => path,
+        };
+
+        let start = if self.flags & O_APPEND != 0 {
+            match fs.stat(path) {
+                Ok(stat) => stat.size,
+                Err(FsError::NotFound) => 0,
+                Err(e) => return Err(e),
+            }
+        } else {
+            self.offset
+        };
+        let end = start.checked_add(data.len() as u64).ok_or(FsError::OutOfSpace)?;
+
+        fs.write_at(path, start, data)?;
+        Ok(end)
+    }
+}
+
+/// Errors from descriptor table operations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FdError {
+    /// Descriptor is not open
+    BadDescriptor,
+    /// The table has no free slots
+    TooManyOpen,
+}
+
+/// A Vessel's table of open descriptors
+#[derive(Debug, Clone)]
+pub struct FileDescriptorTable {
+    slots: Vec<Option<OpenFile>>,
+}
+
+impl FileDescriptorTable {
+    /// Create an empty table (no stdio)
+    pub fn new() -> Self {
+        Self { slots: Vec::new() }
+    }
+
+    /// Create a table with stdin, stdout and stderr bound to the console
+    pub fn with_stdio() -> Self {
+        let mut table = Self::new();
+        table.slots.push(Some(OpenFile::console(O_READ)));
+        table.slots.push(Some(OpenFile::console(O_WRITE)));
+        table.slots.push(Some(OpenFile::console(O_WRITE)));
+        table
+    }
+
+    /// Install an open file at the lowest free descriptor
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(fd)` - The new descriptor
+    /// * `Err(FdError::TooManyOpen)` - `MAX_OPEN_FILES` already open
+    pub fn insert(&mut self, file: OpenFile) -> Result<usize, FdError> {
+        if let Some(fd) = self.slots.iter().position(|slot| slot.is_none()) {
+            self.slots[fd] = Some(file);
+            return Ok(fd);
+        }
+
+        if self.slots.len() >= MAX_OPEN_FILES {
+            return Err(FdError::TooManyOpen);
+        }
+
+        self.slots.push(Some(file));
+        Ok(self.slots.len() - 1)
+    }
+
+    /// Look up an open descriptor
+    pub fn get(&self, fd: usize) -> Result<&OpenFile, FdError> {
+        self.slots
+            .get(fd)
+            .and_then(|slot| slot.as_ref())
+            .ok_or(FdError::BadDescriptor)
+    }
+
+    /// Move a descriptor's offset after a successful read or write
+    pub fn advance(&mut self, fd: usize, new_offset: u64) -> Result<(), FdError> {
+        let file = self.slots
+            .get_mut(fd)
+            .and_then(|slot| slot.as_mut())
+            .ok_or(FdError::BadDescriptor)?;
+        file.offset = new_offset;
+        Ok(())
+    }
+
+    /// Close a descriptor, freeing its slot for reuse
+    pub fn remove(&mut self, fd: usize) -> Result<OpenFile, FdError> {
+        self.slots
+            .get_mut(fd)
+            .and_then(|slot| slot.take())
+            .ok_or(FdError::BadDescriptor)
+    }
+
+    /// Number of descriptors currently open
+    pub fn open_count(&self) -> usize {
+        self.slots.iter().filter(|slot| slot.is_some()).count()
+    }
+}
+
+impl Default for FileDescriptorTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::mock::MockFs;
+
+    #[test]
+    fn test_stdio_is_preopened() {
+        let table = FileDescriptorTable::with_stdio();
+        assert_eq!(table.open_count(), 3);
+        assert!(table.get(0).unwrap().is_readable());
+        assert!(table.get(1).unwrap().is_writable());
+        assert!(table.get(2).unwrap().is_writable());
+    }
+
+    #[test]
+    fn test_lowest_free_descriptor_is_reused() {
+        let mut table = FileDescriptorTable::with_stdio();
+        let a = table.insert(OpenFile::file(Path::new("/a"), O_READ)).unwrap();
+        let b = table.insert(OpenFile::file(Path::new("/b"), O_READ)).unwrap();
+        assert_eq!((a, b), (3, 4));
+
+        table.remove(a).unwrap();
+        assert_eq!(table.get(a).unwrap_err(), FdError::BadDescriptor);
+        assert_eq!(table.insert(OpenFile::file(Path::new("/c"), O_READ)).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_table_limit() {
+        let mut table = FileDescriptorTable::new();
+        for _ in 0..MAX_OPEN_FILES {
+            table.insert(OpenFile::console(O_READ)).unwrap();
+        }
+        assert_eq!(table.insert(OpenFile::console(O_READ)), Err(FdError::TooManyOpen));
+    }
+
+    #[test]
+    fn test_sequential_reads_follow_offset() {
+        let fs = MockFs::new();
+        fs.write(&Path::new("/scroll.txt"), b"Hello, Heartwood").unwrap();
+
+        let mut table = FileDescriptorTable::new();
+        let fd = table.insert(OpenFile::file(Path::new("/scroll.txt"), O_READ)).unwrap();
+
+        let mut buf = [0u8; 7];
+        let file = table.get(fd).unwrap().clone();
+        let n = file.read(&fs, &mut buf).unwrap();
+        assert_eq!(&buf[..n], b"Hello, ");
+        table.advance(fd, file.offset + n as u64).unwrap();
+
+        let mut rest = [0u8; 32];
+        let file = table.get(fd).unwrap().clone();
+        let n = file.read(&fs, &mut rest).unwrap();
+        assert_eq!(&rest[..n], b"Heartwood");
+        table.advance(fd, file.offset + n as u64).unwrap();
+
+        let file = table.get(fd).unwrap();
+        assert_eq!(file.read(&fs, &mut rest).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_write_splices_at_offset() {
+        let fs = MockFs::new();
+        fs.write(&Path::new("/rune.txt"), b"aaaaaa").unwrap();
+
+        let mut file = OpenFile::file(Path::new("/rune.txt"), O_WRITE);
+        file.offset = 2;
+        assert_eq!(file.write(&fs, b"XY").unwrap(), 4);
+        assert_eq!(fs.read(&Path::new("/rune.txt")).unwrap(), b"aaXYaa");
+
+        let append = OpenFile::file(Path::new("/rune.txt"), O_WRITE | O_APPEND);
+        assert_eq!(append.write(&fs, b"!").unwrap(), 7);
+        assert_eq!(fs.read(&Path::new("/rune.txt")).unwrap(), b"aaXYaa!");
+    }
+}
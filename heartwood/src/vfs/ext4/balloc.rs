@@ -0,0 +1,376 @@
+//! ext4 Block and Inode Allocation
+//!
+//! Each block group tracks its free space in two bitmaps: one bit per block
+//! and one bit per inode. Allocating flips bits in a bitmap, then brings the
+//! group descriptor (counters, flags, checksums) and the superblock's free
+//! counts into line with it - always in that order, so a crash part-way
+//! leaves space leaked rather than doubly owned.
+//!
+//! Groups that have never been used may be flagged `BLOCK_UNINIT` or
+//! `INODE_UNINIT`. Their bitmaps on disk are meaningless and are rebuilt
+//! from the group layout before the first allocation.
+
+use crate::vfs::block_device::BlockDevice;
+use crate::vfs::FsError;
+use super::block::{read_block, write_block};
+use super::superblock::{bg_flags, BlockGroupDesc, Ext4Superblock};
+use alloc::vec::Vec;
+
+/// Longest run of blocks a single extent can describe
+pub const MAX_EXTENT_LEN: u32 = 32768;
+
+fn test_bit(bitmap: &[u8], bit: u32) -> bool {
+    bitmap[(bit / 8) as usize] & (1 << (bit % 8)) != 0
+}
+
+fn set_bit(bitmap: &mut [u8], bit: u32) {
+    bitmap[(bit / 8) as usize] |= 1 << (bit % 8);
+}
+
+fn clear_bit(bitmap: &mut [u8], bit: u32) {
+    bitmap[(bit / 8) as usize] &= !(1 << (bit % 8));
+}
+
+/// Mark every bit from `from` to the end of the bitmap block as in use
+///
+/// Bits past the end of a group describe blocks or inodes that don't
+/// exist; ext4 keeps them set so they're never handed out.
+fn mark_bitmap_end(bitmap: &mut [u8], from: u32) {
+    for bit in from..(bitmap.len() * 8) as u32 {
+        set_bit(bitmap, bit);
+    }
+}
+
+/// Number of blocks occupied by one group's inode table
+fn inode_table_blocks(sb: &Ext4Superblock) -> u64 {
+    let bytes = sb.s_inodes_per_group as u64 * sb.s_inode_size as u64;
+    bytes.div_ceil(sb.block_size() as u64)
+}
+
+fn read_desc(device: &dyn BlockDevice, sb: &Ext4Superblock, group: u32) -> Result<BlockGroupDesc, FsError> {
+    BlockGroupDesc::from_device(device, sb, group).map_err(|_| FsError::IoError)
+}
+
+/// Load a group's block bitmap, building it first if the group is `BLOCK_UNINIT`
+fn load_block_bitmap(
+    device: &dyn BlockDevice,
+    sb: &Ext4Superblock,
+    group: u32,
+    desc: &BlockGroupDesc,
+) -> Result<Vec<u8>, FsError> {
+    let mut bitmap = read_block(device, sb, desc.bg_block_bitmap)
+        .map_err(|_| FsError::IoError)?;
+
+    if !sb.has_group_checksums() || desc.bg_flags & bg_flags::BLOCK_UNINIT == 0 {
+        return Ok(bitmap);
+    }
+
+    bitmap.fill(0);
+
+    // Superblock backup, descriptor table and its reserved growth area
+    if sb.group_has_superblock(group) {
+        let meta = 1 + sb.gdt_block_count() + sb.s_reserved_gdt_blocks as u32;
+        for bit in 0..meta {
+            set_bit(&mut bitmap, bit);
+        }
+    }
+
+    // The group's own bitmaps and inode table, when they live inside it
+    // (with flex_bg they are usually packed into an earlier group)
+    let first = sb.group_first_block(group);
+    let end = first + sb.blocks_in_group(group) as u64;
+    let table = desc.bg_inode_table..desc.bg_inode_table + inode_table_blocks(sb);
+    for block in [desc.bg_block_bitmap, desc.bg_inode_bitmap].into_iter().chain(table) {
+        if block >= first && block < end {
+            set_bit(&mut bitmap, (block - first) as u32);
+        }
+    }
+
+    mark_bitmap_end(&mut bitmap, sb.blocks_in_group(group));
+    Ok(bitmap)
+}
+
+/// Load a group's inode bitmap, building it first if the group is `INODE_UNINIT`
+fn load_inode_bitmap(
+    device: &dyn BlockDevice,
+    sb: &Ext4Superblock,
+    desc: &BlockGroupDesc,
+) -> Result<Vec<u8>, FsError> {
+    let mut bitmap = read_block(device, sb, desc.bg_inode_bitmap)
+        .map_err(|_| FsError::IoError)?;
+
+    if sb.has_group_checksums() && desc.bg_flags & bg_flags::INODE_UNINIT != 0 {
+        bitmap.fill(0);
+        mark_bitmap_end(&mut bitmap, sb.s_inodes_per_group);
+    }
+
+    Ok(bitmap)
+}
+
+/// Write a group's block bitmap and descriptor back, refreshing checksums
+fn store_block_bitmap(
+    device: &dyn BlockDevice,
+    sb: &Ext4Superblock,
+    group: u32,
+    desc: &mut BlockGroupDesc,
+    bitmap: &[u8],
+) -> Result<(), FsError> {
+    desc.bg_flags &= !bg_flags::BLOCK_UNINIT;
+    if sb.has_metadata_csum() {
+        desc.bg_block_bitmap_csum = sb.bitmap_checksum(bitmap, sb.s_blocks_per_group);
+    }
+
+    write_block(device, sb, desc.bg_block_bitmap, bitmap).map_err(|_| FsError::IoError)?;
+    desc.write_to_device(device, sb, group).map_err(|_| FsError::IoError)
+}
+
+/// Write a group's inode bitmap and descriptor back, refreshing checksums
+fn store_inode_bitmap(
+    device: &dyn BlockDevice,
+    sb: &Ext4Superblock,
+    group: u32,
+    desc: &mut BlockGroupDesc,
+    bitmap: &[u8],
+) -> Result<(), FsError> {
+    desc.bg_flags &= !bg_flags::INODE_UNINIT;
+    if sb.has_metadata_csum() {
+        desc.bg_inode_bitmap_csum = sb.bitmap_checksum(bitmap, sb.s_inodes_per_group);
+    }
+
+    write_block(device, sb, desc.bg_inode_bitmap, bitmap).map_err(|_| FsError::IoError)?;
+    desc.write_to_device(device, sb, group).map_err(|_| FsError::IoError)
+}
+
+/// Allocate a run of contiguous blocks
+///
+/// Searches outward from `goal`, so a file's blocks tend to stay together,
+/// and takes the first free block found plus as many free blocks directly
+/// after it as possible (up to `max`, and never past the end of its group).
+///
+/// # Returns
+///
+/// * `Ok((start, count))` - First block of the run and its length (at least 1)
+/// * `Err(FsError::OutOfSpace)` - No free blocks left
+/// * `Err(FsError::IoError)` - Read or write error
+pub fn allocate_blocks(
+    device: &dyn BlockDevice,
+    sb: &Ext4Superblock,
+    goal: u64,
+    max: u32,
+) -> Result<(u64, u32), FsError> {
+    let groups = sb.block_group_count();
+    let goal = goal.clamp(sb.s_first_data_block as u64, sb.s_blocks_count - 1);
+    let goal_group = ((goal - sb.s_first_data_block as u64) / sb.s_blocks_per_group as u64) as u32;
+    let max = max.clamp(1, MAX_EXTENT_LEN);
+
+    for i in 0..groups {
+        let group = (goal_group + i) % groups;
+        let mut desc = read_desc(device, sb, group)?;
+        if desc.bg_free_blocks_count == 0 {
+            continue;
+        }
+
+        let mut bitmap = load_block_bitmap(device, sb, group, &desc)?;
+        let blocks = sb.blocks_in_group(group);
+
+        // Only the goal's own group starts part-way through
+        let start_bit = if group == goal_group {
+            (goal - sb.group_first_block(group)) as u32
+        } else {
+            0
+        };
+
+        let free = (start_bit..blocks)
+            .chain(0..start_bit)
+            .find(|&bit| !test_bit(&bitmap, bit));
+        let first = match free {
+            Some(bit) => bit,
+            None => continue,
+        };
+
+        let mut count = 0;
+        while count < max && first + count < blocks && !test_bit(&bitmap, first + count) {
+            set_bit(&mut bitmap, first + count);
+            count += 1;
+        }
+
+        desc.bg_free_blocks_count = desc.bg_free_blocks_count.saturating_sub(count);
+        store_block_bitmap(device, sb, group, &mut desc, &bitmap)?;
+        sb.adjust_free_counts(device, -(count as i64), 0)
+            .map_err(|_| FsError::IoError)?;
+
+        return Ok((sb.group_first_block(group) + first as u64, count));
+    }
+
+    Err(FsError::OutOfSpace)
+}
+
+/// Return a run of blocks to the free pool
+///
+/// The run may cross group boundaries. Blocks that are already free are
+/// skipped rather than counted twice.
+pub fn free_blocks(
+    device: &dyn BlockDevice,
+    sb: &Ext4Superblock,
+    start: u64,
+    count: u64,
+) -> Result<(), FsError> {
+    let end = start + count;
+    let mut block = start;
+    let mut freed = 0u64;
+
+    while block < end {
+        if block < sb.s_first_data_block as u64 || block >= sb.s_blocks_count {
+            return Err(FsError::IoError);
+        }
+
+        let group = ((block - sb.s_first_data_block as u64) / sb.s_blocks_per_group as u64) as u32;
+        let group_first = sb.group_first_block(group);
+        let group_end = (group_first + sb.blocks_in_group(group) as u64).min(end);
+
+        let mut desc = read_desc(device, sb, group)?;
+        let mut bitmap = load_block_bitmap(device, sb, group, &desc)?;
+
+        let mut cleared = 0;
+        for b in block..group_end {
+            let bit = (b - group_first) as u32;
+            if test_bit(&bitmap, bit) {
+                clear_bit(&mut bitmap, bit);
+                cleared += 1;
+            }
+        }
+
+        desc.bg_free_blocks_count += cleared;
+        store_block_bitmap(device, sb, group, &mut desc, &bitmap)?;
+
+        freed += cleared as u64;
+        block = group_end;
+    }
+
+    sb.adjust_free_counts(device, freed as i64, 0)
+        .map_err(|_| FsError::IoError)
+}
+
+/// Allocate an inode
+///
+/// Starts looking in `preferred_group` (normally the parent directory's
+/// group, to keep a directory's files close together) and moves on to the
+/// following groups when it is full. Reserved inodes are never returned.
+///
+/// # Returns
+///
+/// * `Ok(inode_num)` - The allocated inode number (1-indexed)
+/// * `Err(FsError::OutOfSpace)` - No free inodes left
+/// * `Err(FsError::IoError)` - Read or write error
+pub fn allocate_inode(
+    device: &dyn BlockDevice,
+    sb: &Ext4Superblock,
+    preferred_group: u32,
+    is_dir: bool,
+) -> Result<u32, FsError> {
+    let groups = sb.block_group_count();
+    let per_group = sb.s_inodes_per_group;
+
+    for i in 0..groups {
+        let group = (preferred_group % groups + i) % groups;
+        let mut desc = read_desc(device, sb, group)?;
+        if desc.bg_free_inodes_count == 0 {
+            continue;
+        }
+
+        let mut bitmap = load_inode_bitmap(device, sb, &desc)?;
+
+        // Inodes below s_first_ino (root, journal, ...) are reserved
+        let first_usable = if group == 0 { sb.s_first_ino.saturating_sub(1) } else { 0 };
+        let bit = match (first_usable..per_group).find(|&bit| !test_bit(&bitmap, bit)) {
+            Some(bit) => bit,
+            None => continue,
+        };
+
+        set_bit(&mut bitmap, bit);
+        desc.bg_free_inodes_count -= 1;
+        if is_dir {
+            desc.bg_used_dirs_count += 1;
+        }
+
+        // Inodes past the high-water mark are assumed never used by fsck
+        if sb.has_group_checksums() {
+            let used = per_group - desc.bg_itable_unused;
+            if bit >= used {
+                desc.bg_itable_unused = per_group - bit - 1;
+            }
+        }
+
+        store_inode_bitmap(device, sb, group, &mut desc, &bitmap)?;
+        sb.adjust_free_counts(device, 0, -1)
+            .map_err(|_| FsError::IoError)?;
+
+        return Ok(group * per_group + bit + 1);
+    }
+
+    Err(FsError::OutOfSpace)
+}
+
+/// Return an inode to the free pool
+pub fn free_inode(
+    device: &dyn BlockDevice,
+    sb: &Ext4Superblock,
+    inode_num: u32,
+    is_dir: bool,
+) -> Result<(), FsError> {
+    if inode_num == 0 || inode_num > sb.s_inodes_count {
+        return Err(FsError::IoError);
+    }
+
+    let group = (inode_num - 1) / sb.s_inodes_per_group;
+    let bit = (inode_num - 1) % sb.s_inodes_per_group;
+
+    let mut desc = read_desc(device, sb, group)?;
+    let mut bitmap = load_inode_bitmap(device, sb, &desc)?;
+    if !test_bit(&bitmap, bit) {
+        return Ok(());
+    }
+
+    clear_bit(&mut bitmap, bit);
+    desc.bg_free_inodes_count += 1;
+    if is_dir {
+        desc.bg_used_dirs_count = desc.bg_used_dirs_count.saturating_sub(1);
+    }
+
+    store_inode_bitmap(device, sb, group, &mut desc, &bitmap)?;
+    sb.adjust_free_counts(device, 0, 1)
+        .map_err(|_| FsError::IoError)
+}
+
+/// Block group an inode lives in
+pub fn inode_group(sb: &Ext4Superblock, inode_num: u32) -> u32 {
+    (inode_num.saturating_sub(1)) / sb.s_inodes_per_group
+}
+
+/// First block of the group an inode lives in (an allocation goal)
+pub fn inode_goal(sb: &Ext4Superblock, inode_num: u32) -> u64 {
+    sb.group_first_block(inode_group(sb, inode_num))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bit_helpers() {
+        let mut bitmap = [0u8; 2];
+        set_bit(&mut bitmap, 0);
+        set_bit(&mut bitmap, 9);
+        assert_eq!(bitmap, [0x01, 0x02]);
+        assert!(test_bit(&bitmap, 9));
+        clear_bit(&mut bitmap, 9);
+        assert!(!test_bit(&bitmap, 9));
+    }
+
+    #[test]
+    fn test_mark_bitmap_end() {
+        let mut bitmap = [0u8; 2];
+        mark_bitmap_end(&mut bitmap, 12);
+        assert_eq!(bitmap, [0x00, 0xF0]);
+    }
+}
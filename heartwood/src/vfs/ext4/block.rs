@@ -0,0 +1,73 @@
+//! ext4 Block I/O
+//!
+//! ext4 addresses storage in filesystem blocks (1-64 KB), while block devices
+//! work in sectors. These helpers translate between the two, including the
+//! case where a block is smaller than a sector and writes must
+//! read-modify-write the surrounding sector.
+
+use crate::vfs::block_device::{BlockDevice, BlockDeviceError};
+use super::superblock::Ext4Superblock;
+use alloc::vec::Vec;
+
+/// Read `len` bytes starting at byte `offset` of the device
+pub fn read_bytes(device: &dyn BlockDevice, offset: u64, len: usize) -> Result<Vec<u8>, BlockDeviceError> {
+    let sector_size = device.sector_size() as u64;
+    let start_sector = offset / sector_size;
+    let end = offset + len as u64;
+    let sectors = end.div_ceil(sector_size) - start_sector;
+
+    let mut data = device.read_sectors(start_sector, sectors as u32)?;
+
+    // Trim to exactly the requested range
+    let skip = (offset % sector_size) as usize;
+    data.drain(0..skip);
+    data.truncate(len);
+
+    Ok(data)
+}
+
+/// Write `data` starting at byte `offset` of the device
+///
+/// Partial sectors at either end are read first so their other bytes
+/// survive.
+pub fn write_bytes(device: &dyn BlockDevice, offset: u64, data: &[u8]) -> Result<(), BlockDeviceError> {
+    let sector_size = device.sector_size() as u64;
+    let start_sector = offset / sector_size;
+    let end = offset + data.len() as u64;
+    let sectors = end.div_ceil(sector_size) - start_sector;
+    let skip = (offset % sector_size) as usize;
+
+    if skip == 0 && data.len() as u64 == sectors * sector_size {
+        return device.write_sectors(start_sector, data);
+    }
+
+    let mut buffer = device.read_sectors(start_sector, sectors as u32)?;
+    buffer[skip..skip + data.len()].copy_from_slice(data);
+    device.write_sectors(start_sector, &buffer)
+}
+
+/// Read one filesystem block
+pub fn read_block(
+    device: &dyn BlockDevice,
+    sb: &Ext4Superblock,
+    block_num: u64,
+) -> Result<Vec<u8>, BlockDeviceError> {
+    let block_size = sb.block_size() as u64;
+    read_bytes(device, block_num * block_size, block_size as usize)
+}
+
+/// Write one filesystem block
+///
+/// `data` must be exactly one block long.
+pub fn write_block(
+    device: &dyn BlockDevice,
+    sb: &Ext4Superblock,
+    block_num: u64,
+    data: &[u8],
+) -> Result<(), BlockDeviceError> {
+    let block_size = sb.block_size() as u64;
+    if data.len() as u64 != block_size {
+        return Err(BlockDeviceError::IoError);
+    }
+    write_bytes(device, block_num * block_size, data)
+}
@@ -3,12 +3,26 @@
 //! Directories in ext4 are stored as a sequence of variable-length entries.
 //! Each entry contains the inode number, entry length, name length, file type,
 //! and the file name.
+//!
+//! Entries never cross a block boundary: the last entry in each block
+//! stretches (via `rec_len`) to the end of the block, or, on volumes with
+//! metadata_csum, to a 12-byte checksum tail disguised as an empty entry.
+//!
+//! Inline directories (inline_data feature) keep their entries inside the
+//! inode instead: `i_block` starts with the parent's inode number in place
+//! of "." and "..", and the entries that follow continue into the
+//! `system.data` xattr.
 
 use crate::vfs::block_device::BlockDevice;
-use crate::vfs::{DirEntry as VfsDirEntry, FsError};
-use super::superblock::Ext4Superblock;
-use super::inode::Inode;
+use crate::vfs::{DirEntry as VfsDirEntry, FileType, FsError};
+use super::balloc;
+use super::block::{read_block, write_block};
+use super::checksum::crc32c;
+use super::hash;
+use super::superblock::{features, Ext4Superblock};
+use super::inode::{Inode, EXT4_CASEFOLD_FL, EXT4_INDEX_FL, S_IFDIR, S_IFLNK, S_IFREG};
 use super::extent;
+use alloc::vec;
 use alloc::vec::Vec;
 use alloc::string::{String, ToString};
 
@@ -22,6 +36,33 @@ const EXT4_FT_FIFO: u8 = 5;
 const EXT4_FT_SOCK: u8 = 6;
 const EXT4_FT_SYMLINK: u8 = 7;
 
+/// File type of the fake entry holding a directory block's checksum
+const EXT4_FT_DIR_CSUM: u8 = 0xDE;
+
+/// Size of the checksum tail at the end of each directory block
+const CSUM_TAIL_SIZE: usize = 12;
+
+/// Longest name a directory entry can hold
+const MAX_NAME_LEN: usize = 255;
+
+/// Parent inode number at the start of an inline directory
+const INLINE_PARENT_SIZE: usize = 4;
+
+/// Offset of `dx_root_info`, just past the "." and ".." entries
+const DX_ROOT_INFO_OFFSET: usize = 24;
+
+/// Offset of the entries in an interior index node, past its empty entry
+const DX_NODE_ENTRIES_OFFSET: usize = 8;
+
+/// Size of an index entry (hash and block number)
+const DX_ENTRY_SIZE: usize = 8;
+
+/// Index entries keep flags in the top bits of the block number
+const DX_BLOCK_MASK: u32 = 0x0FFF_FFFF;
+
+/// Most levels an index can have, root included (with the largedir feature)
+const DX_MAX_LEVELS: u8 = 3;
+
 /// ext4 Directory Entry (variable length)
 ///
 /// Format:
@@ -92,11 +133,6 @@ impl DirEntry {
     fn is_valid(&self) -> bool {
         self.inode != 0
     }
-
-    /// Check if this is a directory
-    fn is_dir(&self) -> bool {
-        self.file_type == EXT4_FT_DIR
-    }
 }
 
 /// Find an entry in a directory by name
@@ -119,20 +155,31 @@ pub fn find_entry_in_dir(
     dir_inode: &Inode,
     name: &str,
 ) -> Result<Option<(u32, u8)>, FsError> {
+    if is_indexed(sb, dir_inode) {
+        match find_entry_hashed(device, sb, dir_inode, name) {
+            // Unknown hash or malformed index: the linear scan still works
+            Err(FsError::NotSupported) => {}
+            result => return result,
+        }
+    }
+
     // Read directory data
-    let dir_data = extent::read_file_data(device, sb, dir_inode)?;
+    let dir_data = read_dir_data(device, sb, dir_inode)?;
+    Ok(find_in_entries(&dir_data, name))
+}
 
-    // Parse directory entries
+/// Search a run of directory entries for `name`
+fn find_in_entries(data: &[u8], name: &str) -> Option<(u32, u8)> {
     let mut offset = 0;
-    while offset < dir_data.len() {
-        if offset + 8 > dir_data.len() {
+    while offset < data.len() {
+        if offset + 8 > data.len() {
             break;  // Not enough space for a valid entry
         }
 
-        match DirEntry::from_bytes(&dir_data[offset..]) {
+        match DirEntry::from_bytes(&data[offset..]) {
             Ok(entry) => {
                 if entry.is_valid() && entry.name == name {
-                    return Ok(Some((entry.inode, entry.file_type)));
+                    return Some((entry.inode, entry.file_type));
                 }
 
                 // Move to next entry
@@ -147,7 +194,155 @@ pub fn find_entry_in_dir(
         }
     }
 
-    Ok(None)  // Not found
+    None  // Not found
+}
+
+/// Whether lookups in a directory can use its hashed index
+fn is_indexed(sb: &Ext4Superblock, dir_inode: &Inode) -> bool {
+    dir_inode.i_flags & EXT4_INDEX_FL != 0
+        && dir_inode.i_flags & EXT4_CASEFOLD_FL == 0
+        && sb.s_feature_compat & features::COMPAT_DIR_INDEX != 0
+}
+
+/// One level of the path through a hashed index
+struct IndexLevel {
+    /// The index block holding this level's entries
+    block: Vec<u8>,
+    /// Offset of the count/limit header (which doubles as entry 0)
+    base: usize,
+    /// Number of entries
+    count: usize,
+    /// Entry followed to the level below
+    at: usize,
+}
+
+impl IndexLevel {
+    /// Parse the entries of a dx_root or dx_node block starting at `base`
+    fn new(block: Vec<u8>, base: usize) -> Result<Self, FsError> {
+        let count = block.get(base + 2..base + 4)
+            .map(|raw| u16::from_le_bytes([raw[0], raw[1]]) as usize)
+            .ok_or(FsError::NotSupported)?;
+        if count == 0 || base + count * DX_ENTRY_SIZE > block.len() {
+            return Err(FsError::NotSupported);
+        }
+        Ok(Self { block, base, count, at: 0 })
+    }
+
+    fn read_u32(&self, at: usize) -> u32 {
+        let b = &self.block;
+        u32::from_le_bytes([b[at], b[at + 1], b[at + 2], b[at + 3]])
+    }
+
+    /// Lowest hash stored under entry `index` (entry 0 covers everything below entry 1)
+    fn hash(&self, index: usize) -> u32 {
+        if index == 0 { 0 } else { self.read_u32(self.base + index * DX_ENTRY_SIZE) }
+    }
+
+    /// Logical directory block entry `index` points at
+    fn child(&self, index: usize) -> u32 {
+        self.read_u32(self.base + index * DX_ENTRY_SIZE + 4) & DX_BLOCK_MASK
+    }
+
+    /// Follow the last entry whose hash is at most `hash`
+    fn seek(&mut self, hash: u32) -> u32 {
+        self.at = (1..self.count).take_while(|&i| self.hash(i) <= hash).count();
+        self.child(self.at)
+    }
+}
+
+/// Look a name up through a directory's hashed (htree) index
+///
+/// # Returns
+///
+/// * `Ok(Some((inode_num, file_type)))` / `Ok(None)` - As for [`find_entry_in_dir`]
+/// * `Err(FsError::NotSupported)` - The index can't be used (fall back to a linear scan)
+/// * `Err(FsError)` - Read error
+fn find_entry_hashed(
+    device: &dyn BlockDevice,
+    sb: &Ext4Superblock,
+    dir_inode: &Inode,
+    name: &str,
+) -> Result<Option<(u32, u8)>, FsError> {
+    // dx_root: "." and "..", then dx_root_info, then the top level's entries
+    let root = read_dir_block(device, sb, dir_inode, 0)?;
+    let info = root.get(DX_ROOT_INFO_OFFSET..DX_ROOT_INFO_OFFSET + 8)
+        .ok_or(FsError::NotSupported)?;
+    let (hash_version, info_length, indirect_levels) = (info[4], info[5] as usize, info[6]);
+    if indirect_levels >= DX_MAX_LEVELS {
+        return Err(FsError::NotSupported);
+    }
+
+    let version = hash::effective_version(sb, hash_version);
+    let target = hash::dx_hash(name.as_bytes(), version, &sb.s_hash_seed)
+        .ok_or(FsError::NotSupported)?;
+
+    // Walk down to the leaf whose hash range holds the name
+    let mut path: Vec<IndexLevel> = Vec::new();
+    let mut level = IndexLevel::new(root, DX_ROOT_INFO_OFFSET + info_length)?;
+    let mut leaf = level.seek(target);
+    path.push(level);
+    for _ in 0..indirect_levels {
+        let mut level = IndexLevel::new(read_dir_block(device, sb, dir_inode, leaf)?, DX_NODE_ENTRIES_OFFSET)?;
+        leaf = level.seek(target);
+        path.push(level);
+    }
+
+    loop {
+        let block = read_dir_block(device, sb, dir_inode, leaf)?;
+        if let Some(found) = find_in_entries(&block, name) {
+            return Ok(Some(found));
+        }
+
+        // Names with colliding hashes may spill into the next leaf, which is
+        // then marked by its hash having the low bit set
+        let Some(depth) = path.iter().rposition(|level| level.at + 1 < level.count) else {
+            return Ok(None);
+        };
+        let next = &mut path[depth];
+        if next.hash(next.at + 1) & !1 != target {
+            return Ok(None);
+        }
+        next.at += 1;
+        leaf = next.child(next.at);
+
+        // Take the leftmost branch back down to the leaves
+        path.truncate(depth + 1);
+        while path.len() <= indirect_levels as usize {
+            let level = IndexLevel::new(read_dir_block(device, sb, dir_inode, leaf)?, DX_NODE_ENTRIES_OFFSET)?;
+            leaf = level.child(0);
+            path.push(level);
+        }
+    }
+}
+
+/// Read one logical block of a directory
+///
+/// A hole where the index points is treated as a malformed index.
+fn read_dir_block(
+    device: &dyn BlockDevice,
+    sb: &Ext4Superblock,
+    dir_inode: &Inode,
+    logical_block: u32,
+) -> Result<Vec<u8>, FsError> {
+    let physical = extent::map_block(device, sb, dir_inode, logical_block)?
+        .ok_or(FsError::NotSupported)?;
+    read_block(device, sb, physical).map_err(|_| FsError::IoError)
+}
+
+/// Read a directory's entry stream
+///
+/// For inline directories the leading parent inode number is dropped, which
+/// leaves a plain sequence of entries.
+fn read_dir_data(
+    device: &dyn BlockDevice,
+    sb: &Ext4Superblock,
+    dir_inode: &Inode,
+) -> Result<Vec<u8>, FsError> {
+    let mut data = extent::read_file_data(device, sb, dir_inode)?;
+    if dir_inode.has_inline_data() {
+        data.drain(..core::cmp::min(INLINE_PARENT_SIZE, data.len()));
+    }
+    Ok(data)
 }
 
 /// Read all entries from a directory
@@ -168,7 +363,8 @@ pub fn read_dir_entries(
     dir_inode: &Inode,
 ) -> Result<Vec<VfsDirEntry>, FsError> {
     // Read directory data
-    let dir_data = extent::read_file_data(device, sb, dir_inode)?;
+    let dir_data = read_dir_data(device, sb, dir_inode)?;
+    let has_file_types = sb.s_feature_incompat & features::INCOMPAT_FILETYPE != 0;
 
     let mut entries = Vec::new();
     let mut offset = 0;
@@ -182,9 +378,18 @@ pub fn read_dir_entries(
             Ok(entry) => {
                 // Skip invalid entries, ".", and ".."
                 if entry.is_valid() && entry.name != "." && entry.name != ".." {
+                    let mut file_type = entry.file_type;
+                    if !has_file_types || file_type == EXT4_FT_UNKNOWN {
+                        // Only the inode knows
+                        let inode = Inode::read_from_device(device, sb, entry.inode)
+                            .map_err(|_| FsError::IoError)?;
+                        file_type = file_type_for(&inode);
+                    }
+
                     entries.push(VfsDirEntry {
                         name: entry.name.clone(),
-                        is_dir: entry.is_dir(),
+                        is_dir: file_type == EXT4_FT_DIR,
+                        file_type: vfs_file_type(file_type),
                     });
                 }
 
@@ -202,3 +407,355 @@ pub fn read_dir_entries(
 
     Ok(entries)
 }
+
+/// Directory entry file type for an inode's mode
+pub fn file_type_for(inode: &Inode) -> u8 {
+    match inode.file_type() {
+        S_IFREG => EXT4_FT_REG_FILE,
+        S_IFDIR => EXT4_FT_DIR,
+        S_IFLNK => EXT4_FT_SYMLINK,
+        _ => EXT4_FT_UNKNOWN,
+    }
+}
+
+/// VFS file type for a directory entry's `file_type`
+fn vfs_file_type(file_type: u8) -> FileType {
+    match file_type {
+        EXT4_FT_REG_FILE => FileType::File,
+        EXT4_FT_DIR => FileType::Directory,
+        EXT4_FT_SYMLINK => FileType::Symlink,
+        _ => FileType::Other,
+    }
+}
+
+/// Bytes an entry with a name of `name_len` bytes needs (4-byte aligned)
+fn entry_size(name_len: usize) -> usize {
+    (8 + name_len + 3) & !3
+}
+
+/// Inode number, `rec_len` and `name_len` of the entry at `offset`
+fn entry_at(block: &[u8], offset: usize) -> (u32, usize, usize) {
+    let inode = u32::from_le_bytes([block[offset], block[offset + 1], block[offset + 2], block[offset + 3]]);
+    let rec_len = u16::from_le_bytes([block[offset + 4], block[offset + 5]]) as usize;
+    (inode, rec_len, block[offset + 6] as usize)
+}
+
+/// Write an entry header and name at `offset`
+fn put_entry(block: &mut [u8], offset: usize, inode: u32, rec_len: usize, name: &[u8], file_type: u8) {
+    block[offset..offset + 4].copy_from_slice(&inode.to_le_bytes());
+    block[offset + 4..offset + 6].copy_from_slice(&(rec_len as u16).to_le_bytes());
+    block[offset + 6] = name.len() as u8;
+    block[offset + 7] = file_type;
+    block[offset + 8..offset + 8 + name.len()].copy_from_slice(name);
+}
+
+/// Bytes of each block available to real entries
+fn usable_size(sb: &Ext4Superblock) -> usize {
+    let block_size = sb.block_size() as usize;
+    if sb.has_metadata_csum() {
+        block_size - CSUM_TAIL_SIZE
+    } else {
+        block_size
+    }
+}
+
+/// Write a directory block, refreshing its checksum tail
+fn store_block(
+    device: &dyn BlockDevice,
+    sb: &Ext4Superblock,
+    dir_num: u32,
+    dir_inode: &Inode,
+    physical_block: u64,
+    block: &mut [u8],
+) -> Result<(), FsError> {
+    if sb.has_metadata_csum() {
+        let tail = block.len() - CSUM_TAIL_SIZE;
+        put_entry(block, tail, 0, CSUM_TAIL_SIZE, &[], EXT4_FT_DIR_CSUM);
+        let seed = sb.inode_checksum_seed(dir_num, dir_inode.i_generation);
+        let csum = crc32c(seed, &block[..tail]);
+        block[tail + 8..].copy_from_slice(&csum.to_le_bytes());
+    }
+
+    write_block(device, sb, physical_block, block).map_err(|_| FsError::IoError)
+}
+
+/// Refuse to modify directories this driver can't keep consistent
+fn check_modifiable(dir_inode: &Inode) -> Result<(), FsError> {
+    // Hashed (htree) directories need their index updated too, and inline
+    // directories would have to be converted to blocks to grow
+    if dir_inode.i_flags & EXT4_INDEX_FL != 0 || dir_inode.has_inline_data() {
+        return Err(FsError::NotSupported);
+    }
+    Ok(())
+}
+
+/// Check that a name can be stored in a directory entry
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name != "."
+        && name != ".."
+        && !name.bytes().any(|b| b == b'/' || b == 0)
+}
+
+/// Add an entry to a directory
+///
+/// Reuses free space in an existing block where possible, otherwise appends
+/// a new block to the directory. The caller must check the name isn't
+/// already present, and must write `dir_inode` back afterwards (its size and
+/// block map change when a block is appended).
+pub fn add_entry(
+    device: &dyn BlockDevice,
+    sb: &Ext4Superblock,
+    dir_num: u32,
+    dir_inode: &mut Inode,
+    name: &str,
+    inode_num: u32,
+    file_type: u8,
+) -> Result<(), FsError> {
+    check_modifiable(dir_inode)?;
+    if !is_valid_name(name) {
+        return Err(FsError::InvalidPath);
+    }
+
+    let block_size = sb.block_size() as usize;
+    let usable = usable_size(sb);
+    let needed = entry_size(name.len());
+    let blocks = (dir_inode.size() / block_size as u64) as u32;
+
+    for logical in 0..blocks {
+        let physical = match extent::map_block(device, sb, dir_inode, logical)? {
+            Some(physical) => physical,
+            None => continue,
+        };
+        let mut block = read_block(device, sb, physical).map_err(|_| FsError::IoError)?;
+
+        let mut offset = 0;
+        while offset < usable {
+            let (entry_inode, rec_len, name_len) = entry_at(&block, offset);
+            if rec_len < 8 || offset + rec_len > usable {
+                return Err(FsError::IoError);
+            }
+
+            let used = if entry_inode == 0 { 0 } else { entry_size(name_len) };
+            if rec_len >= used + needed {
+                if used > 0 {
+                    block[offset + 4..offset + 6].copy_from_slice(&(used as u16).to_le_bytes());
+                }
+                put_entry(&mut block, offset + used, inode_num, rec_len - used, name.as_bytes(), file_type);
+                return store_block(device, sb, dir_num, dir_inode, physical, &mut block);
+            }
+
+            offset += rec_len;
+        }
+    }
+
+    // Every block is full: grow the directory by one block
+    let goal = match blocks.checked_sub(1) {
+        Some(last) => extent::map_block(device, sb, dir_inode, last)?
+            .map(|physical| physical + 1)
+            .unwrap_or_else(|| balloc::inode_goal(sb, dir_num)),
+        None => balloc::inode_goal(sb, dir_num),
+    };
+    let (physical, _) = balloc::allocate_blocks(device, sb, goal, 1)?;
+
+    let mut block = vec![0u8; block_size];
+    put_entry(&mut block, 0, inode_num, usable, name.as_bytes(), file_type);
+
+    let result = store_block(device, sb, dir_num, dir_inode, physical, &mut block)
+        .and_then(|_| extent::insert_extent(device, sb, dir_num, dir_inode, blocks, physical, 1));
+    if let Err(e) = result {
+        let _ = balloc::free_blocks(device, sb, physical, 1);
+        return Err(e);
+    }
+
+    dir_inode.set_sectors(dir_inode.sectors() + block_size as u64 / 512);
+    dir_inode.set_size(dir_inode.size() + block_size as u64);
+    Ok(())
+}
+
+/// Remove an entry from a directory
+///
+/// The entry's space is merged into the entry before it; the first entry in
+/// a block is instead marked unused.
+///
+/// # Returns
+///
+/// * `Ok(inode_num)` - Inode the removed entry pointed to
+/// * `Err(FsError::NotFound)` - No entry with that name
+pub fn remove_entry(
+    device: &dyn BlockDevice,
+    sb: &Ext4Superblock,
+    dir_num: u32,
+    dir_inode: &Inode,
+    name: &str,
+) -> Result<u32, FsError> {
+    check_modifiable(dir_inode)?;
+
+    let block_size = sb.block_size() as u64;
+    let usable = usable_size(sb);
+    let blocks = (dir_inode.size() / block_size) as u32;
+
+    for logical in 0..blocks {
+        let physical = match extent::map_block(device, sb, dir_inode, logical)? {
+            Some(physical) => physical,
+            None => continue,
+        };
+        let mut block = read_block(device, sb, physical).map_err(|_| FsError::IoError)?;
+
+        let mut prev: Option<usize> = None;
+        let mut offset = 0;
+        while offset < usable {
+            let (entry_inode, rec_len, name_len) = entry_at(&block, offset);
+            if rec_len < 8 || offset + rec_len > usable {
+                return Err(FsError::IoError);
+            }
+
+            if entry_inode != 0 && &block[offset + 8..offset + 8 + name_len] == name.as_bytes() {
+                match prev {
+                    Some(prev) => {
+                        let (_, prev_len, _) = entry_at(&block, prev);
+                        let merged = (prev_len + rec_len) as u16;
+                        block[prev + 4..prev + 6].copy_from_slice(&merged.to_le_bytes());
+                    }
+                    None => block[offset..offset + 4].fill(0),
+                }
+                store_block(device, sb, dir_num, dir_inode, physical, &mut block)?;
+                return Ok(entry_inode);
+            }
+
+            prev = Some(offset);
+            offset += rec_len;
+        }
+    }
+
+    Err(FsError::NotFound)
+}
+
+/// Write the first block of a new directory, holding "." and ".."
+pub fn write_initial_block(
+    device: &dyn BlockDevice,
+    sb: &Ext4Superblock,
+    dir_num: u32,
+    dir_inode: &Inode,
+    parent_num: u32,
+    physical_block: u64,
+) -> Result<(), FsError> {
+    let usable = usable_size(sb);
+    let mut block = vec![0u8; sb.block_size() as usize];
+
+    let dot_len = entry_size(1);
+    put_entry(&mut block, 0, dir_num, dot_len, b".", EXT4_FT_DIR);
+    put_entry(&mut block, dot_len, parent_num, usable - dot_len, b"..", EXT4_FT_DIR);
+
+    store_block(device, sb, dir_num, dir_inode, physical_block, &mut block)
+}
+
+/// Check whether a directory holds anything besides "." and ".."
+pub fn is_empty(
+    device: &dyn BlockDevice,
+    sb: &Ext4Superblock,
+    dir_inode: &Inode,
+) -> Result<bool, FsError> {
+    Ok(read_dir_entries(device, sb, dir_inode)?.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::mock_ext4::{MockExt4Device, HASHED_COLLISION, HASHED_LEAVES};
+
+    #[test]
+    fn test_entry_size_is_aligned() {
+        assert_eq!(entry_size(1), 12);
+        assert_eq!(entry_size(2), 12);
+        assert_eq!(entry_size(4), 12);
+        assert_eq!(entry_size(5), 16);
+        assert_eq!(entry_size(255), 264);
+    }
+
+    #[test]
+    fn test_valid_names() {
+        assert!(is_valid_name("notes.txt"));
+        assert!(!is_valid_name(""));
+        assert!(!is_valid_name("."));
+        assert!(!is_valid_name(".."));
+        assert!(!is_valid_name("a/b"));
+        assert!(!is_valid_name(&"x".repeat(256)));
+    }
+
+    #[test]
+    fn test_inline_directory_skips_parent() {
+        use super::super::inode::EXT4_INLINE_DATA_FL;
+
+        let device = MockExt4Device::new();
+        let sb = Ext4Superblock::from_device(&device).unwrap();
+
+        // Parent inode, then two entries filling the rest of i_block
+        let mut dir_inode = Inode::new(S_IFDIR | 0o755, 1);
+        dir_inode.i_flags = EXT4_INLINE_DATA_FL;
+        dir_inode.i_block = [0u8; 60];
+        dir_inode.i_block[..4].copy_from_slice(&2u32.to_le_bytes());
+        put_entry(&mut dir_inode.i_block, 4, 12, 16, b"notes", EXT4_FT_REG_FILE);
+        put_entry(&mut dir_inode.i_block, 20, 13, 40, b"sub", EXT4_FT_DIR);
+        dir_inode.set_size(60);
+
+        let entries = read_dir_entries(&device, &sb, &dir_inode).unwrap();
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["notes", "sub"]);
+        assert_eq!(find_entry_in_dir(&device, &sb, &dir_inode, "sub").unwrap(), Some((13, EXT4_FT_DIR)));
+        assert_eq!(check_modifiable(&dir_inode), Err(FsError::NotSupported));
+    }
+
+    /// The mock's /HASHED directory and its superblock
+    fn hashed_directory(device: &MockExt4Device) -> (Ext4Superblock, Inode) {
+        let sb = Ext4Superblock::from_device(device).unwrap();
+        let root = Inode::read_from_device(device, &sb, 2).unwrap();
+        let (inode_num, _) = find_entry_in_dir(device, &sb, &root, "HASHED").unwrap().unwrap();
+        let dir_inode = Inode::read_from_device(device, &sb, inode_num).unwrap();
+        assert!(is_indexed(&sb, &dir_inode));
+        (sb, dir_inode)
+    }
+
+    #[test]
+    fn test_hashed_lookup() {
+        let device = MockExt4Device::new();
+        let (sb, dir_inode) = hashed_directory(&device);
+        let entries = read_dir_data(&device, &sb, &dir_inode).unwrap();
+
+        // Every name is reached through the index, at the entry a scan finds
+        for name in HASHED_LEAVES.iter().flat_map(|leaf| leaf.iter()) {
+            let found = find_entry_hashed(&device, &sb, &dir_inode, name).unwrap();
+            assert!(found.is_some());
+            assert_eq!(found, find_in_entries(&entries, name));
+        }
+    }
+
+    #[test]
+    fn test_hashed_lookup_miss() {
+        let device = MockExt4Device::new();
+        let (sb, dir_inode) = hashed_directory(&device);
+
+        assert_eq!(find_entry_hashed(&device, &sb, &dir_inode, "missing").unwrap(), None);
+        assert_eq!(find_entry_in_dir(&device, &sb, &dir_inode, "missing").unwrap(), None);
+    }
+
+    #[test]
+    fn test_hashed_collision_spans_leaves() {
+        use super::super::hash::{dx_hash, DX_HASH_HALF_MD4};
+
+        let device = MockExt4Device::new();
+        let (sb, dir_inode) = hashed_directory(&device);
+
+        let [first, second] = HASHED_COLLISION;
+        let hash = |name: &str| dx_hash(name.as_bytes(), DX_HASH_HALF_MD4, &sb.s_hash_seed);
+        assert_eq!(hash(first), hash(second));
+
+        // The index leads to the first name's leaf; the second is only found
+        // by following the collision into the next one
+        let (first_inode, _) = find_entry_hashed(&device, &sb, &dir_inode, first).unwrap().unwrap();
+        let (second_inode, file_type) = find_entry_hashed(&device, &sb, &dir_inode, second).unwrap().unwrap();
+        assert_ne!(second_inode, first_inode);
+        assert_eq!(file_type, EXT4_FT_REG_FILE);
+    }
+}
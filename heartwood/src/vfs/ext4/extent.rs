@@ -5,13 +5,33 @@
 //! efficient than storing individual block pointers.
 //!
 //! The extent tree is stored in the inode's i_block field (60 bytes).
+//!
+//! The root holds at most four entries. When a node fills up it is split
+//! into a new block; when the root itself overflows, its entries move down
+//! into a new block and the tree grows one level deeper.
 
 use crate::vfs::block_device::{BlockDevice, BlockDeviceError};
 use crate::vfs::FsError;
+use super::balloc;
+use super::indirect;
+use super::inline;
+use super::block::{read_block, write_block, write_bytes};
+use super::checksum::crc32c;
 use super::superblock::Ext4Superblock;
-use super::inode::Inode;
+use super::inode::{Inode, EXT4_INLINE_DATA_FL};
+use alloc::vec;
 use alloc::vec::Vec;
 
+/// Size of the node header and of every entry that follows it
+const HEADER_SIZE: usize = 12;
+const ENTRY_SIZE: usize = 12;
+
+/// Entries that fit in the root (the 60-byte `i_block`)
+const ROOT_MAX_ENTRIES: u16 = 4;
+
+/// Extent lengths above this mark an unwritten (preallocated) extent
+const MAX_INIT_LEN: u16 = 32768;
+
 /// Extent tree header (12 bytes)
 ///
 /// Located at the start of each extent tree node.
@@ -56,6 +76,26 @@ impl ExtentHeader {
             eh_generation,
         })
     }
+
+    /// Header for an empty node
+    fn new(eh_max: u16, eh_depth: u16) -> Self {
+        Self {
+            eh_magic: Self::MAGIC,
+            eh_entries: 0,
+            eh_max,
+            eh_depth,
+            eh_generation: 0,
+        }
+    }
+
+    /// Serialize the header into the first 12 bytes of `data`
+    fn write_to(&self, data: &mut [u8]) {
+        data[0..2].copy_from_slice(&self.eh_magic.to_le_bytes());
+        data[2..4].copy_from_slice(&self.eh_entries.to_le_bytes());
+        data[4..6].copy_from_slice(&self.eh_max.to_le_bytes());
+        data[6..8].copy_from_slice(&self.eh_depth.to_le_bytes());
+        data[8..12].copy_from_slice(&self.eh_generation.to_le_bytes());
+    }
 }
 
 /// Extent tree index entry (12 bytes)
@@ -97,6 +137,26 @@ impl ExtentIndex {
     fn physical_block(&self) -> u64 {
         (self.ei_leaf_hi as u64) << 32 | self.ei_leaf_lo as u64
     }
+
+    /// Index entry pointing at the child node in `block`
+    fn new(ei_block: u32, block: u64) -> Self {
+        Self {
+            ei_block,
+            ei_leaf_lo: block as u32,
+            ei_leaf_hi: (block >> 32) as u16,
+            ei_unused: 0,
+        }
+    }
+
+    /// Serialize the entry
+    fn to_bytes(self) -> [u8; ENTRY_SIZE] {
+        let mut data = [0u8; ENTRY_SIZE];
+        data[0..4].copy_from_slice(&self.ei_block.to_le_bytes());
+        data[4..8].copy_from_slice(&self.ei_leaf_lo.to_le_bytes());
+        data[8..10].copy_from_slice(&self.ei_leaf_hi.to_le_bytes());
+        data[10..12].copy_from_slice(&self.ei_unused.to_le_bytes());
+        data
+    }
 }
 
 /// Extent leaf entry (12 bytes)
@@ -139,6 +199,43 @@ impl Extent {
         (self.ee_start_hi as u64) << 32 | self.ee_start_lo as u64
     }
 
+    /// Extent mapping `len` blocks at `logical` to `physical`
+    fn new(logical: u32, physical: u64, len: u16) -> Self {
+        Self {
+            ee_block: logical,
+            ee_len: len,
+            ee_start_hi: (physical >> 32) as u16,
+            ee_start_lo: physical as u32,
+        }
+    }
+
+    /// Serialize the extent
+    fn to_bytes(self) -> [u8; ENTRY_SIZE] {
+        let mut data = [0u8; ENTRY_SIZE];
+        data[0..4].copy_from_slice(&self.ee_block.to_le_bytes());
+        data[4..6].copy_from_slice(&self.ee_len.to_le_bytes());
+        data[6..8].copy_from_slice(&self.ee_start_hi.to_le_bytes());
+        data[8..12].copy_from_slice(&self.ee_start_lo.to_le_bytes());
+        data
+    }
+
+    /// Number of blocks covered, whether written or not
+    fn block_count(&self) -> u32 {
+        if self.ee_len > MAX_INIT_LEN {
+            (self.ee_len - MAX_INIT_LEN) as u32
+        } else {
+            self.ee_len as u32
+        }
+    }
+
+    /// Whether `next` continues this extent both logically and physically
+    fn can_absorb(&self, next: &Extent) -> bool {
+        self.ee_len <= MAX_INIT_LEN
+            && self.ee_len as u32 + next.ee_len as u32 <= MAX_INIT_LEN as u32
+            && self.ee_block + self.ee_len as u32 == next.ee_block
+            && self.physical_start() + self.ee_len as u64 == next.physical_start()
+    }
+
     /// Check if this extent covers the given logical block
     fn contains(&self, logical_block: u32) -> bool {
         logical_block >= self.ee_block && logical_block < self.ee_block + self.ee_len as u32
@@ -155,28 +252,6 @@ impl Extent {
     }
 }
 
-/// Read a block from the device
-fn read_block(
-    device: &dyn BlockDevice,
-    sb: &Ext4Superblock,
-    block_num: u64,
-) -> Result<Vec<u8>, BlockDeviceError> {
-    let block_size = sb.block_size();
-    let sector_size = device.sector_size() as u64;
-    let block_offset = block_num * block_size as u64;
-    let start_sector = block_offset / sector_size;
-    let sectors_per_block = (block_size as u64 + sector_size - 1) / sector_size;
-
-    let mut data = device.read_sectors(start_sector, sectors_per_block as u32)?;
-
-    // Trim to exact block size if we read extra sectors
-    let offset = (block_offset % sector_size) as usize;
-    data.drain(0..offset);
-    data.truncate(block_size as usize);
-
-    Ok(data)
-}
-
 /// Lookup a logical block in the extent tree
 ///
 /// # Arguments
@@ -272,6 +347,9 @@ fn lookup_extent(
 
 /// Read file data using extent tree
 ///
+/// Inodes without an extent tree are read from their inline data or
+/// ext2/ext3 block map instead.
+///
 /// # Arguments
 ///
 /// * `device` - Block device to read from
@@ -287,9 +365,11 @@ pub fn read_file_data(
     sb: &Ext4Superblock,
     inode: &Inode,
 ) -> Result<Vec<u8>, FsError> {
+    if inode.has_inline_data() {
+        return inline::read_inline_data(inode);
+    }
     if !inode.uses_extents() {
-        // TODO: Implement indirect block reading for old-style inodes
-        return Err(FsError::NotSupported);
+        return indirect::read_file_data(device, sb, inode);
     }
 
     let file_size = inode.size() as usize;
@@ -349,15 +429,23 @@ pub fn read_blocks(
     start_block: u32,
     num_blocks: u32,
 ) -> Result<Vec<u8>, FsError> {
-    if !inode.uses_extents() {
-        return Err(FsError::NotSupported);
+    let block_size = sb.block_size() as usize;
+
+    if inode.has_inline_data() {
+        // Inline data is all logical block 0; everything else reads as a hole
+        let data = inline::read_inline_data(inode)?;
+        let mut result = vec![0u8; block_size * num_blocks as usize];
+        if start_block == 0 && num_blocks > 0 {
+            let len = core::cmp::min(data.len(), result.len());
+            result[..len].copy_from_slice(&data[..len]);
+        }
+        return Ok(result);
     }
 
-    let block_size = sb.block_size() as usize;
     let mut result = Vec::with_capacity(block_size * num_blocks as usize);
 
     for logical_block in start_block..start_block + num_blocks {
-        match lookup_extent(device, sb, &inode.i_block, logical_block) {
+        match map_block(device, sb, inode, logical_block) {
             Ok(Some(physical_block)) => {
                 let block_data = read_block(device, sb, physical_block)
                     .map_err(|_| FsError::IoError)?;
@@ -373,3 +461,446 @@ pub fn read_blocks(
 
     Ok(result)
 }
+
+/// Map one logical block of a file to its physical block
+///
+/// # Returns
+///
+/// * `Ok(Some(physical_block))` - The block is allocated
+/// * `Ok(None)` - The block is a hole
+/// * `Err(FsError)` - Read error or corrupt extent tree
+/// * `Err(FsError::NotSupported)` - The inode stores its data inline
+pub fn map_block(
+    device: &dyn BlockDevice,
+    sb: &Ext4Superblock,
+    inode: &Inode,
+    logical_block: u32,
+) -> Result<Option<u64>, FsError> {
+    if inode.has_inline_data() {
+        return Err(FsError::NotSupported);
+    }
+    if !inode.uses_extents() {
+        return indirect::map_block(device, sb, inode, logical_block);
+    }
+
+    lookup_extent(device, sb, &inode.i_block, logical_block)
+        .map_err(|_| FsError::IoError)
+}
+
+/// Reset `i_block` to an empty extent tree root
+pub fn init_root(i_block: &mut [u8; 60]) {
+    i_block.fill(0);
+    ExtentHeader::new(ROOT_MAX_ENTRIES, 0).write_to(i_block);
+}
+
+/// State shared by one insertion into an extent tree
+struct TreeWriter<'a> {
+    device: &'a dyn BlockDevice,
+    sb: &'a Ext4Superblock,
+    /// Checksum seed of the owning inode (metadata_csum only)
+    csum_seed: Option<u32>,
+    /// Where to look for blocks for new nodes
+    goal: u64,
+    /// Number of node blocks allocated so far
+    new_nodes: u32,
+}
+
+impl TreeWriter<'_> {
+    /// Entries that fit in a node occupying a whole block
+    fn block_max_entries(&self) -> u16 {
+        ((self.sb.block_size() as usize - HEADER_SIZE) / ENTRY_SIZE) as u16
+    }
+
+    /// Write a non-root node to its block, adding the checksum tail
+    fn store(&self, block: u64, node: &mut [u8]) -> Result<(), FsError> {
+        if let Some(seed) = self.csum_seed {
+            let header = node_header(node)?;
+            let tail = HEADER_SIZE + header.eh_max as usize * ENTRY_SIZE;
+            let csum = crc32c(seed, &node[..tail]);
+            node[tail..tail + 4].copy_from_slice(&csum.to_le_bytes());
+        }
+
+        write_block(self.device, self.sb, block, node).map_err(|_| FsError::IoError)
+    }
+
+    /// Allocate a block for a new node
+    fn allocate_node(&mut self) -> Result<u64, FsError> {
+        let (block, _) = balloc::allocate_blocks(self.device, self.sb, self.goal, 1)?;
+        self.new_nodes += 1;
+        Ok(block)
+    }
+
+    /// Insert an extent into the subtree rooted at `node`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(None)` - The extent fit
+    /// * `Ok(Some((first_block, block)))` - `node` was split; the new sibling
+    ///   starts at logical block `first_block` and lives in `block`
+    fn insert(&mut self, node: &mut [u8], is_root: bool, extent: Extent) -> Result<Option<(u32, u64)>, FsError> {
+        let header = node_header(node)?;
+        let count = header.eh_entries as usize;
+
+        // Entries are sorted by their first logical block
+        let pos = (0..count)
+            .take_while(|&i| entry_first_block(node, i) <= extent.ee_block)
+            .count();
+
+        if header.eh_depth == 0 {
+            if pos > 0 {
+                let at = entry_offset(pos - 1);
+                let mut prev = Extent::from_bytes(&node[at..]).map_err(|_| FsError::IoError)?;
+                if prev.can_absorb(&extent) {
+                    prev.ee_len += extent.ee_len;
+                    node[at..at + ENTRY_SIZE].copy_from_slice(&prev.to_bytes());
+                    return Ok(None);
+                }
+            }
+
+            return self.insert_entry(node, is_root, header, pos, extent.to_bytes());
+        }
+
+        if count == 0 {
+            return Err(FsError::IoError);
+        }
+
+        // Descend into the child covering the extent, widening the first
+        // index if the extent starts before everything in the tree
+        let child_pos = pos.saturating_sub(1);
+        if pos == 0 {
+            node[entry_offset(0)..entry_offset(0) + 4].copy_from_slice(&extent.ee_block.to_le_bytes());
+        }
+
+        let index = ExtentIndex::from_bytes(&node[entry_offset(child_pos)..])
+            .map_err(|_| FsError::IoError)?;
+        let mut child = read_block(self.device, self.sb, index.physical_block())
+            .map_err(|_| FsError::IoError)?;
+
+        let split = self.insert(&mut child, false, extent)?;
+        self.store(index.physical_block(), &mut child)?;
+
+        match split {
+            Some((first_block, block)) => {
+                let entry = ExtentIndex::new(first_block, block).to_bytes();
+                self.insert_entry(node, is_root, header, child_pos + 1, entry)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Put a raw entry at `pos`, making room if the node is full
+    ///
+    /// A full root moves its entries down into a new block and becomes an
+    /// index over it. Any other full node is split: appending moves only the
+    /// new entry into the sibling, so files written front to back leave
+    /// their nodes packed, while insertions elsewhere split it in half.
+    fn insert_entry(
+        &mut self,
+        node: &mut [u8],
+        is_root: bool,
+        header: ExtentHeader,
+        pos: usize,
+        entry: [u8; ENTRY_SIZE],
+    ) -> Result<Option<(u32, u64)>, FsError> {
+        let count = header.eh_entries as usize;
+        if count < header.eh_max as usize {
+            insert_slot(node, count, pos, &entry);
+            return Ok(None);
+        }
+
+        let mut sibling = vec![0u8; self.sb.block_size() as usize];
+        ExtentHeader::new(self.block_max_entries(), header.eh_depth).write_to(&mut sibling);
+
+        if is_root {
+            sibling[HEADER_SIZE..entry_offset(count)].copy_from_slice(&node[HEADER_SIZE..entry_offset(count)]);
+            insert_slot(&mut sibling, count, pos, &entry);
+
+            let block = self.allocate_node()?;
+            self.store(block, &mut sibling)?;
+
+            node[HEADER_SIZE..].fill(0);
+            ExtentHeader::new(header.eh_max, header.eh_depth + 1).write_to(node);
+            let index = ExtentIndex::new(entry_first_block(&sibling, 0), block);
+            insert_slot(node, 0, 0, &index.to_bytes());
+            return Ok(None);
+        }
+
+        let keep = if pos == count { count } else { count / 2 };
+        let moved = count - keep;
+        sibling[HEADER_SIZE..entry_offset(moved)]
+            .copy_from_slice(&node[entry_offset(keep)..entry_offset(count)]);
+        set_entry_count(&mut sibling, moved);
+        set_entry_count(node, keep);
+
+        if keep < count && pos <= keep {
+            insert_slot(node, keep, pos, &entry);
+        } else {
+            insert_slot(&mut sibling, moved, pos - keep, &entry);
+        }
+
+        let block = self.allocate_node()?;
+        self.store(block, &mut sibling)?;
+
+        Ok(Some((entry_first_block(&sibling, 0), block)))
+    }
+}
+
+fn node_header(node: &[u8]) -> Result<ExtentHeader, FsError> {
+    ExtentHeader::from_bytes(node).map_err(|_| FsError::IoError)
+}
+
+fn entry_offset(index: usize) -> usize {
+    HEADER_SIZE + index * ENTRY_SIZE
+}
+
+/// First logical block of an entry (extents and indexes both start with it)
+fn entry_first_block(node: &[u8], index: usize) -> u32 {
+    let at = entry_offset(index);
+    u32::from_le_bytes([node[at], node[at + 1], node[at + 2], node[at + 3]])
+}
+
+fn set_entry_count(node: &mut [u8], count: usize) {
+    node[2..4].copy_from_slice(&(count as u16).to_le_bytes());
+}
+
+/// Insert a raw entry at `pos` in a node that has room for it
+fn insert_slot(node: &mut [u8], count: usize, pos: usize, entry: &[u8; ENTRY_SIZE]) {
+    node.copy_within(entry_offset(pos)..entry_offset(count), entry_offset(pos + 1));
+    node[entry_offset(pos)..entry_offset(pos + 1)].copy_from_slice(entry);
+    set_entry_count(node, count + 1);
+}
+
+/// Refuse to modify files whose data isn't described by an extent tree
+fn check_writable(inode: &Inode) -> Result<(), FsError> {
+    if !inode.uses_extents() || inode.i_flags & EXT4_INLINE_DATA_FL != 0 {
+        return Err(FsError::NotSupported);
+    }
+    Ok(())
+}
+
+/// Map `len` logical blocks starting at `logical_block` to `physical_block`
+///
+/// Merges with the preceding extent when the two are contiguous. New tree
+/// nodes are charged to the inode's block count; the data blocks themselves
+/// are the caller's to account for. The inode is updated in memory only.
+pub fn insert_extent(
+    device: &dyn BlockDevice,
+    sb: &Ext4Superblock,
+    inode_num: u32,
+    inode: &mut Inode,
+    logical_block: u32,
+    physical_block: u64,
+    len: u32,
+) -> Result<(), FsError> {
+    check_writable(inode)?;
+    if len == 0 || len > MAX_INIT_LEN as u32 {
+        return Err(FsError::IoError);
+    }
+
+    let mut writer = TreeWriter {
+        device,
+        sb,
+        csum_seed: sb
+            .has_metadata_csum()
+            .then(|| sb.inode_checksum_seed(inode_num, inode.i_generation)),
+        goal: physical_block,
+        new_nodes: 0,
+    };
+
+    // The root grows a level instead of splitting, so nothing comes back
+    let mut root = inode.i_block;
+    let extent = Extent::new(logical_block, physical_block, len as u16);
+    writer.insert(&mut root, true, extent)?;
+
+    inode.i_block = root;
+    let sectors_per_block = sb.block_size() as u64 / 512;
+    inode.set_sectors(inode.sectors() + writer.new_nodes as u64 * sectors_per_block);
+
+    Ok(())
+}
+
+/// Free every block of a subtree, returning how many were freed
+fn free_subtree(device: &dyn BlockDevice, sb: &Ext4Superblock, node: &[u8]) -> Result<u64, FsError> {
+    let header = node_header(node)?;
+    let mut freed = 0;
+
+    for i in 0..header.eh_entries as usize {
+        let at = entry_offset(i);
+        if header.eh_depth == 0 {
+            let extent = Extent::from_bytes(&node[at..]).map_err(|_| FsError::IoError)?;
+            let count = extent.block_count() as u64;
+            if count > 0 {
+                balloc::free_blocks(device, sb, extent.physical_start(), count)?;
+                freed += count;
+            }
+        } else {
+            let index = ExtentIndex::from_bytes(&node[at..]).map_err(|_| FsError::IoError)?;
+            let child = read_block(device, sb, index.physical_block())
+                .map_err(|_| FsError::IoError)?;
+            freed += free_subtree(device, sb, &child)?;
+            balloc::free_blocks(device, sb, index.physical_block(), 1)?;
+            freed += 1;
+        }
+    }
+
+    Ok(freed)
+}
+
+/// Free all data and tree blocks of a file, leaving it empty
+///
+/// The inode is updated in memory only (empty root, reduced block count).
+pub fn free_tree(
+    device: &dyn BlockDevice,
+    sb: &Ext4Superblock,
+    inode: &mut Inode,
+) -> Result<(), FsError> {
+    check_writable(inode)?;
+
+    let freed = free_subtree(device, sb, &inode.i_block)?;
+    init_root(&mut inode.i_block);
+
+    let sectors_per_block = sb.block_size() as u64 / 512;
+    inode.set_sectors(inode.sectors().saturating_sub(freed * sectors_per_block));
+
+    Ok(())
+}
+
+/// Allocate blocks for `data`, write it, and map it into `inode`
+fn fill_tree(
+    device: &dyn BlockDevice,
+    sb: &Ext4Superblock,
+    inode_num: u32,
+    inode: &mut Inode,
+    data: &[u8],
+) -> Result<(), FsError> {
+    let block_size = sb.block_size() as usize;
+    let sectors_per_block = block_size as u64 / 512;
+    let total_blocks = data.len().div_ceil(block_size) as u32;
+
+    let mut logical = 0;
+    let mut goal = balloc::inode_goal(sb, inode_num);
+
+    while logical < total_blocks {
+        let (start, count) = balloc::allocate_blocks(device, sb, goal, total_blocks - logical)?;
+
+        if let Err(e) = insert_extent(device, sb, inode_num, inode, logical, start, count) {
+            let _ = balloc::free_blocks(device, sb, start, count as u64);
+            return Err(e);
+        }
+        inode.set_sectors(inode.sectors() + count as u64 * sectors_per_block);
+
+        // Pad the final block with zeros
+        let from = logical as usize * block_size;
+        let to = (from + count as usize * block_size).min(data.len());
+        let mut chunk = data[from..to].to_vec();
+        chunk.resize(count as usize * block_size, 0);
+        write_bytes(device, start * block_size as u64, &chunk)
+            .map_err(|_| FsError::IoError)?;
+
+        logical += count;
+        goal = start + count as u64;
+    }
+
+    Ok(())
+}
+
+/// Build the extent tree for a file's new contents
+///
+/// Allocates and writes new data blocks without touching the old ones, and
+/// returns a copy of `inode` describing them. The caller writes that inode
+/// and then releases the old blocks with [`free_tree`], so the file on disk
+/// always points at complete data.
+pub fn write_file_data(
+    device: &dyn BlockDevice,
+    sb: &Ext4Superblock,
+    inode_num: u32,
+    inode: &Inode,
+    data: &[u8],
+) -> Result<Inode, FsError> {
+    check_writable(inode)?;
+
+    let mut new_inode = inode.clone();
+    init_root(&mut new_inode.i_block);
+
+    // Only an extended attribute block survives from the old block count
+    let has_xattr_block = inode.i_file_acl_lo != 0 || inode.i_file_acl_high != 0;
+    let sectors_per_block = sb.block_size() as u64 / 512;
+    new_inode.set_sectors(if has_xattr_block { sectors_per_block } else { 0 });
+
+    if let Err(e) = fill_tree(device, sb, inode_num, &mut new_inode, data) {
+        let _ = free_tree(device, sb, &mut new_inode);
+        return Err(e);
+    }
+
+    new_inode.set_size(data.len() as u64);
+    Ok(new_inode)
+}
+
+/// Write `data` into a file at byte `offset`, in place
+///
+/// Mapped blocks are updated only where touched; holes and blocks past the
+/// end of file are allocated as the write reaches them. Bytes between the
+/// old end of file and `offset` are zeroed, since the old last block may
+/// hold stale data past it. The inode is updated in memory only.
+pub fn write_range(
+    device: &dyn BlockDevice,
+    sb: &Ext4Superblock,
+    inode_num: u32,
+    inode: &mut Inode,
+    offset: u64,
+    data: &[u8],
+) -> Result<(), FsError> {
+    check_writable(inode)?;
+    if data.is_empty() {
+        return Ok(());
+    }
+
+    let block_size = sb.block_size() as u64;
+    let sectors_per_block = block_size / 512;
+    let end = offset.checked_add(data.len() as u64).ok_or(FsError::OutOfSpace)?;
+    let last = u32::try_from((end - 1) / block_size).map_err(|_| FsError::OutOfSpace)?;
+    let old_size = inode.size();
+    let from = offset.min(old_size);
+    let mut goal = balloc::inode_goal(sb, inode_num);
+
+    for logical in (from / block_size) as u32..=last {
+        let base = logical as u64 * block_size;
+        let limit = base + block_size;
+
+        let (physical, mut bytes) = match map_block(device, sb, inode, logical)? {
+            Some(physical) if base < from || limit > end => {
+                let bytes = read_block(device, sb, physical).map_err(|_| FsError::IoError)?;
+                (physical, bytes)
+            }
+            Some(physical) => (physical, vec![0u8; block_size as usize]),
+            // Holes wholly before the data already read back as zeros
+            None if limit <= offset => continue,
+            None => {
+                let (physical, _) = balloc::allocate_blocks(device, sb, goal, 1)?;
+                if let Err(e) = insert_extent(device, sb, inode_num, inode, logical, physical, 1) {
+                    let _ = balloc::free_blocks(device, sb, physical, 1);
+                    return Err(e);
+                }
+                inode.set_sectors(inode.sectors() + sectors_per_block);
+                (physical, vec![0u8; block_size as usize])
+            }
+        };
+
+        let (gap_start, gap_end) = (old_size.max(base), offset.min(limit));
+        if gap_start < gap_end {
+            bytes[(gap_start - base) as usize..(gap_end - base) as usize].fill(0);
+        }
+
+        let (data_start, data_end) = (offset.max(base), end.min(limit));
+        if data_start < data_end {
+            bytes[(data_start - base) as usize..(data_end - base) as usize]
+                .copy_from_slice(&data[(data_start - offset) as usize..(data_end - offset) as usize]);
+        }
+
+        write_bytes(device, physical * block_size, &bytes).map_err(|_| FsError::IoError)?;
+        goal = physical + 1;
+    }
+
+    inode.set_size(old_size.max(end));
+    Ok(())
+}
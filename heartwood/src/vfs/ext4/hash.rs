@@ -0,0 +1,248 @@
+//! ext4 Directory Name Hashing
+//!
+//! Hashed (htree) directories index their entries by a 32-bit hash of the
+//! name. Which function produced the hashes is recorded per directory (in
+//! the dx_root) and, for the older functions, whether name bytes were
+//! treated as signed or unsigned chars is recorded in the superblock.
+//!
+//! Supported: legacy, half-MD4 and TEA, each signed and unsigned.
+//! SipHash (casefolded directories) is not.
+
+use super::superblock::{sb_flags, Ext4Superblock};
+
+/// Hash versions (`dx_root_info.hash_version`)
+pub const DX_HASH_LEGACY: u8 = 0;
+pub const DX_HASH_HALF_MD4: u8 = 1;
+pub const DX_HASH_TEA: u8 = 2;
+pub const DX_HASH_LEGACY_UNSIGNED: u8 = 3;
+pub const DX_HASH_HALF_MD4_UNSIGNED: u8 = 4;
+pub const DX_HASH_TEA_UNSIGNED: u8 = 5;
+
+/// Buffer used when the superblock's hash seed is all zeros
+const DEFAULT_SEED: [u32; 4] = [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476];
+
+/// Hash reserved to mean "end of directory" on 32-bit readdir cookies
+const HTREE_EOF_32BIT: u32 = 0x7fff_ffff;
+
+/// Hash version to use for a directory whose dx_root records `root_version`
+///
+/// The signed variants switch to unsigned when the volume was created on
+/// a platform with unsigned chars.
+pub fn effective_version(sb: &Ext4Superblock, root_version: u8) -> u8 {
+    if root_version <= DX_HASH_TEA && sb.s_flags & sb_flags::UNSIGNED_HASH != 0 {
+        root_version + 3
+    } else {
+        root_version
+    }
+}
+
+/// Major hash of a directory entry name
+///
+/// # Returns
+///
+/// * `Some(hash)` - The hash, with the low (collision) bit clear
+/// * `None` - Unsupported hash version
+pub fn dx_hash(name: &[u8], version: u8, seed: &[u32; 4]) -> Option<u32> {
+    let mut buf = if seed.iter().any(|&word| word != 0) { *seed } else { DEFAULT_SEED };
+
+    let hash = match version {
+        DX_HASH_LEGACY => legacy_hash(name, true),
+        DX_HASH_LEGACY_UNSIGNED => legacy_hash(name, false),
+        DX_HASH_HALF_MD4 | DX_HASH_HALF_MD4_UNSIGNED => {
+            let signed = version == DX_HASH_HALF_MD4;
+            for start in (0..name.len()).step_by(32) {
+                let input = str_to_hash_buf::<8>(&name[start..], signed);
+                half_md4_transform(&mut buf, &input);
+            }
+            buf[1]
+        }
+        DX_HASH_TEA | DX_HASH_TEA_UNSIGNED => {
+            let signed = version == DX_HASH_TEA;
+            for start in (0..name.len()).step_by(16) {
+                let input = str_to_hash_buf::<4>(&name[start..], signed);
+                tea_transform(&mut buf, &input);
+            }
+            buf[0]
+        }
+        _ => return None,
+    };
+
+    let hash = hash & !1;
+    if hash == HTREE_EOF_32BIT << 1 {
+        Some((HTREE_EOF_32BIT - 1) << 1)
+    } else {
+        Some(hash)
+    }
+}
+
+/// Widen a name byte the way the C code's `char` would be
+fn char_value(byte: u8, signed: bool) -> u32 {
+    if signed {
+        byte as i8 as i32 as u32
+    } else {
+        byte as u32
+    }
+}
+
+/// The original ext3 hash
+fn legacy_hash(name: &[u8], signed: bool) -> u32 {
+    let (mut hash0, mut hash1) = (0x12a3_fe2du32, 0x37ab_e8f9u32);
+
+    for &byte in name {
+        let mixed = char_value(byte, signed).wrapping_mul(7_152_373);
+        let mut hash = hash1.wrapping_add(hash0 ^ mixed);
+        if hash & 0x8000_0000 != 0 {
+            hash = hash.wrapping_sub(0x7fff_ffff);
+        }
+        hash1 = hash0;
+        hash0 = hash;
+    }
+
+    hash0 << 1
+}
+
+/// Pack the first `N * 4` bytes of `rest` into words
+///
+/// Unused words and bytes are padded with the length of `rest` (the name
+/// from this chunk to its end).
+fn str_to_hash_buf<const N: usize>(rest: &[u8], signed: bool) -> [u32; N] {
+    let len = rest.len() as u32;
+    let mut pad = len | (len << 8);
+    pad |= pad << 16;
+
+    let mut out = [pad; N];
+    let mut val = pad;
+    let mut word = 0;
+
+    for (i, &byte) in rest.iter().take(N * 4).enumerate() {
+        val = char_value(byte, signed).wrapping_add(val << 8);
+        if i % 4 == 3 {
+            out[word] = val;
+            word += 1;
+            val = pad;
+        }
+    }
+    if word < N {
+        out[word] = val;
+    }
+
+    out
+}
+
+fn half_md4_transform(buf: &mut [u32; 4], input: &[u32; 8]) {
+    const K2: u32 = 0o13240474631;
+    const K3: u32 = 0o15666365641;
+
+    fn f(x: u32, y: u32, z: u32) -> u32 { z ^ (x & (y ^ z)) }
+    fn g(x: u32, y: u32, z: u32) -> u32 { (x & y).wrapping_add((x ^ y) & z) }
+    fn h(x: u32, y: u32, z: u32) -> u32 { x ^ y ^ z }
+
+    fn round(func: fn(u32, u32, u32) -> u32, a: &mut u32, b: u32, c: u32, d: u32, x: u32, s: u32) {
+        *a = a.wrapping_add(func(b, c, d)).wrapping_add(x).rotate_left(s);
+    }
+
+    let [mut a, mut b, mut c, mut d] = *buf;
+    let x = input;
+
+    // Round 1
+    round(f, &mut a, b, c, d, x[0], 3);
+    round(f, &mut d, a, b, c, x[1], 7);
+    round(f, &mut c, d, a, b, x[2], 11);
+    round(f, &mut b, c, d, a, x[3], 19);
+    round(f, &mut a, b, c, d, x[4], 3);
+    round(f, &mut d, a, b, c, x[5], 7);
+    round(f, &mut c, d, a, b, x[6], 11);
+    round(f, &mut b, c, d, a, x[7], 19);
+
+    // Round 2
+    round(g, &mut a, b, c, d, x[1].wrapping_add(K2), 3);
+    round(g, &mut d, a, b, c, x[3].wrapping_add(K2), 5);
+    round(g, &mut c, d, a, b, x[5].wrapping_add(K2), 9);
+    round(g, &mut b, c, d, a, x[7].wrapping_add(K2), 13);
+    round(g, &mut a, b, c, d, x[0].wrapping_add(K2), 3);
+    round(g, &mut d, a, b, c, x[2].wrapping_add(K2), 5);
+    round(g, &mut c, d, a, b, x[4].wrapping_add(K2), 9);
+    round(g, &mut b, c, d, a, x[6].wrapping_add(K2), 13);
+
+    // Round 3
+    round(h, &mut a, b, c, d, x[3].wrapping_add(K3), 3);
+    round(h, &mut d, a, b, c, x[7].wrapping_add(K3), 9);
+    round(h, &mut c, d, a, b, x[2].wrapping_add(K3), 11);
+    round(h, &mut b, c, d, a, x[6].wrapping_add(K3), 15);
+    round(h, &mut a, b, c, d, x[1].wrapping_add(K3), 3);
+    round(h, &mut d, a, b, c, x[5].wrapping_add(K3), 9);
+    round(h, &mut c, d, a, b, x[0].wrapping_add(K3), 11);
+    round(h, &mut b, c, d, a, x[4].wrapping_add(K3), 15);
+
+    buf[0] = buf[0].wrapping_add(a);
+    buf[1] = buf[1].wrapping_add(b);
+    buf[2] = buf[2].wrapping_add(c);
+    buf[3] = buf[3].wrapping_add(d);
+}
+
+fn tea_transform(buf: &mut [u32; 4], input: &[u32; 4]) {
+    const DELTA: u32 = 0x9E37_79B9;
+
+    let (mut b0, mut b1) = (buf[0], buf[1]);
+    let [a, b, c, d] = *input;
+    let mut sum = 0u32;
+
+    for _ in 0..16 {
+        sum = sum.wrapping_add(DELTA);
+        b0 = b0.wrapping_add(
+            ((b1 << 4).wrapping_add(a)) ^ b1.wrapping_add(sum) ^ ((b1 >> 5).wrapping_add(b)),
+        );
+        b1 = b1.wrapping_add(
+            ((b0 << 4).wrapping_add(c)) ^ b0.wrapping_add(sum) ^ ((b0 >> 5).wrapping_add(d)),
+        );
+    }
+
+    buf[0] = buf[0].wrapping_add(b0);
+    buf[1] = buf[1].wrapping_add(b1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NO_SEED: [u32; 4] = [0; 4];
+    const LONG_NAME: &[u8] = b"a-much-longer-file-name-that-spans-two-md4-chunks.txt";
+    const CAFE: &[u8] = "caf\u{e9}".as_bytes();
+
+    // Reference values from `debugfs -R "dx_hash -h <version> <name>"`
+
+    #[test]
+    fn test_legacy_hash() {
+        assert_eq!(dx_hash(b"hello", DX_HASH_LEGACY, &NO_SEED), Some(0x3225_2546));
+        assert_eq!(dx_hash(b"lost+found", DX_HASH_LEGACY, &NO_SEED), Some(0x5e2a_ba24));
+        assert_eq!(dx_hash(CAFE, DX_HASH_LEGACY, &NO_SEED), Some(0x96ca_5a2c));
+        assert_eq!(dx_hash(CAFE, DX_HASH_LEGACY_UNSIGNED, &NO_SEED), Some(0x6dde_4230));
+    }
+
+    #[test]
+    fn test_half_md4_hash() {
+        assert_eq!(dx_hash(b"a", DX_HASH_HALF_MD4, &NO_SEED), Some(0xd5fa_7d7a));
+        assert_eq!(dx_hash(b"hello", DX_HASH_HALF_MD4, &NO_SEED), Some(0x1746_da32));
+        assert_eq!(dx_hash(LONG_NAME, DX_HASH_HALF_MD4, &NO_SEED), Some(0xa56b_0ddc));
+        assert_eq!(dx_hash(CAFE, DX_HASH_HALF_MD4, &NO_SEED), Some(0xfb9c_5e5c));
+        assert_eq!(dx_hash(CAFE, DX_HASH_HALF_MD4_UNSIGNED, &NO_SEED), Some(0x9d72_aed6));
+
+        // Seed 01234567-89ab-cdef-0123-456789abcdef
+        let seed = [0x6745_2301, 0xefcd_ab89, 0x6745_2301, 0xefcd_ab89];
+        assert_eq!(dx_hash(b"hello", DX_HASH_HALF_MD4, &seed), Some(0xa26e_4a80));
+    }
+
+    #[test]
+    fn test_tea_hash() {
+        assert_eq!(dx_hash(b"a", DX_HASH_TEA, &NO_SEED), Some(0x6d0e_a4c0));
+        assert_eq!(dx_hash(b"lost+found", DX_HASH_TEA, &NO_SEED), Some(0x2dbf_9e80));
+        assert_eq!(dx_hash(LONG_NAME, DX_HASH_TEA, &NO_SEED), Some(0x4557_49c6));
+        assert_eq!(dx_hash(CAFE, DX_HASH_TEA, &NO_SEED), Some(0x1058_42ea));
+        assert_eq!(dx_hash(CAFE, DX_HASH_TEA_UNSIGNED, &NO_SEED), Some(0x6621_f032));
+    }
+
+    #[test]
+    fn test_unknown_version() {
+        assert_eq!(dx_hash(b"hello", 6, &NO_SEED), None);
+    }
+}
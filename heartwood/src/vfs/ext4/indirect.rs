@@ -0,0 +1,293 @@
+//! ext2/ext3 Block Maps
+//!
+//! Before extents, `i_block` held fifteen 32-bit block pointers: twelve
+//! direct pointers, then one each to a single, double and triple indirect
+//! block. An indirect block is simply an array of further block pointers.
+//! A zero pointer at any level is a hole.
+//!
+//! Volumes upgraded from ext2/ext3 keep their old files in this format, so
+//! these are read-only here; new files always get an extent tree.
+
+use crate::vfs::block_device::BlockDevice;
+use crate::vfs::FsError;
+use super::block::read_block;
+use super::superblock::Ext4Superblock;
+use super::inode::Inode;
+use alloc::vec::Vec;
+
+/// Number of direct pointers at the start of `i_block`
+const DIRECT_BLOCKS: usize = 12;
+
+/// `i_block` slots of the single, double and triple indirect blocks, with
+/// the number of indirect levels below each
+const INDIRECT_ROOTS: [(usize, u32); 3] = [(12, 1), (13, 2), (14, 3)];
+
+fn pointer(data: &[u8], index: usize) -> u32 {
+    let at = index * 4;
+    u32::from_le_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]])
+}
+
+fn pointers_per_block(sb: &Ext4Superblock) -> u64 {
+    sb.block_size() as u64 / 4
+}
+
+/// Map one logical block of a file to its physical block
+///
+/// # Returns
+///
+/// * `Ok(Some(physical_block))` - The block is allocated
+/// * `Ok(None)` - The block is a hole (or beyond the triple indirect range)
+/// * `Err(FsError)` - Read error
+pub fn map_block(
+    device: &dyn BlockDevice,
+    sb: &Ext4Superblock,
+    inode: &Inode,
+    logical_block: u32,
+) -> Result<Option<u64>, FsError> {
+    let per_block = pointers_per_block(sb);
+    let mut index = logical_block as u64;
+
+    if index < DIRECT_BLOCKS as u64 {
+        return Ok(non_zero(pointer(&inode.i_block, index as usize)));
+    }
+    index -= DIRECT_BLOCKS as u64;
+
+    // Find which indirect tree covers the block
+    for (slot, depth) in INDIRECT_ROOTS {
+        let span = per_block.pow(depth);
+        if index >= span {
+            index -= span;
+            continue;
+        }
+
+        let mut block = pointer(&inode.i_block, slot);
+        for level in (0..depth).rev() {
+            if block == 0 {
+                return Ok(None);
+            }
+            let data = read_block(device, sb, block as u64)
+                .map_err(|_| FsError::IoError)?;
+            let entry = (index / per_block.pow(level)) % per_block;
+            block = pointer(&data, entry as usize);
+        }
+        return Ok(non_zero(block));
+    }
+
+    Ok(None)
+}
+
+/// Read a whole file mapped by indirect blocks
+///
+/// Each indirect block is read once, rather than once per data block as
+/// repeated [`map_block`] calls would.
+pub fn read_file_data(
+    device: &dyn BlockDevice,
+    sb: &Ext4Superblock,
+    inode: &Inode,
+) -> Result<Vec<u8>, FsError> {
+    // A size beyond what the block map can address means a corrupt inode
+    let per_block = pointers_per_block(sb);
+    let max_blocks = INDIRECT_ROOTS
+        .iter()
+        .fold(DIRECT_BLOCKS as u64, |total, &(_, depth)| total.saturating_add(per_block.pow(depth)));
+    if inode.size() > max_blocks.saturating_mul(sb.block_size() as u64) {
+        return Err(FsError::IoError);
+    }
+
+    // The Vec grows as data is read; `i_size` alone doesn't earn an allocation
+    let file_size = inode.size() as usize;
+    let mut result = Vec::new();
+
+    for slot in 0..DIRECT_BLOCKS {
+        if result.len() >= file_size {
+            break;
+        }
+        read_tree(device, sb, pointer(&inode.i_block, slot), 0, file_size, &mut result)?;
+    }
+
+    for (slot, depth) in INDIRECT_ROOTS {
+        if result.len() >= file_size {
+            break;
+        }
+        read_tree(device, sb, pointer(&inode.i_block, slot), depth, file_size, &mut result)?;
+    }
+
+    // Trailing holes
+    let remaining = file_size - result.len();
+    zero_fill(&mut result, remaining)?;
+    Ok(result)
+}
+
+/// Append the data under one pointer to `out`, stopping at `file_size`
+///
+/// `depth` is the number of indirect levels below `block`; zero means
+/// `block` is a data block.
+fn read_tree(
+    device: &dyn BlockDevice,
+    sb: &Ext4Superblock,
+    block: u32,
+    depth: u32,
+    file_size: usize,
+    out: &mut Vec<u8>,
+) -> Result<(), FsError> {
+    let block_size = sb.block_size() as usize;
+    let per_block = pointers_per_block(sb);
+    let wanted = file_size - out.len();
+
+    if block == 0 {
+        // A hole covers everything the missing subtree would have mapped
+        let span = per_block.pow(depth).saturating_mul(block_size as u64);
+        let zeros = core::cmp::min(span, wanted as u64) as usize;
+        return zero_fill(out, zeros);
+    }
+
+    let data = read_block(device, sb, block as u64)
+        .map_err(|_| FsError::IoError)?;
+
+    if depth == 0 {
+        out.extend_from_slice(&data[..core::cmp::min(block_size, wanted)]);
+        return Ok(());
+    }
+
+    for entry in 0..per_block as usize {
+        if out.len() >= file_size {
+            break;
+        }
+        read_tree(device, sb, pointer(&data, entry), depth - 1, file_size, out)?;
+    }
+
+    Ok(())
+}
+
+/// Append `count` zeros for a hole, failing rather than aborting if the
+/// allocation can't be made
+fn zero_fill(out: &mut Vec<u8>, count: usize) -> Result<(), FsError> {
+    out.try_reserve(count).map_err(|_| FsError::IoError)?;
+    out.resize(out.len() + count, 0);
+    Ok(())
+}
+
+fn non_zero(block: u32) -> Option<u64> {
+    if block == 0 {
+        None
+    } else {
+        Some(block as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::mock_ext4::MockExt4Device;
+    use super::super::block::write_block;
+    use super::super::inode::S_IFREG;
+    use alloc::vec;
+
+    /// Blocks past everything the mock volume uses
+    const SCRATCH: u32 = 40;
+
+    fn fill_block(device: &MockExt4Device, sb: &Ext4Superblock, block: u32, byte: u8) {
+        let data = vec![byte; sb.block_size() as usize];
+        write_block(device, sb, block as u64, &data).unwrap();
+    }
+
+    fn pointer_block(device: &MockExt4Device, sb: &Ext4Superblock, block: u32, pointers: &[(usize, u32)]) {
+        let mut data = vec![0u8; sb.block_size() as usize];
+        for &(index, target) in pointers {
+            data[index * 4..index * 4 + 4].copy_from_slice(&target.to_le_bytes());
+        }
+        write_block(device, sb, block as u64, &data).unwrap();
+    }
+
+    fn mapped_inode(i_block: &[(usize, u32)], size: u64) -> Inode {
+        let mut inode = Inode::new(S_IFREG | 0o644, 1);
+        inode.i_flags = 0;
+        inode.i_block = [0u8; 60];
+        for &(slot, target) in i_block {
+            inode.i_block[slot * 4..slot * 4 + 4].copy_from_slice(&target.to_le_bytes());
+        }
+        inode.set_size(size);
+        inode
+    }
+
+    #[test]
+    fn test_direct_and_single_indirect() {
+        let device = MockExt4Device::new_writable();
+        let sb = Ext4Superblock::from_device(&device).unwrap();
+        let bs = sb.block_size() as usize;
+
+        // Block 0 direct, blocks 1..12 holes, block 12 via the single indirect
+        fill_block(&device, &sb, SCRATCH, 0xAA);
+        fill_block(&device, &sb, SCRATCH + 1, 0xBB);
+        pointer_block(&device, &sb, SCRATCH + 2, &[(0, SCRATCH + 1)]);
+        let inode = mapped_inode(&[(0, SCRATCH), (12, SCRATCH + 2)], (12 * bs + 10) as u64);
+
+        let data = read_file_data(&device, &sb, &inode).unwrap();
+        assert_eq!(data.len(), 12 * bs + 10);
+        assert!(data[..bs].iter().all(|&b| b == 0xAA));
+        assert!(data[bs..12 * bs].iter().all(|&b| b == 0));
+        assert!(data[12 * bs..].iter().all(|&b| b == 0xBB));
+
+        assert_eq!(map_block(&device, &sb, &inode, 0), Ok(Some(SCRATCH as u64)));
+        assert_eq!(map_block(&device, &sb, &inode, 5), Ok(None));
+        assert_eq!(map_block(&device, &sb, &inode, 12), Ok(Some(SCRATCH as u64 + 1)));
+    }
+
+    #[test]
+    fn test_double_indirect() {
+        let device = MockExt4Device::new_writable();
+        let sb = Ext4Superblock::from_device(&device).unwrap();
+        let bs = sb.block_size() as usize;
+        let per_block = bs / 4;
+
+        // The second block under the double indirect's second pointer block
+        let logical = 12 + per_block + per_block + 1;
+        fill_block(&device, &sb, SCRATCH, 0xCC);
+        pointer_block(&device, &sb, SCRATCH + 1, &[(1, SCRATCH)]);
+        pointer_block(&device, &sb, SCRATCH + 2, &[(1, SCRATCH + 1)]);
+        let inode = mapped_inode(&[(13, SCRATCH + 2)], ((logical + 1) * bs) as u64);
+
+        assert_eq!(map_block(&device, &sb, &inode, logical as u32), Ok(Some(SCRATCH as u64)));
+        assert_eq!(map_block(&device, &sb, &inode, logical as u32 - 1), Ok(None));
+
+        let data = read_file_data(&device, &sb, &inode).unwrap();
+        assert!(data[..logical * bs].iter().all(|&b| b == 0));
+        assert!(data[logical * bs..].iter().all(|&b| b == 0xCC));
+    }
+
+    #[test]
+    fn test_triple_indirect() {
+        let device = MockExt4Device::new_writable();
+        let sb = Ext4Superblock::from_device(&device).unwrap();
+        let bs = sb.block_size() as usize;
+        let per_block = bs / 4;
+
+        // Triple indirect [1] -> [2] -> [3] -> data
+        let logical = 12 + per_block + per_block * per_block
+            + per_block * per_block + 2 * per_block + 3;
+        fill_block(&device, &sb, SCRATCH, 0xDD);
+        pointer_block(&device, &sb, SCRATCH + 1, &[(3, SCRATCH)]);
+        pointer_block(&device, &sb, SCRATCH + 2, &[(2, SCRATCH + 1)]);
+        pointer_block(&device, &sb, SCRATCH + 3, &[(1, SCRATCH + 2)]);
+        let inode = mapped_inode(&[(14, SCRATCH + 3)], ((logical + 1) * bs) as u64);
+
+        assert_eq!(map_block(&device, &sb, &inode, logical as u32), Ok(Some(SCRATCH as u64)));
+        assert_eq!(map_block(&device, &sb, &inode, logical as u32 - 1), Ok(None));
+        assert_eq!(map_block(&device, &sb, &inode, logical as u32 + per_block as u32), Ok(None));
+
+        let data = read_file_data(&device, &sb, &inode).unwrap();
+        assert_eq!(data.len(), (logical + 1) * bs);
+        assert!(data[logical * bs..].iter().all(|&b| b == 0xDD));
+        assert!(data[(logical - 1) * bs..logical * bs].iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn test_impossible_size_is_rejected() {
+        let device = MockExt4Device::new_writable();
+        let sb = Ext4Superblock::from_device(&device).unwrap();
+
+        // Far more than twelve direct and three indirect trees can map
+        let inode = mapped_inode(&[], u64::MAX / 2);
+        assert_eq!(read_file_data(&device, &sb, &inode), Err(FsError::IoError));
+    }
+}
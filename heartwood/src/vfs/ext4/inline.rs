@@ -0,0 +1,163 @@
+//! ext4 Inline Data
+//!
+//! With the inline_data feature, files and directories small enough to fit
+//! inside the inode have no data blocks at all. The first 60 bytes live in
+//! `i_block`; anything beyond that lives in the value of the `system.data`
+//! extended attribute, stored in the inode's extra space.
+//!
+//! In-inode xattr layout (after `i_extra_isize`):
+//!
+//! ```text
+//! magic (4 bytes, 0xEA020000)
+//! entry: name_len u8, name_index u8, value_offs u16, value_inum u32,
+//!        value_size u32, hash u32, name (padded to 4 bytes)
+//! ...
+//! four zero bytes
+//! values (value_offs counts from the first entry)
+//! ```
+
+use crate::vfs::FsError;
+use super::inode::Inode;
+use alloc::vec::Vec;
+
+/// Magic number at the start of the in-inode xattr area
+const XATTR_MAGIC: u32 = 0xEA02_0000;
+
+/// Size of the fixed part of an xattr entry
+const XATTR_ENTRY_SIZE: usize = 16;
+
+/// Name index of the `system.` namespace
+const XATTR_INDEX_SYSTEM: u8 = 7;
+
+/// Name (without namespace prefix) of the attribute holding the data tail
+const INLINE_DATA_NAME: &[u8] = b"data";
+
+/// Bytes of inline data kept in `i_block`
+pub const INLINE_BLOCK_SIZE: usize = 60;
+
+/// Read the contents of an inline-data inode
+///
+/// # Returns
+///
+/// * `Ok(Vec<u8>)` - File contents (exactly `inode.size()` bytes)
+/// * `Err(FsError::IoError)` - The size exceeds what the inode holds
+pub fn read_inline_data(inode: &Inode) -> Result<Vec<u8>, FsError> {
+    let file_size = inode.size() as usize;
+    let head = core::cmp::min(file_size, INLINE_BLOCK_SIZE);
+
+    let mut result = Vec::with_capacity(file_size);
+    result.extend_from_slice(&inode.i_block[..head]);
+
+    if file_size > INLINE_BLOCK_SIZE {
+        let tail = find_xattr(&inode.inline_xattrs, XATTR_INDEX_SYSTEM, INLINE_DATA_NAME)
+            .ok_or(FsError::IoError)?;
+        let wanted = file_size - INLINE_BLOCK_SIZE;
+        if tail.len() < wanted {
+            return Err(FsError::IoError);
+        }
+        result.extend_from_slice(&tail[..wanted]);
+    }
+
+    Ok(result)
+}
+
+/// Find the value of an in-inode extended attribute
+///
+/// Returns `None` if the area is missing, malformed, or has no such entry.
+fn find_xattr<'a>(area: &'a [u8], name_index: u8, name: &[u8]) -> Option<&'a [u8]> {
+    if area.len() < 4 || read_u32(area, 0) != XATTR_MAGIC {
+        return None;
+    }
+
+    let entries = &area[4..];
+    let mut offset = 0;
+
+    while offset + XATTR_ENTRY_SIZE <= entries.len() && read_u32(entries, offset) != 0 {
+        let name_len = entries[offset] as usize;
+        let entry_index = entries[offset + 1];
+        let value_offs = u16::from_le_bytes([entries[offset + 2], entries[offset + 3]]) as usize;
+        let value_inum = read_u32(entries, offset + 4);
+        let value_size = read_u32(entries, offset + 8) as usize;
+
+        let name_start = offset + XATTR_ENTRY_SIZE;
+        let entry_name = entries.get(name_start..name_start + name_len)?;
+
+        if entry_index == name_index && entry_name == name {
+            // Values stored in a separate inode (ea_inode) can't hold inline data
+            if value_inum != 0 {
+                return None;
+            }
+            return entries.get(value_offs..value_offs + value_size);
+        }
+
+        offset = (name_start + name_len + 3) & !3;
+    }
+
+    None
+}
+
+fn read_u32(data: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    /// Build an in-inode xattr area holding a single `system.data` value
+    fn xattr_area(value: &[u8]) -> Vec<u8> {
+        let mut area = vec![0u8; 96];
+        area[0..4].copy_from_slice(&XATTR_MAGIC.to_le_bytes());
+
+        // One entry (16 + 4 byte name), then the terminator, then the value
+        let value_offs = 20 + 4;
+        let entry = &mut area[4..];
+        entry[0] = INLINE_DATA_NAME.len() as u8;
+        entry[1] = XATTR_INDEX_SYSTEM;
+        entry[2..4].copy_from_slice(&(value_offs as u16).to_le_bytes());
+        entry[8..12].copy_from_slice(&(value.len() as u32).to_le_bytes());
+        entry[16..20].copy_from_slice(INLINE_DATA_NAME);
+        entry[value_offs..value_offs + value.len()].copy_from_slice(value);
+        area
+    }
+
+    fn inline_inode(contents: &[u8], xattrs: Vec<u8>) -> Inode {
+        let mut inode = Inode::new(0o100644, 1);
+        inode.i_flags = super::super::inode::EXT4_INLINE_DATA_FL;
+        inode.i_block = [0u8; 60];
+        let head = core::cmp::min(contents.len(), INLINE_BLOCK_SIZE);
+        inode.i_block[..head].copy_from_slice(&contents[..head]);
+        inode.set_size(contents.len() as u64);
+        inode.inline_xattrs = xattrs;
+        inode
+    }
+
+    #[test]
+    fn test_short_file_lives_in_i_block() {
+        let inode = inline_inode(b"hello", Vec::new());
+        assert_eq!(read_inline_data(&inode).unwrap(), b"hello");
+    }
+
+    #[test]
+    fn test_tail_comes_from_system_data() {
+        let contents: Vec<u8> = (0..90u8).collect();
+        let inode = inline_inode(&contents, xattr_area(&contents[INLINE_BLOCK_SIZE..]));
+        assert_eq!(read_inline_data(&inode).unwrap(), contents);
+    }
+
+    #[test]
+    fn test_missing_tail_is_an_error() {
+        let contents = [7u8; 80];
+        let inode = inline_inode(&contents, Vec::new());
+        assert_eq!(read_inline_data(&inode), Err(FsError::IoError));
+    }
+
+    #[test]
+    fn test_other_attributes_are_skipped() {
+        let mut area = xattr_area(b"tail");
+        // Rename the only entry to system.other
+        area[4 + 16..4 + 20].copy_from_slice(b"othe");
+        assert_eq!(find_xattr(&area, XATTR_INDEX_SYSTEM, INLINE_DATA_NAME), None);
+    }
+}
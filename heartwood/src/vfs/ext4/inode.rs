@@ -5,8 +5,14 @@
 //! - File size and block count
 //! - Timestamps (creation, modification, access)
 //! - Block pointers (direct, indirect, or extent tree)
+//!
+//! Inodes are written back by patching the fields modelled here into the
+//! on-disk record, so fields the driver doesn't understand (extended
+//! timestamps, in-inode xattrs) survive untouched.
 
 use crate::vfs::block_device::{BlockDevice, BlockDeviceError};
+use super::block::{read_block, write_block};
+use super::checksum::crc32c;
 use super::superblock::{Ext4Superblock, BlockGroupDesc};
 use alloc::vec::Vec;
 
@@ -22,6 +28,21 @@ pub const S_IFSOCK: u16 = 0xC000; // Socket
 /// Inode flags
 pub const EXT4_EXTENTS_FL: u32 = 0x00080000;  // Inode uses extents
 pub const EXT4_INLINE_DATA_FL: u32 = 0x10000000; // Inode has inline data
+pub const EXT4_INDEX_FL: u32 = 0x00001000;  // Directory uses a hashed index
+pub const EXT4_HUGE_FILE_FL: u32 = 0x00040000;  // i_blocks is in filesystem blocks
+pub const EXT4_CASEFOLD_FL: u32 = 0x40000000;  // Directory names are case-insensitive
+
+/// Size of the original (rev 0) inode; larger inodes extend it
+const GOOD_OLD_INODE_SIZE: usize = 128;
+
+/// Offset of `i_checksum_lo`
+const CHECKSUM_LO_OFFSET: usize = 0x7C;
+
+/// Offset of `i_checksum_hi` (in the extended area)
+const CHECKSUM_HI_OFFSET: usize = 0x82;
+
+/// `i_extra_isize` given to new inodes (covers the fields up to `i_projid`)
+const NEW_EXTRA_ISIZE: u16 = 32;
 
 /// ext4 Inode
 ///
@@ -74,6 +95,11 @@ pub struct Inode {
 
     /// Extra inode size
     pub i_extra_isize: u16,
+
+    /// Raw in-inode extended attribute area (everything after the extra fields)
+    ///
+    /// Empty for 128-byte inodes. Holds the tail of inline data.
+    pub inline_xattrs: Vec<u8>,
 }
 
 impl Inode {
@@ -94,36 +120,9 @@ impl Inode {
         sb: &Ext4Superblock,
         inode_num: u32,
     ) -> Result<Self, BlockDeviceError> {
-        // Inode numbers are 1-indexed
-        if inode_num == 0 || inode_num > sb.s_inodes_count {
-            return Err(BlockDeviceError::InvalidSector);
-        }
-
-        // Calculate which block group contains this inode
-        let inode_index = inode_num - 1;
-        let block_group = inode_index / sb.s_inodes_per_group;
-        let index_in_group = inode_index % sb.s_inodes_per_group;
-
-        // Read block group descriptor to find inode table location
-        let bg_desc = BlockGroupDesc::from_device(device, sb, block_group)?;
-
-        // Calculate inode offset within the inode table
+        let (block, offset) = Self::location(device, sb, inode_num)?;
+        let data = read_block(device, sb, block)?;
         let inode_size = sb.s_inode_size as u32;
-        let inode_table_block = bg_desc.bg_inode_table;
-        let block_size = sb.block_size();
-
-        let inode_offset_in_table = index_in_group * inode_size;
-        let inode_block = inode_table_block + (inode_offset_in_table / block_size) as u64;
-        let offset_in_block = inode_offset_in_table % block_size;
-
-        // Read the block containing this inode
-        let sector_size = device.sector_size() as u64;
-        let block_offset = inode_block * block_size as u64;
-        let start_sector = block_offset / sector_size;
-        let sectors_per_block = (block_size as u64 + sector_size - 1) / sector_size;
-
-        let data = device.read_sectors(start_sector, sectors_per_block as u32)?;
-        let offset = (block_offset % sector_size) as usize + offset_in_block as usize;
 
         // Parse inode structure (first 128 bytes are standard)
         let i_mode = u16::from_le_bytes([data[offset], data[offset+1]]);
@@ -172,12 +171,20 @@ impl Inode {
             0
         };
 
-        let i_extra_isize = if inode_size >= 128 {
+        let i_extra_isize = if inode_size as usize > GOOD_OLD_INODE_SIZE {
             u16::from_le_bytes([data[offset+128], data[offset+129]])
         } else {
             0
         };
 
+        // Whatever follows the extra fields is in-inode xattr space
+        let xattr_start = GOOD_OLD_INODE_SIZE + i_extra_isize as usize;
+        let inline_xattrs = if inode_size as usize > xattr_start {
+            data[offset + xattr_start..offset + inode_size as usize].to_vec()
+        } else {
+            Vec::new()
+        };
+
         // Combine UID/GID fields
         let i_uid = (i_uid_high as u32) << 16 | i_uid_lo as u32;
         let i_gid = (i_gid_high as u32) << 16 | i_gid_lo as u32;
@@ -204,9 +211,212 @@ impl Inode {
             i_uid_high,
             i_gid_high,
             i_extra_isize,
+            inline_xattrs,
         })
     }
 
+    /// Create an empty inode of the given mode (type and permissions)
+    ///
+    /// The inode has one link, no data, and an empty extent tree root.
+    pub fn new(i_mode: u16, timestamp: u32) -> Self {
+        let mut i_block = [0u8; 60];
+        super::extent::init_root(&mut i_block);
+
+        Self {
+            i_mode,
+            i_uid: 0,
+            i_size_lo: 0,
+            i_atime: timestamp,
+            i_ctime: timestamp,
+            i_mtime: timestamp,
+            i_dtime: 0,
+            i_gid: 0,
+            i_links_count: 1,
+            i_blocks_lo: 0,
+            i_flags: EXT4_EXTENTS_FL,
+            i_osd1: 0,
+            i_block,
+            i_generation: 0,
+            i_file_acl_lo: 0,
+            i_size_high: 0,
+            i_blocks_high: 0,
+            i_file_acl_high: 0,
+            i_uid_high: 0,
+            i_gid_high: 0,
+            i_extra_isize: NEW_EXTRA_ISIZE,
+            inline_xattrs: Vec::new(),
+        }
+    }
+
+    /// Locate an inode on disk
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((block, offset))` - Inode table block and byte offset within it
+    /// * `Err(BlockDeviceError)` - Invalid inode number or read error
+    fn location(
+        device: &dyn BlockDevice,
+        sb: &Ext4Superblock,
+        inode_num: u32,
+    ) -> Result<(u64, usize), BlockDeviceError> {
+        // Inode numbers are 1-indexed
+        if inode_num == 0 || inode_num > sb.s_inodes_count {
+            return Err(BlockDeviceError::InvalidSector);
+        }
+
+        // Calculate which block group contains this inode
+        let inode_index = inode_num - 1;
+        let block_group = inode_index / sb.s_inodes_per_group;
+        let index_in_group = inode_index % sb.s_inodes_per_group;
+
+        // Read block group descriptor to find inode table location
+        let bg_desc = BlockGroupDesc::from_device(device, sb, block_group)?;
+
+        // Calculate inode offset within the inode table
+        let inode_size = sb.s_inode_size as u32;
+        let block_size = sb.block_size();
+        let inode_offset_in_table = index_in_group * inode_size;
+        let inode_block = bg_desc.bg_inode_table + (inode_offset_in_table / block_size) as u64;
+
+        Ok((inode_block, (inode_offset_in_table % block_size) as usize))
+    }
+
+    /// Write this inode back over an existing on-disk inode
+    ///
+    /// Only the fields modelled by `Inode` are replaced; the checksum is
+    /// recomputed.
+    pub fn write_to_device(
+        &self,
+        device: &dyn BlockDevice,
+        sb: &Ext4Superblock,
+        inode_num: u32,
+    ) -> Result<(), BlockDeviceError> {
+        self.store(device, sb, inode_num, false)
+    }
+
+    /// Write this inode into a freshly allocated inode slot
+    ///
+    /// The whole on-disk record is cleared first, so nothing left over from
+    /// a previous owner survives.
+    pub fn create_on_device(
+        &self,
+        device: &dyn BlockDevice,
+        sb: &Ext4Superblock,
+        inode_num: u32,
+    ) -> Result<(), BlockDeviceError> {
+        self.store(device, sb, inode_num, true)
+    }
+
+    fn store(
+        &self,
+        device: &dyn BlockDevice,
+        sb: &Ext4Superblock,
+        inode_num: u32,
+        fresh: bool,
+    ) -> Result<(), BlockDeviceError> {
+        let (block, offset) = Self::location(device, sb, inode_num)?;
+        let mut data = read_block(device, sb, block)?;
+        let inode_size = sb.s_inode_size as usize;
+        let raw = &mut data[offset..offset + inode_size];
+
+        if fresh {
+            raw.fill(0);
+        }
+
+        fn put16(raw: &mut [u8], at: usize, value: u16) {
+            raw[at..at + 2].copy_from_slice(&value.to_le_bytes());
+        }
+        fn put32(raw: &mut [u8], at: usize, value: u32) {
+            raw[at..at + 4].copy_from_slice(&value.to_le_bytes());
+        }
+
+        put16(raw, 0, self.i_mode);
+        put16(raw, 2, self.i_uid as u16);
+        put32(raw, 4, self.i_size_lo);
+        put32(raw, 8, self.i_atime);
+        put32(raw, 12, self.i_ctime);
+        put32(raw, 16, self.i_mtime);
+        put32(raw, 20, self.i_dtime);
+        put16(raw, 24, self.i_gid as u16);
+        put16(raw, 26, self.i_links_count);
+        put32(raw, 28, self.i_blocks_lo);
+        put32(raw, 32, self.i_flags);
+        put32(raw, 36, self.i_osd1);
+        raw[40..100].copy_from_slice(&self.i_block);
+        put32(raw, 100, self.i_generation);
+        put32(raw, 104, self.i_file_acl_lo);
+        put32(raw, 108, self.i_size_high);
+        put16(raw, 116, self.i_blocks_high);
+        put16(raw, 118, self.i_file_acl_high);
+        put16(raw, 120, (self.i_uid >> 16) as u16);
+        put16(raw, 122, (self.i_gid >> 16) as u16);
+
+        if inode_size > GOOD_OLD_INODE_SIZE && fresh {
+            put16(raw, 128, self.i_extra_isize);
+        }
+
+        if sb.has_metadata_csum() {
+            let csum = Self::checksum(sb, inode_num, raw);
+            put16(raw, CHECKSUM_LO_OFFSET, csum as u16);
+            if Self::has_checksum_hi(raw) {
+                put16(raw, CHECKSUM_HI_OFFSET, (csum >> 16) as u16);
+            }
+        }
+
+        write_block(device, sb, block, &data)
+    }
+
+    /// Whether the on-disk inode is large enough to hold `i_checksum_hi`
+    fn has_checksum_hi(raw: &[u8]) -> bool {
+        if raw.len() <= GOOD_OLD_INODE_SIZE {
+            return false;
+        }
+        let extra_isize = u16::from_le_bytes([raw[128], raw[129]]) as usize;
+        GOOD_OLD_INODE_SIZE + extra_isize >= CHECKSUM_HI_OFFSET + 2
+    }
+
+    /// metadata_csum checksum of a raw on-disk inode
+    ///
+    /// The checksum fields themselves are treated as zero.
+    fn checksum(sb: &Ext4Superblock, inode_num: u32, raw: &[u8]) -> u32 {
+        let generation = u32::from_le_bytes([raw[100], raw[101], raw[102], raw[103]]);
+        let seed = sb.inode_checksum_seed(inode_num, generation);
+
+        let mut crc = crc32c(seed, &raw[..CHECKSUM_LO_OFFSET]);
+        crc = crc32c(crc, &[0, 0]);
+        crc = crc32c(crc, &raw[CHECKSUM_LO_OFFSET + 2..GOOD_OLD_INODE_SIZE]);
+
+        if raw.len() > GOOD_OLD_INODE_SIZE {
+            crc = crc32c(crc, &raw[GOOD_OLD_INODE_SIZE..CHECKSUM_HI_OFFSET]);
+            let mut rest = CHECKSUM_HI_OFFSET;
+            if Self::has_checksum_hi(raw) {
+                crc = crc32c(crc, &[0, 0]);
+                rest += 2;
+            }
+            crc = crc32c(crc, &raw[rest..]);
+        }
+
+        crc
+    }
+
+    /// Set the file size (both halves)
+    pub fn set_size(&mut self, size: u64) {
+        self.i_size_lo = size as u32;
+        self.i_size_high = (size >> 32) as u32;
+    }
+
+    /// Set the number of 512-byte sectors charged to this inode
+    pub fn set_sectors(&mut self, sectors: u64) {
+        self.i_blocks_lo = sectors as u32;
+        self.i_blocks_high = (sectors >> 32) as u16;
+        self.i_flags &= !EXT4_HUGE_FILE_FL;
+    }
+
+    /// Number of 512-byte sectors charged to this inode
+    pub fn sectors(&self) -> u64 {
+        (self.i_blocks_high as u64) << 32 | self.i_blocks_lo as u64
+    }
+
     /// Get file type from mode
     pub fn file_type(&self) -> u16 {
         self.i_mode & 0xF000
@@ -237,6 +447,24 @@ impl Inode {
         }
     }
 
+    /// Check if this is a symlink whose target is stored in `i_block`
+    ///
+    /// Such "fast" symlinks own no blocks, apart from an xattr block if
+    /// they have one.
+    pub fn is_fast_symlink(&self, sb: &Ext4Superblock) -> bool {
+        if !self.is_symlink() || self.has_inline_data() {
+            return false;
+        }
+
+        let xattr_sectors = if self.file_acl() != 0 { sb.block_size() as u64 / 512 } else { 0 };
+        self.sectors() == xattr_sectors
+    }
+
+    /// Block holding this inode's extended attributes (0 if none)
+    pub fn file_acl(&self) -> u64 {
+        (self.i_file_acl_high as u64) << 32 | self.i_file_acl_lo as u64
+    }
+
     /// Check if inode uses extents
     pub fn uses_extents(&self) -> bool {
         (self.i_flags & EXT4_EXTENTS_FL) != 0
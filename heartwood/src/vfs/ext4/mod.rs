@@ -1,19 +1,29 @@
 //! ext4 Filesystem Driver
 //!
-//! Implements read-only ext4 support for AethelOS.
+//! Implements ext4 support for AethelOS.
 //!
 //! ext4 is the most widely used filesystem on Linux systems and supports
 //! advanced features like extents, large files, and journaling. This driver
-//! allows AethelOS to read files from ext4 volumes.
+//! allows AethelOS to read and write files on ext4 volumes.
 //!
 //! # Features
 //!
 //! - Superblock and group descriptor parsing
 //! - Extent tree navigation for efficient large file support
+//! - ext2/ext3 indirect block maps and inline data (read-only)
 //! - Inode reading (both 128-byte and 256-byte formats)
 //! - Directory entry parsing (linear and htree formats)
 //! - File reading
-//! - Read-only access (write support planned for future)
+//! - Writing, creating and removing files and directories (journal-less)
+//! - Block and inode bitmap allocation, extent tree growth
+//! - metadata_csum and gdt_csum checksum maintenance
+//!
+//! # Read-only Mounts
+//!
+//! Writes never touch the journal, so a volume is only writable when it
+//! doesn't need one replayed and uses no feature the write path can't keep
+//! consistent (see [`Ext4Superblock::read_only_reason`]). Anything else
+//! still mounts, but read-only.
 //!
 //! # Example
 //!
@@ -29,21 +39,46 @@ pub mod superblock;
 pub mod inode;
 pub mod extent;
 pub mod dir;
+pub mod block;
+pub mod checksum;
+pub mod balloc;
+pub mod hash;
+pub mod indirect;
+pub mod inline;
 
 use super::{FileSystem, Path, FsError, DirEntry as VfsDirEntry, FileStat};
 use super::block_device::BlockDevice;
 use superblock::Ext4Superblock;
-use inode::Inode;
+use inode::{Inode, S_IFDIR, S_IFREG};
 use alloc::boxed::Box;
 use alloc::vec::Vec;
-use alloc::string::ToString;
+use alloc::string::String;
+use spin::Mutex;
+
+/// Permissions given to new files and directories
+const NEW_FILE_MODE: u16 = 0o644;
+const NEW_DIR_MODE: u16 = 0o755;
+
+/// Link count above which directories stop counting subdirectories
+const EXT4_LINK_MAX: u16 = 65000;
+
+/// Symlinks followed while resolving one path before giving up (as Linux)
+const MAX_SYMLINK_FOLLOWS: u32 = 40;
+
+/// Inode of the root directory
+const ROOT_INODE: u32 = 2;
 
 /// ext4 Filesystem
 ///
-/// Provides read-only access to ext4 volumes.
+/// Provides access to ext4 volumes; see the module docs for when a volume
+/// is mounted read-only.
 pub struct Ext4 {
     device: Box<dyn BlockDevice>,
     pub superblock: Ext4Superblock,
+    /// Why writes are refused, if they are
+    read_only_reason: Option<&'static str>,
+    /// Serializes every modification of the volume
+    write_lock: Mutex<()>,
 }
 
 impl Ext4 {
@@ -66,7 +101,30 @@ impl Ext4 {
             return Err(FsError::IoError);
         }
 
-        Ok(Self { device, superblock })
+        let read_only_reason = if device.is_read_only() {
+            Some("device is read-only")
+        } else {
+            superblock.read_only_reason()
+        };
+
+        Ok(Self {
+            device,
+            superblock,
+            read_only_reason,
+            write_lock: Mutex::new(()),
+        })
+    }
+
+    /// Why this volume is mounted read-only, if it is
+    pub fn read_only_reason(&self) -> Option<&'static str> {
+        self.read_only_reason
+    }
+
+    /// Current free block and inode counts, as recorded in the superblock
+    pub fn free_counts(&self) -> Result<(u64, u32), FsError> {
+        let sb = Ext4Superblock::from_device(&*self.device)
+            .map_err(|_| FsError::IoError)?;
+        Ok((sb.s_free_blocks_count, sb.s_free_inodes_count))
     }
 
     /// Read an inode by number
@@ -84,7 +142,7 @@ impl Ext4 {
             .map_err(|_| FsError::IoError)
     }
 
-    /// Find a file or directory by path
+    /// Find a file or directory by path, following symlinks
     ///
     /// # Arguments
     ///
@@ -94,41 +152,267 @@ impl Ext4 {
     ///
     /// * `Ok((inode_num, Inode))` - Found inode and its number
     /// * `Err(FsError::NotFound)` - Path doesn't exist
+    /// * `Err(FsError::SymlinkLoop)` - Too many symlinks along the way
     /// * `Err(FsError::IoError)` - Read error
     fn find_inode(&self, path: &Path) -> Result<(u32, Inode), FsError> {
-        let path_str = path.as_str().trim_start_matches('/');
-
-        // Empty path = root directory (inode 2)
-        if path_str.is_empty() {
-            let root_inode = self.read_inode(2)?;
-            return Ok((2, root_inode));
-        }
+        self.lookup(path, true)
+    }
 
-        // Split path into components
-        let components: Vec<&str> = path_str.split('/').collect();
+    /// Resolve a path to an inode
+    ///
+    /// Symlinks in every component but the last are always followed; the
+    /// last is only followed if `follow_last` is set. `..` climbs to the
+    /// directory the walk actually came through, so it also works after a
+    /// symlink has been followed.
+    fn lookup(&self, path: &Path, follow_last: bool) -> Result<(u32, Inode), FsError> {
+        let device = &*self.device;
+        let sb = &self.superblock;
 
-        // Start at root directory (inode 2)
-        let mut current_inode_num = 2;
-        let mut current_inode = self.read_inode(2)?;
+        // Components still to resolve, next one last
+        let mut pending: Vec<String> = path.as_str().rsplit('/').map(String::from).collect();
+        // Directories above `current`, for `..`
+        let mut ancestors: Vec<(u32, Inode)> = Vec::new();
+        let mut current = (ROOT_INODE, self.read_inode(ROOT_INODE)?);
+        let mut followed = 0;
 
-        // Traverse path components
-        for component in components {
+        while let Some(component) = pending.pop() {
             // Current inode must be a directory
-            if !current_inode.is_dir() {
+            if !current.1.is_dir() {
                 return Err(FsError::NotADirectory);
             }
 
-            // Search for component in current directory
-            match dir::find_entry_in_dir(&*self.device, &self.superblock, &current_inode, component)? {
-                Some((inode_num, entry_type)) => {
-                    current_inode_num = inode_num;
-                    current_inode = self.read_inode(inode_num)?;
+            match component.as_str() {
+                "" | "." => continue,
+                ".." => {
+                    if let Some(parent) = ancestors.pop() {
+                        current = parent;
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+
+            let (inode_num, _) = dir::find_entry_in_dir(device, sb, &current.1, &component)?
+                .ok_or(FsError::NotFound)?;
+            let inode = self.read_inode(inode_num)?;
+
+            if inode.is_symlink() && (follow_last || !pending.is_empty()) {
+                followed += 1;
+                if followed > MAX_SYMLINK_FOLLOWS {
+                    return Err(FsError::SymlinkLoop);
                 }
-                None => return Err(FsError::NotFound),
+
+                // Splice the target into the components still to resolve
+                let target = self.link_target(&inode)?;
+                if target.is_empty() {
+                    return Err(FsError::NotFound);
+                }
+                if target.starts_with('/') {
+                    ancestors.truncate(1);
+                    if let Some(root) = ancestors.pop() {
+                        current = root;
+                    }
+                }
+                pending.extend(target.rsplit('/').map(String::from));
+                continue;
+            }
+
+            ancestors.push(core::mem::replace(&mut current, (inode_num, inode)));
+        }
+
+        Ok(current)
+    }
+
+    /// Read where a symlink points
+    fn link_target(&self, inode: &Inode) -> Result<String, FsError> {
+        let target = if inode.is_fast_symlink(&self.superblock) {
+            let len = core::cmp::min(inode.size() as usize, inode.i_block.len());
+            inode.i_block[..len].to_vec()
+        } else {
+            extent::read_file_data(&*self.device, &self.superblock, inode)?
+        };
+
+        Ok(String::from_utf8_lossy(&target).into_owned())
+    }
+
+    /// Path a symlink at `path` leads to
+    fn link_destination(&self, path: &Path, inode: &Inode) -> Result<Path, FsError> {
+        let target = self.link_target(inode)?;
+        if target.is_empty() {
+            return Err(FsError::NotFound);
+        }
+
+        if target.starts_with('/') {
+            Ok(Path::new(&target))
+        } else {
+            Ok(path.parent().unwrap_or_else(|| Path::new("/")).join(&target))
+        }
+    }
+
+    /// Fail with `ReadOnly` unless this volume was mounted writable
+    fn check_writable(&self) -> Result<(), FsError> {
+        match self.read_only_reason {
+            Some(_) => Err(FsError::ReadOnly),
+            None => Ok(()),
+        }
+    }
+
+    /// Timestamp for new and modified inodes
+    ///
+    /// AethelOS has no wall clock yet, so this is the volume's last write
+    /// time - never earlier than anything already on it.
+    fn timestamp(&self) -> u32 {
+        self.superblock.s_wtime.max(self.superblock.s_mtime).max(1)
+    }
+
+    fn write_inode(&self, inode_num: u32, inode: &Inode) -> Result<(), FsError> {
+        inode.write_to_device(&*self.device, &self.superblock, inode_num)
+            .map_err(|_| FsError::IoError)
+    }
+
+    /// Find the directory that should hold `path`, and the name within it
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((inode_num, inode, name))` - Parent directory and the final component
+    /// * `Err(FsError::InvalidPath)` - `path` is the root, `.` or `..`
+    /// * `Err(FsError::NotFound)` - The parent doesn't exist
+    /// * `Err(FsError::NotADirectory)` - The parent is a file
+    fn resolve_parent<'p>(&self, path: &'p Path) -> Result<(u32, Inode, &'p str), FsError> {
+        let name = path.file_name().ok_or(FsError::InvalidPath)?;
+        if name == "." || name == ".." {
+            return Err(FsError::InvalidPath);
+        }
+
+        let parent = path.parent().unwrap_or_else(|| Path::new("/"));
+        let (parent_num, parent_inode) = self.find_inode(&parent)?;
+        if !parent_inode.is_dir() {
+            return Err(FsError::NotADirectory);
+        }
+
+        Ok((parent_num, parent_inode, name))
+    }
+
+    /// Free everything allocated for an inode that never became reachable
+    fn discard(&self, inode_num: u32, inode: &mut Inode, is_dir: bool) {
+        let _ = extent::free_tree(&*self.device, &self.superblock, inode);
+        let _ = balloc::free_inode(&*self.device, &self.superblock, inode_num, is_dir);
+    }
+
+    /// Link a freshly written inode into its parent directory
+    fn link_new(
+        &self,
+        parent_num: u32,
+        parent: &mut Inode,
+        name: &str,
+        inode_num: u32,
+        inode: &Inode,
+    ) -> Result<(), FsError> {
+        let file_type = dir::file_type_for(inode);
+        dir::add_entry(&*self.device, &self.superblock, parent_num, parent, name, inode_num, file_type)?;
+
+        if inode.is_dir() && parent.i_links_count < EXT4_LINK_MAX {
+            parent.i_links_count += 1;
+        }
+        parent.i_mtime = self.timestamp();
+        parent.i_ctime = parent.i_mtime;
+        self.write_inode(parent_num, parent)
+    }
+
+    /// Create a file holding `data` in `parent`
+    fn create_file(&self, parent_num: u32, parent: &mut Inode, name: &str, data: &[u8]) -> Result<(), FsError> {
+        let device = &*self.device;
+        let sb = &self.superblock;
+
+        let group = balloc::inode_group(sb, parent_num);
+        let inode_num = balloc::allocate_inode(device, sb, group, false)?;
+
+        let empty = Inode::new(S_IFREG | NEW_FILE_MODE, self.timestamp());
+        let mut inode = match extent::write_file_data(device, sb, inode_num, &empty, data) {
+            Ok(inode) => inode,
+            Err(e) => {
+                let _ = balloc::free_inode(device, sb, inode_num, false);
+                return Err(e);
             }
+        };
+
+        // Data, then inode, then the entry that makes it reachable
+        let result = inode.create_on_device(device, sb, inode_num)
+            .map_err(|_| FsError::IoError)
+            .and_then(|_| self.link_new(parent_num, parent, name, inode_num, &inode));
+        if let Err(e) = result {
+            self.discard(inode_num, &mut inode, false);
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// Create a directory, creating missing parents first
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(inode_num)` - Inode of the new directory
+    fn make_dir(&self, path: &Path) -> Result<u32, FsError> {
+        let device = &*self.device;
+        let sb = &self.superblock;
+
+        let name = path.file_name().ok_or(FsError::AlreadyExists)?;
+        if !dir::is_valid_name(name) {
+            return Err(FsError::InvalidPath);
+        }
+
+        let parent_path = path.parent().unwrap_or_else(|| Path::new("/"));
+        let (parent_num, mut parent) = match self.find_inode(&parent_path) {
+            Ok((num, inode)) if inode.is_dir() => (num, inode),
+            Ok(_) => return Err(FsError::NotADirectory),
+            Err(FsError::NotFound) => {
+                let num = self.make_dir(&parent_path)?;
+                (num, self.read_inode(num)?)
+            }
+            Err(e) => return Err(e),
+        };
+
+        if dir::find_entry_in_dir(device, sb, &parent, name)?.is_some() {
+            return Err(FsError::AlreadyExists);
+        }
+
+        let group = balloc::inode_group(sb, parent_num);
+        let inode_num = balloc::allocate_inode(device, sb, group, true)?;
+
+        let mut inode = Inode::new(S_IFDIR | NEW_DIR_MODE, self.timestamp());
+        inode.i_links_count = 2; // "." and the parent's entry
+
+        let result = self.populate_dir(inode_num, &mut inode, parent_num)
+            .and_then(|_| self.link_new(parent_num, &mut parent, name, inode_num, &inode));
+        if let Err(e) = result {
+            self.discard(inode_num, &mut inode, true);
+            return Err(e);
+        }
+
+        Ok(inode_num)
+    }
+
+    /// Give a new directory its first block and write its inode
+    fn populate_dir(&self, inode_num: u32, inode: &mut Inode, parent_num: u32) -> Result<(), FsError> {
+        let device = &*self.device;
+        let sb = &self.superblock;
+        let block_size = sb.block_size() as u64;
+
+        let goal = balloc::inode_goal(sb, inode_num);
+        let (block, _) = balloc::allocate_blocks(device, sb, goal, 1)?;
+
+        let result = dir::write_initial_block(device, sb, inode_num, inode, parent_num, block)
+            .and_then(|_| extent::insert_extent(device, sb, inode_num, inode, 0, block, 1));
+        if let Err(e) = result {
+            let _ = balloc::free_blocks(device, sb, block, 1);
+            return Err(e);
         }
 
-        Ok((current_inode_num, current_inode))
+        inode.set_size(block_size);
+        inode.set_sectors(inode.sectors() + block_size / 512);
+        inode.create_on_device(device, sb, inode_num)
+            .map_err(|_| FsError::IoError)
     }
 }
 
@@ -148,17 +432,135 @@ impl FileSystem for Ext4 {
         extent::read_file_data(&*self.device, &self.superblock, &inode)
     }
 
-    fn write(&self, _path: &Path, _data: &[u8]) -> Result<(), FsError> {
-        // Write support not yet implemented
-        Err(FsError::ReadOnly)
+    fn write(&self, path: &Path, data: &[u8]) -> Result<(), FsError> {
+        self.check_writable()?;
+        let device = &*self.device;
+        let sb = &self.superblock;
+
+        let _guard = self.write_lock.lock();
+
+        // Writing through a symlink writes (or creates) its target
+        let mut path = path.clone();
+        let mut followed = 0;
+        let (inode_num, mut old) = loop {
+            let (parent_num, mut parent, name) = self.resolve_parent(&path)?;
+
+            let inode_num = match dir::find_entry_in_dir(device, sb, &parent, name)? {
+                Some((inode_num, _)) => inode_num,
+                None => return self.create_file(parent_num, &mut parent, name, data),
+            };
+
+            let inode = self.read_inode(inode_num)?;
+            if !inode.is_symlink() {
+                break (inode_num, inode);
+            }
+
+            followed += 1;
+            if followed > MAX_SYMLINK_FOLLOWS {
+                return Err(FsError::SymlinkLoop);
+            }
+            path = self.link_destination(&path, &inode)?;
+        };
+
+        if old.is_dir() {
+            return Err(FsError::IsADirectory);
+        }
+
+        // New contents go to fresh blocks before the inode points at them
+        let mut inode = extent::write_file_data(device, sb, inode_num, &old, data)?;
+        inode.i_mtime = self.timestamp();
+        inode.i_ctime = inode.i_mtime;
+
+        if let Err(e) = self.write_inode(inode_num, &inode) {
+            let _ = extent::free_tree(device, sb, &mut inode);
+            return Err(e);
+        }
+
+        // Only now are the old blocks unreferenced
+        extent::free_tree(device, sb, &mut old)
     }
 
-    fn remove(&self, _path: &Path) -> Result<(), FsError> {
-        Err(FsError::ReadOnly)
+    fn write_at(&self, path: &Path, offset: u64, data: &[u8]) -> Result<(), FsError> {
+        self.check_writable()?;
+
+        // A missing file is created empty, then written like any other
+        let inode_num = match self.find_inode(path) {
+            Ok((inode_num, _)) => inode_num,
+            Err(FsError::NotFound) => {
+                self.write(path, &[])?;
+                self.find_inode(path)?.0
+            }
+            Err(e) => return Err(e),
+        };
+
+        let _guard = self.write_lock.lock();
+        let mut inode = self.read_inode(inode_num)?;
+        if inode.is_dir() {
+            return Err(FsError::IsADirectory);
+        }
+
+        extent::write_range(&*self.device, &self.superblock, inode_num, &mut inode, offset, data)?;
+        inode.i_mtime = self.timestamp();
+        inode.i_ctime = inode.i_mtime;
+        self.write_inode(inode_num, &inode)
     }
 
-    fn create_dir(&self, _path: &Path) -> Result<(), FsError> {
-        Err(FsError::ReadOnly)
+    fn remove(&self, path: &Path) -> Result<(), FsError> {
+        self.check_writable()?;
+        let device = &*self.device;
+        let sb = &self.superblock;
+
+        if path.is_root() {
+            return Err(FsError::PermissionDenied); // Can't delete root
+        }
+
+        let _guard = self.write_lock.lock();
+        let (parent_num, mut parent, name) = self.resolve_parent(path)?;
+        let (inode_num, _) = dir::find_entry_in_dir(device, sb, &parent, name)?
+            .ok_or(FsError::NotFound)?;
+        let mut inode = self.read_inode(inode_num)?;
+        let is_dir = inode.is_dir();
+
+        if is_dir && !dir::is_empty(device, sb, &inode)? {
+            return Err(FsError::DirectoryNotEmpty);
+        }
+
+        // Blocks we can't walk would leak once the inode is freed
+        let owns_blocks = inode.sectors() > 0;
+        if owns_blocks && (!inode.uses_extents() || inode.has_inline_data()) {
+            return Err(FsError::NotSupported);
+        }
+
+        dir::remove_entry(device, sb, parent_num, &parent, name)?;
+
+        if is_dir && parent.i_links_count > 2 && parent.i_links_count < EXT4_LINK_MAX {
+            parent.i_links_count -= 1;
+        }
+        parent.i_mtime = self.timestamp();
+        parent.i_ctime = parent.i_mtime;
+        self.write_inode(parent_num, &parent)?;
+
+        inode.i_links_count = if is_dir { 0 } else { inode.i_links_count.saturating_sub(1) };
+        inode.i_ctime = self.timestamp();
+        if inode.i_links_count > 0 {
+            // Still reachable through another hard link
+            return self.write_inode(inode_num, &inode);
+        }
+
+        if owns_blocks {
+            extent::free_tree(device, sb, &mut inode)?;
+        }
+        inode.i_dtime = self.timestamp();
+        self.write_inode(inode_num, &inode)?;
+
+        balloc::free_inode(device, sb, inode_num, is_dir)
+    }
+
+    fn create_dir(&self, path: &Path) -> Result<(), FsError> {
+        self.check_writable()?;
+
+        let _guard = self.write_lock.lock();
+        self.make_dir(path).map(|_| ())
     }
 
     fn read_dir(&self, path: &Path) -> Result<Vec<VfsDirEntry>, FsError> {
@@ -172,6 +574,16 @@ impl FileSystem for Ext4 {
         dir::read_dir_entries(&*self.device, &self.superblock, &inode)
     }
 
+    fn read_link(&self, path: &Path) -> Result<String, FsError> {
+        let (_inode_num, inode) = self.lookup(path, false)?;
+
+        if !inode.is_symlink() {
+            return Err(FsError::InvalidPath);
+        }
+
+        self.link_target(&inode)
+    }
+
     fn stat(&self, path: &Path) -> Result<FileStat, FsError> {
         let (_inode_num, inode) = self.find_inode(path)?;
 
@@ -184,14 +596,311 @@ impl FileSystem for Ext4 {
     }
 
     fn sync(&self) -> Result<(), FsError> {
-        // Read-only, nothing to sync
-        Ok(())
+        if self.read_only_reason.is_some() {
+            return Ok(());
+        }
+
+        // Every change is written through; only the device may be caching
+        self.device.sync().map_err(|_| FsError::IoError)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::vfs::mock_ext4::{MockExt4Device, DEEP_LINK_TARGET, HASHED_LEAVES, LINK_TARGET, README_CONTENTS};
+    use crate::vfs::FileType;
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::vec;
+
+    fn mount(device: &MockExt4Device) -> Ext4 {
+        Ext4::new(Box::new(device.clone())).unwrap()
+    }
+
+    fn names(fs: &Ext4, path: &str) -> Vec<String> {
+        fs.read_dir(&Path::new(path)).unwrap().into_iter().map(|e| e.name).collect()
+    }
+
+    fn fresh_counts() -> (u64, u32) {
+        (MockExt4Device::FREE_BLOCKS, MockExt4Device::FREE_INODES)
+    }
+
+    #[test]
+    fn test_read_mock_volume() {
+        let fs = mount(&MockExt4Device::new());
+        assert_eq!(fs.read(&Path::new("/README.TXT")).unwrap(), README_CONTENTS);
+        assert_eq!(names(&fs, "/"), ["lost+found", "README.TXT", "LINK.TXT", "LOOP", "DEEP.LNK", "HASHED"]);
+        assert!(fs.stat(&Path::new("/lost+found")).unwrap().is_dir);
+    }
+
+    #[test]
+    fn test_entries_report_file_types() {
+        let fs = mount(&MockExt4Device::new());
+        let types: Vec<FileType> = fs.read_dir(&Path::new("/")).unwrap()
+            .into_iter()
+            .map(|e| e.file_type)
+            .collect();
+        assert_eq!(types, [
+            FileType::Directory,
+            FileType::File,
+            FileType::Symlink,
+            FileType::Symlink,
+            FileType::Symlink,
+            FileType::Directory,
+        ]);
+    }
+
+    #[test]
+    fn test_hashed_directory() {
+        let device = MockExt4Device::new_writable();
+        let fs = mount(&device);
 
-    // Tests will be added once we have a mock block device with ext4 data
+        // Listing walks every leaf and skips the index hidden behind ".."
+        let expected: Vec<&str> = HASHED_LEAVES.iter().flat_map(|leaf| leaf.iter().copied()).collect();
+        assert_eq!(names(&fs, "/HASHED"), expected);
+        for name in expected {
+            assert_eq!(fs.read(&Path::new(&format!("/HASHED/{}", name))).unwrap(), b"");
+        }
+        assert!(!fs.exists(&Path::new("/HASHED/missing")));
+
+        // Adding an entry would leave the index stale
+        assert_eq!(fs.write(&Path::new("/HASHED/new"), b""), Err(FsError::NotSupported));
+        assert_eq!(fs.free_counts().unwrap(), fresh_counts());
+    }
+
+    #[test]
+    fn test_symlinks_are_followed() {
+        let fs = mount(&MockExt4Device::new());
+
+        // Fast, then slow (which goes through `..` and the fast one)
+        assert_eq!(fs.read(&Path::new("/LINK.TXT")).unwrap(), README_CONTENTS);
+        assert_eq!(fs.read(&Path::new("/DEEP.LNK")).unwrap(), README_CONTENTS);
+        assert_eq!(fs.stat(&Path::new("/DEEP.LNK")).unwrap().size, README_CONTENTS.len() as u64);
+        assert_eq!(fs.read(&Path::new("/lost+found/../README.TXT")).unwrap(), README_CONTENTS);
+
+        assert_eq!(fs.read_link(&Path::new("/LINK.TXT")).unwrap(), LINK_TARGET);
+        assert_eq!(fs.read_link(&Path::new("/DEEP.LNK")).unwrap(), DEEP_LINK_TARGET);
+        assert_eq!(fs.read_link(&Path::new("/README.TXT")), Err(FsError::InvalidPath));
+        assert_eq!(fs.read_link(&Path::new("/missing")), Err(FsError::NotFound));
+    }
+
+    #[test]
+    fn test_symlink_loops_are_cut_off() {
+        let fs = mount(&MockExt4Device::new());
+        assert_eq!(fs.read(&Path::new("/LOOP")), Err(FsError::SymlinkLoop));
+        assert_eq!(fs.stat(&Path::new("/LOOP/x")).err(), Some(FsError::SymlinkLoop));
+        assert_eq!(fs.read_link(&Path::new("/LOOP")).unwrap(), "LOOP");
+    }
+
+    #[test]
+    fn test_write_and_remove_symlinks() {
+        let device = MockExt4Device::new_writable();
+        let fs = mount(&device);
+
+        // Writes go to the target; removal takes only the link
+        fs.write(&Path::new("/DEEP.LNK"), b"through the link").unwrap();
+        assert_eq!(fs.read(&Path::new("/README.TXT")).unwrap(), b"through the link");
+
+        fs.remove(&Path::new("/LINK.TXT")).unwrap();
+        fs.remove(&Path::new("/DEEP.LNK")).unwrap();
+        assert!(fs.exists(&Path::new("/README.TXT")));
+        assert_eq!(fs.free_counts().unwrap(), (fresh_counts().0 + 1, fresh_counts().1 + 2));
+    }
+
+    #[test]
+    fn test_read_only_device_refuses_writes() {
+        let fs = mount(&MockExt4Device::new());
+        assert_eq!(fs.read_only_reason(), Some("device is read-only"));
+        assert_eq!(fs.write(&Path::new("/NEW.TXT"), b"x"), Err(FsError::ReadOnly));
+        assert_eq!(fs.create_dir(&Path::new("/dir")), Err(FsError::ReadOnly));
+        assert_eq!(fs.remove(&Path::new("/README.TXT")), Err(FsError::ReadOnly));
+    }
+
+    #[test]
+    fn test_unsupported_features_mount_read_only() {
+        let device = MockExt4Device::new_writable();
+
+        // Superblock starts 1024 bytes in; set an incompat bit we don't know
+        let mut sector = device.read_sector(2).unwrap();
+        sector[0x60 + 2] |= 0x01; // INCOMPAT_MMP (0x100)
+        device.write_sector(2, &sector).unwrap();
+
+        let fs = mount(&device);
+        assert_eq!(fs.read_only_reason(), Some("unsupported incompat features"));
+        assert_eq!(fs.read(&Path::new("/README.TXT")).unwrap(), README_CONTENTS);
+        assert_eq!(fs.write(&Path::new("/NEW.TXT"), b"x"), Err(FsError::ReadOnly));
+    }
+
+    #[test]
+    fn test_write_new_file_and_remount() {
+        let device = MockExt4Device::new_writable();
+        let fs = mount(&device);
+
+        let contents: Vec<u8> = (0..3000u32).map(|i| i as u8).collect();
+        fs.write(&Path::new("/notes.bin"), &contents).unwrap();
+        fs.write(&Path::new("/empty"), b"").unwrap();
+        assert_eq!(fs.free_counts().unwrap(), (fresh_counts().0 - 3, fresh_counts().1 - 2));
+
+        let fs = mount(&device);
+        assert_eq!(fs.read(&Path::new("/notes.bin")).unwrap(), contents);
+        assert_eq!(fs.read(&Path::new("/empty")).unwrap(), b"");
+        assert_eq!(names(&fs, "/")[6..], ["notes.bin", "empty"]);
+    }
+
+    #[test]
+    fn test_overwrite_frees_old_blocks() {
+        let device = MockExt4Device::new_writable();
+        let fs = mount(&device);
+
+        fs.write(&Path::new("/README.TXT"), &[7u8; 4096]).unwrap();
+        assert_eq!(fs.free_counts().unwrap().0, fresh_counts().0 - 3);
+
+        fs.write(&Path::new("/README.TXT"), b"short").unwrap();
+        assert_eq!(fs.read(&Path::new("/README.TXT")).unwrap(), b"short");
+        assert_eq!(fs.free_counts().unwrap(), fresh_counts());
+    }
+
+    #[test]
+    fn test_write_at_updates_blocks_in_place() {
+        let device = MockExt4Device::new_writable();
+        let fs = mount(&device);
+        let log = Path::new("/log.txt");
+
+        // Sequential appends allocate a block only when crossing into it
+        let mut expected = Vec::new();
+        for chunk in 0..4u8 {
+            let data = [b'a' + chunk; 700];
+            fs.write_at(&log, expected.len() as u64, &data).unwrap();
+            expected.extend_from_slice(&data);
+        }
+        assert_eq!(fs.read(&log).unwrap(), expected);
+        assert_eq!(fs.free_counts().unwrap(), (fresh_counts().0 - 3, fresh_counts().1 - 1));
+
+        // Overwriting the middle allocates nothing
+        fs.write_at(&log, 1000, b"XYZ").unwrap();
+        expected[1000..1003].copy_from_slice(b"XYZ");
+        assert_eq!(fs.read(&log).unwrap(), expected);
+        assert_eq!(fs.free_counts().unwrap().0, fresh_counts().0 - 3);
+
+        // A write far past the end leaves a hole that reads as zeros
+        fs.write_at(&log, 10 * 1024, b"end").unwrap();
+        expected.resize(10 * 1024, 0);
+        expected.extend_from_slice(b"end");
+        assert_eq!(fs.free_counts().unwrap().0, fresh_counts().0 - 4);
+        assert_eq!(mount(&device).read(&log).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_remove_file_releases_everything() {
+        let device = MockExt4Device::new_writable();
+        let fs = mount(&device);
+
+        fs.remove(&Path::new("/README.TXT")).unwrap();
+        assert!(!fs.exists(&Path::new("/README.TXT")));
+        assert_eq!(fs.free_counts().unwrap(), (fresh_counts().0 + 1, fresh_counts().1 + 1));
+        assert_eq!(fs.remove(&Path::new("/README.TXT")), Err(FsError::NotFound));
+    }
+
+    #[test]
+    fn test_create_and_remove_directories() {
+        let device = MockExt4Device::new_writable();
+        let fs = mount(&device);
+
+        // Missing parents are created too
+        fs.create_dir(&Path::new("/a/b")).unwrap();
+        fs.write(&Path::new("/a/b/leaf.txt"), b"leaf").unwrap();
+        assert_eq!(fs.create_dir(&Path::new("/a")), Err(FsError::AlreadyExists));
+
+        let root = fs.read_inode(2).unwrap();
+        assert_eq!(root.i_links_count, 5);
+        assert_eq!(fs.read(&Path::new("/a/b/leaf.txt")).unwrap(), b"leaf");
+
+        assert_eq!(fs.remove(&Path::new("/a/b")), Err(FsError::DirectoryNotEmpty));
+        fs.remove(&Path::new("/a/b/leaf.txt")).unwrap();
+        fs.remove(&Path::new("/a/b")).unwrap();
+        fs.remove(&Path::new("/a")).unwrap();
+
+        assert_eq!(fs.read_inode(2).unwrap().i_links_count, 4);
+        assert_eq!(fs.free_counts().unwrap(), fresh_counts());
+    }
+
+    #[test]
+    fn test_directory_grows_when_full() {
+        let device = MockExt4Device::new_writable();
+        let fs = mount(&device);
+
+        // Each entry takes 68 bytes, so a 1 KB block holds about a dozen
+        for i in 0..16 {
+            let name = format!("/a-rather-long-file-name-to-fill-the-directory-block-{:02}", i);
+            fs.write(&Path::new(&name), name.as_bytes()).unwrap();
+        }
+
+        assert_eq!(fs.stat(&Path::new("/")).unwrap().size, 2048);
+        let fs = mount(&device);
+        for i in 0..16 {
+            let name = format!("/a-rather-long-file-name-to-fill-the-directory-block-{:02}", i);
+            assert_eq!(fs.read(&Path::new(&name)).unwrap(), name.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_fragmented_file_grows_extent_tree() {
+        let device = MockExt4Device::new_writable();
+        let fs = mount(&device);
+
+        // Leave single-block holes so the next file needs many extents
+        for i in 0..10 {
+            fs.write(&Path::new(&format!("/f{}", i)), &[i as u8; 1024]).unwrap();
+        }
+        for i in (0..10).step_by(2) {
+            fs.remove(&Path::new(&format!("/f{}", i))).unwrap();
+        }
+
+        let big: Vec<u8> = (0..12 * 1024u32).map(|i| (i / 1024) as u8).collect();
+        fs.write(&Path::new("/big"), &big).unwrap();
+
+        // Five holes plus the tail: more extents than the root holds
+        let (_, inode) = fs.find_inode(&Path::new("/big")).unwrap();
+        assert_eq!(u16::from_le_bytes([inode.i_block[6], inode.i_block[7]]), 1);
+
+        let fs = mount(&device);
+        assert_eq!(fs.read(&Path::new("/big")).unwrap(), big);
+
+        // Freeing walks the index block too
+        fs.remove(&Path::new("/big")).unwrap();
+        for i in (1..10).step_by(2) {
+            fs.remove(&Path::new(&format!("/f{}", i))).unwrap();
+        }
+        assert_eq!(fs.free_counts().unwrap(), fresh_counts());
+    }
+
+    #[test]
+    fn test_out_of_space() {
+        let device = MockExt4Device::new_writable();
+        let fs = mount(&device);
+
+        let too_big = vec![0u8; (MockExt4Device::FREE_BLOCKS as usize + 1) * 1024];
+        assert_eq!(fs.write(&Path::new("/huge"), &too_big), Err(FsError::OutOfSpace));
+        assert!(!fs.exists(&Path::new("/huge")));
+        assert_eq!(fs.free_counts().unwrap(), fresh_counts());
+    }
+
+    #[test]
+    fn test_writes_with_metadata_checksums() {
+        let device = MockExt4Device::new_writable_with_checksums();
+        let fs = mount(&device);
+        assert_eq!(fs.read_only_reason(), None);
+
+        fs.create_dir(&Path::new("/docs")).unwrap();
+        fs.write(&Path::new("/docs/a.txt"), b"checksummed").unwrap();
+
+        let fs = mount(&device);
+        assert_eq!(fs.read(&Path::new("/docs/a.txt")).unwrap(), b"checksummed");
+
+        // The superblock checksum covers its first 1020 bytes
+        let sb = block::read_bytes(&device, 1024, 1024).unwrap();
+        let stored = u32::from_le_bytes([sb[0x3FC], sb[0x3FD], sb[0x3FE], sb[0x3FF]]);
+        assert_eq!(checksum::crc32c(!0, &sb[..0x3FC]), stored);
+    }
 }
@@ -5,9 +5,14 @@
 //! - Feature flags (extents, 64-bit, etc.)
 //! - Group descriptor location
 //! - Filesystem state and mount information
+//!
+//! Writes go through `adjust_free_counts()` and
+//! `BlockGroupDesc::write_to_device()`, which patch only the fields the
+//! driver tracks and recompute the metadata checksums.
 
 use crate::vfs::block_device::{BlockDevice, BlockDeviceError};
-use alloc::vec::Vec;
+use super::block::{read_block, read_bytes, write_block, write_bytes};
+use super::checksum::{crc16, crc32c};
 
 /// ext4 superblock magic number (0xEF53)
 const EXT4_SUPER_MAGIC: u16 = 0xEF53;
@@ -15,6 +20,85 @@ const EXT4_SUPER_MAGIC: u16 = 0xEF53;
 /// Superblock is located at byte offset 1024 from start of partition
 const SUPERBLOCK_OFFSET: u64 = 1024;
 
+/// Size of the on-disk superblock
+const SUPERBLOCK_SIZE: usize = 1024;
+
+/// Offset of `s_checksum` within the superblock
+const SUPERBLOCK_CSUM_OFFSET: usize = 0x3FC;
+
+/// `s_checksum_type` value for CRC32c
+const CHECKSUM_TYPE_CRC32C: u8 = 1;
+
+/// `s_state` bit set when errors were detected
+const STATE_ERRORS: u16 = 0x0002;
+
+/// Feature flags
+pub mod features {
+    /// Compatible: directories may carry a hashed (htree) index
+    pub const COMPAT_DIR_INDEX: u32 = 0x0020;
+    /// Compatible: sparse_super2 (explicit backup groups)
+    pub const COMPAT_SPARSE_SUPER2: u32 = 0x0200;
+
+    /// Incompatible: directory entries record the file type
+    pub const INCOMPAT_FILETYPE: u32 = 0x0002;
+    /// Incompatible: the journal needs to be replayed
+    pub const INCOMPAT_RECOVER: u32 = 0x0004;
+    /// Incompatible: files use extent trees
+    pub const INCOMPAT_EXTENTS: u32 = 0x0040;
+    /// Incompatible: block numbers are 64-bit
+    pub const INCOMPAT_64BIT: u32 = 0x0080;
+    /// Incompatible: group metadata may live in other groups
+    pub const INCOMPAT_FLEX_BG: u32 = 0x0200;
+    /// Incompatible: checksum seed is stored in the superblock
+    pub const INCOMPAT_CSUM_SEED: u32 = 0x2000;
+
+    /// Incompatible features the write path understands
+    ///
+    /// A volume with any other incompat bit set is only mounted read-only.
+    pub const INCOMPAT_WRITABLE: u32 =
+        INCOMPAT_FILETYPE | INCOMPAT_EXTENTS | INCOMPAT_64BIT | INCOMPAT_FLEX_BG | INCOMPAT_CSUM_SEED;
+
+    /// Read-only compatible: superblock backups only in some groups
+    pub const RO_COMPAT_SPARSE_SUPER: u32 = 0x0001;
+    /// Read-only compatible: files may exceed 2 GB
+    pub const RO_COMPAT_LARGE_FILE: u32 = 0x0002;
+    /// Read-only compatible: i_blocks may be in filesystem blocks
+    pub const RO_COMPAT_HUGE_FILE: u32 = 0x0008;
+    /// Read-only compatible: group descriptors carry CRC16 checksums
+    pub const RO_COMPAT_GDT_CSUM: u32 = 0x0010;
+    /// Read-only compatible: directories may exceed 65000 links
+    pub const RO_COMPAT_DIR_NLINK: u32 = 0x0020;
+    /// Read-only compatible: inodes reserve extra space
+    pub const RO_COMPAT_EXTRA_ISIZE: u32 = 0x0040;
+    /// Read-only compatible: all metadata carries CRC32c checksums
+    pub const RO_COMPAT_METADATA_CSUM: u32 = 0x0400;
+
+    /// Read-only compatible features the write path understands
+    pub const RO_COMPAT_WRITABLE: u32 = RO_COMPAT_SPARSE_SUPER
+        | RO_COMPAT_LARGE_FILE
+        | RO_COMPAT_HUGE_FILE
+        | RO_COMPAT_GDT_CSUM
+        | RO_COMPAT_DIR_NLINK
+        | RO_COMPAT_EXTRA_ISIZE
+        | RO_COMPAT_METADATA_CSUM;
+}
+
+/// Superblock flags (`s_flags`)
+pub mod sb_flags {
+    /// Directory hashes were computed with signed chars
+    pub const SIGNED_HASH: u32 = 0x0001;
+    /// Directory hashes were computed with unsigned chars
+    pub const UNSIGNED_HASH: u32 = 0x0002;
+}
+
+/// Block group flags (`bg_flags`)
+pub mod bg_flags {
+    /// Inode table and bitmap are not initialized
+    pub const INODE_UNINIT: u16 = 0x0001;
+    /// Block bitmap is not initialized
+    pub const BLOCK_UNINIT: u16 = 0x0002;
+}
+
 /// ext4 Superblock
 ///
 /// Contains filesystem metadata. The superblock is 1024 bytes and located
@@ -102,6 +186,20 @@ pub struct Ext4Superblock {
 
     /// Descriptor size (for 64-bit mode)
     pub s_desc_size: u16,
+
+    /// Seed for directory name hashes
+    pub s_hash_seed: [u32; 4],
+    /// Default directory hash algorithm
+    pub s_def_hash_version: u8,
+    /// Miscellaneous flags (see [`sb_flags`])
+    pub s_flags: u32,
+
+    /// Blocks reserved after the group descriptors for online resize
+    pub s_reserved_gdt_blocks: u16,
+    /// Metadata checksum algorithm (1 = CRC32c)
+    pub s_checksum_type: u8,
+    /// Checksum seed (only meaningful with `INCOMPAT_CSUM_SEED`)
+    pub s_checksum_seed: u32,
 }
 
 impl Ext4Superblock {
@@ -182,6 +280,20 @@ impl Ext4Superblock {
         // Descriptor size (offset 0xFE)
         let s_desc_size = u16::from_le_bytes([sb_data[0xFE], sb_data[0xFF]]);
 
+        // Directory hashing (offset 0xEC+) and flags (offset 0x160)
+        let mut s_hash_seed = [0u32; 4];
+        for (i, word) in s_hash_seed.iter_mut().enumerate() {
+            let at = 0xEC + i * 4;
+            *word = u32::from_le_bytes([sb_data[at], sb_data[at + 1], sb_data[at + 2], sb_data[at + 3]]);
+        }
+        let s_def_hash_version = sb_data[0xFC];
+        let s_flags = u32::from_le_bytes([sb_data[0x160], sb_data[0x161], sb_data[0x162], sb_data[0x163]]);
+
+        // Resize and checksum fields
+        let s_reserved_gdt_blocks = u16::from_le_bytes([sb_data[0xCE], sb_data[0xCF]]);
+        let s_checksum_type = sb_data[0x175];
+        let s_checksum_seed = u32::from_le_bytes([sb_data[0x270], sb_data[0x271], sb_data[0x272], sb_data[0x273]]);
+
         // Combine 64-bit values
         let s_blocks_count = (s_blocks_count_hi as u64) << 32 | s_blocks_count_lo as u64;
         let s_r_blocks_count = (s_r_blocks_count_hi as u64) << 32 | s_r_blocks_count_lo as u64;
@@ -226,6 +338,12 @@ impl Ext4Superblock {
             s_r_blocks_count_hi,
             s_free_blocks_count_hi,
             s_desc_size,
+            s_hash_seed,
+            s_def_hash_version,
+            s_flags,
+            s_reserved_gdt_blocks,
+            s_checksum_type,
+            s_checksum_seed,
         })
     }
 
@@ -241,25 +359,192 @@ impl Ext4Superblock {
 
     /// Get the number of block groups
     pub fn block_group_count(&self) -> u32 {
-        ((self.s_blocks_count + self.s_blocks_per_group as u64 - 1) / self.s_blocks_per_group as u64) as u32
+        // Groups start at s_first_data_block (block 1 on 1 KB-block volumes)
+        let blocks = self.s_blocks_count - self.s_first_data_block as u64;
+        blocks.div_ceil(self.s_blocks_per_group as u64) as u32
     }
 
     /// Check if 64-bit feature is enabled
     pub fn has_64bit(&self) -> bool {
-        const INCOMPAT_64BIT: u32 = 0x0080;
-        (self.s_feature_incompat & INCOMPAT_64BIT) != 0
+        (self.s_feature_incompat & features::INCOMPAT_64BIT) != 0
     }
 
     /// Check if extents feature is enabled
     pub fn has_extents(&self) -> bool {
-        const INCOMPAT_EXTENTS: u32 = 0x0040;
-        (self.s_feature_incompat & INCOMPAT_EXTENTS) != 0
+        (self.s_feature_incompat & features::INCOMPAT_EXTENTS) != 0
     }
 
     /// Check if flex_bg feature is enabled
     pub fn has_flex_bg(&self) -> bool {
-        const INCOMPAT_FLEX_BG: u32 = 0x0200;
-        (self.s_feature_incompat & INCOMPAT_FLEX_BG) != 0
+        (self.s_feature_incompat & features::INCOMPAT_FLEX_BG) != 0
+    }
+
+    /// Check if metadata_csum (CRC32c on all metadata) is enabled
+    pub fn has_metadata_csum(&self) -> bool {
+        (self.s_feature_ro_compat & features::RO_COMPAT_METADATA_CSUM) != 0
+    }
+
+    /// Check if gdt_csum (CRC16 on group descriptors only) is enabled
+    ///
+    /// metadata_csum supersedes gdt_csum when both are set.
+    pub fn has_gdt_csum(&self) -> bool {
+        (self.s_feature_ro_compat & features::RO_COMPAT_GDT_CSUM) != 0
+    }
+
+    /// Whether group flags such as `BLOCK_UNINIT` are in use
+    pub fn has_group_checksums(&self) -> bool {
+        self.has_metadata_csum() || self.has_gdt_csum()
+    }
+
+    /// Why this volume can't be modified, if it can't
+    ///
+    /// # Returns
+    ///
+    /// * `None` - The write path supports every feature the volume uses
+    /// * `Some(reason)` - The volume must be mounted read-only
+    pub fn read_only_reason(&self) -> Option<&'static str> {
+        if self.s_feature_incompat & features::INCOMPAT_RECOVER != 0 {
+            return Some("journal needs recovery");
+        }
+
+        if self.s_feature_incompat & !features::INCOMPAT_WRITABLE != 0 {
+            return Some("unsupported incompat features");
+        }
+
+        if self.s_feature_ro_compat & !features::RO_COMPAT_WRITABLE != 0 {
+            return Some("unsupported ro_compat features");
+        }
+
+        if self.s_feature_compat & features::COMPAT_SPARSE_SUPER2 != 0 {
+            return Some("sparse_super2 is not supported");
+        }
+
+        if !self.has_extents() || self.s_feature_incompat & features::INCOMPAT_FILETYPE == 0 {
+            return Some("volume lacks extents or filetype");
+        }
+
+        if self.has_metadata_csum() && self.s_checksum_type != CHECKSUM_TYPE_CRC32C {
+            return Some("unknown metadata checksum type");
+        }
+
+        if self.s_state & STATE_ERRORS != 0 {
+            return Some("filesystem has errors");
+        }
+
+        None
+    }
+
+    /// Seed for every metadata_csum checksum
+    pub fn checksum_seed(&self) -> u32 {
+        if self.s_feature_incompat & features::INCOMPAT_CSUM_SEED != 0 {
+            self.s_checksum_seed
+        } else {
+            crc32c(!0, &self.s_uuid)
+        }
+    }
+
+    /// Seed for the checksums of one inode and the blocks it owns
+    ///
+    /// Covers the inode itself, its extent tree blocks and (for
+    /// directories) its directory blocks.
+    pub fn inode_checksum_seed(&self, inode_num: u32, generation: u32) -> u32 {
+        let crc = crc32c(self.checksum_seed(), &inode_num.to_le_bytes());
+        crc32c(crc, &generation.to_le_bytes())
+    }
+
+    /// Checksum of a block or inode bitmap covering `bits` entries
+    pub fn bitmap_checksum(&self, bitmap: &[u8], bits: u32) -> u32 {
+        crc32c(self.checksum_seed(), &bitmap[..(bits / 8) as usize])
+    }
+
+    /// Checksum of one group descriptor (`bg_checksum`)
+    ///
+    /// `desc` is the raw descriptor, `descriptor_size()` bytes long.
+    pub fn group_desc_checksum(&self, group_num: u32, desc: &[u8]) -> u16 {
+        const CSUM_OFFSET: usize = 0x1E;
+        let group_le = group_num.to_le_bytes();
+
+        if self.has_metadata_csum() {
+            let mut crc = crc32c(self.checksum_seed(), &group_le);
+            crc = crc32c(crc, &desc[..CSUM_OFFSET]);
+            crc = crc32c(crc, &[0, 0]);
+            crc = crc32c(crc, &desc[CSUM_OFFSET + 2..]);
+            (crc & 0xFFFF) as u16
+        } else if self.has_gdt_csum() {
+            let mut crc = crc16(!0, &self.s_uuid);
+            crc = crc16(crc, &group_le);
+            crc = crc16(crc, &desc[..CSUM_OFFSET]);
+            crc16(crc, &desc[CSUM_OFFSET + 2..])
+        } else {
+            0
+        }
+    }
+
+    /// Number of blocks in a block group (the last group may be short)
+    pub fn blocks_in_group(&self, group_num: u32) -> u32 {
+        let first = self.group_first_block(group_num);
+        let remaining = self.s_blocks_count - first;
+        remaining.min(self.s_blocks_per_group as u64) as u32
+    }
+
+    /// First block of a block group
+    pub fn group_first_block(&self, group_num: u32) -> u64 {
+        self.s_first_data_block as u64 + group_num as u64 * self.s_blocks_per_group as u64
+    }
+
+    /// Number of blocks holding the group descriptor table
+    pub fn gdt_block_count(&self) -> u32 {
+        let bytes = self.block_group_count() as u64 * self.descriptor_size() as u64;
+        bytes.div_ceil(self.block_size() as u64) as u32
+    }
+
+    /// Whether a group holds a superblock backup (and descriptor table copy)
+    pub fn group_has_superblock(&self, group_num: u32) -> bool {
+        if group_num <= 1 || self.s_feature_ro_compat & features::RO_COMPAT_SPARSE_SUPER == 0 {
+            return true;
+        }
+
+        let is_power_of = |base: u32| {
+            let mut n = base;
+            while n < group_num {
+                n *= base;
+            }
+            n == group_num
+        };
+        is_power_of(3) || is_power_of(5) || is_power_of(7)
+    }
+
+    /// Add to the free block and inode counts in the primary superblock
+    ///
+    /// Reads the on-disk superblock, adjusts both counts, recomputes the
+    /// checksum and writes it back. Backup superblocks are left alone; like
+    /// Linux, we only keep the primary's counters current.
+    pub fn adjust_free_counts(
+        &self,
+        device: &dyn BlockDevice,
+        block_delta: i64,
+        inode_delta: i64,
+    ) -> Result<(), BlockDeviceError> {
+        let mut raw = read_bytes(device, SUPERBLOCK_OFFSET, SUPERBLOCK_SIZE)?;
+
+        let free_lo = u32::from_le_bytes([raw[12], raw[13], raw[14], raw[15]]) as u64;
+        let free_hi = u32::from_le_bytes([raw[0x158], raw[0x159], raw[0x15A], raw[0x15B]]) as u64;
+        let free_blocks = ((free_hi << 32 | free_lo) as i64 + block_delta).max(0) as u64;
+        raw[12..16].copy_from_slice(&(free_blocks as u32).to_le_bytes());
+        if self.has_64bit() {
+            raw[0x158..0x15C].copy_from_slice(&((free_blocks >> 32) as u32).to_le_bytes());
+        }
+
+        let free_inodes = u32::from_le_bytes([raw[16], raw[17], raw[18], raw[19]]) as i64;
+        let free_inodes = (free_inodes + inode_delta).max(0) as u32;
+        raw[16..20].copy_from_slice(&free_inodes.to_le_bytes());
+
+        if self.has_metadata_csum() {
+            let csum = crc32c(!0, &raw[..SUPERBLOCK_CSUM_OFFSET]);
+            raw[SUPERBLOCK_CSUM_OFFSET..].copy_from_slice(&csum.to_le_bytes());
+        }
+
+        write_bytes(device, SUPERBLOCK_OFFSET, &raw)
     }
 
     /// Get descriptor size (32 bytes for normal, 64 bytes for 64-bit)
@@ -290,9 +575,38 @@ pub struct BlockGroupDesc {
     pub bg_free_inodes_count: u32,
     /// Number of directories
     pub bg_used_dirs_count: u32,
+    /// Group flags (see [`bg_flags`])
+    pub bg_flags: u16,
+    /// Number of never-used inodes at the end of the inode table
+    pub bg_itable_unused: u32,
+    /// Checksum of the block bitmap (metadata_csum)
+    pub bg_block_bitmap_csum: u32,
+    /// Checksum of the inode bitmap (metadata_csum)
+    pub bg_inode_bitmap_csum: u32,
 }
 
 impl BlockGroupDesc {
+    /// Locate a group descriptor on disk
+    ///
+    /// # Returns
+    ///
+    /// `(block, offset)` - Block holding the descriptor and its byte offset
+    fn location(sb: &Ext4Superblock, group_num: u32) -> (u64, usize) {
+        let block_size = sb.block_size();
+        let desc_size = sb.descriptor_size() as u32;
+
+        // Group descriptor table starts after the superblock
+        // If block size is 1024, it's at block 2. Otherwise, block 1.
+        let gdt_block = if block_size == 1024 { 2 } else { 1 };
+
+        // Calculate descriptor offset
+        let desc_offset = group_num * desc_size;
+        let desc_block = gdt_block + (desc_offset / block_size);
+        let offset_in_block = desc_offset % block_size;
+
+        (desc_block as u64, offset_in_block as usize)
+    }
+
     /// Read block group descriptor from device
     ///
     /// # Arguments
@@ -310,52 +624,70 @@ impl BlockGroupDesc {
         sb: &Ext4Superblock,
         group_num: u32,
     ) -> Result<Self, BlockDeviceError> {
-        let block_size = sb.block_size();
-        let desc_size = sb.descriptor_size() as u32;
-
-        // Group descriptor table starts after the superblock
-        // If block size is 1024, it's at block 2. Otherwise, block 1.
-        let gdt_block = if block_size == 1024 { 2 } else { 1 };
+        let (block, offset) = Self::location(sb, group_num);
+        let data = read_block(device, sb, block)?;
+        let desc = &data[offset..offset + sb.descriptor_size() as usize];
 
-        // Calculate descriptor offset
-        let desc_offset = group_num * desc_size;
-        let desc_block = gdt_block + (desc_offset / block_size);
-        let offset_in_block = desc_offset % block_size;
+        let u16_at = |at: usize| u16::from_le_bytes([desc[at], desc[at + 1]]) as u32;
+        let u32_at = |at: usize| u32::from_le_bytes([desc[at], desc[at + 1], desc[at + 2], desc[at + 3]]);
 
-        // Read the block containing this descriptor
-        let sector_size = device.sector_size() as u64;
-        let block_offset = desc_block as u64 * block_size as u64;
-        let start_sector = block_offset / sector_size;
-        let sectors_per_block = (block_size as u64 + sector_size - 1) / sector_size;
-
-        let data = device.read_sectors(start_sector, sectors_per_block as u32)?;
-        let offset = (block_offset % sector_size) as usize + offset_in_block as usize;
-
-        // Parse descriptor (first 32 bytes are always present)
-        let bg_block_bitmap_lo = u32::from_le_bytes([data[offset], data[offset+1], data[offset+2], data[offset+3]]);
-        let bg_inode_bitmap_lo = u32::from_le_bytes([data[offset+4], data[offset+5], data[offset+6], data[offset+7]]);
-        let bg_inode_table_lo = u32::from_le_bytes([data[offset+8], data[offset+9], data[offset+10], data[offset+11]]);
-        let bg_free_blocks_count_lo = u16::from_le_bytes([data[offset+12], data[offset+13]]);
-        let bg_free_inodes_count_lo = u16::from_le_bytes([data[offset+14], data[offset+15]]);
-        let bg_used_dirs_count_lo = u16::from_le_bytes([data[offset+16], data[offset+17]]);
-
-        // For 64-bit, read high 32 bits (if descriptor is 64 bytes)
-        let (bg_block_bitmap_hi, bg_inode_bitmap_hi, bg_inode_table_hi) = if desc_size >= 64 {
-            let hi1 = u32::from_le_bytes([data[offset+32], data[offset+33], data[offset+34], data[offset+35]]);
-            let hi2 = u32::from_le_bytes([data[offset+36], data[offset+37], data[offset+38], data[offset+39]]);
-            let hi3 = u32::from_le_bytes([data[offset+40], data[offset+41], data[offset+42], data[offset+43]]);
-            (hi1, hi2, hi3)
-        } else {
-            (0, 0, 0)
-        };
+        // First 32 bytes are always present; 64-bit descriptors carry the high halves
+        let wide = desc.len() >= 64;
+        let hi16 = |at: usize| if wide { u16_at(at) } else { 0 };
+        let hi32 = |at: usize| if wide { u32_at(at) } else { 0 };
 
         Ok(Self {
-            bg_block_bitmap: (bg_block_bitmap_hi as u64) << 32 | bg_block_bitmap_lo as u64,
-            bg_inode_bitmap: (bg_inode_bitmap_hi as u64) << 32 | bg_inode_bitmap_lo as u64,
-            bg_inode_table: (bg_inode_table_hi as u64) << 32 | bg_inode_table_lo as u64,
-            bg_free_blocks_count: bg_free_blocks_count_lo as u32,
-            bg_free_inodes_count: bg_free_inodes_count_lo as u32,
-            bg_used_dirs_count: bg_used_dirs_count_lo as u32,
+            bg_block_bitmap: (hi32(0x20) as u64) << 32 | u32_at(0x00) as u64,
+            bg_inode_bitmap: (hi32(0x24) as u64) << 32 | u32_at(0x04) as u64,
+            bg_inode_table: (hi32(0x28) as u64) << 32 | u32_at(0x08) as u64,
+            bg_free_blocks_count: hi16(0x2C) << 16 | u16_at(0x0C),
+            bg_free_inodes_count: hi16(0x2E) << 16 | u16_at(0x0E),
+            bg_used_dirs_count: hi16(0x30) << 16 | u16_at(0x10),
+            bg_flags: u16_at(0x12) as u16,
+            bg_itable_unused: hi16(0x32) << 16 | u16_at(0x1C),
+            bg_block_bitmap_csum: hi16(0x38) << 16 | u16_at(0x18),
+            bg_inode_bitmap_csum: hi16(0x3A) << 16 | u16_at(0x1A),
         })
     }
+
+    /// Write this descriptor's counters, flags and bitmap checksums back
+    ///
+    /// Bitmap and inode table locations are never changed. The descriptor
+    /// checksum is recomputed.
+    pub fn write_to_device(
+        &self,
+        device: &dyn BlockDevice,
+        sb: &Ext4Superblock,
+        group_num: u32,
+    ) -> Result<(), BlockDeviceError> {
+        let (block, offset) = Self::location(sb, group_num);
+        let mut data = read_block(device, sb, block)?;
+        let desc = &mut data[offset..offset + sb.descriptor_size() as usize];
+
+        fn put16(desc: &mut [u8], at: usize, value: u32) {
+            desc[at..at + 2].copy_from_slice(&(value as u16).to_le_bytes());
+        }
+
+        put16(desc, 0x0C, self.bg_free_blocks_count);
+        put16(desc, 0x0E, self.bg_free_inodes_count);
+        put16(desc, 0x10, self.bg_used_dirs_count);
+        put16(desc, 0x12, self.bg_flags as u32);
+        put16(desc, 0x18, self.bg_block_bitmap_csum);
+        put16(desc, 0x1A, self.bg_inode_bitmap_csum);
+        put16(desc, 0x1C, self.bg_itable_unused);
+
+        if desc.len() >= 64 {
+            put16(desc, 0x2C, self.bg_free_blocks_count >> 16);
+            put16(desc, 0x2E, self.bg_free_inodes_count >> 16);
+            put16(desc, 0x30, self.bg_used_dirs_count >> 16);
+            put16(desc, 0x32, self.bg_itable_unused >> 16);
+            put16(desc, 0x38, self.bg_block_bitmap_csum >> 16);
+            put16(desc, 0x3A, self.bg_inode_bitmap_csum >> 16);
+        }
+
+        let csum = sb.group_desc_checksum(group_num, desc);
+        put16(desc, 0x1E, csum as u32);
+
+        write_block(device, sb, block, &data)
+    }
 }
@@ -11,6 +11,9 @@
 use super::super::block_device::BlockDevice;
 // String types removed - using fixed byte arrays instead
 
+/// FSInfo value meaning "unknown" (free count or next-free hint)
+pub const FSINFO_UNKNOWN: u32 = 0xFFFFFFFF;
+
 /// FSInfo (File System Information) structure
 ///
 /// FAT32-specific sector that tracks free space to speed up allocations.
@@ -70,6 +73,15 @@ impl FSInfo {
         })
     }
 
+    /// Store the free count and next-free hint back into an FSInfo sector
+    ///
+    /// Only the two counters are touched; signatures and reserved bytes are
+    /// left as they were read from disk.
+    pub fn write_to(&self, data: &mut [u8]) {
+        data[488..492].copy_from_slice(&self.free_clusters.to_le_bytes());
+        data[492..496].copy_from_slice(&self.next_free.to_le_bytes());
+    }
+
     /// Read and parse FSInfo from a block device
     pub fn from_device(device: &dyn BlockDevice, sector: u16) -> Result<Self, &'static str> {
         let data = device.read_sector(sector as u64)
@@ -457,5 +469,12 @@ mod tests {
         let fs = FSInfo::parse(&fsinfo).unwrap();
         assert_eq!(fs.free_clusters, 500000);
         assert_eq!(fs.next_free, 100);
+
+        // Counters round-trip without disturbing the signatures
+        let updated = FSInfo { free_clusters: 499990, next_free: 110 };
+        updated.write_to(&mut fsinfo);
+        let fs = FSInfo::parse(&fsinfo).unwrap();
+        assert_eq!(fs.free_clusters, 499990);
+        assert_eq!(fs.next_free, 110);
     }
 }
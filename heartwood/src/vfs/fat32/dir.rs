@@ -7,6 +7,10 @@
 //! 2. **Long entries (LFN)**: Unicode filenames like "My Document.docx"
 //!
 //! Long filenames are stored as multiple LFN entries followed by a short entry.
+//!
+//! When creating entries, every name gets a unique 8.3 alias (with a `~N`
+//! numeric tail if the name had to be shortened), and LFN entries are added
+//! whenever the alias alone would not reproduce the name exactly.
 
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
@@ -22,6 +26,33 @@ pub mod attr {
     pub const LONG_NAME: u8 = 0x0F; // READ_ONLY | HIDDEN | SYSTEM | VOLUME_ID
 }
 
+/// Size of one directory entry slot in bytes
+pub const ENTRY_SIZE: usize = 32;
+
+/// First byte of a deleted (reusable) entry
+pub const DELETED: u8 = 0xE5;
+
+/// First byte marking the end of a directory
+pub const END_OF_DIR: u8 = 0x00;
+
+/// Sequence flag on the LFN entry that holds the end of the name
+const LFN_LAST: u8 = 0x40;
+
+/// UTF-16 characters stored per LFN entry
+const LFN_CHARS_PER_ENTRY: usize = 13;
+
+/// Byte offsets of the 13 characters inside an LFN entry
+const LFN_CHAR_OFFSETS: [usize; LFN_CHARS_PER_ENTRY] = [1, 3, 5, 7, 9, 14, 16, 18, 20, 22, 24, 28, 30];
+
+/// Longest name (in UTF-16 units) an LFN chain can hold
+pub const MAX_LONG_NAME: usize = 255;
+
+/// Characters that may not appear in any FAT name
+const FORBIDDEN_CHARS: &str = "\"*/:<>?\\|";
+
+/// Characters allowed in an 8.3 name besides letters and digits
+const SHORT_NAME_SPECIALS: &str = "$%'-_@~`!(){}^#&";
+
 /// A parsed directory entry
 #[derive(Debug, Clone)]
 pub struct DirEntry {
@@ -191,6 +222,7 @@ pub struct DirEntryIter {
     data: Vec<u8>,
     offset: usize,
     lfn_buffer: Vec<String>, // Buffer for building long filenames
+    lfn_start: Option<usize>, // Slot of the first buffered LFN entry
 }
 
 impl DirEntryIter {
@@ -204,14 +236,25 @@ impl DirEntryIter {
             data,
             offset: 0,
             lfn_buffer: Vec::new(),
+            lfn_start: None,
         }
     }
 
     /// Get the next directory entry
     pub fn next(&mut self) -> Option<DirEntry> {
-        while self.offset + 32 <= self.data.len() {
-            let entry_data = &self.data[self.offset..self.offset + 32];
-            self.offset += 32;
+        self.next_with_slots().map(|(_, _, entry)| entry)
+    }
+
+    /// Get the next directory entry along with the slots it occupies
+    ///
+    /// Returns `(first_slot, short_slot, entry)`. `short_slot` is the index of
+    /// the 8.3 entry; `first_slot` is its first LFN entry, or `short_slot`
+    /// when the entry has no long name.
+    pub fn next_with_slots(&mut self) -> Option<(usize, usize, DirEntry)> {
+        while self.offset + ENTRY_SIZE <= self.data.len() {
+            let slot = self.offset / ENTRY_SIZE;
+            let entry_data = &self.data[self.offset..self.offset + ENTRY_SIZE];
+            self.offset += ENTRY_SIZE;
 
             // Check for end of directory
             if entry_data[0] == 0x00 {
@@ -221,6 +264,9 @@ impl DirEntryIter {
             // Try parsing as LFN entry
             if let Some((sequence, chars)) = DirEntry::parse_lfn_entry(entry_data) {
                 // LFN entries are in reverse order, so insert at front
+                if self.lfn_buffer.is_empty() {
+                    self.lfn_start = Some(slot);
+                }
                 if sequence == 1 {
                     // This is the last LFN entry, prepend to buffer
                     self.lfn_buffer.insert(0, chars);
@@ -232,16 +278,19 @@ impl DirEntryIter {
 
             // Try parsing as regular entry
             if let Some(mut entry) = DirEntry::parse(entry_data) {
+                let first_slot = self.lfn_start.take().unwrap_or(slot);
+
                 // If we have LFN entries buffered, use them as the name
                 if !self.lfn_buffer.is_empty() {
                     entry.name = self.lfn_buffer.concat();
                     self.lfn_buffer.clear();
                 }
-                return Some(entry);
+                return Some((first_slot, slot, entry));
             }
 
             // Skip invalid/deleted entries
             self.lfn_buffer.clear();
+            self.lfn_start = None;
         }
 
         None
@@ -257,6 +306,165 @@ impl DirEntryIter {
     }
 }
 
+/// Check whether a name may be stored in a FAT directory
+///
+/// Rejects empty names, `.` and `..`, names longer than an LFN chain can
+/// hold, control and reserved characters, and trailing dots or spaces
+/// (which other systems silently strip).
+pub fn is_valid_long_name(name: &str) -> bool {
+    if name.is_empty() || name == "." || name == ".." {
+        return false;
+    }
+
+    if name.encode_utf16().count() > MAX_LONG_NAME {
+        return false;
+    }
+
+    if name.ends_with('.') || name.ends_with(' ') {
+        return false;
+    }
+
+    !name.chars().any(|c| (c as u32) < 0x20 || FORBIDDEN_CHARS.contains(c))
+}
+
+/// Convert part of a name to 8.3 characters
+///
+/// Uppercases letters, drops spaces and dots, and replaces anything else an
+/// 8.3 name can't hold with `_`. Sets `lossy` if the result differs from the
+/// input in anything but case.
+fn short_name_chars(part: &str, lossy: &mut bool) -> Vec<u8> {
+    let mut out = Vec::new();
+    for c in part.chars() {
+        if c.is_ascii_alphanumeric() || SHORT_NAME_SPECIALS.contains(c) {
+            out.push(c.to_ascii_uppercase() as u8);
+        } else if c == ' ' || c == '.' {
+            *lossy = true;
+        } else {
+            out.push(b'_');
+            *lossy = true;
+        }
+    }
+    out
+}
+
+/// Generate the 8.3 alias for a name
+///
+/// The alias is the uppercased name when that fits; otherwise the base is
+/// shortened and given a `~N` tail, trying `~1`, `~2`, ... until `taken`
+/// reports an unused alias.
+///
+/// # Arguments
+///
+/// * `name` - The (valid) long name
+/// * `taken` - Whether an 11-byte alias is already used in the directory
+///
+/// # Returns
+///
+/// * `Some((alias, needs_lfn))` - The alias, and whether LFN entries are
+///   needed to preserve `name` exactly
+/// * `None` - Every numeric tail is taken
+pub fn short_name_for(name: &str, taken: impl Fn(&[u8; 11]) -> bool) -> Option<([u8; 11], bool)> {
+    let (base, ext) = match name.rfind('.') {
+        Some(pos) if pos > 0 => (&name[..pos], &name[pos + 1..]),
+        _ => (name, ""),
+    };
+
+    let mut lossy = false;
+    let mut base = short_name_chars(base, &mut lossy);
+    let mut ext = short_name_chars(ext, &mut lossy);
+
+    if base.is_empty() {
+        base.push(b'_');
+        lossy = true;
+    }
+    if base.len() > 8 || ext.len() > 3 {
+        lossy = true;
+    }
+    ext.truncate(3);
+
+    let mut alias = [b' '; 11];
+    alias[8..8 + ext.len()].copy_from_slice(&ext);
+
+    if !lossy {
+        alias[..base.len()].copy_from_slice(&base);
+        if !taken(&alias) {
+            let needs_lfn = DirEntry::parse_short_name(&alias) != name;
+            return Some((alias, needs_lfn));
+        }
+    }
+
+    for n in 1..=999_999u32 {
+        let tail = alloc::format!("~{}", n);
+        let keep = base.len().min(8 - tail.len());
+
+        alias[..8].fill(b' ');
+        alias[..keep].copy_from_slice(&base[..keep]);
+        alias[keep..keep + tail.len()].copy_from_slice(tail.as_bytes());
+
+        if !taken(&alias) {
+            return Some((alias, true));
+        }
+    }
+
+    None
+}
+
+/// Checksum of an 8.3 name, stored in each of its LFN entries
+pub fn lfn_checksum(short_name: &[u8; 11]) -> u8 {
+    short_name.iter().fold(0u8, |sum, &b| {
+        ((sum & 1) << 7).wrapping_add(sum >> 1).wrapping_add(b)
+    })
+}
+
+/// Encode the LFN entries for a name, in on-disk order
+///
+/// The first entry holds the end of the name (flagged with `0x40`); the
+/// entry holding its start comes last, directly before the 8.3 entry.
+pub fn encode_lfn_entries(name: &str, short_name: &[u8; 11]) -> Vec<[u8; ENTRY_SIZE]> {
+    let units: Vec<u16> = name.encode_utf16().collect();
+    let count = units.len().div_ceil(LFN_CHARS_PER_ENTRY);
+    let checksum = lfn_checksum(short_name);
+
+    let mut entries = Vec::with_capacity(count);
+    for sequence in (1..=count).rev() {
+        let mut entry = [0u8; ENTRY_SIZE];
+        entry[0] = sequence as u8 | if sequence == count { LFN_LAST } else { 0 };
+        entry[11] = attr::LONG_NAME;
+        entry[13] = checksum;
+
+        for (i, &offset) in LFN_CHAR_OFFSETS.iter().enumerate() {
+            let index = (sequence - 1) * LFN_CHARS_PER_ENTRY + i;
+            // The name is NUL-terminated, then padded with 0xFFFF
+            let unit = match index.cmp(&units.len()) {
+                core::cmp::Ordering::Less => units[index],
+                core::cmp::Ordering::Equal => 0x0000,
+                core::cmp::Ordering::Greater => 0xFFFF,
+            };
+            entry[offset..offset + 2].copy_from_slice(&unit.to_le_bytes());
+        }
+
+        entries.push(entry);
+    }
+
+    entries
+}
+
+/// Encode an 8.3 directory entry
+pub fn encode_short_entry(short_name: &[u8; 11], attributes: u8, first_cluster: u32, size: u32) -> [u8; ENTRY_SIZE] {
+    let mut entry = [0u8; ENTRY_SIZE];
+    entry[0..11].copy_from_slice(short_name);
+    entry[11] = attributes;
+    set_cluster_and_size(&mut entry, first_cluster, size);
+    entry
+}
+
+/// Update the first cluster and size of an existing 8.3 entry in place
+pub fn set_cluster_and_size(entry: &mut [u8], first_cluster: u32, size: u32) {
+    entry[20..22].copy_from_slice(&((first_cluster >> 16) as u16).to_le_bytes());
+    entry[26..28].copy_from_slice(&(first_cluster as u16).to_le_bytes());
+    entry[28..32].copy_from_slice(&size.to_le_bytes());
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -287,4 +495,62 @@ mod tests {
         assert!(entry.is_dir);
         assert!(!entry.is_hidden);
     }
+
+    #[test]
+    fn test_short_name_for_fitting_names() {
+        let (alias, needs_lfn) = short_name_for("KERNEL.BIN", |_| false).unwrap();
+        assert_eq!(&alias, b"KERNEL  BIN");
+        assert!(!needs_lfn);
+
+        // Lowercase fits in 8.3 but needs an LFN to keep its case
+        let (alias, needs_lfn) = short_name_for("notes.txt", |_| false).unwrap();
+        assert_eq!(&alias, b"NOTES   TXT");
+        assert!(needs_lfn);
+    }
+
+    #[test]
+    fn test_short_name_for_numeric_tails() {
+        let (alias, needs_lfn) = short_name_for("Rescued Photos.jpeg", |_| false).unwrap();
+        assert_eq!(&alias, b"RESCUE~1JPE");
+        assert!(needs_lfn);
+
+        // ~1 and ~2 are taken, so the next alias is ~3
+        let taken = |alias: &[u8; 11]| alias == b"RESCUE~1JPE" || alias == b"RESCUE~2JPE";
+        let (alias, _) = short_name_for("Rescued Photos.jpeg", taken).unwrap();
+        assert_eq!(&alias, b"RESCUE~3JPE");
+
+        let (alias, _) = short_name_for(".profile", |_| false).unwrap();
+        assert_eq!(&alias, b"PROFIL~1   ");
+    }
+
+    #[test]
+    fn test_long_name_validation() {
+        assert!(is_valid_long_name("Recovered Files"));
+        assert!(!is_valid_long_name(""));
+        assert!(!is_valid_long_name(".."));
+        assert!(!is_valid_long_name("what?"));
+        assert!(!is_valid_long_name("trailing."));
+    }
+
+    #[test]
+    fn test_lfn_entries_round_trip() {
+        let name = "A rather long filename.txt";
+        let (alias, needs_lfn) = short_name_for(name, |_| false).unwrap();
+        assert!(needs_lfn);
+
+        let mut data = Vec::new();
+        for entry in encode_lfn_entries(name, &alias) {
+            assert_eq!(entry[13], lfn_checksum(&alias));
+            data.extend_from_slice(&entry);
+        }
+        data.extend_from_slice(&encode_short_entry(&alias, attr::ARCHIVE, 0x12345, 99));
+
+        let mut iter = DirEntryIter::new(data);
+        let (first_slot, short_slot, entry) = iter.next_with_slots().unwrap();
+        assert_eq!(entry.name, name);
+        assert_eq!(entry.first_cluster, 0x12345);
+        assert_eq!(entry.size, 99);
+        assert_eq!((first_slot, short_slot), (0, 2));
+        assert!(iter.next().is_none());
+    }
 }
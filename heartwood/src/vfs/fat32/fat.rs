@@ -10,6 +10,9 @@
 //! - 0xFFFFFF0-0xFFFFFF6: Reserved
 //! - 0xFFFFFF7: Bad cluster
 //! - 0xFFFFFF8-0xFFFFFFF: End of chain (EOC)
+//!
+//! Every write goes to all FAT copies (`num_fats`) so the mirrors never
+//! disagree about which clusters are in use.
 
 use super::bpb::Fat32Bpb;
 use super::super::block_device::{BlockDevice, BlockDeviceError};
@@ -20,9 +23,13 @@ pub const FAT_FREE: u32 = 0x00000000;
 pub const FAT_BAD: u32 = 0x0FFFFFF7;
 pub const FAT_EOC: u32 = 0x0FFFFFF8; // End of chain (any value >= this)
 
-/// FAT Table reader
+/// End-of-chain value written when terminating a chain
+pub const FAT_EOC_MARK: u32 = 0x0FFFFFFF;
+
+/// FAT Table reader and writer
 ///
-/// Provides methods to read FAT entries and follow cluster chains.
+/// Provides methods to read FAT entries, follow cluster chains, and
+/// allocate or free chains.
 pub struct FatTable<'a> {
     device: &'a dyn BlockDevice,
     bpb: &'a Fat32Bpb,
@@ -65,6 +72,55 @@ impl<'a> FatTable<'a> {
         Ok(entry & 0x0FFFFFFF)
     }
 
+    /// Write a single FAT entry to every FAT copy
+    ///
+    /// The top 4 bits of the existing entry are reserved and preserved.
+    ///
+    /// # Arguments
+    ///
+    /// * `cluster` - Cluster number to update
+    /// * `value` - New entry value (next cluster, `FAT_EOC_MARK` or `FAT_FREE`)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - All copies updated
+    /// * `Err(BlockDeviceError)` - Invalid cluster or write failed
+    pub fn write_entry(&self, cluster: u32, value: u32) -> Result<(), BlockDeviceError> {
+        if cluster < 2 || cluster >= self.total_clusters() + 2 {
+            return Err(BlockDeviceError::InvalidSector);
+        }
+
+        let bytes_per_sector = self.bpb.bytes_per_sector as u64;
+        let fat_size = self.bpb.sectors_per_fat as u64 * bytes_per_sector;
+
+        for copy in 0..self.bpb.num_fats as u64 {
+            let fat_offset = self.bpb.fat_offset() + copy * fat_size + (cluster as u64 * 4);
+            let sector = fat_offset / bytes_per_sector;
+            let offset_in_sector = (fat_offset % bytes_per_sector) as usize;
+
+            let mut sector_data = self.device.read_sector(sector)?;
+            let entry = &mut sector_data[offset_in_sector..offset_in_sector + 4];
+
+            let old = u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]);
+            let new = (old & 0xF0000000) | (value & 0x0FFFFFFF);
+            entry.copy_from_slice(&new.to_le_bytes());
+
+            self.device.write_sector(sector, &sector_data)?;
+        }
+
+        Ok(())
+    }
+
+    /// Number of data clusters on the volume
+    ///
+    /// Valid cluster numbers are `2..total_clusters() + 2`.
+    pub fn total_clusters(&self) -> u32 {
+        let data_sectors = self.bpb.total_sectors as u64
+            - self.bpb.reserved_sectors as u64
+            - (self.bpb.num_fats as u64 * self.bpb.sectors_per_fat as u64);
+        (data_sectors / self.bpb.sectors_per_cluster as u64) as u32
+    }
+
     /// Check if a FAT entry indicates end of chain
     pub fn is_eoc(entry: u32) -> bool {
         (entry & 0x0FFFFFFF) >= FAT_EOC
@@ -195,30 +251,91 @@ impl<'a> FatTable<'a> {
     /// * `Ok(u32)` - Free cluster number
     /// * `Err(IoError)` - No free clusters (disk full)
     pub fn find_free_cluster(&self, start_hint: u32) -> Result<u32, BlockDeviceError> {
-        // Calculate total clusters
-        let data_sectors = self.bpb.total_sectors as u64
-            - self.bpb.reserved_sectors as u64
-            - (self.bpb.num_fats as u64 * self.bpb.sectors_per_fat as u64);
-        let total_clusters = (data_sectors / self.bpb.sectors_per_cluster as u64) as u32;
+        self.next_free_cluster(start_hint)?
+            .ok_or(BlockDeviceError::IoError)
+    }
 
-        // Search from hint to end
-        for cluster in start_hint..total_clusters + 2 {
+    /// Search for a free cluster, wrapping around once
+    ///
+    /// An out-of-range hint (such as FSInfo's "unknown" value) starts the
+    /// search at cluster 2.
+    fn next_free_cluster(&self, start_hint: u32) -> Result<Option<u32>, BlockDeviceError> {
+        let end = self.total_clusters() + 2;
+        let start = if (2..end).contains(&start_hint) { start_hint } else { 2 };
+
+        // Search from hint to end, then wrap around from cluster 2 to hint
+        for cluster in (start..end).chain(2..start) {
             let entry = self.read_entry(cluster)?;
             if Self::is_free(entry) {
-                return Ok(cluster);
+                return Ok(Some(cluster));
             }
         }
 
-        // Wrap around and search from cluster 2 to hint
-        for cluster in 2..start_hint {
-            let entry = self.read_entry(cluster)?;
-            if Self::is_free(entry) {
-                return Ok(cluster);
+        // No free clusters
+        Ok(None)
+    }
+
+    /// Allocate and link a new cluster chain
+    ///
+    /// Each cluster is claimed in the FAT as soon as it is found, so the
+    /// search never hands out the same cluster twice.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - Number of clusters to allocate (must be at least 1)
+    /// * `start_hint` - Cluster to start searching from (FSInfo next-free)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(chain))` - The new chain, in order, terminated with EOC
+    /// * `Ok(None)` - Not enough free clusters (nothing is left allocated)
+    /// * `Err(BlockDeviceError)` - Read or write failed
+    pub fn allocate_chain(&self, count: u32, start_hint: u32) -> Result<Option<Vec<u32>>, BlockDeviceError> {
+        let mut chain: Vec<u32> = Vec::with_capacity(count as usize);
+        let mut hint = start_hint;
+
+        while chain.len() < count as usize {
+            let cluster = match self.next_free_cluster(hint)? {
+                Some(cluster) => cluster,
+                None => {
+                    // Disk full - give back what we claimed so far
+                    if let Some(&first) = chain.first() {
+                        self.free_chain(first)?;
+                    }
+                    return Ok(None);
+                }
+            };
+
+            self.write_entry(cluster, FAT_EOC_MARK)?;
+            if let Some(&previous) = chain.last() {
+                self.write_entry(previous, cluster)?;
             }
+
+            chain.push(cluster);
+            hint = cluster + 1;
         }
 
-        // No free clusters
-        Err(BlockDeviceError::IoError)
+        Ok(Some(chain))
+    }
+
+    /// Free every cluster in a chain
+    ///
+    /// # Arguments
+    ///
+    /// * `start_cluster` - First cluster in the chain
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(u32)` - Number of clusters freed
+    /// * `Err(BlockDeviceError)` - Read or write failed
+    pub fn free_chain(&self, start_cluster: u32) -> Result<u32, BlockDeviceError> {
+        let chain = self.follow_chain(start_cluster)?;
+
+        for &cluster in &chain {
+            self.write_entry(cluster, FAT_FREE)?;
+        }
+
+        Ok(chain.len() as u32)
     }
 }
 
@@ -1,10 +1,11 @@
 //! FAT32 Filesystem Driver
 //!
-//! Implements read-only FAT32 support for AethelOS.
+//! Implements read-write FAT32 support for AethelOS.
 //!
 //! FAT32 is the most widely used filesystem for removable media (USB drives,
 //! SD cards) and is supported by all major operating systems. This driver
-//! allows AethelOS to read files from FAT32 volumes.
+//! allows AethelOS to read files from FAT32 volumes and to write recovered
+//! files back onto them.
 //!
 //! # Features
 //!
@@ -12,7 +13,20 @@
 //! - FAT table navigation and cluster chain following
 //! - Directory entry parsing (including long filenames)
 //! - File reading
-//! - Read-only access (write support planned for future)
+//! - File writing, removal and directory creation
+//! - Cluster chain allocation, mirrored to every FAT copy
+//! - LFN + 8.3 directory entry creation
+//! - FSInfo free-count tracking
+//!
+//! # Write Ordering
+//!
+//! New data is written to freshly allocated clusters before the directory
+//! entry points at it, and old clusters are freed only afterwards. An
+//! interrupted write can leak clusters, but never leaves an entry pointing
+//! at half-written or free clusters.
+//!
+//! Writes at an offset update only the clusters they touch, in place, and
+//! link any clusters the file grows into only once those hold their data.
 //!
 //! # Example
 //!
@@ -28,21 +42,51 @@ pub mod bpb;
 pub mod fat;
 pub mod dir;
 
-use super::{FileSystem, Path, FsError, DirEntry as VfsDirEntry, FileStat};
-use super::block_device::BlockDevice;
-use bpb::Fat32Bpb;
+use super::{FileSystem, Path, FsError, DirEntry as VfsDirEntry, FileStat, FileType};
+use super::block_device::{BlockDevice, BlockDeviceError};
+use bpb::{Fat32Bpb, FSInfo, FSINFO_UNKNOWN};
 use fat::FatTable;
-use dir::{DirEntry, DirEntryIter};
+use dir::{DirEntry, DirEntryIter, ENTRY_SIZE};
 use alloc::boxed::Box;
+use alloc::vec;
 use alloc::vec::Vec;
 use alloc::string::ToString;
+use spin::Mutex;
 
 /// FAT32 Filesystem
 ///
-/// Provides read-only access to FAT32 volumes.
+/// Provides read-write access to FAT32 volumes.
 pub struct Fat32 {
     device: Box<dyn BlockDevice>,
     pub bpb: Fat32Bpb,
+    /// Live free-cluster count and next-free hint
+    ///
+    /// Held for the whole of every mutating operation, which also keeps
+    /// concurrent writers from claiming the same clusters.
+    fsinfo: Mutex<FSInfo>,
+}
+
+/// A directory's raw entry slots and the clusters they live in
+struct RawDir {
+    clusters: Vec<u32>,
+    data: Vec<u8>,
+}
+
+/// An entry found in a directory, with the slots it occupies
+struct Located {
+    /// First slot of the entry (its first LFN slot, if it has a long name)
+    first_slot: usize,
+    /// Slot holding the 8.3 entry
+    short_slot: usize,
+    entry: DirEntry,
+}
+
+/// Map a block device error onto the VFS error space
+fn device_error(error: BlockDeviceError) -> FsError {
+    match error {
+        BlockDeviceError::WriteProtected => FsError::ReadOnly,
+        _ => FsError::IoError,
+    }
 }
 
 impl Fat32 {
@@ -68,7 +112,23 @@ impl Fat32 {
         unsafe {
         }
 
-        Ok(Self { device, bpb })
+        let fsinfo = bpb.fsinfo.clone().unwrap_or(FSInfo {
+            free_clusters: FSINFO_UNKNOWN,
+            next_free: FSINFO_UNKNOWN,
+        });
+
+        Ok(Self { device, bpb, fsinfo: Mutex::new(fsinfo) })
+    }
+
+    /// Number of free clusters, as tracked since mount
+    ///
+    /// Unlike `bpb.free_clusters()`, this reflects writes made through this
+    /// driver. Returns `None` if the volume has no usable FSInfo count.
+    pub fn free_clusters(&self) -> Option<u32> {
+        match self.fsinfo.lock().free_clusters {
+            FSINFO_UNKNOWN => None,
+            count => Some(count),
+        }
     }
 
     /// Find a file or directory by path
@@ -140,6 +200,370 @@ impl Fat32 {
 
         Err(FsError::NotFound)
     }
+
+    /// FAT accessor for this volume
+    fn fat(&self) -> FatTable<'_> {
+        FatTable::new(&*self.device, &self.bpb)
+    }
+
+    /// Fail with `ReadOnly` if the device can't be written
+    fn check_writable(&self) -> Result<(), FsError> {
+        if self.device.is_read_only() {
+            return Err(FsError::ReadOnly);
+        }
+        Ok(())
+    }
+
+    /// First cluster of a directory entry's contents
+    ///
+    /// `..` entries pointing at the root store cluster 0.
+    fn dir_cluster(&self, entry: &DirEntry) -> u32 {
+        if entry.first_cluster == 0 {
+            self.bpb.root_cluster
+        } else {
+            entry.first_cluster
+        }
+    }
+
+    /// Find the directory that should hold `path`, and the name within it
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((cluster, name))` - Parent directory's first cluster and the final component
+    /// * `Err(FsError::InvalidPath)` - `path` is the root, `.` or `..`
+    /// * `Err(FsError::NotFound)` - The parent doesn't exist
+    /// * `Err(FsError::NotADirectory)` - The parent is a file
+    fn resolve_parent<'p>(&self, path: &'p Path) -> Result<(u32, &'p str), FsError> {
+        let name = path.file_name().ok_or(FsError::InvalidPath)?;
+        if name == "." || name == ".." {
+            return Err(FsError::InvalidPath);
+        }
+
+        let parent = path.parent().unwrap_or_else(|| Path::new("/"));
+        let entry = self.find_entry(&parent)?;
+        if !entry.is_dir {
+            return Err(FsError::NotADirectory);
+        }
+
+        Ok((self.dir_cluster(&entry), name))
+    }
+
+    /// Read every slot of a directory
+    fn load_dir(&self, first_cluster: u32) -> Result<RawDir, FsError> {
+        let fat = self.fat();
+        let clusters = fat.follow_chain(first_cluster).map_err(device_error)?;
+
+        let mut data = Vec::with_capacity(clusters.len() * self.bpb.cluster_size() as usize);
+        for &cluster in &clusters {
+            let sector = self.bpb.cluster_to_sector(cluster);
+            let cluster_data = self.device
+                .read_sectors(sector, self.bpb.sectors_per_cluster as u32)
+                .map_err(device_error)?;
+            data.extend_from_slice(&cluster_data);
+        }
+
+        Ok(RawDir { clusters, data })
+    }
+
+    /// Write back the clusters of a directory covering `count` slots from `first_slot`
+    fn store_slots(&self, dir: &RawDir, first_slot: usize, count: usize) -> Result<(), FsError> {
+        let cluster_size = self.bpb.cluster_size() as usize;
+        let first = first_slot * ENTRY_SIZE / cluster_size;
+        let last = ((first_slot + count) * ENTRY_SIZE - 1) / cluster_size;
+
+        for index in first..=last {
+            let bytes = &dir.data[index * cluster_size..(index + 1) * cluster_size];
+            self.write_cluster(dir.clusters[index], bytes)?;
+        }
+
+        Ok(())
+    }
+
+    /// Write one cluster, zero-padding `bytes` to the cluster size
+    fn write_cluster(&self, cluster: u32, bytes: &[u8]) -> Result<(), FsError> {
+        let cluster_size = self.bpb.cluster_size() as usize;
+        let sector = self.bpb.cluster_to_sector(cluster);
+
+        if bytes.len() == cluster_size {
+            return self.device.write_sectors(sector, bytes).map_err(device_error);
+        }
+
+        let mut padded = vec![0u8; cluster_size];
+        padded[..bytes.len()].copy_from_slice(bytes);
+        self.device.write_sectors(sector, &padded).map_err(device_error)
+    }
+
+    /// Find a named entry in a loaded directory (case-insensitive)
+    fn locate(dir: &RawDir, name: &str) -> Option<Located> {
+        let mut iter = DirEntryIter::new(dir.data.clone());
+        while let Some((first_slot, short_slot, entry)) = iter.next_with_slots() {
+            if entry.name.eq_ignore_ascii_case(name) {
+                return Some(Located { first_slot, short_slot, entry });
+            }
+        }
+        None
+    }
+
+    /// Collect the 8.3 names in use in a directory
+    fn short_names(dir: &RawDir) -> Vec<[u8; 11]> {
+        let mut names = Vec::new();
+        for slot in dir.data.chunks_exact(ENTRY_SIZE) {
+            if slot[0] == dir::END_OF_DIR {
+                break;
+            }
+            if slot[0] == dir::DELETED || slot[11] == dir::attr::LONG_NAME {
+                continue;
+            }
+
+            let mut name = [0u8; 11];
+            name.copy_from_slice(&slot[0..11]);
+            names.push(name);
+        }
+        names
+    }
+
+    /// Find `count` consecutive reusable slots in a directory
+    fn find_free_slots(dir: &RawDir, count: usize) -> Option<usize> {
+        let mut run_start = 0;
+        let mut run_len = 0;
+
+        for (slot, entry) in dir.data.chunks_exact(ENTRY_SIZE).enumerate() {
+            if entry[0] == dir::DELETED || entry[0] == dir::END_OF_DIR {
+                if run_len == 0 {
+                    run_start = slot;
+                }
+                run_len += 1;
+                if run_len == count {
+                    return Some(run_start);
+                }
+            } else {
+                run_len = 0;
+            }
+        }
+
+        None
+    }
+
+    /// Allocate a cluster chain and account for it in FSInfo
+    fn allocate_clusters(&self, info: &mut FSInfo, count: u32) -> Result<Vec<u32>, FsError> {
+        let chain = self.fat()
+            .allocate_chain(count, info.next_free)
+            .map_err(device_error)?
+            .ok_or(FsError::OutOfSpace)?;
+
+        if info.free_clusters != FSINFO_UNKNOWN {
+            info.free_clusters = info.free_clusters.saturating_sub(count);
+        }
+        if let Some(&last) = chain.last() {
+            info.next_free = last + 1;
+        }
+
+        Ok(chain)
+    }
+
+    /// Free a cluster chain and account for it in FSInfo
+    fn release_chain(&self, info: &mut FSInfo, first_cluster: u32) -> Result<(), FsError> {
+        let freed = self.fat().free_chain(first_cluster).map_err(device_error)?;
+
+        if info.free_clusters != FSINFO_UNKNOWN {
+            info.free_clusters = info.free_clusters.saturating_add(freed);
+        }
+
+        Ok(())
+    }
+
+    /// Write file contents into newly allocated clusters
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(cluster)` - First cluster of the new chain (0 for empty data)
+    /// * `Err(FsError::OutOfSpace)` - Not enough free clusters
+    fn store_data(&self, info: &mut FSInfo, data: &[u8]) -> Result<u32, FsError> {
+        if data.is_empty() {
+            return Ok(0);
+        }
+
+        let cluster_size = self.bpb.cluster_size() as usize;
+        let count = data.len().div_ceil(cluster_size) as u32;
+        let chain = self.allocate_clusters(info, count)?;
+
+        for (cluster, bytes) in chain.iter().zip(data.chunks(cluster_size)) {
+            if let Err(e) = self.write_cluster(*cluster, bytes) {
+                let _ = self.release_chain(info, chain[0]);
+                return Err(e);
+            }
+        }
+
+        Ok(chain[0])
+    }
+
+    /// Grow a directory by one zeroed cluster
+    fn extend_dir(&self, info: &mut FSInfo, dir: &mut RawDir) -> Result<(), FsError> {
+        let cluster = self.allocate_clusters(info, 1)?[0];
+        let cluster_size = self.bpb.cluster_size() as usize;
+
+        let linked = self.write_cluster(cluster, &[])
+            .and_then(|_| {
+                let last = *dir.clusters.last().ok_or(FsError::IoError)?;
+                self.fat().write_entry(last, cluster).map_err(device_error)
+            });
+        if let Err(e) = linked {
+            let _ = self.release_chain(info, cluster);
+            return Err(e);
+        }
+
+        dir.clusters.push(cluster);
+        dir.data.resize(dir.data.len() + cluster_size, 0);
+        Ok(())
+    }
+
+    /// Add a new entry (LFN entries plus 8.3 alias) to a directory
+    fn insert_entry(
+        &self,
+        info: &mut FSInfo,
+        dir: &mut RawDir,
+        name: &str,
+        attributes: u8,
+        first_cluster: u32,
+        size: u32,
+    ) -> Result<(), FsError> {
+        if !dir::is_valid_long_name(name) {
+            return Err(FsError::InvalidPath);
+        }
+
+        let taken = Self::short_names(dir);
+        let (alias, needs_lfn) = dir::short_name_for(name, |alias| taken.contains(alias))
+            .ok_or(FsError::AlreadyExists)?;
+
+        let mut slots = if needs_lfn {
+            dir::encode_lfn_entries(name, &alias)
+        } else {
+            Vec::new()
+        };
+        slots.push(dir::encode_short_entry(&alias, attributes, first_cluster, size));
+
+        let first_slot = loop {
+            if let Some(slot) = Self::find_free_slots(dir, slots.len()) {
+                break slot;
+            }
+            self.extend_dir(info, dir)?;
+        };
+
+        for (i, entry) in slots.iter().enumerate() {
+            let offset = (first_slot + i) * ENTRY_SIZE;
+            dir.data[offset..offset + ENTRY_SIZE].copy_from_slice(entry);
+        }
+
+        self.store_slots(dir, first_slot, slots.len())
+    }
+
+    /// Create a directory, creating missing parents first
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(cluster)` - First cluster of the new directory
+    fn make_dir(&self, info: &mut FSInfo, path: &Path) -> Result<u32, FsError> {
+        let name = path.file_name().ok_or(FsError::AlreadyExists)?;
+        if name == "." || name == ".." {
+            return Err(FsError::InvalidPath);
+        }
+
+        let parent_path = path.parent().unwrap_or_else(|| Path::new("/"));
+        let parent = match self.find_entry(&parent_path) {
+            Ok(entry) if entry.is_dir => self.dir_cluster(&entry),
+            Ok(_) => return Err(FsError::NotADirectory),
+            Err(FsError::NotFound) => self.make_dir(info, &parent_path)?,
+            Err(e) => return Err(e),
+        };
+
+        let mut dir = self.load_dir(parent)?;
+        if Self::locate(&dir, name).is_some() {
+            return Err(FsError::AlreadyExists);
+        }
+
+        let cluster = self.allocate_clusters(info, 1)?[0];
+
+        // Every directory starts with "." and ".." (0 means the root)
+        let parent_ref = if parent == self.bpb.root_cluster { 0 } else { parent };
+        let mut contents = [0u8; 2 * ENTRY_SIZE];
+        contents[..ENTRY_SIZE].copy_from_slice(
+            &dir::encode_short_entry(b".          ", dir::attr::DIRECTORY, cluster, 0));
+        contents[ENTRY_SIZE..].copy_from_slice(
+            &dir::encode_short_entry(b"..         ", dir::attr::DIRECTORY, parent_ref, 0));
+
+        let created = self.write_cluster(cluster, &contents)
+            .and_then(|_| self.insert_entry(info, &mut dir, name, dir::attr::DIRECTORY, cluster, 0));
+        if let Err(e) = created {
+            let _ = self.release_chain(info, cluster);
+            return Err(e);
+        }
+
+        Ok(cluster)
+    }
+
+    /// Fill the clusters covering `from..offset + data.len()` of a file
+    ///
+    /// `chain` holds the file's clusters; the first `old_clusters` of them
+    /// already carry data and are read back where only partly overwritten.
+    /// Bytes between the old end of file and `offset` are zeroed, since the
+    /// tail of the old last cluster may hold stale data.
+    fn fill_range(
+        &self,
+        chain: &[u32],
+        old_clusters: usize,
+        old_size: u64,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<(), FsError> {
+        let cluster_size = self.bpb.cluster_size() as u64;
+        let end = offset + data.len() as u64;
+        let from = offset.min(old_size);
+
+        let first = (from / cluster_size) as usize;
+        let last = ((end - 1) / cluster_size) as usize;
+
+        for (index, &cluster) in chain.iter().enumerate().take(last + 1).skip(first) {
+            let base = index as u64 * cluster_size;
+            let limit = base + cluster_size;
+
+            let mut bytes = if index < old_clusters && (base < from || limit > end) {
+                let sector = self.bpb.cluster_to_sector(cluster);
+                self.device
+                    .read_sectors(sector, self.bpb.sectors_per_cluster as u32)
+                    .map_err(device_error)?
+            } else {
+                vec![0u8; cluster_size as usize]
+            };
+
+            let (gap_start, gap_end) = (old_size.max(base), offset.min(limit));
+            if gap_start < gap_end {
+                bytes[(gap_start - base) as usize..(gap_end - base) as usize].fill(0);
+            }
+
+            let (data_start, data_end) = (offset.max(base), end.min(limit));
+            if data_start < data_end {
+                bytes[(data_start - base) as usize..(data_end - base) as usize]
+                    .copy_from_slice(&data[(data_start - offset) as usize..(data_end - offset) as usize]);
+            }
+
+            self.write_cluster(cluster, &bytes)?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the tracked free count and next-free hint to the FSInfo sector
+    fn flush_fsinfo(&self, info: &FSInfo) -> Result<(), FsError> {
+        if self.bpb.fsinfo.is_none() {
+            // No valid FSInfo sector on this volume
+            return Ok(());
+        }
+
+        let sector = self.bpb.fsinfo_sector as u64;
+        let mut data = self.device.read_sector(sector).map_err(device_error)?;
+        info.write_to(&mut data);
+        self.device.write_sector(sector, &data).map_err(device_error)
+    }
 }
 
 impl FileSystem for Fat32 {
@@ -154,6 +578,11 @@ impl FileSystem for Fat32 {
             return Err(FsError::IsADirectory);
         }
 
+        // Empty files have no cluster chain
+        if entry.size == 0 {
+            return Ok(Vec::new());
+        }
+
         // Read file data
         let fat = FatTable::new(&*self.device, &self.bpb);
         let data = fat.read_chain(entry.first_cluster, entry.size as u64)
@@ -162,17 +591,168 @@ impl FileSystem for Fat32 {
         Ok(data)
     }
 
-    fn write(&self, _path: &Path, _data: &[u8]) -> Result<(), FsError> {
-        // Write support not yet implemented
-        Err(FsError::ReadOnly)
+    fn write(&self, path: &Path, data: &[u8]) -> Result<(), FsError> {
+        self.check_writable()?;
+
+        // FAT32 file sizes are 32-bit
+        let size = u32::try_from(data.len()).map_err(|_| FsError::OutOfSpace)?;
+
+        let mut info = self.fsinfo.lock();
+        let (parent, name) = self.resolve_parent(path)?;
+        let mut dir = self.load_dir(parent)?;
+
+        let existing = Self::locate(&dir, name);
+        if existing.as_ref().is_some_and(|found| found.entry.is_dir) {
+            return Err(FsError::IsADirectory);
+        }
+
+        // New contents go to fresh clusters before any entry points at them
+        let first_cluster = self.store_data(&mut info, data)?;
+
+        let result = match &existing {
+            Some(found) => {
+                let offset = found.short_slot * ENTRY_SIZE;
+                dir::set_cluster_and_size(&mut dir.data[offset..offset + ENTRY_SIZE], first_cluster, size);
+                self.store_slots(&dir, found.short_slot, 1)
+            }
+            None => self.insert_entry(&mut info, &mut dir, name, dir::attr::ARCHIVE, first_cluster, size),
+        };
+
+        if let Err(e) = result {
+            if first_cluster != 0 {
+                let _ = self.release_chain(&mut info, first_cluster);
+            }
+            return Err(e);
+        }
+
+        // Only now is the old chain unreferenced
+        if let Some(found) = existing {
+            if found.entry.first_cluster >= 2 {
+                self.release_chain(&mut info, found.entry.first_cluster)?;
+            }
+        }
+
+        self.flush_fsinfo(&info)
+    }
+
+    fn write_at(&self, path: &Path, offset: u64, data: &[u8]) -> Result<(), FsError> {
+        self.check_writable()?;
+
+        // FAT32 file sizes are 32-bit
+        let end = offset
+            .checked_add(data.len() as u64)
+            .and_then(|end| u32::try_from(end).ok())
+            .ok_or(FsError::OutOfSpace)?;
+
+        // A missing file is created empty, then written like any other
+        match self.find_entry(path) {
+            Err(FsError::NotFound) => self.write(path, &[])?,
+            Err(e) => return Err(e),
+            Ok(_) => {}
+        }
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        let mut info = self.fsinfo.lock();
+        let (parent, name) = self.resolve_parent(path)?;
+        let mut dir = self.load_dir(parent)?;
+        let found = Self::locate(&dir, name).ok_or(FsError::NotFound)?;
+        if found.entry.is_dir {
+            return Err(FsError::IsADirectory);
+        }
+
+        let mut chain = if found.entry.first_cluster >= 2 {
+            self.fat().follow_chain(found.entry.first_cluster).map_err(device_error)?
+        } else {
+            Vec::new()
+        };
+        let old_clusters = chain.len();
+
+        // Clusters the file grows into stay unlinked until they hold data
+        let cluster_size = self.bpb.cluster_size() as u64;
+        let needed = (end as u64).div_ceil(cluster_size) as usize;
+        if needed > old_clusters {
+            let grown = self.allocate_clusters(&mut info, (needed - old_clusters) as u32)?;
+            chain.extend_from_slice(&grown);
+        }
+
+        let old_size = found.entry.size as u64;
+        let linked = self.fill_range(&chain, old_clusters, old_size, offset, data)
+            .and_then(|_| {
+                if old_clusters == 0 || chain.len() == old_clusters {
+                    return Ok(());
+                }
+                self.fat()
+                    .write_entry(chain[old_clusters - 1], chain[old_clusters])
+                    .map_err(device_error)
+            });
+        if let Err(e) = linked {
+            if chain.len() > old_clusters {
+                let _ = self.release_chain(&mut info, chain[old_clusters]);
+            }
+            return Err(e);
+        }
+
+        let first_cluster = chain.first().copied().unwrap_or(0);
+        let size = end.max(found.entry.size);
+        if first_cluster != found.entry.first_cluster || size != found.entry.size {
+            let at = found.short_slot * ENTRY_SIZE;
+            dir::set_cluster_and_size(&mut dir.data[at..at + ENTRY_SIZE], first_cluster, size);
+            self.store_slots(&dir, found.short_slot, 1)?;
+        }
+
+        if chain.len() > old_clusters {
+            self.flush_fsinfo(&info)?;
+        }
+        Ok(())
     }
 
-    fn remove(&self, _path: &Path) -> Result<(), FsError> {
-        Err(FsError::ReadOnly)
+    fn remove(&self, path: &Path) -> Result<(), FsError> {
+        self.check_writable()?;
+
+        if path.is_root() {
+            return Err(FsError::PermissionDenied); // Can't delete root
+        }
+
+        let mut info = self.fsinfo.lock();
+        let (parent, name) = self.resolve_parent(path)?;
+        let mut dir = self.load_dir(parent)?;
+        let found = Self::locate(&dir, name).ok_or(FsError::NotFound)?;
+
+        if found.entry.is_dir {
+            let contents = self.load_dir(self.dir_cluster(&found.entry))?;
+            let has_children = DirEntryIter::new(contents.data)
+                .collect()
+                .iter()
+                .any(|e| e.name != "." && e.name != "..");
+            if has_children {
+                return Err(FsError::DirectoryNotEmpty);
+            }
+        }
+
+        // Mark the 8.3 entry and all of its LFN entries deleted
+        for slot in found.first_slot..=found.short_slot {
+            dir.data[slot * ENTRY_SIZE] = dir::DELETED;
+        }
+        self.store_slots(&dir, found.first_slot, found.short_slot - found.first_slot + 1)?;
+
+        if found.entry.first_cluster >= 2 {
+            self.release_chain(&mut info, found.entry.first_cluster)?;
+        }
+
+        self.flush_fsinfo(&info)
     }
 
-    fn create_dir(&self, _path: &Path) -> Result<(), FsError> {
-        Err(FsError::ReadOnly)
+    fn create_dir(&self, path: &Path) -> Result<(), FsError> {
+        self.check_writable()?;
+
+        let mut info = self.fsinfo.lock();
+        let result = self.make_dir(&mut info, path);
+
+        // Missing parents may have been created even if the last step failed
+        self.flush_fsinfo(&info)?;
+        result.map(|_| ())
     }
 
     fn read_dir(&self, path: &Path) -> Result<Vec<VfsDirEntry>, FsError> {
@@ -204,6 +784,7 @@ impl FileSystem for Fat32 {
             .map(|e| VfsDirEntry {
                 name: e.name,
                 is_dir: e.is_dir,
+                file_type: if e.is_dir { FileType::Directory } else { FileType::File },
             })
             .collect();
 
@@ -222,14 +803,167 @@ impl FileSystem for Fat32 {
     }
 
     fn sync(&self) -> Result<(), FsError> {
-        // Read-only, nothing to sync
-        Ok(())
+        if self.device.is_read_only() {
+            return Ok(());
+        }
+
+        self.flush_fsinfo(&self.fsinfo.lock())?;
+        self.device.sync().map_err(device_error)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::vfs::mock_fat32::MockFat32Device;
+
+    /// Free clusters on a fresh mock image
+    const MOCK_FREE: u32 = 77;
+
+    fn mount(device: &MockFat32Device) -> Fat32 {
+        Fat32::new(Box::new(device.clone())).unwrap()
+    }
+
+    fn names(fs: &Fat32, path: &str) -> Vec<alloc::string::String> {
+        fs.read_dir(&Path::new(path)).unwrap().into_iter().map(|e| e.name).collect()
+    }
 
-    // Tests will be added once we have a mock block device with FAT32 data
+    #[test]
+    fn test_read_only_device_refuses_writes() {
+        let fs = mount(&MockFat32Device::new());
+        assert_eq!(fs.write(&Path::new("/NEW.TXT"), b"x"), Err(FsError::ReadOnly));
+        assert_eq!(fs.remove(&Path::new("/README.TXT")), Err(FsError::ReadOnly));
+        assert_eq!(fs.create_dir(&Path::new("/DIR")), Err(FsError::ReadOnly));
+    }
+
+    #[test]
+    fn test_write_new_file_and_remount() {
+        let device = MockFat32Device::new_writable();
+        let fs = mount(&device);
+
+        // 1500 bytes spans three 512-byte clusters
+        let data: Vec<u8> = (0..1500).map(|i| i as u8).collect();
+        fs.write(&Path::new("/Recovered Report.txt"), &data).unwrap();
+
+        assert_eq!(fs.read(&Path::new("/Recovered Report.txt")).unwrap(), data);
+        assert_eq!(fs.stat(&Path::new("/recovered report.TXT")).unwrap().size, 1500);
+        assert_eq!(fs.free_clusters(), Some(MOCK_FREE - 3));
+        assert!(names(&fs, "/").contains(&"Recovered Report.txt".to_string()));
+
+        // Everything, including FSInfo, survives a remount
+        let fs = mount(&device);
+        assert_eq!(fs.read(&Path::new("/Recovered Report.txt")).unwrap(), data);
+        assert_eq!(fs.bpb.free_clusters(), Some(MOCK_FREE - 3));
+    }
+
+    #[test]
+    fn test_fat_copies_stay_mirrored() {
+        let device = MockFat32Device::new_writable();
+        let fs = mount(&device);
+        fs.write(&Path::new("/MIRROR.BIN"), &[0xAB; 2000]).unwrap();
+        fs.remove(&Path::new("/TEST.TXT")).unwrap();
+
+        // FAT1 is sectors 32..40, FAT2 is sectors 40..48
+        for sector in 0..8 {
+            assert_eq!(
+                device.read_sector(32 + sector).unwrap(),
+                device.read_sector(40 + sector).unwrap(),
+            );
+        }
+    }
+
+    #[test]
+    fn test_overwrite_frees_old_chain() {
+        let device = MockFat32Device::new_writable();
+        let fs = mount(&device);
+
+        fs.write(&Path::new("/README.TXT"), &[b'a'; 1024]).unwrap();
+        assert_eq!(fs.read(&Path::new("/README.TXT")).unwrap(), [b'a'; 1024]);
+        assert_eq!(fs.free_clusters(), Some(MOCK_FREE - 1));
+
+        fs.write(&Path::new("/README.TXT"), b"").unwrap();
+        assert!(fs.read(&Path::new("/README.TXT")).unwrap().is_empty());
+        assert_eq!(fs.free_clusters(), Some(MOCK_FREE + 1));
+    }
+
+    #[test]
+    fn test_write_at_touches_only_the_range() {
+        let device = MockFat32Device::new_writable();
+        let fs = mount(&device);
+        let log = Path::new("/LOG.TXT");
+
+        // Sequential appends grow the chain as they cross cluster boundaries
+        let mut expected = Vec::new();
+        for chunk in 0..4u8 {
+            let data = [b'a' + chunk; 300];
+            fs.write_at(&log, expected.len() as u64, &data).unwrap();
+            expected.extend_from_slice(&data);
+        }
+        assert_eq!(fs.read(&log).unwrap(), expected);
+        assert_eq!(fs.free_clusters(), Some(MOCK_FREE - 3));
+
+        // Overwriting the middle neither grows nor moves the file
+        fs.write_at(&log, 500, b"XYZ").unwrap();
+        expected[500..503].copy_from_slice(b"XYZ");
+        assert_eq!(fs.read(&log).unwrap(), expected);
+        assert_eq!(fs.free_clusters(), Some(MOCK_FREE - 3));
+
+        // Writing past the end leaves a zeroed gap
+        fs.write_at(&log, 2000, b"end").unwrap();
+        expected.resize(2000, 0);
+        expected.extend_from_slice(b"end");
+        assert_eq!(fs.free_clusters(), Some(MOCK_FREE - 4));
+        assert_eq!(mount(&device).read(&log).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_create_and_remove_directories() {
+        let device = MockFat32Device::new_writable();
+        let fs = mount(&device);
+
+        // Missing parents are created too
+        fs.create_dir(&Path::new("/Rescue/Photos")).unwrap();
+        fs.write(&Path::new("/Rescue/Photos/IMG_0001.JPG"), b"jpeg").unwrap();
+        assert_eq!(names(&fs, "/Rescue/Photos"), ["IMG_0001.JPG"]);
+        assert_eq!(fs.create_dir(&Path::new("/Rescue")), Err(FsError::AlreadyExists));
+
+        assert_eq!(fs.remove(&Path::new("/Rescue")), Err(FsError::DirectoryNotEmpty));
+        fs.remove(&Path::new("/Rescue/Photos/IMG_0001.JPG")).unwrap();
+        fs.remove(&Path::new("/Rescue/Photos")).unwrap();
+        fs.remove(&Path::new("/Rescue")).unwrap();
+
+        assert_eq!(names(&fs, "/"), ["README.TXT", "TEST.TXT"]);
+        assert_eq!(fs.free_clusters(), Some(MOCK_FREE));
+        assert_eq!(fs.remove(&Path::new("/Rescue")), Err(FsError::NotFound));
+    }
+
+    #[test]
+    fn test_directory_grows_when_full() {
+        let device = MockFat32Device::new_writable();
+        let fs = mount(&device);
+
+        // Each long name takes 3 slots; a 512-byte root holds only 16
+        for i in 0..10 {
+            let name = alloc::format!("/Recovered document {}.txt", i);
+            fs.write(&Path::new(&name), name.as_bytes()).unwrap();
+        }
+
+        let root = names(&fs, "/");
+        assert_eq!(root.len(), 12);
+        for i in 0..10 {
+            let name = alloc::format!("/Recovered document {}.txt", i);
+            assert_eq!(fs.read(&Path::new(&name)).unwrap(), name.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_out_of_space_leaves_volume_unchanged() {
+        let device = MockFat32Device::new_writable();
+        let fs = mount(&device);
+
+        let too_big = vec![0u8; (MOCK_FREE as usize + 1) * 512];
+        assert_eq!(fs.write(&Path::new("/HUGE.BIN"), &too_big), Err(FsError::OutOfSpace));
+        assert_eq!(fs.free_clusters(), Some(MOCK_FREE));
+        assert!(!fs.exists(&Path::new("/HUGE.BIN")));
+    }
 }
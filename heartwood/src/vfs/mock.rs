@@ -1,6 +1,6 @@
 //! Mock Filesystem - In-memory filesystem for testing
 
-use super::{FileSystem, Path, FsError, DirEntry, FileStat};
+use super::{FileSystem, Path, FsError, DirEntry, FileStat, FileType};
 use alloc::collections::BTreeMap;
 use alloc::vec::Vec;
 use alloc::string::{String, ToString};
@@ -218,6 +218,7 @@ impl FileSystem for MockFs {
                     entries.push(DirEntry {
                         name: name.to_string(),
                         is_dir: false,
+                        file_type: FileType::File,
                     });
                 }
             }
@@ -232,6 +233,7 @@ impl FileSystem for MockFs {
                     entries.push(DirEntry {
                         name: name.to_string(),
                         is_dir: true,
+                        file_type: FileType::Directory,
                     });
                 }
             }
@@ -0,0 +1,464 @@
+//! Mock ext4 Block Device for Testing
+//!
+//! This module builds a tiny ext4 filesystem image in memory for testing
+//! the ext4 driver without real hardware.
+
+use super::block_device::{BlockDevice, BlockDeviceError};
+use super::ext4::checksum::crc32c;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use spin::Mutex;
+
+/// Block size of the mock volume
+const BLOCK_SIZE: usize = 1024;
+
+/// Total blocks (64 KB, the buddy allocator's largest allocation)
+const TOTAL_BLOCKS: usize = 64;
+
+/// Inodes in the single block group
+const INODES: u32 = 64;
+
+/// Size of each on-disk inode
+const INODE_SIZE: usize = 128;
+
+/// Block layout
+const SUPERBLOCK_BLOCK: usize = 1;
+const GDT_BLOCK: usize = 2;
+const BLOCK_BITMAP_BLOCK: usize = 3;
+const INODE_BITMAP_BLOCK: usize = 4;
+const INODE_TABLE_BLOCK: usize = 5;
+const ROOT_DIR_BLOCK: usize = 13;
+const LOST_FOUND_BLOCK: usize = 14;
+const README_BLOCK: usize = 15;
+const DEEP_LINK_BLOCK: usize = 16;
+const HASHED_BLOCK: usize = 17;                          // dx_root, then three leaves
+
+/// Inode numbers
+const ROOT_INODE: u32 = 2;
+const LOST_FOUND_INODE: u32 = 11;
+const README_INODE: u32 = 12;
+const LINK_INODE: u32 = 13;
+const LOOP_INODE: u32 = 14;
+const DEEP_LINK_INODE: u32 = 15;
+const HASHED_INODE: u32 = 16;
+const FIRST_HASHED_FILE_INODE: u32 = 17;
+
+/// Blocks and inodes in use on a fresh image
+const USED_BLOCKS: u32 = (HASHED_BLOCK + HASHED_LEAVES.len()) as u32;
+const USED_INODES: u32 = FIRST_HASHED_FILE_INODE + HASHED_FILES as u32 - 1;
+
+/// Timestamp stamped on everything in the image
+const MOCK_TIME: u32 = 1_700_000_000;
+
+/// Contents of /README.TXT
+pub const README_CONTENTS: &[u8] = b"Hello from ext4 on AethelOS!\n";
+
+/// Target of /LINK.TXT (a fast symlink)
+pub const LINK_TARGET: &str = "README.TXT";
+
+/// Target of /LOOP, which points at itself
+const LOOP_TARGET: &str = "LOOP";
+
+/// Target of /DEEP.LNK (too long for a fast symlink)
+pub const DEEP_LINK_TARGET: &str = "lost+found/../lost+found/../lost+found/../lost+found/../LINK.TXT";
+
+/// Two names with the same half-MD4 hash (under the default seed)
+pub const HASHED_COLLISION: [&str; 2] = ["f24416", "f44182"];
+
+/// Their shared hash
+const COLLIDING_HASH: u32 = 0x5a4f_e376;
+
+/// Empty files in /HASHED, by leaf block
+///
+/// The first leaf holds hashes below the collision and the second starts at
+/// it. The second colliding name spilled into the third leaf, which also takes
+/// every higher hash.
+pub const HASHED_LEAVES: [&[&str]; 3] = [
+    &["charlie", "golf", "hotel"],
+    &[HASHED_COLLISION[0]],
+    &[HASHED_COLLISION[1], "india", "alpha"],
+];
+
+/// Number of files in /HASHED
+const HASHED_FILES: usize = HASHED_LEAVES[0].len() + HASHED_LEAVES[1].len() + HASHED_LEAVES[2].len();
+
+/// Mock block device containing a minimal ext4 filesystem
+///
+/// This creates a small journal-less ext4 volume with:
+/// - 1 KB blocks, one block group, 64 inodes of 128 bytes
+/// - Features: filetype, extents (and optionally metadata_csum)
+/// - Root holds "lost+found", "README.TXT" and three symlinks: "LINK.TXT"
+///   (to README.TXT), "LOOP" (to itself) and "DEEP.LNK" (a slow symlink
+///   that winds through lost+found to LINK.TXT)
+/// - "HASHED", a directory with a half-MD4 htree index over a few empty files
+///   (its index block carries no checksum tail)
+/// - Total size: 64KB (64 blocks × 1024 bytes), 43 blocks free
+///
+/// Clones share the same image, so a test can keep one handle to inspect the
+/// raw sectors after giving another to the driver.
+#[derive(Clone)]
+pub struct MockExt4Device {
+    data: Arc<Mutex<Vec<u8>>>,
+    read_only: bool,
+}
+
+impl MockExt4Device {
+    /// Free blocks on a fresh image
+    pub const FREE_BLOCKS: u64 = (TOTAL_BLOCKS as u32 - 1 - USED_BLOCKS) as u64;
+
+    /// Free inodes on a fresh image
+    pub const FREE_INODES: u32 = INODES - USED_INODES;
+
+    /// Create a new read-only mock ext4 device with test data
+    pub fn new() -> Self {
+        Self::build(false, true)
+    }
+
+    /// Create a writable mock ext4 device with the same test data
+    pub fn new_writable() -> Self {
+        Self::build(false, false)
+    }
+
+    /// Create a writable mock ext4 device whose metadata is checksummed
+    pub fn new_writable_with_checksums() -> Self {
+        Self::build(true, false)
+    }
+
+    fn build(metadata_csum: bool, read_only: bool) -> Self {
+        let mut data = Vec::with_capacity(TOTAL_BLOCKS * BLOCK_SIZE);
+        data.resize(TOTAL_BLOCKS * BLOCK_SIZE, 0u8);
+
+        Self::write_superblock(&mut data, metadata_csum);
+        Self::write_bitmaps(&mut data);
+        Self::write_inodes(&mut data);
+        Self::write_directories(&mut data);
+        Self::write_file_data(&mut data);
+
+        if metadata_csum {
+            Self::write_checksums(&mut data);
+        }
+
+        Self {
+            data: Arc::new(Mutex::new(data)),
+            read_only,
+        }
+    }
+
+    fn block(data: &mut [u8], block: usize) -> &mut [u8] {
+        &mut data[block * BLOCK_SIZE..(block + 1) * BLOCK_SIZE]
+    }
+
+    fn put16(buf: &mut [u8], at: usize, value: u16) {
+        buf[at..at + 2].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn put32(buf: &mut [u8], at: usize, value: u32) {
+        buf[at..at + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn write_superblock(data: &mut [u8], metadata_csum: bool) {
+        let sb = Self::block(data, SUPERBLOCK_BLOCK);
+
+        Self::put32(sb, 0x00, INODES);                       // s_inodes_count
+        Self::put32(sb, 0x04, TOTAL_BLOCKS as u32);          // s_blocks_count_lo
+        Self::put32(sb, 0x0C, MockExt4Device::FREE_BLOCKS as u32);
+        Self::put32(sb, 0x10, MockExt4Device::FREE_INODES);
+        Self::put32(sb, 0x14, 1);                            // s_first_data_block
+        Self::put32(sb, 0x18, 0);                            // s_log_block_size (1 KB)
+        Self::put32(sb, 0x1C, 0);                            // s_log_cluster_size
+        Self::put32(sb, 0x20, 8192);                         // s_blocks_per_group
+        Self::put32(sb, 0x24, 8192);                         // s_clusters_per_group
+        Self::put32(sb, 0x28, INODES);                       // s_inodes_per_group
+        Self::put32(sb, 0x2C, MOCK_TIME);                    // s_mtime
+        Self::put32(sb, 0x30, MOCK_TIME);                    // s_wtime
+        Self::put16(sb, 0x36, 0xFFFF);                       // s_max_mnt_count
+        Self::put16(sb, 0x38, 0xEF53);                       // s_magic
+        Self::put16(sb, 0x3A, 1);                            // s_state: clean
+        Self::put16(sb, 0x3C, 1);                            // s_errors: continue
+        Self::put32(sb, 0x4C, 1);                            // s_rev_level: dynamic
+        Self::put32(sb, 0x54, LOST_FOUND_INODE);             // s_first_ino
+        Self::put16(sb, 0x58, INODE_SIZE as u16);            // s_inode_size
+
+        // Features: dir_index, filetype + extents, sparse_super (+ metadata_csum)
+        Self::put32(sb, 0x5C, 0x0020);
+        Self::put32(sb, 0x60, 0x0002 | 0x0040);
+        let ro_compat = if metadata_csum { 0x0001 | 0x0400 } else { 0x0001 };
+        Self::put32(sb, 0x64, ro_compat);
+
+        // UUID and volume label
+        sb[0x68..0x78].copy_from_slice(b"AethelOS-ext4-fs");
+        sb[0x78..0x80].copy_from_slice(b"AETHELOS");
+
+        if metadata_csum {
+            sb[0x175] = 1; // s_checksum_type: crc32c
+        }
+
+        // Group descriptor 0
+        let gd = Self::block(data, GDT_BLOCK);
+        Self::put32(gd, 0x00, BLOCK_BITMAP_BLOCK as u32);
+        Self::put32(gd, 0x04, INODE_BITMAP_BLOCK as u32);
+        Self::put32(gd, 0x08, INODE_TABLE_BLOCK as u32);
+        Self::put16(gd, 0x0C, MockExt4Device::FREE_BLOCKS as u16);
+        Self::put16(gd, 0x0E, MockExt4Device::FREE_INODES as u16);
+        Self::put16(gd, 0x10, 3);                            // root, lost+found, HASHED
+        if metadata_csum {
+            Self::put16(gd, 0x1C, MockExt4Device::FREE_INODES as u16); // bg_itable_unused
+        }
+    }
+
+    fn write_bitmaps(data: &mut [u8]) {
+        // Group 0 starts at block 1, so bit N is block N + 1. Bits past the
+        // end of the group are always set.
+        let bitmap = Self::block(data, BLOCK_BITMAP_BLOCK);
+        for bit in 0..BLOCK_SIZE * 8 {
+            if bit < USED_BLOCKS as usize || bit >= TOTAL_BLOCKS - 1 {
+                bitmap[bit / 8] |= 1 << (bit % 8);
+            }
+        }
+
+        let bitmap = Self::block(data, INODE_BITMAP_BLOCK);
+        for bit in 0..BLOCK_SIZE * 8 {
+            if bit < USED_INODES as usize || bit >= INODES as usize {
+                bitmap[bit / 8] |= 1 << (bit % 8);
+            }
+        }
+    }
+
+    fn inode(data: &mut [u8], inode_num: u32) -> &mut [u8] {
+        let offset = INODE_TABLE_BLOCK * BLOCK_SIZE + (inode_num as usize - 1) * INODE_SIZE;
+        &mut data[offset..offset + INODE_SIZE]
+    }
+
+    /// Write an inode whose data is a single extent of `blocks` blocks
+    fn write_inode(data: &mut [u8], inode_num: u32, mode: u16, links: u16, size: u32, block: usize, blocks: usize) {
+        let inode = Self::inode(data, inode_num);
+
+        Self::put16(inode, 0x00, mode);
+        Self::put32(inode, 0x04, size);
+        Self::put32(inode, 0x08, MOCK_TIME);                 // i_atime
+        Self::put32(inode, 0x0C, MOCK_TIME);                 // i_ctime
+        Self::put32(inode, 0x10, MOCK_TIME);                 // i_mtime
+        Self::put16(inode, 0x1A, links);
+        Self::put32(inode, 0x1C, (blocks * BLOCK_SIZE / 512) as u32); // i_blocks_lo
+        Self::put32(inode, 0x20, 0x0008_0000);               // EXT4_EXTENTS_FL
+
+        // Extent tree root: header, then one extent
+        Self::put16(inode, 0x28, 0xF30A);                    // eh_magic
+        Self::put16(inode, 0x2A, 1);                         // eh_entries
+        Self::put16(inode, 0x2C, 4);                         // eh_max
+        Self::put16(inode, 0x2E, 0);                         // eh_depth
+        Self::put32(inode, 0x34, 0);                         // ee_block
+        Self::put16(inode, 0x38, blocks as u16);             // ee_len
+        Self::put32(inode, 0x3C, block as u32);              // ee_start_lo
+    }
+
+    /// Write a symlink whose target is kept in `i_block`
+    fn write_fast_symlink(data: &mut [u8], inode_num: u32, target: &str) {
+        let inode = Self::inode(data, inode_num);
+
+        Self::put16(inode, 0x00, 0xA1FF);
+        Self::put32(inode, 0x04, target.len() as u32);
+        Self::put32(inode, 0x08, MOCK_TIME);                 // i_atime
+        Self::put32(inode, 0x0C, MOCK_TIME);                 // i_ctime
+        Self::put32(inode, 0x10, MOCK_TIME);                 // i_mtime
+        Self::put16(inode, 0x1A, 1);
+        inode[0x28..0x28 + target.len()].copy_from_slice(target.as_bytes());
+    }
+
+    /// Write an empty regular file with an empty extent tree
+    fn write_empty_file(data: &mut [u8], inode_num: u32) {
+        let inode = Self::inode(data, inode_num);
+
+        Self::put16(inode, 0x00, 0x81A4);
+        Self::put32(inode, 0x08, MOCK_TIME);                 // i_atime
+        Self::put32(inode, 0x0C, MOCK_TIME);                 // i_ctime
+        Self::put32(inode, 0x10, MOCK_TIME);                 // i_mtime
+        Self::put16(inode, 0x1A, 1);
+        Self::put32(inode, 0x20, 0x0008_0000);               // EXT4_EXTENTS_FL
+        Self::put16(inode, 0x28, 0xF30A);                    // eh_magic
+        Self::put16(inode, 0x2C, 4);                         // eh_max
+    }
+
+    fn write_inodes(data: &mut [u8]) {
+        Self::write_inode(data, ROOT_INODE, 0x41ED, 4, BLOCK_SIZE as u32, ROOT_DIR_BLOCK, 1);
+        Self::write_inode(data, LOST_FOUND_INODE, 0x41C0, 2, BLOCK_SIZE as u32, LOST_FOUND_BLOCK, 1);
+        Self::write_inode(data, README_INODE, 0x81A4, 1, README_CONTENTS.len() as u32, README_BLOCK, 1);
+        Self::write_fast_symlink(data, LINK_INODE, LINK_TARGET);
+        Self::write_fast_symlink(data, LOOP_INODE, LOOP_TARGET);
+        Self::write_inode(data, DEEP_LINK_INODE, 0xA1FF, 1, DEEP_LINK_TARGET.len() as u32, DEEP_LINK_BLOCK, 1);
+
+        let blocks = 1 + HASHED_LEAVES.len();
+        Self::write_inode(data, HASHED_INODE, 0x41ED, 2, (blocks * BLOCK_SIZE) as u32, HASHED_BLOCK, blocks);
+        Self::put32(Self::inode(data, HASHED_INODE), 0x20, 0x0008_1000); // EXT4_EXTENTS_FL | EXT4_INDEX_FL
+        for inode_num in FIRST_HASHED_FILE_INODE..=USED_INODES {
+            Self::write_empty_file(data, inode_num);
+        }
+    }
+
+    /// Write a directory entry, returning the offset of the next one
+    fn write_dirent(block: &mut [u8], offset: usize, inode: u32, rec_len: usize, name: &[u8], file_type: u8) -> usize {
+        Self::put32(block, offset, inode);
+        Self::put16(block, offset + 4, rec_len as u16);
+        block[offset + 6] = name.len() as u8;
+        block[offset + 7] = file_type;
+        block[offset + 8..offset + 8 + name.len()].copy_from_slice(name);
+        offset + rec_len
+    }
+
+    fn write_directories(data: &mut [u8]) {
+        // The last entry in each block runs to the end, less the checksum
+        // tail (written later, and harmless padding without metadata_csum)
+        let end = BLOCK_SIZE - 12;
+
+        let root = Self::block(data, ROOT_DIR_BLOCK);
+        let mut offset = Self::write_dirent(root, 0, ROOT_INODE, 12, b".", 2);
+        offset = Self::write_dirent(root, offset, ROOT_INODE, 12, b"..", 2);
+        offset = Self::write_dirent(root, offset, LOST_FOUND_INODE, 20, b"lost+found", 2);
+        offset = Self::write_dirent(root, offset, README_INODE, 20, b"README.TXT", 1);
+        offset = Self::write_dirent(root, offset, LINK_INODE, 16, b"LINK.TXT", 7);
+        offset = Self::write_dirent(root, offset, LOOP_INODE, 12, b"LOOP", 7);
+        offset = Self::write_dirent(root, offset, DEEP_LINK_INODE, 16, b"DEEP.LNK", 7);
+        Self::write_dirent(root, offset, HASHED_INODE, end - offset, b"HASHED", 2);
+
+        let lost_found = Self::block(data, LOST_FOUND_BLOCK);
+        let offset = Self::write_dirent(lost_found, 0, LOST_FOUND_INODE, 12, b".", 2);
+        Self::write_dirent(lost_found, offset, ROOT_INODE, end - offset, b"..", 2);
+
+        Self::write_hashed_directory(data);
+
+        // Without checksums the tail is just an empty entry
+        for (block, _) in Self::leaf_blocks() {
+            Self::write_dirent(Self::block(data, block), end, 0, 12, b"", 0);
+        }
+    }
+
+    /// Write /HASHED: a dx_root block indexing one leaf per `HASHED_LEAVES` entry
+    fn write_hashed_directory(data: &mut [u8]) {
+        let root = Self::block(data, HASHED_BLOCK);
+        let offset = Self::write_dirent(root, 0, HASHED_INODE, 12, b".", 2);
+        Self::write_dirent(root, offset, ROOT_INODE, BLOCK_SIZE - offset, b"..", 2);
+
+        // dx_root_info: hash version half-MD4, 8 bytes long, no index nodes
+        root[0x1C] = 1;
+        root[0x1D] = 8;
+
+        // Count/limit header (carrying the first leaf), then hash/block pairs.
+        // The low bit on the third leaf's hash marks the collision running on.
+        Self::put16(root, 0x20, ((BLOCK_SIZE - 0x20) / 8) as u16);
+        Self::put16(root, 0x22, HASHED_LEAVES.len() as u16);
+        Self::put32(root, 0x24, 1);
+        for (i, hash) in [(1, COLLIDING_HASH), (2, COLLIDING_HASH | 1)] {
+            Self::put32(root, 0x20 + i * 8, hash);
+            Self::put32(root, 0x24 + i * 8, i as u32 + 1);
+        }
+
+        let end = BLOCK_SIZE - 12;
+        let mut inode_num = FIRST_HASHED_FILE_INODE;
+        for (i, names) in HASHED_LEAVES.iter().enumerate() {
+            let leaf = Self::block(data, HASHED_BLOCK + 1 + i);
+            let mut offset = 0;
+            for (j, name) in names.iter().enumerate() {
+                let rec_len = if j + 1 == names.len() { end - offset } else { 16 };
+                offset = Self::write_dirent(leaf, offset, inode_num, rec_len, name.as_bytes(), 1);
+                inode_num += 1;
+            }
+        }
+    }
+
+    /// Directory blocks made of plain entries
+    fn leaf_blocks() -> impl Iterator<Item = (usize, u32)> {
+        [(ROOT_DIR_BLOCK, ROOT_INODE), (LOST_FOUND_BLOCK, LOST_FOUND_INODE)].into_iter()
+            .chain((1..=HASHED_LEAVES.len()).map(|i| (HASHED_BLOCK + i, HASHED_INODE)))
+    }
+
+    fn write_file_data(data: &mut [u8]) {
+        let block = Self::block(data, README_BLOCK);
+        block[..README_CONTENTS.len()].copy_from_slice(README_CONTENTS);
+
+        let block = Self::block(data, DEEP_LINK_BLOCK);
+        block[..DEEP_LINK_TARGET.len()].copy_from_slice(DEEP_LINK_TARGET.as_bytes());
+    }
+
+    /// Fill in every metadata_csum checksum
+    fn write_checksums(data: &mut [u8]) {
+        let seed = crc32c(!0, b"AethelOS-ext4-fs");
+        let inode_seed = |inode_num: u32| crc32c(crc32c(seed, &inode_num.to_le_bytes()), &0u32.to_le_bytes());
+
+        let inodes = [ROOT_INODE, LOST_FOUND_INODE, README_INODE, LINK_INODE, LOOP_INODE, DEEP_LINK_INODE];
+        for inode_num in inodes.into_iter().chain(HASHED_INODE..=USED_INODES) {
+            let inode = Self::inode(data, inode_num);
+            let csum = crc32c(inode_seed(inode_num), inode);
+            Self::put16(inode, 0x7C, csum as u16);
+        }
+
+        for (block, owner) in Self::leaf_blocks() {
+            let block = Self::block(data, block);
+            block[BLOCK_SIZE - 12 + 7] = 0xDE;
+            let csum = crc32c(inode_seed(owner), &block[..BLOCK_SIZE - 12]);
+            Self::put32(block, BLOCK_SIZE - 4, csum);
+        }
+
+        let block_bitmap_csum = crc32c(seed, &Self::block(data, BLOCK_BITMAP_BLOCK)[..8192 / 8]);
+        let inode_bitmap_csum = crc32c(seed, &Self::block(data, INODE_BITMAP_BLOCK)[..INODES as usize / 8]);
+
+        let gd = &mut Self::block(data, GDT_BLOCK)[..32];
+        Self::put16(gd, 0x18, block_bitmap_csum as u16);
+        Self::put16(gd, 0x1A, inode_bitmap_csum as u16);
+        let mut crc = crc32c(seed, &0u32.to_le_bytes());
+        crc = crc32c(crc, &gd[..0x1E]);
+        crc = crc32c(crc, &[0, 0]);
+        crc = crc32c(crc, &gd[0x20..]);
+        Self::put16(gd, 0x1E, crc as u16);
+
+        let sb = Self::block(data, SUPERBLOCK_BLOCK);
+        let csum = crc32c(!0, &sb[..0x3FC]);
+        Self::put32(sb, 0x3FC, csum);
+    }
+}
+
+impl BlockDevice for MockExt4Device {
+    fn sector_size(&self) -> u32 {
+        512
+    }
+
+    fn sector_count(&self) -> u64 {
+        (self.data.lock().len() / 512) as u64
+    }
+
+    fn read_sector(&self, sector: u64) -> Result<Vec<u8>, BlockDeviceError> {
+        let data = self.data.lock();
+        let offset = sector as usize * 512;
+        if offset + 512 > data.len() {
+            return Err(BlockDeviceError::InvalidSector);
+        }
+
+        Ok(data[offset..offset + 512].to_vec())
+    }
+
+    fn write_sector(&self, sector: u64, sector_data: &[u8]) -> Result<(), BlockDeviceError> {
+        if self.read_only {
+            return Err(BlockDeviceError::WriteProtected);
+        }
+
+        if sector_data.len() != 512 {
+            return Err(BlockDeviceError::IoError);
+        }
+
+        let mut data = self.data.lock();
+        let offset = sector as usize * 512;
+        if offset + 512 > data.len() {
+            return Err(BlockDeviceError::InvalidSector);
+        }
+
+        data[offset..offset + 512].copy_from_slice(sector_data);
+        Ok(())
+    }
+
+    fn sync(&self) -> Result<(), BlockDeviceError> {
+        Ok(())
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+}
@@ -4,7 +4,9 @@
 //! for testing the FAT32 driver without real hardware.
 
 use super::block_device::{BlockDevice, BlockDeviceError};
+use alloc::sync::Arc;
 use alloc::vec::Vec;
+use spin::Mutex;
 
 /// Mock block device containing a minimal FAT32 filesystem
 ///
@@ -13,12 +15,17 @@ use alloc::vec::Vec;
 /// - 2 files in root: "README.TXT", "TEST.TXT"
 /// - Total size: 64KB (128 sectors × 512 bytes)
 /// - Limited to 64KB due to buddy allocator's 64KB max allocation
+///
+/// Clones share the same image, so a test can keep one handle to inspect the
+/// raw sectors after giving another to the driver.
+#[derive(Clone)]
 pub struct MockFat32Device {
-    data: Vec<u8>,
+    data: Arc<Mutex<Vec<u8>>>,
+    read_only: bool,
 }
 
 impl MockFat32Device {
-    /// Create a new mock FAT32 device with test data
+    /// Create a new read-only mock FAT32 device with test data
     pub fn new() -> Self {
         // Create a minimal FAT32 filesystem (128 sectors = 64KB)
         // NOTE: Limited to 64KB because the buddy allocator's MAX_ORDER = 10
@@ -36,7 +43,17 @@ impl MockFat32Device {
         Self::write_root_directory(&mut data);
         Self::write_file_data(&mut data);
 
-        Self { data }
+        Self {
+            data: Arc::new(Mutex::new(data)),
+            read_only: true,
+        }
+    }
+
+    /// Create a writable mock FAT32 device with the same test data
+    pub fn new_writable() -> Self {
+        let mut device = Self::new();
+        device.read_only = false;
+        device
     }
 
     fn write_boot_sector(data: &mut [u8]) {
@@ -218,20 +235,36 @@ impl BlockDevice for MockFat32Device {
     }
 
     fn sector_count(&self) -> u64 {
-        (self.data.len() / 512) as u64
+        (self.data.lock().len() / 512) as u64
     }
 
     fn read_sector(&self, sector: u64) -> Result<Vec<u8>, BlockDeviceError> {
+        let data = self.data.lock();
         let offset = sector as usize * 512;
-        if offset + 512 > self.data.len() {
+        if offset + 512 > data.len() {
             return Err(BlockDeviceError::InvalidSector);
         }
 
-        Ok(self.data[offset..offset + 512].to_vec())
+        Ok(data[offset..offset + 512].to_vec())
     }
 
-    fn write_sector(&self, _sector: u64, _data: &[u8]) -> Result<(), BlockDeviceError> {
-        Err(BlockDeviceError::WriteProtected)
+    fn write_sector(&self, sector: u64, sector_data: &[u8]) -> Result<(), BlockDeviceError> {
+        if self.read_only {
+            return Err(BlockDeviceError::WriteProtected);
+        }
+
+        if sector_data.len() != 512 {
+            return Err(BlockDeviceError::IoError);
+        }
+
+        let mut data = self.data.lock();
+        let offset = sector as usize * 512;
+        if offset + 512 > data.len() {
+            return Err(BlockDeviceError::InvalidSector);
+        }
+
+        data[offset..offset + 512].copy_from_slice(sector_data);
+        Ok(())
     }
 
     fn sync(&self) -> Result<(), BlockDeviceError> {
@@ -239,6 +272,6 @@ impl BlockDevice for MockFat32Device {
     }
 
     fn is_read_only(&self) -> bool {
-        true
+        self.read_only
     }
 }
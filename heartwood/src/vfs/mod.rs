@@ -14,8 +14,10 @@ pub mod block_device;
 pub mod fat32;
 pub mod ext4;
 pub mod mock_fat32;
+pub mod mock_ext4;
 pub mod global;
 pub mod debug_cmd;
+pub mod descriptor;
 
 #[cfg(test)]
 mod tests;
@@ -101,6 +103,19 @@ impl From<String> for Path {
     }
 }
 
+/// Kind of object a directory entry names
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileType {
+    /// Regular file
+    File,
+    /// Directory
+    Directory,
+    /// Symbolic link (see [`FileSystem::read_link`])
+    Symlink,
+    /// Device node, FIFO, socket, or anything else
+    Other,
+}
+
 /// Directory entry information
 #[derive(Debug, Clone)]
 pub struct DirEntry {
@@ -108,6 +123,8 @@ pub struct DirEntry {
     pub name: String,
     /// Whether this entry is a directory
     pub is_dir: bool,
+    /// What kind of object the entry names
+    pub file_type: FileType,
 }
 
 /// File metadata
@@ -142,10 +159,14 @@ pub enum FsError {
     IoError,
     /// Filesystem is out of space
     OutOfSpace,
+    /// Directory still has entries (when removing it)
+    DirectoryNotEmpty,
     /// Filesystem is mounted read-only
     ReadOnly,
     /// Operation not supported by this filesystem
     NotSupported,
+    /// Too many symbolic links were followed resolving a path
+    SymlinkLoop,
 }
 
 impl core::fmt::Display for FsError {
@@ -159,8 +180,10 @@ impl core::fmt::Display for FsError {
             FsError::InvalidPath => write!(f, "Invalid path"),
             FsError::IoError => write!(f, "I/O error"),
             FsError::OutOfSpace => write!(f, "Filesystem out of space"),
+            FsError::DirectoryNotEmpty => write!(f, "Directory not empty"),
             FsError::ReadOnly => write!(f, "Filesystem is read-only"),
             FsError::NotSupported => write!(f, "Operation not supported"),
+            FsError::SymlinkLoop => write!(f, "Too many levels of symbolic links"),
         }
     }
 }
@@ -207,6 +230,29 @@ pub trait FileSystem: Send + Sync {
     /// * `Err(FsError::IoError)` - I/O error occurred
     fn read(&self, path: &Path) -> Result<Vec<u8>, FsError>;
 
+    /// Read part of a file starting at a byte offset
+    ///
+    /// The default implementation reads the whole file and copies out the
+    /// requested window. Filesystems that can seek should override it.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the file to read
+    /// * `offset` - Byte offset to start reading from
+    /// * `buf` - Buffer to fill
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - Bytes copied into `buf` (0 at or past end of file)
+    /// * `Err(FsError)` - Same errors as `read()`
+    fn read_at(&self, path: &Path, offset: u64, buf: &mut [u8]) -> Result<usize, FsError> {
+        let data = self.read(path)?;
+        let start = core::cmp::min(offset, data.len() as u64) as usize;
+        let count = core::cmp::min(buf.len(), data.len() - start);
+        buf[..count].copy_from_slice(&data[start..start + count]);
+        Ok(count)
+    }
+
     /// Write entire file (create or overwrite)
     ///
     /// If the file exists, it will be overwritten. If it doesn't exist,
@@ -226,6 +272,40 @@ pub trait FileSystem: Send + Sync {
     /// * `Err(FsError::IoError)` - I/O error occurred
     fn write(&self, path: &Path, data: &[u8]) -> Result<(), FsError>;
 
+    /// Write part of a file starting at a byte offset
+    ///
+    /// Creates the file if it doesn't exist and grows it if the write ends
+    /// past its current size; any gap before `offset` reads back as zeros.
+    /// The default implementation reads the whole file, splices `data` in
+    /// and rewrites it. Filesystems that can seek should override it.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the file to write
+    /// * `offset` - Byte offset to start writing at
+    /// * `data` - Data to write
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - Write successful
+    /// * `Err(FsError)` - Same errors as `write()`
+    fn write_at(&self, path: &Path, offset: u64, data: &[u8]) -> Result<(), FsError> {
+        let mut contents = match self.read(path) {
+            Ok(contents) => contents,
+            Err(FsError::NotFound) => Vec::new(),
+            Err(e) => return Err(e),
+        };
+
+        let start = usize::try_from(offset).map_err(|_| FsError::OutOfSpace)?;
+        let end = start.checked_add(data.len()).ok_or(FsError::OutOfSpace)?;
+        if contents.len() < end {
+            contents.resize(end, 0);
+        }
+        contents[start..end].copy_from_slice(data);
+
+        self.write(path, &contents)
+    }
+
     /// Delete file or empty directory
     ///
     /// # Arguments
@@ -236,6 +316,7 @@ pub trait FileSystem: Send + Sync {
     ///
     /// * `Ok(())` - Deletion successful
     /// * `Err(FsError::NotFound)` - File doesn't exist
+    /// * `Err(FsError::DirectoryNotEmpty)` - Directory still has entries
     /// * `Err(FsError::ReadOnly)` - Filesystem is read-only
     /// * `Err(FsError::PermissionDenied)` - No delete permission
     /// * `Err(FsError::IoError)` - I/O error occurred
@@ -271,6 +352,28 @@ pub trait FileSystem: Send + Sync {
     /// * `Err(FsError::IoError)` - I/O error occurred
     fn read_dir(&self, path: &Path) -> Result<Vec<DirEntry>, FsError>;
 
+    /// Read the target of a symbolic link
+    ///
+    /// The link itself is not followed, though symlinks in earlier path
+    /// components are. The default implementation suits filesystems
+    /// without symlinks: anything that exists just isn't one.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the symbolic link
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The link target, exactly as stored
+    /// * `Err(FsError::NotFound)` - Path doesn't exist
+    /// * `Err(FsError::InvalidPath)` - Path is not a symbolic link
+    /// * `Err(FsError::SymlinkLoop)` - Too many links in the leading components
+    /// * `Err(FsError::IoError)` - I/O error occurred
+    fn read_link(&self, path: &Path) -> Result<String, FsError> {
+        self.stat(path)?;
+        Err(FsError::InvalidPath)
+    }
+
     /// Get file/directory metadata
     ///
     /// # Arguments
@@ -141,6 +141,7 @@ pub fn show_wards_page(page: usize) {
                             ThreadState::Weaving => "Weaving",
                             ThreadState::Resting => "Resting",
                             ThreadState::Tangled => "Tangled",
+                            ThreadState::Blocked(_) => "Blocked",
                             ThreadState::Fading => "Fading",
                         };
 
@@ -2,24 +2,11 @@
 #![no_main]
 
 use corelib::syscalls::{sys_exit, sys_write};
-use core::alloc::{GlobalAlloc, Layout};
-
-/// Dummy allocator for minimal programs
-/// (Real allocator would use sys_mmap/sys_munmap)
-struct DummyAllocator;
-
-unsafe impl GlobalAlloc for DummyAllocator {
-    unsafe fn alloc(&self, _layout: Layout) -> *mut u8 {
-        core::ptr::null_mut()
-    }
-
-    unsafe fn dealloc(&self, _ptr: *mut u8, _layout: Layout) {
-        // Do nothing
-    }
-}
+use corelib::allocator::VesselHeap;
 
+/// Heap backed by sys_mmap/sys_munmap
 #[global_allocator]
-static ALLOCATOR: DummyAllocator = DummyAllocator;
+static ALLOCATOR: VesselHeap = VesselHeap::new();
 
 #[no_mangle]
 pub extern "C" fn _start() -> ! {
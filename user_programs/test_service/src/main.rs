@@ -10,25 +10,12 @@
 #![no_std]
 #![no_main]
 
-use corelib::syscalls::{sys_exit, sys_write, sys_yield, sys_getpid, sys_time};
-use core::alloc::{GlobalAlloc, Layout};
-
-/// Dummy allocator for minimal programs
-/// (Real allocator would use sys_mmap/sys_munmap)
-struct DummyAllocator;
-
-unsafe impl GlobalAlloc for DummyAllocator {
-    unsafe fn alloc(&self, _layout: Layout) -> *mut u8 {
-        core::ptr::null_mut()
-    }
-
-    unsafe fn dealloc(&self, _ptr: *mut u8, _layout: Layout) {
-        // Do nothing
-    }
-}
+use corelib::syscalls::{sys_exit, sys_write, sys_yield, sys_gettid, sys_time};
+use corelib::allocator::VesselHeap;
 
+/// Heap backed by sys_mmap/sys_munmap
 #[global_allocator]
-static ALLOCATOR: DummyAllocator = DummyAllocator;
+static ALLOCATOR: VesselHeap = VesselHeap::new();
 
 /// Simple byte-to-hex conversion for debugging
 fn byte_to_hex(b: u8, buf: &mut [u8; 2]) {
@@ -59,7 +46,7 @@ pub extern "C" fn _start() -> ! {
     let _ = sys_write(1, startup_msg);
 
     // Get and display thread ID
-    let tid = sys_getpid();
+    let tid = sys_gettid();
     let mut tid_buf = [0u8; 16];
     u64_to_hex(tid, &mut tid_buf);
 